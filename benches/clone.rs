@@ -0,0 +1,25 @@
+//! Compares cloning `Ntp` values with cloning the `String` server names they used to hold
+//!
+//! Run with `cargo bench --bench clone`
+
+use std::hint::black_box;
+use std::time::Instant;
+use thetime::{IntTime, Ntp};
+
+fn main() {
+    let times: Vec<Ntp> = (0..1_000_000u32).map(|i| (1_700_000_000 + i).unix::<Ntp>()).collect();
+    let servers: Vec<String> = times.iter().map(|t| t.server()).collect();
+
+    let start = Instant::now();
+    let cloned = black_box(&times).clone();
+    let ntp_time = start.elapsed();
+
+    let start = Instant::now();
+    let strings = black_box(&servers).clone();
+    let string_time = start.elapsed();
+
+    assert_eq!(cloned, times);
+    assert_eq!(strings.len(), cloned.len());
+    println!("Vec<Ntp>::clone:    {:?} for {} values", ntp_time, times.len());
+    println!("Vec<String>::clone: {:?} for {} server names (the old per-value cost)", string_time, servers.len());
+}
@@ -0,0 +1,57 @@
+//! Compares `strftime` with `strftime_compiled` over a batch of times, and a per-row `strftime` export of a column with `format_column`
+//! and `write_column`
+//!
+//! Run with `cargo bench --bench format`
+
+use std::hint::black_box;
+use std::time::Instant;
+use thetime::{format_all, format_column, write_column, CompiledFormat, IntTime, System, Time};
+
+const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f %A %B";
+
+fn main() {
+    let times: Vec<System> = (0..100_000u32).map(|i| (1_700_000_000 + i * 37).unix::<System>()).collect();
+
+    let start = Instant::now();
+    let plain: Vec<String> = times.iter().map(|t| t.strftime(black_box(FORMAT))).collect();
+    let plain_time = start.elapsed();
+
+    let start = Instant::now();
+    let fmt = CompiledFormat::new(black_box(FORMAT)).unwrap();
+    let compiled = format_all(&times, &fmt);
+    let compiled_time = start.elapsed();
+
+    assert_eq!(plain, compiled);
+    println!("strftime:          {:?} for {} times", plain_time, times.len());
+    println!("strftime_compiled: {:?} for {} times", compiled_time, times.len());
+
+    // a column as it would go into a CSV export, including times before 1970
+    let column: Vec<System> = (0..1_000_000u64).map(|i| (i * 7_919).unix::<System>().add_days(-40_000)).collect();
+    let column_format = "%Y-%m-%d %H:%M:%S";
+
+    let start = Instant::now();
+    let mut naive = Vec::new();
+    for (i, time) in column.iter().enumerate() {
+        if i > 0 {
+            naive.push(b'\n');
+        }
+        naive.extend_from_slice(time.strftime(black_box(column_format)).as_bytes());
+    }
+    let naive_time = start.elapsed();
+
+    let fmt = CompiledFormat::new(column_format).unwrap();
+    let start = Instant::now();
+    let mut buffer = Vec::new();
+    format_column(&column, &fmt, &mut buffer, b'\n');
+    let buffer_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut sink = std::io::sink();
+    write_column(&column, &fmt, &mut sink, b'\n').unwrap();
+    let writer_time = start.elapsed();
+
+    assert_eq!(naive, buffer);
+    println!("per-row strftime:  {:?} for {} times", naive_time, column.len());
+    println!("format_column:     {:?} for {} times", buffer_time, column.len());
+    println!("write_column:      {:?} for {} times", writer_time, column.len());
+}
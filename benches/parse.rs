@@ -0,0 +1,36 @@
+//! Compares parsing log timestamps from bytes with `parse_time_bytes` against UTF-8 validating them and using `strptime`
+//!
+//! Run with `cargo bench --bench parse`
+
+use std::hint::black_box;
+use std::time::Instant;
+use thetime::{parse_time_bytes, BytesTime, IntTime, System, Time};
+
+const FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f";
+
+fn main() {
+    let lines: Vec<Vec<u8>> = (0..100_000u32)
+        .map(|i| (1_700_000_000 + i * 37).unix::<System>().strftime(FORMAT).into_bytes())
+        .collect();
+
+    let start = Instant::now();
+    let via_str: Vec<System> = lines
+        .iter()
+        .map(|line| System::strptime(core::str::from_utf8(black_box(line)).unwrap(), FORMAT))
+        .collect();
+    let str_time = start.elapsed();
+
+    let start = Instant::now();
+    let via_bytes: Vec<System> = lines.iter().map(|line| parse_time_bytes(black_box(line), FORMAT).unwrap()).collect();
+    let bytes_time = start.elapsed();
+
+    let start = Instant::now();
+    let lenient: Vec<System> = lines.iter().map(|line| black_box(line).strp_iso_lenient().unwrap()).collect();
+    let lenient_time = start.elapsed();
+
+    assert!(via_str.iter().zip(&via_bytes).all(|(a, b)| a.unix_ms() == b.unix_ms()));
+    assert!(via_str.iter().zip(&lenient).all(|(a, b)| a.unix_ms() == b.unix_ms()));
+    println!("from_utf8 + strptime:          {:?} for {} lines", str_time, lines.len());
+    println!("parse_time_bytes:              {:?} for {} lines", bytes_time, lines.len());
+    println!("BytesTime::strp_iso_lenient:   {:?} for {} lines", lenient_time, lines.len());
+}
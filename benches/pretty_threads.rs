@@ -0,0 +1,60 @@
+//! Compares allocating `pretty()` calls with the per thread buffer behind `with_pretty` and `Display`, on 8 threads, counting
+//! allocations with a global allocator
+//!
+//! Run with `cargo bench --bench pretty_threads`
+
+use std::alloc::{GlobalAlloc, Layout, System as SystemAlloc};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use thetime::{IntTime, System, Time};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        SystemAlloc.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        SystemAlloc.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const THREADS: usize = 8;
+const PER_THREAD: u32 = 200_000;
+
+/// run `work` on each thread over the same times, returning the wall time and the allocations made
+fn run(work: fn(&System) -> usize) -> (std::time::Duration, u64) {
+    let times: Vec<System> = (0..1000u32).map(|i| (1_700_000_000 + i * 37).unix::<System>()).collect();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                let mut total = 0;
+                for i in 0..PER_THREAD {
+                    total += work(&times[i as usize % times.len()]);
+                }
+                black_box(total);
+            });
+        }
+    });
+    (start.elapsed(), ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+fn main() {
+    let (plain_time, plain_allocs) = run(|t| black_box(t.pretty()).len());
+    let (buffered_time, buffered_allocs) = run(|t| t.with_pretty(|s| black_box(s).len()));
+
+    let calls = THREADS as u64 * PER_THREAD as u64;
+    println!("pretty():      {:?}, {} allocations for {} calls", plain_time, plain_allocs, calls);
+    println!("with_pretty(): {:?}, {} allocations for {} calls", buffered_time, buffered_allocs, calls);
+    assert!(buffered_allocs < plain_allocs / 100);
+}
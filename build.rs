@@ -0,0 +1,11 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// records when thetime itself was compiled, as the fallback for `build_time!()` when SOURCE_DATE_EPOCH is unset in the calling crate -
+// taken from SOURCE_DATE_EPOCH here too when it is set and parses, so a reproducible build of thetime is reproducible all the way down
+fn main() {
+    let source_date_epoch = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|v| v.parse::<i64>().ok());
+    let now = source_date_epoch
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0));
+    println!("cargo:rustc-env=THETIME_BUILD_UNIX={}", now);
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
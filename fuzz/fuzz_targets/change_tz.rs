@@ -0,0 +1,23 @@
+#![no_main]
+//! Offset strings: `try_change_tz`, `change_tz` (which must leave the time alone on bad input) and `Tz::from_offset_str`
+
+use libfuzzer_sys::fuzz_target;
+use thetime::{ParseError, System, Time, Tz, MAX_UTC_OFFSET};
+
+fuzz_target!(|s: &str| {
+    let x = System::from_epoch_offset(13348939589123, 3600);
+    match x.try_change_tz(s) {
+        Ok(y) => {
+            assert_eq!(y.raw(), x.raw());
+            // "-00:00" comes back as "+00:00", so compare after a second trip
+            assert_eq!(y.try_change_tz(y.tz_offset()).map(|z| z.utc_offset()), Ok(y.utc_offset()));
+        }
+        // an offset past MAX_UTC_OFFSET is clamped rather than ignored
+        Err(ParseError::OutOfRange { .. }) => assert_eq!(x.change_tz(s).utc_offset().abs(), MAX_UTC_OFFSET),
+        Err(_) => assert_eq!(x.change_tz(s).utc_offset(), 3600),
+    }
+    if let Ok(tz) = Tz::from_offset_str(s) {
+        assert_eq!(x.change_tz(s).utc_offset(), tz.offset());
+    }
+    let _ = Tz::try_offset_struct(s, x);
+});
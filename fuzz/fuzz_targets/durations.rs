@@ -0,0 +1,12 @@
+#![no_main]
+//! The duration parsers: media player `H:MM:SS` and `ps` elapsed times
+
+use libfuzzer_sys::fuzz_target;
+use thetime::{format_hms_ms, parse_etime, parse_hms, HmsHours};
+
+fuzz_target!(|s: &str| {
+    if let Ok(duration) = parse_hms(s) {
+        let _ = format_hms_ms(duration, HmsHours::IfNonZero);
+    }
+    let _ = parse_etime(s);
+});
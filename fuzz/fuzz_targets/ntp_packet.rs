@@ -0,0 +1,17 @@
+#![no_main]
+//! NTP packets off the wire: decoding, and everything that reads a decoded packet
+
+use libfuzzer_sys::fuzz_target;
+use thetime::NtpPacket;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(packet) = NtpPacket::from_bytes(data) {
+        // every decoded packet encodes back to the bytes it came from
+        assert_eq!(packet.to_bytes().ok().as_ref().map(|b| &b[..]), Some(&data[..48]));
+        let _ = packet.kiss_code();
+        let _ = packet.hexdump();
+        for timestamp in [packet.reference, packet.origin, packet.receive, packet.transmit] {
+            let _ = timestamp.to_unix_ms();
+        }
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+//! `try_strptime` and `parse_time_bytes` with any input and any format - both must return errors, never panic
+
+use libfuzzer_sys::fuzz_target;
+use thetime::{parse_time_bytes, ExtendedTime, Ntp, StrTime, System, Time};
+
+fuzz_target!(|input: (&str, &str)| {
+    let (s, format) = input;
+    let system = System::try_strptime(s, format);
+    let _ = Ntp::try_strptime(s, format);
+    let _ = ExtendedTime::try_strptime(s, format);
+    let _ = s.try_parse_time::<System>(format);
+    let bytes = parse_time_bytes::<System>(s.as_bytes(), format);
+
+    // whatever parses must format again
+    if let Ok(x) = &system {
+        let _ = (x.pretty(), x.rfc3339(), x.iso8601_precision(6));
+    }
+    if let Ok(x) = &bytes {
+        let _ = x.strftime("%Y-%m-%d %H:%M:%S%.3f %z");
+    }
+});
@@ -0,0 +1,33 @@
+#![no_main]
+//! Every fixed-format string parser - ISO 8601, RFC 3339 and 2822, HTTP and JS dates, ASN.1, log formats, tagged and era timestamps
+
+use libfuzzer_sys::fuzz_target;
+use thetime::{epoch, http, ExtendedTime, FormatString, ParseOptions, StrTime, System, Time};
+
+fuzz_target!(|s: &str| {
+    let _ = s.try_strp_iso8601::<System>();
+    let _ = s.try_strp_rf3339::<System>();
+    let _ = s.try_strp_rf3339::<ExtendedTime>();
+    let _ = s.strp_iso_lenient::<System>();
+    let _ = s.strp_iso_lenient::<ExtendedTime>();
+    for options in [ParseOptions::default(), ParseOptions::strict(), ParseOptions::lenient()] {
+        let _ = s.strp_rfc2822_with::<System>(&options);
+        let _ = s.strp_js_date_with::<System>(&options);
+        let _ = http::parse_http_date_with::<System>(s, &options);
+    }
+    let _ = s.strp_asn1_utctime::<System>();
+    let _ = s.strp_asn1_generalizedtime::<System>();
+    let _ = s.strp_clf::<System>();
+    let _ = s.strp_nginx_error::<System>();
+    let _ = s.strp_tle_epoch::<System>();
+    let _ = s.parse_tagged::<System>();
+    let _ = s.parse_tagged::<ExtendedTime>();
+    let _ = epoch::parse_int_timestamp(s);
+    let _ = epoch::parse_int_timestamp_lenient(s);
+    let _ = http::parse_retry_after(s, &System::from_epoch(0));
+    let _ = http::parse_age(s);
+    if let Ok(x) = ExtendedTime::parse_era(s) {
+        let _ = x.pretty_era();
+    }
+    let _ = FormatString::parse_untrusted(s);
+});
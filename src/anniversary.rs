@@ -0,0 +1,103 @@
+use crate::Time;
+use chrono::{Datelike, NaiveDate};
+
+/// What a Feb 29 origin's anniversary is in a year without a Feb 29 - see `Time::next_anniversary`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum LeapDayPolicy {
+    /// the last day of February
+    #[default]
+    Feb28,
+    /// the day after Feb 28
+    Mar1,
+    /// no anniversary that year
+    SkipYear,
+}
+
+impl LeapDayPolicy {
+    /// The date of the anniversary of `origin` in `year`, or None if there is none (a Feb 29 origin under `SkipYear`, or a year outside
+    /// chrono's range)
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::LeapDayPolicy;
+    /// use chrono::NaiveDate;
+    /// let leap_day = NaiveDate::from_ymd_opt(2020, 2, 29).unwrap();
+    /// assert_eq!(LeapDayPolicy::Mar1.anniversary_in(leap_day, 2021), NaiveDate::from_ymd_opt(2021, 3, 1));
+    /// assert_eq!(LeapDayPolicy::SkipYear.anniversary_in(leap_day, 2021), None);
+    /// ```
+    pub fn anniversary_in(self, origin: NaiveDate, year: i32) -> Option<NaiveDate> {
+        match origin.with_year(year) {
+            Some(date) => Some(date),
+            // only Feb 29 can fail to move to a valid year
+            None if origin.month() == 2 && origin.day() == 29 => match self {
+                LeapDayPolicy::Feb28 => NaiveDate::from_ymd_opt(year, 2, 28),
+                LeapDayPolicy::Mar1 => NaiveDate::from_ymd_opt(year, 3, 1),
+                LeapDayPolicy::SkipYear => None,
+            },
+            None => None,
+        }
+    }
+}
+
+/// internal only - the anniversary of `origin` in `year`, at its wall clock time and offset
+pub(crate) fn anniversary<T: Time>(origin: &T, year: i32, policy: LeapDayPolicy) -> Option<T> {
+    let wall = origin.wall_clock();
+    if year <= wall.year() || year > 9999 {
+        return None;
+    }
+    let date = policy.anniversary_in(wall.date(), year)?;
+    Some(T::from_wall_clock(date.and_time(wall.time()), origin.utc_offset()).with_offset_source(origin.offset_source()))
+}
+
+/// internal only - the year the anniversaries of `origin` at or after `t` could start in
+pub(crate) fn first_year<T: Time, U: Time>(origin: &T, t: &U) -> i32 {
+    // read in the origin's offset, an anniversary at or after `t` can't be in an earlier year
+    let year = T::from_epoch_offset(t.raw(), origin.utc_offset()).wall_clock().year();
+    year.max(origin.wall_clock().year() + 1)
+}
+
+/// An iterator over the yearly anniversaries of a time, from `Time::anniversaries_between`
+///
+/// Anniversaries are at the origin's wall clock time and offset, and in order - years without one (under `LeapDayPolicy::SkipYear`) are skipped
+#[derive(Debug, Clone)]
+pub struct Anniversaries<T: Time> {
+    origin: T,
+    policy: LeapDayPolicy,
+    year: i32,
+    start: i64,
+    end: i64,
+}
+
+impl<T: Time> Anniversaries<T> {
+    pub(crate) fn new<S: Time, E: Time>(origin: T, start: &S, end: &E, policy: LeapDayPolicy) -> Self {
+        Anniversaries {
+            year: first_year(&origin, start),
+            origin,
+            policy,
+            start: start.unix_ms(),
+            end: end.unix_ms(),
+        }
+    }
+}
+
+impl<T: Time> Iterator for Anniversaries<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.year <= 9999 {
+            let year = self.year;
+            self.year += 1;
+            let Some(t) = anniversary(&self.origin, year, self.policy) else {
+                continue;
+            };
+            if t.unix_ms() >= self.end {
+                self.year = i32::MAX;
+                return None;
+            }
+            if t.unix_ms() >= self.start {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
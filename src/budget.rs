@@ -0,0 +1,109 @@
+use crate::{BudgetExceeded, Time};
+use core::time::Duration;
+
+/// A deadline for handling a request, with a budget of time that can be spent explicitly or split between sub-calls
+///
+/// The budget runs from a start time to a deadline. `remaining` reads it against a clock, while `consume` spends it explicitly (moving
+/// the start on), and `child` hands a share of what is left to a sub-call. Cloning copies two time values, and it serializes as just the
+/// deadline, so it can be passed to another process in a header - deserializing starts the budget at the system clock's `System::now`
+/// (which respects the `testing` feature's mock clock), whatever `T` is, so that decoding never waits on the network
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, TimeBudget};
+/// use core::time::Duration;
+/// let start = 1704465989u32.unix::<System>();
+/// let mut budget = TimeBudget::new(Duration::from_secs(2), start.clone());
+/// assert_eq!(budget.deadline().pretty(), "2024-01-05 14:46:31");
+/// assert_eq!(budget.remaining(&start.add_seconds(1)), Some(Duration::from_secs(1)));
+///
+/// let lookup = budget.child(0.25);
+/// assert_eq!(lookup.available(), Duration::from_millis(500));
+/// budget.consume(lookup.available()).unwrap();
+/// assert_eq!(budget.available(), Duration::from_millis(1500));
+/// assert!(budget.consume(Duration::from_secs(2)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeBudget<T> {
+    start: T,
+    deadline: T,
+}
+
+impl<T: Time + Clone> TimeBudget<T> {
+    /// A budget of `total`, starting at `start` (the deadline saturates at `Time::far_future`)
+    pub fn new(total: Duration, start: T) -> Self {
+        TimeBudget { deadline: start.expires_at(total), start }
+    }
+
+    /// A budget running from `now` to `deadline` - empty if the deadline has already passed
+    pub fn until(deadline: T, now: T) -> Self {
+        if now.raw() > deadline.raw() {
+            TimeBudget { start: deadline.clone(), deadline }
+        } else {
+            TimeBudget { start: now, deadline }
+        }
+    }
+
+    /// The time left before the deadline, by the clock reading `now`, or None once it has passed (exactly at the deadline counts as passed)
+    pub fn remaining<U: Time>(&self, now: &U) -> Option<Duration> {
+        let remaining = self.deadline.unix_ms() as i128 - now.unix_ms() as i128;
+        (remaining > 0).then(|| Duration::from_millis(remaining.min(u64::MAX as i128) as u64))
+    }
+
+    /// The time left to spend, ignoring the clock - the total, less everything consumed so far
+    pub fn available(&self) -> Duration {
+        Duration::from_millis(self.deadline.raw() - self.start.raw())
+    }
+
+    /// Whether the whole budget has been consumed
+    pub fn is_exhausted(&self) -> bool {
+        self.start.raw() == self.deadline.raw()
+    }
+
+    /// Spend `d` of the budget - if that is more than is available, nothing is spent and the error says how much there was
+    pub fn consume(&mut self, d: Duration) -> Result<(), BudgetExceeded> {
+        let available = self.available();
+        if d > available {
+            return Err(BudgetExceeded { requested: d, available });
+        }
+        self.start = self.start.expires_at(d);
+        Ok(())
+    }
+
+    /// A budget for a sub-call: `fraction` (clamped to `0.0..=1.0`) of what is available, starting now. The parent is not charged - consume
+    /// the child's share from it once the sub-call is done
+    ///
+    /// Children's shares are rounded to the millisecond, so fractions adding up to 1 split the whole budget to within a millisecond per child
+    pub fn child(&self, fraction: f64) -> Self {
+        let fraction = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+        let share = (self.available().as_millis() as f64 * fraction).round() as u64;
+        TimeBudget::new(Duration::from_millis(share), self.start.clone())
+    }
+
+    /// When the budget runs out
+    pub fn deadline(&self) -> T {
+        self.deadline.clone()
+    }
+
+    /// Where the budget has been spent up to - the start, moved on by everything consumed
+    pub fn start(&self) -> T {
+        self.start.clone()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ::serde::Serialize> ::serde::Serialize for TimeBudget<T> {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deadline.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Time + Clone + ::serde::Deserialize<'de>> ::serde::Deserialize<'de> for TimeBudget<T> {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let deadline = T::deserialize(deserializer)?;
+        // the local clock rather than T::now, which for Ntp is a network round trip
+        let now = T::from_epoch_offset(crate::System::now().raw(), deadline.utc_offset());
+        Ok(TimeBudget::until(deadline, now))
+    }
+}
@@ -0,0 +1,76 @@
+use crate::System;
+
+/// when this crate was compiled, in seconds since the Unix epoch (set by the build script, from `SOURCE_DATE_EPOCH` if that was set)
+#[doc(hidden)]
+pub const CRATE_BUILD_UNIX: i64 = parse_unix_const(Some(env!("THETIME_BUILD_UNIX")), 0);
+
+/// internal only - parses a decimal (optionally negative) timestamp at compile time, falling back to `default` if it is missing or malformed
+#[doc(hidden)]
+pub const fn parse_unix_const(value: Option<&str>, default: i64) -> i64 {
+    let bytes = match value {
+        Some(v) => v.as_bytes(),
+        None => return default,
+    };
+    let negative = !bytes.is_empty() && bytes[0] == b'-';
+    let mut i = if negative { 1 } else { 0 };
+    if i == bytes.len() {
+        return default;
+    }
+    let mut n: i64 = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if !b.is_ascii_digit() {
+            return default;
+        }
+        n = match n.checked_mul(10) {
+            Some(x) => match x.checked_add((b - b'0') as i64) {
+                Some(x) => x,
+                None => return default,
+            },
+            None => return default,
+        };
+        i += 1;
+    }
+    if negative { -n } else { n }
+}
+
+/// Reads `SOURCE_DATE_EPOCH` (the reproducible builds convention, whole seconds since the Unix epoch) at runtime
+///
+/// Returns None if it is unset or not an integer
+///
+/// # Examples
+/// ```rust
+/// use thetime::buildtime::from_source_date_epoch;
+/// if let Some(t) = from_source_date_epoch() {
+///     println!("reproducible build from {}", t);
+/// }
+/// ```
+pub fn from_source_date_epoch() -> Option<System> {
+    parse_source_date_epoch(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref())
+}
+
+/// internal only - `from_source_date_epoch`, given the variable's value (None if unset)
+pub(crate) fn parse_source_date_epoch(value: Option<&str>) -> Option<System> {
+    value?.trim().parse::<i64>().ok().map(System::from_unix_const)
+}
+
+/// The time the calling crate was compiled, as a `System` (usable in `const` items)
+///
+/// Uses `SOURCE_DATE_EPOCH` from the compiling environment if set, so reproducible builds get a stable value.
+/// Otherwise falls back to when `thetime` itself was compiled - for a fresh build that is the same build, but incremental builds of your crate will not update it
+///
+/// # Examples
+/// ```rust
+/// use thetime::{build_time, System, Time};
+/// const BUILT: System = build_time!();
+/// println!("built at {}", BUILT);
+/// ```
+#[macro_export]
+macro_rules! build_time {
+    () => {
+        $crate::System::from_unix_const($crate::buildtime::parse_unix_const(
+            option_env!("SOURCE_DATE_EPOCH"),
+            $crate::buildtime::CRATE_BUILD_UNIX,
+        ))
+    };
+}
@@ -0,0 +1,119 @@
+use crate::{ParseError, System, Time};
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, Weekday};
+
+/// What the shorthand in `parse_business_shorthand` means: when the business day closes, how the working week runs, and the wall clock
+/// it is all read on
+///
+/// The default closes at 17:00 UTC, with a Monday to Friday week
+///
+/// # Examples
+/// ```rust
+/// use thetime::{BusinessConventions, Weekday};
+/// use chrono::NaiveTime;
+/// let london = BusinessConventions { close_of_business: NaiveTime::from_hms_opt(17, 30, 0).unwrap(), ..BusinessConventions::default() };
+/// assert_eq!(london.week_start, Weekday::Mon);
+/// assert_eq!(london.last_working_day(), Weekday::Fri);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BusinessConventions {
+    /// the wall clock time the business day ends - what "EOD" and "COB" resolve to, on whichever day
+    pub close_of_business: NaiveTime,
+    /// the first working day of the week
+    pub week_start: Weekday,
+    /// how many days from `week_start` are worked - "EOW" is the close of the last of them
+    pub working_days: u8,
+    /// the offset, in seconds from UTC, of the wall clock the days are read on
+    pub utc_offset: i32,
+}
+
+impl Default for BusinessConventions {
+    fn default() -> Self {
+        BusinessConventions {
+            close_of_business: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            week_start: Weekday::Mon,
+            working_days: 5,
+            utc_offset: 0,
+        }
+    }
+}
+
+impl BusinessConventions {
+    /// The last working day of the week - `working_days` (between 1 and 7) on from `week_start`
+    pub fn last_working_day(&self) -> Weekday {
+        (1..self.working_days.clamp(1, 7)).fold(self.week_start, |day, _| day.succ())
+    }
+}
+
+/// Resolve ops shorthand for a deadline to the instant it means, on the wall clock of `conventions`, as seen at `now`
+///
+/// - "EOD" and "COB": the close of business today - even if that has passed, so a late deadline shows as overdue
+/// - "EOD Friday" and "COB Fri": the close of business on the next Friday, or today if it is one
+/// - "EOW": the close of business on the last working day of this week
+/// - "EOM", "EOQ" and "EOY": the close of business on the last day of the month, quarter or year
+///
+/// Keywords and weekdays are case insensitive, and weekdays can be abbreviated. The result is in the offset of `conventions`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{parse_business_shorthand, BusinessConventions, System, Time, StrTime};
+/// use chrono::NaiveTime;
+/// let conventions = BusinessConventions { close_of_business: NaiveTime::from_hms_opt(17, 30, 0).unwrap(), ..BusinessConventions::default() };
+/// // a Wednesday afternoon
+/// let now = "2024-04-17 14:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// assert_eq!(parse_business_shorthand("COB Friday", &now, &conventions).unwrap().pretty(), "2024-04-19 17:30:00");
+/// assert_eq!(parse_business_shorthand("EOM", &now, &conventions).unwrap().pretty(), "2024-04-30 17:30:00");
+/// assert!(parse_business_shorthand("EOM Friday", &now, &conventions).is_err());
+/// ```
+pub fn parse_business_shorthand(input: &str, now: &impl Time, conventions: &BusinessConventions) -> Result<System, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let today = System::from_epoch_offset(now.raw(), crate::clamp_offset(conventions.utc_offset)).wall_clock().date();
+
+    let (keyword, qualifier) = match trimmed.split_once(char::is_whitespace) {
+        Some((keyword, rest)) => (keyword, Some(rest.trim_start())),
+        None => (trimmed, None),
+    };
+    let qualifier_pos = qualifier.map_or(trimmed.len(), |q| trimmed.len() - q.len());
+    let keyword = keyword.to_ascii_uppercase();
+
+    let day = match (keyword.as_str(), qualifier) {
+        ("EOD" | "COB", None) => Some(today),
+        ("EOD" | "COB", Some(weekday)) => {
+            let weekday = crate::parse::weekday_name(weekday.as_bytes()).ok_or_else(|| ParseError::invalid(trimmed, qualifier_pos, "a weekday name"))?;
+            today.checked_add_days(Days::new(days_until(today.weekday(), weekday)))
+        }
+        ("EOW" | "EOM" | "EOQ" | "EOY", Some(_)) => return Err(ParseError::invalid(trimmed, qualifier_pos, "end of input")),
+        ("EOW", None) => {
+            let into_week = days_until(conventions.week_start, today.weekday());
+            let last = days_until(conventions.week_start, conventions.last_working_day());
+            // either side of today, so the checked arithmetic goes the right way
+            if last >= into_week {
+                today.checked_add_days(Days::new(last - into_week))
+            } else {
+                today.checked_sub_days(Days::new(into_week - last))
+            }
+        }
+        ("EOM", None) => last_day_of_month(today.year(), today.month()),
+        ("EOQ", None) => last_day_of_month(today.year(), today.month0() / 3 * 3 + 3),
+        ("EOY", None) => NaiveDate::from_ymd_opt(today.year(), 12, 31),
+        _ => return Err(ParseError::invalid(trimmed, 0, "EOD, COB, EOW, EOM, EOQ or EOY")),
+    };
+    let day = day.ok_or(ParseError::OutOfRange {
+        field: "year",
+        value: today.year() as i64,
+    })?;
+    Ok(System::from_wall_clock(day.and_time(conventions.close_of_business), crate::clamp_offset(conventions.utc_offset)))
+}
+
+/// internal only - how many days on from `from` the next `to` is (0 if they are the same)
+fn days_until(from: Weekday, to: Weekday) -> u64 {
+    (to.num_days_from_monday() + 7 - from.num_days_from_monday()) as u64 % 7
+}
+
+/// internal only - the last day of a month
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let next = if month == 12 { NaiveDate::from_ymd_opt(year + 1, 1, 1) } else { NaiveDate::from_ymd_opt(year, month + 1, 1) };
+    next?.pred_opt()
+}
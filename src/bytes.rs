@@ -0,0 +1,280 @@
+use crate::parse::{iso_lenient_bytes, Cursor};
+use crate::{OffsetSource, ParseError, Time};
+use chrono::format::{Fixed, Item, Numeric, Parsed, StrftimeItems};
+use chrono::DateTime;
+
+/// whitespace as chrono skips it, for the ASCII range
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\x0b' | b'\x0c' | b'\r')
+}
+
+/// the number of leading whitespace bytes
+fn space_len(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|b| is_space(**b)).count()
+}
+
+/// consume between `min` and `max` ASCII digits from the cursor, like chrono's number scanner
+fn number(cur: &mut Cursor, min: usize, max: usize, what: &str) -> Result<i64, ParseError> {
+    let start = cur.pos;
+    let mut value = 0i64;
+    while cur.pos - start < max {
+        match cur.peek() {
+            Some(b) if b.is_ascii_digit() => {
+                value = value
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add((b - b'0') as i64))
+                    .ok_or_else(|| cur.error_at(start, what))?;
+                cur.pos += 1;
+            }
+            _ => break,
+        }
+    }
+    if cur.pos - start < min {
+        return Err(cur.error(what));
+    }
+    Ok(value)
+}
+
+/// internal only - run the strftime `items` over `input`, validating only the bytes that the items look at
+///
+/// Literals, spaces, numbers, fractions and `%Z` are matched byte by byte; anything else (names, offsets, `%+`...) is handed to chrono
+/// on the longest valid UTF-8 prefix of what is left
+fn parse_items(input: &[u8], format: &str) -> Result<Parsed, ParseError> {
+    let mut parsed = Parsed::new();
+    let mut cur = Cursor::from_bytes(input);
+    run_items(&mut parsed, &mut cur, input, format)?;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(parsed)
+}
+
+/// internal only - the body of `parse_items`, from the cursor's position, leaving the cursor after whatever the format matched
+fn run_items(parsed: &mut Parsed, cur: &mut Cursor, input: &[u8], format: &str) -> Result<(), ParseError> {
+    for item in StrftimeItems::new(format) {
+        let rest = &input[cur.pos..];
+        let start = cur.pos;
+        match item {
+            Item::Literal(lit) => {
+                if !rest.starts_with(lit.as_bytes()) {
+                    return Err(cur.error(&format!("{:?}", lit)));
+                }
+                cur.pos += lit.len();
+            }
+            Item::OwnedLiteral(ref lit) => {
+                if !rest.starts_with(lit.as_bytes()) {
+                    return Err(cur.error(&format!("{:?}", lit)));
+                }
+                cur.pos += lit.len();
+            }
+            Item::Space(_) | Item::OwnedSpace(_) => cur.pos += space_len(rest),
+            Item::Numeric(ref spec, _) if !matches!(spec, Numeric::Internal(_)) => {
+                type Setter = fn(&mut Parsed, i64) -> chrono::format::ParseResult<()>;
+                let (width, signed, set, what): (usize, bool, Setter, &str) = match spec {
+                    Numeric::Year => (4, true, Parsed::set_year, "a year"),
+                    Numeric::YearDiv100 => (2, false, Parsed::set_year_div_100, "a century"),
+                    Numeric::YearMod100 => (2, false, Parsed::set_year_mod_100, "a two digit year"),
+                    Numeric::IsoYear => (4, true, Parsed::set_isoyear, "an ISO year"),
+                    Numeric::IsoYearDiv100 => (2, false, Parsed::set_isoyear_div_100, "an ISO century"),
+                    Numeric::IsoYearMod100 => (2, false, Parsed::set_isoyear_mod_100, "a two digit ISO year"),
+                    Numeric::Month => (2, false, Parsed::set_month, "a month"),
+                    Numeric::Day => (2, false, Parsed::set_day, "a day"),
+                    Numeric::WeekFromSun => (2, false, Parsed::set_week_from_sun, "a week number"),
+                    Numeric::WeekFromMon => (2, false, Parsed::set_week_from_mon, "a week number"),
+                    Numeric::IsoWeek => (2, false, Parsed::set_isoweek, "an ISO week"),
+                    Numeric::Ordinal => (3, false, Parsed::set_ordinal, "a day of the year"),
+                    Numeric::Hour => (2, false, Parsed::set_hour, "an hour"),
+                    Numeric::Hour12 => (2, false, Parsed::set_hour12, "an hour"),
+                    Numeric::Minute => (2, false, Parsed::set_minute, "a minute"),
+                    Numeric::Second => (2, false, Parsed::set_second, "a second"),
+                    Numeric::Nanosecond => (9, false, Parsed::set_nanosecond, "nanoseconds"),
+                    Numeric::Timestamp => (usize::MAX, false, Parsed::set_timestamp, "a timestamp"),
+                    // weekday numbers and anything newer go through chrono
+                    _ => {
+                        fallback(parsed, cur, input, item.clone())?;
+                        continue;
+                    }
+                };
+                cur.pos += space_len(rest);
+                let value = if signed && cur.eat(b'-') {
+                    -number(cur, 1, usize::MAX, what)?
+                } else if signed && cur.eat(b'+') {
+                    number(cur, 1, usize::MAX, what)?
+                } else {
+                    number(cur, 1, width, what)?
+                };
+                set(parsed, value).map_err(|e| cur.error_at(start, &format!("{} ({})", what, e)))?;
+            }
+            Item::Fixed(Fixed::Nanosecond) => {
+                if cur.eat(b'.') {
+                    let digits_start = cur.pos;
+                    let value = number(cur, 1, 9, "fractional digits")?;
+                    let scale = 10i64.pow(9 - (cur.pos - digits_start) as u32);
+                    parsed.set_nanosecond(value * scale).map_err(|e| cur.error_at(start, &e.to_string()))?;
+                    while matches!(cur.peek(), Some(b) if b.is_ascii_digit()) {
+                        cur.pos += 1;
+                    }
+                }
+            }
+            Item::Fixed(ref fixed @ (Fixed::Nanosecond3 | Fixed::Nanosecond6 | Fixed::Nanosecond9)) => {
+                let digits = match fixed {
+                    Fixed::Nanosecond3 => 3,
+                    Fixed::Nanosecond6 => 6,
+                    _ => 9,
+                };
+                if cur.eat(b'.') {
+                    let value = number(cur, digits, digits, "fractional digits")?;
+                    parsed
+                        .set_nanosecond(value * 10i64.pow(9 - digits as u32))
+                        .map_err(|e| cur.error_at(start, &e.to_string()))?;
+                }
+            }
+            // a zone name is skipped without being looked at, so it can hold any bytes
+            Item::Fixed(Fixed::TimezoneName) => cur.pos += rest.iter().take_while(|b| !is_space(**b)).count(),
+            Item::Error => return Err(cur.error_at(0, "a valid format string")),
+            other => fallback(parsed, cur, input, other)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// internal only - parse `input` with a format that may hold one `%Q`, which matches a timezone name (a run of ASCII letters and slashes)
+///
+/// Returns the parsed fields, and the name with its byte position if the format had a `%Q`
+#[cfg(feature = "timezones")]
+pub(crate) fn parse_with_zone_name(input: &str, format: &str) -> Result<(Parsed, Option<(String, usize)>), ParseError> {
+    // find the %Q, stepping over other specifiers so that "%%Q" stays a literal
+    let bytes = format.as_bytes();
+    let mut split = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && bytes.get(i + 1) == Some(&b'Q') {
+            if split.is_some() {
+                return Err(ParseError::invalid(format, i, "a format with at most one %Q"));
+            }
+            split = Some(i);
+        }
+        i += if bytes[i] == b'%' { 2 } else { 1 };
+    }
+
+    let mut parsed = Parsed::new();
+    let mut cur = Cursor::new(input);
+    let name = match split {
+        None => {
+            run_items(&mut parsed, &mut cur, input.as_bytes(), format)?;
+            None
+        }
+        Some(at) => {
+            run_items(&mut parsed, &mut cur, input.as_bytes(), &format[..at])?;
+            let start = cur.pos;
+            while matches!(cur.peek(), Some(b) if b.is_ascii_alphabetic() || b == b'/') {
+                cur.pos += 1;
+            }
+            if cur.pos == start {
+                return Err(cur.error("a timezone name"));
+            }
+            let name = input[start..cur.pos].to_string();
+            run_items(&mut parsed, &mut cur, input.as_bytes(), &format[at + 2..])?;
+            Some((name, start))
+        }
+    };
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok((parsed, name))
+}
+
+/// parse a single item with chrono, over the valid UTF-8 prefix of the rest of the input
+fn fallback(parsed: &mut Parsed, cur: &mut Cursor, input: &[u8], item: Item) -> Result<(), ParseError> {
+    let rest = &input[cur.pos..];
+    let valid = match core::str::from_utf8(rest) {
+        Ok(s) => s,
+        Err(e) => core::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or_default(),
+    };
+    let remainder = chrono::format::parse_and_remainder(parsed, valid, core::iter::once(item)).map_err(|e| cur.error(&e.to_string()))?;
+    cur.pos += valid.len() - remainder.len();
+    Ok(())
+}
+
+/// internal only - the bytes equivalent of `parse_with_default_offset`
+fn parse_bytes_assuming<T: Time>(input: &[u8], format: &str, default_offset: Option<i32>, assumed: OffsetSource) -> Result<T, ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let parsed = parse_items(input, format)?;
+    let invalid = |e: &dyn ToString| ParseError::invalid(&String::from_utf8_lossy(input), input.len(), e.to_string());
+    let (unix_ms, offset, source) = crate::resolve_parsed(&parsed, format, default_offset, assumed).map_err(|e| invalid(&e))?;
+    let wall = DateTime::from_timestamp_millis(unix_ms + offset as i64 * 1000).ok_or_else(|| invalid(&"a representable time"))?;
+    Ok(T::from_wall_clock(wall.naive_utc(), offset).with_offset_source(source))
+}
+
+/// Parse bytes with a strftime format into a time struct of choice, without converting them to a `&str` first
+///
+/// Gives the same result as `Time::strptime` on the equivalent string (assuming UTC if there is no offset), but only the bytes that
+/// the format looks at need to be ASCII - so a `%Z` zone name, for example, may contain invalid UTF-8. Errors instead of panicking,
+/// with the byte position of the problem
+///
+/// # Examples
+/// ```rust
+/// use thetime::{parse_time_bytes, System, Time};
+/// let x: System = parse_time_bytes(b"2024-01-05 14:46:29.123 \xff\xfe", "%Y-%m-%d %H:%M:%S%.3f %Z").unwrap();
+/// assert_eq!(x.unix_ms(), 1704465989123);
+/// assert!(parse_time_bytes::<System>(b"2024-01-05", "%Y-%m-%d %H").is_err());
+/// ```
+pub fn parse_time_bytes<T: Time>(input: &[u8], format: &str) -> Result<T, ParseError> {
+    parse_bytes_assuming(input, format, Some(0), OffsetSource::AssumedUtc)
+}
+
+/// Provides wrappers on byte slices to parse into time structs, mirroring `StrTime` - all of these return errors rather than panicking
+pub trait BytesTime: AsRef<[u8]> {
+    /// Parse bytes into a time struct of choice (see `parse_time_bytes`)
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, BytesTime};
+    /// assert_eq!(b"2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").unwrap().unix(), 1483228800);
+    /// ```
+    fn parse_time<T: Time>(&self, format: &str) -> Result<T, ParseError> {
+        parse_time_bytes(self.as_ref(), format)
+    }
+
+    /// Parse bytes into a time struct of choice, using the ISO8601 format
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, BytesTime};
+    /// assert_eq!(b"2017-01-01T00:00:00.000".strp_iso8601::<System>().unwrap().unix(), 1483228800);
+    /// ```
+    fn strp_iso8601<T: Time>(&self) -> Result<T, ParseError> {
+        parse_time_bytes(self.as_ref(), "%Y-%m-%dT%H:%M:%S%.f")
+    }
+
+    /// Parse bytes into a time struct of choice, using the RFC3339 format
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, BytesTime};
+    /// assert_eq!(b"2017-01-01T00:00:00.000Z".strp_rf3339::<System>().unwrap().unix(), 1483228800);
+    /// ```
+    fn strp_rf3339<T: Time>(&self) -> Result<T, ParseError> {
+        parse_time_bytes(self.as_ref(), "%Y-%m-%dT%H:%M:%S%.fZ")
+    }
+
+    /// Parse "ISO8601-ish" bytes leniently, as found in real world logs - see `StrTime::strp_iso_lenient` for what is accepted
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, BytesTime};
+    /// assert_eq!(b"2024-01-05_14:46:29,123".strp_iso_lenient::<System>().unwrap().unix_ms(), 1704465989123);
+    /// ```
+    fn strp_iso_lenient<T: Time>(&self) -> Result<T, ParseError> {
+        iso_lenient_bytes(self.as_ref())
+    }
+}
+
+impl BytesTime for [u8] {}
+
+impl<const N: usize> BytesTime for [u8; N] {}
+
+impl BytesTime for Vec<u8> {}
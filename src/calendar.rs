@@ -0,0 +1,140 @@
+use crate::{Time, Weekday};
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+/// Options for `format_month`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{MonthFormat, Weekday};
+/// let opts = MonthFormat::default().week_start(Weekday::Sun).highlight(25);
+/// assert_eq!(opts.highlight, Some(25));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MonthFormat {
+    /// the first column of the grid, `Weekday::Mon` by default
+    pub week_start: Weekday,
+    /// a day of the month to show in reverse video (with ANSI escapes), eg today
+    pub highlight: Option<u32>,
+    /// fill the first and last weeks with the neighbouring months' days instead of blanks
+    pub neighbours: bool,
+}
+
+impl Default for MonthFormat {
+    fn default() -> Self {
+        MonthFormat {
+            week_start: Weekday::Mon,
+            highlight: None,
+            neighbours: false,
+        }
+    }
+}
+
+impl MonthFormat {
+    /// Start weeks on a different day
+    pub fn week_start(mut self, week_start: Weekday) -> Self {
+        self.week_start = week_start;
+        self
+    }
+
+    /// Highlight a day of the month
+    pub fn highlight(mut self, day: u32) -> Self {
+        self.highlight = Some(day);
+        self
+    }
+
+    /// Show the neighbouring months' days in the first and last weeks
+    pub fn neighbours(mut self, neighbours: bool) -> Self {
+        self.neighbours = neighbours;
+        self
+    }
+}
+
+/// internal only - the dates of a month's grid, one row per week
+fn date_grid(year: i32, month: u8, week_start: Weekday, neighbours: bool) -> Vec<[Option<NaiveDate>; 7]> {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month as u32, 1) else {
+        return Vec::new();
+    };
+    let back = (first.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+    let mut day = first - chrono::Duration::days(back as i64);
+    let mut weeks = Vec::new();
+    loop {
+        let week: [Option<NaiveDate>; 7] = core::array::from_fn(|i| {
+            let date = day + chrono::Duration::days(i as i64);
+            (neighbours || date.month() == month as u32).then_some(date)
+        });
+        weeks.push(week);
+        day += chrono::Duration::days(7);
+        if day.month() != month as u32 || day.year() != year {
+            break;
+        }
+    }
+    weeks
+}
+
+/// The weeks of a month, as rows of seven days starting on `week_start`, each day being midnight UTC
+///
+/// Days outside the month are None - use `month_grid_with` to fill them with the neighbouring months' days instead. An invalid month gives an empty grid
+///
+/// # Examples
+/// ```rust
+/// use thetime::{month_grid, System, Time, Weekday};
+/// let weeks = month_grid::<System>(2024, 2, Weekday::Mon);
+/// assert_eq!(weeks.len(), 5);
+/// assert!(weeks[0][2].is_none()); // February 2024 starts on a Thursday
+/// assert_eq!(weeks[0][3].as_ref().unwrap().pretty(), "2024-02-01 00:00:00");
+/// ```
+pub fn month_grid<T: Time>(year: i32, month: u8, week_start: Weekday) -> Vec<[Option<T>; 7]> {
+    month_grid_with(year, month, week_start, false)
+}
+
+/// Like `month_grid`, but with `neighbours` set the first and last weeks are filled with days from the previous and next months
+pub fn month_grid_with<T: Time>(year: i32, month: u8, week_start: Weekday, neighbours: bool) -> Vec<[Option<T>; 7]> {
+    date_grid(year, month, week_start, neighbours)
+        .into_iter()
+        .map(|week| week.map(|date| date.map(|d| T::from_wall_clock(d.and_time(NaiveTime::MIN), 0))))
+        .collect()
+}
+
+/// A `cal`-style text block for a month: the centred month and year, a row of two-letter day names, then one line per week
+///
+/// Lines are 20 columns wide at most, with trailing spaces trimmed. An invalid month gives an empty string
+///
+/// # Examples
+/// ```rust
+/// use thetime::{format_month, MonthFormat};
+/// print!("{}", format_month(2024, 2, MonthFormat::default()));
+/// //    February 2024
+/// // Mo Tu We Th Fr Sa Su
+/// //           1  2  3  4
+/// // ...
+/// ```
+pub fn format_month(year: i32, month: u8, opts: MonthFormat) -> String {
+    let Some(name) = chrono::Month::try_from(month).ok().map(|m| m.name()) else {
+        return String::new();
+    };
+    let title = format!("{} {}", name, year);
+    let mut out = format!("{}{}\n", " ".repeat(20usize.saturating_sub(title.len()) / 2), title);
+
+    let mut weekday = opts.week_start;
+    let mut names = Vec::with_capacity(7);
+    for _ in 0..7 {
+        names.push(weekday.to_string()[..2].to_string());
+        weekday = weekday.succ();
+    }
+    out += &names.join(" ");
+    out.push('\n');
+
+    for week in date_grid(year, month, opts.week_start, opts.neighbours) {
+        let cells: Vec<String> = week
+            .iter()
+            .map(|date| match date {
+                Some(d) if d.month() == month as u32 && opts.highlight == Some(d.day()) => format!("\x1b[7m{:>2}\x1b[0m", d.day()),
+                Some(d) => format!("{:>2}", d.day()),
+                None => "  ".to_string(),
+            })
+            .collect();
+        out += cells.join(" ").trim_end();
+        out.push('\n');
+    }
+    out
+}
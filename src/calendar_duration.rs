@@ -0,0 +1,105 @@
+use crate::{ImplsDuration, MonthRollError, MonthRollPolicy, Time};
+use core::fmt::{self, Display};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A duration measured partly on the calendar - "1 month and 3 days" - which, unlike an exact duration, has no fixed length: a month
+/// is 28 to 31 days depending on where it starts. Applied with `Time::add_calendar`
+///
+/// Each part may be negative, independently of the others. Equality compares the parts as they are, so "1mo" isn't equal to "30d"
+/// and "1d" isn't equal to "24:00:00". There is deliberately no ordering: whether "1mo" is longer than "30d" depends on the month, so
+/// to compare two calendar durations, apply both to the same time and compare the results
+///
+/// # Examples
+/// ```rust
+/// use thetime::{CalendarDuration, System, Time, StrTime, MonthRollPolicy};
+/// let policy = CalendarDuration::new(1, 3, 4 * 3_600_000);
+/// assert_eq!(policy.to_string(), "1mo 3d 04:00:00");
+/// let x = "2024-01-31 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// assert_eq!(x.add_calendar(&policy, MonthRollPolicy::Clamp).unwrap().pretty(), "2024-03-03 13:00:00");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CalendarDuration {
+    /// whole calendar months
+    pub months: i32,
+    /// whole calendar days
+    pub days: i32,
+    /// the exact part, in milliseconds
+    pub hms_ms: i64,
+}
+
+impl CalendarDuration {
+    /// A calendar duration from all three parts
+    pub fn new(months: i32, days: i32, hms_ms: i64) -> Self {
+        CalendarDuration { months, days, hms_ms }
+    }
+
+    /// Just whole months
+    pub fn months(months: i32) -> Self {
+        CalendarDuration::new(months, 0, 0)
+    }
+
+    /// Just whole days
+    pub fn days(days: i32) -> Self {
+        CalendarDuration::new(0, days, 0)
+    }
+
+    /// Just an exact duration - a `core::time::Duration`, a `chrono::Duration`, or whole seconds as an `i64`
+    pub fn exact<D: ImplsDuration>(duration: D) -> Self {
+        CalendarDuration::new(0, 0, duration.num_milliseconds())
+    }
+
+    /// Whether every part is zero
+    pub fn is_zero(&self) -> bool {
+        *self == CalendarDuration::default()
+    }
+
+    /// Each part negated (saturating), for going back by the same amount
+    pub fn negated(&self) -> Self {
+        CalendarDuration::new(self.months.saturating_neg(), self.days.saturating_neg(), self.hms_ms.saturating_neg())
+    }
+
+    /// internal only - `Time::add_calendar`
+    pub(crate) fn add_to<T: Time>(&self, time: &T, policy: MonthRollPolicy) -> Result<T, MonthRollError> {
+        // months first, so that Jan 30 + "1mo 1d" is Mar 1 (via Feb 29) - days first would give Feb 29 (via Jan 31)
+        let unix_ms = if self.months == 0 { time.unix_ms() } else { time.add_months_with(self.months, policy)?.unix_ms() };
+
+        // then days, then the exact part - offsets are fixed, so a day on the wall clock is always 24 hours
+        let unix_ms = (self.days as i64 * 86_400_000)
+            .checked_add(self.hms_ms)
+            .and_then(|ms| unix_ms.checked_add(ms))
+            .ok_or(MonthRollError::OutOfRange)?;
+        if unix_ms < -(crate::OFFSET_1601 as i64 * 1000) || unix_ms > crate::FAR_FUTURE_UNIX_MS {
+            return Err(MonthRollError::OutOfRange);
+        }
+        let raw = (unix_ms + crate::OFFSET_1601 as i64 * 1000) as u64;
+        Ok(T::from_epoch_offset(raw, time.utc_offset()).with_offset_source(time.offset_source()))
+    }
+}
+
+/// "1mo 3d 04:00:00" - months and days are left out when zero, and the exact part is left out when zero unless it is all there is.
+/// Milliseconds are shown only when non-zero ("00:00:01.500"), hours are padded to two digits but not limited to 24, and each
+/// negative part gets its own '-'
+impl Display for CalendarDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        if self.months != 0 {
+            write!(f, "{}mo", self.months)?;
+            sep = " ";
+        }
+        if self.days != 0 {
+            write!(f, "{}{}d", sep, self.days)?;
+            sep = " ";
+        }
+        if self.hms_ms != 0 || sep.is_empty() {
+            let sign = if self.hms_ms < 0 { "-" } else { "" };
+            let ms = self.hms_ms.unsigned_abs();
+            write!(f, "{}{}{:02}:{:02}:{:02}", sep, sign, ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60)?;
+            if !ms.is_multiple_of(1000) {
+                write!(f, ".{:03}", ms % 1000)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! A frozen byte encoding of an instant, for signing and hashing records that hold timestamps
+//!
+//! The encoding is 10 bytes: the milliseconds since `1601-01-01 00:00:00` UTC as a big-endian `u64`, then the version byte
+//! (`CANONICAL_VERSION`) and a reserved byte that is always 0. The offset a time is displayed in is not part of it, so every offset
+//! of one instant encodes the same. **This layout is frozen** - signatures made over it stay valid across releases, and any change
+//! would come with a new version byte, which older decoders reject
+//!
+//! # Examples
+//! ```rust
+//! use thetime::{System, Time, IntTime};
+//! let x = 1704465989u32.unix::<System>();
+//! let bytes = x.canonical_bytes();
+//! assert_eq!(bytes, [0x00, 0x00, 0x0c, 0x24, 0x0a, 0xcd, 0x65, 0x88, 0x01, 0x00]);
+//! assert_eq!(x.change_tz("+09:00").canonical_bytes(), bytes);
+//! assert_eq!(System::from_canonical_bytes(&bytes).unwrap(), x);
+//! ```
+
+use crate::{Time, TimeError};
+
+/// The version byte of the canonical encoding this release writes and reads
+pub const CANONICAL_VERSION: u8 = 1;
+
+/// The length of the canonical encoding, in bytes
+pub const CANONICAL_LEN: usize = 10;
+
+/// internal only - `Time::canonical_bytes`
+pub(crate) fn encode<T: Time + ?Sized>(time: &T) -> [u8; CANONICAL_LEN] {
+    let mut out = [0; CANONICAL_LEN];
+    out[..8].copy_from_slice(&time.raw().to_be_bytes());
+    out[8] = CANONICAL_VERSION;
+    out
+}
+
+/// internal only - `Time::from_canonical_bytes`, in UTC
+pub(crate) fn decode<T: Time>(bytes: &[u8; CANONICAL_LEN]) -> Result<T, TimeError> {
+    if bytes[8] != CANONICAL_VERSION {
+        return Err(TimeError::InvalidField { field: "canonical version", value: bytes[8] as i64 });
+    }
+    if bytes[9] != 0 {
+        return Err(TimeError::InvalidField { field: "canonical reserved byte", value: bytes[9] as i64 });
+    }
+    let raw = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+    if raw > crate::FAR_FUTURE_UNIX_MS as u64 + crate::OFFSET_1601 * 1000 {
+        return Err(TimeError::Overflow);
+    }
+    Ok(T::from_epoch_offset(raw, 0))
+}
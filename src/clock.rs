@@ -0,0 +1,131 @@
+use crate::{Ntp, System, Time};
+
+/// A source of the current time, so that code can be handed a clock rather than calling `System::now` directly
+///
+/// `RealClock` reads the real clocks, `FixedClock` always gives the same time, and `OffsetClock` skews another clock - with the
+/// `testing` feature, `mock::MockClock` reads the per-thread mock time. References and boxes of clocks are clocks too, so
+/// `&dyn Clock` and `Box<dyn Clock>` work where a generic `impl Clock` is not wanted
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Clock, FixedClock, RealClock, System, Time, IntTime};
+/// struct Session { started: System }
+/// impl Session {
+///     fn start(clock: &impl Clock) -> Session {
+///         Session { started: clock.now_system() }
+///     }
+/// }
+/// let fixed = FixedClock(1704465989u32.unix::<System>());
+/// assert_eq!(Session::start(&fixed).started.unix(), 1704465989);
+/// assert!(Session::start(&RealClock).started.unix_ms() > 1704465989000);
+/// ```
+pub trait Clock {
+    /// The current time, as a `System`
+    fn now_system(&self) -> System;
+
+    /// The current time, as an `Ntp` - by default, `now_system` converted (so with no server reply)
+    fn now_ntp(&self) -> Ntp {
+        convert(self.now_system())
+    }
+
+    /// The current time, as any time type - built from `now_system`, keeping its offset
+    fn now<T: Time>(&self) -> T
+    where
+        Self: Sized,
+    {
+        convert(self.now_system())
+    }
+}
+
+/// internal only - a `System` reading as another time type, keeping its offset
+fn convert<T: Time>(now: System) -> T {
+    T::from_epoch_offset(now.raw(), now.utc_offset()).with_offset_source(now.offset_source())
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now_system(&self) -> System {
+        (**self).now_system()
+    }
+
+    fn now_ntp(&self) -> Ntp {
+        (**self).now_ntp()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now_system(&self) -> System {
+        (**self).now_system()
+    }
+
+    fn now_ntp(&self) -> Ntp {
+        (**self).now_ntp()
+    }
+}
+
+/// The real clocks: `System::now`, and `Ntp::now` (which queries pool.ntp.org, falling back to the system clock)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now_system(&self) -> System {
+        System::now()
+    }
+
+    fn now_ntp(&self) -> Ntp {
+        Ntp::now()
+    }
+}
+
+/// A clock stopped at one time, for deterministic tests
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Clock, FixedClock, System, Time, IntTime};
+/// let clock = FixedClock(1704465989u32.unix::<System>());
+/// assert_eq!(clock.now_system(), clock.now_system());
+/// assert_eq!(clock.now_ntp().unix(), 1704465989);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedClock(pub System);
+
+impl Clock for FixedClock {
+    fn now_system(&self) -> System {
+        self.0.clone()
+    }
+}
+
+/// Another clock, running `skew_ms` milliseconds ahead of it (or behind, if negative) - for simulating a host whose clock is off
+///
+/// The skewed time saturates at `1601-01-01 00:00:00`, and keeps the inner clock's offset
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Clock, FixedClock, OffsetClock, System, Time, IntTime};
+/// let clock = OffsetClock { inner: FixedClock(1704465989u32.unix::<System>()), skew_ms: -1500 };
+/// assert_eq!(clock.now_system().unix_ms(), 1704465987500);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OffsetClock<C> {
+    /// the clock being skewed
+    pub inner: C,
+    /// how far ahead of `inner` this clock runs, in milliseconds
+    pub skew_ms: i64,
+}
+
+impl<C: Clock> OffsetClock<C> {
+    /// internal only - `t` moved on by the skew
+    fn skew<T: Time>(&self, t: T) -> T {
+        let raw = (t.raw() as i64).saturating_add(self.skew_ms).max(0) as u64;
+        T::from_epoch_offset(raw, t.utc_offset()).with_offset_source(t.offset_source())
+    }
+}
+
+impl<C: Clock> Clock for OffsetClock<C> {
+    fn now_system(&self) -> System {
+        self.skew(self.inner.now_system())
+    }
+
+    fn now_ntp(&self) -> Ntp {
+        self.skew(self.inner.now_ntp())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{ImplsDuration, Time};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The aggregated samples falling within one bucket of a `Downsampler`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Bucket<T> {
+    /// the start of the bucket (inclusive)
+    pub start: T,
+    /// the number of samples in the bucket
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    /// the value of the sample with the latest timestamp in the bucket
+    pub last: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_raw: u64,
+}
+
+impl<T> Bucket<T> {
+    /// the mean of the samples in the bucket
+    pub fn avg(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Downsamples `(time, value)` samples into fixed-width buckets anchored at an origin, keeping at most `max_buckets` of the most recent buckets
+///
+/// Samples can arrive out of order - as long as they fall within the retained buckets they are aggregated into the right one, anything older is dropped
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, Downsampler};
+/// let origin = System::now();
+/// let mut ds = Downsampler::new(origin.clone(), core::time::Duration::from_secs(60), 60);
+/// ds.push(&origin.add_seconds(30), 1.0);
+/// ds.push(&origin.add_seconds(90), 3.0);
+/// for bucket in ds.buckets() {
+///     println!("{}: avg {}", bucket.start, bucket.avg());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Downsampler<T: Time> {
+    origin: T,
+    width_ms: i64,
+    max_buckets: usize,
+    buckets: BTreeMap<i64, Bucket<T>>,
+}
+
+impl<T: Time> Downsampler<T> {
+    /// Create a new downsampler with buckets of `bucket_width` starting at `origin`, retaining at most `max_buckets` (both clamped to at least 1ms / 1 bucket)
+    pub fn new<D: ImplsDuration>(origin: T, bucket_width: D, max_buckets: usize) -> Self {
+        Downsampler {
+            origin,
+            width_ms: bucket_width.num_milliseconds().max(1),
+            max_buckets: max_buckets.max(1),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Add a sample, returning false if it was dropped for being older than the retained buckets
+    pub fn push<U: Time>(&mut self, time: &U, value: f64) -> bool {
+        let index = (time.raw() as i64 - self.origin.raw() as i64).div_euclid(self.width_ms);
+
+        if let Some((&newest, _)) = self.buckets.last_key_value() {
+            if index <= newest - self.max_buckets as i64 {
+                return false;
+            }
+        }
+
+        let start = T::from_epoch_offset(
+            (self.origin.raw() as i64 + index * self.width_ms) as u64,
+            self.origin.utc_offset(),
+        );
+        let bucket = self.buckets.entry(index).or_insert(Bucket {
+            start,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            last: value,
+            last_raw: time.raw(),
+        });
+        bucket.count += 1;
+        bucket.min = bucket.min.min(value);
+        bucket.max = bucket.max.max(value);
+        bucket.sum += value;
+        if time.raw() >= bucket.last_raw {
+            bucket.last = value;
+            bucket.last_raw = time.raw();
+        }
+
+        // evict anything that has fallen out of the retention window
+        let newest = *self.buckets.last_key_value().unwrap().0;
+        self.buckets = self.buckets.split_off(&(newest - self.max_buckets as i64 + 1));
+        true
+    }
+
+    /// The retained, non-empty buckets in chronological order
+    pub fn buckets(&self) -> Vec<Bucket<T>>
+    where
+        T: Clone,
+    {
+        self.buckets.values().cloned().collect()
+    }
+}
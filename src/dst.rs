@@ -0,0 +1,204 @@
+use crate::{Time, TimeError, OFFSET_1601};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+/// When a daylight saving transition happens, POSIX `TZ` style (`Mm.w.d/time`): the `week`th `weekday` of `month`, at `time` seconds past
+/// local midnight, in the offset in effect before the transition
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TransitionRule {
+    /// the month, from 1 to 12
+    pub month: u8,
+    /// which occurrence of `weekday` in the month, from 1 to 4, or 5 for the last
+    pub week: u8,
+    /// the day of the week
+    pub weekday: Weekday,
+    /// seconds past local midnight
+    pub time: i32,
+}
+
+impl TransitionRule {
+    /// The local date of the transition in `year`, or None if the rule or year is invalid
+    pub fn date(&self, year: i32) -> Option<NaiveDate> {
+        match self.week {
+            1..=4 => NaiveDate::from_weekday_of_month_opt(year, self.month as u32, self.weekday, self.week),
+            5 => NaiveDate::from_weekday_of_month_opt(year, self.month as u32, self.weekday, 5)
+                .or_else(|| NaiveDate::from_weekday_of_month_opt(year, self.month as u32, self.weekday, 4)),
+            _ => None,
+        }
+    }
+
+    /// internal only - the transition in `year` as unix seconds, given the offset in effect before it
+    fn unix(&self, year: i32, offset_before: i32) -> Option<i64> {
+        let midnight = self.date(year)?.and_time(NaiveTime::MIN).and_utc().timestamp();
+        Some(midnight + self.time as i64 - offset_before as i64)
+    }
+}
+
+/// What to do with a local time that a daylight saving transition skips (a gap) or repeats (a fold)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum AmbiguityPolicy {
+    /// return an error saying which problem occurred, with the candidate instants
+    #[default]
+    Reject,
+    /// the earlier of the two candidate instants
+    Earlier,
+    /// the later of the two candidate instants
+    Later,
+    /// in a gap, the transition itself (the first local time after the gap); in a fold, the later instant
+    ShiftForward,
+}
+
+/// A yearly daylight saving rule: a standard offset, a daylight offset, and when each starts
+///
+/// Rules where `start` falls later in the year than `end` (as in the southern hemisphere) are handled
+///
+/// # Examples
+/// ```rust
+/// use thetime::{DstRule, AmbiguityPolicy, System, Time};
+/// let cet = DstRule::eu(3600);
+/// let summer: System = cet.resolve_local(2024, 7, 1, 12, 0, 0, AmbiguityPolicy::Reject).unwrap();
+/// assert_eq!(summer.utc_offset(), 7200);
+/// assert_eq!(summer.to_utc().pretty(), "2024-07-01 10:00:00");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DstRule {
+    /// the offset outside daylight saving time, in seconds east of UTC
+    pub standard_offset: i32,
+    /// the offset during daylight saving time, in seconds east of UTC
+    pub dst_offset: i32,
+    /// when daylight saving time starts
+    pub start: TransitionRule,
+    /// when daylight saving time ends
+    pub end: TransitionRule,
+}
+
+impl DstRule {
+    /// The European Union rule for a zone with the given standard offset: an hour forward on the last Sunday of March and back on the last
+    /// Sunday of October, both at 01:00 UTC
+    pub fn eu(standard_offset: i32) -> DstRule {
+        let last_sunday = |month, time| TransitionRule {
+            month,
+            week: 5,
+            weekday: Weekday::Sun,
+            time,
+        };
+        DstRule {
+            standard_offset,
+            dst_offset: standard_offset + 3600,
+            start: last_sunday(3, 3600 + standard_offset),
+            end: last_sunday(10, 3600 + standard_offset + 3600),
+        }
+    }
+
+    /// The United States rule for a zone with the given standard offset: an hour forward on the second Sunday of March and back on the first
+    /// Sunday of November, both at 02:00 local time
+    pub fn us(standard_offset: i32) -> DstRule {
+        let sunday = |month, week| TransitionRule {
+            month,
+            week,
+            weekday: Weekday::Sun,
+            time: 7200,
+        };
+        DstRule {
+            standard_offset,
+            dst_offset: standard_offset + 3600,
+            start: sunday(3, 2),
+            end: sunday(11, 1),
+        }
+    }
+
+    /// The start and end of daylight saving time in `year`, as unix seconds
+    pub fn transitions(&self, year: i32) -> Option<(i64, i64)> {
+        Some((self.start.unix(year, self.standard_offset)?, self.end.unix(year, self.dst_offset)?))
+    }
+
+    /// internal only - whether daylight saving time is in effect at a unix second
+    fn is_dst_unix(&self, unix: i64) -> bool {
+        let year = chrono::DateTime::from_timestamp(unix + self.standard_offset as i64, 0).map_or(1970, |d| d.year());
+        match self.transitions(year) {
+            Some((start, end)) if start <= end => start <= unix && unix < end,
+            Some((start, end)) => unix < end || start <= unix,
+            None => false,
+        }
+    }
+
+    /// Whether daylight saving time is in effect at the instant of a time object
+    pub fn is_dst<T: Time>(&self, time: &T) -> bool {
+        self.is_dst_unix(time.unix_ms().div_euclid(1000))
+    }
+
+    /// The offset in effect at the instant of a time object
+    pub fn offset_at<T: Time>(&self, time: &T) -> i32 {
+        if self.is_dst(time) {
+            self.dst_offset
+        } else {
+            self.standard_offset
+        }
+    }
+
+    /// The same instant as a time object, displayed in the offset this rule gives it
+    pub fn localize<T: Time>(&self, time: &T) -> T {
+        time.change_tz_seconds(self.offset_at(time))
+    }
+
+    /// Build a time object from a local wall clock under this rule, resolving skipped and repeated local times with `policy`
+    ///
+    /// The result displays in the offset in effect at its instant. Errors with `TimeError::InvalidField` for an impossible date, and
+    /// (under `AmbiguityPolicy::Reject`) with `TimeError::SkippedLocalTime` or `TimeError::AmbiguousLocalTime`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{DstRule, AmbiguityPolicy, System, Time, TimeError};
+    /// let cet = DstRule::eu(3600);
+    /// let skipped = cet.resolve_local::<System>(2024, 3, 31, 2, 30, 0, AmbiguityPolicy::Reject);
+    /// assert!(matches!(skipped, Err(TimeError::SkippedLocalTime { .. })));
+    /// let shifted: System = cet.resolve_local(2024, 3, 31, 2, 30, 0, AmbiguityPolicy::ShiftForward).unwrap();
+    /// assert_eq!(shifted.pretty(), "2024-03-31 03:00:00");
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn resolve_local<T: Time>(&self, year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32, policy: AmbiguityPolicy) -> Result<T, TimeError> {
+        let invalid = |field, value: u32| TimeError::InvalidField { field, value: value as i64 };
+        let date = NaiveDate::from_ymd_opt(year, month, day).ok_or(invalid("date", day))?;
+        let time = NaiveTime::from_hms_opt(hour, minute, second).ok_or(match (hour, minute) {
+            (24.., _) => invalid("hour", hour),
+            (_, 60..) => invalid("minute", minute),
+            _ => invalid("second", second),
+        })?;
+        self.resolve_wall_clock(date.and_time(time), policy)
+    }
+
+    /// Like `resolve_local`, from a chrono `NaiveDateTime`
+    pub fn resolve_wall_clock<T: Time>(&self, wall: NaiveDateTime, policy: AmbiguityPolicy) -> Result<T, TimeError> {
+        let local = wall.and_utc().timestamp();
+        let with_std = local - self.standard_offset as i64;
+        let with_dst = local - self.dst_offset as i64;
+        let (earlier, later) = (with_std.min(with_dst), with_std.max(with_dst));
+        let std_ok = !self.is_dst_unix(with_std);
+        let dst_ok = self.is_dst_unix(with_dst);
+
+        let unix = match (std_ok, dst_ok) {
+            (true, false) => with_std,
+            (false, true) => with_dst,
+            _ if with_std == with_dst => with_std,
+            (true, true) => match policy {
+                AmbiguityPolicy::Reject => return Err(TimeError::AmbiguousLocalTime { earlier, later }),
+                AmbiguityPolicy::Earlier => earlier,
+                AmbiguityPolicy::Later | AmbiguityPolicy::ShiftForward => later,
+            },
+            (false, false) => match policy {
+                AmbiguityPolicy::Reject => return Err(TimeError::SkippedLocalTime { earlier, later }),
+                AmbiguityPolicy::Earlier => earlier,
+                AmbiguityPolicy::Later => later,
+                // the transition is the first instant whose offset no longer matches the earlier reading
+                AmbiguityPolicy::ShiftForward => {
+                    let (start, end) = self.transitions(wall.year()).unwrap_or((later, later));
+                    [start, end].into_iter().find(|t| (earlier..=later).contains(t)).unwrap_or(later)
+                }
+            },
+        };
+
+        let ms = (unix + OFFSET_1601 as i64) * 1000;
+        let raw = u64::try_from(ms).map_err(|_| TimeError::Overflow)?;
+        let utc = T::from_epoch_offset(raw, 0);
+        Ok(self.localize(&utc))
+    }
+}
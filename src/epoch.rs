@@ -0,0 +1,522 @@
+use crate::{ParseError, System, Time, TimeError, MAGIC_MAC_OS, MAGIC_MAC_OS_CFA, MAGIC_SAS_4GL, OFFSET_1601, REF_TIME_1970};
+use chrono::{DateTime, Datelike};
+use core::fmt::Display;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub const UNIX: &str = "1970-01-01 00:00:00";
+pub const WINDOWS_NT: &str = "1601-01-01 00:00:00";
+pub const WEBKIT: &str = "1601-01-01 00:00:00";
+pub const MAC_OS: &str = "1904-01-01 00:00:00";
+pub const MAC_OS_CFA: &str = "2001-01-01 00:00:00";
+pub const SAS_4GL: &str = "1960-01-01 00:00:00";
+pub const NTP: &str = "1900-01-01 00:00:00";
+
+/// The start of `epoch` as a time struct of choice, in UTC
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, TimeDiff, epoch::{self, Epoch}};
+/// assert_eq!(epoch::of::<System>(Epoch::MacOs).pretty(), "1904-01-01 00:00:00");
+/// let x = System::strptime("2024-01-05 14:46:29", "%Y-%m-%d %H:%M:%S");
+/// assert_eq!(x.unix(), x.diff(&epoch::unix::<System>()) as i64);
+/// ```
+pub fn of<T: Time>(epoch: Epoch) -> T {
+    // every epoch is on or after 1601, so this never goes negative
+    T::from_epoch(((OFFSET_1601 as i64 + epoch.unix_offset()) * 1000) as u64)
+}
+
+/// The Unix epoch, `1970-01-01 00:00:00` - see `UNIX`
+pub fn unix<T: Time>() -> T {
+    of(Epoch::Unix)
+}
+
+/// The Windows NT epoch, `1601-01-01 00:00:00` - see `WINDOWS_NT`
+pub fn windows_nt<T: Time>() -> T {
+    of(Epoch::Windows)
+}
+
+/// The WebKit epoch, `1601-01-01 00:00:00` - see `WEBKIT`
+pub fn webkit<T: Time>() -> T {
+    of(Epoch::WebKit)
+}
+
+/// The Mac OS epoch, `1904-01-01 00:00:00` - see `MAC_OS`
+pub fn mac_os<T: Time>() -> T {
+    of(Epoch::MacOs)
+}
+
+/// The Mac OS Absolute epoch, `2001-01-01 00:00:00` - see `MAC_OS_CFA`
+pub fn mac_os_cfa<T: Time>() -> T {
+    of(Epoch::MacOsCfa)
+}
+
+/// The SAS 4GL epoch, `1960-01-01 00:00:00` - see `SAS_4GL`
+pub fn sas_4gl<T: Time>() -> T {
+    of(Epoch::Sas4gl)
+}
+
+/// The NTP epoch, `1900-01-01 00:00:00` - see `NTP`
+pub fn ntp<T: Time>() -> T {
+    of(Epoch::Ntp)
+}
+
+/// The epochs this crate knows about
+///
+/// `Windows` and `WebKit` share an epoch (1601) but are kept apart, as they are conventionally counted in different units
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Epoch {
+    /// 1970-01-01
+    Unix,
+    /// 1601-01-01, usually counted in 100ns ticks
+    Windows,
+    /// 1601-01-01, usually counted in microseconds
+    WebKit,
+    /// 1904-01-01
+    MacOs,
+    /// 2001-01-01 (Mac OS Absolute time, `CFAbsoluteTime`)
+    MacOsCfa,
+    /// 1960-01-01
+    Sas4gl,
+    /// 1900-01-01
+    Ntp,
+}
+
+impl Epoch {
+    /// every epoch, in rough order of how common it is
+    pub const ALL: [Epoch; 7] = [
+        Epoch::Unix,
+        Epoch::Windows,
+        Epoch::WebKit,
+        Epoch::MacOs,
+        Epoch::MacOsCfa,
+        Epoch::Ntp,
+        Epoch::Sas4gl,
+    ];
+
+    /// seconds from 1970-01-01 to this epoch (negative for earlier epochs)
+    pub fn unix_offset(&self) -> i64 {
+        match self {
+            Epoch::Unix => 0,
+            Epoch::Windows | Epoch::WebKit => -(OFFSET_1601 as i64),
+            Epoch::MacOs => -MAGIC_MAC_OS,
+            Epoch::MacOsCfa => MAGIC_MAC_OS_CFA,
+            Epoch::Sas4gl => -MAGIC_SAS_4GL,
+            Epoch::Ntp => -(REF_TIME_1970 as i64),
+        }
+    }
+
+    /// the start of this epoch as a time struct of choice - the same as `epoch::of`
+    pub fn start<T: Time>(&self) -> T {
+        of(*self)
+    }
+
+    /// the short lowercase name of the epoch, eg `unix` or `macos-cfa`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Epoch::Unix => "unix",
+            Epoch::Windows => "windows",
+            Epoch::WebKit => "webkit",
+            Epoch::MacOs => "macos",
+            Epoch::MacOsCfa => "macos-cfa",
+            Epoch::Sas4gl => "sas4gl",
+            Epoch::Ntp => "ntp",
+        }
+    }
+
+    /// Decode `value`, counted in `unit`s since this epoch, into a time struct of choice
+    ///
+    /// Rounds towards negative infinity to whole milliseconds. Errors with `TimeError::Overflow` for values before 1601 or beyond chrono's range
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, epoch::{Epoch, Unit}};
+    /// let x: System = Epoch::WebKit.decode(13127702400000000, Unit::Microseconds).unwrap();
+    /// assert_eq!(x.pretty(), "2017-01-01 00:00:00");
+    /// assert!(Epoch::Unix.decode::<System>(-20_000_000_000, Unit::Seconds).is_err());
+    /// ```
+    pub fn decode<T: Time>(&self, value: i64, unit: Unit) -> Result<T, TimeError> {
+        self.decode_wide(value as i128, unit)
+    }
+
+    /// internal only - `decode` for values that may not fit in an `i64`, such as nanoseconds since 1601
+    pub(crate) fn decode_wide<T: Time>(&self, value: i128, unit: Unit) -> Result<T, TimeError> {
+        let ms = value.checked_mul(1000).ok_or(TimeError::Overflow)?.div_euclid(unit.per_second() as i128);
+        let unix_ms = ms.checked_add(self.unix_offset() as i128 * 1000).ok_or(TimeError::Overflow)?;
+        let unix_ms = i64::try_from(unix_ms).map_err(|_| TimeError::Overflow)?;
+        DateTime::from_timestamp_millis(unix_ms).ok_or(TimeError::Overflow)?;
+        let ms_1601 = u64::try_from(unix_ms as i128 + OFFSET_1601 as i128 * 1000).map_err(|_| TimeError::Overflow)?;
+        Ok(T::from_epoch(ms_1601))
+    }
+
+    /// Encode `time` as a count of `unit`s since this epoch - the inverse of `decode`. Rounds towards negative infinity for units
+    /// coarser than a millisecond, and is negative for times before the epoch
+    ///
+    /// The result is an `i128`, as nanoseconds since 1601 run past `i64::MAX` in the year 2185
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime, epoch::{Epoch, Unit}};
+    /// let x = 1483228800u32.unix::<System>();
+    /// assert_eq!(Epoch::WebKit.encode(&x, Unit::Microseconds), 13127702400000000);
+    /// assert_eq!(Epoch::MacOsCfa.encode(&x, Unit::Seconds), 504921600);
+    /// ```
+    pub fn encode<T: Time + ?Sized>(&self, time: &T, unit: Unit) -> i128 {
+        let ms = time.unix_ms() as i128 - self.unix_offset() as i128 * 1000;
+        (ms * unit.per_second() as i128).div_euclid(1000)
+    }
+
+    /// The epoch with the short name `name`, as given by `name` - `None` if there isn't one
+    pub fn from_name(name: &str) -> Option<Epoch> {
+        Epoch::ALL.into_iter().find(|epoch| epoch.name() == name)
+    }
+}
+
+impl Display for Epoch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// The units a timestamp can be counted in
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Unit {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    /// 100 nanosecond ticks, as used by Windows `FILETIME`
+    Ticks100ns,
+    Nanoseconds,
+}
+
+impl Unit {
+    /// every unit, coarsest first
+    pub const ALL: [Unit; 5] = [Unit::Seconds, Unit::Milliseconds, Unit::Microseconds, Unit::Ticks100ns, Unit::Nanoseconds];
+
+    /// how many of this unit make up a second
+    pub fn per_second(&self) -> i64 {
+        match self {
+            Unit::Seconds => 1,
+            Unit::Milliseconds => 1_000,
+            Unit::Microseconds => 1_000_000,
+            Unit::Ticks100ns => 10_000_000,
+            Unit::Nanoseconds => 1_000_000_000,
+        }
+    }
+
+    /// the short name of the unit, eg `s` or `us`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Unit::Seconds => "s",
+            Unit::Milliseconds => "ms",
+            Unit::Microseconds => "us",
+            Unit::Ticks100ns => "100ns",
+            Unit::Nanoseconds => "ns",
+        }
+    }
+
+    /// The unit with the short name `name`, as given by `name` - `None` if there isn't one
+    pub fn from_name(name: &str) -> Option<Unit> {
+        Unit::ALL.into_iter().find(|unit| unit.name() == name)
+    }
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// Fixed width timestamp representations that run out, for auditing rollover risk - see `Time::fits_in`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimestampKind {
+    /// unsigned 32 bit seconds since 1970, from 1970 to 2106-02-07 06:28:15
+    U32Unix,
+    /// signed 32 bit seconds since 1970 (`time_t` on older systems), from 1901-12-13 20:45:52 to 2038-01-19 03:14:07
+    I32Unix,
+    /// unsigned 32 bit seconds since 1900, the first NTP era, from 1900 to 2036-02-07 06:28:15
+    NtpEra0,
+    /// MS-DOS/FAT date and time, in local time to 2 seconds, from 1980 to 2107-12-31 23:59:59 (stored as 23:59:58)
+    DosDate,
+    /// signed 64 bit 100ns ticks since 1601 (Windows `FILETIME`), from 1601 to 30828-09-14 02:48:05
+    FileTime64,
+}
+
+impl TimestampKind {
+    /// every representation, soonest to run out first
+    pub const ALL: [TimestampKind; 5] = [
+        TimestampKind::NtpEra0,
+        TimestampKind::I32Unix,
+        TimestampKind::U32Unix,
+        TimestampKind::DosDate,
+        TimestampKind::FileTime64,
+    ];
+
+    /// the first and last whole seconds this representation holds, in unix seconds (wall clock seconds, for `DosDate`)
+    pub fn unix_range(&self) -> (i64, i64) {
+        match self {
+            TimestampKind::U32Unix => (0, u32::MAX as i64),
+            TimestampKind::I32Unix => (i32::MIN as i64, i32::MAX as i64),
+            TimestampKind::NtpEra0 => (-(REF_TIME_1970 as i64), u32::MAX as i64 - REF_TIME_1970 as i64),
+            // 1980-01-01 00:00:00 to 2107-12-31 23:59:59
+            TimestampKind::DosDate => (315_532_800, 4_354_819_199),
+            TimestampKind::FileTime64 => (-(OFFSET_1601 as i64), i64::MAX / 10_000_000 - OFFSET_1601 as i64),
+        }
+    }
+
+    /// The last whole second this representation holds - one second later it overflows (or wraps around). `DosDate` is in local time,
+    /// so its rollover is given as a wall clock, in UTC
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Time, epoch::TimestampKind};
+    /// assert_eq!(TimestampKind::I32Unix.next_rollover().rfc3339(), "2038-01-19T03:14:07.000Z");
+    /// assert_eq!(TimestampKind::NtpEra0.next_rollover().pretty(), "2036-02-07 06:28:15");
+    /// ```
+    pub fn next_rollover(&self) -> System {
+        System::from_epoch(((self.unix_range().1 + OFFSET_1601 as i64) * 1000) as u64)
+    }
+
+    /// the short name of the representation, eg `i32-unix`
+    pub fn name(&self) -> &'static str {
+        match self {
+            TimestampKind::U32Unix => "u32-unix",
+            TimestampKind::I32Unix => "i32-unix",
+            TimestampKind::NtpEra0 => "ntp-era-0",
+            TimestampKind::DosDate => "dos-date",
+            TimestampKind::FileTime64 => "filetime-64",
+        }
+    }
+}
+
+impl Display for TimestampKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+/// One possible reading of a mystery timestamp, from `identify_timestamp`
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochGuess {
+    pub epoch: Epoch,
+    pub unit: Unit,
+    /// the value decoded with this epoch and unit
+    pub time: System,
+    /// how plausible the reading is, from 1.0 (inside the window) falling towards 0.0 the further outside it the date is
+    pub score: f64,
+}
+
+/// the epoch and unit combinations worth trying, in tie-break order
+const CANDIDATES: [(Epoch, Unit); 10] = [
+    (Epoch::Unix, Unit::Seconds),
+    (Epoch::Unix, Unit::Milliseconds),
+    (Epoch::Unix, Unit::Microseconds),
+    (Epoch::Unix, Unit::Nanoseconds),
+    (Epoch::Windows, Unit::Ticks100ns),
+    (Epoch::WebKit, Unit::Microseconds),
+    (Epoch::MacOs, Unit::Seconds),
+    (Epoch::MacOsCfa, Unit::Seconds),
+    (Epoch::Ntp, Unit::Seconds),
+    (Epoch::Sas4gl, Unit::Seconds),
+];
+
+/// Guess which epoch and unit a mystery integer uses, assuming it is probably between 1990 and 2040
+///
+/// Every reading that decodes to a representable date is returned, most plausible first
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Time, epoch::{identify_timestamp, Epoch, Unit}};
+/// let best = &identify_timestamp(1700000000)[0];
+/// assert_eq!((best.epoch, best.unit), (Epoch::Unix, Unit::Seconds));
+/// assert_eq!(best.time.strftime("%Y"), "2023");
+/// ```
+pub fn identify_timestamp(value: i64) -> Vec<EpochGuess> {
+    identify_timestamp_within(value, 1990, 2040)
+}
+
+/// Like `identify_timestamp`, with the plausible window given as a range of years (inclusive)
+pub fn identify_timestamp_within(value: i64, start_year: i32, end_year: i32) -> Vec<EpochGuess> {
+    let (start_year, end_year) = (start_year.min(end_year), start_year.max(end_year));
+    let mut guesses: Vec<EpochGuess> = CANDIDATES
+        .iter()
+        .filter_map(|&(epoch, unit)| {
+            let time: System = epoch.decode(value, unit).ok()?;
+            let year = time.wall_clock().year();
+            let outside = if year < start_year {
+                start_year - year
+            } else if year > end_year {
+                year - end_year
+            } else {
+                0
+            };
+            Some(EpochGuess {
+                epoch,
+                unit,
+                time,
+                score: 1.0 / (1.0 + outside as f64 / 10.0),
+            })
+        })
+        .collect();
+    // a stable sort keeps the candidate order for equal scores
+    guesses.sort_by(|a, b| b.score.total_cmp(&a.score));
+    guesses
+}
+
+/// Format the guesses from `identify_timestamp` as a table, one reading per line
+///
+/// # Examples
+/// ```rust
+/// use thetime::epoch::identify_and_pretty;
+/// println!("{}", identify_and_pretty(3787310789));
+/// ```
+pub fn identify_and_pretty(value: i64) -> String {
+    let mut table = format!("{:<6} {:<10} {:<6} {}\n", "score", "epoch", "unit", "time");
+    for guess in identify_timestamp(value) {
+        table += &format!("{:<6.2} {:<10} {:<6} {}\n", guess.score, guess.epoch, guess.unit, guess.time.pretty());
+    }
+    table
+}
+
+/// internal only - the digits of an integer starting at `pos`, with an optional 0x or 0b prefix and `,`/`_` separators (and spaces, unless
+/// `lenient`). Strict parsing must use everything before `end`; lenient parsing stops at the first byte that can't continue the number
+fn parse_digits(input: &str, mut pos: usize, end: usize, lenient: bool) -> Result<u64, ParseError> {
+    let bytes = input.as_bytes();
+    let is_digit = |i: usize, radix: u32| i < end && (bytes[i] as char).is_digit(radix);
+    let mut radix = 10;
+    if bytes[pos] == b'0' && pos + 1 < end {
+        let prefixed = match bytes[pos + 1] {
+            b'x' | b'X' => 16,
+            b'b' | b'B' => 2,
+            _ => 10,
+        };
+        if prefixed != 10 && (!lenient || is_digit(pos + 2, prefixed)) {
+            radix = prefixed;
+            pos += 2;
+        }
+    }
+
+    let mut value: Option<u64> = None;
+    while pos < end {
+        let b = bytes[pos];
+        if let Some(digit) = (b as char).to_digit(radix) {
+            value = Some(
+                value
+                    .unwrap_or(0)
+                    .checked_mul(radix as u64)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or_else(|| ParseError::invalid(input, pos, "a number that fits in 64 bits"))?,
+            );
+        } else if matches!(b, b',' | b'_') || (b == b' ' && !lenient) {
+            // a lenient number only continues past a separator onto another digit, so "1,2" is one number but "1, 2" is not
+            if lenient && (value.is_none() || !is_digit(pos + 1, radix)) {
+                break;
+            }
+        } else if lenient {
+            break;
+        } else {
+            return Err(ParseError::invalid(input, pos, "a digit"));
+        }
+        pos += 1;
+    }
+    value.ok_or_else(|| ParseError::invalid(input, pos, "a digit"))
+}
+
+/// Parse a timestamp integer as it might arrive from a log or a spreadsheet, ready for `identify_timestamp` or `Epoch::decode`
+///
+/// Commas, underscores and spaces are ignored (as in "1,483,228,800"), a `0x` prefix reads hex and a `0b` prefix binary, and
+/// surrounding whitespace is trimmed. Anything else is rejected with its byte position - see `parse_int_timestamp_lenient` to pick the number
+/// out of surrounding text
+///
+/// # Examples
+/// ```rust
+/// use thetime::epoch::parse_int_timestamp;
+/// assert_eq!(parse_int_timestamp("1,483,228,800"), Ok(1483228800));
+/// assert_eq!(parse_int_timestamp("0x58643F80"), Ok(1482964864));
+/// assert!(parse_int_timestamp("1483228800s").is_err());
+/// ```
+pub fn parse_int_timestamp(s: &str) -> Result<u64, ParseError> {
+    let start = s.len() - s.trim_start().len();
+    let end = s.trim_end().len();
+    if start >= end {
+        return Err(ParseError::Empty);
+    }
+    parse_digits(s, start, end, false)
+}
+
+/// Like `parse_int_timestamp`, but taking the first integer in `s` and ignoring the text around it, as in "ts=1700000000;"
+///
+/// Separators only count between digits, and spaces end the number
+///
+/// # Examples
+/// ```rust
+/// use thetime::epoch::parse_int_timestamp_lenient;
+/// assert_eq!(parse_int_timestamp_lenient("ts=1700000000;"), Ok(1700000000));
+/// assert_eq!(parse_int_timestamp_lenient("created 1,700,000,000, updated 1,700,000,100"), Ok(1700000000));
+/// assert!(parse_int_timestamp_lenient("no digits").is_err());
+/// ```
+pub fn parse_int_timestamp_lenient(s: &str) -> Result<u64, ParseError> {
+    if s.trim().is_empty() {
+        return Err(ParseError::Empty);
+    }
+    match s.bytes().position(|b| b.is_ascii_digit()) {
+        Some(start) => parse_digits(s, start, s.len(), true),
+        None => Err(ParseError::invalid(s, s.len(), "a number")),
+    }
+}
+
+/// Format `time` as a self-describing timestamp: the epoch and unit it is counted in, then the count - as in "unix-s:1483228800" or
+/// "webkit-us:13127702400000000". See `Time::tagged`
+pub fn tagged<T: Time + ?Sized>(time: &T, epoch: Epoch, unit: Unit) -> String {
+    format!("{}-{}:{}", epoch, unit, epoch.encode(time, unit))
+}
+
+/// internal only - what a tag may be, for errors from `parse_tagged`
+fn supported_tags() -> String {
+    let epochs: Vec<&str> = Epoch::ALL.iter().map(Epoch::name).collect();
+    let units: Vec<&str> = Unit::ALL.iter().map(Unit::name).collect();
+    format!("an epoch ({}), '-' and a unit ({})", epochs.join(", "), units.join(", "))
+}
+
+/// Parse a self-describing timestamp written by `tagged`, into a time struct of choice (in UTC, as the tag carries no offset)
+///
+/// Any epoch and unit known to `Epoch` and `Unit` is read; an unknown one is rejected with the list of supported ones rather than guessed
+/// at. The count is a plain decimal integer, with a '-' for times before the epoch. Surrounding whitespace is trimmed. Errors with
+/// `ParseError::OutOfRange` for counts the time struct can't hold
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, epoch::parse_tagged};
+/// assert_eq!(parse_tagged::<System>("webkit-us:13127702400000000").unwrap().pretty(), "2017-01-01 00:00:00");
+/// assert_eq!(parse_tagged::<System>("unix-s:-1").unwrap().pretty(), "1969-12-31 23:59:59");
+/// assert!(parse_tagged::<System>("unix-fortnights:3").is_err());
+/// ```
+pub fn parse_tagged<T: Time>(s: &str) -> Result<T, ParseError> {
+    let start = s.len() - s.trim_start().len();
+    let end = s.trim_end().len();
+    if start >= end {
+        return Err(ParseError::Empty);
+    }
+    let colon = match s[start..end].find(':') {
+        Some(colon) => start + colon,
+        None => return Err(ParseError::invalid(s, end, "':' after the tag")),
+    };
+    let (epoch, unit) = s[start..colon].rsplit_once('-').ok_or_else(|| ParseError::invalid(s, start, supported_tags()))?;
+    let epoch = Epoch::from_name(epoch).ok_or_else(|| ParseError::invalid(s, start, supported_tags()))?;
+    let unit = Unit::from_name(unit).ok_or_else(|| ParseError::invalid(s, colon - unit.len(), supported_tags()))?;
+
+    let digits_start = if s[colon + 1..end].starts_with('-') { colon + 2 } else { colon + 1 };
+    if let Some(bad) = (digits_start..end).find(|&i| !s.as_bytes()[i].is_ascii_digit()) {
+        return Err(ParseError::invalid(s, bad, "a digit"));
+    }
+    if digits_start == end {
+        return Err(ParseError::invalid(s, end, "a digit"));
+    }
+    let saturated = if digits_start == colon + 2 { i64::MIN } else { i64::MAX };
+    let out_of_range = |value: i64| ParseError::OutOfRange { field: "timestamp", value };
+    let value: i128 = s[colon + 1..end].parse().map_err(|_| out_of_range(saturated))?;
+    epoch.decode_wide(value, unit).map_err(|_| out_of_range(i64::try_from(value).unwrap_or(saturated)))
+}
@@ -0,0 +1,255 @@
+use chrono::Weekday;
+use core::fmt::Display;
+
+/// An error returned by the fallible parsers in this crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// the input was empty (or only whitespace)
+    Empty,
+    /// the input did not have the expected shape - `position` is the byte offset at which parsing failed
+    Invalid {
+        input: String,
+        position: usize,
+        expected: String,
+    },
+    /// a field parsed, but was outside of its allowed range
+    OutOfRange { field: &'static str, value: i64 },
+    /// the input named a weekday, but the date falls on another - only with `ParseOptions::verify_weekday`
+    WeekdayMismatch { named: Weekday, computed: Weekday },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty input"),
+            ParseError::Invalid {
+                input,
+                position,
+                expected,
+            } => write!(f, "invalid input {:?} at position {}: expected {}", input, position, expected),
+            ParseError::OutOfRange { field, value } => write!(f, "{} out of range: {}", field, value),
+            ParseError::WeekdayMismatch { named, computed } => write!(f, "weekday {} does not match the date, a {}", named, computed),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// internal only - shorthand for building a `ParseError::Invalid`
+    pub(crate) fn invalid<T: ToString>(input: &str, position: usize, expected: T) -> Self {
+        ParseError::Invalid {
+            input: input.to_string(),
+            position,
+            expected: expected.to_string(),
+        }
+    }
+}
+
+/// An error returned when a time cannot be represented or constructed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeError {
+    /// the value does not fit in the target representation
+    Overflow,
+    /// a field was outside of its allowed range, or inconsistent with the others
+    InvalidField { field: &'static str, value: i64 },
+    /// a local time falls in a daylight saving gap - `earlier` and `later` are the instants (unix seconds) it would be with each offset
+    SkippedLocalTime { earlier: i64, later: i64 },
+    /// a local time occurs twice because of a daylight saving fold - `earlier` and `later` are the two instants, in unix seconds
+    AmbiguousLocalTime { earlier: i64, later: i64 },
+}
+
+impl Display for TimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TimeError::Overflow => write!(f, "value does not fit in the target representation"),
+            TimeError::InvalidField { field, value } => write!(f, "invalid {}: {}", field, value),
+            TimeError::SkippedLocalTime { earlier, later } => {
+                write!(f, "local time skipped by a daylight saving transition (candidates {} and {})", earlier, later)
+            }
+            TimeError::AmbiguousLocalTime { earlier, later } => {
+                write!(f, "local time occurs twice because of a daylight saving transition (candidates {} and {})", earlier, later)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimeError {}
+
+/// An error returned when a strftime-style format string is invalid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// the format string contained an unknown or malformed specifier
+    Invalid(String),
+    /// `FormatString::parse_untrusted` refused the format string - `position` is the byte offset of the problem
+    Rejected {
+        format: String,
+        position: usize,
+        reason: &'static str,
+    },
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FormatError::Invalid(format) => write!(f, "invalid format string {:?}", format),
+            FormatError::Rejected { format, position, reason } => {
+                write!(f, "rejected format string {:?} at position {}: {}", format, position, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// An error returned by `Time::try_strptime` and the `try_` parsers of `StrTime`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTimeError {
+    /// the format string has an unknown or malformed specifier, so no input could match it
+    InvalidFormat { input: String, format: String },
+    /// the input does not match the format - `reason` says how
+    Mismatch { input: String, format: String, reason: String },
+}
+
+impl Display for ParseTimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseTimeError::InvalidFormat { input, format } => write!(f, "invalid format string {:?} (parsing {:?})", format, input),
+            ParseTimeError::Mismatch { input, format, reason } => {
+                write!(f, "input {:?} does not match format {:?}: {}", input, format, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseTimeError {}
+
+/// An error yielded by `merge_sorted_checked` when an input stream is not sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// the item at `position` (counting from 0) of stream `source` is earlier than the item before it
+    Unsorted { source: usize, position: usize },
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MergeError::Unsorted { source, position } => write!(f, "stream {} is not sorted at item {}", source, position),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// An error returned by `eval_time_expr` and `eval_duration_expr`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    /// the expression (or the time at its start) could not be parsed - positions are byte offsets into the whole expression
+    Parse(ParseError),
+    /// the result is before `1601-01-01 00:00:00` or after `9999-12-31 23:59:59.999`
+    OutOfRange,
+    /// a duration expression added up to less than zero
+    Negative { ms: i64 },
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ExprError::Parse(e) => write!(f, "{}", e),
+            ExprError::OutOfRange => write!(f, "result out of range"),
+            ExprError::Negative { ms } => write!(f, "negative duration: {}ms", ms),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExprError::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for ExprError {
+    fn from(e: ParseError) -> Self {
+        ExprError::Parse(e)
+    }
+}
+
+/// An error returned by `Time::add_months_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonthRollError {
+    /// the day of the month doesn't exist in the target month, under `MonthRollPolicy::Reject`
+    DayMissing { year: i32, month: u8, day: u8 },
+    /// the result is before the year 1601 or after the year 9999
+    OutOfRange,
+}
+
+impl Display for MonthRollError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MonthRollError::DayMissing { year, month, day } => write!(f, "{:04}-{:02} has no day {}", year, month, day),
+            MonthRollError::OutOfRange => write!(f, "result out of range"),
+        }
+    }
+}
+
+impl std::error::Error for MonthRollError {}
+
+/// An error returned by `TimeBudget::consume` when more time is asked for than the budget has left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExceeded {
+    /// the time asked for
+    pub requested: core::time::Duration,
+    /// the time the budget had left
+    pub available: core::time::Duration,
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "time budget exceeded: {}ms requested, {}ms available", self.requested.as_millis(), self.available.as_millis())
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// A timestamp that breaks a strictly increasing sequence, from `MonotonicValidator::check` - times are Unix milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonotonicViolation {
+    /// the time is earlier than the latest time seen, by more than the tolerance
+    Regression { prev: i64, cur: i64, delta: u64 },
+    /// the time is exactly the latest time seen
+    Duplicate { at: i64 },
+}
+
+impl Display for MonotonicViolation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MonotonicViolation::Regression { prev, cur, delta } => {
+                write!(f, "time went backwards by {}ms (from {} to {} unix ms)", delta, prev, cur)
+            }
+            MonotonicViolation::Duplicate { at } => write!(f, "duplicate time {} unix ms", at),
+        }
+    }
+}
+
+impl std::error::Error for MonotonicViolation {}
+
+/// A timestamp refused by `Hlc::update` for being too far ahead to trust - past `Time::far_future`, or more than the clock's maximum
+/// drift ahead of its wall clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HlcDrift {
+    /// the timestamp received
+    pub received: crate::HlcTimestamp,
+    /// the latest wall time the clock would accept, in milliseconds since 1601
+    pub limit: u64,
+}
+
+impl Display for HlcDrift {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HLC timestamp {} is ahead of the latest acceptable wall time {:016x}", self.received, self.limit)
+    }
+}
+
+impl std::error::Error for HlcDrift {}
@@ -0,0 +1,151 @@
+use crate::parse::{iso_lenient_bytes, Cursor};
+use crate::{ExprError, ParseError, Time, OFFSET_1601};
+use core::time::Duration;
+
+/// internal only - the length of a unit in milliseconds, for the units `eval_duration_expr` accepts
+fn unit_ms(unit: &str) -> Option<i64> {
+    Some(match unit {
+        "ms" => 1,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1000,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60_000,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000,
+        "d" | "day" | "days" => 86_400_000,
+        "w" | "week" | "weeks" => 604_800_000,
+        _ => return None,
+    })
+}
+
+/// internal only - skip ASCII whitespace
+fn skip_space(cur: &mut Cursor) {
+    while matches!(cur.peek(), Some(b) if b.is_ascii_whitespace()) {
+        cur.pos += 1;
+    }
+}
+
+/// internal only - one duration term, such as "90m", "2 days" or "1h30m", in milliseconds
+fn term(cur: &mut Cursor) -> Result<i64, ParseError> {
+    let mut total = 0i64;
+    loop {
+        let start = cur.pos;
+        let digits = cur.digit_run();
+        if digits.is_empty() {
+            return Err(cur.error("a number"));
+        }
+        let amount: i64 = digits.parse().map_err(|_| cur.error_at(start, "a smaller number"))?;
+
+        skip_space(cur);
+        let unit_start = cur.pos;
+        while matches!(cur.peek(), Some(b) if b.is_ascii_alphabetic()) {
+            cur.pos += 1;
+        }
+        let unit = cur.slice(unit_start, cur.pos);
+        let ms = unit_ms(unit).ok_or_else(|| cur.error_at(unit_start, "a unit (ms, s, m, h, d or w)"))?;
+        total = amount
+            .checked_mul(ms)
+            .and_then(|x| x.checked_add(total))
+            .ok_or_else(|| cur.error_at(start, "a smaller number"))?;
+
+        if !matches!(cur.peek(), Some(b) if b.is_ascii_digit()) {
+            return Ok(total);
+        }
+    }
+}
+
+/// internal only - a chain of signed duration terms, in milliseconds; the first sign may be left off if `sign_optional`
+fn terms(cur: &mut Cursor, mut sign_optional: bool) -> Result<i64, ParseError> {
+    let mut total = 0i64;
+    loop {
+        skip_space(cur);
+        if cur.is_done() {
+            return Ok(total);
+        }
+        let op = cur.pos;
+        let sign = if cur.eat(b'+') {
+            1
+        } else if cur.eat(b'-') {
+            -1
+        } else if sign_optional {
+            1
+        } else {
+            return Err(cur.error("'+' or '-'"));
+        };
+        sign_optional = false;
+        skip_space(cur);
+        let ms = term(cur)?;
+        total = total.checked_add(sign * ms).ok_or_else(|| cur.error_at(op, "a smaller total"))?;
+    }
+}
+
+/// internal only - where the time literal at the start of `expr` ends: after "now", or at the first '+' or '-' that follows whitespace
+fn literal_end(expr: &[u8], start: usize) -> usize {
+    let rest = &expr[start..];
+    if rest.len() >= 3 && rest[..3].eq_ignore_ascii_case(b"now") && !rest.get(3).is_some_and(|b| b.is_ascii_alphanumeric()) {
+        return start + 3;
+    }
+    (start + 1..expr.len())
+        .find(|i| matches!(expr[*i], b'+' | b'-') && expr[*i - 1].is_ascii_whitespace())
+        .unwrap_or(expr.len())
+}
+
+/// Evaluate a date math expression: a time followed by any number of `+` or `-` duration terms, such as `"2024-01-05 14:00 + 3d - 2h"`
+/// or `"now + 90m"`
+///
+/// The time is `now` (see `Time::now`) or anything `StrTime::strp_iso_lenient` accepts, and keeps its offset. A term is a whole number and
+/// a unit (`ms`, `s`, `m`, `h`, `d`, `w`, or their longer names such as `mins` or `days`), and several can be run together as in `1h30m`.
+/// After a date, operators must follow whitespace, so that they are not mistaken for part of the date. Errors carry the byte position
+/// of the problem within `expr`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{eval_time_expr, System, Time};
+/// let x: System = eval_time_expr("2024-01-05 14:00 + 3d - 2h").unwrap();
+/// assert_eq!(x.pretty(), "2024-01-08 12:00:00");
+/// assert!(eval_time_expr::<System>("now + 90m").unwrap() > System::now());
+/// ```
+pub fn eval_time_expr<T: Time>(expr: &str) -> Result<T, ExprError> {
+    let bytes = expr.as_bytes();
+    let start = bytes.iter().take_while(|b| b.is_ascii_whitespace()).count();
+    if start == bytes.len() {
+        return Err(ParseError::Empty.into());
+    }
+    let end = literal_end(bytes, start);
+    let literal = expr[start..end].trim_end();
+    let base = if literal.eq_ignore_ascii_case("now") {
+        T::now()
+    } else {
+        iso_lenient_bytes::<T>(literal.as_bytes()).map_err(|e| match e {
+            ParseError::Invalid { position, expected, .. } => ParseError::invalid(expr, start + position, expected),
+            other => other,
+        })?
+    };
+
+    let mut cur = Cursor::new(expr);
+    cur.pos = end;
+    let delta = terms(&mut cur, false)?;
+    let raw = (base.raw() as i64).checked_add(delta).filter(|raw| *raw >= 0 && *raw <= crate::FAR_FUTURE_UNIX_MS + OFFSET_1601 as i64 * 1000);
+    match raw {
+        Some(raw) => Ok(T::from_epoch_offset(raw as u64, base.utc_offset()).with_offset_source(base.offset_source())),
+        None => Err(ExprError::OutOfRange),
+    }
+}
+
+/// Evaluate a sum of duration terms, such as `"3d - 2h"` or `"1h30m + 15s"` - see `eval_time_expr` for the units. The first term may
+/// have a sign, and a negative total is an error
+///
+/// # Examples
+/// ```rust
+/// use thetime::{eval_duration_expr, ExprError};
+/// use core::time::Duration;
+/// assert_eq!(eval_duration_expr("3d - 2h").unwrap(), Duration::from_secs(70 * 3600));
+/// assert_eq!(eval_duration_expr("1h - 2h"), Err(ExprError::Negative { ms: -3_600_000 }));
+/// ```
+pub fn eval_duration_expr(expr: &str) -> Result<Duration, ExprError> {
+    if expr.trim().is_empty() {
+        return Err(ParseError::Empty.into());
+    }
+    let ms = terms(&mut Cursor::new(expr), true)?;
+    if ms < 0 {
+        return Err(ExprError::Negative { ms });
+    }
+    Ok(Duration::from_millis(ms as u64))
+}
@@ -0,0 +1,302 @@
+use crate::parse::Cursor;
+use crate::{Ntp, OffsetSource, ParseError, ParseTimeError, PrecisionLoss, System, Time, TimeDiff, TimeError, OFFSET_1601};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use core::fmt::Display;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A time that can also be before 1601, for historical dates (proleptic Gregorian calendar, no Julian conversion)
+///
+/// `ms` is the time as signed milliseconds since `1601-01-01 00:00:00`, so dates back to the start of chrono's range (about 262000 BC) can be held.
+///
+/// The crate-internal `raw()` value is unsigned, so helpers built on it (`cast`, `epoch`, and the Windows/WebKit conversions) clamp pre-1601 values to 1601 -
+/// the comparisons, differences, formatting and arithmetic below are all signed
+///
+/// # Examples
+/// ```rust
+/// use thetime::{ExtendedTime, Time};
+/// let landfall = ExtendedTime::strptime("1492-10-12 00:00:00", "%Y-%m-%d %H:%M:%S");
+/// assert!(landfall.unix() < 0);
+/// assert_eq!(landfall.pretty(), "1492-10-12 00:00:00");
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtendedTime {
+    ms: i64,
+    utc_offset: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    offset_source: OffsetSource,
+}
+
+impl ExtendedTime {
+    /// internal only - the fields compared by `Eq` and `Ord`, leaving out the offset source
+    fn key(&self) -> (i64, i32) {
+        (self.ms, self.utc_offset)
+    }
+}
+
+impl PartialEq for ExtendedTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ExtendedTime {}
+
+impl PartialOrd for ExtendedTime {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ExtendedTime {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl core::hash::Hash for ExtendedTime {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl Display for ExtendedTime {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.pretty())
+    }
+}
+
+impl ExtendedTime {
+    /// Build from signed milliseconds since `1601-01-01 00:00:00` and an offset in seconds - `ms` is clamped to chrono's range
+    pub fn from_ms_1601(ms: i64, utc_offset: i32) -> ExtendedTime {
+        let to_1601 = |unix_ms: i64| unix_ms + OFFSET_1601 as i64 * 1000;
+        let (min, max) = (to_1601(DateTime::<chrono::Utc>::MIN_UTC.timestamp_millis()), to_1601(DateTime::<chrono::Utc>::MAX_UTC.timestamp_millis()));
+        ExtendedTime {
+            ms: ms.clamp(min, max),
+            utc_offset: crate::clamp_offset(utc_offset),
+            offset_source: OffsetSource::Explicit,
+        }
+    }
+
+    /// The signed milliseconds since `1601-01-01 00:00:00`
+    pub fn ms_1601(&self) -> i64 {
+        self.ms
+    }
+
+    /// Parse a date with an optional time and an optional era, eg `1492-10-12`, `0044-03-15 12:00:00 BC`, or `-0043-03-15`
+    ///
+    /// `BC`/`BCE` years count back from 1 BC, which is the year 0 (astronomical numbering, as used by a leading `-`). `AD`/`CE` are accepted and ignored.
+    /// The time is UTC
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{ExtendedTime, Time};
+    /// let ides = ExtendedTime::parse_era("44-03-15 BC").unwrap();
+    /// assert_eq!(ides, ExtendedTime::parse_era("-0043-03-15").unwrap());
+    /// assert_eq!(ides.pretty_era(), "0044-03-15 00:00:00 BC");
+    /// ```
+    pub fn parse_era(input: &str) -> Result<ExtendedTime, ParseError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let mut cur = Cursor::new(trimmed);
+
+        let negative = cur.eat(b'-');
+        let year_pos = cur.pos;
+        let year_digits = cur.digit_run();
+        if year_digits.is_empty() {
+            return Err(cur.error("a year"));
+        }
+        let mut year: i64 = year_digits
+            .parse()
+            .map_err(|_| ParseError::invalid(trimmed, year_pos, "a year"))?;
+        cur.expect(b'-', "'-'")?;
+        let month = cur.field(2, 1, 12, "month 01-12")?;
+        cur.expect(b'-', "'-'")?;
+        let day_pos = cur.pos;
+        let day = cur.field(2, 1, 31, "day 01-31")?;
+
+        let (mut hour, mut minute, mut second) = (0, 0, 0);
+        if cur.peek() == Some(b'T') || (cur.peek() == Some(b' ') && matches!(cur.peek_at(1), Some(b) if b.is_ascii_digit())) {
+            cur.pos += 1;
+            hour = cur.field(2, 0, 23, "hour 00-23")?;
+            cur.expect(b':', "':'")?;
+            minute = cur.field(2, 0, 59, "minute 00-59")?;
+            if cur.eat(b':') {
+                second = cur.field(2, 0, 59, "second 00-59")?;
+            }
+        }
+
+        while cur.eat(b' ') {}
+        let era_pos = cur.pos;
+        let era = &trimmed[cur.pos..];
+        match era.to_ascii_uppercase().as_str() {
+            "" => {}
+            "AD" | "CE" if !negative => {}
+            "BC" | "BCE" if !negative => {
+                if year == 0 {
+                    return Err(ParseError::invalid(trimmed, year_pos, "a BC year of at least 1"));
+                }
+                year = 1 - year;
+            }
+            _ => return Err(ParseError::invalid(trimmed, era_pos, "end of input, or an era (AD, CE, BC, BCE) without a leading '-'")),
+        }
+        if negative {
+            year = -year;
+        }
+
+        let year = i32::try_from(year).map_err(|_| ParseError::OutOfRange { field: "year", value: year })?;
+        let date = NaiveDate::from_ymd_opt(year, month, day)
+            .ok_or_else(|| ParseError::invalid(trimmed, day_pos, "a day that exists in the month"))?;
+        let wall = date.and_time(NaiveTime::from_hms_opt(hour, minute, second).unwrap());
+        Ok(ExtendedTime::from_wall_clock(wall, 0))
+    }
+
+    /// Pretty print with an era instead of a signed year, eg `1492-10-12 00:00:00 AD` or `0044-03-15 00:00:00 BC`
+    pub fn pretty_era(&self) -> String {
+        let wall = self.wall_clock();
+        let (year, era) = if wall.year() <= 0 { (1 - wall.year(), "BC") } else { (wall.year(), "AD") };
+        format!("{:04}-{} {}", year, wall.format("%m-%d %H:%M:%S"), era)
+    }
+}
+
+impl TimeDiff for ExtendedTime {
+    fn diff<T: Time>(&self, other: &T) -> u64 {
+        self.unix_ms().abs_diff(other.unix_ms()) / 1000
+    }
+
+    fn diff_ms<T: Time>(&self, other: &T) -> u64 {
+        self.unix_ms().abs_diff(other.unix_ms())
+    }
+}
+
+impl Time for ExtendedTime {
+    fn now() -> Self {
+        System::now().into()
+    }
+
+    fn unix(&self) -> i64 {
+        let (secs, loss) = self.unix_checked();
+        crate::precision::strict(loss, "unix");
+        secs
+    }
+
+    fn unix_checked(&self) -> (i64, PrecisionLoss) {
+        let ms = self.unix_ms();
+        (ms.div_euclid(1000), PrecisionLoss::rounding(ms, 1000))
+    }
+
+    fn unix_ms(&self) -> i64 {
+        // saturating, as a deserialized value hasn't been through `from_ms_1601`'s clamp
+        self.ms.saturating_sub(OFFSET_1601 as i64 * 1000)
+    }
+
+    fn utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+
+    fn try_strptime_assuming<T: ToString, G: ToString>(s: T, format: G, default_offset: Option<i32>, assumed: OffsetSource) -> Result<Self, ParseTimeError> {
+        let (unix_ms, offset, source) = crate::try_parse_with_default_offset(&s.to_string(), &format.to_string(), default_offset, assumed)?;
+        Ok(ExtendedTime {
+            ms: unix_ms + OFFSET_1601 as i64 * 1000,
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: source,
+        })
+    }
+
+    fn offset_source(&self) -> OffsetSource {
+        self.offset_source
+    }
+
+    fn with_offset_source(mut self, source: OffsetSource) -> Self {
+        self.offset_source = source;
+        self
+    }
+
+    fn strftime(&self, format: &str) -> String {
+        self.format_items(chrono::format::StrftimeItems::new(format))
+    }
+
+    fn from_epoch(timestamp: u64) -> Self {
+        ExtendedTime::from_ms_1601(timestamp as i64, 0)
+    }
+
+    /// clamped to 0 for times before 1601
+    fn raw(&self) -> u64 {
+        self.ms.max(0) as u64
+    }
+
+    fn from_epoch_offset(timestamp: u64, offset: i32) -> Self {
+        ExtendedTime::from_ms_1601(timestamp as i64, offset)
+    }
+
+    fn canonicalize(&self) -> Self {
+        ExtendedTime {
+            ms: self.ms,
+            utc_offset: crate::clamp_offset(self.utc_offset),
+            offset_source: self.offset_source,
+        }
+    }
+
+    fn change_tz_seconds(&self, offset_seconds: i32) -> Self {
+        ExtendedTime::from_ms_1601(self.ms, offset_seconds)
+    }
+
+    fn add_seconds(&self, duration: i64) -> Self {
+        ExtendedTime::from_ms_1601(self.ms.saturating_add(duration.saturating_mul(1000)), 0)
+    }
+
+    fn past_future<T: Time>(&self, other: &T) -> crate::RelativeTime {
+        match self.unix_ms().cmp(&other.unix_ms()) {
+            core::cmp::Ordering::Less => crate::RelativeTime::Past,
+            core::cmp::Ordering::Greater => crate::RelativeTime::Future,
+            core::cmp::Ordering::Equal => crate::RelativeTime::Present,
+        }
+    }
+
+    fn try_epoch(&self) -> Result<i64, TimeError> {
+        Ok(self.ms)
+    }
+
+    fn try_wall_clock(&self) -> Option<NaiveDateTime> {
+        Some(DateTime::from_timestamp_millis(self.unix_ms().checked_add(self.utc_offset as i64 * 1000)?)?.naive_utc())
+    }
+
+    fn from_wall_clock(wall: NaiveDateTime, offset: i32) -> Self {
+        ExtendedTime::from_ms_1601(wall.and_utc().timestamp_millis() - offset as i64 * 1000 + OFFSET_1601 as i64 * 1000, offset)
+    }
+}
+
+impl From<System> for ExtendedTime {
+    fn from(t: System) -> Self {
+        ExtendedTime::from_ms_1601(t.raw() as i64, t.utc_offset())
+    }
+}
+
+impl From<Ntp> for ExtendedTime {
+    fn from(t: Ntp) -> Self {
+        ExtendedTime::from_ms_1601(t.raw() as i64, t.utc_offset())
+    }
+}
+
+impl TryFrom<ExtendedTime> for System {
+    type Error = TimeError;
+
+    /// fails with `TimeError::Overflow` for times before 1601
+    fn try_from(t: ExtendedTime) -> Result<Self, Self::Error> {
+        let raw = u64::try_from(t.ms).map_err(|_| TimeError::Overflow)?;
+        Ok(System::from_epoch_offset(raw, t.utc_offset))
+    }
+}
+
+impl TryFrom<ExtendedTime> for Ntp {
+    type Error = TimeError;
+
+    /// fails with `TimeError::Overflow` for times before 1601
+    fn try_from(t: ExtendedTime) -> Result<Self, Self::Error> {
+        let raw = u64::try_from(t.ms).map_err(|_| TimeError::Overflow)?;
+        Ok(Ntp::from_epoch_offset(raw, t.utc_offset))
+    }
+}
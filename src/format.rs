@@ -0,0 +1,367 @@
+use crate::{FormatError, Time};
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use std::cell::RefCell;
+use std::sync::{OnceLock, RwLock};
+
+/// the format `pretty()` uses when nothing else is configured
+pub(crate) const PRETTY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// the format set with `set_default_pretty_format`, if any
+static PRETTY_OVERRIDE: RwLock<Option<CompiledFormat>> = RwLock::new(None);
+
+/// the `THETIME_FORMAT` environment variable, read the first time it is needed (None if unset or invalid)
+static PRETTY_ENV: OnceLock<Option<CompiledFormat>> = OnceLock::new();
+
+/// A strftime-style format string, parsed once so it can be reused without re-parsing
+///
+/// # Examples
+/// ```rust
+/// use thetime::{CompiledFormat, System, Time, IntTime};
+/// let fmt = CompiledFormat::new("%Y-%m-%d %H:%M:%S").unwrap();
+/// let x = 1704465989u32.unix::<System>();
+/// assert_eq!(x.strftime_compiled(&fmt), x.strftime("%Y-%m-%d %H:%M:%S"));
+/// assert!(CompiledFormat::new("%Y-%").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledFormat {
+    source: String,
+    items: Vec<Item<'static>>,
+}
+
+impl CompiledFormat {
+    /// Parses `format`, returning an error if it contains an invalid specifier
+    pub fn new(format: &str) -> Result<Self, FormatError> {
+        let items = StrftimeItems::new(format)
+            .parse_to_owned()
+            .map_err(|_| FormatError::Invalid(format.to_string()))?;
+        Ok(CompiledFormat {
+            source: format.to_string(),
+            items,
+        })
+    }
+
+    /// The format string this was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// internal only - the parsed items
+    pub(crate) fn items(&self) -> core::slice::Iter<'_, Item<'static>> {
+        self.items.iter()
+    }
+}
+
+/// The longest format string `FormatString::parse_untrusted` accepts, in bytes
+pub const MAX_UNTRUSTED_FORMAT_LEN: usize = 256;
+
+/// the specifiers `FormatString::parse_untrusted` accepts (after the '%')
+const UNTRUSTED_SPECIFIERS: [&str; 51] = [
+    "Y", "C", "y", "m", "b", "B", "h", "d", "e", "a", "A", "w", "u", "U", "W", "G", "g", "V", "j", "D", "x", "F", "v", "H", "k", "I", "l",
+    "P", "p", "M", "S", "f", ".f", ".3f", ".6f", ".9f", "3f", "6f", "9f", "R", "T", "X", "r", "Z", "z", ":z", "c", "s", "t", "n", "%",
+];
+
+/// the specifiers that take a padding flag
+const PADDED_SPECIFIERS: &str = "YCymdewuUWGgVjHkIlMSs";
+
+/// A strftime-style format string from an untrusted source, checked so that formatting with it can not panic or produce unbounded output
+///
+/// Build one with `parse_untrusted`, and format with `Time::strftime_checked`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{FormatString, System, Time, IntTime};
+/// let fmt = FormatString::parse_untrusted("%Y-%m-%d, 100%").unwrap();
+/// assert_eq!(fmt.as_str(), "%Y-%m-%d, 100%%");
+/// assert_eq!(1704465989u32.unix::<System>().strftime_checked(&fmt), "2024-01-05, 100%");
+/// assert!(FormatString::parse_untrusted("%1000000d").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatString {
+    compiled: CompiledFormat,
+}
+
+impl FormatString {
+    /// Check a format string from an untrusted source
+    ///
+    /// - it must be at most `MAX_UNTRUSTED_FORMAT_LEN` bytes, so the output is bounded (no specifier writes more than a few dozen bytes)
+    /// - only these specifiers are accepted: `%Y %C %y %m %b %B %h %d %e %a %A %w %u %U %W %G %g %V %j %D %x %F %v %H %k %I %l %P %p %M %S
+    ///   %f %.f %.3f %.6f %.9f %3f %6f %9f %R %T %X %r %Z %z %:z %c %s %t %n %%` - anything else after a '%' is rejected, rather than
+    ///   passed through or guessed at
+    /// - padding is limited to one `-`, `_` or `0` flag before a numeric specifier, so no field is wider than its natural width -
+    ///   explicit widths (as in `%1000d`) are rejected
+    /// - a '%' at the end, or before whitespace, is taken literally and escaped as `%%`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{FormatString, FormatError};
+    /// assert!(FormatString::parse_untrusted("%-d/%-m/%Y %H:%M:%S%.3f %:z").is_ok());
+    /// assert!(matches!(FormatString::parse_untrusted("%Y %Q"), Err(FormatError::Rejected { position: 3, .. })));
+    /// assert!(matches!(FormatString::parse_untrusted("%-B"), Err(FormatError::Rejected { position: 0, .. })));
+    /// ```
+    pub fn parse_untrusted(format: &str) -> Result<FormatString, FormatError> {
+        let reject = |position, reason| FormatError::Rejected { format: format.to_string(), position, reason };
+        if format.len() > MAX_UNTRUSTED_FORMAT_LEN {
+            return Err(reject(MAX_UNTRUSTED_FORMAT_LEN, "at most 256 bytes"));
+        }
+
+        let bytes = format.as_bytes();
+        let mut sanitized = String::with_capacity(format.len() + 8);
+        let (mut i, mut literal_start) = (0, 0);
+        while i < bytes.len() {
+            if bytes[i] != b'%' {
+                i += 1;
+                continue;
+            }
+            sanitized.push_str(&format[literal_start..i]);
+            let start = i;
+            i += 1;
+            if bytes.get(i).is_none_or(|b| b.is_ascii_whitespace()) {
+                sanitized.push_str("%%");
+                literal_start = i;
+                continue;
+            }
+
+            let flag = matches!(bytes[i], b'-' | b'_' | b'0');
+            if flag {
+                i += 1;
+            }
+            let rest = &bytes[i..];
+            let specifier = match UNTRUSTED_SPECIFIERS.iter().filter(|s| rest.starts_with(s.as_bytes())).max_by_key(|s| s.len()) {
+                Some(specifier) => *specifier,
+                None if rest.first().is_some_and(|b| b.is_ascii_digit()) => return Err(reject(start, "a specifier without a width")),
+                None => return Err(reject(start, "a known specifier")),
+            };
+            if flag && !(specifier.len() == 1 && PADDED_SPECIFIERS.contains(specifier)) {
+                return Err(reject(start, "a padding flag only before a numeric specifier"));
+            }
+            i += specifier.len();
+            sanitized.push_str(&format[start..i]);
+            literal_start = i;
+        }
+        sanitized.push_str(&format[literal_start..]);
+
+        Ok(FormatString {
+            compiled: CompiledFormat::new(&sanitized)?,
+        })
+    }
+
+    /// The checked format string, with stray '%'s escaped
+    pub fn as_str(&self) -> &str {
+        self.compiled.as_str()
+    }
+
+    /// The checked format, compiled - usable anywhere a `CompiledFormat` is
+    pub fn compiled(&self) -> &CompiledFormat {
+        &self.compiled
+    }
+}
+
+/// internal only - format `time` with `items`, writing whatever is formattable rather than panicking if an item is not (and nothing if
+/// the time is out of chrono's range)
+pub(crate) fn format_checked<T: Time + ?Sized>(time: &T, items: core::slice::Iter<'_, Item<'static>>) -> String {
+    use chrono::TimeZone;
+    use core::fmt::Write;
+    let mut out = String::new();
+    let Some(wall) = time.try_wall_clock() else {
+        return out;
+    };
+    let _ = match chrono::FixedOffset::east_opt(time.utc_offset()).and_then(|offset| offset.from_local_datetime(&wall).single()) {
+        Some(local) => write!(out, "{}", local.format_with_items(items)),
+        None => write!(out, "{}", wall.format_with_items(items)),
+    };
+    out
+}
+
+/// Formats every time object in `times` with `format`, parsing the format only once
+///
+/// # Examples
+/// ```rust
+/// use thetime::{format_all, CompiledFormat, System, IntTime};
+/// let times = [0u32.unix::<System>(), 86400u32.unix::<System>()];
+/// let fmt = CompiledFormat::new("%Y-%m-%d").unwrap();
+/// assert_eq!(format_all(&times, &fmt), vec!["1970-01-01", "1970-01-02"]);
+/// ```
+pub fn format_all<T: Time>(times: &[T], format: &CompiledFormat) -> Vec<String> {
+    times.iter().map(|t| t.strftime_compiled(format)).collect()
+}
+
+/// internal only - lets the formatter append straight to a byte buffer
+struct ByteSink<'a>(&'a mut Vec<u8>);
+
+impl core::fmt::Write for ByteSink<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Formats every time object in `times` with `format`, appending them all to `out` with `separator` between each (but not after the
+/// last) - one growing buffer rather than a `String` per value, for exporting large columns
+///
+/// The bytes are the same as joining `strftime` of each time with `separator`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{format_column, CompiledFormat, System, IntTime};
+/// let times = [0u32.unix::<System>(), 86400u32.unix::<System>()];
+/// let mut out = b"day\n".to_vec();
+/// format_column(&times, &CompiledFormat::new("%Y-%m-%d").unwrap(), &mut out, b'\n');
+/// assert_eq!(out, b"day\n1970-01-01\n1970-01-02");
+/// ```
+pub fn format_column<T: Time>(times: &[T], format: &CompiledFormat, out: &mut Vec<u8>, separator: u8) {
+    for (i, time) in times.iter().enumerate() {
+        if i > 0 {
+            out.push(separator);
+        }
+        // the format was checked when it was compiled, so writing it can't fail
+        let _ = time.format_items_into(format.items(), &mut ByteSink(out));
+    }
+}
+
+/// how many bytes `write_column` gathers before handing them to the writer
+const COLUMN_BUFFER: usize = 64 * 1024;
+
+/// Like `format_column`, writing to `out` as it goes instead of into one buffer - the values are gathered into chunks of about 64KiB,
+/// so `out` needn't be buffered itself. Errors only if `out` does
+///
+/// # Examples
+/// ```rust
+/// use thetime::{write_column, CompiledFormat, System, IntTime};
+/// let times = [0u32.unix::<System>(), 86400u32.unix::<System>()];
+/// let mut out = Vec::new();
+/// write_column(&times, &CompiledFormat::new("%d/%m").unwrap(), &mut out, b',').unwrap();
+/// assert_eq!(out, b"01/01,02/01");
+/// ```
+pub fn write_column<T: Time, W: std::io::Write>(times: &[T], format: &CompiledFormat, mut out: W, separator: u8) -> std::io::Result<()> {
+    let mut buffer = Vec::with_capacity(COLUMN_BUFFER + 256);
+    for (i, time) in times.iter().enumerate() {
+        if i > 0 {
+            buffer.push(separator);
+        }
+        let _ = time.format_items_into(format.items(), &mut ByteSink(&mut buffer));
+        if buffer.len() >= COLUMN_BUFFER {
+            out.write_all(&buffer)?;
+            buffer.clear();
+        }
+    }
+    out.write_all(&buffer)
+}
+
+/// Set the format `pretty()` and the `Display` impls use, for the whole process - `iso8601()`, `rfc3339()` and friends are not affected
+///
+/// The format is validated first, and left unchanged on error. Undo with `reset_default_pretty_format`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{set_default_pretty_format, reset_default_pretty_format, System, Time, IntTime};
+/// let x = 1704465989u32.unix::<System>();
+/// set_default_pretty_format("%d/%m/%Y %H:%M").unwrap();
+/// assert_eq!(x.to_string(), "05/01/2024 14:46");
+/// assert!(set_default_pretty_format("%Y-%").is_err());
+/// reset_default_pretty_format();
+/// assert_eq!(x.pretty(), "2024-01-05 14:46:29");
+/// ```
+pub fn set_default_pretty_format(format: &str) -> Result<(), FormatError> {
+    let compiled = CompiledFormat::new(format)?;
+    *PRETTY_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = Some(compiled);
+    Ok(())
+}
+
+/// Undo `set_default_pretty_format`, going back to the `THETIME_FORMAT` environment variable if it holds a valid format, or
+/// `%Y-%m-%d %H:%M:%S` otherwise
+pub fn reset_default_pretty_format() {
+    *PRETTY_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The format `pretty()` currently uses
+///
+/// # Examples
+/// ```rust
+/// use thetime::default_pretty_format;
+/// println!("times print as {}", default_pretty_format());
+/// ```
+pub fn default_pretty_format() -> String {
+    if let Some(format) = PRETTY_OVERRIDE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return format.as_str().to_string();
+    }
+    env_format().map_or(PRETTY_FORMAT, |f| f.as_str()).to_string()
+}
+
+/// internal only - the `THETIME_FORMAT` environment variable, if it holds a valid format
+fn env_format() -> Option<&'static CompiledFormat> {
+    PRETTY_ENV
+        .get_or_init(|| std::env::var("THETIME_FORMAT").ok().and_then(|f| CompiledFormat::new(&f).ok()))
+        .as_ref()
+}
+
+/// internal only - whether formatting an item reads the offset, so the wall clock alone would give the wrong output
+pub(crate) fn needs_offset(item: &Item) -> bool {
+    match item {
+        Item::Literal(_) | Item::OwnedLiteral(_) | Item::Space(_) | Item::OwnedSpace(_) | Item::Error => false,
+        Item::Numeric(spec, _) => matches!(spec, Numeric::Timestamp | Numeric::Internal(_)),
+        Item::Fixed(spec) => !matches!(
+            spec,
+            Fixed::ShortMonthName
+                | Fixed::LongMonthName
+                | Fixed::ShortWeekdayName
+                | Fixed::LongWeekdayName
+                | Fixed::LowerAmPm
+                | Fixed::UpperAmPm
+                | Fixed::Nanosecond
+                | Fixed::Nanosecond3
+                | Fixed::Nanosecond6
+                | Fixed::Nanosecond9
+        ),
+    }
+}
+
+/// internal only - a fraction of a second of `digits` digits (at most 9) from milliseconds, with its '.' - empty for 0 digits
+pub(crate) fn fraction(ms: u32, digits: u8) -> String {
+    match digits.min(9) {
+        0 => String::new(),
+        digits => format!(".{:09}", ms * 1_000_000)[..digits as usize + 1].to_string(),
+    }
+}
+
+/// internal only - format a time object with the current default pretty format
+pub(crate) fn pretty<T: Time + ?Sized>(time: &T) -> String {
+    // room for the default format, and most others, without growing
+    let mut out = String::with_capacity(32);
+    pretty_into(time, &mut out);
+    out
+}
+
+/// internal only - append a time object in the current default pretty format to `out`
+pub(crate) fn pretty_into<T: Time + ?Sized>(time: &T, out: &mut String) {
+    // every pretty format was checked when it was set, so writing it can't fail
+    let _ = if let Some(format) = PRETTY_OVERRIDE.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        time.format_items_into(format.items(), out)
+    } else {
+        match env_format() {
+            Some(format) => time.format_items_into(format.items(), out),
+            None => time.format_items_into(StrftimeItems::new(PRETTY_FORMAT), out),
+        }
+    };
+}
+
+thread_local! {
+    /// the buffer `with_pretty` formats into, reused by every call on the thread
+    static PRETTY_BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// internal only - `Time::with_pretty`
+pub(crate) fn with_pretty<T: Time + ?Sized, R, F: FnOnce(&str) -> R>(time: &T, f: F) -> R {
+    let mut f = Some(f);
+    let reused = PRETTY_BUFFER.try_with(|buffer| {
+        // a call from inside `f` finds the buffer borrowed, and falls back to a fresh string
+        let mut buffer = buffer.try_borrow_mut().ok()?;
+        buffer.clear();
+        pretty_into(time, &mut buffer);
+        Some((f.take().unwrap())(&buffer))
+    });
+    match reused {
+        Ok(Some(result)) => result,
+        // the buffer is borrowed, or already gone as the thread exits
+        _ => (f.take().unwrap())(&pretty(time)),
+    }
+}
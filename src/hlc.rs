@@ -0,0 +1,236 @@
+use crate::{Clock, HlcDrift, ParseError, RealClock, System, Time};
+use core::fmt::Display;
+use core::time::Duration;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The length of `HlcTimestamp::to_bytes`: the wall time, logical counter and node, big-endian
+pub const HLC_TIMESTAMP_LEN: usize = 14;
+
+/// A hybrid logical clock reading: a wall time, a logical counter ordering events within (or behind) that millisecond, and the node
+/// that made it, to break ties
+///
+/// Timestamps order by wall time, then counter, then node. The string form is fixed-width lowercase hex (`wall-logical-node`), and the
+/// binary form big-endian, so both sort the same way as the timestamps themselves. Serializes as its string form
+///
+/// # Examples
+/// ```rust
+/// use thetime::{HlcTimestamp, System, Time, IntTime};
+/// let x = HlcTimestamp { wall_ms: 1704465989u32.unix::<System>().raw(), logical: 3, node: 7 };
+/// assert_eq!(x.to_string(), "00000c240acd6588-00000003-0007");
+/// assert_eq!(x.to_string().parse::<HlcTimestamp>().unwrap(), x);
+/// assert_eq!(HlcTimestamp::from_bytes(x.to_bytes()), x);
+/// assert_eq!(x.to_system().unix(), 1704465989);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct HlcTimestamp {
+    /// the wall time, in milliseconds since 1601 (as `Time::raw`)
+    pub wall_ms: u64,
+    /// events at the same wall time, counted
+    pub logical: u32,
+    /// the node that made the timestamp
+    pub node: u16,
+}
+
+impl HlcTimestamp {
+    /// The timestamp as bytes that sort as it does: the wall time, logical counter and node, big-endian
+    pub fn to_bytes(&self) -> [u8; HLC_TIMESTAMP_LEN] {
+        let mut bytes = [0; HLC_TIMESTAMP_LEN];
+        bytes[..8].copy_from_slice(&self.wall_ms.to_be_bytes());
+        bytes[8..12].copy_from_slice(&self.logical.to_be_bytes());
+        bytes[12..].copy_from_slice(&self.node.to_be_bytes());
+        bytes
+    }
+
+    /// The timestamp from `to_bytes`
+    pub fn from_bytes(bytes: [u8; HLC_TIMESTAMP_LEN]) -> Self {
+        HlcTimestamp {
+            wall_ms: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            logical: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            node: u16::from_be_bytes(bytes[12..].try_into().unwrap()),
+        }
+    }
+
+    /// The wall time, in UTC - the logical counter and node are dropped, so this is for display rather than ordering
+    pub fn to_system(&self) -> System {
+        System::from_epoch(self.wall_ms)
+    }
+}
+
+impl From<HlcTimestamp> for System {
+    fn from(timestamp: HlcTimestamp) -> Self {
+        timestamp.to_system()
+    }
+}
+
+impl Display for HlcTimestamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:016x}-{:08x}-{:04x}", self.wall_ms, self.logical, self.node)
+    }
+}
+
+impl FromStr for HlcTimestamp {
+    type Err = ParseError;
+
+    /// Parses the `Display` form: 16, 8 and 4 hex digits, separated by '-'
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        // each field is a fixed number of hex digits, followed by '-' (or the end, for the last)
+        let mut fields = [(16, "16 hex digits of wall time", 0), (8, "8 hex digits of logical counter", 0), (4, "4 hex digits of node", 0)];
+        let mut pos = 0;
+        for (i, (digits, what, value)) in fields.iter_mut().enumerate() {
+            let hex = s.get(pos..pos + *digits).filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()));
+            *value = u64::from_str_radix(hex.ok_or_else(|| ParseError::invalid(s, pos, what))?, 16).unwrap();
+            pos += *digits;
+            let separator = if i < 2 { Some(&b'-') } else { None };
+            if s.as_bytes().get(pos) != separator {
+                return Err(ParseError::invalid(s, pos, if i < 2 { "'-'" } else { "end of input" }));
+            }
+            pos += 1;
+        }
+        let [(_, _, wall_ms), (_, _, logical), (_, _, node)] = fields;
+        let (logical, node) = (logical as u32, node as u16);
+        Ok(HlcTimestamp { wall_ms, logical, node })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for HlcTimestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for HlcTimestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A hybrid logical clock, for ordering events across services: timestamps track the wall clock, but never go backwards, and a
+/// timestamp made after receiving another always sorts after it
+///
+/// When the wall clock stalls or steps back, the logical counter moves on instead, so every timestamp from one `Hlc` is greater than the
+/// last. Merging a received timestamp with `update` carries the causal order across nodes (Kulkarni et al., 2014). Should the counter
+/// ever run out, the wall time is moved on a millisecond
+///
+/// A received timestamp past `Time::far_future` is refused, as is one more than `max_drift` ahead of the wall clock if that is set, so
+/// that one bad peer can't drag every clock it talks to into the far future
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Hlc, FixedClock, System, Time, IntTime};
+/// let clock = FixedClock(1704465989u32.unix::<System>());
+/// let mut a = Hlc::with_clock(1, &clock);
+/// let mut b = Hlc::with_clock(2, &clock);
+/// let sent = a.now();
+/// let received = b.update(sent).unwrap();
+/// assert!(received > sent);
+/// assert!(a.now() > sent);
+/// assert_eq!(received.to_system().unix(), 1704465989);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hlc<C = RealClock> {
+    node: u16,
+    clock: C,
+    last: HlcTimestamp,
+    max_drift: Option<Duration>,
+}
+
+impl Hlc<RealClock> {
+    /// A hybrid logical clock for node `node`, reading the system clock
+    pub fn new(node: u16) -> Self {
+        Hlc::with_clock(node, RealClock)
+    }
+}
+
+impl<C: Clock> Hlc<C> {
+    /// A hybrid logical clock for node `node`, reading `clock`
+    pub fn with_clock(node: u16, clock: C) -> Self {
+        Hlc {
+            node,
+            clock,
+            last: HlcTimestamp { wall_ms: 0, logical: 0, node },
+            max_drift: None,
+        }
+    }
+
+    /// Refuse received timestamps more than `max_drift` ahead of this clock's wall clock
+    pub fn max_drift(mut self, max_drift: Duration) -> Self {
+        self.max_drift = Some(max_drift);
+        self
+    }
+
+    /// The node this clock stamps its timestamps with
+    pub fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// The latest timestamp made, or `HlcTimestamp::default()` with this node if none yet
+    pub fn last(&self) -> HlcTimestamp {
+        self.last
+    }
+
+    /// A timestamp for a local or send event, greater than every timestamp this clock has made or merged
+    pub fn now(&mut self) -> HlcTimestamp {
+        let physical = self.physical();
+        self.last = if physical > self.last.wall_ms {
+            self.stamp(physical, 0)
+        } else {
+            // the wall time never passes far_future by more than a millisecond per 2^32 events, so this can't run out
+            self.tick(self.last.wall_ms, self.last.logical).expect("HLC wall time out of range")
+        };
+        self.last
+    }
+
+    /// A timestamp for receiving `received`, greater than it and than every timestamp this clock has made or merged - or an error,
+    /// leaving the clock as it was, if `received` is too far ahead to trust
+    pub fn update(&mut self, received: HlcTimestamp) -> Result<HlcTimestamp, HlcDrift> {
+        let physical = self.physical();
+        let limit = match self.max_drift {
+            Some(drift) => physical.saturating_add(u64::try_from(drift.as_millis()).unwrap_or(u64::MAX)).min(Self::far_future()),
+            None => Self::far_future(),
+        };
+        if received.wall_ms > limit {
+            return Err(HlcDrift { received, limit });
+        }
+        let wall = physical.max(self.last.wall_ms).max(received.wall_ms);
+        let next = match (wall == self.last.wall_ms, wall == received.wall_ms) {
+            (true, true) => self.tick(wall, self.last.logical.max(received.logical)),
+            (true, false) => self.tick(wall, self.last.logical),
+            (false, true) => self.tick(wall, received.logical),
+            (false, false) => Some(self.stamp(wall, 0)),
+        };
+        self.last = next.ok_or(HlcDrift { received, limit })?;
+        Ok(self.last)
+    }
+
+    /// internal only - the wall clock, held at `far_future`
+    fn physical(&self) -> u64 {
+        self.clock.now_system().raw().min(Self::far_future())
+    }
+
+    /// internal only - `Time::far_future`, in milliseconds since 1601
+    fn far_future() -> u64 {
+        crate::FAR_FUTURE_UNIX_MS as u64 + crate::OFFSET_1601 * 1000
+    }
+
+    /// internal only - a timestamp of this node's
+    fn stamp(&self, wall_ms: u64, logical: u32) -> HlcTimestamp {
+        HlcTimestamp { wall_ms, logical, node: self.node }
+    }
+
+    /// internal only - the timestamp one count after `logical` at `wall_ms`, moving to the next millisecond if the counter is full
+    fn tick(&self, wall_ms: u64, logical: u32) -> Option<HlcTimestamp> {
+        match logical.checked_add(1) {
+            Some(logical) => Some(self.stamp(wall_ms, logical)),
+            None => Some(self.stamp(wall_ms.checked_add(1)?, 0)),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::parse::{fraction_ms, Cursor};
+use crate::{ImplsDuration, ParseError};
+use core::fmt::Write;
+use core::time::Duration;
+
+/// Whether `format_hms` and `format_hms_ms` write the hours field when it is zero
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum HmsHours {
+    /// always write the hours: "0:05:09"
+    #[default]
+    Always,
+    /// leave the hours out when they are zero, with the minutes padded to two digits: "05:09"
+    IfNonZero,
+}
+
+/// internal only - format a signed number of milliseconds, writing the milliseconds or not
+fn hms(ms: i64, hours: HmsHours, with_ms: bool) -> String {
+    // without the milliseconds, round towards zero so that -0.5s is "0:00:00" rather than "-0:00:00"
+    let ms = if with_ms { ms } else { ms / 1000 * 1000 };
+    let sign = if ms < 0 { "-" } else { "" };
+    let ms = ms.unsigned_abs();
+    let (h, m, s) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60);
+    let mut out = match hours {
+        HmsHours::IfNonZero if h == 0 => format!("{}{:02}:{:02}", sign, m, s),
+        _ => format!("{}{}:{:02}:{:02}", sign, h, m, s),
+    };
+    if with_ms {
+        // writing to a String can't fail
+        let _ = write!(out, ".{:03}", ms % 1000);
+    }
+    out
+}
+
+/// Format a duration the way media players show it - "1:05:09", with the hours unpadded and the minutes and seconds padded to two
+/// digits. Takes a `core::time::Duration`, a `chrono::Duration`, or whole seconds as an `i64`; negative durations get a leading '-',
+/// and any fraction of a second is dropped
+///
+/// # Examples
+/// ```rust
+/// use thetime::{format_hms, HmsHours};
+/// use core::time::Duration;
+/// assert_eq!(format_hms(3909i64, HmsHours::Always), "1:05:09");
+/// assert_eq!(format_hms(Duration::from_secs(65), HmsHours::IfNonZero), "01:05");
+/// assert_eq!(format_hms(chrono::Duration::seconds(-65), HmsHours::Always), "-0:01:05");
+/// ```
+pub fn format_hms<D: ImplsDuration>(duration: D, hours: HmsHours) -> String {
+    hms(duration.num_milliseconds(), hours, false)
+}
+
+/// As `format_hms`, with the milliseconds: "1:05:09.250"
+///
+/// # Examples
+/// ```rust
+/// use thetime::{format_hms_ms, HmsHours};
+/// use core::time::Duration;
+/// assert_eq!(format_hms_ms(Duration::from_millis(3_909_250), HmsHours::Always), "1:05:09.250");
+/// assert_eq!(format_hms_ms(chrono::Duration::milliseconds(-500), HmsHours::IfNonZero), "-00:00.500");
+/// ```
+pub fn format_hms_ms<D: ImplsDuration>(duration: D, hours: HmsHours) -> String {
+    hms(duration.num_milliseconds(), hours, true)
+}
+
+/// Parse a media player style duration: "H:MM:SS", "MM:SS" or bare seconds, each with an optional fraction ("1:05:09.25"). The first
+/// field may have any number of digits ("90:00" is an hour and a half), the rest must be two digits below 60. Fractions past the
+/// millisecond are dropped, and errors carry the byte position of the problem, counted after trimming surrounding whitespace
+///
+/// # Examples
+/// ```rust
+/// use thetime::parse_hms;
+/// use core::time::Duration;
+/// assert_eq!(parse_hms("1:05:09").unwrap(), Duration::from_secs(3909));
+/// assert_eq!(parse_hms("05:09.5").unwrap(), Duration::from_millis(309_500));
+/// assert_eq!(parse_hms("3909").unwrap(), Duration::from_secs(3909));
+/// assert!(parse_hms("1:5:09").is_err());
+/// ```
+pub fn parse_hms(input: &str) -> Result<Duration, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+    let first = cur.digit_run();
+    if first.is_empty() {
+        return Err(cur.error("digits"));
+    }
+    let mut seconds = first.parse::<u64>().map_err(|_| ParseError::OutOfRange { field: "duration", value: i64::MAX })?;
+
+    let mut fields = 1;
+    while cur.eat(b':') {
+        if fields == 3 {
+            return Err(cur.error_at(cur.pos - 1, "end of input"));
+        }
+        let value = cur.field(2, 0, 59, "two digits 00-59")?;
+        seconds = seconds
+            .checked_mul(60)
+            .and_then(|s| s.checked_add(value as u64))
+            .ok_or(ParseError::OutOfRange { field: "duration", value: i64::MAX })?;
+        fields += 1;
+    }
+
+    let mut ms = 0;
+    if cur.eat(b'.') {
+        let fraction = cur.digit_run();
+        if fraction.is_empty() {
+            return Err(cur.error("fractional digits"));
+        }
+        ms = fraction_ms(fraction);
+    }
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(Duration::from_secs(seconds) + Duration::from_millis(ms as u64))
+}
@@ -0,0 +1,164 @@
+//! Reading and writing the time-valued HTTP headers: `Retry-After`, `Age`, `Expires` and `Date`
+//!
+//! Dates are read in all three forms RFC 9110 asks recipients to accept (IMF-fixdate, RFC 850 and asctime) and always written as
+//! IMF-fixdate, in GMT
+//!
+//! # Examples
+//! ```rust
+//! use thetime::{System, Time, IntTime, http};
+//! use core::time::Duration;
+//! let now = 1445412480u32.unix::<System>();
+//! assert_eq!(http::parse_retry_after("120", &now).unwrap().unix(), 1445412600);
+//! assert_eq!(http::parse_retry_after("Wed, 21 Oct 2015 07:30:00 GMT", &now).unwrap().unix(), 1445412600);
+//! assert_eq!(http::freshness_lifetime("Wed, 21 Oct 2015 08:28:00 GMT", "Wed, 21 Oct 2015 07:28:00 GMT").unwrap(), Duration::from_secs(3600));
+//! ```
+
+use crate::{parse, ParseError, ParseOptions, System, Time};
+use chrono::DateTime;
+use core::time::Duration;
+
+/// The largest delta-seconds value RFC 9111 asks caches to keep - anything larger is read as this
+pub const MAX_DELTA_SECONDS: u64 = 2_147_483_648;
+
+/// internal only - a delta-seconds value: one or more digits and nothing else, capped at `MAX_DELTA_SECONDS`
+fn delta_seconds(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // only digits, so the parse can only fail by overflowing
+    Some(value.parse::<u64>().unwrap_or(u64::MAX).min(MAX_DELTA_SECONDS))
+}
+
+/// Parse an HTTP date, in UTC: "Sun, 06 Nov 1994 08:49:37 GMT", the obsolete "Sunday, 06-Nov-94 08:49:37 GMT" (two digit years below
+/// 70 being 20YY) or asctime's "Sun Nov  6 08:49:37 1994". Errors carry the byte position of the problem
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, http};
+/// let x = http::parse_http_date::<System>("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+/// assert_eq!(http::parse_http_date::<System>("Sunday, 06-Nov-94 08:49:37 GMT").unwrap(), x);
+/// assert_eq!(http::parse_http_date::<System>("Sun Nov  6 08:49:37 1994").unwrap(), x);
+/// assert!(http::parse_http_date::<System>("Sun, 06 Nov 1994 08:49:37 EST").is_err());
+/// ```
+pub fn parse_http_date<T: Time>(value: &str) -> Result<T, ParseError> {
+    parse::http_date(value, &ParseOptions::default())
+}
+
+/// Parse an HTTP date as `parse_http_date` does, but with the weekday and day-of-month checks `options` ask for
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, ParseOptions, http};
+/// // 6 Nov 1994 was a Sunday
+/// assert!(http::parse_http_date::<System>("Mon, 06 Nov 1994 08:49:37 GMT").is_ok());
+/// assert!(http::parse_http_date_with::<System>("Mon, 06 Nov 1994 08:49:37 GMT", &ParseOptions::strict()).is_err());
+/// ```
+pub fn parse_http_date_with<T: Time>(value: &str, options: &ParseOptions) -> Result<T, ParseError> {
+    parse::http_date(value, options)
+}
+
+/// The time as an HTTP date (IMF-fixdate), in GMT whatever its offset - for `Date`, `Expires`, `Last-Modified` and the like
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, http};
+/// assert_eq!(http::http_date(&784111777u32.unix::<System>().change_tz("+09:00")), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// ```
+pub fn http_date<T: Time>(time: &T) -> String {
+    // clamped to the end of the year 9999, so always within chrono's range
+    let utc = DateTime::from_timestamp_millis(time.unix_ms().min(crate::FAR_FUTURE_UNIX_MS)).unwrap();
+    utc.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// When a `Retry-After` header says to retry: `now` plus the delay for the delta-seconds form ("120"), or the date given for the HTTP
+/// date form. Either way the result is in `now`'s offset
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, http};
+/// let now = 1445412480u32.unix::<System>();
+/// assert_eq!(http::parse_retry_after("120", &now).unwrap().unix(), 1445412600);
+/// assert!(http::parse_retry_after("-5", &now).is_err());
+/// ```
+pub fn parse_retry_after<T: Time>(value: &str, now: &T) -> Result<System, ParseError> {
+    let raw = match delta_seconds(value) {
+        Some(seconds) => now.raw().saturating_add(seconds * 1000),
+        None => parse::http_date::<System>(value, &ParseOptions::default())?.raw(),
+    };
+    Ok(System::from_epoch_offset(raw, now.utc_offset()))
+}
+
+/// A `Retry-After` header value for a delay, in the delta-seconds form - rounded up to a whole second, so a client never retries early
+///
+/// # Examples
+/// ```rust
+/// use thetime::http;
+/// use core::time::Duration;
+/// assert_eq!(http::format_retry_after(Duration::from_millis(1500)), "2");
+/// ```
+pub fn format_retry_after(delay: Duration) -> String {
+    let seconds = delay.as_secs() + (delay.subsec_nanos() > 0) as u64;
+    seconds.to_string()
+}
+
+/// How old an `Age` header says a response is. RFC 9111 has caches ignore an invalid value, so anything other than a non-negative
+/// whole number of seconds (a negative value, a fraction, a date) is read as zero. Values past `MAX_DELTA_SECONDS` are capped to it
+///
+/// # Examples
+/// ```rust
+/// use thetime::http;
+/// use core::time::Duration;
+/// assert_eq!(http::parse_age("3600"), Duration::from_secs(3600));
+/// assert_eq!(http::parse_age("-1"), Duration::ZERO);
+/// ```
+pub fn parse_age(value: &str) -> Duration {
+    Duration::from_secs(delta_seconds(value).unwrap_or(0))
+}
+
+/// An `Age` header value for a duration, in whole seconds (rounded down, as RFC 9111 computes ages)
+///
+/// # Examples
+/// ```rust
+/// use thetime::http;
+/// use core::time::Duration;
+/// assert_eq!(http::format_age(Duration::from_millis(3600_900)), "3600");
+/// ```
+pub fn format_age(age: Duration) -> String {
+    age.as_secs().min(MAX_DELTA_SECONDS).to_string()
+}
+
+/// How long a response stays fresh, from its `Expires` and `Date` headers: the time between the two, or zero if `Expires` is not after
+/// `Date`. An `Expires` that isn't a valid HTTP date (such as the common "0") means already expired, as RFC 9111 requires, so also gives
+/// zero - only an invalid `Date` is an error
+///
+/// # Examples
+/// ```rust
+/// use thetime::http;
+/// use core::time::Duration;
+/// let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+/// assert_eq!(http::freshness_lifetime("Wed, 21 Oct 2015 07:38:00 GMT", date).unwrap(), Duration::from_secs(600));
+/// assert_eq!(http::freshness_lifetime("0", date).unwrap(), Duration::ZERO);
+/// assert!(http::freshness_lifetime("0", "yesterday").is_err());
+/// ```
+pub fn freshness_lifetime(expires: &str, date: &str) -> Result<Duration, ParseError> {
+    let date = parse::http_date::<System>(date, &ParseOptions::default())?;
+    Ok(match parse::http_date::<System>(expires, &ParseOptions::default()) {
+        Ok(expires) => Duration::from_millis(expires.raw().saturating_sub(date.raw())),
+        Err(_) => Duration::ZERO,
+    })
+}
+
+/// An `Expires` header value for a response sent at `date` that stays fresh for `lifetime` (whole seconds, rounded down)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, http};
+/// use core::time::Duration;
+/// let date = 1445412480u32.unix::<System>();
+/// assert_eq!(http::format_expires(&date, Duration::from_secs(3600)), "Wed, 21 Oct 2015 08:28:00 GMT");
+/// ```
+pub fn format_expires<T: Time>(date: &T, lifetime: Duration) -> String {
+    let seconds = i64::try_from(lifetime.as_secs()).unwrap_or(i64::MAX);
+    http_date(&System::from_epoch(date.raw()).add_seconds(seconds))
+}
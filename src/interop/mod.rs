@@ -0,0 +1,14 @@
+/// Conversions to and from the `time` crate (enabled with the `time` feature)
+#[cfg(feature = "time")]
+pub mod time_rs;
+
+/// Conversions to and from protobuf's `Timestamp`, as `prost_types::Timestamp` (enabled with the `prost` feature)
+#[cfg(feature = "prost")]
+pub mod prost;
+
+/// `schemars::JsonSchema` for the time types and serde helpers (enabled with the `schema` feature)
+#[cfg(feature = "schema")]
+pub mod schemars;
+
+/// The Windows `SYSTEMTIME` layout, for passing times to and from Windows APIs
+pub mod windows;
@@ -0,0 +1,43 @@
+use crate::{Ntp, System, Time, TimeError};
+use prost_types::Timestamp;
+
+/// Converts to a protobuf `Timestamp` (see `Time::to_proto_timestamp`)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime};
+/// let ts: prost_types::Timestamp = 1704465989u32.unix::<System>().into();
+/// assert_eq!((ts.seconds, ts.nanos), (1704465989, 0));
+/// ```
+impl From<System> for Timestamp {
+    fn from(time: System) -> Self {
+        let (seconds, nanos) = time.to_proto_timestamp();
+        Timestamp { seconds, nanos }
+    }
+}
+
+/// Converts to a protobuf `Timestamp` (see `Time::to_proto_timestamp`)
+impl From<Ntp> for Timestamp {
+    fn from(time: Ntp) -> Self {
+        let (seconds, nanos) = time.to_proto_timestamp();
+        Timestamp { seconds, nanos }
+    }
+}
+
+/// Converts from a protobuf `Timestamp`, truncating to milliseconds - see `Time::from_proto_timestamp` for the errors
+impl TryFrom<Timestamp> for System {
+    type Error = TimeError;
+
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        System::from_proto_timestamp(ts.seconds, ts.nanos)
+    }
+}
+
+/// Converts from a protobuf `Timestamp`, truncating to milliseconds - see `Time::from_proto_timestamp` for the errors
+impl TryFrom<Timestamp> for Ntp {
+    type Error = TimeError;
+
+    fn try_from(ts: Timestamp) -> Result<Self, Self::Error> {
+        Ntp::from_proto_timestamp(ts.seconds, ts.nanos)
+    }
+}
@@ -0,0 +1,133 @@
+//! `schemars::JsonSchema` for the time types and serde helpers, so API docs say exactly how each field is encoded
+//!
+//! `System`, `Ntp`, `Tz` (with the `timezones` feature) and `serde::PrecisionSafe` implement `JsonSchema`, so `#[derive(JsonSchema)]` works on structs holding them.
+//! Fields using one of the `#[serde(with = ..)]` helpers need the matching schema function here as well, as a
+//! `#[schemars(schema_with = ..)]` attribute
+//!
+//! # Examples
+//! ```rust
+//! use thetime::System;
+//! #[derive(serde::Serialize, schemars::JsonSchema)]
+//! struct Event {
+//!     at: System,
+//!     #[serde(with = "thetime::serde::string_ms")]
+//!     #[schemars(schema_with = "thetime::interop::schemars::string_ms")]
+//!     seen: System,
+//! }
+//! let schema = serde_json::to_value(schemars::schema_for!(Event)).unwrap();
+//! assert_eq!(schema["properties"]["seen"]["type"], "string");
+//! ```
+
+use crate::serde::PrecisionSafe;
+#[cfg(feature = "timezones")]
+use crate::Tz;
+use crate::{Ntp, System};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use std::borrow::Cow;
+
+/// internal only - the properties of the wire format shared by `System` and `Ntp`
+fn wire_schema(description: &str, with_server: bool) -> Schema {
+    let mut schema = json_schema!({
+        "type": "object",
+        "description": description,
+        "properties": {
+            "v": { "type": "integer", "format": "uint8", "const": 1, "description": "wire format version" },
+            "ms1601": { "type": "integer", "format": "uint64", "minimum": 0, "description": "milliseconds since 1601-01-01 00:00:00 UTC" },
+            "offset": { "type": "integer", "format": "int32", "description": "offset from UTC, in seconds" }
+        },
+        "required": ["v", "ms1601", "offset"]
+    });
+    if with_server {
+        let properties = schema.get_mut("properties").and_then(|p| p.as_object_mut()).unwrap();
+        properties.insert("server".to_string(), json_schema!({ "type": "string", "description": "the NTP server the time came from" }).into());
+        let required = schema.get_mut("required").and_then(|r| r.as_array_mut()).unwrap();
+        required.push("server".into());
+    }
+    schema
+}
+
+/// internal only - a decimal integer in a string
+fn integer_string(description: &str) -> Schema {
+    json_schema!({
+        "type": "string",
+        "pattern": "^-?[0-9]+$",
+        "description": description
+    })
+}
+
+impl JsonSchema for System {
+    fn schema_name() -> Cow<'static, str> {
+        "System".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "thetime::System".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        wire_schema("a point in time, with the offset from UTC it is displayed in", false)
+    }
+}
+
+impl JsonSchema for Ntp {
+    fn schema_name() -> Cow<'static, str> {
+        "Ntp".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "thetime::Ntp".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        wire_schema("a point in time from an NTP server, with the offset from UTC it is displayed in", true)
+    }
+}
+
+#[cfg(feature = "timezones")]
+impl JsonSchema for Tz {
+    fn schema_name() -> Cow<'static, str> {
+        "Tz".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "thetime::Tz".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let names: Vec<String> = Tz::ALL.iter().map(|tz| format!("{:?}", tz)).collect();
+        json_schema!({
+            "type": "string",
+            "enum": names,
+            "description": "a fixed offset timezone"
+        })
+    }
+}
+
+impl<T> JsonSchema for PrecisionSafe<T> {
+    fn schema_name() -> Cow<'static, str> {
+        "PrecisionSafe".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "thetime::serde::PrecisionSafe".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        integer_string("an integer, as a decimal string so that it survives JSON parsers that read numbers as f64")
+    }
+}
+
+/// `#[schemars(schema_with = "thetime::interop::schemars::string_ms")]`, for a field using `thetime::serde::string_ms`
+pub fn string_ms(_: &mut SchemaGenerator) -> Schema {
+    integer_string("milliseconds since 1970-01-01 00:00:00 UTC, as a decimal string")
+}
+
+/// `#[schemars(schema_with = "thetime::interop::schemars::string_windows_ns")]`, for a field using `thetime::serde::string_windows_ns`
+pub fn string_windows_ns(_: &mut SchemaGenerator) -> Schema {
+    integer_string("100 nanosecond ticks since 1601-01-01 00:00:00 UTC (a Windows FILETIME), as a decimal string")
+}
+
+/// `#[schemars(schema_with = "thetime::interop::schemars::precision_safe")]`, for a field using `thetime::serde::precision_safe`
+pub fn precision_safe(generator: &mut SchemaGenerator) -> Schema {
+    PrecisionSafe::<i64>::json_schema(generator)
+}
@@ -0,0 +1,69 @@
+use crate::{ImplsDuration, Ntp, System, Time, TimeError, OFFSET_1601};
+use time::{OffsetDateTime, UtcOffset};
+
+/// internal only - milliseconds since 1601 (clamped to 1601 itself) and the offset in seconds of an `OffsetDateTime`
+fn parts(dt: OffsetDateTime) -> (u64, i32) {
+    let unix_ms = dt.unix_timestamp_nanos().div_euclid(1_000_000);
+    let ms_1601 = (unix_ms + OFFSET_1601 as i128 * 1000).max(0) as u64;
+    (ms_1601, dt.offset().whole_seconds())
+}
+
+/// internal only - the reverse of `parts`
+fn to_offset_date_time<T: Time>(time: &T) -> Result<OffsetDateTime, TimeError> {
+    let nanos = time.unix_ms() as i128 * 1_000_000;
+    let offset = UtcOffset::from_whole_seconds(time.utc_offset()).map_err(|_| TimeError::Overflow)?;
+    Ok(OffsetDateTime::from_unix_timestamp_nanos(nanos)
+        .map_err(|_| TimeError::Overflow)?
+        .to_offset(offset))
+}
+
+/// Converts an `OffsetDateTime`, truncating to milliseconds - dates before `1601-01-01 00:00:00` are clamped to it
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time};
+/// let x: System = time::OffsetDateTime::UNIX_EPOCH.into();
+/// assert_eq!(x.unix(), 0);
+/// ```
+impl From<OffsetDateTime> for System {
+    fn from(dt: OffsetDateTime) -> Self {
+        let (ms, offset) = parts(dt);
+        System::from_epoch_offset(ms, offset)
+    }
+}
+
+/// Converts an `OffsetDateTime`, truncating to milliseconds - dates before `1601-01-01 00:00:00` are clamped to it
+impl From<OffsetDateTime> for Ntp {
+    fn from(dt: OffsetDateTime) -> Self {
+        let (ms, offset) = parts(dt);
+        Ntp::from_epoch_offset(ms, offset)
+    }
+}
+
+/// Converts to an `OffsetDateTime`, failing if the value is outside of the range the `time` crate supports
+impl TryFrom<System> for OffsetDateTime {
+    type Error = TimeError;
+
+    fn try_from(time: System) -> Result<Self, Self::Error> {
+        to_offset_date_time(&time)
+    }
+}
+
+/// Converts to an `OffsetDateTime`, failing if the value is outside of the range the `time` crate supports
+impl TryFrom<Ntp> for OffsetDateTime {
+    type Error = TimeError;
+
+    fn try_from(time: Ntp) -> Result<Self, Self::Error> {
+        to_offset_date_time(&time)
+    }
+}
+
+impl ImplsDuration for time::Duration {
+    fn num_seconds(&self) -> i64 {
+        self.whole_seconds()
+    }
+
+    fn num_milliseconds(&self) -> i64 {
+        self.whole_milliseconds() as i64
+    }
+}
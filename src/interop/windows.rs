@@ -0,0 +1,101 @@
+use crate::{Time, TimeError};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// A time split into fields, laid out like the Windows `SYSTEMTIME` struct (`wYear`, `wMonth`, `wDayOfWeek`, `wDay`, `wHour`, `wMinute`, `wSecond`, `wMilliseconds`)
+///
+/// `#[repr(C)]`, so it can be passed to Windows APIs as a `SYSTEMTIME` directly
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime};
+/// let parts = 1704465989u32.unix::<System>().to_systemtime_parts();
+/// assert_eq!((parts.year, parts.month, parts.day, parts.day_of_week), (2024, 1, 5, 5)); // a Friday
+/// ```
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SystemTimeParts {
+    /// `wYear`, 1601 to 30827
+    pub year: u16,
+    /// `wMonth`, 1 (January) to 12
+    pub month: u16,
+    /// `wDayOfWeek`, 0 (Sunday) to 6 (Saturday)
+    pub day_of_week: u16,
+    /// `wDay`, 1 to 31
+    pub day: u16,
+    /// `wHour`, 0 to 23
+    pub hour: u16,
+    /// `wMinute`, 0 to 59
+    pub minute: u16,
+    /// `wSecond`, 0 to 59
+    pub second: u16,
+    /// `wMilliseconds`, 0 to 999
+    pub milliseconds: u16,
+}
+
+/// internal only - errors unless `value` is within `min..=max`
+fn check(field: &'static str, value: u16, min: u16, max: u16) -> Result<u32, TimeError> {
+    if value < min || value > max {
+        return Err(TimeError::InvalidField { field, value: value as i64 });
+    }
+    Ok(value as u32)
+}
+
+impl SystemTimeParts {
+    /// Split a wall clock into fields - years past 65535 saturate
+    pub fn from_naive(wall: NaiveDateTime) -> Self {
+        SystemTimeParts {
+            year: wall.year().clamp(0, u16::MAX as i32) as u16,
+            month: wall.month() as u16,
+            day_of_week: wall.weekday().num_days_from_sunday() as u16,
+            day: wall.day() as u16,
+            hour: wall.hour() as u16,
+            minute: wall.minute() as u16,
+            second: wall.second() as u16,
+            milliseconds: (wall.nanosecond() / 1_000_000).min(999) as u16,
+        }
+    }
+
+    /// The wall clock these fields describe, checking that every field is in range - `day_of_week` is ignored, as Windows does when reading a `SYSTEMTIME`
+    pub fn to_naive(&self) -> Result<NaiveDateTime, TimeError> {
+        let year = check("wYear", self.year, 1601, 30827)?;
+        let month = check("wMonth", self.month, 1, 12)?;
+        check("wDayOfWeek", self.day_of_week, 0, 6)?;
+        let date = NaiveDate::from_ymd_opt(year as i32, month, check("wDay", self.day, 1, 31)?).ok_or(TimeError::InvalidField {
+            field: "wDay",
+            value: self.day as i64,
+        })?;
+        let time = NaiveTime::from_hms_milli_opt(
+            check("wHour", self.hour, 0, 23)?,
+            check("wMinute", self.minute, 0, 59)?,
+            check("wSecond", self.second, 0, 59)?,
+            check("wMilliseconds", self.milliseconds, 0, 999)?,
+        )
+        .ok_or(TimeError::Overflow)?;
+        Ok(date.and_time(time))
+    }
+
+    /// Strict mode - the same fields, or an error if `day_of_week` does not match the date
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, SystemTimeParts};
+    /// let parts = SystemTimeParts { year: 2024, month: 1, day_of_week: 1, day: 7, ..Default::default() };
+    /// assert!(System::from_systemtime_parts(parts).is_ok()); // day_of_week is recomputed
+    /// assert!(parts.strict().is_err()); // 2024-01-07 is a Sunday, not a Monday
+    /// ```
+    pub fn strict(self) -> Result<Self, TimeError> {
+        let expected = self.to_naive()?.weekday().num_days_from_sunday() as u16;
+        if self.day_of_week != expected {
+            return Err(TimeError::InvalidField {
+                field: "wDayOfWeek",
+                value: self.day_of_week as i64,
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// internal only - the time object for `parts` read as a wall clock in `offset`
+pub(crate) fn from_parts<T: Time>(parts: SystemTimeParts, offset: i32) -> Result<T, TimeError> {
+    Ok(T::from_wall_clock(parts.to_naive()?, offset))
+}
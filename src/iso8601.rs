@@ -0,0 +1,123 @@
+use crate::parse::Cursor;
+use crate::ParseError;
+use chrono::{NaiveDate, Weekday};
+
+/// Which ISO 8601 date form `Time::iso8601_strict` writes, and `is_valid_iso8601` accepts - all use the extended format (with separators)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum IsoProfile {
+    /// year, month and day, as in "2024-01-05T14:46:29.123+05:30"
+    #[default]
+    Calendar,
+    /// ISO week-numbering year, week and weekday (Monday is 1), as in "2024-W01-5T14:46:29.123+05:30"
+    Week,
+    /// year and day of the year, as in "2024-005T14:46:29.123+05:30"
+    Ordinal,
+}
+
+impl IsoProfile {
+    /// internal only - the strftime format for the date part
+    pub(crate) fn date_format(self) -> &'static str {
+        match self {
+            IsoProfile::Calendar => "%Y-%m-%d",
+            IsoProfile::Week => "%G-W%V-%u",
+            IsoProfile::Ordinal => "%Y-%j",
+        }
+    }
+}
+
+/// internal only - the date part of `profile`, checking that it names a day that exists
+fn date(cur: &mut Cursor, profile: IsoProfile) -> Result<(), ParseError> {
+    let year = cur.digits(4, "a four digit year")? as i32;
+    cur.expect(b'-', "'-'")?;
+    let start = cur.pos;
+    let exists = match profile {
+        IsoProfile::Calendar => {
+            let month = cur.field(2, 1, 12, "month 01-12")?;
+            cur.expect(b'-', "'-'")?;
+            let day = cur.field(2, 1, 31, "day 01-31")?;
+            NaiveDate::from_ymd_opt(year, month, day).is_some()
+        }
+        IsoProfile::Week => {
+            cur.expect(b'W', "'W'")?;
+            let week = cur.field(2, 1, 53, "week 01-53")?;
+            cur.expect(b'-', "'-'")?;
+            let day = cur.field(1, 1, 7, "weekday 1-7")?;
+            let weekday = Weekday::try_from(day as u8 - 1).unwrap_or(Weekday::Mon);
+            NaiveDate::from_isoywd_opt(year, week, weekday).is_some()
+        }
+        IsoProfile::Ordinal => NaiveDate::from_yo_opt(year, cur.field(3, 1, 366, "day of the year 001-366")?).is_some(),
+    };
+    if !exists {
+        return Err(cur.error_at(start, "a day that exists in the year"));
+    }
+    Ok(())
+}
+
+/// internal only - "hh:mm", "hh:mm:ss" or "hh:mm:ss.fff" (the fraction may use a comma, and have any number of digits)
+fn time(cur: &mut Cursor) -> Result<(), ParseError> {
+    cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    cur.field(2, 0, 59, "minute 00-59")?;
+    if cur.eat(b':') {
+        // 60 is a leap second
+        cur.field(2, 0, 60, "second 00-60")?;
+        if (cur.eat(b'.') || cur.eat(b',')) && cur.digit_run().is_empty() {
+            return Err(cur.error("fractional digits"));
+        }
+    }
+    Ok(())
+}
+
+/// internal only - an optional "Z", "+hh" or "+hh:mm" suffix
+fn offset(cur: &mut Cursor) -> Result<(), ParseError> {
+    if cur.eat(b'Z') || !(cur.eat(b'+') || cur.eat(b'-')) {
+        return Ok(());
+    }
+    cur.field(2, 0, 23, "offset hours 00-23")?;
+    if cur.eat(b':') {
+        cur.field(2, 0, 59, "offset minutes 00-59")?;
+    }
+    Ok(())
+}
+
+/// Check that `input` is an ISO 8601 date, or date and time, in the extended format of `profile`, returning the position of the first problem
+///
+/// The date must be zero padded, with a four digit year, and name a day that exists. It can be followed by 'T' and a time of at least
+/// hours and minutes (with optional seconds and fraction), and then an optional "Z", "+hh" or "+hh:mm" offset. Nothing else is
+/// accepted - no space separator, lowercase 't' or 'z', or basic format ("20240105", "+0530")
+///
+/// # Examples
+/// ```rust
+/// use thetime::{validate_iso8601, IsoProfile, ParseError};
+/// assert!(validate_iso8601("2024-W01-5T14:46:29.123+05:30", IsoProfile::Week).is_ok());
+/// assert!(matches!(validate_iso8601("2024-1-5T9:00", IsoProfile::Calendar), Err(ParseError::Invalid { position: 6, .. })));
+/// ```
+pub fn validate_iso8601(input: &str, profile: IsoProfile) -> Result<(), ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(input);
+    date(&mut cur, profile)?;
+    if cur.eat(b'T') {
+        time(&mut cur)?;
+        offset(&mut cur)?;
+    }
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(())
+}
+
+/// Whether `input` is an ISO 8601 date, or date and time, in the extended format of `profile` - see `validate_iso8601` for the rules
+///
+/// # Examples
+/// ```rust
+/// use thetime::{is_valid_iso8601, IsoProfile};
+/// assert!(is_valid_iso8601("2024-01-05T14:46:29.123+05:30", IsoProfile::Calendar));
+/// assert!(is_valid_iso8601("2024-005", IsoProfile::Ordinal));
+/// assert!(!is_valid_iso8601("2024-1-5T9:00", IsoProfile::Calendar));
+/// assert!(!is_valid_iso8601("2024-01-05 14:46:29", IsoProfile::Calendar));
+/// ```
+pub fn is_valid_iso8601(input: &str, profile: IsoProfile) -> bool {
+    validate_iso8601(input, profile).is_ok()
+}
@@ -0,0 +1,225 @@
+use crate::{ImplsDuration, Time, TimeSpan};
+use core::marker::PhantomData;
+
+/// Marks a `TimeItem` that is a time itself
+#[derive(Debug, Copy, Clone)]
+pub struct ByValue;
+
+/// Marks a `TimeItem` that is a reference to a time
+#[derive(Debug, Copy, Clone)]
+pub struct ByRef;
+
+/// A time, or a reference to one - what the iterators `TimeIterExt` works on yield, so that `times.iter()` and `times.into_iter()` both
+/// work. `M` is `ByValue` or `ByRef`, and is always inferred
+pub trait TimeItem<M> {
+    type Time: Time;
+
+    /// Borrow the time
+    fn time(&self) -> &Self::Time;
+
+    /// Take the time, cloning it if this is a reference
+    fn into_time(self) -> Self::Time;
+}
+
+impl<T: Time> TimeItem<ByValue> for T {
+    type Time = T;
+
+    fn time(&self) -> &T {
+        self
+    }
+
+    fn into_time(self) -> T {
+        self
+    }
+}
+
+impl<T: Time + Clone> TimeItem<ByRef> for &T {
+    type Time = T;
+
+    fn time(&self) -> &T {
+        self
+    }
+
+    fn into_time(self) -> T {
+        self.clone()
+    }
+}
+
+/// internal only - `time` moved by `ms` milliseconds, keeping its offset and clamped at 1601 like `Time::add_seconds`
+fn shifted<T: Time>(time: &T, ms: i64) -> T {
+    let raw = (time.raw() as i64).saturating_add(ms).max(0) as u64;
+    T::from_epoch_offset(raw, time.utc_offset())
+}
+
+/// Adaptors for iterators over times (or references to times), for pipelines that would otherwise need a closure at every step
+///
+/// Everything but `min_time` and `max_time` is lazy, and every adaptor yields owned times, so they chain
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, TimeSpan, TimeIterExt, IntTime};
+/// let times = vec![1704465989u32.unix::<System>(), 1704469589u32.unix::<System>(), 1704473189u32.unix::<System>()];
+/// let span = TimeSpan::new(times[0].clone(), times[2].clone());
+/// let shifted: Vec<_> = times.iter().add_each(chrono::Duration::minutes(30)).filter_within(&span).to_offset(3600).collect();
+/// assert_eq!(shifted.iter().map(|t| t.unix()).collect::<Vec<_>>(), [1704467789, 1704471389]);
+/// assert_eq!(shifted[0].pretty(), "2024-01-05 16:16:29");
+/// assert_eq!(times.iter().max_time().unwrap().unix(), 1704473189);
+/// ```
+pub trait TimeIterExt<M>: Iterator + Sized
+where
+    Self::Item: TimeItem<M>,
+{
+    /// Show each time on the wall clock `offset` seconds from UTC, as `Time::change_tz_seconds` (clamped to ±23:59:59)
+    fn to_offset(self, offset: i32) -> ToOffset<Self, M> {
+        ToOffset {
+            iter: self,
+            offset: crate::clamp_offset(offset),
+            kind: PhantomData,
+        }
+    }
+
+    /// Move each time by `duration` - a `chrono::Duration` may be negative. Offsets are kept, and results before 1601 are clamped to it.
+    /// Only whole milliseconds are added
+    fn add_each<D: ImplsDuration>(self, duration: D) -> AddEach<Self, M> {
+        AddEach {
+            iter: self,
+            ms: duration.num_milliseconds(),
+            kind: PhantomData,
+        }
+    }
+
+    /// Move each time that falls outside `span` to the nearest end of it (`start` or `end`), keeping its offset - a span built back to
+    /// front is taken the right way round
+    fn clamp_to<U: Time>(self, span: &TimeSpan<U>) -> ClampTo<Self, M> {
+        ClampTo {
+            iter: self,
+            start: span.start.raw().min(span.end.raw()),
+            end: span.start.raw().max(span.end.raw()),
+            kind: PhantomData,
+        }
+    }
+
+    /// Only the times inside `span` - `start` inclusive, `end` exclusive, as `TimeSpan::contains` (a span built back to front is taken
+    /// the right way round)
+    fn filter_within<U: Time>(self, span: &TimeSpan<U>) -> FilterWithin<Self, M> {
+        FilterWithin {
+            iter: self,
+            start: span.start.raw().min(span.end.raw()),
+            end: span.start.raw().max(span.end.raw()),
+            kind: PhantomData,
+        }
+    }
+
+    /// The earliest time, whatever the offsets - the first of any that tie
+    fn min_time(self) -> Option<<Self::Item as TimeItem<M>>::Time> {
+        self.min_by_key(|t| t.time().raw()).map(TimeItem::into_time)
+    }
+
+    /// The latest time, whatever the offsets - the last of any that tie
+    fn max_time(self) -> Option<<Self::Item as TimeItem<M>>::Time> {
+        self.max_by_key(|t| t.time().raw()).map(TimeItem::into_time)
+    }
+}
+
+impl<I: Iterator, M> TimeIterExt<M> for I where I::Item: TimeItem<M> {}
+
+/// The iterator returned by `TimeIterExt::to_offset`
+#[derive(Debug, Clone)]
+pub struct ToOffset<I, M> {
+    iter: I,
+    offset: i32,
+    kind: PhantomData<fn() -> M>,
+}
+
+impl<I: Iterator, M> Iterator for ToOffset<I, M>
+where
+    I::Item: TimeItem<M>,
+{
+    type Item = <I::Item as TimeItem<M>>::Time;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|t| t.time().change_tz_seconds(self.offset))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The iterator returned by `TimeIterExt::add_each`
+#[derive(Debug, Clone)]
+pub struct AddEach<I, M> {
+    iter: I,
+    ms: i64,
+    kind: PhantomData<fn() -> M>,
+}
+
+impl<I: Iterator, M> Iterator for AddEach<I, M>
+where
+    I::Item: TimeItem<M>,
+{
+    type Item = <I::Item as TimeItem<M>>::Time;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|t| shifted(t.time(), self.ms))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The iterator returned by `TimeIterExt::clamp_to`
+#[derive(Debug, Clone)]
+pub struct ClampTo<I, M> {
+    iter: I,
+    start: u64,
+    end: u64,
+    kind: PhantomData<fn() -> M>,
+}
+
+impl<I: Iterator, M> Iterator for ClampTo<I, M>
+where
+    I::Item: TimeItem<M>,
+{
+    type Item = <I::Item as TimeItem<M>>::Time;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.iter.next()?;
+        let raw = t.time().raw();
+        Some(if raw < self.start || raw > self.end {
+            <Self::Item as Time>::from_epoch_offset(raw.clamp(self.start, self.end), t.time().utc_offset())
+        } else {
+            t.into_time()
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// The iterator returned by `TimeIterExt::filter_within`
+#[derive(Debug, Clone)]
+pub struct FilterWithin<I, M> {
+    iter: I,
+    start: u64,
+    end: u64,
+    kind: PhantomData<fn() -> M>,
+}
+
+impl<I: Iterator, M> Iterator for FilterWithin<I, M>
+where
+    I::Item: TimeItem<M>,
+{
+    type Item = <I::Item as TimeItem<M>>::Time;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end) = (self.start, self.end);
+        self.iter.find(|t| (start..end).contains(&t.time().raw())).map(TimeItem::into_time)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
@@ -4,7 +4,14 @@ pub mod ntp;
 /// re-exported for easier access (no `use thetime::system::System;`, just `use thetime::System;`)
 pub mod system;
 
-/// Timezones - a list of common timezones
+/// NTP packets, as sent and received by the `Ntp` client
+pub mod ntp_packet;
+
+/// Resolving and choosing NTP servers - selection strategies, SRV records and pluggable resolvers (enabled with the `ntp-client` feature)
+#[cfg(feature = "ntp-client")]
+pub mod ntp_server;
+
+/// Timezones - a list of common timezones (enabled with the `timezones` feature, on by default)
 /// Note: some names clash, examples Arabia Standard Time (AST) and Atlantic Standard Time (ATST), so we lengthen as shown above
 /// 
 /// # Examples
@@ -13,27 +20,321 @@ pub mod system;
 /// println!("{}", Tz::UtcWet);
 /// println!("{}", Tz::BstCet);
 /// ```
+#[cfg(feature = "timezones")]
 pub mod timezones;
 
-pub mod epoch {
-    pub const UNIX: &str = "1970-01-01 00:00:00";
-    pub const WINDOWS_NT: &str = "1601-01-01 00:00:00";
-    pub const WEBKIT: &str = "1601-01-01 00:00:00";
-    pub const MAC_OS: &str = "1904-01-01 00:00:00";
-    pub const MAC_OS_CFA: &str = "2001-01-01 00:00:00";
-    pub const SAS_4GL: &str = "1960-01-01 00:00:00";
-}
+/// Error types shared by the fallible parts of the library
+pub mod errors;
+
+/// Spans of time between two time objects
+pub mod span;
+
+/// Times before 1601, for historical dates
+pub mod extended;
+
+/// Pre-parsed strftime format strings, for formatting many times with the same format
+pub mod format;
+
+/// Time objects paired with the timezone they should be viewed in (enabled with the `timezones` feature)
+#[cfg(feature = "timezones")]
+pub mod zoned;
+
+/// Bucketed downsampling of timestamped samples
+pub mod downsample;
+
+/// Month grids and `cal`-style month text, for calendar UIs
+pub mod calendar;
+
+/// Parsing times straight from byte slices, without UTF-8 validating them first
+pub mod bytes;
+
+/// Merging several time-sorted streams into one, in global order
+pub mod merge;
+
+/// Reading the local and NTP clocks together, for audit records
+pub mod snapshot;
+
+/// Month and weekday names in other languages, for `strftime_with_names`
+pub mod names;
+
+/// Iterators of evenly spaced times
+pub mod steps;
+
+/// ISO week identifiers, such as `2024-W05`
+pub mod week;
+
+/// Daylight saving rules, and resolving local times that they skip or repeat
+pub mod dst;
+
+/// Evaluating date math expressions such as `now + 90m`
+pub mod expr;
+
+/// Calendar-relative descriptions such as "yesterday" or "next week", and the phrases they are built from
+pub mod relative;
+
+/// Offset, jitter and delay statistics over repeated NTP samples
+pub mod ntp_stats;
+
+/// Saving the last good NTP time to disk, for an estimate of the time before the network is up
+pub mod ntp_cache;
+
+/// Deadlines and time budgets for request handling
+pub mod budget;
+
+/// Strict ISO 8601 profiles and validation
+pub mod iso8601;
+
+/// Injectable sources of the current time
+pub mod clock;
+
+/// Yearly anniversaries, and what happens to Feb 29 in other years
+pub mod anniversary;
+
+/// Rounding to "nice" boundaries, and tick marks for chart axes
+pub mod ticks;
+
+/// Month arithmetic with end-of-month policies, and business day adjustment
+pub mod months;
+
+/// Ordering and range checks on the time of day alone
+pub mod time_of_day;
+
+/// Drop-in versions of C's `time`, `gmtime`, `localtime`, `mktime`, `strftime` and `strptime`, for porting C code
+pub mod libc_compat;
+
+/// Recurring schedules, sleep hints, and what to run after missed occurrences
+pub mod schedule;
+
+/// A frozen byte encoding of an instant, for signing and hashing
+pub mod canonical;
+
+/// Reading and writing the `Retry-After`, `Age`, `Expires` and `Date` HTTP headers
+pub mod http;
+
+/// A day taken from one reading of the clock, so its start and end can't straddle midnight
+pub mod today;
 
-use chrono::Local;
+/// Media player style durations - "1:05:09" and "05:09"
+pub mod hms;
+
+/// Checking that a stream of times only ever increases
+pub mod monotonic;
+
+/// Certificate and token validity windows, with leeway for clock skew
+pub mod validity;
+
+/// Parsing timestamps from a stream that arrives in chunks
+pub mod streaming;
+
+/// Adaptors for iterators over times
+pub mod iter_ext;
+
+/// Durations measured partly on the calendar - "1 month and 3 days"
+pub mod calendar_duration;
+
+/// Reporting what conversions to coarser representations drop
+pub mod precision;
+
+/// Options for the parsers that can check a date's weekday and ranges
+pub mod parse_options;
+
+/// Hybrid logical clocks, for ordering events across services
+pub mod hlc;
+
+/// Ops shorthand for deadlines ("EOD", "COB Friday", "EOM"), resolved to instants
+pub mod business;
+
+/// Query many NTP servers at once, and report which agree and which are falsetickers
+pub mod ntp_survey;
+
+/// Conversions to and from other date and time crates, each behind a feature of the same name
+pub mod interop;
+
+#[cfg(feature = "serde")]
+mod wire;
+
+/// Serde helpers that keep large timestamps exact through JSON, by writing them as strings (enabled with the `serde` feature)
+#[cfg(feature = "serde")]
+pub mod serde;
+
+mod parse;
+
+/// Setting the OS clock from a time object (enabled with the `sysclock` feature)
+#[cfg(feature = "sysclock")]
+pub mod sysclock;
+
+/// Sunrise, sunset and solar noon for a latitude/longitude (enabled with the `solar` feature)
+#[cfg(feature = "solar")]
+pub mod solar;
+
+/// A per-thread mock clock for tests (enabled with the `testing` feature)
+#[cfg(feature = "testing")]
+pub mod mock;
+
+/// Assertion macros for comparing time objects in tests (enabled with the `test-util` feature)
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+/// Coarsening, jittering and shifting timestamps for sharing logs without exact times
+pub mod privacy;
+
+/// Build timestamps - `SOURCE_DATE_EPOCH` at runtime, and the `build_time!()` macro at compile time
+pub mod buildtime;
+
+/// Sleeping until a time object, on threads or (with the `tokio` feature) tokio
+pub mod sleep;
+
+/// Helpers for process timing - the app start time and uptime, parsing `ps` elapsed times and `/proc` start times
+pub mod process;
+
+/// The epochs this crate supports, and identifying which one a mystery timestamp uses
+pub mod epoch;
+
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, NaiveTime, TimeZone, Timelike};
 /// export the ntp file for easier access
 pub use ntp::*;
 
 /// export the system file for easier access
 pub use system::*;
 
+/// export the ntp_packet file for easier access
+pub use ntp_packet::*;
+
+/// export the ntp_server file for easier access
+#[cfg(feature = "ntp-client")]
+pub use ntp_server::*;
+
 // export the timezones file for easier access
+#[cfg(feature = "timezones")]
 pub use timezones::*;
 
+/// export the errors file for easier access
+pub use errors::*;
+
+/// export the process file for easier access
+pub use process::*;
+
+/// export the span file for easier access
+pub use span::*;
+
+/// export the extended file for easier access
+pub use extended::*;
+
+/// export the format file for easier access
+pub use format::*;
+
+/// export the zoned file for easier access
+#[cfg(feature = "timezones")]
+pub use zoned::*;
+
+/// export the downsample file for easier access
+pub use downsample::*;
+
+/// export the calendar file for easier access
+pub use calendar::*;
+
+/// export the bytes file for easier access
+pub use bytes::*;
+
+/// export the merge file for easier access
+pub use merge::*;
+
+/// export the snapshot file for easier access
+pub use snapshot::*;
+
+/// export the names file for easier access
+pub use names::*;
+
+/// export the steps file for easier access
+pub use steps::*;
+
+/// export the week file for easier access
+pub use week::*;
+
+/// export the dst file for easier access
+pub use dst::*;
+
+/// export the expr file for easier access
+pub use expr::*;
+
+/// export the relative file for easier access
+pub use relative::*;
+
+/// export the ntp_stats file for easier access
+pub use ntp_stats::*;
+
+/// export the ntp_cache file for easier access
+pub use ntp_cache::*;
+
+/// export the budget file for easier access
+pub use budget::*;
+
+/// export the iso8601 file for easier access
+pub use iso8601::*;
+
+/// export the clock file for easier access
+pub use clock::*;
+
+/// export the anniversary file for easier access
+pub use anniversary::*;
+
+/// export the ticks file for easier access
+pub use ticks::*;
+
+/// export the months file for easier access
+pub use months::*;
+
+/// export the time_of_day file for easier access
+pub use time_of_day::*;
+
+/// export the schedule file for easier access
+pub use schedule::*;
+
+/// export the canonical file for easier access
+pub use canonical::*;
+
+/// export the today file for easier access
+pub use today::*;
+
+/// export the hms file for easier access
+pub use hms::*;
+
+/// export the monotonic file for easier access
+pub use monotonic::*;
+
+/// export the validity file for easier access
+pub use validity::*;
+
+/// export the streaming file for easier access
+pub use streaming::*;
+
+/// export the iter_ext file for easier access
+pub use iter_ext::*;
+
+/// export the calendar_duration file for easier access
+pub use calendar_duration::*;
+
+/// export the precision file for easier access
+pub use precision::*;
+
+/// export the parse_options file for easier access
+pub use parse_options::*;
+
+/// export the hlc file for easier access
+pub use hlc::*;
+
+/// export the business file for easier access
+pub use business::*;
+
+/// export the ntp_survey file for easier access
+pub use ntp_survey::*;
+
+/// export the Windows `SYSTEMTIME` struct for easier access
+pub use interop::windows::SystemTimeParts;
+
+/// re-exported so that week based helpers can be used without depending on chrono directly
+pub use chrono::Weekday;
+
 /// Reference time
 pub const REF_TIME_1970: u64 = 2208988800;
 
@@ -48,6 +349,20 @@ pub const MAGIC_MAC_OS: i64 = 2082844800;
 
 /// Magic number for Macos Absolute epoch (offset between 2001 and 1970)
 pub const MAGIC_MAC_OS_CFA: i64 = 978307200;
+
+/// The largest UTC offset (either way) a time object holds, 18 hours - larger offsets are clamped to this
+pub const MAX_UTC_OFFSET: i32 = 18 * 3600;
+
+/// How far from now `Time::log_stamp` describes a time before saturating, 30 days
+pub const LOG_STAMP_HORIZON: core::time::Duration = core::time::Duration::from_secs(30 * 86_400);
+
+/// internal only - `9999-12-31 23:59:59.999` UTC as Unix milliseconds, the value of `Time::far_future`
+pub(crate) const FAR_FUTURE_UNIX_MS: i64 = 253402300799999;
+
+/// internal only - clamps an offset to `±MAX_UTC_OFFSET`
+pub(crate) fn clamp_offset(offset: i32) -> i32 {
+    offset.clamp(-MAX_UTC_OFFSET, MAX_UTC_OFFSET)
+}
 /// Returns the current time in seconds since Unix epoch
 ///
 /// # Examples
@@ -56,7 +371,63 @@ pub const MAGIC_MAC_OS_CFA: i64 = 978307200;
 /// println!("{} seconds since Unix epoch", now());
 /// ```
 pub fn now() -> i64 {
-    System::now().unix()
+    System::now().unix_ms().div_euclid(1000)
+}
+
+/// Returns the current time in milliseconds since Unix epoch
+///
+/// # Examples
+/// ```rust
+/// use thetime::now_ms;
+/// println!("{} milliseconds since Unix epoch", now_ms());
+/// ```
+pub fn now_ms() -> i64 {
+    System::now().unix_ms()
+}
+
+/// Returns the current time in microseconds since Unix epoch (at millisecond resolution, like `System`)
+///
+/// # Examples
+/// ```rust
+/// use thetime::now_us;
+/// println!("{} microseconds since Unix epoch", now_us());
+/// ```
+pub fn now_us() -> i64 {
+    System::now().unix_ms() * 1000
+}
+
+/// Returns the current time in 100ns chunks since Windows epoch, like `Time::windows_ns`
+///
+/// # Examples
+/// ```rust
+/// use thetime::now_windows_ns;
+/// println!("{} 100ns chunks since Windows epoch", now_windows_ns());
+/// ```
+pub fn now_windows_ns() -> i64 {
+    System::now().windows_ns()
+}
+
+/// Returns the current time as an RFC 3339 string in UTC, eg `2024-01-05T14:46:29.123Z`
+///
+/// # Examples
+/// ```rust
+/// use thetime::now_rfc3339;
+/// assert!(now_rfc3339().ends_with('Z'));
+/// ```
+pub fn now_rfc3339() -> String {
+    System::now().rfc3339()
+}
+
+/// Returns the current time in the timezone `tz`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{now_in, Time, Tz};
+/// assert_eq!(now_in(Tz::Ist).utc_offset(), 19800);
+/// ```
+#[cfg(feature = "timezones")]
+pub fn now_in(tz: Tz) -> System {
+    System::now().change_tz_seconds(tz.offset())
 }
 
 /// An enum to represent whether a time is in the past, present or future
@@ -77,6 +448,114 @@ impl core::fmt::Display for RelativeTime {
     }
 }
 
+/// Where the offset of a time object came from - parsed strings without an offset have one assumed for them, which downstream code may want to warn about
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum OffsetSource {
+    /// the offset was known - parsed from the input, or given by the constructor
+    #[default]
+    Explicit,
+    /// the input had no offset, so UTC was assumed (plain `strptime`)
+    AssumedUtc,
+    /// the input had no offset, so the system's local offset was assumed (`strptime_local`)
+    AssumedLocal,
+    /// the input had no offset, so the caller's default was assumed (`strptime_with_default_offset`)
+    AssumedDefault,
+}
+
+/// The calendar units `Time::truncate_to` can round down to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum TruncateUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    Month,
+    Year,
+}
+
+/// The calendar boundaries `Time::crossed_boundary` and `Time::boundaries_crossed` look for: the top of each minute or hour, midnight,
+/// Monday midnight (ISO weeks), the first of the month or New Year
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum BoundaryUnit {
+    Minute,
+    Hour,
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    Month,
+    Year,
+}
+
+impl BoundaryUnit {
+    /// internal only - which unit a Unix time in milliseconds falls in, counted from 1970 on the wall clock `offset` seconds from UTC
+    fn index(self, unix_ms: i64, offset: i32) -> i64 {
+        let local = unix_ms.saturating_add(offset as i64 * 1000);
+        let days = local.div_euclid(86_400_000);
+        let date = || DateTime::from_timestamp_millis(local).map(|d| d.date_naive());
+        match self {
+            BoundaryUnit::Minute => local.div_euclid(60_000),
+            BoundaryUnit::Hour => local.div_euclid(3_600_000),
+            BoundaryUnit::Day => days,
+            // 1970-01-01 was a Thursday, so weeks start 3 days before it
+            BoundaryUnit::Week => (days + 3).div_euclid(7),
+            BoundaryUnit::Month => date().map_or(0, |d| d.year() as i64 * 12 + d.month0() as i64),
+            BoundaryUnit::Year => date().map_or(0, |d| d.year() as i64),
+        }
+    }
+}
+
+/// internal only - parses `s`, falling back to `default_offset` (or the local timezone if None) when the format has no offset
+///
+/// Returns the milliseconds since the Unix epoch, the offset in seconds, and where the offset came from
+pub(crate) fn parse_with_default_offset(s: &str, format: &str, default_offset: Option<i32>, assumed: OffsetSource) -> Result<(i64, i32, OffsetSource), chrono::ParseError> {
+    let mut parsed = chrono::format::Parsed::new();
+    chrono::format::parse(&mut parsed, s, chrono::format::StrftimeItems::new(format))?;
+    resolve_parsed(&parsed, format, default_offset, assumed)
+}
+
+/// internal only - `parse_with_default_offset`, telling an invalid format apart from input that doesn't match it
+pub(crate) fn try_parse_with_default_offset(s: &str, format: &str, default_offset: Option<i32>, assumed: OffsetSource) -> Result<(i64, i32, OffsetSource), ParseTimeError> {
+    // checked first, as chrono only notices a bad specifier once the input before it has matched
+    if chrono::format::StrftimeItems::new(format).any(|item| item == chrono::format::Item::Error) {
+        return Err(ParseTimeError::InvalidFormat { input: s.to_string(), format: format.to_string() });
+    }
+    parse_with_default_offset(s, format, default_offset, assumed).map_err(|e| ParseTimeError::Mismatch {
+        input: s.to_string(),
+        format: format.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// internal only - turn parsed fields into Unix milliseconds, an offset and where it came from, using `default_offset` (or the local timezone if None) when there is no offset
+pub(crate) fn resolve_parsed(parsed: &chrono::format::Parsed, format: &str, default_offset: Option<i32>, assumed: OffsetSource) -> Result<(i64, i32, OffsetSource), chrono::ParseError> {
+    let (dt, source) = match parsed.to_datetime() {
+        Ok(dt) => (dt, OffsetSource::Explicit),
+        Err(e) if format.contains("%z") => return Err(e),
+        Err(_) => {
+            let naive = parsed.to_naive_datetime_with_offset(0)?;
+            let offset = match default_offset {
+                Some(secs) => FixedOffset::east_opt(secs).unwrap_or(FixedOffset::east_opt(0).unwrap()),
+                None => Local
+                    .from_local_datetime(&naive)
+                    .earliest()
+                    .map(|x| *x.offset())
+                    .unwrap_or(FixedOffset::east_opt(0).unwrap()),
+            };
+            let dt = offset
+                .from_local_datetime(&naive)
+                .single()
+                .unwrap_or_else(|| naive.and_utc().fixed_offset());
+            (dt, assumed)
+        }
+    };
+    Ok((dt.timestamp_millis(), dt.offset().local_minus_utc(), source))
+}
+
 /// Implements the core functionality of the library
 /// 
 /// The conversion methods from struct to various timestamps do support negatives where needed (everything but `windows_ns` as it uses the same epoch as we do)
@@ -93,23 +572,121 @@ pub trait Time {
     fn now() -> Self;
 
     /// Parse a string into a time struct
+    /// 
+    /// If the format has no offset (`%z`), the time is assumed to be in UTC - `offset_source()` then returns `OffsetSource::AssumedUtc` so that this can be detected
+    ///
+    /// # Panics
+    /// If the format is invalid or the input doesn't match it - use `try_strptime` for input that comes from users
     ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
     /// println!("The time was {}", System::strptime("2015-01-18 23:16:09", "%Y-%m-%d %H:%M:%S"));
     /// ```
-    fn strptime<T: ToString, G: ToString>(s: T, format: G) -> Self;
+    fn strptime<T: ToString, G: ToString>(s: T, format: G) -> Self
+    where Self: Sized {
+        Self::strptime_assuming(s, format, Some(0), OffsetSource::AssumedUtc)
+    }
+
+    /// Parse a string into a time struct, assuming the given offset (in seconds from UTC) if the string has none
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, OffsetSource};
+    /// let x = System::strptime_with_default_offset("2015-01-18 23:16:09", "%Y-%m-%d %H:%M:%S", 3600);
+    /// assert_eq!(x.unix(), 1421622969 - 3600);
+    /// assert_eq!(x.offset_source(), OffsetSource::AssumedDefault);
+    /// ```
+    fn strptime_with_default_offset<T: ToString, G: ToString>(s: T, format: G, offset_secs: i32) -> Self
+    where Self: Sized {
+        Self::strptime_assuming(s, format, Some(offset_secs), OffsetSource::AssumedDefault)
+    }
+
+    /// Parse a string into a time struct, assuming the system's local timezone (at that date) if the string has no offset
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::strptime_local("2015-01-18 23:16:09", "%Y-%m-%d %H:%M:%S"));
+    /// ```
+    fn strptime_local<T: ToString, G: ToString>(s: T, format: G) -> Self
+    where Self: Sized {
+        Self::strptime_assuming(s, format, None, OffsetSource::AssumedLocal)
+    }
+
+    /// Parse a string into a time struct as `strptime` does, but returning an error rather than panicking when the format is invalid or
+    /// the input doesn't match it - for input that comes from users
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, ParseTimeError};
+    /// assert_eq!(System::try_strptime("2015-01-18 23:16:09", "%Y-%m-%d %H:%M:%S").unwrap().unix(), 1421622969);
+    /// assert!(matches!(System::try_strptime("garbage", "%Y-%m-%d"), Err(ParseTimeError::Mismatch { .. })));
+    /// assert!(matches!(System::try_strptime("2015-01-18", "%Y-%m-%d %Q"), Err(ParseTimeError::InvalidFormat { .. })));
+    /// ```
+    fn try_strptime<T: ToString, G: ToString>(s: T, format: G) -> Result<Self, ParseTimeError>
+    where Self: Sized {
+        Self::try_strptime_assuming(s, format, Some(0), OffsetSource::AssumedUtc)
+    }
+
+    /// internal only - parse a string, using `default_offset` (or the local timezone if None) when it has no offset, and recording `assumed` as the source
+    #[doc(hidden)]
+    fn strptime_assuming<T: ToString, G: ToString>(s: T, format: G, default_offset: Option<i32>, assumed: OffsetSource) -> Self
+    where Self: Sized {
+        match Self::try_strptime_assuming(s, format, default_offset, assumed) {
+            Ok(x) => x,
+            Err(_) => panic!("Bad format string"),
+        }
+    }
+
+    /// internal only - `strptime_assuming`, returning an error rather than panicking
+    #[doc(hidden)]
+    fn try_strptime_assuming<T: ToString, G: ToString>(s: T, format: G, default_offset: Option<i32>, assumed: OffsetSource) -> Result<Self, ParseTimeError>
+    where Self: Sized;
+
+    /// internal only - the same time object, recording `source` as where its offset came from
+    #[doc(hidden)]
+    fn with_offset_source(self, _source: OffsetSource) -> Self
+    where Self: Sized {
+        self
+    }
+
+    /// Where the offset of this time object came from - whether it was explicit or assumed while parsing
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, OffsetSource};
+    /// assert_eq!(System::strptime("2015-01-18 23:16:09", "%Y-%m-%d %H:%M:%S").offset_source(), OffsetSource::AssumedUtc);
+    /// assert_eq!(System::strptime("2015-01-18 23:16:09 +0100", "%Y-%m-%d %H:%M:%S %z").offset_source(), OffsetSource::Explicit);
+    /// ```
+    fn offset_source(&self) -> OffsetSource;
     /// Get the time in seconds since Unix epoch
     ///
+    /// The milliseconds are dropped (rounding down) - `unix_checked` reports them, and with the `strict-conversions` feature this
+    /// `debug_assert`s that there are none
+    ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
-    /// println!("{} seconds since Unix epoch", System::now().unix());
-    /// println!("{} seconds since Unix epoch from pool.ntp.org", System::now().unix());
+    /// let x = System::strptime("2024-01-05 14:46:29", "%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.unix(), 1704465989);
     /// ```
     fn unix(&self) -> i64;
 
+    /// As `unix`, along with the milliseconds it drops
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// let (secs, loss) = 1704465989u32.unix::<System>().unix_checked();
+    /// assert_eq!(secs, 1704465989);
+    /// assert!(loss.is_lossless());
+    /// ```
+    fn unix_checked(&self) -> (i64, PrecisionLoss) {
+        let raw = self.raw();
+        ((raw / 1000) as i64 - OFFSET_1601 as i64, PrecisionLoss { discarded_ms: (raw % 1000) as u32 })
+    }
+
     /// Get the time in milliseconds since Unix epoch
     ///
     /// # Examples
@@ -118,6 +695,8 @@ pub trait Time {
     /// println!("{} milliseconds since Unix epoch", System::now().unix_ms());
     /// println!("{} milliseconds since Unix epoch from pool.ntp.org", System::now().unix_ms());
     /// ```
+    /// 
+    /// Saturates at `i64::MAX` for times too far in the future to fit (past the year 292 million)
     fn unix_ms(&self) -> i64;
 
     /// Gets the time in nanoseconds (approximate) since Windows epoch (`1601-01-01 00:00:00`)
@@ -128,8 +707,22 @@ pub trait Time {
     /// println!("{} nanoseconds since Windows epoch", System::now().windows_ns());
     /// println!("{} nanoseconds since Windows epoch from pool.ntp.org", System::now().windows_ns());
     /// ```
+    /// 
+    /// Saturates at `i64::MAX` for times too far in the future to fit (past the year 30828) - use `try_windows_ns` to detect this
     fn windows_ns(&self) -> i64 {
-        ((self.epoch() as f64) * 1e4) as i64
+        self.try_windows_ns().unwrap_or(i64::MAX)
+    }
+
+    /// Gets the time in 100ns chunks since Windows epoch (`1601-01-01 00:00:00`), or an error if it does not fit in an `i64`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{:?}", System::now().try_windows_ns());
+    /// assert!(System::strptime("+262142-01-01 00:00:00", "%Y-%m-%d %H:%M:%S").try_windows_ns().is_err());
+    /// ```
+    fn try_windows_ns(&self) -> Result<i64, TimeError> {
+        i64::try_from(self.try_epoch()? as i128 * 10_000).map_err(|_| TimeError::Overflow)
     }
 
     /// Gets the time in microseconds (approximate) since Webkit epoch (`1601-01-01 00:00:00`)
@@ -140,8 +733,10 @@ pub trait Time {
     /// println!("{} microseconds since Webkit epoch", System::now().webkit());
     /// println!("{} microseconds since Webkit epoch from pool.ntp.org", System::now().webkit());
     /// ```
+    /// 
+    /// Saturates at `i64::MAX` for times too far in the future to fit
     fn webkit(&self) -> i64 {
-        ((self.epoch() as f64) * 1e3) as i64
+        i64::try_from(self.epoch() as i128 * 1000).unwrap_or(i64::MAX)
     }
 
     /// Get the time in seconds since the Mac OS epoch (1904-01-01 00:00:00)
@@ -149,11 +744,22 @@ pub trait Time {
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
-    /// println!("{} seconds since Mac OS epoch", System::now().mac_os());
-    /// println!("{} seconds since Mac OS epoch from pool.ntp.org", System::now().mac_os());
+    /// let x = System::strptime("2024-01-05 14:46:29", "%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.mac_os(), 3787310789);
     /// ```
+    ///
+    /// The milliseconds are dropped - `mac_os_checked` reports them, and with the `strict-conversions` feature this `debug_assert`s that
+    /// there are none
     fn mac_os(&self) -> i64 {
-        self.unix() + MAGIC_MAC_OS
+        let (secs, loss) = self.mac_os_checked();
+        precision::strict(loss, "mac_os");
+        secs
+    }
+
+    /// As `mac_os`, along with the milliseconds it drops
+    fn mac_os_checked(&self) -> (i64, PrecisionLoss) {
+        let (secs, loss) = self.unix_checked();
+        (secs + MAGIC_MAC_OS, loss)
     }
 
     /// Get the time in seconds since the Mac OS Absolute epoch (2001-01-01 00:00:00)
@@ -165,7 +771,20 @@ pub trait Time {
     /// println!("{} seconds since Mac OS Absolute epoch from pool.ntp.org", System::now().mac_os_cfa());
     /// ```
     fn mac_os_cfa(&self) -> i64 {
-        self.unix() - MAGIC_MAC_OS_CFA
+        self.unix_checked().0 - MAGIC_MAC_OS_CFA
+    }
+
+    /// Get the time as fractional seconds since the Mac OS Absolute epoch (2001-01-01 00:00:00), as stored by `CFAbsoluteTime` and Core Data
+    ///
+    /// Unlike `mac_os_cfa`, this keeps the milliseconds, and is negative before 2001
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, FloatTime};
+    /// assert_eq!(726158877.5f64.cf_absolute::<System>().cf_absolute(), 726158877.5);
+    /// ```
+    fn cf_absolute(&self) -> f64 {
+        (self.unix_ms() - MAGIC_MAC_OS_CFA * 1000) as f64 / 1000.0
     }
 
     /// Get the time in seconds since the SAS 4GL epoch (1960-01-01 00:00:00)
@@ -173,12 +792,24 @@ pub trait Time {
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
-    /// println!("{} seconds since SAS 4GL epoch", System::now().sas_4gl());
-    /// println!("{} seconds since SAS 4GL epoch from pool.ntp.org", System::now().sas_4gl());
+    /// let x = System::strptime("2024-01-05 14:46:29", "%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.sas_4gl(), 2020085189);
     /// ```
+    ///
+    /// The milliseconds are dropped - `sas_4gl_checked` reports them, and with the `strict-conversions` feature this `debug_assert`s that
+    /// there are none
     fn sas_4gl(&self) -> i64 {
-        self.unix() + MAGIC_SAS_4GL
+        let (secs, loss) = self.sas_4gl_checked();
+        precision::strict(loss, "sas_4gl");
+        secs
+    }
+
+    /// As `sas_4gl`, along with the milliseconds it drops
+    fn sas_4gl_checked(&self) -> (i64, PrecisionLoss) {
+        let (secs, loss) = self.unix_checked();
+        (secs + MAGIC_SAS_4GL, loss)
     }
+
     /// Format the time according to the given format string
     ///
     /// # Examples
@@ -189,664 +820,6354 @@ pub trait Time {
     /// ```
     fn strftime(&self, format: &str) -> String;
 
-    /// Get the time since the epoch we use (`1601-01-01 00:00:00`). we use this for full compataibility with Windows
+    /// Format the time object using a pre-parsed `CompiledFormat` - the same output as `strftime`, without re-parsing the format string each call
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{} milliseconds since the epoch we use", System::now().epoch());
-    /// println!("{} milliseconds since the epoch we use from pool.ntp.org", System::now().epoch());
+    /// use thetime::{CompiledFormat, System, Time};
+    /// let fmt = CompiledFormat::new("%Y-%m-%d %H:%M:%S").unwrap();
+    /// println!("{}", System::now().strftime_compiled(&fmt));
     /// ```
-    fn epoch(&self) -> i64 {
-        self.unix_ms() + (OFFSET_1601 as i64 * 1000i64)
+    fn strftime_compiled(&self, format: &CompiledFormat) -> String {
+        self.format_items(format.items())
     }
 
-    /// pretty print the time object
+    /// Format the time object with a format string checked by `FormatString::parse_untrusted` - safe for formats that come from users, as
+    /// it never panics, and the output is bounded by the format's length (and empty for a time too far out for chrono to represent)
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time, StrTime};
-    /// let date2017 = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-    /// println!("2017 - {}", date2017.pretty());
-    /// assert_eq!(date2017.pretty(), "2017-01-01 00:00:00");
+    /// use thetime::{FormatString, System, Time, IntTime};
+    /// let fmt = FormatString::parse_untrusted("%d/%m/%Y %H:%M %:z").unwrap();
+    /// assert_eq!(1704465989u32.unix::<System>().strftime_checked(&fmt), "05/01/2024 14:46 +00:00");
     /// ```
-    fn pretty(&self) -> String {
-        self.strftime("%Y-%m-%d %H:%M:%S")
+    fn strftime_checked(&self, format: &FormatString) -> String {
+        format::format_checked(self, format.compiled().items())
     }
 
-    /// Don't use this method, it's for internal use only (for instantiating structs from timestamps using the `1601-01-01 00:00:00` epoch)
-    #[doc(hidden)]
-    fn from_epoch(timestamp: u64) -> Self;
-
-    /// Don't use this method, it's for internal use only (returns raw struct ms)
-    #[doc(hidden)]
-    fn raw(&self) -> u64;
-
-    /// Returns the date formatted in ISO8601 format
+    /// Format the time object like `strftime`, but with month and weekday names (`%B`, `%b`/`%h`, `%A` and `%a`) taken from `names`
+    ///
+    /// Everything else is formatted as usual - including `%c` and friends, which stay in English
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().iso8601());
-    /// println!("{}", System::now().iso8601());
+    /// use thetime::{NameTable, System, Time, IntTime};
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert_eq!(x.strftime_with_names("%A %-d %B %Y", &NameTable::DE), "Freitag 5 Januar 2024");
+    /// assert_eq!(x.strftime_with_names("%a %d %b", &NameTable::FR), "ven. 05 janv.");
     /// ```
-    fn iso8601(&self) -> String {
-        self.strftime("%Y-%m-%d %H:%M:%S.") + &(self.raw() % 1000).to_string()
+    fn strftime_with_names(&self, format: &str, names: &NameTable) -> String {
+        use chrono::format::{Fixed, Item, StrftimeItems};
+        let wall = self.wall_clock();
+        let month = wall.month();
+        let weekday = wall.weekday().num_days_from_monday();
+        let items: Vec<Item> = StrftimeItems::new(format)
+            .map(|item| {
+                let name = match item {
+                    Item::Fixed(Fixed::LongMonthName) => names.month(month),
+                    Item::Fixed(Fixed::ShortMonthName) => names.month_short(month),
+                    Item::Fixed(Fixed::LongWeekdayName) => names.weekday(weekday),
+                    Item::Fixed(Fixed::ShortWeekdayName) => names.weekday_short(weekday),
+                    _ => None,
+                };
+                name.map_or(item, Item::Literal)
+            })
+            .collect();
+        self.format_items(items.iter())
     }
 
-    /// Returns the date formatted in RFC3339 format
-    ///
-    /// # Examples
-    /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().rfc3339());
-    /// println!("{}", System::now().rfc3339());
-    /// ```
-    fn rfc3339(&self) -> String {
-        self.strftime("%Y-%m-%dT%H:%M:%S.") + &(self.raw() % 1000).to_string() + "Z"
+    /// internal only - renders the wall clock with parsed format items, carrying the offset so that `%z` works
+    /// 
+    /// Offsets chrono cannot represent (a day or more) fall back to the bare wall clock, without `%z` support
+    #[doc(hidden)]
+    fn format_items<'a, I, B>(&self, items: I) -> String
+    where
+        I: Iterator<Item = B> + Clone,
+        B: core::borrow::Borrow<chrono::format::Item<'a>>,
+    {
+        match FixedOffset::east_opt(self.utc_offset()) {
+            Some(offset) => offset.from_local_datetime(&self.wall_clock()).unwrap().format_with_items(items).to_string(),
+            None => self.wall_clock().format_with_items(items).to_string(),
+        }
     }
 
-    /// internal only
+    /// internal only - as `format_items`, appending to `out` (usually a `String`) rather than allocating a new string
     #[doc(hidden)]
-    fn utc_offset(&self) -> i32;
+    fn format_items_into<'a, I, B, W>(&self, items: I, out: &mut W) -> core::fmt::Result
+    where
+        I: Iterator<Item = B> + Clone,
+        B: core::borrow::Borrow<chrono::format::Item<'a>>,
+        W: core::fmt::Write + ?Sized,
+    {
+        // chrono allocates the offset's name up front, so only carry the offset when the format uses it
+        let offset = FixedOffset::east_opt(self.utc_offset()).filter(|_| items.clone().any(|item| format::needs_offset(item.borrow())));
+        match offset {
+            Some(offset) => offset.from_local_datetime(&self.wall_clock()).unwrap().format_with_items(items).write_to(out),
+            None => self.wall_clock().format_with_items(items).write_to(out),
+        }
+    }
 
-    /// Gets the timezone offset in the format HH:MM
-    /// 
+    /// Get the time since the epoch we use (`1601-01-01 00:00:00`). we use this for full compataibility with Windows
+    ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
-    /// println!("{}", System::now().tz_offset());
-    /// println!("{}", System::now().tz_offset());
+    /// println!("{} milliseconds since the epoch we use", System::now().epoch());
+    /// println!("{} milliseconds since the epoch we use from pool.ntp.org", System::now().epoch());
     /// ```
-    fn tz_offset(&self) -> String {
-        let offset = self.utc_offset();
-        let sign = if offset < 0 { "-" } else { "+" };
-        let offset = offset.abs();
-        let hours = offset / 3600;
-        let minutes = (offset % 3600) / 60;
-        format!("{}{:02}:{:02}", sign, hours, minutes)
+    /// 
+    /// Saturates at `i64::MAX` for values that do not fit - use `try_epoch` to detect this
+    fn epoch(&self) -> i64 {
+        self.try_epoch().unwrap_or(i64::MAX)
     }
 
-    /// Represents the timezone as an enum
-    /// 
+    /// Get the time in milliseconds since the epoch we use (`1601-01-01 00:00:00`), or an error if it does not fit in an `i64`
+    ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time};
-    /// println!("{:?}", System::now().tz_enum());
-    /// println!("{:?}", System::now().tz_enum());
+    /// println!("{:?}", System::now().try_epoch());
     /// ```
-    fn tz_enum(&self) -> Option<Tz> {
-        Tz::from_offset(-self.utc_offset())
+    fn try_epoch(&self) -> Result<i64, TimeError> {
+        // the raw value is already milliseconds since 1601, so going through unix_ms() would only add ways to overflow
+        i64::try_from(self.raw()).map_err(|_| TimeError::Overflow)
     }
 
-    /// Changes the timezone offset of the time object, where `offset` is in the form "+|-[0-5][0-9]:[0-5][0-9]"
-    /// Note that this change is relative to UTC, not the current timezone
-    /// 
+    /// pretty print the time object, as `%Y-%m-%d %H:%M:%S` unless changed with `set_default_pretty_format` or the `THETIME_FORMAT`
+    /// environment variable (read once, when first needed)
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().change_tz("+01:00"));
-    /// println!("{}", System::now().change_tz("-01:00"));
+    /// use thetime::{System, Time, StrTime};
+    /// let date2017 = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// println!("2017 - {}", date2017.pretty());
+    /// assert_eq!(date2017.pretty(), "2017-01-01 00:00:00");
     /// ```
-    fn change_tz<T: ToString>(&self, offset: T) -> Self 
-    where Self: Sized {
-        let offset = offset.to_string();
-        let offset_seconds = offset[1..3].parse::<i32>().unwrap() * 3600
-            + offset[4..6].parse::<i32>().unwrap() * 60;
-
-        let offset_seconds = if offset.starts_with('+') {
-            offset_seconds
-        } else {
-            -offset_seconds
-        };
-
-        let duped_secs = offset_seconds;
-
-
-        let utc_self = Self::from_epoch_offset((self.raw() as i64 + (self.utc_offset() as i64 * 1000i64)) as u64, 0);
-
-
-        Self::from_epoch_offset((utc_self.raw() as i64 + (offset_seconds as i64 * 1000i64)) as u64, -duped_secs)
+    fn pretty(&self) -> String {
+        format::pretty(self)
     }
 
-    /// Changes the timezone offset of the time object to the local timezone
-    /// 
+    /// `pretty` into a string the caller keeps, which is cleared first - reusing one string across calls saves an allocation per call
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().local());
-    /// println!("{}", System::now().local());
+    /// use thetime::{System, Time, IntTime};
+    /// let mut buffer = String::new();
+    /// for secs in [1704465989u32, 1704466000] {
+    ///     secs.unix::<System>().pretty_into_string(&mut buffer);
+    ///     println!("{}", buffer);
+    /// }
+    /// assert_eq!(buffer, 1704466000u32.unix::<System>().pretty());
     /// ```
-    fn local(&self) -> Self
-    where Self: Sized {
-        self.change_tz(Local::now().format("%:z").to_string())
+    fn pretty_into_string(&self, out: &mut String) {
+        out.clear();
+        format::pretty_into(self, out);
     }
 
-    /// add an amount in seconds to a time object
-    /// 
+    /// Call `f` with `pretty`'s output, formatted into a buffer kept per thread rather than a new string - `Display` for `System` and
+    /// `Ntp` goes through this, so formatting on many threads doesn't contend on the allocator
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().add_seconds(3600));
-    /// println!("{}", System::now().add_seconds(3600));
+    /// use thetime::{System, Time, IntTime};
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert_eq!(x.with_pretty(|s| s.len()), 19);
+    /// x.with_pretty(|s| assert_eq!(s, x.pretty()));
     /// ```
-    fn add_seconds(&self, duration: i64) -> Self
-    where Self: Sized {
-        Self::from_epoch((self.raw() as i64 + (duration * 1000)) as u64)
+    fn with_pretty<R, F: FnOnce(&str) -> R>(&self, f: F) -> R {
+        format::with_pretty(self, f)
     }
 
-    /// add an amount in minutes to a time object
-    /// 
+    /// Don't use this method, it's for internal use only (for instantiating structs from timestamps using the `1601-01-01 00:00:00` epoch)
+    #[doc(hidden)]
+    fn from_epoch(timestamp: u64) -> Self;
+
+    /// Don't use this method, it's for internal use only (returns raw struct ms)
+    #[doc(hidden)]
+    fn raw(&self) -> u64;
+
+    /// Returns the date formatted in ISO8601 format, to the millisecond
+    ///
+    /// Kept for compatibility, but not conformant: the date and time are separated by a space rather than 'T', and there is no offset.
+    /// Use `iso8601_strict` for conformant output
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().add_minutes(60));
-    /// println!("{}", System::now().add_minutes(-60));
+    /// use thetime::{System, Time, StrTime};
+    /// println!("{}", System::now().iso8601());
+    /// let x = "2024-01-05 14:46:29.005".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+    /// assert_eq!(x.iso8601(), "2024-01-05 14:46:29.005");
     /// ```
-    fn add_minutes(&self, minutes: i64) -> Self
-    where Self: Sized {
-        self.add_seconds(minutes * 60)
+    fn iso8601(&self) -> String {
+        self.iso8601_precision(3)
     }
 
-    /// add an amount in hours to a time object
-    /// 
+    /// Returns the date formatted as `iso8601` does, with `digits` digits of fraction - none (and no '.') for 0, and at most 9
+    ///
+    /// Digits past the millisecond are always zero, and digits left off are dropped rather than rounded
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().add_minutes(60));
-    /// println!("{}", System::now().add_minutes(24));
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 14:46:29.050".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+    /// assert_eq!(x.iso8601_precision(0), "2024-01-05 14:46:29");
+    /// assert_eq!(x.iso8601_precision(1), "2024-01-05 14:46:29.0");
+    /// assert_eq!(x.iso8601_precision(6), "2024-01-05 14:46:29.050000");
     /// ```
-    fn add_hours(&self, hours: i64) -> Self
-    where Self: Sized {
-        self.add_seconds(hours * 3600)
+    fn iso8601_precision(&self, digits: u8) -> String {
+        self.strftime("%Y-%m-%d %H:%M:%S") + &format::fraction(self.unix_ms().rem_euclid(1000) as u32, digits)
     }
 
-    /// add an amount in days to a time object
-    /// 
+    /// Returns the wall clock time in conformant ISO 8601 extended format, to the millisecond, with the offset - "Z" for UTC, otherwise
+    /// "+hh:mm" (offsets with seconds are truncated to the minute)
+    ///
+    /// The date is written in the form `profile` selects - calendar, ISO week or ordinal. `is_valid_iso8601` accepts everything this writes,
+    /// for years up to 9999
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().add_days(7));
-    /// println!("{}", System::now().add_days(24));
+    /// use thetime::{System, Time, StrTime, IsoProfile};
+    /// let x = "2024-01-05 14:46:29.123 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+    /// assert_eq!(x.iso8601_strict(IsoProfile::Calendar), "2024-01-05T14:46:29.123+05:30");
+    /// assert_eq!(x.iso8601_strict(IsoProfile::Week), "2024-W01-5T14:46:29.123+05:30");
+    /// assert_eq!(x.iso8601_strict(IsoProfile::Ordinal), "2024-005T14:46:29.123+05:30");
+    /// assert_eq!(x.to_utc().iso8601_strict(IsoProfile::Calendar), "2024-01-05T09:16:29.123Z");
     /// ```
-    fn add_days(&self, days: i64) -> Self
-    where Self: Sized {
-        self.add_seconds(days * 86400)
+    fn iso8601_strict(&self, profile: IsoProfile) -> String {
+        let offset = if self.utc_offset() == 0 { "Z".to_string() } else { self.tz_offset() };
+        let date = self.strftime(profile.date_format());
+        format!("{}T{}.{:03}{}", date, self.strftime("%H:%M:%S"), self.unix_ms().rem_euclid(1000), offset)
     }
 
-    /// add an amount in weeks to a time object
-    /// we stop at weeks to avoid potential leap year confusion
-    /// 
+    /// The time as an Apache/nginx access log (Common Log Format) timestamp, in its own offset, such as "[05/Jan/2024:14:46:29 +0000]"
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// println!("{}", System::now().add_weeks(7));
-    /// println!("{}", System::now().add_weeks(52));
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(1704465989u32.unix::<System>().change_tz("-05:00").clf(), "[05/Jan/2024:09:46:29 -0500]");
     /// ```
-    fn add_weeks(&self, weeks: i64) -> Self
-    where Self: Sized {
-        self.add_seconds(weeks * 604800)
+    fn clf(&self) -> String {
+        self.strftime("[%d/%b/%Y:%H:%M:%S %z]")
     }
 
+    /// The time as an nginx error log timestamp, on its own wall clock, such as "2024/01/05 14:46:29"
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(1704465989u32.unix::<System>().nginx_error(), "2024/01/05 14:46:29");
+    /// ```
+    fn nginx_error(&self) -> String {
+        self.strftime("%Y/%m/%d %H:%M:%S")
+    }
 
-    /// determine whether a time object is in the past, present or future
-    /// 
+    /// The time as a satellite two-line element set epoch: a two digit year, the day of the year and the fraction of that day to 8
+    /// decimal places, in UTC, such as "08264.51782528". None outside the years 1957-2056, which the field can't hold
+    ///
+    /// 8 decimal places is finer than a millisecond, so `StrTime::strp_tle_epoch` reads the result back exactly
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time};
-    /// let x = System::now();
-    /// let y = System::now();
-    /// println!("{} is in the {}", x, x.past_future(&y));
-    /// println!("{} is in the {}", y, y.past_future(&x));
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(1704465989u32.unix::<System>().tle_epoch().unwrap(), "24005.61561343");
+    /// assert_eq!(2745532800u32.unix::<System>().tle_epoch(), None); // 2057-01-01
     /// ```
-    fn past_future<T: Time>(&self, other: &T) -> RelativeTime {
-        #[allow(clippy::comparison_chain)] // this is a false positive: we don't want to use a match statement here
-        if self.raw() < other.raw() {
-            RelativeTime::Past
-        } else if self.raw() > other.raw() {
-            RelativeTime::Future
-        } else {
-            RelativeTime::Present
+    fn tle_epoch(&self) -> Option<String> {
+        let wall = DateTime::from_timestamp_millis(self.unix_ms())?.naive_utc();
+        if !(1957..=2056).contains(&wall.year()) {
+            return None;
         }
+        let ms_of_day = wall.num_seconds_from_midnight() as u64 * 1000 + self.unix_ms().rem_euclid(1000) as u64;
+        // 1e-8 of a day is 0.864ms
+        let fraction = (ms_of_day * 1000 + 432) / 864;
+        Some(format!("{:02}{:03}.{:08}", wall.year() % 100, wall.ordinal(), fraction))
     }
 
-    /// add a duration to a time object
-    /// 
+    /// The instant as its frozen 10 byte canonical encoding, for signing - milliseconds since 1601 in UTC, big-endian, then a version
+    /// byte and a reserved byte. Every offset of one instant encodes the same; see the `canonical` module for the layout
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time, ImplsDuration};
-    /// let x = System::now();
-    /// println!("{}", x.add_duration(chrono::Duration::seconds(3600)));
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(0u32.unix::<System>().canonical_bytes(), [0x00, 0x00, 0x0a, 0x97, 0x30, 0xb6, 0x68, 0x00, 0x01, 0x00]);
     /// ```
-    fn add_duration<T: ImplsDuration>(&self, duration: T) -> Self
-        where Self: Sized {
-        self.add_seconds(duration.num_seconds())
+    fn canonical_bytes(&self) -> [u8; CANONICAL_LEN] {
+        canonical::encode(self)
     }
 
-    /// cast a time object to another time object
-    /// 
+    /// Decode a `canonical_bytes` encoding, in UTC - errors if the version byte isn't `CANONICAL_VERSION`, the reserved byte isn't 0, or
+    /// the instant is past the year 9999
+    ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Ntp, Time};
-    /// let x = System::now();
-    /// println!("{}", x.cast::<Ntp>());
+    /// use thetime::{System, Time, TimeError};
+    /// let x = System::from_canonical_bytes(&[0x00, 0x00, 0x0a, 0x97, 0x30, 0xb6, 0x68, 0x00, 0x01, 0x00]).unwrap();
+    /// assert_eq!(x.unix(), 0);
+    /// assert!(matches!(System::from_canonical_bytes(&[0, 0, 0, 0, 0, 0, 0, 0, 2, 0]), Err(TimeError::InvalidField { value: 2, .. })));
     /// ```
-    fn cast<T: Time>(&self) -> T
+    fn from_canonical_bytes(bytes: &[u8; CANONICAL_LEN]) -> Result<Self, TimeError>
     where Self: Sized {
-        T::from_epoch(self.raw())
-    }
-
-    /// internal only
-    #[doc(hidden)]
-    fn from_epoch_offset(timestamp: u64, offset: i32) -> Self;
-}
-
-/// A trait so that we can use chrono::Duration and core::time::Duration interchangeably in the `Time::add_duration` function
-pub trait ImplsDuration {
-    fn num_seconds(&self) -> i64;
-}
-impl ImplsDuration for chrono::Duration {
-    fn num_seconds(&self) -> i64 {
-        self.num_seconds()
+        canonical::decode(bytes)
     }
-}
 
-impl ImplsDuration for core::time::Duration {
-    fn num_seconds(&self) -> i64 {
-        self.as_secs() as i64
+    /// Feed the canonical encoding into a `core::hash::Hasher` - the same instant in any offset feeds the same frozen bytes. `Hash` also
+    /// ignores the offset, but writes a native-endian `u64` whose bytes can differ between platforms and Rust releases, so prefer this for
+    /// hashes that are stored or shared, with a fixed-key hasher
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use core::hash::Hasher;
+    /// let hash = |t: &System| { let mut h = DefaultHasher::new(); t.canonical_hash(&mut h); h.finish() };
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert_eq!(hash(&x), hash(&x.change_tz("-05:00")));
+    /// ```
+    fn canonical_hash<H: core::hash::Hasher>(&self, hasher: &mut H) {
+        hasher.write(&self.canonical_bytes());
     }
-}
 
-/// Implements the diff functions (optional)
-pub trait TimeDiff {
-    /// Get the difference between two times in seconds
+    /// Feed the canonical encoding into a `digest` hash (needs the `digest` feature), such as a `sha2::Sha256`
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time, TimeDiff, StrTime, IntTime};
-    /// let x = "2018-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-    /// let y = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-    /// println!("{} seconds difference", x.diff(&y));
-    /// assert_eq!(x.diff(&y), 31536000u64);
+    /// use thetime::{System, Time, IntTime};
+    /// use sha2::{Digest, Sha256};
+    /// let mut sha = Sha256::new();
+    /// 1704465989u32.unix::<System>().canonical_digest(&mut sha);
+    /// assert_eq!(sha.finalize().len(), 32);
     /// ```
-    fn diff<T: Time>(&self, other: &T) -> u64
-    where
-        Self: Time,
-    {
-        self.raw().abs_diff(other.raw()) / 1000
+    #[cfg(feature = "digest")]
+    fn canonical_digest<D: digest::Update>(&self, digest: &mut D) {
+        digest.update(&self.canonical_bytes());
     }
 
-    /// Get the difference between two times in milliseconds
+    /// The time as a DER ASN.1 UTCTime, as in X.509 validity fields: "YYMMDDHHMMSSZ" in UTC, with no fraction. None outside the years
+    /// 1950-2049, which UTCTime can't hold
+    ///
+    /// The milliseconds are dropped - `asn1_utctime_checked` reports them, and with the `strict-conversions` feature this
+    /// `debug_assert`s that there are none
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time, TimeDiff};
-    /// let x = System::now();
-    /// let y = System::now();
-    /// println!("{} milliseconds difference", x.diff_ms(&y));
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(1704465989u32.unix::<System>().asn1_utctime().unwrap(), "240105144629Z");
+    /// assert_eq!(2524608000u32.unix::<System>().asn1_utctime(), None); // 2050-01-01
     /// ```
-    fn diff_ms<T: Time>(&self, other: &T) -> u64
-    where
-        Self: Time,
-    {
-        self.raw().abs_diff(other.raw())
+    fn asn1_utctime(&self) -> Option<String> {
+        let (utctime, loss) = self.asn1_utctime_checked()?;
+        precision::strict(loss, "asn1_utctime");
+        Some(utctime)
     }
-}
 
-/// Provides wrappers on string std types to parse into time structs
-pub trait StrTime {
-    /// Parse a string into a time struct of choice
+    /// As `asn1_utctime`, along with the milliseconds it drops
     ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, StrTime};
-    /// println!("2017 - {}", "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S"));
-    /// println!("{}", "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").unix());
-    /// assert_eq!("2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").unix(), 1483228800);
+    /// let x = "2024-01-05 14:46:29.120".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+    /// let (utctime, loss) = x.asn1_utctime_checked().unwrap();
+    /// assert_eq!((utctime.as_str(), loss.discarded_ms), ("240105144629Z", 120));
     /// ```
-    fn parse_time<T: Time>(&self, format: &str) -> T
-    where
-        Self: core::fmt::Display,
-    {
-        T::strptime(self, format)
+    fn asn1_utctime_checked(&self) -> Option<(String, PrecisionLoss)> {
+        let ms = self.unix_ms();
+        let wall = DateTime::from_timestamp_millis(ms)?.naive_utc();
+        (1950..=2049)
+            .contains(&wall.year())
+            .then(|| (format!("{:02}{}", wall.year() % 100, wall.format("%m%d%H%M%SZ")), PrecisionLoss::rounding(ms, 1000)))
     }
 
-    /// Parse a string into a time struct of choice, using the ISO8601 format
+    /// The time as an MS-DOS/FAT date and time, packed as `date << 16 | time` (the order zip headers store them in): the wall clock,
+    /// in local time, to 2 seconds. None outside 1980 to 2107, which DOS dates can't hold
+    ///
+    /// An odd second and the milliseconds are dropped - `dos_checked` reports them, and with the `strict-conversions` feature this
+    /// `debug_assert`s that there are none
     ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, StrTime};
-    /// println!("2017 - {}", "2017-01-01T00:00:00.000".strp_iso8601::<System>());
-    /// println!("{}", "2017-01-01T00:00:00.000".strp_iso8601::<System>().unix());
-    /// assert_eq!("2017-01-01T00:00:00.000".strp_iso8601::<System>().unix(), 1483228800);
+    /// let x = "2024-01-05 14:46:28".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.dos(), Some(0x5825_75ce));
+    /// assert_eq!("1979-12-31 23:59:58".parse_time::<System>("%Y-%m-%d %H:%M:%S").dos(), None);
     /// ```
-    fn strp_iso8601<T: Time>(&self) -> T
-    where
-        Self: core::fmt::Display,
-    {
-        T::strptime(self, "%Y-%m-%dT%H:%M:%S.%f")
+    fn dos(&self) -> Option<u32> {
+        let (dos, loss) = self.dos_checked()?;
+        precision::strict(loss, "dos");
+        Some(dos)
     }
 
-    /// Parse a string into a time struct of choice, using the RFC3339 format
+    /// As `dos`, along with the milliseconds it drops (up to 1999, for an odd second)
+    fn dos_checked(&self) -> Option<(u32, PrecisionLoss)> {
+        let wall = self.wall_clock();
+        if !(1980..=2107).contains(&wall.year()) {
+            return None;
+        }
+        let date = (((wall.year() - 1980) as u32) << 9) | (wall.month() << 5) | wall.day();
+        let time = (wall.hour() << 11) | (wall.minute() << 5) | (wall.second() / 2);
+        let ms = (wall.second() % 2 * 1000 + wall.nanosecond() / 1_000_000) as i64;
+        Some(((date << 16) | time, PrecisionLoss::rounding(ms, 2000)))
+    }
+
+    /// The time as a DER ASN.1 GeneralizedTime: "YYYYMMDDHHMMSS[.fff]Z" in UTC, with the fraction only if there are milliseconds, and
+    /// without trailing zeros
     ///
     /// # Examples
     /// ```rust
-    /// use thetime::{System, Time, StrTime};
-    /// println!("2017 - {}", "2017-01-01T00:00:00.000Z".strp_rf3339::<System>());
-    /// println!("{}", "2017-01-01T00:00:00.000Z".strp_rf3339::<System>().unix());
-    /// assert_eq!("2017-01-01T00:00:00.000Z".strp_rf3339::<System>().unix(), 1483228800);
+    /// use thetime::{System, Time, StrTime, IntTime};
+    /// assert_eq!(1704465989u32.unix::<System>().asn1_generalizedtime(), "20240105144629Z");
+    /// let x = "2024-01-05 15:46:29.120 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+    /// assert_eq!(x.asn1_generalizedtime(), "20240105144629.12Z");
     /// ```
-    fn strp_rf3339<T: Time>(&self) -> T
-    where
-        Self: core::fmt::Display,
-    {
-        T::strptime(self, "%Y-%m-%dT%H:%M:%S.%fZ")
+    fn asn1_generalizedtime(&self) -> String {
+        let ms = self.unix_ms();
+        let wall = DateTime::from_timestamp_millis(ms).map_or(NaiveDateTime::MIN, |t| t.naive_utc());
+        let fraction = match ms.rem_euclid(1000) {
+            0 => String::new(),
+            ms => format!(".{:03}", ms).trim_end_matches('0').to_string(),
+        };
+        format!("{:04}{}{}Z", wall.year(), wall.format("%m%d%H%M%S"), fraction)
     }
-}
 
-/// Provides wrappers on integer std types to parse into time structs, and also to pretty print timestamp integers
-///
-/// Note: If there is an error, the function will return the Unix epoch time for the struct of choice
-/// 
-/// You can only convert from positive integers, as negative integers are not supported, as they cannot be represented in the time structs. While it would be possible to fix this, I don't think it is a needed feature at the moment.
-pub trait IntTime: core::fmt::Display + Into<u64> {
-    /// Convert an integer into a time struct of choice
+    /// Format as a self-describing timestamp carrying its own epoch and unit, as in "unix-s:1483228800" - read back with
+    /// `StrTime::parse_tagged`. Counts are rounded down to whole `unit`s, and the offset isn't kept
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime, epoch::{Epoch, Unit}};
+    /// let x = 1483228800u32.unix::<System>();
+    /// assert_eq!(x.tagged(Epoch::WebKit, Unit::Microseconds), "webkit-us:13127702400000000");
+    /// assert_eq!(x.tagged(Epoch::Unix, Unit::Seconds), "unix-s:1483228800");
+    /// ```
+    fn tagged(&self, epoch: epoch::Epoch, unit: epoch::Unit) -> String {
+        epoch::tagged(self, epoch, unit)
+    }
+
+    /// Whether this time can be stored in `kind` - the whole second it falls in must be in range (read as a wall clock, for
+    /// `TimestampKind::DosDate`, which holds local time, so a time too far out to have one doesn't fit)
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, epoch::TimestampKind};
+    /// let x = "2040-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert!(!x.fits_in(TimestampKind::I32Unix));
+    /// assert!(x.fits_in(TimestampKind::U32Unix));
+    /// assert!(!x.fits_in(TimestampKind::NtpEra0));
+    /// ```
+    fn fits_in(&self, kind: epoch::TimestampKind) -> bool {
+        let secs = match kind {
+            epoch::TimestampKind::DosDate => match self.try_wall_clock() {
+                Some(wall) => wall.and_utc().timestamp(),
+                None => return false,
+            },
+            _ => self.unix_ms().div_euclid(1000),
+        };
+        let (first, last) = kind.unix_range();
+        first <= secs && secs <= last
+    }
+
+    /// Whether this time fits in a signed 32 bit `time_t` - from 1901-12-13 20:45:52 to 2038-01-19 03:14:07 UTC
     ///
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, IntTime};
-    /// assert_eq!(1483228800u32.unix::<System>().pretty(), "2017-01-01 00:00:00");
+    /// assert!(2147483647u32.unix::<System>().y2038_safe());
+    /// assert!(!2147483648u32.unix::<System>().y2038_safe());
     /// ```
-    fn unix<T: Time>(self) -> T {
-        let unix: u64 = self.into();
-        T::from_epoch((unix + OFFSET_1601) * 1000)
+    fn y2038_safe(&self) -> bool {
+        self.fits_in(epoch::TimestampKind::I32Unix)
     }
 
-    /// Convert an integer into a time struct of choice, from a Windows timestamp (100ns since `1601-01-01 00:00:00`)
+    /// Returns the date formatted in RFC3339 format, in UTC to the millisecond
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// println!("{}", System::now().rfc3339());
+    /// let x = "2024-01-05 15:46:29.005 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+    /// assert_eq!(x.rfc3339(), "2024-01-05T14:46:29.005Z");
+    /// ```
+    fn rfc3339(&self) -> String {
+        self.rfc3339_precision(3)
+    }
+
+    /// Returns the date formatted as `rfc3339` does, with `digits` digits of fraction - none (and no '.') for 0, and at most 9
     ///
+    /// Digits past the millisecond are always zero, and digits left off are dropped rather than rounded
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 14:46:29.005".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+    /// assert_eq!(x.rfc3339_precision(0), "2024-01-05T14:46:29Z");
+    /// assert_eq!(x.rfc3339_precision(6), "2024-01-05T14:46:29.005000Z");
+    /// ```
+    fn rfc3339_precision(&self, digits: u8) -> String {
+        // the "Z" suffix means UTC, so render the instant rather than the local wall clock
+        let ms = self.unix_ms();
+        let utc = DateTime::from_timestamp_millis(ms).unwrap().naive_utc();
+        utc.format("%Y-%m-%dT%H:%M:%S").to_string() + &format::fraction(ms.rem_euclid(1000) as u32, digits) + "Z"
+    }
+
+    /// internal only
+    #[doc(hidden)]
+    fn utc_offset(&self) -> i32;
+
+    /// Gets the timezone offset in the format HH:MM
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().tz_offset());
+    /// println!("{}", System::now().tz_offset());
+    /// ```
+    fn tz_offset(&self) -> String {
+        let offset = self.utc_offset();
+        let sign = if offset < 0 { "-" } else { "+" };
+        let offset = offset.abs();
+        let hours = offset / 3600;
+        let minutes = (offset % 3600) / 60;
+        format!("{}{:02}:{:02}", sign, hours, minutes)
+    }
+
+    /// Represents the timezone as an enum
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{:?}", System::now().tz_enum());
+    /// println!("{:?}", System::now().tz_enum());
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn tz_enum(&self) -> Option<Tz> {
+        Tz::from_offset(self.utc_offset())
+    }
+
+    /// Changes the timezone offset of the time object, where `offset` is in the form "+|-HH:MM"
+    /// Note that this change is relative to UTC, not the current timezone
+    /// 
+    /// An offset not in that form leaves the time unchanged - use `try_change_tz` to find out
+    /// 
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, IntTime};
-    /// assert_eq!(131277024000000000u64.windows_ns::<System>().pretty(),"2017-01-01 00:00:00");
+    /// println!("{}", System::now().change_tz("+01:00"));
+    /// println!("{}", System::now().change_tz("-01:00"));
+    /// let x = 1704465989u32.unix::<System>().change_tz("+01:00");
+    /// assert_eq!(x.change_tz("banana").utc_offset(), 3600);
     /// ```
-    fn windows_ns<T: Time>(self) -> T {
-        T::from_epoch(self.into() / (1e4 as u64))
+    fn change_tz<T: ToString>(&self, offset: T) -> Self 
+    where Self: Sized {
+        let offset = parse::utc_offset(&offset.to_string()).unwrap_or(self.utc_offset());
+        self.change_tz_seconds(offset)
     }
 
-    /// Convert an integer into a time struct of choice, from a Webkit timestamp (microseconds since `1601-01-01 00:00:00`)
+    /// Changes the timezone offset of the time object as `change_tz` does, returning an error if `offset` is not in the form
+    /// "+|-HH:MM" (hours 00-23, minutes 00-59), or is further from UTC than `MAX_UTC_OFFSET` (which `change_tz` clamps to)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime, ParseError};
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert_eq!(x.try_change_tz("+05:30").unwrap().pretty(), "2024-01-05 20:16:29");
+    /// assert!(matches!(x.try_change_tz("+5:30"), Err(ParseError::Invalid { position: 2, .. })));
+    /// assert_eq!(x.try_change_tz("-21:00"), Err(ParseError::OutOfRange { field: "offset", value: -75600 }));
+    /// ```
+    fn try_change_tz<T: ToString>(&self, offset: T) -> Result<Self, ParseError>
+    where Self: Sized {
+        match parse::utc_offset(&offset.to_string())? {
+            offset if offset.abs() > MAX_UTC_OFFSET => Err(ParseError::OutOfRange { field: "offset", value: offset as i64 }),
+            offset => Ok(self.change_tz_seconds(offset)),
+        }
+    }
+
+    /// Changes the timezone offset of the time object, where `offset_seconds` is the offset from UTC in seconds (eg `3600` for "+01:00")
+    /// This is the numeric equivalent of `change_tz`, skipping the string parsing
+    /// 
+    /// The instant is unchanged - only the offset it is displayed in - so chained conversions never drift
     /// 
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, IntTime};
-    /// println!("2017 - {:#?}", 13127702400000000u64.webkit::<System>());
-    /// assert_eq!(13127702400000000u64.webkit::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2017-01-01 00:00:00");
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert_eq!(x.change_tz_seconds(3600).pretty(), "2024-01-05 15:46:29");
+    /// assert_eq!(x.change_tz_seconds(19800).change_tz_seconds(-39600).unix(), x.unix());
     /// ```
-    fn webkit<T: Time>(self) -> T {
-        T::from_epoch(self.into() / (1e3 as u64))
+    fn change_tz_seconds(&self, offset_seconds: i32) -> Self
+    where Self: Sized {
+        Self::from_epoch_offset(self.raw(), offset_seconds)
     }
 
-    /// Convert an integer into a time struct of choice, from a Mac OS timestamp (seconds since 1904-01-01 00:00:00)
-    ///
+    /// The same instant, displayed in UTC
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 14:46:29 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+    /// assert_eq!(x.to_utc().pretty(), "2024-01-05 09:16:29");
+    /// assert_eq!(x.to_utc().tz_offset(), "+00:00");
+    /// ```
+    fn to_utc(&self) -> Self
+    where Self: Sized {
+        self.change_tz_seconds(0)
+    }
+
+    /// Re-normalises the time object: whole seconds carried out of the milliseconds, and the offset clamped to `±MAX_UTC_OFFSET`
+    /// 
+    /// Every constructor and arithmetic method already returns canonical values, so this is only needed for values built some other way - it is idempotent
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, MAX_UTC_OFFSET};
+    /// let x = System::now();
+    /// assert_eq!(x.canonicalize(), x);
+    /// assert_eq!(x.change_tz_seconds(100 * 3600).utc_offset(), MAX_UTC_OFFSET);
+    /// ```
+    fn canonicalize(&self) -> Self
+    where Self: Sized {
+        Self::from_epoch_offset(self.raw(), self.utc_offset())
+    }
+
+    /// Whether the time object is canonical (see `canonicalize`)
+    fn is_canonical(&self) -> bool {
+        self.utc_offset().abs() <= MAX_UTC_OFFSET
+    }
+
+    /// The Unix epoch, `1970-01-01 00:00:00` UTC - this is also `Default` for `System` and `Ntp`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// assert_eq!(System::unix_epoch().pretty(), "1970-01-01 00:00:00");
+    /// assert_eq!(System::default(), System::unix_epoch());
+    /// ```
+    fn unix_epoch() -> Self
+    where Self: Sized {
+        Self::from_epoch(OFFSET_1601 * 1000)
+    }
+
+    /// The Windows epoch, `1601-01-01 00:00:00` UTC, which is also the earliest time `System` and `Ntp` can hold
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// assert_eq!(System::windows_epoch().pretty(), "1601-01-01 00:00:00");
+    /// ```
+    fn windows_epoch() -> Self
+    where Self: Sized {
+        Self::from_epoch(0)
+    }
+
+    /// A sentinel for "never", such as an expiry that is not set: `9999-12-31 23:59:59.999` UTC, the latest time that every format (including four digit years) can show
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// assert!(System::far_future() > System::now());
+    /// assert_eq!(System::far_future().pretty(), "9999-12-31 23:59:59");
+    /// ```
+    fn far_future() -> Self
+    where Self: Sized {
+        Self::from_epoch((FAR_FUTURE_UNIX_MS + OFFSET_1601 as i64 * 1000) as u64)
+    }
+
+    /// Whether this is exactly the Unix epoch, `1970-01-01 00:00:00` UTC (in any offset)
+    fn is_unix_epoch(&self) -> bool {
+        self.unix_ms() == 0
+    }
+
+    /// Whether this is one of the placeholder values - `unix_epoch`, `windows_epoch`, or `far_future` (or later) - rather than a real timestamp
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// assert!(System::default().is_sentinel());
+    /// assert!(System::far_future().is_sentinel());
+    /// assert!(!System::now().is_sentinel());
+    /// ```
+    fn is_sentinel(&self) -> bool {
+        let ms = self.unix_ms();
+        ms == 0 || ms == -(OFFSET_1601 as i64 * 1000) || ms >= FAR_FUTURE_UNIX_MS
+    }
+
+    /// Whether two time objects (of any types) are the same instant, ignoring their offsets and where they came from
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Ntp, Time, IntTime};
+    /// let x = 1704465989u32.unix::<System>();
+    /// assert!(x.instant_eq(&x.change_tz("+05:30")));
+    /// assert!(x.instant_eq(&1704465989u32.unix::<Ntp>()));
+    /// assert!(!x.instant_eq(&x.add_seconds(1)));
+    /// ```
+    fn instant_eq<T: Time>(&self, other: &T) -> bool {
+        self.unix_ms() == other.unix_ms()
+    }
+
+    /// A key identifying this instant, for keying maps by instant across offsets and types
+    /// 
+    /// This is the Unix millisecond timestamp reinterpreted as a `u64` - equal instants always give equal keys, and different instants never collide
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Ntp, Time, IntTime};
+    /// use std::collections::HashMap;
+    /// let x = 1704465989u32.unix::<System>();
+    /// let mut seen = HashMap::new();
+    /// seen.insert(x.hash_key(), "first");
+    /// assert_eq!(seen.get(&x.change_tz("-08:00").hash_key()), Some(&"first"));
+    /// assert_eq!(seen.get(&1704465989u32.unix::<Ntp>().hash_key()), Some(&"first"));
+    /// ```
+    fn hash_key(&self) -> u64 {
+        self.unix_ms() as u64
+    }
+
+    /// Split into the fields of a Windows `SYSTEMTIME`, in UTC (like `GetSystemTime`)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-07 09:30:15.250 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+    /// let parts = x.to_systemtime_parts();
+    /// assert_eq!((parts.day_of_week, parts.hour, parts.milliseconds), (0, 8, 250));
+    /// ```
+    fn to_systemtime_parts(&self) -> SystemTimeParts
+    where Self: Sized {
+        SystemTimeParts::from_naive(self.to_utc().wall_clock())
+    }
+
+    /// Split into the fields of a Windows `SYSTEMTIME`, on the time object's own wall clock (like `GetLocalTime`)
+    fn to_local_systemtime_parts(&self) -> SystemTimeParts {
+        SystemTimeParts::from_naive(self.wall_clock())
+    }
+
+    /// Build a UTC time object from the fields of a Windows `SYSTEMTIME`, checking each field's range
+    /// 
+    /// The day of the week is recomputed from the date - use `SystemTimeParts::strict` first to reject one that does not match
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, SystemTimeParts};
+    /// let parts = SystemTimeParts { year: 2024, month: 2, day: 29, hour: 12, ..Default::default() };
+    /// assert_eq!(System::from_systemtime_parts(parts).unwrap().pretty(), "2024-02-29 12:00:00");
+    /// assert!(System::from_systemtime_parts(SystemTimeParts { day: 30, ..parts }).is_err());
+    /// ```
+    fn from_systemtime_parts(parts: SystemTimeParts) -> Result<Self, TimeError>
+    where Self: Sized {
+        interop::windows::from_parts(parts, 0)
+    }
+
+    /// Build a time object from the fields of a Windows `SYSTEMTIME` that hold a wall clock `offset` seconds from UTC, as from `GetLocalTime`
+    fn from_local_systemtime_parts(parts: SystemTimeParts, offset: i32) -> Result<Self, TimeError>
+    where Self: Sized {
+        interop::windows::from_parts(parts, offset)
+    }
+
+    /// Nanoseconds since the Unix epoch, as OpenTelemetry timestamps use - times before 1970 saturate to 0
+    /// 
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, IntTime};
-    /// println!("2024 - {:#?}", 3787310789u64.mac_os::<System>());
-    /// assert_eq!(3787310789u64.mac_os::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-05 14:46:29");
+    /// assert_eq!(1704465989u32.unix::<System>().otel_ns(), 1704465989000000000);
+    /// assert_eq!(System::windows_epoch().otel_ns(), 0);
     /// ```
-    fn mac_os<T: Time>(self) -> T {
-        let selfu64: u64 = self.into();
-        let selfi64: i64 = selfu64 as i64;
-        let unix: i64 = selfi64 - MAGIC_MAC_OS;
-        T::from_epoch((unix + (OFFSET_1601 as i64)) as u64 * 1000)
+    fn otel_ns(&self) -> u64 {
+        (self.unix_ms() as i128 * 1_000_000).clamp(0, u64::MAX as i128) as u64
     }
 
-    /// Convert an integer into a time struct of choice, from a Mac OS Absolute timestamp (seconds since 2001-01-01 00:00:00)
-    ///
+    /// Milliseconds since the Unix epoch, as the Prometheus exposition format uses - negative before 1970
+    fn prom_ms(&self) -> i64 {
+        self.unix_ms()
+    }
+
+    /// The seconds and nanoseconds of a protobuf `google.protobuf.Timestamp` - before 1970 the seconds are negative and the nanoseconds
+    /// still count forwards, so 1969-12-31 23:59:59.500 is `(-1, 500_000_000)`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// let x = System::from_proto_timestamp(-1, 500_000_000).unwrap();
+    /// assert_eq!(x.strftime("%Y-%m-%d %H:%M:%S%.3f"), "1969-12-31 23:59:59.500");
+    /// assert_eq!(x.to_proto_timestamp(), (-1, 500_000_000));
+    /// ```
+    fn to_proto_timestamp(&self) -> (i64, i32) {
+        let ms = self.unix_ms();
+        (ms.div_euclid(1000), ms.rem_euclid(1000) as i32 * 1_000_000)
+    }
+
+    /// Build a UTC time object from the seconds and nanoseconds of a protobuf `Timestamp`, truncating to the millisecond
+    /// 
+    /// Errors with `TimeError::InvalidField` unless `nanos` is in `0..1_000_000_000`, and with `TimeError::Overflow` before 1601 or
+    /// beyond the range chrono supports
+    fn from_proto_timestamp(secs: i64, nanos: i32) -> Result<Self, TimeError>
+    where Self: Sized {
+        if !(0..1_000_000_000).contains(&nanos) {
+            return Err(TimeError::InvalidField { field: "nanos", value: nanos as i64 });
+        }
+        let ms = secs.checked_mul(1000).and_then(|ms| ms.checked_add(nanos as i64 / 1_000_000)).ok_or(TimeError::Overflow)?;
+        epoch::Epoch::Unix.decode(ms, epoch::Unit::Milliseconds)
+    }
+
+    /// Pairs the time object with a timezone to view it in
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, Tz};
+    /// println!("{}", System::now().in_zone(Tz::Ist));
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn in_zone(&self, zone: Tz) -> Zoned<Self>
+    where Self: Sized + Clone {
+        Zoned::new(self.clone(), zone)
+    }
+
+    /// Formats the time object in every known timezone, sorted by offset (west to east), using `pretty()`
+    /// 
     /// # Examples
     /// ```rust
     /// use thetime::{System, Time, IntTime};
-    /// println!("2024 - {:#?}", 726158877u64.mac_os_cfa::<System>());
-    /// assert_eq!(726158877u64.mac_os_cfa::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-05 14:47:57");
+    /// for (tz, time) in 1704465989u32.unix::<System>().world_view() {
+    ///     println!("{:>16} {} {}", tz.name(), tz.offset_str(), time);
+    /// }
     /// ```
-    fn mac_os_cfa<T: Time>(self) -> T {
-        let selfu64: u64 = self.into();
-        let unix: u64 = selfu64 + MAGIC_MAC_OS_CFA as u64;
-        T::from_epoch((unix + OFFSET_1601) * 1000)
+    #[cfg(feature = "timezones")]
+    fn world_view(&self) -> Vec<(Tz, String)>
+    where Self: Sized {
+        self.world_view_fmt("%Y-%m-%d %H:%M:%S")
+    }
+
+    /// Formats the time object in every known timezone with the given format, sorted by offset (west to east)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, Tz, IntTime};
+    /// let view = 1704465989u32.unix::<System>().world_view_fmt("%H:%M");
+    /// assert_eq!(view[0], (Tz::Sast, "03:46".to_string()));
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn world_view_fmt(&self, format: &str) -> Vec<(Tz, String)>
+    where Self: Sized {
+        let mut zones = Tz::ALL;
+        zones.sort_by_key(|tz| tz.offset());
+        self.in_zones_fmt(&zones, format)
+    }
+
+    /// Formats the time object in each of the given timezones, in the order given, using `pretty()`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, Tz, IntTime};
+    /// let view = 1704465989u32.unix::<System>().in_zones(&[Tz::Pst, Tz::Ist]);
+    /// assert_eq!(view[1], (Tz::Ist, "2024-01-05 20:16:29".to_string()));
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn in_zones(&self, zones: &[Tz]) -> Vec<(Tz, String)>
+    where Self: Sized {
+        self.in_zones_fmt(zones, "%Y-%m-%d %H:%M:%S")
+    }
+
+    /// Formats the time object in each of the given timezones with the given format, in the order given - `%Z` gives each zone's name
+    /// under the process-wide `TzNamePreference` (see `set_tz_name_preference`)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, Tz, IntTime};
+    /// let view = 1704465989u32.unix::<System>().in_zones_fmt(&[Tz::Pst], "%H:%M");
+    /// assert_eq!(view[0], (Tz::Pst, "06:46".to_string()));
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn in_zones_fmt(&self, zones: &[Tz], format: &str) -> Vec<(Tz, String)>
+    where Self: Sized {
+        zones
+            .iter()
+            .map(|tz| (*tz, timezones::strftime_zone_name(&self.change_tz_seconds(tz.offset()), format, &tz.preferred_name())))
+            .collect()
+    }
+
+    /// Changes the timezone offset of the time object to the local timezone
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().local());
+    /// println!("{}", System::now().local());
+    /// ```
+    fn local(&self) -> Self
+    where Self: Sized {
+        let offset = match Local.timestamp_millis_opt(self.unix_ms()) {
+            chrono::LocalResult::Single(dt) | chrono::LocalResult::Ambiguous(dt, _) => dt.offset().local_minus_utc(),
+            chrono::LocalResult::None => Local::now().offset().local_minus_utc(),
+        };
+        self.change_tz_seconds(offset)
+    }
+
+    /// add an amount in seconds to a time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_seconds(3600));
+    /// println!("{}", System::now().add_seconds(3600));
+    /// ```
+    fn add_seconds(&self, duration: i64) -> Self
+    where Self: Sized {
+        // clamped at 1601 rather than wrapping
+        Self::from_epoch((self.raw() as i64).saturating_add(duration.saturating_mul(1000)).max(0) as u64)
+    }
+
+    /// add an amount in minutes to a time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_minutes(60));
+    /// println!("{}", System::now().add_minutes(-60));
+    /// ```
+    fn add_minutes(&self, minutes: i64) -> Self
+    where Self: Sized {
+        self.add_seconds(minutes * 60)
+    }
+
+    /// add an amount in hours to a time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_minutes(60));
+    /// println!("{}", System::now().add_minutes(24));
+    /// ```
+    fn add_hours(&self, hours: i64) -> Self
+    where Self: Sized {
+        self.add_seconds(hours * 3600)
+    }
+
+    /// add an amount in days to a time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_days(7));
+    /// println!("{}", System::now().add_days(24));
+    /// ```
+    fn add_days(&self, days: i64) -> Self
+    where Self: Sized {
+        self.add_seconds(days * 86400)
+    }
+
+    /// add an amount in weeks to a time object
+    /// we stop at weeks to avoid potential leap year confusion
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_weeks(7));
+    /// println!("{}", System::now().add_weeks(52));
+    /// ```
+    fn add_weeks(&self, weeks: i64) -> Self
+    where Self: Sized {
+        self.add_seconds(weeks * 604800)
+    }
+
+
+    /// determine whether a time object is in the past, present or future
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// let x = System::now();
+    /// let y = System::now();
+    /// println!("{} is in the {}", x, x.past_future(&y));
+    /// println!("{} is in the {}", y, y.past_future(&x));
+    /// ```
+    fn past_future<T: Time>(&self, other: &T) -> RelativeTime {
+        #[allow(clippy::comparison_chain)] // this is a false positive: we don't want to use a match statement here
+        if self.raw() < other.raw() {
+            RelativeTime::Past
+        } else if self.raw() > other.raw() {
+            RelativeTime::Future
+        } else {
+            RelativeTime::Present
+        }
+    }
+
+    /// add a duration to a time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, ImplsDuration};
+    /// let x = System::now();
+    /// println!("{}", x.add_duration(chrono::Duration::seconds(3600)));
+    /// ```
+    fn add_duration<T: ImplsDuration>(&self, duration: T) -> Self
+        where Self: Sized {
+        self.add_seconds(duration.num_seconds())
+    }
+
+    /// cast a time object to another time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Ntp, Time};
+    /// let x = System::now();
+    /// println!("{}", x.cast::<Ntp>());
+    /// ```
+    fn cast<T: Time>(&self) -> T
+    where Self: Sized {
+        T::from_epoch(self.raw())
+    }
+
+    /// The first anniversary of this time that is strictly after `after` - the same month, day and wall clock time, in this offset, in a
+    /// later year. `policy` decides where a Feb 29 origin's anniversary falls in other years
+    ///
+    /// Saturates at `far_future` if there is no anniversary before the year 10000
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, LeapDayPolicy};
+    /// let signup = "2020-02-29 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let now = "2022-06-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(signup.next_anniversary(&now, LeapDayPolicy::Feb28).pretty(), "2023-02-28 09:00:00");
+    /// assert_eq!(signup.next_anniversary(&now, LeapDayPolicy::Mar1).pretty(), "2023-03-01 09:00:00");
+    /// assert_eq!(signup.next_anniversary(&now, LeapDayPolicy::SkipYear).pretty(), "2024-02-29 09:00:00");
+    /// ```
+    fn next_anniversary<T: Time>(&self, after: &T, policy: LeapDayPolicy) -> Self
+    where Self: Sized + Clone {
+        let after_ms = after.unix_ms().saturating_add(1);
+        let mut year = anniversary::first_year(self, after);
+        while year <= 9999 {
+            match anniversary::anniversary(self, year, policy) {
+                Some(t) if t.unix_ms() >= after_ms => return t,
+                _ => year += 1,
+            }
+        }
+        Self::far_future()
+    }
+
+    /// The anniversaries of this time from `start` (inclusive) to `end` (exclusive), in order - see `next_anniversary`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, LeapDayPolicy};
+    /// let signup = "2020-02-29 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let end = "2025-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let renewals: Vec<String> = signup.anniversaries_between(&signup, &end, LeapDayPolicy::SkipYear).map(|t| t.pretty()).collect();
+    /// assert_eq!(renewals, ["2024-02-29 09:00:00"]);
+    /// ```
+    fn anniversaries_between<S: Time, E: Time>(&self, start: &S, end: &E, policy: LeapDayPolicy) -> Anniversaries<Self>
+    where Self: Sized + Clone {
+        Anniversaries::new(self.clone(), start, end, policy)
+    }
+
+    /// Whether this time falls on an anniversary of `origin` (on the same date, read in `origin`'s offset, in a later year) - the time
+    /// of day is ignored
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, LeapDayPolicy};
+    /// let signup = "2020-02-29 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let day = "2021-03-01 17:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert!(day.is_anniversary_of(&signup, LeapDayPolicy::Mar1));
+    /// assert!(!day.is_anniversary_of(&signup, LeapDayPolicy::Feb28));
+    /// ```
+    fn is_anniversary_of<T: Time>(&self, origin: &T, policy: LeapDayPolicy) -> bool {
+        let origin_date = origin.wall_clock().date();
+        let date = T::from_epoch_offset(self.raw(), origin.utc_offset()).wall_clock().date();
+        date.year() > origin_date.year() && policy.anniversary_in(origin_date, date.year()) == Some(date)
+    }
+
+    /// Add (or, if negative, subtract) whole calendar months, keeping the wall clock time and offset. `policy` decides what happens when
+    /// the day of the month doesn't exist in the target month
+    ///
+    /// Errors with `MonthRollError::DayMissing` under `MonthRollPolicy::Reject`, and `MonthRollError::OutOfRange` outside the years
+    /// 1601-9999
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, MonthRollPolicy};
+    /// let x = "2024-02-29 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.add_months_with(1, MonthRollPolicy::Clamp).unwrap().pretty(), "2024-03-29 09:00:00");
+    /// assert_eq!(x.add_months_with(1, MonthRollPolicy::EndOfMonth).unwrap().pretty(), "2024-03-31 09:00:00");
+    /// assert_eq!(x.add_months_with(-12, MonthRollPolicy::Clamp).unwrap().pretty(), "2023-02-28 09:00:00");
+    /// ```
+    fn add_months_with(&self, months: i32, policy: MonthRollPolicy) -> Result<Self, MonthRollError>
+    where Self: Sized {
+        months::add_months(self, months, policy)
+    }
+
+    /// Add (or, if a part is negative, subtract) a `CalendarDuration`, keeping the offset: the months first (as `add_months_with`, under
+    /// `policy`), then the days on the wall clock, then the exact part. The order matters around the ends of months - see the examples
+    ///
+    /// Errors as `add_months_with`, and with `MonthRollError::OutOfRange` if the days or the exact part take the result outside the years
+    /// 1601-9999
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, CalendarDuration, MonthRollPolicy};
+    /// let x = "2024-01-30 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// // Jan 30 + 1mo = Feb 29, + 1d = Mar 1 (adding the day first would give Jan 31 + 1mo = Feb 29)
+    /// assert_eq!(x.add_calendar(&CalendarDuration::new(1, 1, 0), MonthRollPolicy::Clamp).unwrap().pretty(), "2024-03-01 09:00:00");
+    /// // the exact part comes last, so it can cross a day: Feb 29 09:00 + 15h = Mar 1 00:00
+    /// assert_eq!(x.add_calendar(&CalendarDuration::new(1, 0, 15 * 3_600_000), MonthRollPolicy::Clamp).unwrap().pretty(), "2024-03-01 00:00:00");
+    /// ```
+    fn add_calendar(&self, duration: &CalendarDuration, policy: MonthRollPolicy) -> Result<Self, MonthRollError>
+    where Self: Sized {
+        duration.add_to(self, policy)
+    }
+
+    /// Adjust to a business day with the ISDA "modified following" rule: move forward to the next business day, unless that is in the
+    /// next month, in which case move back to the previous one instead. Business days are left alone, and the wall clock time and offset
+    /// are kept
+    ///
+    /// Returns the time unchanged if the calendar has no business days
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{BusinessCalendar, System, Time, StrTime, MonthRollPolicy};
+    /// let cal = BusinessCalendar::new();
+    /// let sat = "2024-06-01 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(sat.modified_following(&cal).pretty(), "2024-06-03 09:00:00");
+    /// // the following Monday is in July, so roll back to Friday
+    /// let payment = "2024-05-31 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").add_months_with(1, MonthRollPolicy::EndOfMonth).unwrap();
+    /// assert_eq!(payment.modified_following(&cal).pretty(), "2024-06-28 09:00:00");
+    /// ```
+    fn modified_following(&self, calendar: &BusinessCalendar) -> Self
+    where Self: Sized + Clone {
+        let wall = self.wall_clock();
+        match calendar.modified_following_date(wall.date()) {
+            Some(date) => Self::from_wall_clock(date.and_time(wall.time()), self.utc_offset()).with_offset_source(self.offset_source()),
+            None => self.clone(),
+        }
+    }
+
+    /// combine the calendar date of `date_source` with the wall-clock time of `self`, keeping the offset of `self`
+    /// 
+    /// Both values are read as they display (ie what `strftime` shows), so the result shows `date_source`'s date and `self`'s time, in `self`'s offset.
+    /// As the whole date (year included) is copied, the result is always a valid date - a Feb 29 source stays Feb 29, and never needs clamping
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let schedule = "2024-01-05 14:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let tomorrow = "2024-01-06 09:12:45".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(schedule.replace_date(&tomorrow).pretty(), "2024-01-06 14:30:00");
+    /// ```
+    fn replace_date<T: Time>(&self, date_source: &T) -> Self
+    where Self: Sized {
+        let wall = date_source.wall_clock().date().and_time(self.wall_clock().time());
+        Self::from_wall_clock(wall, self.utc_offset())
+    }
+
+    /// combine the wall-clock time of `time_source` with the calendar date of `self`, keeping the offset of `self`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let day = "2024-01-05 14:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let time = "1999-12-31 09:12:45".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(day.replace_time(&time).pretty(), "2024-01-05 09:12:45");
+    /// ```
+    fn replace_time<T: Time>(&self, time_source: &T) -> Self
+    where Self: Sized {
+        let wall = self.wall_clock().date().and_time(time_source.wall_clock().time());
+        Self::from_wall_clock(wall, self.utc_offset())
+    }
+
+    /// the start of the day (00:00:00.000) this time object falls on, in its own offset
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 14:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.at_midnight().pretty(), "2024-01-05 00:00:00");
+    /// ```
+    fn at_midnight(&self) -> Self
+    where Self: Sized {
+        Self::from_wall_clock(self.wall_clock().date().and_time(NaiveTime::MIN), self.utc_offset())
+    }
+
+    /// The milliseconds since midnight on the wall clock, in this time object's own offset - a key for ordering by time of day across
+    /// dates (see `ByTimeOfDay`)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 14:30:00.250 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+    /// assert_eq!(x.time_of_day_key(), (14 * 3600 + 30 * 60) * 1000 + 250);
+    /// ```
+    fn time_of_day_key(&self) -> u32 {
+        let time = self.wall_clock().time();
+        time.num_seconds_from_midnight() * 1000 + time.nanosecond() / 1_000_000
+    }
+
+    /// Whether the wall clock time of day is in the range from `start` (inclusive) to `end` (exclusive), each an (hour, minute, second).
+    /// If `end` is earlier than `start`, the range wraps midnight, so (22, 0, 0) to (6, 0, 0) covers the night. Equal bounds are an empty
+    /// range
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-01-05 23:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert!(x.is_between_times((22, 0, 0), (6, 0, 0)));
+    /// assert!(!x.is_between_times((9, 0, 0), (17, 0, 0)));
+    /// ```
+    fn is_between_times(&self, start: (u8, u8, u8), end: (u8, u8, u8)) -> bool {
+        let (key, start, end) = (self.time_of_day_key(), time_of_day::hms_ms(start), time_of_day::hms_ms(end));
+        if start <= end {
+            start <= key && key < end
+        } else {
+            key >= start || key < end
+        }
+    }
+
+    /// Whether at least one `unit` boundary (such as midnight for `BoundaryUnit::Day`) falls after `previous` and at or before this time,
+    /// reading the wall clock `offset` seconds from UTC - false if this time is not after `previous`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, BoundaryUnit};
+    /// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let now = at("2024-01-05 23:00:10");
+    /// assert!(now.crossed_boundary(&at("2024-01-05 22:59:50"), BoundaryUnit::Hour, 0));
+    /// assert!(!now.crossed_boundary(&at("2024-01-05 22:59:50"), BoundaryUnit::Day, 0));
+    /// // but it is past midnight in UTC+1
+    /// assert!(now.crossed_boundary(&at("2024-01-05 22:59:50"), BoundaryUnit::Day, 3600));
+    /// ```
+    fn crossed_boundary<U: Time>(&self, previous: &U, unit: BoundaryUnit, offset: i32) -> bool {
+        self.boundaries_crossed(previous, unit, offset) > 0
+    }
+
+    /// How many `unit` boundaries fall after `previous` and at or before this time, reading the wall clock `offset` seconds from UTC -
+    /// for catching up after a sleep. 0 if this time is not after `previous`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, BoundaryUnit};
+    /// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(at("2024-03-01 00:00:00").boundaries_crossed(&at("2024-01-31 12:00:00"), BoundaryUnit::Month, 0), 2);
+    /// assert_eq!(at("2024-01-08 00:00:00").boundaries_crossed(&at("2024-01-07 23:59:59"), BoundaryUnit::Week, 0), 1);
+    /// ```
+    fn boundaries_crossed<U: Time>(&self, previous: &U, unit: BoundaryUnit, offset: i32) -> u32 {
+        let crossed = unit.index(self.unix_ms(), offset) - unit.index(previous.unix_ms(), offset);
+        crossed.clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// round the time object down to the start of the given unit, read in its own offset
+    /// 
+    /// Weeks start on Monday (ISO) - use `week_days` for other start days
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, TruncateUnit};
+    /// let x = "2024-01-05 14:30:15".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.truncate_to(TruncateUnit::Hour).pretty(), "2024-01-05 14:00:00");
+    /// assert_eq!(x.truncate_to(TruncateUnit::Week).pretty(), "2024-01-01 00:00:00");
+    /// assert_eq!(x.truncate_to(TruncateUnit::Year).pretty(), "2024-01-01 00:00:00");
+    /// ```
+    fn truncate_to(&self, unit: TruncateUnit) -> Self
+    where Self: Sized {
+        let wall = self.wall_clock();
+        let date = wall.date();
+        let truncated = match unit {
+            TruncateUnit::Second => date.and_hms_opt(wall.hour(), wall.minute(), wall.second()),
+            TruncateUnit::Minute => date.and_hms_opt(wall.hour(), wall.minute(), 0),
+            TruncateUnit::Hour => date.and_hms_opt(wall.hour(), 0, 0),
+            TruncateUnit::Day => Some(date.and_time(NaiveTime::MIN)),
+            TruncateUnit::Week => Some((date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)).and_time(NaiveTime::MIN)),
+            TruncateUnit::Month => date.with_day(1).map(|d| d.and_time(NaiveTime::MIN)),
+            TruncateUnit::Year => date.with_ordinal(1).map(|d| d.and_time(NaiveTime::MIN)),
+        };
+        // every unit truncates to a valid wall clock time
+        Self::from_wall_clock(truncated.unwrap(), self.utc_offset())
+    }
+
+    /// Round down to a boundary of the shortest nice step (see `NICE_STEPS`) at or above `target`, on this value's wall clock - returns
+    /// the rounded time and the step used
+    ///
+    /// Steps of up to a day are counted from local midnight, and weekly steps start on Monday. Targets above 7 days use 7 days
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// use core::time::Duration;
+    /// let x = "2024-01-05 14:47:29".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let (floor, step) = x.nice_floor(Duration::from_secs(7 * 60));
+    /// assert_eq!((floor.pretty(), step), ("2024-01-05 14:40:00".to_string(), Duration::from_secs(600)));
+    /// assert_eq!(x.nice_floor(Duration::from_secs(4 * 3600)).0.pretty(), "2024-01-05 12:00:00");
+    /// ```
+    fn nice_floor(&self, target: core::time::Duration) -> (Self, core::time::Duration)
+    where Self: Sized {
+        let step = ticks::nice_step(target);
+        (ticks::snap_floor(self, step.as_millis() as u64), step)
+    }
+
+    /// Round up to a boundary of the shortest nice step at or above `target` - see `nice_floor`. Values already on a boundary are unchanged
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// use core::time::Duration;
+    /// let x = "2024-01-05 14:47:29".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.nice_ceil(Duration::from_secs(7 * 60)).0.pretty(), "2024-01-05 14:50:00");
+    /// assert_eq!(x.nice_ceil(Duration::from_secs(2 * 86400)).0.pretty(), "2024-01-08 00:00:00");
+    /// ```
+    fn nice_ceil(&self, target: core::time::Duration) -> (Self, core::time::Duration)
+    where Self: Sized {
+        let step = ticks::nice_step(target);
+        (ticks::snap_ceil(self, step.as_millis() as u64), step)
+    }
+
+    /// whether at least `d` has passed between this time object and `now` - a time exactly `d` old counts as older
+    /// 
+    /// Taking `now` as a parameter keeps expiry checks deterministic in tests
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let created = 1704465989u32.unix::<System>();
+    /// assert!(!created.is_older_than(Duration::from_secs(60), &created.add_seconds(59)));
+    /// assert!(created.is_older_than(Duration::from_secs(60), &created.add_seconds(60)));
+    /// ```
+    fn is_older_than<T: Time>(&self, d: core::time::Duration, now: &T) -> bool {
+        now.unix_ms() as i128 - self.unix_ms() as i128 >= d.as_millis() as i128
+    }
+
+    /// whether this time object is in the last `d` before `now` - from exactly `now` back to (but not including) `d` ago
+    /// 
+    /// Times after `now` are not within the last `d`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let now = 1704465989u32.unix::<System>();
+    /// assert!(now.add_seconds(-30).is_within_last(Duration::from_secs(60), &now));
+    /// assert!(!now.add_seconds(-60).is_within_last(Duration::from_secs(60), &now));
+    /// assert!(!now.add_seconds(1).is_within_last(Duration::from_secs(60), &now));
+    /// ```
+    fn is_within_last<T: Time>(&self, d: core::time::Duration, now: &T) -> bool {
+        self.unix_ms() <= now.unix_ms() && !self.is_older_than(d, now)
+    }
+
+    /// when something created at this time with a time-to-live of `ttl` expires, in the same offset (saturating at `far_future`, and never earlier than this time)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let created = 1704465989u32.unix::<System>();
+    /// assert_eq!(created.expires_at(Duration::from_secs(3600)).pretty(), "2024-01-05 15:46:29");
+    /// ```
+    fn expires_at(&self, ttl: core::time::Duration) -> Self
+    where Self: Sized {
+        let ms = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX);
+        let ceiling = ((FAR_FUTURE_UNIX_MS + OFFSET_1601 as i64 * 1000) as u64).max(self.raw());
+        Self::from_epoch_offset(self.raw().saturating_add(ms).min(ceiling), self.utc_offset())
+    }
+
+    /// how long is left before something created at this time with a time-to-live of `ttl` expires, or None once it has (exactly at the TTL counts as expired)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let created = 1704465989u32.unix::<System>();
+    /// let ttl = Duration::from_secs(60);
+    /// assert_eq!(created.remaining_ttl(ttl, &created.add_seconds(45)), Some(Duration::from_secs(15)));
+    /// assert_eq!(created.remaining_ttl(ttl, &created.add_seconds(60)), None);
+    /// ```
+    fn remaining_ttl<T: Time>(&self, ttl: core::time::Duration, now: &T) -> Option<core::time::Duration> {
+        let remaining = self.unix_ms() as i128 + ttl.as_millis() as i128 - now.unix_ms() as i128;
+        (remaining > 0).then(|| core::time::Duration::from_millis(remaining.min(u64::MAX as i128) as u64))
+    }
+
+    /// A one line stamp for request logs: the instant in RFC3339 (UTC, to the millisecond) and how long ago it was, such as
+    /// "2024-01-05T14:46:29.123Z (3.2s ago)"
+    ///
+    /// The distance is a single unit (`ms`, `s` to a tenth, then whole `m`, `h` or `d`), and saturates at `LOG_STAMP_HORIZON`, as in "(>30d ago)".
+    /// See `log_stamp_with` to pass `now` and the horizon
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// println!("{}", System::now().add_seconds(-3).log_stamp());
+    /// ```
+    fn log_stamp(&self) -> String {
+        self.log_stamp_with(None::<&System>, LOG_STAMP_HORIZON)
+    }
+
+    /// Like `log_stamp`, measured from `now` (or the current time, if None) and saturating beyond `horizon` - times after `now` are
+    /// "from now", and `now` itself is "(now)"
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime, LOG_STAMP_HORIZON};
+    /// use core::time::Duration;
+    /// let now = 1704465992u32.unix::<System>();
+    /// let x = now.add_seconds(-3);
+    /// assert_eq!(x.log_stamp_with(Some(&now), LOG_STAMP_HORIZON), "2024-01-05T14:46:29.000Z (3.0s ago)");
+    /// assert_eq!(x.log_stamp_with(Some(&now.add_days(-1)), Duration::from_secs(3600)), "2024-01-05T14:46:29.000Z (>1h from now)");
+    /// ```
+    fn log_stamp_with<T: Time>(&self, now: Option<&T>, horizon: core::time::Duration) -> String {
+        let now_ms = now.map_or_else(|| System::now().unix_ms(), |n| n.unix_ms());
+        let distance = self.unix_ms().abs_diff(now_ms);
+        let horizon_ms = u64::try_from(horizon.as_millis()).unwrap_or(u64::MAX);
+        let when = match self.unix_ms().cmp(&now_ms) {
+            core::cmp::Ordering::Equal => "now".to_string(),
+            order => {
+                let suffix = if order.is_lt() { "ago" } else { "from now" };
+                if distance > horizon_ms {
+                    format!(">{} {}", compact_duration(horizon_ms), suffix)
+                } else {
+                    format!("{} {}", compact_duration(distance), suffix)
+                }
+            }
+        };
+        let utc = DateTime::from_timestamp_millis(self.unix_ms()).unwrap().naive_utc();
+        format!("{} ({})", utc.format("%Y-%m-%dT%H:%M:%S%.3fZ"), when)
+    }
+
+    /// Describe this time relative to `now` in calendar words - "today at 14:30", "yesterday", "tomorrow at 09:00", "last Tuesday",
+    /// "next week", "in March" - the further away it is, the coarser the description
+    ///
+    /// Days and months are each time object's own, so a time in another offset is described by its local date. The words are English -
+    /// see `describe_relative_with` for other languages
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// let now = 1704465989u32.unix::<System>(); // Friday 2024-01-05 14:46:29
+    /// assert_eq!(now.add_hours(-1).describe_relative(&now), "today at 13:46");
+    /// assert_eq!(now.add_days(-3).describe_relative(&now), "last Tuesday");
+    /// assert_eq!(now.add_days(60).describe_relative(&now), "in March");
+    /// ```
+    fn describe_relative<T: Time>(&self, now: &T) -> String {
+        self.describe_relative_with(now, &RelativeWords::EN)
+    }
+
+    /// Like `describe_relative`, with the phrases and names from `words`
+    fn describe_relative_with<T: Time>(&self, now: &T, words: &RelativeWords) -> String {
+        words.describe(self.wall_clock(), now.wall_clock())
+    }
+
+    /// an endless iterator of times starting at this one, `step` apart (to the millisecond - a step under 1ms yields only this time)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let ticks: Vec<String> = 1704465989u32.unix::<System>().iter_from(Duration::from_millis(500)).take(3).map(|t| t.strftime("%S%.3f")).collect();
+    /// assert_eq!(ticks, vec!["29.000", "29.500", "30.000"]);
+    /// ```
+    fn iter_from(self, step: core::time::Duration) -> TimeSteps<Self>
+    where Self: Sized {
+        TimeSteps::new(self, step, false, None)
+    }
+
+    /// times from this one to `end`, `step` apart - `end` is included if a step lands exactly on it, and nothing is yielded if it is earlier than this time
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let hours: Vec<i64> = 0u32.unix::<System>().iter_until(&7200u32.unix::<System>(), Duration::from_secs(3600)).map(|t| t.unix()).collect();
+    /// assert_eq!(hours, vec![0, 3600, 7200]);
+    /// ```
+    fn iter_until<T: Time>(self, end: &T, step: core::time::Duration) -> TimeSteps<Self>
+    where Self: Sized {
+        TimeSteps::new(self, step, false, Some(end.raw()))
+    }
+
+    /// times going backwards from this one, `step` apart, stopping at the last one that is not before `1601-01-01 00:00:00`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// use core::time::Duration;
+    /// let days = 1704465989u32.unix::<System>().countdown(Duration::from_secs(86400)).take(2).map(|t| t.pretty()).collect::<Vec<_>>();
+    /// assert_eq!(days, vec!["2024-01-05 14:46:29", "2024-01-04 14:46:29"]);
+    /// ```
+    fn countdown(self, step: core::time::Duration) -> TimeSteps<Self>
+    where Self: Sized {
+        TimeSteps::new(self, step, true, None)
+    }
+
+    /// how far through `span` this time object is, from 0.0 at the start to 1.0 at the end (clamped outside the span - an empty span is 0.0 before it and 1.0 from it on)
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeSpan, StrTime};
+    /// let sprint = TimeSpan::new(
+    ///     "2024-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S"),
+    ///     "2024-01-15 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S"),
+    /// );
+    /// let now = "2024-01-08 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(now.progress_through(&sprint), 0.5);
+    /// ```
+    fn progress_through(&self, span: &TimeSpan<Self>) -> f64
+    where Self: Sized {
+        let ((start, end), now) = (span.bounds(), self.raw());
+        if now < start {
+            0.0
+        } else if now >= end {
+            1.0
+        } else {
+            (now - start) as f64 / (end - start) as f64
+        }
+    }
+
+    /// how far through its day (in its own offset) this time object is
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let noon = "2024-01-05 12:00:00 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+    /// assert_eq!(noon.progress_through_day(), 0.5);
+    /// ```
+    fn progress_through_day(&self) -> f64
+    where Self: Sized {
+        self.progress_through(&span::period_of(self, TruncateUnit::Day))
+    }
+
+    /// how far through its month (in its own offset) this time object is
+    fn progress_through_month(&self) -> f64
+    where Self: Sized {
+        self.progress_through(&span::period_of(self, TruncateUnit::Month))
+    }
+
+    /// how far through its year (in its own offset) this time object is - leap years have 366 days
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2024-07-02 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(x.progress_through_year(), 183.0 / 366.0);
+    /// ```
+    fn progress_through_year(&self) -> f64
+    where Self: Sized {
+        self.progress_through(&span::period_of(self, TruncateUnit::Year))
+    }
+
+    /// the seven midnights of the week containing this time object, starting from `week_start`, in its own offset
+    /// 
+    /// Use `Weekday::Mon` for ISO weeks, `Weekday::Sun` for US style weeks, or any other start day as needed
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, Weekday};
+    /// let x = "2024-01-06 13:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S"); // a Saturday
+    /// let days = x.week_days(Weekday::Mon);
+    /// assert_eq!(days[0].pretty(), "2024-01-01 00:00:00");
+    /// assert_eq!(days[6].pretty(), "2024-01-07 00:00:00");
+    /// ```
+    fn week_days(&self, week_start: Weekday) -> [Self; 7]
+    where Self: Sized {
+        let date = self.wall_clock().date();
+        let back = (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7;
+        let first = date - chrono::Duration::days(back as i64);
+        core::array::from_fn(|i| {
+            let day = first + chrono::Duration::days(i as i64);
+            Self::from_wall_clock(day.and_time(NaiveTime::MIN), self.utc_offset())
+        })
+    }
+
+    /// the span of the week containing this time object, from the midnight starting `week_start` to the midnight a week later
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, Weekday};
+    /// let x = "2024-01-06 13:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let span = x.week_span(Weekday::Sun);
+    /// assert_eq!(span.start.pretty(), "2023-12-31 00:00:00");
+    /// assert_eq!(span.end.pretty(), "2024-01-07 00:00:00");
+    /// ```
+    fn week_span(&self, week_start: Weekday) -> TimeSpan<Self>
+    where Self: Sized {
+        let [start, ..] = self.week_days(week_start);
+        // not add_days, which would drop the offset
+        let end = Self::from_wall_clock(start.wall_clock() + chrono::Duration::days(7), self.utc_offset());
+        TimeSpan::new(start, end)
+    }
+
+    /// whether `other` falls in the same week as this time object, with weeks starting on `week_start`
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, Weekday};
+    /// let sat = "2024-01-06 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let sun = "2024-01-07 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert!(sat.same_week_as(&sun, Weekday::Mon));
+    /// assert!(!sat.same_week_as(&sun, Weekday::Sun));
+    /// ```
+    fn same_week_as<T: Time>(&self, other: &T, week_start: Weekday) -> bool
+    where Self: Sized {
+        self.week_span(week_start).contains(other)
+    }
+
+    /// the ISO 8601 week-numbering year of this time object's wall clock - the same as the calendar year, except that days around new year
+    /// may belong to the neighbouring year's weeks
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "2021-01-03 12:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!((x.iso_week_year(), x.iso_week()), (2020, 53));
+    /// ```
+    fn iso_week_year(&self) -> i32 {
+        self.wall_clock().iso_week().year()
+    }
+
+    /// the ISO 8601 week number (1 to 53) of this time object's wall clock - see `iso_week_year` and `YearWeek`
+    fn iso_week(&self) -> u32 {
+        self.wall_clock().iso_week().week()
+    }
+
+    /// internal only - the wall clock this value displays as (ie what `strftime` renders): the UTC instant shifted by the offset
+    #[doc(hidden)]
+    fn wall_clock(&self) -> NaiveDateTime {
+        self.try_wall_clock().unwrap()
+    }
+
+    /// internal only - `wall_clock`, or None if it is out of chrono's range
+    #[doc(hidden)]
+    fn try_wall_clock(&self) -> Option<NaiveDateTime> {
+        let ms = (self.raw() as i64).checked_sub(OFFSET_1601 as i64 * 1000i64)?.checked_add(self.utc_offset() as i64 * 1000i64)?;
+        Some(DateTime::from_timestamp_millis(ms)?.naive_utc())
+    }
+
+    /// internal only - builds the value that displays as `wall` with the given offset
+    #[doc(hidden)]
+    fn from_wall_clock(wall: NaiveDateTime, offset: i32) -> Self
+    where Self: Sized {
+        let ms = wall.and_utc().timestamp_millis() - (offset as i64 * 1000i64) + (OFFSET_1601 as i64 * 1000i64);
+        Self::from_epoch_offset(ms.max(0) as u64, offset)
+    }
+
+    /// internal only
+    #[doc(hidden)]
+    fn from_epoch_offset(timestamp: u64, offset: i32) -> Self;
+}
+
+/// A trait so that we can use chrono::Duration, core::time::Duration and whole seconds as an `i64` interchangeably in the
+/// `Time::add_duration` function and the `format_hms` family
+pub trait ImplsDuration {
+    fn num_seconds(&self) -> i64;
+
+    /// the duration in whole milliseconds (defaults to the whole seconds, for implementors without sub-second precision)
+    fn num_milliseconds(&self) -> i64 {
+        self.num_seconds() * 1000
+    }
+}
+impl ImplsDuration for chrono::Duration {
+    fn num_seconds(&self) -> i64 {
+        self.num_seconds()
+    }
+
+    fn num_milliseconds(&self) -> i64 {
+        self.num_milliseconds()
+    }
+}
+
+impl ImplsDuration for core::time::Duration {
+    fn num_seconds(&self) -> i64 {
+        self.as_secs() as i64
+    }
+
+    fn num_milliseconds(&self) -> i64 {
+        self.as_millis() as i64
+    }
+}
+
+impl ImplsDuration for i64 {
+    fn num_seconds(&self) -> i64 {
+        *self
+    }
+
+    fn num_milliseconds(&self) -> i64 {
+        self.saturating_mul(1000)
+    }
+}
+
+/// Implements the diff functions (optional)
+pub trait TimeDiff {
+    /// Get the difference between two times in seconds
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeDiff, StrTime, IntTime};
+    /// let x = "2018-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let y = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// println!("{} seconds difference", x.diff(&y));
+    /// assert_eq!(x.diff(&y), 31536000u64);
+    /// ```
+    fn diff<T: Time>(&self, other: &T) -> u64
+    where
+        Self: Time,
+    {
+        self.raw().abs_diff(other.raw()) / 1000
+    }
+
+    /// Get the difference between two times in milliseconds
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeDiff};
+    /// let x = System::now();
+    /// let y = System::now();
+    /// println!("{} milliseconds difference", x.diff_ms(&y));
+    /// ```
+    fn diff_ms<T: Time>(&self, other: &T) -> u64
+    where
+        Self: Time,
+    {
+        self.raw().abs_diff(other.raw())
+    }
+}
+
+/// Provides wrappers on string std types to parse into time structs
+pub trait StrTime {
+    /// Parse a string into a time struct of choice
+    ///
+    /// Panics if the input doesn't match, as `Time::strptime` does - use `try_parse_time` for input that comes from users
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// println!("2017 - {}", "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S"));
+    /// println!("{}", "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").unix());
+    /// assert_eq!("2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").unix(), 1483228800);
+    /// ```
+    fn parse_time<T: Time>(&self, format: &str) -> T
+    where
+        Self: core::fmt::Display,
+    {
+        T::strptime(self, format)
+    }
+
+    /// Parse a string into a time struct of choice, using the ISO8601 format
+    ///
+    /// The fraction of a second is optional, and can have any number of digits - ".5" is 500ms
+    ///
+    /// Panics if the input doesn't match, as `Time::strptime` does - use `try_strp_iso8601` for input that comes from users
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// println!("2017 - {}", "2017-01-01T00:00:00.000".strp_iso8601::<System>());
+    /// println!("{}", "2017-01-01T00:00:00.000".strp_iso8601::<System>().unix());
+    /// assert_eq!("2017-01-01T00:00:00.000".strp_iso8601::<System>().unix(), 1483228800);
+    /// ```
+    fn strp_iso8601<T: Time>(&self) -> T
+    where
+        Self: core::fmt::Display,
+    {
+        T::strptime(self, "%Y-%m-%dT%H:%M:%S%.f")
+    }
+
+    /// Parse a string into a time struct of choice, using the RFC3339 format
+    ///
+    /// The fraction of a second is optional, and can have any number of digits - ".5" is 500ms
+    ///
+    /// Panics if the input doesn't match, as `Time::strptime` does - use `try_strp_rf3339` for input that comes from users
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// println!("2017 - {}", "2017-01-01T00:00:00.000Z".strp_rf3339::<System>());
+    /// println!("{}", "2017-01-01T00:00:00.000Z".strp_rf3339::<System>().unix());
+    /// assert_eq!("2017-01-01T00:00:00.000Z".strp_rf3339::<System>().unix(), 1483228800);
+    /// assert_eq!("2017-01-01T00:00:00.05Z".strp_rf3339::<System>().unix_ms(), 1483228800050);
+    /// ```
+    fn strp_rf3339<T: Time>(&self) -> T
+    where
+        Self: core::fmt::Display,
+    {
+        T::strptime(self, "%Y-%m-%dT%H:%M:%S%.fZ")
+    }
+
+    /// Parse a string into a time struct of choice as `parse_time` does, but returning an error rather than panicking
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("2017-01-01 00:00:00".try_parse_time::<System>("%Y-%m-%d %H:%M:%S").unwrap().unix(), 1483228800);
+    /// assert!("garbage".try_parse_time::<System>("%Y-%m-%d").is_err());
+    /// ```
+    fn try_parse_time<T: Time>(&self, format: &str) -> Result<T, ParseTimeError>
+    where
+        Self: core::fmt::Display,
+    {
+        T::try_strptime(self, format)
+    }
+
+    /// Parse a string into a time struct of choice using the ISO8601 format, as `strp_iso8601` does, but returning an error rather than
+    /// panicking
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("2017-01-01T00:00:00.000".try_strp_iso8601::<System>().unwrap().unix(), 1483228800);
+    /// assert!("2017-01-01".try_strp_iso8601::<System>().is_err());
+    /// ```
+    fn try_strp_iso8601<T: Time>(&self) -> Result<T, ParseTimeError>
+    where
+        Self: core::fmt::Display,
+    {
+        T::try_strptime(self, "%Y-%m-%dT%H:%M:%S%.f")
+    }
+
+    /// Parse a string into a time struct of choice using the RFC3339 format, as `strp_rf3339` does, but returning an error rather than
+    /// panicking
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("2017-01-01T00:00:00.000Z".try_strp_rf3339::<System>().unwrap().unix(), 1483228800);
+    /// assert!("2017-01-01T00:00:00.000".try_strp_rf3339::<System>().is_err());
+    /// ```
+    fn try_strp_rf3339<T: Time>(&self) -> Result<T, ParseTimeError>
+    where
+        Self: core::fmt::Display,
+    {
+        T::try_strptime(self, "%Y-%m-%dT%H:%M:%S%.fZ")
+    }
+
+    /// Parse an "ISO8601-ish" string leniently, as found in real world logs
+    /// 
+    /// - the date and time can be separated by 'T', spaces or an underscore
+    /// - the fraction can use a comma or a dot
+    /// - trailing components can be left off (eg "2024-01-05T14" or "2024-01-05"), and are filled with zeros
+    /// - an optional "Z", "+HH", "+HHMM" or "+HH:MM" suffix sets the offset, otherwise UTC is assumed
+    /// 
+    /// Out of range fields (month 13, hour 25) and malformed input are rejected, with the byte position of the problem
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("2024-01-05_14:46:29,123".strp_iso_lenient::<System>().unwrap().unix_ms(), 1704465989123);
+    /// assert_eq!("2024-01-05T14".strp_iso_lenient::<System>().unwrap().pretty(), "2024-01-05 14:00:00");
+    /// assert!("2024-13-05".strp_iso_lenient::<System>().is_err());
+    /// ```
+    fn strp_iso_lenient<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::iso_lenient(&self.to_string())
+    }
+
+    /// Parse a date as JavaScript prints it, for strings copied out of a browser console
+    ///
+    /// Accepts `Date.toString()` output ("Fri Jan 05 2024 14:46:29 GMT+0530 (India Standard Time)"), whose "GMT+HHMM" sets the offset and
+    /// whose trailing parenthesized zone name is optional and ignored, and `Date.toUTCString()` output ("Fri, 05 Jan 2024 09:16:29 GMT").
+    /// The weekday must be letters but is otherwise not checked, as the date already says which day it is
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "Fri Jan 05 2024 14:46:29 GMT+0530 (India Standard Time)".strp_js_date::<System>().unwrap();
+    /// assert_eq!(x.utc_offset(), 19800);
+    /// assert_eq!(x.unix(), 1704446189);
+    /// assert_eq!("Fri, 05 Jan 2024 09:16:29 GMT".strp_js_date::<System>().unwrap().unix(), 1704446189);
+    /// assert!("Fri Jan 32 2024 14:46:29 GMT+0530".strp_js_date::<System>().is_err());
+    /// ```
+    fn strp_js_date<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::js_date(&self.to_string(), &ParseOptions::default())
+    }
+
+    /// Parse JavaScript's `Date.toString()` or `Date.toUTCString()` output as `strp_js_date` does, but with the weekday and
+    /// day-of-month checks `options` ask for
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, ParseOptions};
+    /// // 5 Jan 2024 was a Friday
+    /// assert!("Mon Jan 05 2024 14:46:29 GMT+0530".strp_js_date_with::<System>(&ParseOptions::strict()).is_err());
+    /// assert_eq!("Fri Feb 30 2024 14:46:29 GMT+0000".strp_js_date_with::<System>(&ParseOptions::lenient()).unwrap().strftime("%Y-%m-%d"), "2024-03-01");
+    /// ```
+    fn strp_js_date_with<T: Time>(&self, options: &ParseOptions) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::js_date(&self.to_string(), options)
+    }
+
+    /// Parse an RFC 2822 (email `Date:` header) date, such as "Tue, 1 Jul 2003 10:52:37 +0200", keeping its offset
+    ///
+    /// The weekday is optional, and the obsolete forms are accepted: two or three digit years, zone names ("GMT", "EST"...) and a
+    /// trailing comment such as "(PDT)". The weekday is not checked against the date - use `strp_rfc2822_with` for that
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "Tue, 1 Jul 2003 10:52:37 +0200".strp_rfc2822::<System>().unwrap();
+    /// assert_eq!(x.utc_offset(), 7200);
+    /// assert_eq!(x.unix(), 1057049557);
+    /// assert_eq!("01 Jul 03 08:52:37 GMT".strp_rfc2822::<System>().unwrap().unix(), 1057049557);
+    /// assert!("Tue, 31 Jun 2003 10:52:37 +0200".strp_rfc2822::<System>().is_err());
+    /// ```
+    fn strp_rfc2822<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::rfc2822(&self.to_string(), &ParseOptions::default())
+    }
+
+    /// Parse an RFC 2822 date as `strp_rfc2822` does, but with the weekday and day-of-month checks `options` ask for
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, ParseOptions};
+    /// let x = "Fri, 30 Feb 2024 12:00 +0000".strp_rfc2822_with::<System>(&ParseOptions::lenient()).unwrap();
+    /// assert_eq!(x.strftime("%Y-%m-%d"), "2024-03-01");
+    /// assert!("Fri, 30 Feb 2024 12:00 +0000".strp_rfc2822_with::<System>(&ParseOptions::strict()).is_err());
+    /// ```
+    fn strp_rfc2822_with<T: Time>(&self, options: &ParseOptions) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::rfc2822(&self.to_string(), options)
+    }
+
+    /// Parse a DER ASN.1 UTCTime, as in X.509 validity fields: exactly "YYMMDDHHMMSSZ", in UTC
+    ///
+    /// Two digit years below 50 are 20YY, and the rest 19YY (RFC 5280). Fractions, offsets and a missing 'Z' are rejected, with the byte
+    /// position of the problem
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("240105144629Z".strp_asn1_utctime::<System>().unwrap().unix(), 1704465989);
+    /// assert_eq!("500101000000Z".strp_asn1_utctime::<System>().unwrap().pretty(), "1950-01-01 00:00:00");
+    /// assert!("240105144629".strp_asn1_utctime::<System>().is_err());
+    /// ```
+    fn strp_asn1_utctime<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::asn1_utctime(&self.to_string())
+    }
+
+    /// Parse a DER ASN.1 GeneralizedTime: "YYYYMMDDHHMMSS" with an optional fraction of a second after a '.', then 'Z', in UTC
+    ///
+    /// As DER requires, the seconds and the 'Z' must be there, and a fraction must not end in a zero. Digits after the milliseconds are
+    /// dropped
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("20240105144629.25Z".strp_asn1_generalizedtime::<System>().unwrap().unix_ms(), 1704465989250);
+    /// assert!("20240105144629.250Z".strp_asn1_generalizedtime::<System>().is_err());
+    /// assert!("20240105144629+0000".strp_asn1_generalizedtime::<System>().is_err());
+    /// ```
+    fn strp_asn1_generalizedtime<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::asn1_generalizedtime(&self.to_string())
+    }
+
+    /// Parse an Apache/nginx access log (Common Log Format) timestamp, such as "[05/Jan/2024:14:46:29 +0000]", keeping its offset
+    ///
+    /// The brackets are optional, and either may be missing. Month names ignore case. Errors carry the byte position of the problem,
+    /// counted after trimming surrounding whitespace
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "[05/Jan/2024:14:46:29 +0100]".strp_clf::<System>().unwrap();
+    /// assert_eq!((x.unix(), x.utc_offset()), (1704462389, 3600));
+    /// assert_eq!("05/JAN/2024:14:46:29 +0100".strp_clf::<System>().unwrap(), x);
+    /// assert!("[05/Jan/2024 14:46:29 +0100]".strp_clf::<System>().is_err());
+    /// ```
+    fn strp_clf<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::clf(&self.to_string())
+    }
+
+    /// Parse an nginx error log timestamp, such as "2024/01/05 14:46:29". These have no offset, so UTC is assumed (see
+    /// `Time::offset_source`). Errors carry the byte position of the problem
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, OffsetSource};
+    /// let x = "2024/01/05 14:46:29".strp_nginx_error::<System>().unwrap();
+    /// assert_eq!(x.unix(), 1704465989);
+    /// assert_eq!(x.offset_source(), OffsetSource::AssumedUtc);
+    /// ```
+    fn strp_nginx_error<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::nginx_error(&self.to_string())
+    }
+
+    /// Parse the epoch field of a satellite two-line element set, such as "08264.51782528": a two digit year, the day of the year (1 for
+    /// January 1st) and the fraction of that day, in UTC
+    ///
+    /// Two digit years below 57 are 20YY, and the rest 19YY (the convention since Sputnik). The fraction may have any number of digits,
+    /// and is rounded to the nearest millisecond. Surrounding whitespace is ignored, and errors carry the byte position of the problem
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// let x = "08264.51782528".strp_tle_epoch::<System>().unwrap();
+    /// assert_eq!(x.strftime("%Y-%m-%d %H:%M:%S%.3f"), "2008-09-20 12:25:40.104");
+    /// assert_eq!("57001.0".strp_tle_epoch::<System>().unwrap().pretty(), "1957-01-01 00:00:00");
+    /// assert!("23366.5".strp_tle_epoch::<System>().is_err());
+    /// ```
+    fn strp_tle_epoch<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        parse::tle_epoch(&self.to_string())
+    }
+
+    /// Parse a string with a strftime format that may hold `%Q`, a timezone name or abbreviation (such as "EST", "CET" or "BST/CET") that
+    /// sets the offset for the rest of the time
+    ///
+    /// `%Q` matches a run of ASCII letters and slashes, looked up with `Tz::from_name` and then `Tz::from_abbreviation`. An abbreviation
+    /// that means several zones ("IST", "CST") takes the first of them found in `preferences`, and is an error if there is none. Errors
+    /// instead of panicking - unknown names list similar known ones. Without a `%Q`, this parses as `parse_time_bytes` does
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, Tz};
+    /// let x = "2024-01-05 09:46:29 EST".parse_with_tz_names::<System>("%Y-%m-%d %H:%M:%S %Q", &[]).unwrap();
+    /// assert_eq!(x.to_utc().pretty(), "2024-01-05 14:46:29");
+    /// let irish = "2024-01-05 15:46:29 IST".parse_with_tz_names::<System>("%Y-%m-%d %H:%M:%S %Q", &[Tz::BstCet]).unwrap();
+    /// assert_eq!(irish.utc_offset(), 3600);
+    /// assert!("2024-01-05 15:46:29 IST".parse_with_tz_names::<System>("%Y-%m-%d %H:%M:%S %Q", &[]).is_err());
+    /// ```
+    #[cfg(feature = "timezones")]
+    fn parse_with_tz_names<T: Time>(&self, format: &str, preferences: &[Tz]) -> Result<T, TzNameError>
+    where
+        Self: core::fmt::Display,
+    {
+        timezones::parse_with_tz_names(&self.to_string(), format, preferences)
+    }
+
+    /// Parse an integer timestamp (as `epoch::parse_int_timestamp` does: separators, hex and binary are accepted) counted in `unit`s since
+    /// `epoch`, into a time struct of choice
+    ///
+    /// Errors with `ParseError::OutOfRange` for values the time struct can't hold
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime, epoch::{Epoch, Unit}};
+    /// assert_eq!("1,483,228,800".parse_epoch::<System>(Epoch::Unix, Unit::Seconds).unwrap().pretty(), "2017-01-01 00:00:00");
+    /// assert_eq!("0x01D263C1FEB0C000".parse_epoch::<System>(Epoch::Windows, Unit::Ticks100ns).unwrap().pretty(), "2017-01-01 00:00:00");
+    /// ```
+    fn parse_epoch<T: Time>(&self, epoch: epoch::Epoch, unit: epoch::Unit) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        let value = epoch::parse_int_timestamp(&self.to_string())?;
+        let out_of_range = ParseError::OutOfRange {
+            field: "timestamp",
+            value: i64::try_from(value).unwrap_or(i64::MAX),
+        };
+        let value = i64::try_from(value).map_err(|_| out_of_range.clone())?;
+        epoch.decode(value, unit).map_err(|_| out_of_range)
+    }
+
+    /// Parse a self-describing timestamp from `Time::tagged`, such as "webkit-us:13127702400000000", into a time struct of choice
+    ///
+    /// Unknown tags are rejected with the list of supported ones - see `epoch::parse_tagged`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, StrTime};
+    /// assert_eq!("unix-s:1483228800".parse_tagged::<System>().unwrap().pretty(), "2017-01-01 00:00:00");
+    /// assert!("unix-ks:1483228".parse_tagged::<System>().is_err());
+    /// ```
+    fn parse_tagged<T: Time>(&self) -> Result<T, ParseError>
+    where
+        Self: core::fmt::Display,
+    {
+        epoch::parse_tagged(&self.to_string())
+    }
+}
+
+/// Provides wrappers on integer std types to parse into time structs, and also to pretty print timestamp integers
+///
+/// Note: If there is an error, the function will return the Unix epoch time for the struct of choice
+/// 
+/// You can only convert from positive integers, as negative integers are not supported, as they cannot be represented in the time structs. While it would be possible to fix this, I don't think it is a needed feature at the moment.
+pub trait IntTime: core::fmt::Display + Into<u64> {
+    /// Convert an integer into a time struct of choice
+    ///
+    /// Exact both ways for values up to `u64::MAX / 1000 - OFFSET_1601` (`x.unix::<T>().unix() == x`), saturating at the latest representable
+    /// time beyond that. A time's `unix()` converts back exactly (to the second) when it is not before 1970
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(1483228800u32.unix::<System>().pretty(), "2017-01-01 00:00:00");
+    /// ```
+    fn unix<T: Time>(self) -> T {
+        from_epoch_secs(self.into(), OFFSET_1601 as i64)
+    }
+
+    /// Convert an integer into a time struct of choice, from a Windows timestamp (100ns since `1601-01-01 00:00:00`)
+    ///
+    /// Truncates to the millisecond, so only multiples of 10,000 (up to `i64::MAX`, where `Time::windows_ns` saturates) convert back
+    /// exactly. Every time up to `i64::MAX / 10_000` milliseconds since 1601 (about the year 30828) converts back exactly from its `windows_ns()`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// assert_eq!(131277024000000000u64.windows_ns::<System>().pretty(),"2017-01-01 00:00:00");
+    /// ```
+    fn windows_ns<T: Time>(self) -> T {
+        T::from_epoch(self.into() / 10_000)
+    }
+
+    /// Convert an integer into a time struct of choice, from a Webkit timestamp (microseconds since `1601-01-01 00:00:00`)
+    /// 
+    /// Truncates to the millisecond, so only multiples of 1,000 (up to `i64::MAX`, where `Time::webkit` saturates) convert back exactly.
+    /// Every time up to `i64::MAX / 1000` milliseconds since 1601 converts back exactly from its `webkit()`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// println!("2017 - {:#?}", 13127702400000000u64.webkit::<System>());
+    /// assert_eq!(13127702400000000u64.webkit::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2017-01-01 00:00:00");
+    /// ```
+    fn webkit<T: Time>(self) -> T {
+        T::from_epoch(self.into() / 1000)
+    }
+
+    /// Convert an integer into a time struct of choice, from a Mac OS timestamp (seconds since 1904-01-01 00:00:00)
+    ///
+    /// Exact both ways for values up to `u64::MAX / 1000 - (OFFSET_1601 - MAGIC_MAC_OS)`, saturating beyond that. A time's `mac_os()`
+    /// converts back exactly (to the second) when it is not before 1904
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// println!("2024 - {:#?}", 3787310789u64.mac_os::<System>());
+    /// assert_eq!(3787310789u64.mac_os::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-05 14:46:29");
+    /// ```
+    fn mac_os<T: Time>(self) -> T {
+        from_epoch_secs(self.into(), OFFSET_1601 as i64 - MAGIC_MAC_OS)
+    }
+
+    /// Convert an integer into a time struct of choice, from a Mac OS Absolute timestamp (seconds since 2001-01-01 00:00:00)
+    ///
+    /// Exact both ways for values up to `u64::MAX / 1000 - (OFFSET_1601 + MAGIC_MAC_OS_CFA)`, saturating beyond that. A time's `mac_os_cfa()`
+    /// converts back exactly (to the second) when it is not before 2001
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// println!("2024 - {:#?}", 726158877u64.mac_os_cfa::<System>());
+    /// assert_eq!(726158877u64.mac_os_cfa::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-05 14:47:57");
+    /// ```
+    fn mac_os_cfa<T: Time>(self) -> T {
+        from_epoch_secs(self.into(), OFFSET_1601 as i64 + MAGIC_MAC_OS_CFA)
+    }
+
+    /// Convert an integer into a time struct of choice, from a SAS 4GL timestamp (seconds since 1960-01-01 00:00:00)
+    ///
+    /// Exact both ways for values up to `u64::MAX / 1000 - (OFFSET_1601 - MAGIC_SAS_4GL)`, saturating beyond that. A time's `sas_4gl()`
+    /// converts back exactly (to the second) when it is not before 1960
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// println!("2024 - {:#?}", 2020003754u64.sas_4gl::<System>());
+    /// assert_eq!(2020003754u64.sas_4gl::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-04 16:09:14");
+    /// ```
+    fn sas_4gl<T: Time>(self) -> T {
+        from_epoch_secs(self.into(), OFFSET_1601 as i64 - MAGIC_SAS_4GL)
+    }
+
+    /// Prints the time duration in a formatted string. Note that this only goes up to weeks, as years are rather subjective
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::IntTime;
+    /// let duration = 3600u64;
+    /// let formatted = duration.ts_print();
+    /// assert_eq!(formatted, "0w 0d 1h 0m 0s");
+    /// ```
+    fn ts_print(self) -> String {
+        let duration = chrono::Duration::seconds(self.into() as i64);
+        format!(
+            "{}w {}d {}h {}m {}s",
+            duration.num_weeks(),
+            duration.num_days() % 7,
+            duration.num_hours() % 24,
+            duration.num_minutes() % 60,
+            duration.num_seconds() % 60
+        )
+    }
+
+    /// Like `ts_print`, but taking the integer as milliseconds (as from `diff_ms`), printing the seconds with 3 decimal places
+    /// 
+    /// 0 prints as "0w 0d 0h 0m 0.000s", and durations over a year keep counting weeks (a year is "52w 1d ...")
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::IntTime;
+    /// assert_eq!(90_250u64.ts_print_ms(), "0w 0d 0h 1m 30.250s");
+    /// ```
+    fn ts_print_ms(self) -> String {
+        self.ts_print_ms_with(3)
+    }
+
+    /// Like `ts_print_ms`, with `places` decimal places on the seconds - the milliseconds are truncated (not rounded) to fit, and zero padded past 3 places
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::IntTime;
+    /// assert_eq!(90_250u64.ts_print_ms_with(1), "0w 0d 0h 1m 30.2s");
+    /// assert_eq!(90_250u64.ts_print_ms_with(0), "0w 0d 0h 1m 30s");
+    /// ```
+    fn ts_print_ms_with(self, places: usize) -> String {
+        let ms: u64 = self.into();
+        let [weeks, days, hours, minutes, seconds] = duration_units(ms / 1000);
+        let fraction = match places {
+            0 => String::new(),
+            1..=3 => format!(".{:03}", ms % 1000)[..=places].to_string(),
+            _ => format!(".{:03}{}", ms % 1000, "0".repeat(places - 3)),
+        };
+        format!("{}w {}d {}h {}m {}{}s", weeks, days, hours, minutes, seconds, fraction)
+    }
+
+    /// Prints the integer (as seconds) with at most `max_units` units, starting from the largest that is not zero - so 90061 seconds
+    /// with 2 units is "1d 1h". Like `ts_print` this stops at weeks. 0 prints as "0s", and `max_units` of 0 is treated as 1
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::IntTime;
+    /// assert_eq!(90061u64.ts_print_units(2), "1d 1h");
+    /// assert_eq!(3600u64.ts_print_units(3), "1h 0m 0s");
+    /// assert_eq!(0u64.ts_print_units(2), "0s");
+    /// ```
+    fn ts_print_units(self, max_units: usize) -> String {
+        let units = duration_units(self.into());
+        let first = units.iter().position(|u| *u != 0).unwrap_or(units.len() - 1);
+        units[first..]
+            .iter()
+            .zip(&["w", "d", "h", "m", "s"][first..])
+            .take(max_units.max(1))
+            .map(|(n, unit)| format!("{}{}", n, unit))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// internal only - split seconds into weeks, days, hours, minutes and seconds
+fn duration_units(secs: u64) -> [u64; 5] {
+    [secs / 604_800, secs / 86_400 % 7, secs / 3600 % 24, secs / 60 % 60, secs % 60]
+}
+
+/// internal only - `secs` seconds after an epoch `epoch_from_1601` seconds after 1601, as a time struct, saturating at the largest raw value
+fn from_epoch_secs<T: Time>(secs: u64, epoch_from_1601: i64) -> T {
+    let ms = (secs as i128 + epoch_from_1601 as i128) * 1000;
+    T::from_epoch(ms.clamp(0, u64::MAX as i128) as u64)
+}
+
+/// internal only - milliseconds since 1601 as Unix milliseconds, saturating at `i64::MAX` (for raw values past the year 292 million)
+pub(crate) fn unix_ms_of_raw(raw: u64) -> i64 {
+    (raw as i128 - OFFSET_1601 as i128 * 1000).min(i64::MAX as i128) as i64
+}
+
+/// internal only - a duration in milliseconds as its largest whole unit, for `Time::log_stamp` ("450ms", "3.2s", "12m", "5h", "30d")
+fn compact_duration(ms: u64) -> String {
+    match ms {
+        0..=999 => format!("{}ms", ms),
+        1_000..=59_999 => format!("{}.{}s", ms / 1000, ms / 100 % 10),
+        60_000..=3_599_999 => format!("{}m", ms / 60_000),
+        3_600_000..=86_399_999 => format!("{}h", ms / 3_600_000),
+        _ => format!("{}d", ms / 86_400_000),
+    }
+}
+
+/// internal only - an instant as a UTC string to the millisecond, for the Debug output of the time structs
+pub(crate) fn debug_instant(unix_ms: i64) -> String {
+    match DateTime::from_timestamp_millis(unix_ms) {
+        Some(utc) => utc.format("%Y-%m-%d %H:%M:%S%.3f UTC").to_string(),
+        None => format!("{}ms since 1970 UTC", unix_ms),
+    }
+}
+
+/// A trait for floating point timestamps, such as those stored by Apple frameworks
+pub trait FloatTime: Into<f64> + Copy {
+    /// Convert fractional seconds since the Mac OS Absolute epoch (2001-01-01 00:00:00, `CFAbsoluteTime` and Core Data timestamps) into a time struct of choice
+    ///
+    /// Rounds to the nearest millisecond. Negative values are before 2001; anything before 1601 (or NaN) saturates to 1601, and anything
+    /// after 9999 (or infinity) to `Time::far_future`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, FloatTime};
+    /// assert_eq!(726158877.5f64.cf_absolute::<System>().strftime("%Y-%m-%d %H:%M:%S%.3f"), "2024-01-05 14:47:57.500");
+    /// ```
+    fn cf_absolute<T: Time>(self) -> T {
+        let secs = self.into();
+        if secs.is_nan() {
+            return T::from_epoch(0);
+        }
+        // the cast saturates, so infinities land on i64::MIN and i64::MAX before clamping
+        let ms = (secs * 1000.0).round() as i64;
+        let ms_1601 = ms.saturating_add((MAGIC_MAC_OS_CFA + OFFSET_1601 as i64) * 1000).clamp(0, FAR_FUTURE_UNIX_MS + OFFSET_1601 as i64 * 1000);
+        T::from_epoch(ms_1601 as u64)
+    }
+}
+
+/// implement the FloatTime trait for `f64`
+impl FloatTime for f64 {}
+
+/// implement the FloatTime trait for `f32`
+impl FloatTime for f32 {}
+
+/// implement the StrTime trait for `String` types
+impl StrTime for str {}
+
+/// implement the StrTime trait for `&str` types
+impl StrTime for String {}
+
+/// implement the IntTime trait for all integer types that implement conversion to u64
+impl<T: core::fmt::Display + Into<u64>> IntTime for T {}
+
+// the main suite uses serde_json, the network client and timezones throughout, so it runs with the default features - see
+// `feature_matrix` for the tests that run under every combination
+#[cfg(all(test, feature = "serde", feature = "ntp-client", feature = "timezones"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_system() {
+        let x = System::now();
+        println!("{}", x);
+        println!("{}", x.unix_ms());
+        println!("{}", x.strftime("%Y-%m-%d %H:%M:%S"));
+    }
+
+    #[test]
+    fn test_ntp() {
+        let x = Ntp::now();
+        println!("{:#?}", x);
+        println!("{}", x.unix_ms());
+        println!("{}", x);
+    }
+
+    #[test]
+    fn strptime() {
+        let x = System::strptime("2015-02-18 23:16:09.234", "%Y-%m-%d %H:%M:%S%.3f");
+        println!("2015 - {}", x);
+        let x = Ntp::strptime("2021-01-01 00:00:00 +0000", "%Y-%m-%d %H:%M:%S %z");
+        println!("2021 - {}", x);
+        assert_eq!(x.unix(), 1609459200);
+        println!("1950 - {}", Ntp::strptime("1950-01-01 00:00:00", "%Y-%m-%d %H:%M:%S"))
+    }
+
+    #[test]
+    fn str_time() {
+        let date2017 = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        println!("2017 - {}", date2017);
+        assert_eq!(date2017.unix(), 1483228800);
+    }
+
+    #[test]
+    fn time_diff() {
+        let x = "2015-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let y = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        println!("{} difference", y.diff(&x).ts_print());
+        println!("{} milliseconds difference", x.diff_ms(&y));
+        assert_eq!(x.diff(&y), 63158400u64);
+    }
+
+    #[test]
+    fn int_ntp_time() {
+        assert_eq!(1483228800u32.unix::<Ntp>().pretty(), "2017-01-01 00:00:00");
+        assert_eq!(
+            131277024000000000u64.windows_ns::<Ntp>().pretty(),
+            "2017-01-01 00:00:00"
+        );
+        assert_eq!(
+            3787310789u64.mac_os::<Ntp>().pretty(),
+            "2024-01-05 14:46:29"
+        );
+        assert_eq!(
+            726158877u32.mac_os_cfa::<Ntp>().pretty(),
+            "2024-01-05 14:47:57"
+        );
+        assert_eq!(0u32.sas_4gl::<Ntp>().pretty(), "1960-01-01 00:00:00");
+    }
+
+    #[test]
+    fn int_system_time() {
+        assert_eq!(1483228800u32.unix::<System>().pretty(), "2017-01-01 00:00:00");
+        assert_eq!(
+            131277024000000000u64.windows_ns::<System>().pretty(),
+            "2017-01-01 00:00:00"
+        );
+        assert_eq!(
+            3787310789u64.mac_os::<System>().pretty(),
+            "2024-01-05 14:46:29"
+        );
+        assert_eq!(
+            726158877u32.mac_os_cfa::<System>().pretty(),
+            "2024-01-05 14:47:57"
+        );
+        assert_eq!(0u32.sas_4gl::<System>().pretty(), "1960-01-01 00:00:00");
+    }
+
+    #[test]
+    fn windows_tests() {
+        let x = System::now();
+        println!("{} lots of 100ns since Windows epoch", x.windows_ns());
+    }
+
+    #[test]
+    fn mac_os() {
+        let x = System::now();
+        println!("{} seconds since Mac OS epoch", x.mac_os());
+        println!("{} seconds since Mac OS Absolute epoch", x.mac_os_cfa());
+    }
+
+    #[test]
+    fn sas_4gl() {
+        let x = System::now();
+        println!("{} seconds since SAS 4GL epoch", x.sas_4gl());
+    }
+
+    #[test]
+    fn rfc3339_iso8601() {
+        let x = System::now();
+        println!("{}", x.iso8601());
+        println!("{}", x.rfc3339());
+    }
+
+    #[test]
+    fn strptime_rfc_and_iso() {
+        let x = "2017-01-01T00:00:00.000".strp_iso8601::<System>();
+        let y = "2017-01-01T00:00:00.000Z".strp_rf3339::<System>();
+        assert_eq!(x.unix(), 1483228800);
+        assert_eq!(y.unix(), 1483228800);
+    }
+
+    #[test]
+    fn padded_fractions() {
+        for ms in [0u64, 5, 50, 500, 999] {
+            let x = System::from_epoch(1704465989000 + ms + OFFSET_1601 * 1000).change_tz("-03:30");
+            let rfc = x.rfc3339();
+            assert_eq!(rfc, format!("2024-01-05T14:46:29.{:03}Z", ms));
+            assert_eq!(rfc.strp_rf3339::<System>().unix_ms(), x.unix_ms(), "{rfc}");
+            assert_eq!(x.iso8601(), format!("2024-01-05 11:16:29.{:03}", ms));
+            assert_eq!(x.iso8601().replace(' ', "T").strp_iso8601::<System>().unix_ms() - x.utc_offset() as i64 * 1000, x.unix_ms());
+            assert_eq!(ExtendedTime::from(x.clone()).rfc3339(), rfc);
+
+            // every precision parses back, to the digits kept
+            for digits in [0, 1, 2, 3, 6, 9] {
+                let kept = 10u64.pow(3u32.saturating_sub(digits as u32));
+                let rfc = x.rfc3339_precision(digits);
+                assert_eq!(rfc.len(), "2024-01-05T14:46:29Z".len() + if digits == 0 { 0 } else { digits as usize + 1 }, "{rfc}");
+                assert_eq!(rfc.parse_time::<System>("%Y-%m-%dT%H:%M:%S%.fZ").unix_ms() as u64, (x.unix_ms() as u64) / kept * kept, "{rfc}");
+                assert_eq!(x.iso8601_precision(digits)[..19], x.iso8601()[..19]);
+            }
+        }
+        let x = System::from_epoch(1704465989005 + OFFSET_1601 * 1000);
+        assert_eq!(x.iso8601_precision(2), "2024-01-05 14:46:29.00");
+        assert_eq!(x.rfc3339_precision(200), "2024-01-05T14:46:29.005000000Z");
+        // before 1970, the fraction still counts up from the second
+        assert_eq!(ExtendedTime::from_ms_1601(OFFSET_1601 as i64 * 1000 - 1, 0).rfc3339(), "1969-12-31T23:59:59.999Z");
+        assert_eq!(ExtendedTime::from_ms_1601(-1, 0).iso8601(), "1600-12-31 23:59:59.999");
+    }
+
+    #[test]
+    fn fuzz_regressions() {
+        // change_tz sliced the offset without checking it
+        let x = 1704465989u32.unix::<System>().change_tz("+01:00");
+        for bad in ["", "+", "+1", "+01", "01:00", "+01:0", "+01:00:00", "+0é:00", "é", "+2a:00", "+01:60"] {
+            assert!(x.try_change_tz(bad).is_err(), "{bad:?}");
+            assert_eq!(x.change_tz(bad).utc_offset(), 3600, "{bad:?}");
+        }
+        assert_eq!(x.try_change_tz("-00:00").map(|y| y.tz_offset()), Ok("+00:00".to_string()));
+        // past MAX_UTC_OFFSET, an error - change_tz still clamps, as it always has
+        assert_eq!(x.try_change_tz("-21:11"), Err(ParseError::OutOfRange { field: "offset", value: -76260 }));
+        assert_eq!(x.change_tz("-21:11").utc_offset(), -MAX_UTC_OFFSET);
+        assert_eq!(x.change_tz("+18:00").unix(), x.unix());
+
+        // parse_etime overflowed multiplying out the days
+        assert!(matches!(parse_etime("05555555555555555455-4:4:5"), Err(ParseError::OutOfRange { field: "days", .. })));
+        assert_eq!(parse_etime("213503982334601-07:00:15").unwrap().as_secs(), u64::MAX);
+        assert!(matches!(parse_etime("213503982334601-07:00:16"), Err(ParseError::OutOfRange { field: "days", .. })));
+    }
+
+    #[test]
+    fn fallible_strptime() {
+        assert!("garbage".try_parse_time::<System>("%Y-%m-%d").is_err());
+        assert_eq!(
+            "garbage".try_parse_time::<Ntp>("%Y-%m-%d"),
+            Err(ParseTimeError::Mismatch {
+                input: "garbage".to_string(),
+                format: "%Y-%m-%d".to_string(),
+                reason: "input contains invalid characters".to_string(),
+            })
+        );
+        // a bad specifier is reported as such, even when the input fails to match before it
+        for format in ["%Y-%m-%d %Q", "%Y-%m-%d %"] {
+            assert_eq!(
+                System::try_strptime("nope", format),
+                Err(ParseTimeError::InvalidFormat { input: "nope".to_string(), format: format.to_string() })
+            );
+        }
+        assert!(matches!("2017-01-01 00:00".try_parse_time::<ExtendedTime>("%Y-%m-%d %H:%M:%S"), Err(ParseTimeError::Mismatch { .. })));
+        assert!("2017-02-30T00:00:00.000".try_strp_iso8601::<System>().is_err());
+        assert!("2017-01-01T00:00:00.000".try_strp_rf3339::<System>().is_err());
+
+        // successes are what the panicking parsers give, offset handling included
+        for (input, format) in [("2017-01-01 00:00:00", "%Y-%m-%d %H:%M:%S"), ("2017-01-01 00:00:00 +0530", "%Y-%m-%d %H:%M:%S %z")] {
+            let x = input.try_parse_time::<System>(format).unwrap();
+            let y = input.parse_time::<System>(format);
+            assert_eq!((x.raw(), x.utc_offset(), x.offset_source()), (y.raw(), y.utc_offset(), y.offset_source()));
+        }
+        assert_eq!("2017-01-01T00:00:00.000".try_strp_iso8601::<Ntp>().unwrap().unix(), 1483228800);
+        assert_eq!("2017-01-01T00:00:00.000Z".try_strp_rf3339::<ExtendedTime>().unwrap().unix(), 1483228800);
+        assert!(std::panic::catch_unwind(|| System::strptime("garbage", "%Y-%m-%d")).is_err());
+    }
+
+    #[test]
+    fn tz_tests() {
+        let x = Ntp::now();
+        // println!("{}", x.tz_offset());
+        // println!("{}", x);
+        println!("{:#?}", x);
+        println!("{}", x.change_tz("+01:00"));
+        println!("{}", x.change_tz("-01:00"));
+    }
+
+    #[test]
+    fn test_add_seconds() {
+        let x = System::now();
+        println!("{}", x.add_seconds(3600));
+    } 
+
+    #[test]
+    fn test_add_minshoursdaysweeks() {
+        let x = System::now();
+        println!("{}", x.add_minutes(60));
+        println!("{}", x.add_hours(24));
+        println!("{}", x.add_days(7));
+        println!("{}", x.add_weeks(52));
+    }
+
+    #[test]
+    fn long_ago_dates() {
+        let x = System::from_epoch(0);
+        println!("{}", x);
+        println!("{}", x.unix());
+        println!("{}", x.strftime("%Y-%m-%d %H:%M:%S"));
+    }
+
+    #[test]
+    fn test_past_future() {
+        let x = "2029-01-01T00:00:00.000".strp_iso8601::<System>();
+        let y = "2017-01-01T00:00:00.000Z".strp_rf3339::<System>();
+        println!("{} is in the {}", x, x.past_future(&y));
+        println!("{} is in the {}", y, y.past_future(&x));
+    }
+
+    #[test]
+    fn test_add_duration() {
+        let x = System::now();
+        
+        println!("{}", x.add_duration(std::time::Duration::from_secs(3600)));
+
+        println!("{}", x.add_duration(chrono::Duration::seconds(3600)));
+    }
+    #[test]
+    fn test_local() {
+        let x = System::now();
+        println!("{}", x.local());
+    }
+
+    #[test]
+    fn test_tz_enum() {
+        let x = System::now().change_tz("+08:00");
+        println!("{}", x.tz_enum().unwrap_or_default());
+        println!("{}", Tz::from_offset(3600).unwrap_or_default());
+        println!("{}", Tz::from_offset(0).unwrap_or_default()); // Some(UtcWet)
+        println!("{}", Tz::from_offset(3600).unwrap_or_default()); // Some(BstCet)
+        println!("{}", Tz::from_offset(7200).unwrap_or_default()); // Some(CestEet)
+        println!("{}", Tz::from_offset(123456).unwrap_or_default()); // None
+        println!("{}", Tz::Acst.offset_struct(System::now()));
+    }
+
+    #[test]
+    fn huge_number() {
+        // the last year chrono can hold - NaiveDate::MAX has been +262142-12-31 since chrono 0.4.35
+        let x = System::strptime("+262142-01-01 00:00:00", "%Y-%m-%d %H:%M:%S");
+        println!("{}", x);
+        assert_eq!(x.try_windows_ns(), Err(TimeError::Overflow));
+        assert_eq!(x.windows_ns(), i64::MAX);
+        assert_eq!(x.epoch(), x.raw() as i64);
+    }
+
+    #[test]
+    fn epoch_saturates() {
+        let x = System::from_epoch(u64::MAX);
+        assert_eq!(x.try_epoch(), Err(TimeError::Overflow));
+        assert_eq!(x.epoch(), i64::MAX);
+        assert_eq!(x.windows_ns(), i64::MAX);
+        assert_eq!(x.webkit(), i64::MAX);
+
+        // exact integer maths, no f64 rounding
+        let x = System::from_epoch(13_127_702_400_123);
+        assert_eq!(x.windows_ns(), 131_277_024_001_230_000);
+        assert_eq!(x.webkit(), 13_127_702_400_123_000);
+    }
+
+    /// an IntTime constructor for each time struct, its Time accessor, and the seconds from 1601 to its epoch
+    type SecondEpoch = (&'static str, fn(u64) -> System, fn(u64) -> Ntp, fn(&System) -> i64, i64);
+
+    const SECOND_EPOCHS: [SecondEpoch; 4] = [
+        ("unix", |v| v.unix(), |v| v.unix(), |t| t.unix(), OFFSET_1601 as i64),
+        ("mac_os", |v| v.mac_os(), |v| v.mac_os(), |t| t.mac_os(), OFFSET_1601 as i64 - MAGIC_MAC_OS),
+        ("mac_os_cfa", |v| v.mac_os_cfa(), |v| v.mac_os_cfa(), |t| t.mac_os_cfa(), OFFSET_1601 as i64 + MAGIC_MAC_OS_CFA),
+        ("sas_4gl", |v| v.sas_4gl(), |v| v.sas_4gl(), |t| t.sas_4gl(), OFFSET_1601 as i64 - MAGIC_SAS_4GL),
+    ];
+
+    /// plausible timestamps, anything whose milliseconds fit, and anything at all
+    fn any_timestamp() -> impl proptest::strategy::Strategy<Value = u64> {
+        proptest::prop_oneof![0u64..40_000_000_000, 0..=u64::MAX / 1000, proptest::prelude::any::<u64>()]
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn epoch_ints_round_trip(v in any_timestamp()) {
+            for (name, system, ntp, accessor, from_1601) in SECOND_EPOCHS {
+                let (x, n) = (system(v), ntp(v));
+                assert_eq!(x.raw(), n.raw(), "{}", name);
+                if v <= u64::MAX / 1000 - from_1601 as u64 {
+                    assert_eq!(accessor(&x), v as i64, "{} {}", name, v);
+                    assert_eq!(x.unix(), n.unix());
+                } else {
+                    assert_eq!(x.raw(), u64::MAX, "{} {} saturates", name, v);
+                }
+            }
+
+            let x = v.windows_ns::<System>();
+            assert_eq!(x.raw(), v.windows_ns::<Ntp>().raw());
+            assert_eq!(x.raw(), v / 10_000);
+            if v <= i64::MAX as u64 {
+                assert_eq!(x.windows_ns() as u64, v - v % 10_000);
+            } else {
+                assert_eq!(x.windows_ns(), i64::MAX);
+            }
+
+            let x = v.webkit::<Ntp>();
+            assert_eq!(x.raw(), v.webkit::<System>().raw());
+            if v <= i64::MAX as u64 {
+                assert_eq!(x.webkit() as u64, v - v % 1000);
+            } else {
+                assert_eq!(x.webkit(), i64::MAX);
+            }
+        }
+
+        #[test]
+        fn epoch_times_round_trip(raw in any_timestamp()) {
+            let (x, n) = (System::from_epoch(raw), Ntp::from_epoch(raw));
+            let whole_seconds = raw - raw % 1000;
+            for (name, system, ntp, accessor, from_1601) in SECOND_EPOCHS {
+                let secs = accessor(&x);
+                assert_eq!(secs, (raw / 1000) as i64 - from_1601, "{}", name);
+                if secs >= 0 {
+                    assert_eq!(system(secs as u64).raw(), whole_seconds, "{} {}", name, raw);
+                    assert_eq!(ntp(secs as u64).raw(), whole_seconds, "{} {}", name, raw);
+                }
+            }
+            assert_eq!(x.unix_ms(), (raw as i128 - OFFSET_1601 as i128 * 1000).min(i64::MAX as i128) as i64);
+            assert_eq!(x.unix_ms(), n.unix_ms());
+
+            if raw <= i64::MAX as u64 / 10_000 {
+                assert_eq!((x.windows_ns() as u64).windows_ns::<Ntp>().raw(), raw);
+            } else {
+                assert_eq!(n.windows_ns(), i64::MAX);
+            }
+            if raw <= i64::MAX as u64 / 1000 {
+                assert_eq!((n.webkit() as u64).webkit::<System>().raw(), raw);
+            } else {
+                assert_eq!(x.webkit(), i64::MAX);
+            }
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn windows_ns_round_trips_or_errors(raw in proptest::prelude::any::<u64>()) {
+            let x = System::from_epoch(raw);
+            match x.try_windows_ns() {
+                Ok(ns) => proptest::prop_assert_eq!((ns as u64).windows_ns::<System>().raw(), raw),
+                Err(_) => proptest::prop_assert_eq!(x.windows_ns(), i64::MAX),
+            }
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn compiled_format_matches_strftime(ms in -11644473600000i64..253402300799999i64, offset in -50400i32..50400) {
+            let x = System::from_epoch_offset((ms + OFFSET_1601 as i64 * 1000) as u64, offset);
+            for format in ["%Y-%m-%d %H:%M:%S", "%a %b %e %T %Y", "%Y-%m-%dT%H:%M:%S%.3f", "%j %U %W %G-W%V-%u", "%s %%"] {
+                let compiled = CompiledFormat::new(format).unwrap();
+                proptest::prop_assert_eq!(x.strftime_compiled(&compiled), x.strftime(format));
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_format_errors_and_bulk() {
+        assert_eq!(CompiledFormat::new("%Y-%m-%Q"), Err(FormatError::Invalid("%Y-%m-%Q".to_string())));
+        assert!(CompiledFormat::new("trailing %").is_err());
+        assert_eq!(CompiledFormat::new("%H:%M").unwrap().as_str(), "%H:%M");
+
+        let times: Vec<Ntp> = (0..3u32).map(|d| (1704412800 + d * 86400).unix::<Ntp>()).collect();
+        let fmt = CompiledFormat::new("%d/%m %a").unwrap();
+        assert_eq!(format_all(&times, &fmt), vec!["05/01 Fri", "06/01 Sat", "07/01 Sun"]);
+    }
+
+    #[test]
+    fn column_formatting() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let times = [
+            at("1601-01-01 00:00:00.000 +0000"),
+            at("1969-12-31 23:59:59.999 +0000"),
+            at("1900-02-28 12:00:00.001 -0330"),
+            at("1970-01-01 00:00:00.000 +0000"),
+            at("2024-01-05 15:46:29.120 +0100"),
+        ];
+        for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.3f%:z", "%s", "%a %e %b"] {
+            let fmt = CompiledFormat::new(format).unwrap();
+            let naive = times.iter().map(|t| t.strftime(format)).collect::<Vec<_>>().join(",");
+            let mut out = b"x,".to_vec();
+            format_column(&times, &fmt, &mut out, b',');
+            assert_eq!(out, format!("x,{}", naive).into_bytes());
+            let mut written = Vec::new();
+            write_column(&times, &fmt, &mut written, b',').unwrap();
+            assert_eq!(written, naive.as_bytes());
+        }
+        let fmt = CompiledFormat::new("%Y-%m-%dT%H:%M:%S%.3f%:z").unwrap();
+        let mut out = Vec::new();
+        format_column(&times[1..3], &fmt, &mut out, b'\t');
+        assert_eq!(out, b"1969-12-31T23:59:59.999+00:00\t1900-02-28T12:00:00.001-03:30");
+
+        // more than one chunk
+        let many: Vec<Ntp> = (0..20_000u32).map(|i| (i * 3_601).unix::<Ntp>()).collect();
+        let mut out = Vec::new();
+        format_column(&many, &fmt, &mut out, b'\n');
+        let mut written = Vec::new();
+        write_column(&many, &fmt, &mut written, b'\n').unwrap();
+        assert_eq!(out, written);
+        assert_eq!(out.split(|&b| b == b'\n').count(), many.len());
+
+        let mut out = Vec::new();
+        format_column::<System>(&[], &fmt, &mut out, b',');
+        assert!(out.is_empty());
+        write_column::<System, _>(&[], &fmt, &mut out, b',').unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn truncate_units() {
+        let x = "2024-02-29 23:59:59.999".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+        assert_eq!(x.truncate_to(TruncateUnit::Second).raw() % 1000, 0);
+        for (unit, expected) in [
+            (TruncateUnit::Minute, "2024-02-29 23:59:00"),
+            (TruncateUnit::Day, "2024-02-29 00:00:00"),
+            (TruncateUnit::Week, "2024-02-26 00:00:00"),
+            (TruncateUnit::Month, "2024-02-01 00:00:00"),
+            (TruncateUnit::Year, "2024-01-01 00:00:00"),
+        ] {
+            assert_eq!(x.truncate_to(unit).wall_clock().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn privacy_determinism() {
+        let x = 1704465989u32.unix::<System>();
+        let max = core::time::Duration::from_secs(600);
+        for id in ["alice", "bob", "carol"] {
+            let a = privacy::jitter(&x, max, id, 7);
+            assert_eq!(a, privacy::jitter(&x, max, id, 7));
+            assert!((a.unix_ms() - x.unix_ms()).abs() <= 600_000);
+        }
+        // different seeds move the same id differently
+        assert_ne!(privacy::jitter(&x, max, "alice", 7), privacy::jitter(&x, max, "alice", 8));
+        // the jitter is pinned, so a change of hash (or of Rust release) that would move everyone's times fails here
+        assert_eq!(["alice", "bob", "carol"].map(|id| privacy::jitter(&x, max, id, 7).unix_ms() - x.unix_ms()), [-303126, 363370, 361280]);
+        // integer ids and length-prefixed ids hash as little-endian, with lengths as 64-bit, whatever the target
+        assert_eq!(privacy::jitter(&x, max, &42u32, 7).unix_ms() - x.unix_ms(), -312383);
+        assert_eq!(privacy::jitter(&x, max, "user-42", 7).unix_ms() - x.unix_ms(), 524081);
+        assert_eq!(privacy::jitter(&x, max, &[1u16, 2][..], 7).unix_ms() - x.unix_ms(), -541552);
+        // and depends only on the id and seed, not on the time
+        assert_eq!(privacy::jitter(&x, max, "alice", 7).unix_ms() - x.unix_ms(), privacy::jitter(&x.add_days(3), max, "alice", 7).unix_ms() - x.add_days(3).unix_ms());
+
+        let original: Vec<System> = [5u32, 1, 3, 3, 9].iter().map(|s| (1704465989 + s).unix::<System>()).collect();
+        let mut shifted = original.clone();
+        privacy::shift_dataset(&mut shifted, chrono::Duration::milliseconds(-987_654_321));
+        for i in 0..original.len() {
+            for j in 0..original.len() {
+                assert_eq!(original[i].cmp(&original[j]), shifted[i].cmp(&shifted[j]));
+            }
+        }
+        privacy::unshift_dataset(&mut shifted, chrono::Duration::milliseconds(-987_654_321));
+        assert_eq!(shifted, original);
+
+        // a shift that runs into 1601 is clamped there, and can't be undone
+        let mut early = vec![System::from_epoch(1000), System::from_epoch(5000)];
+        privacy::shift_dataset(&mut early, chrono::Duration::seconds(-2));
+        assert_eq!(early.iter().map(|t| t.raw()).collect::<Vec<_>>(), [0, 3000]);
+        privacy::unshift_dataset(&mut early, chrono::Duration::seconds(-2));
+        assert_eq!(early.iter().map(|t| t.raw()).collect::<Vec<_>>(), [2000, 5000]);
+    }
+
+    #[test]
+    fn cf_absolute_fractions() {
+        let x = 726158877.5f64.cf_absolute::<System>();
+        assert_eq!(x.strftime("%Y-%m-%d %H:%M:%S%.3f"), "2024-01-05 14:47:57.500");
+        assert_eq!(x.cf_absolute(), 726158877.5);
+        assert_eq!(x.mac_os_cfa(), 726158877);
+
+        let y = (-31622399.75f64).cf_absolute::<Ntp>();
+        assert_eq!(y.strftime("%Y-%m-%d %H:%M:%S%.3f"), "2000-01-01 00:00:00.250");
+        assert_eq!(y.cf_absolute(), -31622399.75);
+
+        assert_eq!(0.0004f64.cf_absolute::<System>().cf_absolute(), 0.0);
+        assert_eq!(0.0006f32.cf_absolute::<System>().cf_absolute(), 0.001);
+        assert_eq!((-1e20f64).cf_absolute::<System>().raw(), 0);
+        assert_eq!(f64::NAN.cf_absolute::<System>().raw(), 0);
+        assert_eq!(f64::NEG_INFINITY.cf_absolute::<System>().raw(), 0);
+        assert_eq!(f64::INFINITY.cf_absolute::<System>(), System::far_future());
+        assert_eq!(1e300f64.cf_absolute::<System>(), System::far_future());
+        assert_eq!(f32::INFINITY.cf_absolute::<Ntp>().pretty(), "9999-12-31 23:59:59");
+    }
+
+    #[test]
+    fn build_time_helpers() {
+        const BUILT: System = build_time!();
+        assert!(BUILT.unix() > 1704465989);
+        assert!(BUILT.unix() <= System::now().unix());
+
+        assert_eq!(buildtime::parse_unix_const(Some("1704465989"), 7), 1704465989);
+        assert_eq!(buildtime::parse_unix_const(Some("-86400"), 7), -86400);
+        for bad in [Some(""), Some("-"), Some("12a"), Some("99999999999999999999"), None] {
+            assert_eq!(buildtime::parse_unix_const(bad, 7), 7);
+        }
+
+        // the value is passed in rather than set in the environment, which other tests running in parallel share
+        assert_eq!(buildtime::parse_source_date_epoch(Some("1704465989")).unwrap().pretty(), "2024-01-05 14:46:29");
+        assert_eq!(buildtime::parse_source_date_epoch(Some(" 1704465989\n")).unwrap().unix(), 1704465989);
+        assert_eq!(buildtime::parse_source_date_epoch(Some("yesterday")), None);
+        assert_eq!(buildtime::parse_source_date_epoch(None), None);
+    }
+
+    #[test]
+    fn span_set_semantics() {
+        let t = |s: u32| (1704067200 + s).unix::<System>();
+        let mut set = SpanSet::new();
+        set.insert(TimeSpan::new(t(0), t(100)), "outer");
+        set.insert(TimeSpan::new(t(20), t(30)), "nested");
+        set.insert(TimeSpan::new(t(90), t(150)), "overlapping");
+        set.insert(TimeSpan::new(t(150), t(160)), "touching");
+        set.insert(TimeSpan::new(t(500), t(600)), "disjoint");
+        set.insert(TimeSpan::new(t(550), t(550)), "empty");
+
+        assert_eq!(set.query_point(&t(25)), vec![&"outer", &"nested"]);
+        assert_eq!(set.query_point(&t(30)), vec![&"outer"]);
+        assert_eq!(set.query_point(&t(95)), vec![&"outer", &"overlapping"]);
+        assert_eq!(set.query_point(&t(150)), vec![&"touching"]);
+        assert_eq!(set.query_point(&t(550)), vec![&"disjoint"]);
+        assert!(set.query_point(&t(300)).is_empty());
+
+        assert_eq!(set.query_overlapping(&TimeSpan::new(t(100), t(150))), vec![&"overlapping"]);
+        assert_eq!(set.query_overlapping(&TimeSpan::new(t(160), t(500))), Vec::<&&str>::new());
+        assert_eq!(set.query_overlapping(&TimeSpan::new(t(25), t(25))), Vec::<&&str>::new());
+        assert_eq!(set.query_overlapping(&TimeSpan::new(t(29), t(91))), vec![&"outer", &"nested", &"overlapping"]);
+
+        // 0..160 and 500..600
+        assert_eq!(set.total_covered_duration(), core::time::Duration::from_secs(260));
+
+        let mut same = SpanSet::new();
+        same.insert(TimeSpan::new(t(10), t(20)), 1);
+        same.insert(TimeSpan::new(t(0), t(10)), 1);
+        same.insert(TimeSpan::new(t(5), t(8)), 2);
+        same.insert(TimeSpan::new(t(21), t(30)), 1);
+        same.merge_adjacent();
+        let merged: Vec<(i64, i64, i32)> = same.iter().map(|(s, v)| (s.start.unix() - 1704067200, s.end.unix() - 1704067200, *v)).collect();
+        assert_eq!(merged, vec![(0, 20, 1), (5, 8, 2), (21, 30, 1)]);
+        assert_eq!(same.query_point(&t(15)), vec![&1]);
+    }
+
+    #[test]
+    fn span_set_matches_brute_force() {
+        // a small LCG keeps this deterministic without pulling in a rng
+        let mut seed = 0x2545F4914F6CDD1Du64;
+        let mut next = |n: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % n
+        };
+        let t = |ms: u64| System::from_epoch(13348935989123 + ms);
+
+        let mut set = SpanSet::new();
+        let mut spans = Vec::new();
+        for i in 0..10_000 {
+            let start = next(10_000_000);
+            let span = TimeSpan::new(t(start), t(start + next(50_000)));
+            spans.push((span.clone(), i));
+            set.insert(span, i);
+        }
+        let sorted = |mut v: Vec<i32>| {
+            v.sort();
+            v
+        };
+        for _ in 0..500 {
+            let p = t(next(10_100_000));
+            let expected: Vec<i32> = spans.iter().filter(|(s, _)| s.contains(&p)).map(|(_, i)| *i).collect();
+            assert_eq!(sorted(set.query_point(&p).into_iter().copied().collect()), sorted(expected));
+
+            let start = next(10_100_000);
+            let q = TimeSpan::new(t(start), t(start + next(20_000)));
+            let expected: Vec<i32> = spans
+                .iter()
+                .filter(|(s, _)| s.start.raw() < q.end.raw() && q.start.raw() < s.end.raw())
+                .map(|(_, i)| *i)
+                .collect();
+            assert_eq!(sorted(set.query_overlapping(&q).into_iter().copied().collect()), sorted(expected));
+        }
+    }
+
+    #[test]
+    fn extended_time_history() {
+        let landfall = ExtendedTime::strptime("1492-10-12 00:00:00", "%Y-%m-%d %H:%M:%S");
+        assert_eq!(landfall.unix(), -15059606400);
+        assert_eq!(landfall.pretty(), "1492-10-12 00:00:00");
+        assert_eq!(landfall.pretty_era(), "1492-10-12 00:00:00 AD");
+        assert!(landfall.ms_1601() < 0);
+        assert_eq!(ExtendedTime::parse_era("1492-10-12 AD").unwrap().ms_1601(), landfall.ms_1601());
+        let json = serde_json::to_string(&landfall).unwrap();
+        // the offset source isn't serialized, and isn't compared or hashed either
+        assert_eq!(landfall.offset_source(), OffsetSource::AssumedUtc);
+        let back = serde_json::from_str::<ExtendedTime>(&json).unwrap();
+        assert_eq!(back, landfall);
+        assert_eq!(back.cmp(&landfall), core::cmp::Ordering::Equal);
+        assert!(std::collections::HashSet::from([back]).contains(&landfall));
+        assert_ne!(landfall, landfall.change_tz_seconds(3600));
+
+        // out of chrono's range clamps to its ends rather than overflowing
+        let earliest = ExtendedTime::from_ms_1601(i64::MIN, 0);
+        assert_eq!(earliest.unix_ms(), chrono::DateTime::<chrono::Utc>::MIN_UTC.timestamp_millis());
+        assert!(earliest.pretty_era().ends_with(" BC"));
+        assert_eq!(ExtendedTime::from_ms_1601(i64::MAX, 0).unix_ms(), chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp_millis());
+        assert_eq!(earliest.add_seconds(i64::MIN), earliest);
+        let unclamped = serde_json::from_str::<ExtendedTime>(&format!(r#"{{"ms":{},"utc_offset":0}}"#, i64::MIN)).unwrap();
+        assert_eq!(unclamped.unix_ms(), i64::MIN);
+
+        assert_eq!(System::try_from(landfall.clone()), Err(TimeError::Overflow));
+        assert!(Ntp::try_from(landfall.clone()).is_err());
+        let modern = 1704465989u32.unix::<System>();
+        assert_eq!(System::try_from(ExtendedTime::from(modern.clone())).unwrap(), modern);
+
+        assert_eq!(landfall.add_days(1).pretty(), "1492-10-13 00:00:00");
+        assert_eq!(landfall.diff(&modern), 1704465989u64 + 15059606400);
+        assert_eq!(landfall.past_future(&modern), RelativeTime::Past);
+        assert!(landfall < ExtendedTime::from(modern));
+
+        let ides = ExtendedTime::parse_era("0044-03-15 12:30:00 BCE").unwrap();
+        assert_eq!(ides.strftime("%Y-%m-%d %H:%M"), "-0043-03-15 12:30");
+        assert_eq!(ides, ExtendedTime::parse_era("-0043-03-15T12:30").unwrap());
+        assert_eq!(ides.pretty_era(), "0044-03-15 12:30:00 BC");
+        assert_eq!(ides.at_midnight().pretty_era(), "0044-03-15 00:00:00 BC");
+
+        assert!(matches!(ExtendedTime::parse_era("-0043-03-15 BC"), Err(ParseError::Invalid { .. })));
+        assert!(ExtendedTime::parse_era("0000-01-01 BC").is_err());
+        assert!(ExtendedTime::parse_era("1492-02-30").is_err());
+        assert_eq!(ExtendedTime::parse_era(" "), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn sleep_deadlines() {
+        let past = System::now().add_seconds(-10);
+        assert_eq!(sleep::duration_until(&past), None);
+        let started = std::time::Instant::now();
+        sleep::sleep_until(&past);
+        assert!(started.elapsed() < core::time::Duration::from_millis(50));
+
+        let soon = System::from_epoch(System::now().raw() + 150);
+        let remaining = sleep::duration_until(&soon).unwrap();
+        assert!(remaining <= core::time::Duration::from_millis(150));
+        sleep::sleep_until(&soon);
+        assert_eq!(sleep::duration_until(&soon), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_instant_conversion() {
+        let epsilon = core::time::Duration::from_millis(20);
+        let expected = tokio::time::Instant::now() + core::time::Duration::from_millis(500);
+        let actual = sleep::to_tokio_instant(&System::from_epoch(System::now().raw() + 500));
+        let gap = if actual > expected { actual - expected } else { expected - actual };
+        assert!(gap < epsilon, "{:?}", gap);
+
+        let past = sleep::to_tokio_instant(&System::now().add_seconds(-1));
+        assert!(past <= tokio::time::Instant::now());
+    }
+
+    #[test]
+    fn change_tz_round_trips() {
+        let offsets = [
+            -43200, -36000, -34200, -16200, -12600, -3600, 0, 3600, 12600, 16200, 19800, 20700, 31500, 34200, 45900, 46800, 50400,
+        ];
+        for x in [System::from_epoch(13348939589123), System::from_epoch(13363473600000)] {
+            for a in offsets {
+                let in_a = x.change_tz_seconds(a);
+                assert_eq!(in_a.unix_ms(), x.unix_ms());
+                assert_eq!(in_a.tz_offset().replace(':', ""), in_a.strftime("%z"));
+                assert_eq!(in_a.wall_clock(), x.to_utc().wall_clock() + chrono::Duration::seconds(a as i64));
+                for b in offsets {
+                    let there_and_back = in_a.change_tz_seconds(b).change_tz_seconds(a);
+                    assert_eq!(there_and_back, in_a);
+                    assert_eq!(there_and_back.pretty(), in_a.pretty());
+                    assert_eq!(in_a.change_tz_seconds(b).to_utc(), x.to_utc());
+                }
+            }
+        }
+
+        let x = "2024-01-05 14:46:29 +0000".parse_time::<Ntp>("%Y-%m-%d %H:%M:%S %z");
+        let chained = x.change_tz("+13:00").change_tz("-11:00");
+        assert_eq!(chained.pretty(), "2024-01-05 03:46:29");
+        assert_eq!(chained.strftime("%Y-%m-%d %H:%M:%S %:z"), "2024-01-05 03:46:29 -11:00");
+        assert_eq!(chained.local(), x.local());
+        assert_eq!(chained.local().local(), x.local());
+        assert_eq!(chained.change_tz("+13:00").strftime("%d %H:%M %z"), "06 03:46 +1300");
+
+        let ist = "2024-01-05 20:16:29 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(ist.pretty(), "2024-01-05 20:16:29");
+        assert_eq!(ist.tz_offset(), "+05:30");
+        assert_eq!(ist.tz_enum(), Some(Tz::Ist));
+        assert_eq!(ist.to_utc().pretty(), "2024-01-05 14:46:29");
+        assert_eq!(ist.rfc3339(), "2024-01-05T14:46:29.000Z");
+        assert_eq!(Tz::Pst.offset_struct(ist.clone()).tz_enum(), Some(Tz::Pst));
+    }
+
+    #[test]
+    fn identify_epochs() {
+        let ranked = |value: i64| -> Vec<(epoch::Epoch, epoch::Unit, String)> {
+            epoch::identify_timestamp(value)
+                .into_iter()
+                .filter(|g| g.score == 1.0)
+                .map(|g| (g.epoch, g.unit, g.time.strftime("%Y")))
+                .collect()
+        };
+        use epoch::{Epoch::*, Unit::*};
+
+        assert_eq!(ranked(1700000000), vec![(Unix, Seconds, "2023".to_string()), (Sas4gl, Seconds, "2013".to_string())]);
+        assert_eq!(ranked(1700000000123), vec![(Unix, Milliseconds, "2023".to_string())]);
+        assert_eq!(ranked(13300000000000000), vec![(WebKit, Microseconds, "2022".to_string())]);
+        assert_eq!(ranked(133000000000000000), vec![(Windows, Ticks100ns, "2022".to_string())]);
+        // small values are ambiguous - equal scores fall back to how common the epoch is
+        assert_eq!(ranked(726158877), vec![(Unix, Seconds, "1993".to_string()), (MacOsCfa, Seconds, "2024".to_string())]);
+
+        let all = epoch::identify_timestamp(3787310789);
+        let position = |e, u| all.iter().position(|g| g.epoch == e && g.unit == u).unwrap();
+        assert_eq!(all[position(MacOs, Seconds)].time.strftime("%Y"), "2024");
+        assert_eq!(all[position(Unix, Seconds)].time.strftime("%Y"), "2090");
+        assert!(position(MacOs, Seconds) < position(Unix, Seconds));
+        assert!(all.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let wide = epoch::identify_timestamp_within(3787310789, 1900, 2100);
+        assert_eq!((wide[0].epoch, wide[0].unit), (Unix, Seconds));
+
+        // only nanoseconds reach back no further than 1601
+        let min = epoch::identify_timestamp(i64::MIN);
+        assert_eq!(min.len(), 1);
+        assert_eq!((min[0].epoch, min[0].unit, min[0].time.strftime("%Y")), (Unix, Nanoseconds, "1677".to_string()));
+        let table = epoch::identify_and_pretty(1700000000);
+        assert!(table.lines().nth(1).unwrap().starts_with("1.00   unix       s      2023-11-14 22:13:20"));
+    }
+
+    #[test]
+    fn ntp_packet_short_buffers() {
+        for len in [0, 1, 20, 47] {
+            assert!(matches!(NtpPacket::from_bytes(&vec![0x24; len]), Err(NtpError::ShortPacket(n)) if n == len));
+        }
+        let mut oversized = vec![0u8; 68];
+        oversized[0] = 0x24;
+        assert!(NtpPacket::from_bytes(&oversized).is_ok());
+    }
+
+    /// a one-shot localhost NTP "server" that answers the first request with `reply`, optionally preceded by a spoofed reply from another socket
+    fn ntp_responder(reply: Vec<u8>, spoof: Option<Vec<u8>>) -> u16 {
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 48];
+            let (_, client) = server.recv_from(&mut buf).unwrap();
+            if let Some(spoof) = spoof {
+                std::net::UdpSocket::bind("127.0.0.1:0").unwrap().send_to(&spoof, client).unwrap();
+            }
+            server.send_to(&reply, client).unwrap();
+        });
+        port
+    }
+
+    #[test]
+    fn ntp_localhost_replies() {
+        let config = |port| NtpConfig::new("127.0.0.1").port(port).timeout(core::time::Duration::from_secs(2));
+
+        let port = ntp_responder(vec![0x24; 20], None);
+        assert!(matches!(Ntp::with_config(&config(port)), Err(NtpError::ShortPacket(20))));
+
+        let mut good = NtpPacket::client_request();
+        good.mode = 4;
+        good.transmit = NtpTimestamp::from_unix_ms(1704465989123);
+        let mut forged = good;
+        forged.transmit = NtpTimestamp::from_unix_ms(0);
+        let port = ntp_responder(good.to_bytes().unwrap().to_vec(), Some(forged.to_bytes().unwrap().to_vec()));
+        let ntp = Ntp::with_config(&config(port)).unwrap();
+        assert_eq!(ntp.unix_ms(), 1704465989123);
+        assert_eq!(ntp.last_packet(), Some(&good));
+        assert!(format!("{:?}", ntp).contains(r#"server: "127.0.0.1", valid: true,"#));
+
+        // a socket that never answers
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let started = std::time::Instant::now();
+        let result = Ntp::with_config(&config(silent.local_addr().unwrap().port()).timeout(core::time::Duration::from_millis(200)));
+        assert!(matches!(result, Err(NtpError::Timeout)));
+        assert!(started.elapsed() < core::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn ntp_rate_guard() {
+        let secs = core::time::Duration::from_secs;
+        let t = 1704465989u32.unix::<System>();
+        let mut guard = RateGuard::default();
+        assert_eq!(guard.min_interval(), DEFAULT_MIN_QUERY_INTERVAL);
+        assert!(guard.check("pool.ntp.org:123", &t).is_ok());
+        let retry = |result: Result<(), NtpError>| match result {
+            Err(NtpError::RateLimited { retry_after }) => retry_after,
+            other => panic!("expected RateLimited, got {:?}", other),
+        };
+        assert_eq!(retry(guard.check("pool.ntp.org:123", &t)), secs(4));
+        assert_eq!(retry(guard.check("pool.ntp.org:123", &t.add_seconds(3))), secs(1));
+        // a refused query doesn't push the next one back
+        assert!(guard.check("pool.ntp.org:123", &t.add_seconds(4)).is_ok());
+        assert!(guard.check("pool.ntp.org:1123", &t.add_seconds(4)).is_ok());
+        assert!(guard.check("time.google.com:123", &t.add_seconds(4)).is_ok());
+        // a clock stepped back doesn't lock the server out
+        assert!(guard.check("pool.ntp.org:123", &t.add_seconds(-60)).is_ok());
+
+        guard.set_min_interval(secs(60));
+        assert_eq!(retry(guard.check("time.google.com:123", &t.add_seconds(10))), secs(54));
+        assert!(guard.check("time.google.com:123", &t.add_seconds(64)).is_ok());
+
+        // only DENY and RSTR refuse a server, and for good
+        assert!(!guard.record_kiss_of_death("time.google.com:123", "RATE"));
+        assert!(guard.record_kiss_of_death("pool.ntp.org:123", "DENY"));
+        assert!(guard.record_kiss_of_death("pool.ntp.org:1123", "RSTR"));
+        for later in [1, 3600, 86400 * 365] {
+            assert!(matches!(
+                guard.check("pool.ntp.org:123", &t.add_seconds(later)),
+                Err(NtpError::Denied { server, code }) if server == "pool.ntp.org:123" && code == "DENY"
+            ));
+        }
+        assert!(guard.is_denied("pool.ntp.org:1123"));
+        assert!(!guard.is_denied("time.google.com:123"));
+        assert!(guard.check("time.google.com:123", &t.add_seconds(200)).is_ok());
+    }
+
+    #[test]
+    fn ntp_kiss_of_death_persists() {
+        let config = |port| NtpConfig::new("127.0.0.1").port(port).timeout(core::time::Duration::from_secs(2));
+        let mut kod = NtpPacket::client_request();
+        kod.mode = 4;
+        kod.ref_id = u32::from_be_bytes(*b"DENY");
+        let port = ntp_responder(kod.to_bytes().unwrap().to_vec(), None);
+        let denied = |result: Result<Ntp, NtpError>| matches!(result, Err(NtpError::Denied { code, .. }) if code == "DENY");
+        assert!(denied(Ntp::with_config(&config(port))));
+        // refused without asking again - the one-shot responder has gone, so this would otherwise time out
+        assert!(denied(Ntp::with_config(&config(port))));
+    }
+
+    #[test]
+    fn ntp_server_selection() {
+        let addrs: Vec<std::net::SocketAddr> = (1..=4).map(|i| format!("192.0.2.{}:123", i).parse().unwrap()).collect();
+        assert_eq!(ServerSelection::First.select(&addrs), Some(addrs[0]));
+        assert_eq!(ServerSelection::Random.select(&[]), None);
+
+        // round robin visits every address once per cycle, wherever the shared position starts
+        let cycle: Vec<_> = (0..4).map(|_| ServerSelection::RoundRobin.select(&addrs).unwrap()).collect();
+        let start = addrs.iter().position(|a| *a == cycle[0]).unwrap();
+        for (i, addr) in cycle.iter().enumerate() {
+            assert_eq!(*addr, addrs[(start + i) % 4]);
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..400 {
+            seen.insert(ServerSelection::Random.select(&addrs).unwrap());
+        }
+        assert_eq!(seen.len(), 4);
+    }
+
+    #[test]
+    fn ntp_srv_records() {
+        struct MockResolver;
+        impl Resolver for MockResolver {
+            fn lookup_host(&self, host: &str, port: u16) -> std::io::Result<Vec<std::net::SocketAddr>> {
+                match host {
+                    "ntp1.example.com" => Ok(vec![std::net::SocketAddr::new([192, 0, 2, 1].into(), port)]),
+                    "example.com" => Ok(vec![std::net::SocketAddr::new([192, 0, 2, 99].into(), port)]),
+                    _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no such host")),
+                }
+            }
+
+            fn lookup_srv(&self, name: &str) -> std::io::Result<Vec<SrvRecord>> {
+                assert_eq!(name, "_ntp._udp.example.com");
+                ["20 0 1123 ntp1.example.com.", "10 0 123 dead.example.com."]
+                    .iter()
+                    .map(|r| SrvRecord::parse(r).map_err(std::io::Error::other))
+                    .collect()
+            }
+        }
+
+        assert!(matches!(SrvRecord::parse("10 5 123"), Err(ParseError::Invalid { position: 8, .. })));
+        assert!(matches!(SrvRecord::parse("10 five 123 x."), Err(ParseError::Invalid { position: 3, .. })));
+        assert!(SrvRecord::parse("10 5 123 a. b.").is_err());
+
+        // the priority 10 target does not resolve, so the priority 20 one is used, on its own port
+        let config = NtpConfig::new("example.com");
+        assert_eq!(resolve_server(&config, &MockResolver).unwrap(), vec!["192.0.2.1:1123".parse().unwrap()]);
+        assert_eq!(resolve_server(&config.clone().srv(false), &MockResolver).unwrap(), vec!["192.0.2.99:123".parse().unwrap()]);
+        assert!(resolve_server(&NtpConfig::new("nowhere.invalid").srv(false), &MockResolver).is_err());
+
+        // weighted ordering within a priority, lowest priority first
+        let records: Vec<SrvRecord> = ["5 0 1 c", "1 1 1 a", "1 3 1 b"].iter().map(|r| SrvRecord::parse(r).unwrap()).collect();
+        let targets = |pick: u64| ntp_server::order_srv(&records, || pick).into_iter().map(|r| r.target).collect::<Vec<_>>();
+        assert_eq!(targets(0), ["a", "b", "c"]);
+        assert_eq!(targets(1), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn now_functions() {
+        let tolerance = 2000;
+        assert!((now_ms() - System::now().unix_ms()).abs() < tolerance);
+        assert!((now_us() / 1000 - System::now().unix_ms()).abs() < tolerance);
+        assert!((now_windows_ns() - System::now().windows_ns()).abs() < tolerance * 10_000);
+        let rfc = now_rfc3339();
+        assert!(rfc.ends_with('Z'));
+        assert!(System::strptime(&rfc[..19], "%Y-%m-%dT%H:%M:%S").diff(&System::now()) < 2);
+
+        for tz in [Tz::UtcWet, Tz::Ist, Tz::Nst, Tz::Acst] {
+            let local = now_in(tz);
+            assert_eq!(local.utc_offset(), tz.offset());
+            assert!(local.diff_ms(&System::now()) < tolerance as u64);
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn mock_clock() {
+        let frozen = "2024-01-05 14:46:29.123 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        {
+            let _guard = mock::set_mock_time(&frozen);
+            assert!(mock::is_mocked());
+            assert_eq!(System::now(), frozen);
+            assert_eq!(Ntp::now().unix_ms(), frozen.unix_ms());
+            assert_eq!(now(), 1704446189);
+            assert_eq!(now_ms(), 1704446189123);
+            assert_eq!(now_us(), 1704446189123000);
+            assert_eq!(now_windows_ns(), frozen.windows_ns());
+            assert_eq!(now_rfc3339(), "2024-01-05T09:16:29.123Z");
+            assert_eq!(now_in(Tz::UtcWet).pretty(), "2024-01-05 09:16:29");
+
+            // nested guards restore the outer mock
+            {
+                let _inner = mock::set_mock_time(&frozen.add_seconds(60));
+                assert_eq!(now(), 1704446249);
+            }
+            mock::advance_mock(1000);
+            assert_eq!(now_ms(), 1704446190123);
+        }
+        assert!(!mock::is_mocked());
+        assert!(now() > 1704446189);
+    }
+
+    #[test]
+    fn month_grids() {
+        let feb = month_grid::<System>(2024, 2, Weekday::Mon);
+        assert_eq!(feb.len(), 5);
+        assert_eq!(feb[0].iter().filter(|d| d.is_none()).count(), 3);
+        assert_eq!(feb[4][3].as_ref().unwrap().pretty(), "2024-02-29 00:00:00");
+        assert!(feb[4][4..].iter().all(|d| d.is_none()));
+        assert_eq!(feb.iter().flatten().flatten().count(), 29);
+
+        let filled = month_grid_with::<System>(2024, 2, Weekday::Mon, true);
+        assert_eq!(filled[0][0].as_ref().unwrap().pretty(), "2024-01-29 00:00:00");
+        assert_eq!(filled[4][6].as_ref().unwrap().pretty(), "2024-03-03 00:00:00");
+
+        // September 2024 starts on a Sunday
+        let sept = month_grid::<System>(2024, 9, Weekday::Sun);
+        assert_eq!(sept.len(), 5);
+        assert_eq!(sept[0][0].as_ref().unwrap().pretty(), "2024-09-01 00:00:00");
+        assert_eq!(sept[4][1].as_ref().unwrap().pretty(), "2024-09-30 00:00:00");
+        assert_eq!(month_grid::<System>(2024, 9, Weekday::Mon).len(), 6);
+
+        assert!(month_grid::<System>(2024, 13, Weekday::Mon).is_empty());
+        assert_eq!(format_month(2024, 0, MonthFormat::default()), "");
+
+        assert_eq!(
+            format_month(2024, 2, MonthFormat::default()),
+            "   February 2024\n\
+             Mo Tu We Th Fr Sa Su\n\
+             \x20         1  2  3  4\n\
+             \x205  6  7  8  9 10 11\n\
+             12 13 14 15 16 17 18\n\
+             19 20 21 22 23 24 25\n\
+             26 27 28 29\n"
+        );
+        let highlighted = format_month(2024, 9, MonthFormat::default().week_start(Weekday::Sun).highlight(1));
+        assert!(highlighted.starts_with("   September 2024\nSu Mo Tu We Th Fr Sa\n\x1b[7m 1\x1b[0m  2"));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn operations_stay_canonical(
+            raw in 0u64..300_000_000_000_000,
+            offset in proptest::prelude::any::<i32>(),
+            ops in proptest::collection::vec((0u8..6, proptest::prelude::any::<i32>()), 0..12),
+        ) {
+            let mut x = System::from_epoch_offset(raw, offset);
+            let mut n = Ntp::from_epoch_offset(raw, offset);
+            proptest::prop_assert!(x.is_canonical() && n.is_canonical());
+            for (op, arg) in ops {
+                let arg = arg as i64;
+                (x, n) = match op {
+                    0 => (x.add_seconds(arg), n.add_seconds(arg)),
+                    1 => (x.change_tz_seconds(arg as i32), n.change_tz_seconds(arg as i32)),
+                    2 => (x.add_days(arg % 100_000), n.add_days(arg % 100_000)),
+                    3 => (x.truncate_to(TruncateUnit::Hour), n.truncate_to(TruncateUnit::Month)),
+                    4 => (x.to_utc(), n.local()),
+                    _ => (System::from_wall_clock(x.wall_clock(), arg as i32), Ntp::from_wall_clock(n.wall_clock(), arg as i32)),
+                };
+                proptest::prop_assert!(x.is_canonical() && n.is_canonical());
+                proptest::prop_assert_eq!(x.canonicalize(), x.clone());
+                proptest::prop_assert_eq!(n.canonicalize().canonicalize(), n.canonicalize());
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_edge_cases() {
+        let x = System::from_epoch_offset(1999, 100 * 3600);
+        assert_eq!((x.raw(), x.utc_offset()), (1999, MAX_UTC_OFFSET));
+        assert_eq!(x.change_tz_seconds(i32::MIN).utc_offset(), -MAX_UTC_OFFSET);
+        assert!(ExtendedTime::from_ms_1601(-5, i32::MAX).is_canonical());
+
+        // before 1601 clamps to 1601 rather than wrapping
+        let early = System::strptime("1500-06-01 12:00:00.500", "%Y-%m-%d %H:%M:%S%.3f");
+        assert_eq!(early.raw(), 0);
+        assert!(early.is_canonical());
+
+        let legacy: System = serde_json::from_str(r#"{"inner_secs":1,"inner_milliseconds":2500,"utc_offset":99999999}"#).unwrap();
+        assert_eq!((legacy.raw(), legacy.utc_offset()), (3500, MAX_UTC_OFFSET));
+        assert!(legacy.is_canonical());
+    }
+
+    #[test]
+    fn tz_inverse_conversions() {
+        let x = "2024-01-05 00:10:07.250".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+        for tz in Tz::ALL {
+            assert_eq!(tz.to_utc_struct(tz.offset_struct(x.clone())), x.to_utc());
+            let shifted = tz.offset_struct(x.clone()).wall_clock();
+            assert_eq!(tz.wall_clock_of(&x), (shifted.hour() as u8, shifted.minute() as u8, shifted.second() as u8));
+        }
+        // negative half-hour zones cross midnight backwards
+        assert_eq!(Tz::Nst.wall_clock_of(&x), (20, 40, 7));
+        assert_eq!(Tz::Mart.wall_clock_of(&x), (14, 40, 7));
+        assert_eq!(Tz::Nst.to_utc_struct(x.clone()).pretty(), "2024-01-05 03:40:07");
+        assert_eq!(Tz::Chast.wall_clock_of(&x), (12, 55, 7));
+    }
+
+    #[test]
+    fn default_and_sentinels() {
+        assert_eq!(System::default().pretty(), "1970-01-01 00:00:00");
+        assert_eq!(Ntp::default().pretty(), "1970-01-01 00:00:00");
+        assert!(System::default().is_unix_epoch() && Ntp::default().is_unix_epoch());
+        assert!(System::unix_epoch().change_tz_seconds(3600).is_unix_epoch());
+        assert_eq!(ExtendedTime::unix_epoch().unix(), 0);
+
+        assert_eq!(System::windows_epoch().raw(), 0);
+        assert!(System::windows_epoch().is_sentinel());
+        assert!(!System::from_epoch(1).is_sentinel());
+
+        assert!(System::far_future() > System::now());
+        assert!(Ntp::far_future() > Ntp::from_epoch(System::now().raw()));
+        assert_eq!(System::far_future().iso8601(), "9999-12-31 23:59:59.999");
+        assert!(System::far_future().is_sentinel() && System::far_future().add_days(1).is_sentinel());
+        assert!(!System::far_future().add_seconds(-1).is_sentinel());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn bytes_parsing_matches_str(ms in -11644473600000i64..253402300799999i64, offset in -50400i32..50400, junk in "[ -~]{0,3}") {
+            let x = System::from_epoch_offset((ms + OFFSET_1601 as i64 * 1000) as u64, offset);
+            for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.3f", "%Y-%m-%d %H:%M:%S%.f %z", "%d/%b/%Y:%H:%M:%S %:z", "%a %e %B %Y %I:%M:%S %p", "%s", "%j %Y %H%M%S", "%D %T"] {
+                let formatted = x.strftime(format);
+                for input in [formatted.clone(), format!("{}{}", formatted, junk), format!(" {}", junk)] {
+                    let via_str = parse_with_default_offset(&input, format, Some(0), OffsetSource::AssumedUtc);
+                    let via_bytes = parse_time_bytes::<System>(input.as_bytes(), format);
+                    match (via_str, via_bytes) {
+                        (Ok((ms, offset, source)), Ok(y)) => {
+                            proptest::prop_assert_eq!(y.unix_ms(), ms.max(-(OFFSET_1601 as i64) * 1000));
+                            proptest::prop_assert_eq!(y.utc_offset(), offset);
+                            proptest::prop_assert_eq!(y.offset_source(), source);
+                        }
+                        (Err(_), Err(_)) => {}
+                        (a, b) => proptest::prop_assert!(false, "{:?} with {:?}: {:?} vs {:?}", input, format, a, b),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bytes_parsing() {
+        let line = b"2024-01-05 14:46:29.123 \xc3\x28zone +0100";
+        let x: System = parse_time_bytes(line, "%Y-%m-%d %H:%M:%S%.3f %Z %z").unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704465989123 - 3_600_000, 3600));
+        assert_eq!(x.offset_source(), OffsetSource::Explicit);
+
+        // invalid UTF-8 where the format needs a digit is an error at that byte
+        match parse_time_bytes::<System>(b"2024-01-\xff5", "%Y-%m-%d") {
+            Err(ParseError::Invalid { position, .. }) => assert_eq!(position, 8),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(parse_time_bytes::<System>(b"", "%Y"), Err(ParseError::Empty));
+        assert!(parse_time_bytes::<System>(b"2024-01-05 extra", "%Y-%m-%d").is_err());
+        assert!(b"05/Jan/2024:14:46:29 +0000".parse_time::<Ntp>("%d/%b/%Y:%H:%M:%S %z").is_ok());
+
+        // the bytes version of the lenient ISO parser
+        assert_eq!(b"  2024-01-05T14:46:29,5Z ".strp_iso_lenient::<System>().unwrap().unix_ms(), 1704465989500);
+        assert_eq!(
+            b"2024-13-05".strp_iso_lenient::<System>().unwrap_err(),
+            "2024-13-05".strp_iso_lenient::<System>().unwrap_err()
+        );
+        assert!(vec![0xffu8, b'1'].strp_iso_lenient::<System>().is_err());
+    }
+
+    #[test]
+    fn ttl_boundaries() {
+        use core::time::Duration;
+        let created = "2024-01-05 14:46:29.500 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let ttl = Duration::from_millis(1500);
+        let at = |ms: i64| System::from_epoch((created.raw() as i64 + ms) as u64);
+
+        assert_eq!(created.expires_at(ttl).unix_ms(), created.unix_ms() + 1500);
+        assert_eq!(created.expires_at(ttl).utc_offset(), 19800);
+        assert_eq!(created.expires_at(Duration::MAX).raw(), System::far_future().raw());
+
+        // one millisecond before, exactly at, and after the TTL
+        assert!(!created.is_older_than(ttl, &at(1499)));
+        assert!(created.is_older_than(ttl, &at(1500)));
+        assert!(created.is_within_last(ttl, &at(1499)));
+        assert!(!created.is_within_last(ttl, &at(1500)));
+        assert_eq!(created.remaining_ttl(ttl, &at(1499)), Some(Duration::from_millis(1)));
+        assert_eq!(created.remaining_ttl(ttl, &at(1500)), None);
+        assert_eq!(created.remaining_ttl(ttl, &at(-500)), Some(Duration::from_millis(2000)));
+        assert!(!created.is_within_last(ttl, &at(-1)));
+
+        // a zero TTL is expired from the moment of creation
+        assert!(created.is_older_than(Duration::ZERO, &created));
+        assert!(!created.is_within_last(Duration::ZERO, &created));
+        assert_eq!(created.remaining_ttl(Duration::ZERO, &created), None);
+        assert_eq!(created.expires_at(Duration::ZERO), created.change_tz_seconds(19800));
+    }
+
+    #[test]
+    fn systemtime_parts() {
+        // a Sunday, in +01:00
+        let x = "2024-01-07 00:30:15.250 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let utc = x.to_systemtime_parts();
+        assert_eq!(
+            utc,
+            SystemTimeParts { year: 2024, month: 1, day_of_week: 6, day: 6, hour: 23, minute: 30, second: 15, milliseconds: 250 }
+        );
+        let local = x.to_local_systemtime_parts();
+        assert_eq!((local.day, local.day_of_week, local.hour), (7, 0, 0));
+        assert_eq!(local.strict(), Ok(local));
+
+        assert_eq!(System::from_systemtime_parts(utc).unwrap().unix_ms(), x.unix_ms());
+        let back = System::from_local_systemtime_parts(local, 3600).unwrap();
+        assert_eq!((back.unix_ms(), back.utc_offset()), (x.unix_ms(), 3600));
+        assert_eq!(std::mem::size_of::<SystemTimeParts>(), 16);
+
+        // 1601-01-01 was a Monday
+        let epoch = System::windows_epoch().to_systemtime_parts();
+        assert_eq!((epoch.year, epoch.day_of_week), (1601, 1));
+
+        let wrong_day = SystemTimeParts { day_of_week: 3, ..local };
+        assert_eq!(wrong_day.strict(), Err(TimeError::InvalidField { field: "wDayOfWeek", value: 3 }));
+        assert_eq!(System::from_systemtime_parts(wrong_day).unwrap().unix_ms(), x.unix_ms() + 3_600_000);
+        for (bad, field) in [
+            (SystemTimeParts { month: 13, ..local }, "wMonth"),
+            (SystemTimeParts { day_of_week: 7, ..local }, "wDayOfWeek"),
+            (SystemTimeParts { day: 0, ..local }, "wDay"),
+            (SystemTimeParts { hour: 24, ..local }, "wHour"),
+            (SystemTimeParts { milliseconds: 1000, ..local }, "wMilliseconds"),
+            (SystemTimeParts { year: 1600, ..local }, "wYear"),
+            (SystemTimeParts { year: 2023, month: 2, day: 29, ..local }, "wDay"),
+        ] {
+            assert!(matches!(Ntp::from_systemtime_parts(bad), Err(TimeError::InvalidField { field: f, .. }) if f == field));
+        }
+    }
+
+    #[test]
+    fn merge_streams() {
+        let t = |s: u32| s.unix::<System>();
+        let a = vec![t(1), t(3), t(5), t(5)];
+        let b = vec![t(2), t(3), t(6)];
+        let c = vec![t(0), t(3), t(5), t(7)];
+        let merged: Vec<(usize, i64)> = merge_sorted(vec![a.clone().into_iter(), b.clone().into_iter(), c.clone().into_iter()])
+            .map(|(i, x)| (i, x.unix()))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![(2, 0), (0, 1), (1, 2), (0, 3), (1, 3), (2, 3), (0, 5), (0, 5), (2, 5), (1, 6), (2, 7)]
+        );
+
+        // payloads follow their times, and ties keep stream order
+        let tagged = |v: &[System], tag: char| v.iter().enumerate().map(move |(n, x)| (x.clone(), (tag, n))).collect::<Vec<_>>().into_iter();
+        let payloads: Vec<(char, usize)> = merge_sorted_by_key(vec![tagged(&a, 'a'), tagged(&b, 'b'), tagged(&c, 'c')])
+            .map(|(_, (_, p))| p)
+            .collect();
+        assert_eq!(&payloads[3..6], &[('a', 1), ('b', 1), ('c', 1)]);
+        assert_eq!(&payloads[6..9], &[('a', 2), ('a', 3), ('c', 2)]);
+
+        // sorted input checks clean, unsorted input reports each backwards step
+        assert!(merge_sorted_checked(vec![a.into_iter(), b.into_iter(), c.into_iter()]).all(|r| r.is_ok()));
+        let bad = vec![t(4), t(2), t(8), t(1)];
+        let results: Vec<_> = merge_sorted_checked(vec![bad.into_iter(), vec![t(3)].into_iter()]).collect();
+        let errors: Vec<_> = results.iter().filter_map(|r| r.clone().err()).collect();
+        assert_eq!(errors, vec![MergeError::Unsorted { source: 0, position: 1 }, MergeError::Unsorted { source: 0, position: 3 }]);
+        let ok: Vec<i64> = results.iter().filter_map(|r| r.as_ref().ok()).map(|(_, x)| x.unix()).collect();
+        assert_eq!(ok, vec![3, 4, 8]);
+        assert_eq!(merge_sorted(Vec::<std::vec::IntoIter<System>>::new()).count(), 0);
+    }
+
+    #[test]
+    fn clock_snapshots() {
+        let mut readings = vec![1704465989000i64, 1704465989250].into_iter();
+        let system = || System::from_epoch((readings.next().unwrap() + OFFSET_1601 as i64 * 1000) as u64).change_tz("+01:00");
+        let snapshot = ClockSnapshot::capture_with(system, || Ok(1704465990u32.unix::<Ntp>())).unwrap();
+        assert_eq!(snapshot.rtt_ms, 250);
+        assert_eq!(snapshot.system.unix_ms(), 1704465989125);
+        assert_eq!(snapshot.system.utc_offset(), 3600);
+        assert_eq!(snapshot.skew_ms, 875);
+
+        // a clock stepping backwards mid request is not a negative round trip
+        let mut readings = vec![2000u32, 1000].into_iter();
+        let snapshot = ClockSnapshot::capture_with(|| readings.next().unwrap().unix::<System>(), || Ok(1000u32.unix::<Ntp>())).unwrap();
+        assert_eq!((snapshot.rtt_ms, snapshot.skew_ms), (0, -1_000_000));
+
+        let failed = ClockSnapshot::capture_with(System::now, || Err(NtpError::Timeout));
+        assert!(matches!(failed, Err(NtpError::Timeout)));
+
+        let json: serde_json::Value = serde_json::to_value(&snapshot).unwrap();
+        let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["ntp", "rtt_ms", "skew_ms", "system"]);
+        assert_eq!(json["skew_ms"], -1_000_000);
+        assert_eq!(json["system"]["ms1601"], 11644475600000u64);
+        let back: ClockSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!((back.system.unix(), back.ntp.unix(), back.skew_ms), (2000, 1000, -1_000_000));
+    }
+
+    #[test]
+    fn localized_names() {
+        // Sunday 2023-12-31, Monday 2024-01-01 and Wednesday 2024-03-13
+        let days = [1703980800u32.unix::<System>(), 1704067200u32.unix::<System>(), 1710288000u32.unix::<System>()];
+        let format = "%A %a %d %B %b %Y";
+        let render = |names: &NameTable| days.iter().map(|d| d.strftime_with_names(format, names)).collect::<Vec<_>>();
+        assert_eq!(
+            render(&NameTable::EN),
+            days.iter().map(|d| d.strftime(format)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            render(&NameTable::DE),
+            vec!["Sonntag So 31 Dezember Dez 2023", "Montag Mo 01 Januar Jan 2024", "Mittwoch Mi 13 März Mär 2024"]
+        );
+        assert_eq!(
+            render(&NameTable::FR),
+            vec!["dimanche dim. 31 décembre déc. 2023", "lundi lun. 01 janvier janv. 2024", "mercredi mer. 13 mars mars 2024"]
+        );
+        assert_eq!(
+            render(&NameTable::ES),
+            vec!["domingo dom 31 diciembre dic 2023", "lunes lun 01 enero ene 2024", "miércoles mié 13 marzo mar 2024"]
+        );
+
+        // names follow the wall clock, not UTC
+        let late = days[0].change_tz("+01:00");
+        assert_eq!(late.strftime_with_names("%A %h", &NameTable::DE), "Sonntag Dez");
+
+        // a custom table, with names that look like format specifiers
+        let shouty = NameTable {
+            months: NameTable::EN.months.map(|_| "%MONTH%"),
+            weekdays_short: ["M", "T", "W", "T", "F", "S", "S"],
+            ..NameTable::EN
+        };
+        assert_eq!(days[2].strftime_with_names("%a %B %%", &shouty), "W %MONTH% %");
+    }
+
+    #[test]
+    fn time_steps() {
+        use core::time::Duration;
+        let start = 1704465989u32.unix::<System>().change_tz("+05:30");
+
+        // integer stepping: 1000 steps of 100ms land exactly 100s on, keeping the offset
+        let last = start.clone().iter_from(Duration::from_millis(100)).nth(1000).unwrap();
+        assert_eq!(last.unix_ms() - start.unix_ms(), 100_000);
+        assert_eq!(last.utc_offset(), 19800);
+        let odd = start.clone().iter_from(Duration::from_micros(333_700)).nth(3000).unwrap();
+        assert_eq!(odd.unix_ms() - start.unix_ms(), 999_000);
+
+        // bounded: end included when landed on, otherwise the last step before it
+        let end = start.add_seconds(10);
+        let exact: Vec<_> = start.clone().iter_until(&end, Duration::from_secs(2)).collect();
+        assert_eq!((exact.len(), exact.last().unwrap().unix()), (6, end.unix()));
+        let uneven: Vec<_> = start.clone().iter_until(&end, Duration::from_secs(3)).collect();
+        assert_eq!((uneven.len(), uneven.last().unwrap().unix() - start.unix()), (4, 9));
+        assert_eq!(end.clone().iter_until(&start, Duration::from_secs(1)).count(), 0);
+        assert_eq!(start.clone().iter_until(&end, Duration::from_micros(10)).count(), 1);
+
+        // backwards, stopping at the 1601 floor
+        let near_floor = System::from_epoch(2500);
+        let down: Vec<u64> = near_floor.countdown(Duration::from_secs(1)).map(|t| t.raw()).collect();
+        assert_eq!(down, vec![2500, 1500, 500]);
+        let on_floor: Vec<u64> = System::from_epoch(2000).countdown(Duration::from_secs(1)).map(|t| t.raw()).collect();
+        assert_eq!(on_floor, vec![2000, 1000, 0]);
+        assert_eq!(System::from_epoch(5).countdown(Duration::MAX).count(), 1);
+    }
+
+    #[test]
+    fn year_weeks() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+
+        // 2020 has 53 weeks, the last ending on Sunday 2021-01-03
+        let last = YearWeek::new(2020, 53).unwrap();
+        assert_eq!(YearWeek::weeks_in_year(2020), Some(53));
+        assert!(YearWeek::new(2021, 53).is_none());
+        assert_eq!(last.start::<System>().pretty(), "2020-12-28 00:00:00");
+        assert_eq!(last.end::<System>().pretty(), "2021-01-04 00:00:00");
+        assert!(last.contains(&at("2021-01-03 23:59:59")));
+        assert!(!last.contains(&at("2021-01-04 00:00:00")));
+        assert_eq!(last.succ().to_string(), "2021-W01");
+        assert_eq!(last.succ().pred(), last);
+        assert_eq!(YearWeek::new(2020, 52).unwrap().succ(), last);
+
+        // year boundary weeks belong to the ISO year, not the calendar year
+        let new_year = at("2024-12-30 09:00:00");
+        assert_eq!(YearWeek::from(&new_year).to_string(), "2025-W01");
+        assert_eq!(YearWeek::of(&at("2027-01-01 00:00:00")).to_string(), "2026-W53");
+        assert_eq!("2025-W01".parse::<YearWeek>().unwrap().pred().to_string(), "2024-W52");
+        // by wall clock, so an offset can move a time into the next week
+        assert_eq!(YearWeek::of(&at("2024-12-29 23:30:00").change_tz("+01:00")).to_string(), "2025-W01");
+
+        assert!("2024-W5".parse::<YearWeek>().is_err());
+        assert!("24-W05".parse::<YearWeek>().is_err());
+        assert!("2024W05".parse::<YearWeek>().is_err());
+        assert_eq!("".parse::<YearWeek>(), Err(ParseError::Empty));
+        assert_eq!("2024-W53".parse::<YearWeek>(), Err(ParseError::OutOfRange { field: "week", value: 53 }));
+        assert!("2024-W00".parse::<YearWeek>().is_err());
+
+        assert_eq!(serde_json::to_string(&last).unwrap(), "\"2020-W53\"");
+        assert_eq!(serde_json::from_str::<YearWeek>("\"2020-W53\"").unwrap(), last);
+        assert!(serde_json::from_str::<YearWeek>("\"2021-W53\"").is_err());
+    }
+
+    #[test]
+    fn dst_ambiguity() {
+        let cet = DstRule::eu(3600);
+        assert_eq!(cet.transitions(2024), Some((1711846800, 1729990800)));
+        let resolve = |d, h, m, policy| cet.resolve_local::<System>(2024, if d == 31 { 3 } else { 10 }, d, h, m, 0, policy);
+        let show = |r: Result<System, TimeError>| r.map(|t| (t.to_utc().strftime("%H:%M"), t.strftime("%H:%M"), t.utc_offset()));
+
+        // the gap: 02:30 on 2024-03-31 does not exist in CET/CEST
+        assert_eq!(
+            resolve(31, 2, 30, AmbiguityPolicy::Reject).unwrap_err(),
+            TimeError::SkippedLocalTime { earlier: 1711845000, later: 1711848600 }
+        );
+        assert_eq!(show(resolve(31, 2, 30, AmbiguityPolicy::Earlier)).unwrap(), ("00:30".into(), "01:30".into(), 3600));
+        assert_eq!(show(resolve(31, 2, 30, AmbiguityPolicy::Later)).unwrap(), ("01:30".into(), "03:30".into(), 7200));
+        assert_eq!(show(resolve(31, 2, 30, AmbiguityPolicy::ShiftForward)).unwrap(), ("01:00".into(), "03:00".into(), 7200));
+        // either side of the gap is unaffected by the policy
+        assert_eq!(show(resolve(31, 1, 59, AmbiguityPolicy::Reject)).unwrap(), ("00:59".into(), "01:59".into(), 3600));
+        assert_eq!(show(resolve(31, 3, 0, AmbiguityPolicy::Reject)).unwrap(), ("01:00".into(), "03:00".into(), 7200));
+
+        // the fold: 02:30 on 2024-10-27 happens twice
+        assert_eq!(
+            resolve(27, 2, 30, AmbiguityPolicy::Reject).unwrap_err(),
+            TimeError::AmbiguousLocalTime { earlier: 1729989000, later: 1729992600 }
+        );
+        assert_eq!(show(resolve(27, 2, 30, AmbiguityPolicy::Earlier)).unwrap(), ("00:30".into(), "02:30".into(), 7200));
+        assert_eq!(show(resolve(27, 2, 30, AmbiguityPolicy::Later)).unwrap(), ("01:30".into(), "02:30".into(), 3600));
+        assert_eq!(show(resolve(27, 2, 30, AmbiguityPolicy::ShiftForward)).unwrap(), ("01:30".into(), "02:30".into(), 3600));
+        assert_eq!(show(resolve(27, 3, 0, AmbiguityPolicy::Reject)).unwrap(), ("02:00".into(), "03:00".into(), 3600));
+
+        assert!(matches!(cet.resolve_local::<System>(2024, 2, 30, 0, 0, 0, AmbiguityPolicy::Earlier), Err(TimeError::InvalidField { .. })));
+        assert!(TimeError::SkippedLocalTime { earlier: 1, later: 2 }.to_string().contains("skipped"));
+
+        // other rules: the US, and a southern hemisphere rule with start after end
+        let eastern = DstRule::us(-18000);
+        assert!(matches!(eastern.resolve_local::<System>(2024, 3, 10, 2, 30, 0, AmbiguityPolicy::Reject), Err(TimeError::SkippedLocalTime { .. })));
+        assert!(matches!(eastern.resolve_local::<System>(2024, 11, 3, 1, 30, 0, AmbiguityPolicy::Reject), Err(TimeError::AmbiguousLocalTime { .. })));
+        let sydney = DstRule {
+            standard_offset: 36000,
+            dst_offset: 39600,
+            start: TransitionRule { month: 10, week: 1, weekday: Weekday::Sun, time: 7200 },
+            end: TransitionRule { month: 4, week: 1, weekday: Weekday::Sun, time: 10800 },
+        };
+        assert!(sydney.is_dst(&1704465989u32.unix::<System>()));
+        assert!(!sydney.is_dst(&1719835200u32.unix::<System>()));
+        assert_eq!(sydney.localize(&1704465989u32.unix::<System>()).utc_offset(), 39600);
+    }
+
+    #[test]
+    fn ntp_server_names() {
+        // sentinel, well known and custom names all read back the same, and compare by their text
+        let from_strptime = "2024-01-05 14:46:29".parse_time::<Ntp>("%Y-%m-%d %H:%M:%S");
+        assert_eq!(from_strptime.server(), "strptime");
+        assert!(!from_strptime.valid_server());
+        assert_eq!(Ntp::from_epoch(0).server(), "from_epoch");
+        assert!(Ntp::from_epoch(0).valid_server());
+
+        let custom: Ntp = serde_json::from_str(r#"{"v":1,"ms1601":0,"offset":0,"server":"ntp1.corp.example"}"#).unwrap();
+        let known: Ntp = serde_json::from_str(r#"{"v":1,"ms1601":0,"offset":0,"server":"pool.ntp.org"}"#).unwrap();
+        let sentinel: Ntp = serde_json::from_str(r#"{"v":1,"ms1601":0,"offset":0,"server":"chrono::Utc"}"#).unwrap();
+        assert_eq!(custom.server(), "ntp1.corp.example");
+        assert_eq!(known.server(), "pool.ntp.org");
+        assert!(custom.valid_server() && known.valid_server() && !sentinel.valid_server());
+        assert_eq!(custom.clone(), custom);
+        assert!(custom < known);
+        assert!(format!("{:?}", custom).contains(r#"server: "ntp1.corp.example""#));
+
+        // serde still writes the plain string
+        let json = serde_json::to_value(&custom).unwrap();
+        assert_eq!(json["server"], "ntp1.corp.example");
+        let legacy: Ntp = serde_json::from_str(r#"{"inner_secs":1,"inner_milliseconds":0,"utc_offset":0,"server":"time.windows.com"}"#).unwrap();
+        assert_eq!(legacy.server(), "time.windows.com");
+    }
+
+    #[test]
+    fn time_expressions() {
+        use core::time::Duration;
+        let eval = |e: &str| eval_time_expr::<System>(e).map(|t| t.strftime("%Y-%m-%d %H:%M:%S%.3f %z"));
+
+        // chained operators, multi-unit terms and loose whitespace
+        assert_eq!(eval("2024-01-05 14:00 + 3d - 2h").unwrap(), "2024-01-08 12:00:00.000 +0000");
+        assert_eq!(eval("  2024-01-05T14:00:00+05:30 -1h30m +250ms  ").unwrap(), "2024-01-05 12:30:00.250 +0530");
+        assert_eq!(eval("2024-01-05 +1w+ 2 days").unwrap(), "2024-01-14 00:00:00.000 +0000");
+        assert_eq!(eval("2024-01-05 14:46:29").unwrap(), "2024-01-05 14:46:29.000 +0000");
+
+        // a bare now, and now with terms
+        let before = System::now();
+        let now = eval_time_expr::<System>("now").unwrap();
+        assert!(now >= before && now.unix() - before.unix() <= 1);
+        let later = eval_time_expr::<System>("NOW+90m").unwrap();
+        assert!(later.unix() - before.unix() >= 5400);
+
+        // errors point into the whole expression
+        let position = |e: ExprError| match e {
+            ExprError::Parse(ParseError::Invalid { input, position, .. }) => (input, position),
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(position(eval("2024-01-05 + 3x - 2h").unwrap_err()), ("2024-01-05 + 3x - 2h".to_string(), 14));
+        assert_eq!(position(eval("now + 3d 2h").unwrap_err()).1, 9);
+        assert_eq!(position(eval(" 2024-13-05 + 1d").unwrap_err()).1, 6);
+        assert_eq!(position(eval("now + d").unwrap_err()).1, 6);
+        assert_eq!(eval("   "), Err(ExprError::Parse(ParseError::Empty)));
+        assert_eq!(eval("1601-01-01 - 1ms"), Err(ExprError::OutOfRange));
+
+        assert_eq!(eval_duration_expr("3d - 2h").unwrap(), Duration::from_secs(70 * 3600));
+        assert_eq!(eval_duration_expr("-1h + 2h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(eval_duration_expr("90 minutes").unwrap(), Duration::from_secs(5400));
+        assert_eq!(eval_duration_expr("2h - 3h"), Err(ExprError::Negative { ms: -3_600_000 }));
+        assert!(eval_duration_expr("2h 3h").is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn hash_matches_eq(ms in 0u64..(FAR_FUTURE_UNIX_MS as u64 + OFFSET_1601 * 1000), a in -64800i32..64800, b in -64800i32..64800, server in "[a-z.]{0,12}") {
+            use std::hash::{BuildHasher, Hash, Hasher};
+            let state = std::collections::hash_map::RandomState::new();
+            let hash = |x: &dyn Fn(&mut std::collections::hash_map::DefaultHasher)| {
+                let mut h = state.build_hasher();
+                x(&mut h);
+                h.finish()
+            };
+
+            let x = System::from_epoch_offset(ms, a);
+            let y = System::from_epoch_offset(ms, b);
+            assert!(x.instant_eq(&y));
+            assert_eq!(hash(&|h| x.hash(h)), hash(&|h| y.hash(h)));
+            assert_eq!(hash(&|h| x.hash(h)), hash(&|h| x.clone().hash(h)));
+            assert_eq!(x.hash_key(), y.hash_key());
+
+            let n = Ntp::from_epoch_offset(ms, a);
+            let named: Ntp = serde_json::from_value(serde_json::json!({"v": 1, "ms1601": ms, "offset": b, "server": server})).unwrap();
+            assert!(n.instant_eq(&named) && n.instant_eq(&x));
+            assert_eq!(hash(&|h| n.hash(h)), hash(&|h| named.hash(h)));
+            assert_eq!(n.hash_key(), x.hash_key());
+
+            // the contract itself: whatever Eq says is equal must hash equally - so build values equal to x by other routes, and check
+            // that they really are equal before comparing hashes
+            let assumed = System::strptime_with_default_offset(x.strftime("%Y-%m-%d %H:%M:%S%.3f"), "%Y-%m-%d %H:%M:%S%.f", a);
+            let round_trip: System = serde_json::from_str(&serde_json::to_string(&x).unwrap()).unwrap();
+            let moved_back = x.change_tz_seconds(b).change_tz_seconds(a);
+            for q in [x.clone(), assumed, round_trip, moved_back] {
+                proptest::prop_assert_eq!(&x, &q);
+                proptest::prop_assert_eq!(hash(&|h| x.hash(h)), hash(&|h| q.hash(h)));
+                proptest::prop_assert_eq!(x.hash_key(), q.hash_key());
+            }
+            let n_round_trip: Ntp = serde_json::from_str(&serde_json::to_string(&n).unwrap()).unwrap();
+            for q in [n.clone(), n_round_trip] {
+                proptest::prop_assert_eq!(&n, &q);
+                proptest::prop_assert_eq!(hash(&|h| n.hash(h)), hash(&|h| q.hash(h)));
+            }
+            let other = System::from_epoch_offset(ms ^ 1, a);
+            for (p, q) in [(&x, &y), (&x, &other), (&y, &other)] {
+                proptest::prop_assert_eq!(p.hash_key() == q.hash_key(), p.instant_eq(q));
+            }
+        }
+    }
+
+    #[test]
+    fn ms_duration_printing() {
+        assert_eq!(90_250u64.ts_print_ms(), "0w 0d 0h 1m 30.250s");
+        assert_eq!(604_800_000u64.ts_print_ms(), "1w 0d 0h 0m 0.000s");
+        assert_eq!(0u64.ts_print_ms(), "0w 0d 0h 0m 0.000s");
+        // a (non leap) year keeps counting in weeks
+        assert_eq!(31_536_000_000u64.ts_print_ms(), "52w 1d 0h 0m 0.000s");
+        assert_eq!(90_999u64.ts_print_ms_with(2), "0w 0d 0h 1m 30.99s");
+        assert_eq!(90_250u64.ts_print_ms_with(5), "0w 0d 0h 1m 30.25000s");
+        // agrees with ts_print on whole seconds
+        assert_eq!(3_600_000u64.ts_print_ms_with(0), 3600u64.ts_print());
+
+        assert_eq!(90u64.ts_print_units(1), "1m");
+        assert_eq!(604_801u64.ts_print_units(5), "1w 0d 0h 0m 1s");
+        assert_eq!(604_801u64.ts_print_units(0), "1w");
+        assert_eq!(31_536_000u64.ts_print_units(2), "52w 1d");
+        assert_eq!(0u64.ts_print_units(3), "0s");
+        assert_eq!(45u64.ts_print_units(3), "45s");
+    }
+
+    #[test]
+    fn log_stamps() {
+        let now = System::from_epoch((1704465989123 + OFFSET_1601 as i64 * 1000) as u64);
+        let stamp = |x: System| x.log_stamp_with(Some(&now), LOG_STAMP_HORIZON);
+        assert_eq!(stamp(now.clone()), "2024-01-05T14:46:29.123Z (now)");
+        assert_eq!(stamp(System::from_epoch(now.raw() - 3250)), "2024-01-05T14:46:25.873Z (3.2s ago)");
+        assert_eq!(stamp(now.add_minutes(90)), "2024-01-05T16:16:29.123Z (1h from now)");
+        assert_eq!(stamp(System::from_epoch(now.raw() - 450)), "2024-01-05T14:46:28.673Z (450ms ago)");
+        assert_eq!(stamp(System::from_epoch(now.raw() + 999)), "2024-01-05T14:46:30.122Z (999ms from now)");
+        assert_eq!(stamp(now.add_days(-30)), "2023-12-06T14:46:29.123Z (30d ago)");
+        assert_eq!(stamp(now.add_days(-30).add_seconds(-1)), "2023-12-06T14:46:28.123Z (>30d ago)");
+        assert_eq!(stamp(now.add_days(400)), "2025-02-08T14:46:29.123Z (>30d from now)");
+        // the offset of either side doesn't matter
+        assert_eq!(now.change_tz("+05:30").add_minutes(-5).log_stamp_with(Some(&now.change_tz("-08:00").cast::<Ntp>()), LOG_STAMP_HORIZON), "2024-01-05T14:41:29.123Z (5m ago)");
+        assert_eq!(now.log_stamp_with(Some(&now.add_days(-2)), core::time::Duration::ZERO), "2024-01-05T14:46:29.123Z (>0ms from now)");
+    }
+
+    #[test]
+    fn int_timestamp_parsing() {
+        use epoch::{parse_int_timestamp, parse_int_timestamp_lenient, Epoch, Unit};
+        assert_eq!(parse_int_timestamp("1,483,228,800"), Ok(1483228800));
+        assert_eq!(parse_int_timestamp(" 1_483 228_800\n"), Ok(1483228800));
+        assert_eq!(parse_int_timestamp("0x58643F80"), Ok(1482964864));
+        assert_eq!(parse_int_timestamp("0b101"), Ok(5));
+        assert_eq!(parse_int_timestamp("0"), Ok(0));
+        assert_eq!(parse_int_timestamp(""), Err(ParseError::Empty));
+        assert_eq!(parse_int_timestamp("  "), Err(ParseError::Empty));
+        assert_eq!(parse_int_timestamp("0x"), Err(ParseError::invalid("0x", 2, "a digit")));
+        assert_eq!(parse_int_timestamp(",,"), Err(ParseError::invalid(",,", 2, "a digit")));
+        assert_eq!(parse_int_timestamp("12a4"), Err(ParseError::invalid("12a4", 2, "a digit")));
+        assert_eq!(parse_int_timestamp("0b102"), Err(ParseError::invalid("0b102", 4, "a digit")));
+        assert_eq!(parse_int_timestamp("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(parse_int_timestamp("18446744073709551616"), Err(ParseError::invalid("18446744073709551616", 19, "a number that fits in 64 bits")));
+
+        assert_eq!(parse_int_timestamp_lenient("ts=1700000000;"), Ok(1700000000));
+        assert_eq!(parse_int_timestamp_lenient("id 7, at 0x58643F80"), Ok(7));
+        assert_eq!(parse_int_timestamp_lenient("filetime=0x01D263C1FEB0C000."), Ok(0x01D263C1FEB0C000));
+        assert_eq!(parse_int_timestamp_lenient("0xg"), Ok(0));
+        assert_eq!(parse_int_timestamp_lenient("1,2,"), Ok(12));
+        assert_eq!(parse_int_timestamp_lenient(""), Err(ParseError::Empty));
+        assert_eq!(parse_int_timestamp_lenient("none"), Err(ParseError::invalid("none", 4, "a number")));
+
+        let filetime = "0x01D263C1FEB0C000".parse_epoch::<System>(Epoch::Windows, Unit::Ticks100ns).unwrap();
+        assert_eq!(filetime.unix(), 1483228800);
+        let best = &epoch::identify_timestamp(parse_int_timestamp_lenient("ts=1700000000;").unwrap() as i64)[0];
+        assert_eq!((best.epoch, best.unit), (Epoch::Unix, Unit::Seconds));
+        assert_eq!("".parse_epoch::<System>(Epoch::Unix, Unit::Seconds), Err(ParseError::Empty));
+        assert_eq!(
+            "18,446,744,073,709,551,615".parse_epoch::<System>(Epoch::Unix, Unit::Seconds),
+            Err(ParseError::OutOfRange { field: "timestamp", value: i64::MAX })
+        );
+        assert_eq!(
+            "99999999999999".parse_epoch::<Ntp>(Epoch::Unix, Unit::Seconds),
+            Err(ParseError::OutOfRange { field: "timestamp", value: 99999999999999 })
+        );
+    }
+
+    #[test]
+    fn tagged_timestamps() {
+        use epoch::{Epoch, Unit};
+        let whole = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let early = "1650-06-15 12:34:56.789 +0200".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let late = "9999-12-31 23:59:59.999".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+        for epoch in Epoch::ALL {
+            for unit in Unit::ALL {
+                for time in [&whole, &early, &late] {
+                    let tagged = time.tagged(epoch, unit);
+                    assert!(tagged.starts_with(&format!("{}-{}:", epoch, unit)), "{}", tagged);
+                    let back = tagged.parse_tagged::<System>().unwrap();
+                    // only seconds lose anything, and they round down
+                    let expected = if unit == Unit::Seconds { time.unix_ms().div_euclid(1000) * 1000 } else { time.unix_ms() };
+                    assert_eq!(back.unix_ms(), expected, "{}", tagged);
+                    assert_eq!(back.utc_offset(), 0);
+                    assert_eq!(tagged.parse_tagged::<Ntp>().unwrap().unix_ms(), expected);
+                }
+            }
+        }
+
+        assert_eq!(whole.tagged(Epoch::WebKit, Unit::Microseconds), "webkit-us:13127702400000000");
+        assert_eq!(whole.tagged(Epoch::Unix, Unit::Seconds), "unix-s:1483228800");
+        assert_eq!(whole.tagged(Epoch::MacOsCfa, Unit::Ticks100ns), "macos-cfa-100ns:5049216000000000");
+        assert_eq!(early.tagged(Epoch::Unix, Unit::Seconds), "unix-s:-10083878704");
+        assert_eq!(late.tagged(Epoch::Windows, Unit::Nanoseconds), "windows-ns:265046774399999000000");
+        assert_eq!(" unix-ms:0\n".parse_tagged::<System>().unwrap().pretty(), "1970-01-01 00:00:00");
+
+        // unknown tags are refused, not guessed at - the error lists what is supported
+        let supported = "an epoch (unix, windows, webkit, macos, macos-cfa, ntp, sas4gl), '-' and a unit (s, ms, us, 100ns, ns)";
+        assert_eq!("gps-s:1000".parse_tagged::<System>(), Err(ParseError::invalid("gps-s:1000", 0, supported)));
+        assert_eq!("unix-ks:1000".parse_tagged::<System>(), Err(ParseError::invalid("unix-ks:1000", 5, supported)));
+        assert_eq!("unix:1000".parse_tagged::<System>(), Err(ParseError::invalid("unix:1000", 0, supported)));
+        assert_eq!("1483228800".parse_tagged::<System>(), Err(ParseError::invalid("1483228800", 10, "':' after the tag")));
+        assert_eq!("unix-s:".parse_tagged::<System>(), Err(ParseError::invalid("unix-s:", 7, "a digit")));
+        assert_eq!("unix-s:-".parse_tagged::<System>(), Err(ParseError::invalid("unix-s:-", 8, "a digit")));
+        assert_eq!("unix-s:1,000".parse_tagged::<System>(), Err(ParseError::invalid("unix-s:1,000", 8, "a digit")));
+        assert_eq!("".parse_tagged::<System>(), Err(ParseError::Empty));
+        assert_eq!(
+            "unix-s:-20000000000".parse_tagged::<System>(),
+            Err(ParseError::OutOfRange { field: "timestamp", value: -20000000000 })
+        );
+        assert_eq!(
+            "unix-ns:99999999999999999999999999999999999999999".parse_tagged::<System>(),
+            Err(ParseError::OutOfRange { field: "timestamp", value: i64::MAX })
+        );
+    }
+
+    #[test]
+    fn ntp_jitter_stats() {
+        let mut stats = NtpStats::new();
+        assert_eq!(stats.report(), NtpStatsReport { count: 0, mean_offset_ms: None, median_offset_ms: None, jitter_ms: None, min_delay_ms: None, max_delay_ms: None });
+        stats.add_sample(1.5, 30.0);
+        assert_eq!((stats.median_offset_ms(), stats.jitter_ms()), (Some(1.5), None));
+        assert_eq!(stats.summary(), "1 sample: offset mean 1.500ms median 1.500ms, jitter n/a, delay 30.000ms..30.000ms");
+
+        // successive differences 1, -3, 4, -1, 0: squares sum to 27 over 5
+        stats.extend([(2.5, 28.0), (-0.5, 41.0), (3.5, 25.0), (2.5, 27.0), (2.5, 29.0)]);
+        stats.add_sample(f64::NAN, 1.0);
+        stats.add_sample(1.0, f64::INFINITY);
+        assert_eq!(stats.count(), 6);
+        assert_eq!(stats.jitter_ms(), Some(5.4f64.sqrt()));
+        assert_eq!(stats.median_offset_ms(), Some(2.5));
+        assert_eq!(stats.mean_offset_ms(), Some(2.0));
+        assert_eq!((stats.min_delay_ms(), stats.max_delay_ms()), (Some(25.0), Some(41.0)));
+        assert_eq!(stats.summary(), "6 samples: offset mean 2.000ms median 2.500ms, jitter 2.324ms, delay 25.000ms..41.000ms");
+        stats.add_sample(10.0, 30.0);
+        assert_eq!(stats.median_offset_ms(), Some(2.5));
+
+        let snapshot = ClockSnapshot::capture_with(|| 1000u32.unix::<System>(), || Ok(1002u32.unix::<Ntp>())).unwrap();
+        let mut from_snapshots = NtpStats::new();
+        from_snapshots.add_snapshot(&snapshot);
+        assert_eq!(from_snapshots.samples(), &[NtpSample { offset_ms: 2000.0, delay_ms: 0.0 }]);
+
+        let json = serde_json::to_string(&from_snapshots).unwrap();
+        assert_eq!(json, r#"{"samples":[{"offset_ms":2000.0,"delay_ms":0.0}]}"#);
+        assert_eq!(serde_json::from_str::<NtpStats>(&json).unwrap(), from_snapshots);
+        assert_eq!(
+            serde_json::to_string(&from_snapshots.report()).unwrap(),
+            r#"{"count":1,"mean_offset_ms":2000.0,"median_offset_ms":2000.0,"jitter_ms":null,"min_delay_ms":0.0,"max_delay_ms":0.0}"#
+        );
+    }
+
+    #[test]
+    fn debug_output() {
+        let x = System::from_epoch((1704465989123 + OFFSET_1601 as i64 * 1000) as u64).change_tz("+05:30");
+        assert_eq!(
+            format!("{:?}", x),
+            r#"System { instant: "2024-01-05 14:46:29.123 UTC", utc_offset: +05:30, offset_source: Explicit, raw_ms_1601: 13348939589123, inner_secs: 13348939589, inner_milliseconds: 123 }"#
+        );
+        assert_eq!(
+            format!("{:#?}", x),
+            r#"System {
+    instant: "2024-01-05 14:46:29.123 UTC",
+    utc_offset: +05:30,
+    offset_source: Explicit,
+    raw_ms_1601: 13348939589123,
+    inner_secs: 13348939589,
+    inner_milliseconds: 123,
+}"#
+        );
+
+        let n = x.to_utc().cast::<Ntp>();
+        assert_eq!(
+            format!("{:?}", n),
+            r#"Ntp { instant: "2024-01-05 14:46:29.123 UTC", utc_offset: +00:00, server: "from_epoch", valid: false, offset_source: Explicit, raw_ms_1601: 13348939589123, inner_secs: 13348939589, inner_milliseconds: 123, last_packet: None, peer: None }"#
+        );
+        assert_eq!(
+            format!("{:#?}", "2024-01-05 14:46:29 -0800".parse_time::<Ntp>("%Y-%m-%d %H:%M:%S %z")),
+            r#"Ntp {
+    instant: "2024-01-05 22:46:29.000 UTC",
+    utc_offset: -08:00,
+    server: "strptime",
+    valid: false,
+    offset_source: Explicit,
+    raw_ms_1601: 13348968389000,
+    inner_secs: 13348968389,
+    inner_milliseconds: 0,
+    last_packet: None,
+    peer: None,
+}"#
+        );
+        assert!(format!("{:?}", System::default()).starts_with(r#"System { instant: "1970-01-01 00:00:00.000 UTC""#));
+    }
+
+    #[test]
+    fn precision_safe_serde() {
+        #[derive(::serde::Serialize, ::serde::Deserialize, Debug, PartialEq)]
+        struct Record {
+            #[serde(with = "crate::serde::string_windows_ns")]
+            at: System,
+            #[serde(with = "crate::serde::string_ms")]
+            seen: Ntp,
+            #[serde(with = "crate::serde::precision_safe")]
+            ticks: i64,
+        }
+
+        let at = System::from_epoch((1704465989123 + OFFSET_1601 as i64 * 1000) as u64);
+        let ticks = at.windows_ns() + 1;
+        assert!(ticks as u64 > crate::serde::MAX_SAFE_INTEGER);
+        let record = Record { at: at.clone(), seen: at.cast(), ticks };
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, r#"{"at":"133489395891230000","seen":"1704465989123","ticks":"133489395891230001"}"#);
+        let back: Record = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.at, record.at);
+        assert_eq!(back.seen.unix_ms(), 1704465989123);
+        assert_eq!(back.ticks, ticks);
+
+        // numbers are accepted too, and whole floats
+        let from_numbers: Record = serde_json::from_str(r#"{"at":133489395891230000,"seen":1704465989123.0,"ticks":-5}"#).unwrap();
+        assert_eq!((from_numbers.at, from_numbers.seen.unix_ms(), from_numbers.ticks), (at.clone(), 1704465989123, -5));
+        assert!(serde_json::from_str::<Record>(r#"{"at":"x","seen":1,"ticks":1}"#).is_err());
+        assert!(serde_json::from_str::<Record>(r#"{"at":1,"seen":1.5,"ticks":1}"#).is_err());
+        assert!(serde_json::from_str::<Record>(r#"{"at":1,"seen":1,"ticks":"9223372036854775808"}"#).is_err());
+        assert!(serde_json::from_str::<Record>(r#"{"at":-1,"seen":1,"ticks":1}"#).is_err());
+
+        // as a plain number, a reader using f64 (as JavaScript does) rounds the value
+        let plain = serde_json::to_string(&ticks).unwrap();
+        let as_js = serde_json::from_str::<f64>(&plain).unwrap() as i64;
+        assert_ne!(as_js, ticks);
+        assert_eq!(as_js, 133489395891230000);
+        let safe = serde_json::to_string(&crate::serde::PrecisionSafe(ticks)).unwrap();
+        assert_eq!(serde_json::from_str::<crate::serde::PrecisionSafe<i64>>(&safe).unwrap().0, ticks);
+        assert!(serde_json::from_str::<crate::serde::PrecisionSafe<u8>>("256").is_err());
+    }
+
+    #[test]
+    fn epoch_values() {
+        use epoch::Epoch;
+        assert_eq!(epoch::unix::<System>().unix(), 0);
+        assert_eq!(epoch::windows_nt::<System>().unix(), -(OFFSET_1601 as i64));
+        assert_eq!(epoch::webkit::<Ntp>().unix(), -(OFFSET_1601 as i64));
+        assert_eq!(epoch::mac_os::<System>().unix(), -MAGIC_MAC_OS);
+        assert_eq!(epoch::mac_os_cfa::<System>().unix(), MAGIC_MAC_OS_CFA);
+        assert_eq!(epoch::sas_4gl::<System>().unix(), -MAGIC_SAS_4GL);
+        assert_eq!(epoch::ntp::<System>().unix(), -(REF_TIME_1970 as i64));
+
+        // each matches its string constant
+        let pairs = [
+            (Epoch::Unix, epoch::UNIX),
+            (Epoch::Windows, epoch::WINDOWS_NT),
+            (Epoch::WebKit, epoch::WEBKIT),
+            (Epoch::MacOs, epoch::MAC_OS),
+            (Epoch::MacOsCfa, epoch::MAC_OS_CFA),
+            (Epoch::Sas4gl, epoch::SAS_4GL),
+            (Epoch::Ntp, epoch::NTP),
+        ];
+        for (e, text) in pairs {
+            assert_eq!(epoch::of::<System>(e).pretty(), text);
+            assert_eq!(e.start::<Ntp>(), epoch::of::<Ntp>(e));
+            assert_eq!(e.start::<System>().utc_offset(), 0);
+        }
+
+        let x = 3787310789u64.mac_os::<System>();
+        assert_eq!(x.diff(&epoch::mac_os::<System>()), 3787310789);
+        assert_eq!(x.mac_os(), x.unix() - epoch::mac_os::<System>().unix());
+        assert_eq!(epoch::windows_nt::<System>(), System::windows_epoch());
+    }
+
+    #[test]
+    fn telemetry_timestamps() {
+        let x = System::from_epoch((1704465989123 + OFFSET_1601 as i64 * 1000) as u64).change_tz("+05:30");
+        assert_eq!(x.otel_ns(), 1704465989123000000);
+        assert_eq!(x.prom_ms(), 1704465989123);
+        assert_eq!(x.to_proto_timestamp(), (1704465989, 123000000));
+        let (secs, nanos) = x.to_proto_timestamp();
+        let back = Ntp::from_proto_timestamp(secs, nanos).unwrap();
+        assert!(back.instant_eq(&x));
+        assert_eq!(back.utc_offset(), 0);
+
+        // before 1970: negative seconds, nanoseconds counting forwards
+        let old = "1969-07-20 20:17:40.250 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        assert_eq!(old.to_proto_timestamp(), (-14182940, 250000000));
+        assert_eq!(old.prom_ms(), -14182939750);
+        assert_eq!(old.otel_ns(), 0);
+        assert_eq!(System::from_proto_timestamp(-14182940, 250000000).unwrap(), old);
+        assert_eq!(System::from_proto_timestamp(-1, 999_999_999).unwrap().unix_ms(), -1);
+        assert_eq!(System::from_proto_timestamp(0, 0).unwrap(), System::unix_epoch());
+        assert_eq!(System::windows_epoch().to_proto_timestamp(), (-(OFFSET_1601 as i64), 0));
+
+        assert_eq!(System::from_proto_timestamp(0, 1_000_000_000), Err(TimeError::InvalidField { field: "nanos", value: 1_000_000_000 }));
+        assert_eq!(System::from_proto_timestamp(0, -1), Err(TimeError::InvalidField { field: "nanos", value: -1 }));
+        assert_eq!(System::from_proto_timestamp(-(OFFSET_1601 as i64) - 1, 0), Err(TimeError::Overflow));
+        assert_eq!(System::from_proto_timestamp(i64::MAX, 0), Err(TimeError::Overflow));
+        assert_eq!(System::from_epoch(u64::MAX).otel_ns(), u64::MAX);
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn prost_timestamps() {
+        let x = System::from_proto_timestamp(-14182940, 250_000_000).unwrap();
+        let ts: prost_types::Timestamp = x.clone().into();
+        assert_eq!((ts.seconds, ts.nanos), (-14182940, 250_000_000));
+        assert_eq!(System::try_from(ts), Ok(x));
+        let ntp: prost_types::Timestamp = 1704465989u32.unix::<Ntp>().into();
+        assert_eq!(Ntp::try_from(ntp).unwrap().unix(), 1704465989);
+        assert!(System::try_from(prost_types::Timestamp { seconds: 0, nanos: -5 }).is_err());
+    }
+
+    #[test]
+    fn relative_descriptions() {
+        // Friday 2024-01-05 14:46:29 UTC
+        let now = 1704465989u32.unix::<System>();
+        let describe = |x: System| x.describe_relative(&now);
+        assert_eq!(describe(now.clone()), "today at 14:46");
+        assert_eq!(describe(now.add_hours(-14)), "today at 00:46");
+        assert_eq!(describe(now.add_hours(9)), "today at 23:46");
+        assert_eq!(describe(now.add_hours(-15)), "yesterday");
+        assert_eq!(describe(now.add_hours(10)), "tomorrow at 00:46");
+        assert_eq!(describe(now.add_days(-3)), "last Tuesday");
+        assert_eq!(describe(now.add_days(-6)), "last Saturday");
+        assert_eq!(describe(now.add_days(2)), "on Sunday");
+        assert_eq!(describe(now.add_days(-7)), "last week");
+        assert_eq!(describe(now.add_days(13)), "next week");
+        assert_eq!(describe(now.add_days(20)), "later this month");
+        assert_eq!(describe(now.add_days(30)), "next month");
+        assert_eq!(describe(now.add_days(-14)), "last month");
+        assert_eq!(describe(now.add_days(300)), "in October");
+        assert_eq!(describe(now.add_days(-100)), "last September");
+        assert_eq!(describe(now.add_days(340)), "in December");
+        assert_eq!(describe(now.add_days(400)), "in February 2025");
+        assert_eq!(describe(now.add_days(-400)), "in December 2022");
+
+        let later = "2024-01-20 12:00:00 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(now.describe_relative(&later), "earlier this month");
+
+        // each side's own wall clock decides the day: 23:30 in -05:00 is still the 4th there, while now is the 5th in +05:00
+        let evening = "2024-01-04 23:30:00 -0500".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(evening.describe_relative(&now.change_tz("+05:00")), "yesterday");
+        assert_eq!(evening.describe_relative(&now.change_tz("-05:00")), "yesterday");
+        assert_eq!(evening.to_utc().describe_relative(&now), "today at 04:30");
+
+        let fr = RelativeWords {
+            names: NameTable::FR,
+            today: "aujourd'hui à {time}",
+            last_weekday: "{weekday} dernier",
+            future_month: "en {month}",
+            ..RelativeWords::default()
+        };
+        assert_eq!(now.describe_relative_with(&now, &fr), "aujourd'hui à 14:46");
+        assert_eq!(now.add_days(-3).describe_relative_with(&now, &fr), "mardi dernier");
+        assert_eq!(now.add_days(300).cast::<Ntp>().describe_relative_with(&now, &fr), "en octobre");
+    }
+
+    #[test]
+    fn tz_name_parsing() {
+        let format = "%Y-%m-%d %H:%M:%S %Q";
+        let parse = |s: &str, prefs: &[Tz]| s.parse_with_tz_names::<System>(format, prefs);
+
+        let est = parse("2024-01-05 09:46:29 EST", &[]).unwrap();
+        assert_eq!((est.unix(), est.utc_offset(), est.offset_source()), (1704465989, -18000, OffsetSource::Explicit));
+        assert_eq!(est.pretty(), "2024-01-05 09:46:29");
+        assert_eq!(parse("2024-01-05 14:46:29 utc", &[]).unwrap().unix(), 1704465989);
+        assert_eq!(parse("2024-01-05 15:46:29 BST/CET", &[]).unwrap().unix(), 1704465989);
+        assert_eq!("EST 2024-01-05 09:46".parse_with_tz_names::<Ntp>("%Q %Y-%m-%d %H:%M", &[]).unwrap().unix(), 1704465960);
+
+        // IST is India, Ireland or Israel
+        assert_eq!(
+            parse("2024-01-05 20:16:29 IST", &[]),
+            Err(TzNameError::Ambiguous { name: "IST".to_string(), position: 20, candidates: vec![Tz::Ist, Tz::BstCet, Tz::CestEet] })
+        );
+        assert_eq!(parse("2024-01-05 20:16:29 IST", &[Tz::Est, Tz::Ist]).unwrap().unix(), 1704465989);
+        assert_eq!(parse("2024-01-05 16:46:29 IST", &[Tz::CestEet, Tz::Ist]).unwrap().unix(), 1704465989);
+        assert_eq!(
+            parse("2024-01-05 20:16:29 IST", &[]).unwrap_err().to_string(),
+            r#"ambiguous timezone "IST" at position 20, could be IST (+05:30), BST/CET (+01:00), CEST/EET (+02:00)"#
+        );
+
+        assert_eq!(parse("2024-01-05 14:46:29 XYZ", &[]), Err(TzNameError::Unknown { name: "XYZ".to_string(), position: 20, near: vec![] }));
+        assert_eq!(parse("2024-01-05 14:46:29 XYZ", &[]).unwrap_err().to_string(), r#"unknown timezone "XYZ" at position 20"#);
+        let typo = parse("2024-01-05 14:46:29 ESTT", &[]).unwrap_err();
+        assert_eq!(typo.to_string(), r#"unknown timezone "ESTT" at position 20, did you mean EST, AEST, AST, BST, CEST?"#);
+
+        assert!(matches!(parse("2024-01-05 14:46:29 +0100", &[]), Err(TzNameError::Parse(ParseError::Invalid { position: 20, .. }))));
+        assert!(matches!(parse("2024-01-05 14:46:29 EST!", &[]), Err(TzNameError::Parse(ParseError::Invalid { position: 23, .. }))));
+        assert!(matches!(parse("2024-13-05 14:46:29 EST", &[]), Err(TzNameError::Parse(_))));
+        assert_eq!(parse("  ", &[]), Err(TzNameError::Parse(ParseError::Empty)));
+        assert!(matches!("x".parse_with_tz_names::<System>("%Q %Q", &[]), Err(TzNameError::Parse(ParseError::Invalid { position: 3, .. }))));
+
+        // %%Q is a literal, and a format without %Q parses as usual
+        assert_eq!("%Q 2024-01-05 00:00".parse_with_tz_names::<System>("%%Q %Y-%m-%d %H:%M", &[]).unwrap().unix(), 1704412800);
+        let plain = "2024-01-05 14:46:29".parse_with_tz_names::<System>("%Y-%m-%d %H:%M:%S", &[]).unwrap();
+        assert_eq!((plain.unix(), plain.offset_source()), (1704465989, OffsetSource::AssumedUtc));
+    }
+
+    #[test]
+    fn time_budgets() {
+        use core::time::Duration;
+        let start = System::from_epoch((1704465989000 + OFFSET_1601 as i64 * 1000) as u64);
+        let mut budget = TimeBudget::new(Duration::from_secs(1), start.clone());
+        assert_eq!(budget.deadline().unix_ms(), 1704465990000);
+        assert_eq!(budget.remaining(&start), Some(Duration::from_secs(1)));
+        assert_eq!(budget.remaining(&start.add_seconds(1)), None);
+        assert_eq!(budget.remaining(&start.add_seconds(-1)), Some(Duration::from_secs(2)));
+
+        // splitting a budget hands out all of it
+        let shares: Vec<Duration> = [0.5, 0.3, 0.2].iter().map(|f| budget.child(*f).available()).collect();
+        assert_eq!(shares, [Duration::from_millis(500), Duration::from_millis(300), Duration::from_millis(200)]);
+        assert_eq!(shares.iter().sum::<Duration>(), budget.available());
+        let thirds: Duration = (0..3).map(|_| budget.child(1.0 / 3.0).available()).sum();
+        assert!(budget.available() - thirds <= Duration::from_millis(3));
+        assert_eq!(budget.child(2.0).available(), budget.available());
+        assert_eq!(budget.child(f64::NAN).available(), Duration::ZERO);
+
+        // sequential sub-calls: each child starts where the parent has spent up to
+        for share in shares {
+            let child = budget.child(share.as_millis() as f64 / budget.available().as_millis() as f64);
+            assert_eq!(child.start(), budget.start());
+            assert_eq!(child.available(), share);
+            budget.consume(child.available()).unwrap();
+        }
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.start(), budget.deadline());
+
+        let mut budget = TimeBudget::new(Duration::from_millis(1500), start.clone());
+        budget.consume(Duration::from_millis(1000)).unwrap();
+        let exceeded = budget.consume(Duration::from_secs(1)).unwrap_err();
+        assert_eq!(exceeded, BudgetExceeded { requested: Duration::from_secs(1), available: Duration::from_millis(500) });
+        assert_eq!(exceeded.to_string(), "time budget exceeded: 1000ms requested, 500ms available");
+        assert_eq!(budget.available(), Duration::from_millis(500));
+
+        let forever = TimeBudget::new(Duration::MAX, start.clone());
+        assert_eq!(forever.deadline(), System::far_future());
+        assert!(forever.remaining(&start).is_some());
+
+        assert!(TimeBudget::until(start.clone(), start.add_seconds(5)).is_exhausted());
+        assert_eq!(TimeBudget::until(start.add_seconds(5), start.clone()).available(), Duration::from_secs(5));
+
+        // crossing a process boundary keeps only the deadline
+        let budget = TimeBudget::new(Duration::from_secs(30), start.clone());
+        let header = serde_json::to_string(&budget).unwrap();
+        assert_eq!(serde_json::from_str::<System>(&header).unwrap(), budget.deadline());
+        #[cfg(feature = "testing")]
+        {
+            let now = start.add_seconds(12);
+            let _guard = mock::set_mock_time(&now);
+            let received: TimeBudget<System> = serde_json::from_str(&header).unwrap();
+            assert_eq!(received.deadline(), budget.deadline());
+            assert_eq!(received.remaining(&now), budget.remaining(&now));
+            assert_eq!(received.available(), Duration::from_secs(18));
+            mock::advance_mock(20_000);
+            let late: TimeBudget<System> = serde_json::from_str(&header).unwrap();
+            assert!(late.is_exhausted());
+            assert_eq!(late.remaining(&System::now()), None);
+        }
+        #[cfg(all(feature = "testing", feature = "ntp-client"))]
+        {
+            // an Ntp budget starts from the local clock, not a query to a time server
+            let _guard = mock::set_mock_time(&start.add_seconds(12));
+            let ntp_header = serde_json::to_string(&TimeBudget::new(Duration::from_secs(30), Ntp::from_epoch(start.raw()))).unwrap();
+            let received: TimeBudget<Ntp> = serde_json::from_str(&ntp_header).unwrap();
+            assert_eq!(received.deadline().unix_ms(), budget.deadline().unix_ms());
+            assert_eq!(received.available(), Duration::from_secs(18));
+        }
+    }
+
+    #[test]
+    fn iso8601_profiles() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let x = at("2024-01-05 14:46:29.007 +0530");
+        assert_eq!(x.iso8601(), "2024-01-05 14:46:29.007");
+        assert_eq!(x.iso8601_strict(IsoProfile::Calendar), "2024-01-05T14:46:29.007+05:30");
+        assert_eq!(x.iso8601_strict(IsoProfile::Week), "2024-W01-5T14:46:29.007+05:30");
+        assert_eq!(x.iso8601_strict(IsoProfile::Ordinal), "2024-005T14:46:29.007+05:30");
+        assert_eq!(x.iso8601_strict(IsoProfile::default()), x.iso8601_strict(IsoProfile::Calendar));
+
+        // week years differ from calendar years around new year
+        let y = at("2021-01-01 00:00:00.000 -0330");
+        assert_eq!(y.iso8601_strict(IsoProfile::Calendar), "2021-01-01T00:00:00.000-03:30");
+        assert_eq!(y.iso8601_strict(IsoProfile::Week), "2020-W53-5T00:00:00.000-03:30");
+        assert_eq!(y.iso8601_strict(IsoProfile::Ordinal), "2021-001T00:00:00.000-03:30");
+        let z = at("2024-12-31 23:59:59.999 +0000");
+        assert_eq!(z.iso8601_strict(IsoProfile::Calendar), "2024-12-31T23:59:59.999Z");
+        assert_eq!(z.iso8601_strict(IsoProfile::Week), "2025-W01-2T23:59:59.999Z");
+        assert_eq!(z.iso8601_strict(IsoProfile::Ordinal), "2024-366T23:59:59.999Z");
+        assert_eq!(System::unix_epoch().iso8601_strict(IsoProfile::Calendar), "1970-01-01T00:00:00.000Z");
+        assert_eq!(ExtendedTime::from_ms_1601(OFFSET_1601 as i64 * 1000 - 1, 0).iso8601_strict(IsoProfile::Calendar), "1969-12-31T23:59:59.999Z");
+
+        for t in [x, y, z, System::windows_epoch(), System::far_future()] {
+            for profile in [IsoProfile::Calendar, IsoProfile::Week, IsoProfile::Ordinal] {
+                assert!(is_valid_iso8601(&t.iso8601_strict(profile), profile), "{}", t.iso8601_strict(profile));
+            }
+        }
+
+        for (input, profile) in [
+            ("2024-01-05", IsoProfile::Calendar),
+            ("2024-01-05T14:46", IsoProfile::Calendar),
+            ("2024-01-05T14:46:29,5-08", IsoProfile::Calendar),
+            ("2016-12-31T23:59:60Z", IsoProfile::Calendar),
+            ("2020-W53-7", IsoProfile::Week),
+            ("2024-366T00:00Z", IsoProfile::Ordinal),
+        ] {
+            assert_eq!(validate_iso8601(input, profile), Ok(()), "{}", input);
+        }
+        let position = |input: &str, profile| match validate_iso8601(input, profile) {
+            Err(ParseError::Invalid { position, .. }) => position,
+            other => panic!("{} gave {:?}", input, other),
+        };
+        assert_eq!(position("2024-1-5T9:00", IsoProfile::Calendar), 6);
+        assert_eq!(position("2024-01-05T9:00", IsoProfile::Calendar), 12);
+        assert_eq!(position("2024-01-05 14:46", IsoProfile::Calendar), 10);
+        assert_eq!(position("2024-02-30", IsoProfile::Calendar), 5);
+        assert_eq!(position("2024-01-05T14", IsoProfile::Calendar), 13);
+        assert_eq!(position("2024-01-05T14:46+0530", IsoProfile::Calendar), 19);
+        assert_eq!(position("2024-01-05T14:46:29.Z", IsoProfile::Calendar), 20);
+        assert_eq!(position("2024-01-05t14:46z", IsoProfile::Calendar), 10);
+        assert_eq!(position("2024-W01-5", IsoProfile::Calendar), 5);
+        assert_eq!(position("2021-W53-1", IsoProfile::Week), 5);
+        assert_eq!(position("2024-W01-8", IsoProfile::Week), 9);
+        assert_eq!(position("2023-366", IsoProfile::Ordinal), 5);
+        assert_eq!(position("20240105", IsoProfile::Calendar), 4);
+        assert_eq!(validate_iso8601("", IsoProfile::Week), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn injected_clocks() {
+        let at = "2024-01-05 14:46:29.123 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let fixed = FixedClock(at.clone());
+
+        // relative descriptions and log stamps depend on now - a fixed clock makes them deterministic
+        let event = at.add_days(-1);
+        assert_eq!(event.describe_relative(&fixed.now_system()), event.describe_relative(&at));
+        assert_eq!(at.add_seconds(-3).log_stamp_with(Some(&fixed.now_system()), LOG_STAMP_HORIZON), "2024-01-05T09:16:26.123Z (3.0s ago)");
+        let budget = TimeBudget::new(core::time::Duration::from_secs(2), fixed.now_system());
+        assert_eq!(budget.remaining(&fixed.now_ntp()), Some(core::time::Duration::from_secs(2)));
+        assert_eq!(fixed.now::<ExtendedTime>().unix_ms(), at.unix_ms());
+        assert_eq!(fixed.now_ntp().utc_offset(), 19800);
+
+        // a host whose clock runs 2.5s behind
+        let skewed = OffsetClock { inner: fixed.clone(), skew_ms: -2500 };
+        assert_eq!(skewed.now_system().unix_ms(), at.unix_ms() - 2500);
+        assert_eq!(skewed.now_system().utc_offset(), 19800);
+        let snapshot = ClockSnapshot::capture_with(|| skewed.now_system(), || Ok(fixed.now_ntp())).unwrap();
+        assert_eq!((snapshot.skew_ms, snapshot.rtt_ms), (2500, 0));
+        let ahead = OffsetClock { inner: &skewed, skew_ms: 4000 };
+        assert_eq!(ahead.now_ntp().unix_ms(), at.unix_ms() + 1500);
+        assert_eq!(OffsetClock { inner: FixedClock(System::windows_epoch()), skew_ms: -1 }.now_system(), System::windows_epoch());
+
+        // interchangeable behind a trait object
+        let clocks: Vec<Box<dyn Clock>> = vec![Box::new(fixed.clone()), Box::new(skewed), Box::new(RealClock)];
+        let readings: Vec<i64> = clocks.iter().map(|c| c.now_system().unix_ms()).collect();
+        assert_eq!(readings[..2], [at.unix_ms(), at.unix_ms() - 2500]);
+        assert!(readings[2] > at.unix_ms());
+        assert_eq!(clocks[0].now::<System>().unix_ms(), at.unix_ms());
+
+        #[cfg(feature = "testing")]
+        {
+            assert!(mock::MockClock.now_system().unix_ms() > at.unix_ms());
+            let _guard = mock::set_mock_time(&at);
+            assert_eq!(mock::MockClock.now_system(), at);
+            assert_eq!(RealClock.now_system(), at);
+            mock::advance_mock(500);
+            assert_eq!(mock::MockClock.now_ntp().unix_ms(), at.unix_ms() + 500);
+        }
+    }
+
+    #[test]
+    fn leap_day_anniversaries() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        let origin = at("2020-02-29 09:00:00 +0100");
+        let end = at("2026-01-01 00:00:00 +0100");
+        let years = |policy| origin.anniversaries_between(&origin, &end, policy).map(|t| t.strftime("%Y-%m-%d %H:%M %z")).collect::<Vec<_>>();
+        assert_eq!(years(LeapDayPolicy::Feb28), ["2021-02-28 09:00 +0100", "2022-02-28 09:00 +0100", "2023-02-28 09:00 +0100", "2024-02-29 09:00 +0100", "2025-02-28 09:00 +0100"]);
+        assert_eq!(years(LeapDayPolicy::Mar1), ["2021-03-01 09:00 +0100", "2022-03-01 09:00 +0100", "2023-03-01 09:00 +0100", "2024-02-29 09:00 +0100", "2025-03-01 09:00 +0100"]);
+        assert_eq!(years(LeapDayPolicy::SkipYear), ["2024-02-29 09:00 +0100"]);
+
+        for policy in [LeapDayPolicy::Feb28, LeapDayPolicy::Mar1, LeapDayPolicy::SkipYear] {
+            // each anniversary is the next one after the one before, and is an anniversary
+            let mut previous = origin.clone();
+            for t in origin.anniversaries_between(&origin, &end, policy) {
+                assert_eq!(origin.next_anniversary(&previous, policy), t);
+                assert!(t.is_anniversary_of(&origin, policy));
+                assert!(!t.add_days(1).is_anniversary_of(&origin, policy));
+                previous = t;
+            }
+            assert!(!origin.is_anniversary_of(&origin, policy));
+        }
+        // an origin exactly at `after` moves on a year, and one just before it doesn't
+        let renewal = at("2024-02-29 09:00:00 +0100");
+        assert_eq!(origin.next_anniversary(&renewal, LeapDayPolicy::Mar1), at("2025-03-01 09:00:00 +0100"));
+        assert_eq!(origin.next_anniversary(&renewal.add_seconds(-1), LeapDayPolicy::Mar1), renewal);
+        // SkipYear jumps the 8 years around 2100, which is not a leap year
+        assert_eq!(origin.next_anniversary(&at("2096-03-01 00:00:00 +0100"), LeapDayPolicy::SkipYear), at("2104-02-29 09:00:00 +0100"));
+        assert_eq!(origin.next_anniversary(&at("9997-01-01 00:00:00 +0000"), LeapDayPolicy::SkipYear), System::far_future());
+        // Feb 28 in a leap year is not the anniversary of a Feb 29 origin
+        assert!(!at("2024-02-28 12:00:00 +0100").is_anniversary_of(&origin, LeapDayPolicy::Feb28));
+        assert!(at("2023-02-28 23:59:00 +0100").is_anniversary_of(&origin, LeapDayPolicy::Feb28));
+        // read in the origin's offset, 23:30 UTC on Feb 27 is already Feb 28
+        assert!(at("2023-02-27 23:30:00 +0000").is_anniversary_of(&origin, LeapDayPolicy::Feb28));
+
+        // Dec 31 origins cross the year end
+        let new_years_eve = at("2023-12-31 23:30:00 +0530");
+        assert_eq!(new_years_eve.next_anniversary(&at("2024-12-31 23:45:00 +0530"), LeapDayPolicy::Feb28), at("2025-12-31 23:30:00 +0530"));
+        assert_eq!(new_years_eve.next_anniversary(&at("2024-12-31 23:15:00 +0530"), LeapDayPolicy::Feb28), at("2024-12-31 23:30:00 +0530"));
+        // 17:00 UTC is 22:30 in the origin's offset, before that year's anniversary
+        assert_eq!(new_years_eve.next_anniversary(&at("2024-12-31 17:00:00 +0000"), LeapDayPolicy::Feb28).unix(), at("2024-12-31 18:00:00 +0000").unix());
+        let eves: Vec<i32> = new_years_eve
+            .anniversaries_between(&at("2025-01-01 00:00:00 +0000"), &at("2028-01-01 00:00:00 +0530"), LeapDayPolicy::SkipYear)
+            .map(|t| t.wall_clock().year())
+            .collect();
+        assert_eq!(eves, [2025, 2026, 2027]);
+        assert!(at("2026-12-31 00:00:00 +0530").is_anniversary_of(&new_years_eve, LeapDayPolicy::Feb28));
+        assert!(!at("2026-12-31 20:00:00 +0000").is_anniversary_of(&new_years_eve, LeapDayPolicy::Feb28));
+        assert_eq!(new_years_eve.anniversaries_between(&end, &origin, LeapDayPolicy::Feb28).count(), 0);
+    }
+
+    #[test]
+    fn untrusted_formats() {
+        let x = "2024-01-05 14:46:29.123 +0530".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let checked = |f: &str| x.strftime_checked(&FormatString::parse_untrusted(f).unwrap());
+        for format in ["%Y-%m-%d %H:%M:%S", "%-d %B %Y", "%_H:%0M %P", "%a %b %e %T %Y", "%G-W%V-%u", "%s%.3f", "%c", "%D%t%F%n%R", "%j %U %W %C %g"] {
+            assert_eq!(checked(format), x.strftime(format), "{}", format);
+        }
+        assert_eq!(checked("%.f|%.6f|%3f|%Z|%z|%:z"), ".123|.123000|123|+05:30|+0530|+05:30");
+        assert_eq!(checked("50% off%"), "50% off%");
+        assert_eq!(FormatString::parse_untrusted("50% off%").unwrap().as_str(), "50%% off%%");
+        assert_eq!(checked("100%% %Y"), "100% 2024");
+        assert_eq!(checked("€%Y€"), "€2024€");
+        assert_eq!(checked(""), "");
+
+        let rejected = |f: &str| match FormatString::parse_untrusted(f) {
+            Err(FormatError::Rejected { position, reason, .. }) => (position, reason),
+            other => panic!("{:?} gave {:?}", f, other),
+        };
+        assert_eq!(rejected("%Y-%m-%Q"), (6, "a known specifier"));
+        assert_eq!(rejected("%99999999999d"), (0, "a specifier without a width"));
+        assert_eq!(rejected("%1f"), (0, "a specifier without a width"));
+        assert_eq!(rejected("%-_d"), (0, "a known specifier"));
+        assert_eq!(FormatString::parse_untrusted("%%%").unwrap().as_str(), "%%%%");
+        assert_eq!(rejected("%-"), (0, "a known specifier"));
+        assert_eq!(rejected("%0B"), (0, "a padding flag only before a numeric specifier"));
+        assert_eq!(rejected("%-%"), (0, "a padding flag only before a numeric specifier"));
+        assert_eq!(rejected("%::z"), (0, "a known specifier"));
+        assert_eq!(rejected("%#z"), (0, "a known specifier"));
+        assert_eq!(rejected("%.2f"), (0, "a known specifier"));
+        assert_eq!(rejected("%é"), (0, "a known specifier"));
+        assert_eq!(rejected("%+"), (0, "a known specifier"));
+        assert_eq!(rejected(&"%Y".repeat(129)), (MAX_UNTRUSTED_FORMAT_LEN, "at most 256 bytes"));
+        assert!(FormatString::parse_untrusted(&"%Y".repeat(128)).is_ok());
+        assert_eq!(
+            FormatString::parse_untrusted("%Q").unwrap_err().to_string(),
+            r#"rejected format string "%Q" at position 0: a known specifier"#
+        );
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn untrusted_formats_never_panic(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..300)) {
+            let format = String::from_utf8_lossy(&bytes);
+            if let Ok(fmt) = FormatString::parse_untrusted(&format) {
+                for t in [System::windows_epoch(), System::far_future(), System::unix_epoch().change_tz_seconds(-43200)] {
+                    assert!(t.strftime_checked(&fmt).len() <= fmt.as_str().len() * 40);
+                }
+                assert_eq!(System::from_epoch(1 << 60).strftime_checked(&fmt), "");
+            }
+        }
+
+        #[test]
+        fn untrusted_specifier_soup_never_panics(format in "([%\\-_0.:#3-9a-zA-Z ]|%%){0,60}") {
+            if let Ok(fmt) = FormatString::parse_untrusted(&format) {
+                let x = ExtendedTime::from_ms_1601(-62_135_596_800_000, 3600);
+                assert!(x.strftime_checked(&fmt).len() <= fmt.as_str().len() * 40);
+                assert!(System::far_future().strftime_checked(&fmt).len() <= fmt.as_str().len() * 40);
+            }
+        }
+    }
+
+    #[test]
+    fn rollover_instants() {
+        use epoch::TimestampKind;
+        let rollovers: Vec<String> = TimestampKind::ALL.iter().map(|k| format!("{} {}", k, k.next_rollover().pretty())).collect();
+        assert_eq!(
+            rollovers,
+            [
+                "ntp-era-0 2036-02-07 06:28:15",
+                "i32-unix 2038-01-19 03:14:07",
+                "u32-unix 2106-02-07 06:28:15",
+                "dos-date 2107-12-31 23:59:59",
+                "filetime-64 +30828-09-14 02:48:05",
+            ]
+        );
+        assert_eq!(TimestampKind::I32Unix.next_rollover().unix(), i32::MAX as i64);
+        assert_eq!(TimestampKind::U32Unix.next_rollover().unix(), u32::MAX as i64);
+        assert_eq!(TimestampKind::NtpEra0.next_rollover().unix() + REF_TIME_1970 as i64, u32::MAX as i64);
+        assert!(TimestampKind::FileTime64.next_rollover().try_windows_ns().is_ok());
+        assert!(TimestampKind::FileTime64.next_rollover().add_seconds(1).try_windows_ns().is_err());
+
+        for kind in TimestampKind::ALL {
+            let last = kind.next_rollover();
+            assert!(last.fits_in(kind), "{}", kind);
+            assert!(System::from_epoch(last.raw() + 999).fits_in(kind), "{}", kind);
+            assert!(!last.add_seconds(1).fits_in(kind), "{}", kind);
+            let (first, _) = kind.unix_range();
+            assert!(System::from_epoch(((first + OFFSET_1601 as i64) * 1000) as u64).fits_in(kind), "{}", kind);
+            if first > -(OFFSET_1601 as i64) {
+                assert!(!System::from_epoch(((first - 1 + OFFSET_1601 as i64) * 1000) as u64).fits_in(kind), "{}", kind);
+            }
+        }
+
+        let x = 2147483647u32.unix::<System>();
+        assert!(x.y2038_safe() && !x.add_seconds(1).y2038_safe());
+        assert!(ExtendedTime::from_ms_1601((OFFSET_1601 as i64 - 2147483648) * 1000, 0).y2038_safe());
+        assert!(!ExtendedTime::from_ms_1601((OFFSET_1601 as i64 - 2147483649) * 1000, 0).y2038_safe());
+        assert!(!System::unix_epoch().add_seconds(-1).fits_in(TimestampKind::U32Unix));
+        assert!(System::unix_epoch().fits_in(TimestampKind::NtpEra0));
+        assert!(!System::unix_epoch().fits_in(TimestampKind::DosDate));
+        assert!(!System::from_epoch(1 << 60).fits_in(TimestampKind::DosDate));
+        assert!(!ExtendedTime::from_ms_1601(i64::MAX, 0).fits_in(TimestampKind::DosDate));
+
+        // DOS dates hold local time, so the offset moves the limits
+        let eve = "2107-12-31 23:00:00 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        assert!(eve.fits_in(TimestampKind::DosDate));
+        assert!(!eve.change_tz_seconds(3600).fits_in(TimestampKind::DosDate));
+        assert!(eve.add_seconds(3600).change_tz_seconds(-3600).fits_in(TimestampKind::DosDate));
+    }
+
+    #[test]
+    fn nice_ticks() {
+        use core::time::Duration;
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let labels = |ticks: Vec<System>| ticks.iter().map(|t| t.strftime("%d %H:%M")).collect::<Vec<_>>();
+
+        // a 47 minute span with about 6 ticks
+        let start = at("2024-01-05 14:03:17.250 +0530");
+        let span = TimeSpan::new(start.clone(), start.add_minutes(47));
+        assert_eq!(labels(tick_marks(&span, 6)), ["05 14:10", "05 14:20", "05 14:30", "05 14:40", "05 14:50"]);
+        assert!(tick_marks(&span, 6).iter().all(|t| t.utc_offset() == 19800));
+        assert_eq!(labels(tick_marks(&span, 4)), ["05 14:15", "05 14:30", "05 14:45"]);
+        assert_eq!(labels(tick_marks(&span, 3)), ["05 14:30"]);
+        assert_eq!(tick_marks(&span, 0), Vec::<System>::new());
+
+        // a 3 day span gets 12 hour ticks at local midnight and noon
+        let start = at("2024-01-05 09:30:00.000 -0500");
+        let ticks = tick_marks(&TimeSpan::new(start.clone(), start.add_days(3)), 6);
+        assert_eq!(labels(ticks.clone()), ["05 12:00", "06 00:00", "06 12:00", "07 00:00", "07 12:00", "08 00:00"]);
+        assert_eq!(ticks[1].unix(), at("2024-01-06 05:00:00.000 +0000").unix());
+
+        // ends on a boundary are included, and long spans step in whole weeks from Mondays
+        let day = at("2024-01-01 00:00:00.000 +0000");
+        assert_eq!(tick_marks(&TimeSpan::new(day.clone(), day.add_hours(1)), 2).len(), 3);
+        let weeks = tick_marks(&TimeSpan::new(day.clone(), day.add_days(365)), 4);
+        assert!(weeks.len() >= 4 && weeks.len() <= 5);
+        assert!(weeks.windows(2).all(|w| w[1].raw() - w[0].raw() == 14 * 7 * 86_400_000));
+        assert!(weeks.iter().all(|t| t.wall_clock().weekday() == Weekday::Mon && t.wall_clock().time() == NaiveTime::MIN));
+        assert!(tick_marks(&TimeSpan::new(System::windows_epoch(), System::far_future()), 10).len() <= 11);
+
+        let x = at("2024-01-05 14:47:29.500 +0530");
+        for (target, floor, ceil, step) in [
+            (Duration::from_millis(300), "14:47:29.000", "14:47:30.000", 1),
+            (Duration::from_secs(7), "14:47:20.000", "14:47:30.000", 10),
+            (Duration::from_secs(7 * 60), "14:40:00.000", "14:50:00.000", 600),
+            (Duration::from_secs(3600), "14:00:00.000", "15:00:00.000", 3600),
+            (Duration::from_secs(4 * 3600), "12:00:00.000", "18:00:00.000", 21600),
+            (Duration::from_secs(13 * 3600), "00:00:00.000", "00:00:00.000", 86400),
+        ] {
+            let (f, s) = x.nice_floor(target);
+            let (c, s2) = x.nice_ceil(target);
+            assert_eq!((f.strftime("%H:%M:%S%.3f"), c.strftime("%H:%M:%S%.3f"), s.as_secs(), s2), (floor.to_string(), ceil.to_string(), step, s));
+            assert_eq!(f.utc_offset(), 19800);
+        }
+        assert_eq!(x.nice_floor(Duration::from_secs(30 * 86400)).0.pretty(), "2024-01-01 00:00:00");
+        assert_eq!(x.nice_ceil(Duration::from_secs(30 * 86400)), (at("2024-01-08 00:00:00.000 +0530"), Duration::from_secs(7 * 86400)));
+        let boundary = at("2024-01-05 14:40:00.000 +0530");
+        assert_eq!(boundary.nice_floor(Duration::from_secs(600)).0, boundary);
+        assert_eq!(boundary.nice_ceil(Duration::from_secs(600)).0, boundary);
+        assert_eq!(nice_step(Duration::ZERO), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn ntp_cache_files() {
+        let path = std::env::temp_dir().join(format!("thetime-test-{}.ntpcache", std::process::id()));
+        let synced = "2024-01-05 14:46:29.123 +0530".parse_time::<Ntp>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let local = synced.cast::<System>().add_seconds(-90);
+        NtpCache::save_with(&path, &synced, &local).unwrap();
+
+        let estimate = NtpCache::load_with(&path, &local.add_seconds(30)).unwrap();
+        assert_eq!((estimate.time.unix_ms(), estimate.time.utc_offset()), (synced.unix_ms() + 30_000, 19800));
+        assert_eq!((estimate.elapsed_ms, estimate.stale), (Some(30_000), false));
+        assert_eq!((estimate.last_sync.unix_ms(), estimate.last_sync.server()), (synced.unix_ms(), "strptime".to_string()));
+        // a clock reset by a reboot never takes the estimate below the saved time
+        let estimate = NtpCache::load_with(&path, &System::unix_epoch()).unwrap();
+        assert_eq!((estimate.time.unix_ms(), estimate.elapsed_ms, estimate.stale), (synced.unix_ms(), None, true));
+        assert!(NtpCache::load_estimate(&path).unwrap() >= synced.cast::<System>());
+
+        NtpCache::save(&path, &synced).unwrap();
+        assert!(NtpCache::load(&path).unwrap().elapsed_ms.unwrap() < 60_000);
+
+        // every single byte flip, and every truncation, is caught
+        let good = std::fs::read(&path).unwrap();
+        for i in 0..good.len() {
+            let mut bad = good.clone();
+            bad[i] ^= 0x40;
+            std::fs::write(&path, &bad).unwrap();
+            assert_eq!(NtpCache::load(&path), None, "flipped byte {}", i);
+            std::fs::write(&path, &good[..i]).unwrap();
+            assert_eq!(NtpCache::load(&path), None, "truncated to {}", i);
+        }
+        std::fs::write(&path, b"not a cache file at all, but long enough to have a header").unwrap();
+        assert_eq!(NtpCache::load_estimate(&path), None);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(NtpCache::load_estimate(&path), None);
+        assert!(NtpCache::save(std::env::temp_dir().join("thetime-missing-dir").join("x.ntpcache"), &synced).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "timezones")]
+    fn tz_name_preferences() {
+        let x = 1704465989u32.unix::<System>();
+        let paris = TzNamePreference::new(&["cet", "CEST"]);
+        assert_eq!(paris.name_for(Tz::BstCet), "cet");
+        assert_eq!(paris.name_for(Tz::CestEet), "CEST");
+        // not covered, so the compound name
+        assert_eq!(paris.name_for(Tz::AestChst), "AEST/CHST");
+        assert_eq!(TzNamePreference::new(&["NOPE"]).name_for(Tz::BstCet), "BST/CET");
+
+        // earlier names win, even when a later one also fits
+        let london = TzNamePreference::new(&["BST", "CET", "GMT"]);
+        assert_eq!(Tz::BstCet.name_with(&london), "BST");
+        assert_eq!(Tz::UtcWet.name_with(&london), "GMT");
+
+        let zoned = x.in_zone(Tz::BstCet);
+        assert_eq!(zoned.strftime_with_tz_names("%Y-%m-%d %H:%M %Z (%z)", &london), "2024-01-05 15:46 BST (+0100)");
+        assert_eq!(x.in_zone(Tz::Pst).strftime_with_tz_names("%H:%M %Z", &london), "06:46 PST");
+        // nothing set process-wide in this binary
+        assert_eq!(zoned.strftime("%H:%M %Z"), "15:46 BST/CET");
+    }
+
+    #[test]
+    fn js_date_strings() {
+        let local = "Fri Jan 05 2024 14:46:29 GMT+0530 (India Standard Time)".strp_js_date::<System>().unwrap();
+        assert_eq!(local.utc_offset(), 19800);
+        assert_eq!(local.strftime("%Y-%m-%d %H:%M:%S"), "2024-01-05 14:46:29");
+        assert_eq!(local.to_utc().pretty(), "2024-01-05 09:16:29");
+
+        let utc = "Fri, 05 Jan 2024 09:16:29 GMT".strp_js_date::<Ntp>().unwrap();
+        assert_eq!(utc.utc_offset(), 0);
+        assert_eq!(utc.unix(), local.unix());
+
+        // no parenthetical, a western offset, and a weekday that doesn't match (or isn't one)
+        let west = "Thu Jan 04 2024 22:46:29 GMT-0800".strp_js_date::<System>().unwrap();
+        assert_eq!((west.utc_offset(), west.to_utc().pretty()), (-28800, "2024-01-05 06:46:29".to_string()));
+        assert_eq!("Mon Jan 05 2024 14:46:29 GMT+0000".strp_js_date::<System>().unwrap().pretty(), "2024-01-05 14:46:29");
+        assert_eq!("Fryday, 05 Jan 2024 09:16:29 GMT".strp_js_date::<System>().unwrap().unix(), local.unix());
+
+        for bad in ["", "Fri Foo 05 2024 14:46:29 GMT", "Fri Jan 05 2024 14:46:29 EST", "Fri Jan 05 2024 14:46:29 GMT+0530 (IST", "Fri Jan 05 2024 14:46:29 GMT trailing"] {
+            assert!(bad.strp_js_date::<System>().is_err(), "{bad}");
+        }
+        assert_eq!(
+            "Fri Jan 05 2024 25:46:29 GMT".strp_js_date::<System>(),
+            Err(ParseError::invalid("Fri Jan 05 2024 25:46:29 GMT", 16, "hour 00-23"))
+        );
+    }
+
+    #[test]
+    fn weekday_checked_parsing() {
+        // 2003-07-01 was a Tuesday
+        let strict = ParseOptions::strict();
+        let x = "Tue, 1 Jul 2003 10:52:37 +0200".strp_rfc2822_with::<System>(&strict).unwrap();
+        assert_eq!((x.utc_offset(), x.to_utc().pretty()), (7200, "2003-07-01 08:52:37".to_string()));
+        assert_eq!(
+            "Wed, 01 Jul 2003 10:52:37 +0200".strp_rfc2822_with::<System>(&strict),
+            Err(ParseError::WeekdayMismatch { named: Weekday::Wed, computed: Weekday::Tue })
+        );
+        assert_eq!(
+            "Wed, 01 Jul 2003 10:52:37 +0200".strp_rfc2822::<System>().unwrap().unix(),
+            x.unix()
+        );
+        // full names count, in any case, but not made up ones
+        assert!("tuesday, 01 Jul 2003 10:52:37 +0200".strp_rfc2822_with::<System>(&strict).is_ok());
+        assert_eq!(
+            "Tuz, 01 Jul 2003 10:52:37 +0200".strp_rfc2822_with::<System>(&strict),
+            Err(ParseError::invalid("Tuz, 01 Jul 2003 10:52:37 +0200", 0, "a weekday name"))
+        );
+        assert!("Fryday, 05 Jan 2024 09:16:29 GMT".strp_js_date_with::<System>(&strict).is_err());
+        assert!(http::parse_http_date_with::<System>("Tue, 01 Jul 2003 08:52:37 GMT", &strict).is_ok());
+        assert!(matches!(
+            http::parse_http_date_with::<System>("Wednesday, 01-Jul-03 08:52:37 GMT", &strict),
+            Err(ParseError::WeekdayMismatch { .. })
+        ));
+
+        // the obsolete RFC 2822 forms
+        for (input, unix) in [
+            ("1 Jul 2003 10:52:37 +0200", 1057049557),
+            ("01 Jul 03 08:52:37 GMT", 1057049557),
+            ("01 Jul 103 01:52 PDT", 1057049520),
+            ("Tue, 01 Jul 2003 03:52:37 -0500 (CDT)", 1057049557),
+        ] {
+            assert_eq!(input.strp_rfc2822::<System>().unwrap().unix(), unix, "{input}");
+        }
+        for bad in ["", "Tue 01 Jul 2003 10:52:37 +0200", "Tue, 01 Jul 2003 10:52:37 XYZ", "Tue, 01 Jul 2003 10:52:37 +0200 trailing"] {
+            assert!(bad.strp_rfc2822::<System>().is_err(), "{bad}");
+        }
+
+        // a day past the end of the month rolls over only when lenient
+        let feb30 = "Fri, 30 Feb 2024 12:00:00 +0000";
+        let rolled = feb30.strp_rfc2822_with::<System>(&ParseOptions::lenient()).unwrap();
+        assert_eq!(rolled.pretty(), "2024-03-01 12:00:00");
+        assert_eq!(
+            feb30.strp_rfc2822_with::<System>(&ParseOptions::default()),
+            Err(ParseError::invalid(feb30, 5, "a day that exists in the month"))
+        );
+        assert!(feb30.strp_rfc2822_with::<System>(&strict).is_err());
+        assert_eq!(
+            "Fri Jan 31 2025 00:00:00 GMT+0000".strp_js_date_with::<System>(&ParseOptions::lenient()).unwrap().pretty(),
+            "2025-01-31 00:00:00"
+        );
+        assert_eq!(
+            http::parse_http_date_with::<System>("Sun, 31 Nov 2024 00:00:00 GMT", &ParseOptions::lenient()).unwrap().pretty(),
+            "2024-12-01 00:00:00"
+        );
+    }
+
+    #[test]
+    fn hybrid_logical_clocks() {
+        // a wall clock the test moves by hand
+        struct HandClock(std::cell::Cell<u64>);
+        impl Clock for HandClock {
+            fn now_system(&self) -> System {
+                System::from_epoch(self.0.get())
+            }
+        }
+        let start = 1704465989u32.unix::<System>().raw();
+        let (wall_a, wall_b) = (HandClock(std::cell::Cell::new(start)), HandClock(std::cell::Cell::new(start)));
+        let mut a = Hlc::with_clock(1, &wall_a);
+        let mut b = Hlc::with_clock(2, &wall_b);
+
+        // the clock advancing, stalling, then stepping back
+        let first = a.now();
+        assert_eq!((first.wall_ms, first.logical, first.node), (start, 0, 1));
+        let stalled = a.now();
+        assert_eq!((stalled.wall_ms, stalled.logical), (start, 1));
+        wall_a.0.set(start - 5000);
+        let behind = a.now();
+        assert_eq!((behind.wall_ms, behind.logical), (start, 2));
+        wall_a.0.set(start + 10);
+        let caught_up = a.now();
+        assert_eq!((caught_up.wall_ms, caught_up.logical), (start + 10, 0));
+        assert!(first < stalled && stalled < behind && behind < caught_up);
+
+        // b's clock lags a's: receiving moves b up to a's time, and b's replies sort after what it received
+        wall_b.0.set(start - 60_000);
+        let received = b.update(caught_up).unwrap();
+        assert_eq!((received.wall_ms, received.logical, received.node), (start + 10, 1, 2));
+        let reply = b.now();
+        assert!(reply > received && received > caught_up);
+        // a is ahead in wall time but merges b's higher counter
+        let merged = a.update(reply).unwrap();
+        assert_eq!((merged.wall_ms, merged.logical, merged.node), (start + 10, 3, 1));
+
+        // a message from the future: a takes its wall time and carries on counting from it
+        let future = HlcTimestamp { wall_ms: start + 90_000, logical: 7, node: 3 };
+        assert_eq!(a.update(future).unwrap(), HlcTimestamp { wall_ms: start + 90_000, logical: 8, node: 1 });
+        assert_eq!(a.now(), HlcTimestamp { wall_ms: start + 90_000, logical: 9, node: 1 });
+        // and once the wall clock passes it, the counter resets
+        wall_a.0.set(start + 90_001);
+        assert_eq!(a.now(), HlcTimestamp { wall_ms: start + 90_001, logical: 0, node: 1 });
+
+        // a long exchange: every event sorts after the last on its node, and every receive after what was received
+        let mut last_a = a.last();
+        let mut last_b = b.last();
+        for i in 0..200u64 {
+            wall_a.0.set(start + 90_000 + (i * 7919) % 50);
+            wall_b.0.set(start + 89_990 + (i * 104729) % 40);
+            let sent = a.now();
+            assert!(sent > last_a);
+            let got = b.update(sent).unwrap();
+            assert!(got > sent && got > last_b);
+            let back = b.now();
+            let merged = a.update(back).unwrap();
+            assert!(merged > back && merged > sent);
+            (last_a, last_b) = (merged, back);
+        }
+
+        // a full counter moves on to the next millisecond
+        let full = HlcTimestamp { wall_ms: start + 90_100, logical: u32::MAX, node: 2 };
+        assert_eq!(a.update(full).unwrap(), HlcTimestamp { wall_ms: start + 90_101, logical: 0, node: 1 });
+
+        // timestamps too far ahead are refused, leaving the clock as it was
+        let before = a.last();
+        let wild = HlcTimestamp { wall_ms: u64::MAX, logical: u32::MAX, node: 2 };
+        let limit = System::far_future().raw();
+        assert_eq!(a.update(wild), Err(HlcDrift { received: wild, limit }));
+        assert_eq!(Hlc::new(1).update(wild), Err(HlcDrift { received: wild, limit }));
+        assert_eq!(a.last(), before);
+        let at_limit = HlcTimestamp { wall_ms: limit, logical: u32::MAX, node: 2 };
+        assert_eq!(Hlc::with_clock(1, &wall_b).update(at_limit), Ok(HlcTimestamp { wall_ms: limit + 1, logical: 0, node: 1 }));
+        let mut strict = Hlc::with_clock(1, &wall_a).max_drift(core::time::Duration::from_secs(1));
+        let ahead = |ms: u64| HlcTimestamp { wall_ms: wall_a.0.get() + ms, logical: 0, node: 2 };
+        assert!(strict.update(ahead(1000)).is_ok());
+        assert_eq!(strict.update(ahead(1001)), Err(HlcDrift { received: ahead(1001), limit: wall_a.0.get() + 1000 }));
+        assert_eq!(strict.update(ahead(1001)).unwrap_err().to_string(), format!("HLC timestamp {} is ahead of the latest acceptable wall time {:016x}", ahead(1001), wall_a.0.get() + 1000));
+
+        // the encodings sort as the timestamps do
+        let mut stamps = vec![first, stalled, behind, caught_up, received, reply, merged, future, full, HlcTimestamp::default()];
+        stamps.sort();
+        let strings: Vec<String> = stamps.iter().map(|t| t.to_string()).collect();
+        let bytes: Vec<[u8; HLC_TIMESTAMP_LEN]> = stamps.iter().map(|t| t.to_bytes()).collect();
+        assert!(strings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(bytes.windows(2).all(|w| w[0] <= w[1]));
+        for (t, (s, b)) in stamps.iter().zip(strings.iter().zip(bytes)) {
+            assert_eq!(s.parse::<HlcTimestamp>().unwrap(), *t);
+            assert_eq!(HlcTimestamp::from_bytes(b), *t);
+        }
+        assert_eq!(System::from(caught_up).unix_ms(), 1704465989010);
+        assert_eq!(caught_up.to_system().utc_offset(), 0);
+
+        for (bad, pos) in [("00000c240acd6588-00000003", 25), ("00000c240acd6588_00000003-0007", 16), ("00000c240acd658-00000003-0007", 0), ("00000c240acd6588-00000003-0007x", 30), ("00000c240acd6588-0000000g-0007", 17)] {
+            assert!(matches!(bad.parse::<HlcTimestamp>(), Err(ParseError::Invalid { position, .. }) if position == pos), "{bad}");
+        }
+        assert_eq!("".parse::<HlcTimestamp>(), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn business_shorthand() {
+        use chrono::NaiveTime;
+        let conventions = BusinessConventions {
+            close_of_business: NaiveTime::from_hms_opt(17, 30, 0).unwrap(),
+            utc_offset: 3600,
+            ..BusinessConventions::default()
+        };
+        // Wednesday 2024-04-17, 14:00 on the conventions' wall clock
+        let now = "2024-04-17 14:00:00 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        let resolve = |s: &str| parse_business_shorthand(s, &now, &conventions).map(|t| t.strftime("%a %Y-%m-%d %H:%M %z"));
+        for (input, expected) in [
+            ("EOD", "Wed 2024-04-17 17:30 +0100"),
+            ("cob", "Wed 2024-04-17 17:30 +0100"),
+            ("COB Friday", "Fri 2024-04-19 17:30 +0100"),
+            ("EOD fri", "Fri 2024-04-19 17:30 +0100"),
+            ("COB Wednesday", "Wed 2024-04-17 17:30 +0100"),
+            ("COB Monday", "Mon 2024-04-22 17:30 +0100"),
+            ("  EOW ", "Fri 2024-04-19 17:30 +0100"),
+            ("EOM", "Tue 2024-04-30 17:30 +0100"),
+            ("EOQ", "Sun 2024-06-30 17:30 +0100"),
+            ("EOY", "Tue 2024-12-31 17:30 +0100"),
+        ] {
+            assert_eq!(resolve(input).as_deref(), Ok(expected), "{input}");
+        }
+        assert_eq!(parse_business_shorthand("EOD", &now, &conventions).unwrap().unix(), now.unix() + 3 * 3600 + 1800);
+
+        // the day is read on the conventions' wall clock: 23:30 UTC on Wednesday is already Thursday at +0100
+        let late = "2024-04-17 23:30:00 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(parse_business_shorthand("EOD", &late, &conventions).unwrap().strftime("%a %d %H:%M"), "Thu 18 17:30");
+        // at the weekend, EOW is the Friday just gone
+        let saturday = now.add_days(3);
+        assert_eq!(parse_business_shorthand("EOW", &saturday, &conventions).unwrap().strftime("%a %d"), "Fri 19");
+        // a Sunday to Thursday week
+        let gulf = BusinessConventions { week_start: Weekday::Sun, ..conventions };
+        assert_eq!(gulf.last_working_day(), Weekday::Thu);
+        assert_eq!(parse_business_shorthand("EOW", &now, &gulf).unwrap().strftime("%a %d"), "Thu 18");
+        // December's quarter ends with the year
+        let december = now.add_days(230);
+        assert_eq!(parse_business_shorthand("EOQ", &december, &conventions), parse_business_shorthand("EOY", &december, &conventions));
+
+        assert_eq!(resolve(""), Err(ParseError::Empty));
+        assert_eq!(resolve("EOB"), Err(ParseError::invalid("EOB", 0, "EOD, COB, EOW, EOM, EOQ or EOY")));
+        assert_eq!(resolve("COB Fryday"), Err(ParseError::invalid("COB Fryday", 4, "a weekday name")));
+        assert_eq!(resolve("EOM Friday"), Err(ParseError::invalid("EOM Friday", 4, "end of input")));
+    }
+
+    #[test]
+    fn month_roll_policies() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let roll = |t: &System, n: i32, policy: MonthRollPolicy| t.add_months_with(n, policy).map(|t| t.strftime("%Y-%m-%d %H:%M %z"));
+
+        // the classic end-of-month chain, against plain clamping
+        let jan31 = at("2024-01-31 17:00:00.000 +0100");
+        let mut eom = jan31.clone();
+        let mut clamp = jan31.clone();
+        let (mut eom_chain, mut clamp_chain) = (vec![], vec![]);
+        for _ in 0..4 {
+            eom = eom.add_months_with(1, MonthRollPolicy::EndOfMonth).unwrap();
+            clamp = clamp.add_months_with(1, MonthRollPolicy::Clamp).unwrap();
+            eom_chain.push(eom.strftime("%m-%d"));
+            clamp_chain.push(clamp.strftime("%m-%d"));
+        }
+        assert_eq!(eom_chain, ["02-29", "03-31", "04-30", "05-31"]);
+        assert_eq!(clamp_chain, ["02-29", "03-29", "04-29", "05-29"]);
+        assert_eq!(roll(&jan31, 1, MonthRollPolicy::EndOfMonth).unwrap(), "2024-02-29 17:00 +0100");
+        // not the end of the month, so it only clamps
+        assert_eq!(roll(&at("2024-01-30 00:00:00.000 +0000"), 1, MonthRollPolicy::EndOfMonth).unwrap(), "2024-02-29 00:00 +0000");
+        assert_eq!(roll(&at("2023-02-28 00:00:00.000 +0000"), 12, MonthRollPolicy::EndOfMonth).unwrap(), "2024-02-29 00:00 +0000");
+        assert_eq!(roll(&at("2024-03-31 00:00:00.000 +0000"), -13, MonthRollPolicy::Clamp).unwrap(), "2023-02-28 00:00 +0000");
+
+        assert_eq!(roll(&jan31, 1, MonthRollPolicy::Reject), Err(MonthRollError::DayMissing { year: 2024, month: 2, day: 31 }));
+        assert_eq!(roll(&jan31, 2, MonthRollPolicy::Reject).unwrap(), "2024-03-31 17:00 +0100");
+        assert_eq!(roll(&jan31, -12 * 500, MonthRollPolicy::Clamp), Err(MonthRollError::OutOfRange));
+
+        // modified following: forward, unless that crosses into the next month
+        let cal = BusinessCalendar::new().holiday(2024, 12, 25).holiday(2024, 12, 26);
+        let adjust = |s: &str| at(s).modified_following(&cal).strftime("%Y-%m-%d %a");
+        assert_eq!(adjust("2024-12-25 09:00:00.000 +0000"), "2024-12-27 Fri");
+        assert_eq!(adjust("2024-11-30 09:00:00.000 +0000"), "2024-11-29 Fri");
+        assert_eq!(adjust("2024-11-29 09:00:00.000 +0000"), "2024-11-29 Fri");
+        let none = BusinessCalendar::new().weekend(&[Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun]);
+        assert_eq!(jan31.modified_following(&none), jan31);
+    }
+
+    #[test]
+    fn time_of_day_ordering() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let mut events: Vec<ByTimeOfDay<System>> = ["2024-01-05 18:00:00.000 +0000", "2024-03-01 07:30:00.000 +0000", "2023-12-31 12:00:00.500 +0000"]
+            .into_iter()
+            .map(|s| ByTimeOfDay(at(s)))
+            .collect();
+        events.sort();
+        let order: Vec<String> = events.iter().map(|e| e.0.strftime("%Y-%m-%d %H:%M")).collect();
+        assert_eq!(order, ["2024-03-01 07:30", "2023-12-31 12:00", "2024-01-05 18:00"]);
+
+        // the key is read in each time's own offset, and equal keys fall back to the instant
+        let paris = at("2024-01-05 07:30:00.000 +0100");
+        assert_eq!(paris.time_of_day_key(), at("2024-03-01 07:30:00.000 +0000").time_of_day_key());
+        assert!(ByTimeOfDay(paris.clone()) < ByTimeOfDay(at("2024-03-01 07:30:00.000 +0000")));
+        assert_eq!(ByTimeOfDay(paris.clone()), ByTimeOfDay(paris.change_tz_seconds(3600)));
+
+        let night = ((22, 0, 0), (6, 0, 0));
+        assert!(at("2024-01-05 23:30:00.000 +0000").is_between_times(night.0, night.1));
+        assert!(at("2024-01-05 05:59:59.999 +0000").is_between_times(night.0, night.1));
+        assert!(at("2024-01-05 22:00:00.000 +0000").is_between_times(night.0, night.1));
+        assert!(!at("2024-01-05 06:00:00.000 +0000").is_between_times(night.0, night.1));
+        assert!(!at("2024-01-05 12:00:00.000 +0000").is_between_times(night.0, night.1));
+        assert!(at("2024-01-05 12:00:00.000 +0000").is_between_times((9, 0, 0), (17, 0, 0)));
+        assert!(!at("2024-01-05 12:00:00.000 +0000").is_between_times((12, 0, 0), (12, 0, 0)));
+    }
+
+    #[test]
+    fn libc_compat_conventions() {
+        use crate::libc_compat::*;
+        let epoch = gmtime_(0);
+        assert_eq!(epoch, TmLike { tm_mday: 1, tm_year: 70, tm_wday: 4, ..Default::default() });
+
+        let tm = gmtime_(1704465989);
+        assert_eq!((tm.tm_year, tm.tm_mon, tm.tm_mday), (124, 0, 5));
+        assert_eq!((tm.tm_hour, tm.tm_min, tm.tm_sec, tm.tm_wday, tm.tm_yday), (14, 46, 29, 5, 4));
+        assert_eq!(timegm_(&tm), 1704465989);
+        assert_eq!(strftime_("%Y-%m-%d %H:%M:%S %a %j", &tm), "2024-01-05 14:46:29 Fri 005");
+
+        // mktime style normalization: month 13 of 2023, day 0, 25 hours, -1 seconds
+        let odd = TmLike { tm_year: 123, tm_mon: 13, tm_mday: 0, tm_hour: 25, tm_sec: -1, ..Default::default() };
+        assert_eq!(timegm_(&odd), 1706659200 + 86400 + 3599);
+        assert_eq!(strftime_("%Y-%m-%d %H:%M:%S", &odd), "2024-02-01 00:59:59");
+        let negative_month = TmLike { tm_year: 124, tm_mon: -1, tm_mday: 31, ..Default::default() };
+        assert_eq!(gmtime_(timegm_(&negative_month)).tm_year, 123);
+        assert_eq!(gmtime_(timegm_(&negative_month)).tm_mon, 11);
+        assert_eq!(mktime_(&odd) - mktime_(&localtime_(mktime_(&odd))), 0);
+        for t in [0, 1704465989, 1719792000, -86400] {
+            assert_eq!(mktime_(&localtime_(t)), t);
+            assert_eq!(timegm_(&gmtime_(t)), t);
+        }
+
+        let (parsed, used) = strptime_("05/01/2024 14:46 +0530!", "%d/%m/%Y %H:%M %z").unwrap();
+        assert_eq!(used, 22);
+        assert_eq!(parsed, TmLike { tm_min: 46, tm_hour: 14, tm_mday: 5, tm_mon: 0, tm_year: 124, tm_wday: 5, tm_yday: 4, tm_isdst: 0, tm_sec: 0, tm_gmtoff: 19800 });
+        assert_eq!(strptime_("14:46", "%H:%M").unwrap().0, TmLike { tm_hour: 14, tm_min: 46, ..Default::default() });
+        assert!(strptime_("nope", "%H:%M").is_none());
+        assert!((time_() - System::now().unix()).abs() <= 1);
+    }
+
+    #[test]
+    fn missed_schedule_catch_up() {
+        use core::time::Duration;
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let daily = Every::new(&at("2024-01-01 03:00:00.000 +0000"), Duration::from_secs(86400));
+        let days = |runs: &[System]| runs.iter().map(|t| t.strftime("%d")).collect::<Vec<_>>();
+
+        // asleep from just after the Jan 1 run until midday on Jan 8
+        let last_run = at("2024-01-01 03:00:05.000 +0000");
+        let now = at("2024-01-08 12:00:00.000 +0000");
+        assert!(CatchUp::new(MissedPolicy::Skip).fires_between(&daily, &last_run, &now).is_empty());
+        assert_eq!(days(&CatchUp::new(MissedPolicy::CatchUpOne).fires_between(&daily, &last_run, &now)), ["08"]);
+        assert_eq!(days(&CatchUp::new(MissedPolicy::CatchUpAll).fires_between(&daily, &last_run, &now)), ["02", "03", "04", "05", "06", "07", "08"]);
+        // bounded, keeping the most recent
+        assert_eq!(days(&CatchUp::new(MissedPolicy::CatchUpAll).max(3).fires_between(&daily, &last_run, &now)), ["06", "07", "08"]);
+        assert!(CatchUp::new(MissedPolicy::CatchUpAll).max(0).fires_between(&daily, &last_run, &now).is_empty());
+
+        // counting back over a long gap, rather than stepping through every occurrence
+        let every_ms = Every::new(&last_run, Duration::from_millis(1));
+        let decade = at("2034-01-01 00:00:00.000 +0000");
+        assert_eq!(every_ms.nth_before(&decade, 1), Some(decade.clone()));
+        assert_eq!(every_ms.nth_before(&decade, 3).unwrap().unix_ms(), decade.unix_ms() - 2);
+        assert_eq!(every_ms.nth_before(&last_run, 2), None);
+        assert_eq!(every_ms.nth_before(&decade, 0), None);
+        let runs = CatchUp::new(MissedPolicy::CatchUpAll).max(3).fires_between(&every_ms, &last_run, &decade);
+        assert_eq!(runs.iter().map(|t| decade.unix_ms() - t.unix_ms()).collect::<Vec<_>>(), [2, 1, 0]);
+        assert!(CatchUp::new(MissedPolicy::Skip).fires_between(&every_ms, &last_run, &decade).is_empty());
+        assert_eq!(CatchUp::new(MissedPolicy::CatchUpOne).fires_between(&every_ms, &last_run, &decade), std::slice::from_ref(&decade));
+
+        // a daemon that woke on time runs the one due occurrence under every policy
+        let on_time = at("2024-01-02 03:00:00.000 +0000");
+        for policy in [MissedPolicy::Skip, MissedPolicy::CatchUpOne, MissedPolicy::CatchUpAll] {
+            assert_eq!(days(&CatchUp::new(policy).fires_between(&daily, &last_run, &on_time)), ["02"]);
+            assert!(CatchUp::new(policy).fires_between(&daily, &on_time, &on_time).is_empty());
+        }
+
+        // the next run is 15 hours after waking, and in the waking offset
+        assert_eq!(next_fire_in(&daily, &now), Some(Duration::from_secs(15 * 3600)));
+        let paris = now.change_tz("+01:00");
+        assert_eq!(daily.next_after(&paris).unwrap().strftime("%Y-%m-%d %H:%M %z"), "2024-01-09 04:00 +0100");
+        assert_eq!(next_fire_in(&daily, &on_time), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn asn1_times() {
+        // the 49/50 pivot
+        let utc = |s: &str| s.strp_asn1_utctime::<System>().map(|t| t.pretty());
+        assert_eq!(utc("491231235959Z").unwrap(), "2049-12-31 23:59:59");
+        assert_eq!(utc("500101000000Z").unwrap(), "1950-01-01 00:00:00");
+        assert_eq!(utc("000229120000Z").unwrap(), "2000-02-29 12:00:00");
+        let x = "240105144629Z".strp_asn1_utctime::<Ntp>().unwrap();
+        assert_eq!((x.unix(), x.utc_offset()), (1704465989, 0));
+        assert_eq!(x.asn1_utctime().unwrap(), "240105144629Z");
+
+        assert_eq!(utc("240105144629"), Err(ParseError::invalid("240105144629", 12, "'Z'")));
+        assert_eq!(utc("240105144629.5Z"), Err(ParseError::invalid("240105144629.5Z", 12, "'Z'")));
+        assert_eq!(utc("2401051446Z"), Err(ParseError::invalid("2401051446Z", 10, "second 00-59")));
+        assert_eq!(utc("240230000000Z"), Err(ParseError::invalid("240230000000Z", 4, "a day that exists in the month")));
+        assert_eq!(utc(""), Err(ParseError::Empty));
+
+        let general = "20240105144629.123456Z".strp_asn1_generalizedtime::<System>().unwrap();
+        assert_eq!(general.unix_ms(), 1704465989123);
+        assert_eq!(general.asn1_generalizedtime(), "20240105144629.123Z");
+        assert_eq!("19500101000000Z".strp_asn1_generalizedtime::<System>().unwrap().asn1_generalizedtime(), "19500101000000Z");
+        assert_eq!("20240105144629.5Z".strp_asn1_generalizedtime::<System>().unwrap().asn1_generalizedtime(), "20240105144629.5Z");
+        for bad in ["20240105144629", "20240105144629.Z", "20240105144629.50Z", "20240105144629,5Z", "202401051446Z", "20240105144629Zx"] {
+            assert!(bad.strp_asn1_generalizedtime::<System>().is_err(), "{bad}");
+        }
+
+        // written in UTC, whatever the offset
+        let paris = general.change_tz("+01:00");
+        assert_eq!(paris.asn1_generalizedtime(), "20240105144629.123Z");
+        assert_eq!(paris.asn1_utctime().unwrap(), "240105144629Z");
+        assert_eq!("1949-12-31 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S").asn1_utctime(), None);
+    }
+
+    #[test]
+    fn precision_loss() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let lossy = at("2024-01-05 14:46:29.999 +0000");
+        let whole = at("2024-01-05 14:46:28.000 +0000");
+        let loss = |ms| PrecisionLoss { discarded_ms: ms };
+
+        assert_eq!(lossy.unix_checked(), (1704465989, loss(999)));
+        assert_eq!(lossy.cast::<Ntp>().unix_checked(), (1704465989, loss(999)));
+        assert_eq!(lossy.mac_os_checked(), (3787310789, loss(999)));
+        assert_eq!(lossy.sas_4gl_checked(), (2020085189, loss(999)));
+        assert_eq!(lossy.asn1_utctime_checked(), Some(("240105144629Z".to_string(), loss(999))));
+        // DOS time is to 2 seconds, so an odd second is lost too
+        assert_eq!(lossy.dos_checked(), Some((0x5825_75ce, loss(1999))));
+        for (checked, plain) in [(lossy.unix_checked().0, lossy.unix()), (lossy.mac_os_checked().0, lossy.mac_os()), (lossy.sas_4gl_checked().0, lossy.sas_4gl())] {
+            assert_eq!(checked, plain);
+        }
+
+        assert_eq!(whole.unix_checked(), (1704465988, PrecisionLoss::NONE));
+        assert_eq!(whole.mac_os_checked(), (3787310788, PrecisionLoss::NONE));
+        assert_eq!(whole.sas_4gl_checked(), (2020085188, PrecisionLoss::NONE));
+        assert_eq!(whole.asn1_utctime_checked(), Some(("240105144628Z".to_string(), PrecisionLoss::NONE)));
+        assert_eq!(whole.dos_checked(), Some((0x5825_75ce, PrecisionLoss::NONE)));
+        assert!(whole.unix_checked().1.is_lossless());
+        assert_eq!(loss(999).to_string(), "999ms discarded");
+        assert_eq!(PrecisionLoss::NONE.to_string(), "lossless");
+
+        // before 1970 the seconds round down, so the loss is still the milliseconds past the second
+        let early = at("1969-12-31 23:59:59.250 +0000");
+        assert_eq!(early.unix_checked(), (-1, loss(250)));
+        assert_eq!(ExtendedTime::from(early.clone()).unix_checked(), (-1, loss(250)));
+        assert_eq!(ExtendedTime::from(early).unix(), -1);
+
+        // DOS dates are local wall clock times, from 1980 to 2107
+        let local = at("1980-01-01 00:00:01.000 +0100");
+        assert_eq!(local.dos_checked(), Some(((1 << 5 | 1) << 16, loss(1000))));
+        assert_eq!(at("2107-12-31 23:59:58.000 +0000").dos(), Some(0xff9f_bf7d));
+        assert_eq!(at("1979-12-31 23:59:59.000 +0000").dos_checked(), None);
+        assert_eq!(at("2108-01-01 00:00:00.000 +0000").dos(), None);
+        assert_eq!(at("2050-01-01 00:00:00.500 +0000").asn1_utctime_checked(), None);
+    }
+
+    #[test]
+    fn span_splitting() {
+        use core::time::Duration;
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f %z");
+        let show = |pieces: &[TimeSpan<System>]| pieces.iter().map(|p| format!("{}..{}", p.start.strftime("%m-%d %H:%M"), p.end.strftime("%m-%d %H:%M"))).collect::<Vec<_>>();
+        let stay = TimeSpan::new(at("2024-01-28 14:00:00.000 +0000"), at("2024-02-03 09:00:00.000 +0000"));
+
+        assert_eq!(
+            show(&stay.split_by(SplitUnit::Day, 0)),
+            ["01-28 14:00..01-29 00:00", "01-29 00:00..01-30 00:00", "01-30 00:00..01-31 00:00", "01-31 00:00..02-01 00:00", "02-01 00:00..02-02 00:00", "02-02 00:00..02-03 00:00", "02-03 00:00..02-03 09:00"]
+        );
+        assert_eq!(show(&stay.split_by(SplitUnit::Month, 0)), ["01-28 14:00..02-01 00:00", "02-01 00:00..02-03 09:00"]);
+        // Jan 28 is a Sunday, so the ISO week turns over at once
+        assert_eq!(
+            stay.duration_in_each(SplitUnit::Week),
+            vec![("2024-W04".to_string(), Duration::from_secs(10 * 3600)), ("2024-W05".to_string(), Duration::from_secs((5 * 24 + 9) * 3600))]
+        );
+        // pieces always add up to the whole
+        let total: u64 = stay.split_by(SplitUnit::Day, 0).iter().map(|p| p.duration_ms()).sum();
+        assert_eq!(total, stay.duration_ms());
+
+        // boundaries are read in the given offset, and the pieces are in it
+        let tokyo = stay.split_by(SplitUnit::Month, 9 * 3600);
+        assert_eq!(tokyo[0].end.strftime("%Y-%m-%d %H:%M %z"), "2024-02-01 00:00 +0900");
+        assert_eq!(tokyo[0].end.to_utc().pretty(), "2024-01-31 15:00:00");
+
+        // across a leap day
+        let leap = TimeSpan::new(at("2024-02-28 12:00:00.000 +0100"), at("2024-03-01 06:00:00.000 +0100"));
+        let days = leap.duration_in_each(SplitUnit::Day);
+        assert_eq!(days.iter().map(|(d, _)| d.as_str()).collect::<Vec<_>>(), ["2024-02-28", "2024-02-29", "2024-03-01"]);
+        assert_eq!(days[1].1, Duration::from_secs(86400));
+        assert_eq!(leap.duration_in_each(SplitUnit::Year), vec![("2024".to_string(), Duration::from_secs(42 * 3600))]);
+
+        // within one unit, or empty, the span comes back as itself
+        assert_eq!(leap.split_by(SplitUnit::Month, 3600).len(), 2);
+        assert_eq!(leap.split_by(SplitUnit::Year, 0), vec![leap.clone()]);
+        let empty = TimeSpan::new(leap.start.clone(), leap.start.clone());
+        assert_eq!(empty.split_by(SplitUnit::Day, 0), vec![empty.clone()]);
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn time_assertions() {
+        use core::time::Duration;
+        let panic_message = |f: fn()| {
+            let payload = std::panic::catch_unwind(f).unwrap_err();
+            payload.downcast_ref::<String>().cloned().unwrap()
+        };
+
+        assert_time_eq!(1704465989u32.unix::<System>(), 1704465989u32.unix::<Ntp>().change_tz("+05:30"));
+        assert_eq!(
+            panic_message(|| assert_time_eq!(1704465989u32.unix::<System>(), 1704466050u32.unix::<System>().change_tz("+01:00"))),
+            "assertion `left == right` failed for times\n  left: 2024-01-05T14:46:29.000Z (raw 13348939589000ms)\n right: 2024-01-05T14:47:30.000Z (raw 13348939650000ms)\n  diff: +1m 1.000s (right - left)"
+        );
+
+        // the boundary is inclusive
+        let base = System::from_epoch(13348939589000);
+        assert_time_within!(base, System::from_epoch(13348939589250), Duration::from_millis(250));
+        assert_time_within!(System::from_epoch(13348939589250), base, Duration::from_millis(250));
+        let message = panic_message(|| assert_time_within!(System::from_epoch(13348939589251), System::from_epoch(13348939589000), Duration::from_millis(250)));
+        assert!(message.starts_with("assertion `left ≈ right` failed for times (tolerance 250ms)\n"), "{message}");
+        assert!(message.ends_with("  diff: -251ms (right - left)"), "{message}");
+
+        assert_eq!(test_util::human_diff(-1500), "-1.500s");
+        assert_eq!(test_util::human_diff(90_061_001), "+1d 1h 1m 1.001s");
+        assert_eq!(test_util::human_diff(3_600_000), "+1h");
+    }
+
+    #[test]
+    #[cfg(feature = "schema")]
+    fn json_schemas() {
+        #[derive(::serde::Serialize, schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Sample {
+            at: System,
+            synced: Ntp,
+            zone: Tz,
+            #[serde(with = "crate::serde::string_ms")]
+            #[schemars(schema_with = "crate::interop::schemars::string_ms")]
+            seen: System,
+            #[serde(with = "crate::serde::string_windows_ns")]
+            #[schemars(schema_with = "crate::interop::schemars::string_windows_ns")]
+            filetime: System,
+            #[serde(with = "crate::serde::precision_safe")]
+            #[schemars(schema_with = "crate::interop::schemars::precision_safe")]
+            id: u64,
+            big: crate::serde::PrecisionSafe<i64>,
+        }
+
+        let schema = serde_json::to_value(schemars::schema_for!(Sample)).unwrap();
+        let field = |name: &str| {
+            let property = &schema["properties"][name];
+            match property["$ref"].as_str() {
+                Some(reference) => schema["$defs"][reference.trim_start_matches("#/$defs/")].clone(),
+                None => property.clone(),
+            }
+        };
+
+        let at = field("at");
+        assert_eq!(at["type"], "object");
+        assert_eq!((at["properties"]["ms1601"]["type"].as_str(), at["properties"]["ms1601"]["format"].as_str()), (Some("integer"), Some("uint64")));
+        assert_eq!(at["properties"]["offset"]["format"], "int32");
+        assert_eq!(at["required"], serde_json::json!(["v", "ms1601", "offset"]));
+        assert_eq!(field("synced")["required"], serde_json::json!(["v", "ms1601", "offset", "server"]));
+        assert_eq!(field("zone")["enum"].as_array().unwrap().len(), Tz::ALL.len());
+        assert_eq!(field("zone")["enum"][1], "BstCet");
+        for name in ["seen", "filetime", "id", "big"] {
+            assert_eq!(field(name)["type"], "string", "{name}");
+            assert_eq!(field(name)["pattern"], "^-?[0-9]+$", "{name}");
+        }
+        assert!(field("filetime")["description"].as_str().unwrap().contains("1601"));
+
+        // the schema describes what is actually written
+        let x = 1704465989u32.unix::<System>();
+        let value = serde_json::to_value(Sample {
+            at: x.clone(),
+            synced: x.cast(),
+            zone: Tz::BstCet,
+            seen: x.clone(),
+            filetime: x.clone(),
+            id: 7,
+            big: crate::serde::PrecisionSafe(-1),
+        })
+        .unwrap();
+        assert!(value["at"]["ms1601"].is_u64() && value["seen"].is_string() && value["id"].is_string());
+        assert_eq!(value["zone"], "BstCet");
+    }
+
+    #[test]
+    fn log_timestamps() {
+        let clf = |s: &str| s.strp_clf::<System>();
+        let x = clf("[05/Jan/2024:14:46:29 +0530]").unwrap();
+        assert_eq!((x.utc_offset(), x.to_utc().pretty()), (19800, "2024-01-05 09:16:29".to_string()));
+        assert_eq!(x.clf(), "[05/Jan/2024:14:46:29 +0530]");
+        assert_eq!(clf(&x.clf()).unwrap(), x);
+        let west = 1704465989u32.unix::<Ntp>().change_tz("-08:00");
+        assert_eq!(west.clf().strp_clf::<Ntp>().unwrap().unix(), 1704465989);
+
+        // brackets are optional, either can be missing, and month names ignore case
+        for variant in ["05/Jan/2024:14:46:29 +0530", "[05/jan/2024:14:46:29 +0530", "05/JAN/2024:14:46:29 +0530]", " [05/Jan/2024:14:46:29 +0530] "] {
+            assert_eq!(clf(variant).unwrap(), x, "{variant}");
+        }
+        assert_eq!(clf("[05/Jam/2024:14:46:29 +0530]"), Err(ParseError::invalid("[05/Jam/2024:14:46:29 +0530]", 4, "a month name (Jan-Dec)")));
+        assert_eq!(clf("[05/Jan/2024:14:46:29]"), Err(ParseError::invalid("[05/Jan/2024:14:46:29]", 21, "a space")));
+        assert_eq!(clf("[31/Feb/2024:14:46:29 +0000]"), Err(ParseError::invalid("[31/Feb/2024:14:46:29 +0000]", 1, "a day that exists in the month")));
+        assert!(clf("[05/Jan/2024:14:46:29 +0000]]").is_err());
+
+        let nginx = "2024/01/05 14:46:29".strp_nginx_error::<System>().unwrap();
+        assert_eq!(nginx.unix(), 1704465989);
+        assert_eq!(nginx.nginx_error(), "2024/01/05 14:46:29");
+        assert_eq!(nginx.nginx_error().strp_nginx_error::<System>().unwrap(), nginx);
+        assert_eq!(x.nginx_error(), "2024/01/05 14:46:29");
+        assert_eq!(
+            "2024/13/05 14:46:29".strp_nginx_error::<System>(),
+            Err(ParseError::invalid("2024/13/05 14:46:29", 5, "month 01-12"))
+        );
+        assert!("2024/01/05 14:46:29 [error]".strp_nginx_error::<System>().is_err());
+    }
+
+    #[test]
+    fn ntp_survey_falsetickers() {
+        // each "server" answers through a crafted snapshot, the local clock reading half the round trip either side of the request
+        let transport = |server: &str| {
+            let (ntp_ms, rtt_ms) = match server {
+                "a.example" => (1_000_040, 30),
+                "b.example" => (1_000_010, 12),
+                "c.example" => (1_000_025, 20),
+                "liar.example" => (1_009_000, 5),
+                _ => return Err(NtpError::Timeout),
+            };
+            let mut local = [1_000_000 - rtt_ms / 2, 1_000_000 + rtt_ms / 2].into_iter();
+            ClockSnapshot::capture_with(|| System::from_epoch(local.next().unwrap()), || Ok(Ntp::from_epoch(ntp_ms)))
+                .map(|snapshot| SurveyReading::from(&snapshot))
+        };
+        let servers = ["a.example", "b.example", "down.example", "c.example", "liar.example"];
+        let report = survey_with(&servers, &SurveyConfig::new().threshold_ms(100), transport);
+
+        assert_eq!(report.entries.len(), 5);
+        assert_eq!(report.median_offset_ms, Some(32.5));
+        let liars: Vec<&str> = report.falsetickers().map(|e| e.server.as_str()).collect();
+        assert_eq!(liars, ["liar.example"]);
+        assert_eq!(report.failures().next().unwrap().error.as_deref(), Some("timed out waiting for an NTP reply"));
+        assert_eq!(report.best().unwrap().server, "b.example");
+        assert_eq!(report.entries[1].delay_ms, Some(12));
+
+        // a tighter threshold flags more, and with nobody agreeing there is no recommendation
+        let strict = survey_with(&["a.example", "liar.example"], &SurveyConfig::new().threshold_ms(10), transport);
+        assert_eq!(strict.falsetickers().count(), 2);
+        assert!(strict.best().is_none());
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["entries"][4]["falseticker"], true);
+        assert_eq!(json["entries"][2]["offset_ms"], serde_json::Value::Null);
+        assert_eq!(serde_json::from_value::<SurveyReport>(json).unwrap(), report);
+    }
+
+    #[test]
+    fn app_start_and_uptime() {
+        let start = app_start().clone();
+        assert!(std::ptr::eq(app_start(), app_start()));
+        let mut last = app_uptime();
+        for _ in 0..50 {
+            let uptime = app_uptime();
+            assert!(uptime >= last);
+            last = uptime;
+        }
+        assert_eq!(relative_to_start(&start), 0);
+        assert_eq!(relative_to_start(&start.add_seconds(90)), 90_000);
+
+        #[cfg(feature = "testing")]
+        {
+            let _start = mock::set_app_start(&1704465989u32.unix::<System>());
+            assert_eq!(app_start().unix(), 1704465989);
+            let _now = mock::set_mock_time(&1704466049u32.unix::<System>());
+            assert_eq!(app_uptime(), core::time::Duration::from_secs(60));
+            assert_eq!(relative_to_start(&1704465988u32.unix::<System>()), -1000);
+            drop(_now);
+            {
+                let _earlier = mock::set_app_start(&1704400000u32.unix::<System>());
+                assert_eq!(app_start().unix(), 1704400000);
+            }
+            assert_eq!(app_start().unix(), 1704465989);
+        }
+        assert_eq!(app_start(), &start);
+    }
+
+    #[test]
+    fn tle_epochs() {
+        // the ISS, from a published element set
+        let iss = "08264.51782528".strp_tle_epoch::<System>().unwrap();
+        assert_eq!(iss.strftime("%Y-%m-%d %H:%M:%S%.3f"), "2008-09-20 12:25:40.104");
+        assert_eq!(iss.utc_offset(), 0);
+        assert_eq!(iss.tle_epoch().unwrap(), "08264.51782528");
+        assert_eq!(" 24005.61561343 ".strp_tle_epoch::<System>().unwrap().unix(), 1704465989);
+
+        // the year pivot: 56 is the last year of the 21st century range, 57 the first of the 20th
+        assert_eq!("56366.0".strp_tle_epoch::<System>().unwrap().pretty(), "2056-12-31 00:00:00");
+        assert_eq!("57001.5".strp_tle_epoch::<System>().unwrap().pretty(), "1957-01-01 12:00:00");
+        assert_eq!("56001".strp_tle_epoch::<System>().unwrap().tle_epoch().unwrap(), "56001.00000000");
+        assert_eq!("57001.00000000".strp_tle_epoch::<System>().unwrap().tle_epoch().unwrap(), "57001.00000000");
+        assert_eq!("1956-12-31 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S").tle_epoch(), None);
+        assert_eq!("2057-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").tle_epoch(), None);
+
+        // every millisecond value survives the round trip
+        let base = "24005.0".strp_tle_epoch::<System>().unwrap();
+        for ms in (0..86_400_000u64).step_by(86_399) {
+            let t = System::from_epoch(base.raw() + ms);
+            assert_eq!(t.tle_epoch().unwrap().strp_tle_epoch::<System>().unwrap().raw(), t.raw());
+        }
+
+        assert!(matches!("23000.5".strp_tle_epoch::<System>(), Err(ParseError::Invalid { position: 2, .. })));
+        assert!(matches!("23366.5".strp_tle_epoch::<System>(), Err(ParseError::Invalid { position: 2, .. })));
+        assert!(matches!("24005.".strp_tle_epoch::<System>(), Err(ParseError::Invalid { position: 6, .. })));
+        assert!(matches!("24005.5x".strp_tle_epoch::<System>(), Err(ParseError::Invalid { position: 7, .. })));
+        assert!(matches!("".strp_tle_epoch::<System>(), Err(ParseError::Empty)));
+    }
+
+    #[test]
+    fn calendar_boundaries() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+
+        // 30 seconds either side of midnight in UTC+2, which is 22:00 in UTC
+        let before = at("2024-01-05 21:59:45");
+        let after = at("2024-01-05 22:00:15");
+        assert!(after.crossed_boundary(&before, BoundaryUnit::Day, 7200));
+        assert!(after.crossed_boundary(&before, BoundaryUnit::Hour, 7200));
+        assert!(!after.crossed_boundary(&before, BoundaryUnit::Day, 0));
+        assert!(!before.crossed_boundary(&after, BoundaryUnit::Day, 7200));
+
+        // 25 hours apart crosses one midnight or two, depending on where the samples sit
+        let evening = at("2024-01-05 23:30:00");
+        assert_eq!(evening.add_seconds(25 * 3600).boundaries_crossed(&evening, BoundaryUnit::Day, 0), 2);
+        let morning = at("2024-01-05 09:00:00");
+        assert_eq!(morning.add_seconds(25 * 3600).boundaries_crossed(&morning, BoundaryUnit::Day, 0), 1);
+        assert_eq!(morning.add_seconds(25 * 3600).boundaries_crossed(&morning, BoundaryUnit::Hour, 0), 25);
+        assert_eq!(morning.add_seconds(25 * 3600).boundaries_crossed(&morning, BoundaryUnit::Day, -10 * 3600), 2);
+
+        // the same second crosses nothing, and landing exactly on the boundary counts
+        assert!(!morning.crossed_boundary(&morning.clone(), BoundaryUnit::Minute, 0));
+        assert!(at("2024-01-06 00:00:00").crossed_boundary(&at("2024-01-05 23:59:59"), BoundaryUnit::Day, 0));
+        assert!(!at("2024-01-06 00:00:01").crossed_boundary(&at("2024-01-06 00:00:00"), BoundaryUnit::Day, 0));
+
+        // weeks start on Monday, and the longer units follow the calendar
+        assert_eq!(at("2024-01-15 00:00:00").boundaries_crossed(&at("2024-01-01 00:00:00"), BoundaryUnit::Week, 0), 2);
+        assert_eq!(at("2025-01-01 00:00:00").boundaries_crossed(&at("2024-12-31 23:59:59"), BoundaryUnit::Year, 0), 1);
+        assert_eq!(at("2025-01-01 00:00:00").boundaries_crossed(&at("2024-01-01 00:00:00"), BoundaryUnit::Month, 0), 12);
+        assert_eq!(at("1969-12-29 00:00:00").boundaries_crossed(&at("1969-12-28 23:59:59"), BoundaryUnit::Week, 0), 1);
+    }
+
+    #[test]
+    fn canonical_encoding() {
+        // golden vectors - these must never change
+        let golden: [(i64, [u8; 10]); 4] = [
+            (0, [0x00, 0x00, 0x0a, 0x97, 0x30, 0xb6, 0x68, 0x00, 0x01, 0x00]),
+            (1704465989000, [0x00, 0x00, 0x0c, 0x24, 0x0a, 0xcd, 0x65, 0x88, 0x01, 0x00]),
+            (1704465989123, [0x00, 0x00, 0x0c, 0x24, 0x0a, 0xcd, 0x66, 0x03, 0x01, 0x00]),
+            (-11644473600000, [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00]),
+        ];
+        for (unix_ms, bytes) in golden {
+            let x = System::from_epoch((unix_ms + OFFSET_1601 as i64 * 1000) as u64);
+            assert_eq!(x.canonical_bytes(), bytes);
+            assert_eq!(System::from_canonical_bytes(&bytes).unwrap(), x);
+            assert_eq!(Ntp::from_canonical_bytes(&bytes).unwrap().unix_ms(), unix_ms);
+        }
+
+        // the offset is normalized away
+        let x = "2024-01-05 14:46:29 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        for tz in ["+01:00", "-05:00", "+13:45"] {
+            assert_eq!(x.change_tz(tz).canonical_bytes(), x.canonical_bytes());
+        }
+        assert_eq!(System::from_canonical_bytes(&x.change_tz("+01:00").canonical_bytes()).unwrap().utc_offset(), 0);
+
+        let mut bytes = x.canonical_bytes();
+        bytes[8] = 2;
+        assert_eq!(System::from_canonical_bytes(&bytes), Err(TimeError::InvalidField { field: "canonical version", value: 2 }));
+        bytes[8] = 0;
+        assert!(System::from_canonical_bytes(&bytes).is_err());
+        bytes[8] = 1;
+        bytes[9] = 7;
+        assert_eq!(System::from_canonical_bytes(&bytes), Err(TimeError::InvalidField { field: "canonical reserved byte", value: 7 }));
+        assert_eq!(System::from_canonical_bytes(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0x00]), Err(TimeError::Overflow));
+
+        #[cfg(feature = "digest")]
+        {
+            use sha2::{Digest, Sha256};
+            let mut a = Sha256::new();
+            x.canonical_digest(&mut a);
+            let mut b = Sha256::new();
+            x.change_tz("+09:00").canonical_digest(&mut b);
+            assert_eq!(a.finalize(), b.finalize());
+            assert_eq!(Sha256::digest(x.canonical_bytes()), Sha256::digest([0x00, 0x00, 0x0c, 0x24, 0x0a, 0xcd, 0x65, 0x88, 0x01, 0x00]));
+        }
+    }
+
+    #[test]
+    fn http_headers() {
+        use core::time::Duration;
+        let now = "2015-10-21 07:28:00 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z").change_tz("+02:00");
+
+        // Retry-After, in both forms
+        let delta = http::parse_retry_after("120", &now).unwrap();
+        assert_eq!(delta.unix(), now.unix() + 120);
+        assert_eq!(delta.utc_offset(), 7200);
+        let dated = http::parse_retry_after("Wed, 21 Oct 2015 07:30:00 GMT", &now).unwrap();
+        assert_eq!(dated, delta);
+        assert_eq!(http::parse_retry_after(" Wednesday, 21-Oct-15 07:30:00 GMT ", &now).unwrap(), delta);
+        assert_eq!(http::parse_retry_after("Wed Oct 21 07:30:00 2015", &now).unwrap(), delta);
+        assert_eq!(http::parse_retry_after("99999999999999999999", &now).unwrap().unix(), now.unix() + 2_147_483_648);
+        for bad in ["", "-120", "1.5", "Wed, 21 Oct 2015 07:30:00 UTC", "Wed, 32 Oct 2015 07:30:00 GMT"] {
+            assert!(http::parse_retry_after(bad, &now).is_err(), "{:?}", bad);
+        }
+        assert_eq!(http::format_retry_after(Duration::from_secs(120)), "120");
+        assert_eq!(http::format_retry_after(Duration::from_millis(1)), "1");
+
+        // Age: invalid and negative values are ignored
+        assert_eq!(http::parse_age(" 3600 "), Duration::from_secs(3600));
+        for bad in ["-3600", "", "1e3", "3600s", "Wed, 21 Oct 2015 07:28:00 GMT"] {
+            assert_eq!(http::parse_age(bad), Duration::ZERO, "{:?}", bad);
+        }
+        assert_eq!(http::format_age(Duration::from_secs(3600)), "3600");
+
+        // Expires against Date
+        let date = "Wed, 21 Oct 2015 07:28:00 GMT";
+        assert_eq!(http::freshness_lifetime("Wed, 21 Oct 2015 08:28:00 GMT", date).unwrap(), Duration::from_secs(3600));
+        assert_eq!(http::freshness_lifetime("Wed, 21 Oct 2015 06:28:00 GMT", date).unwrap(), Duration::ZERO);
+        assert_eq!(http::freshness_lifetime(date, date).unwrap(), Duration::ZERO);
+        assert_eq!(http::freshness_lifetime("-1", date).unwrap(), Duration::ZERO);
+        assert!(matches!(http::freshness_lifetime(date, "Wed, 21 Oct 2015 07:28:00"), Err(ParseError::Invalid { position: 25, .. })));
+        assert_eq!(http::format_expires(&now, Duration::from_secs(3600)), "Wed, 21 Oct 2015 08:28:00 GMT");
+        assert_eq!(http::http_date(&now), date);
+        assert_eq!(http::parse_http_date::<Ntp>(&http::http_date(&now)).unwrap().unix(), now.unix());
+        assert_eq!(http::format_expires(&now, Duration::MAX), "Fri, 31 Dec 9999 23:59:59 GMT");
+    }
+
+    #[test]
+    fn today_snapshots() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        let now = at("2024-01-05 23:30:00 +0000");
+
+        let today = Today::at(&now, 0);
+        assert_eq!(today.date().to_string(), "2024-01-05");
+        assert_eq!(today.span().duration_ms(), 86_400_000);
+        assert!(today.is_today(&today.start()));
+        assert!(!today.is_today(&today.end()));
+        assert_eq!(today.yesterday().end(), today.start());
+        assert_eq!(today.tomorrow().start(), today.end());
+        assert_eq!(today.tomorrow().now(), &now);
+
+        // half an hour before midnight in UTC is already tomorrow in UTC+1
+        let east = Today::at(&now, 3600);
+        assert_eq!(east.date().to_string(), "2024-01-06");
+        assert_eq!(east.start(), at("2024-01-06 00:00:00 +0100"));
+        assert_eq!(east.start().utc_offset(), 3600);
+        assert!(east.yesterday().is_today(&today.start().add_seconds(3600)));
+
+        #[cfg(feature = "testing")]
+        {
+            let _guard = mock::set_mock_time(&at("2024-01-05 23:59:59 +0000"));
+            let today = Today::capture(0);
+            let start = today.start();
+            // midnight passes between the accessor calls
+            mock::advance_mock(1500);
+            assert_eq!(today.end(), start.add_days(1));
+            assert_eq!(today.date().to_string(), "2024-01-05");
+            assert!(today.is_today(today.now()));
+            assert!(!today.is_today(&System::now()));
+            // reading the clock twice would have torn
+            assert_ne!(System::now().truncate_to(TruncateUnit::Day), start);
+        }
+    }
+
+    #[test]
+    fn hms_durations() {
+        use core::time::Duration;
+        assert_eq!(format_hms(3909i64, HmsHours::Always), "1:05:09");
+        assert_eq!(format_hms(3909i64, HmsHours::IfNonZero), "1:05:09");
+        assert_eq!(format_hms(Duration::from_secs(65), HmsHours::Always), "0:01:05");
+        assert_eq!(format_hms(Duration::from_secs(65), HmsHours::IfNonZero), "01:05");
+        assert_eq!(format_hms(0i64, HmsHours::Always), "0:00:00");
+        assert_eq!(format_hms(Duration::ZERO, HmsHours::IfNonZero), "00:00");
+        assert_eq!(format_hms(-3909i64, HmsHours::Always), "-1:05:09");
+        assert_eq!(format_hms(chrono::Duration::seconds(-65), HmsHours::IfNonZero), "-01:05");
+        assert_eq!(format_hms(chrono::Duration::milliseconds(-999), HmsHours::Always), "0:00:00");
+        assert_eq!(format_hms(Duration::from_secs(100 * 3600), HmsHours::Always), "100:00:00");
+
+        assert_eq!(format_hms_ms(Duration::from_millis(3_909_007), HmsHours::Always), "1:05:09.007");
+        assert_eq!(format_hms_ms(Duration::from_millis(65_250), HmsHours::IfNonZero), "01:05.250");
+        assert_eq!(format_hms_ms(chrono::Duration::milliseconds(-65_250), HmsHours::Always), "-0:01:05.250");
+
+        assert_eq!(parse_hms("1:05:09").unwrap(), Duration::from_secs(3909));
+        assert_eq!(parse_hms("05:09").unwrap(), Duration::from_secs(309));
+        assert_eq!(parse_hms(" 65 ").unwrap(), Duration::from_secs(65));
+        assert_eq!(parse_hms("0").unwrap(), Duration::ZERO);
+        assert_eq!(parse_hms("90:00").unwrap(), Duration::from_secs(5400));
+        assert_eq!(parse_hms("1:05:09.0075").unwrap(), Duration::from_millis(3_909_007));
+        for d in [0u64, 65_000, 3_909_250, 359_999_999] {
+            let d = Duration::from_millis(d);
+            assert_eq!(parse_hms(&format_hms_ms(d, HmsHours::Always)).unwrap(), d);
+            assert_eq!(parse_hms(&format_hms_ms(d, HmsHours::IfNonZero)).unwrap(), d);
+            assert_eq!(parse_hms(&format_hms(d, HmsHours::IfNonZero)).unwrap().as_secs(), d.as_secs());
+        }
+
+        assert_eq!(parse_hms(""), Err(ParseError::Empty));
+        assert_eq!(parse_hms("-1:05:09"), Err(ParseError::invalid("-1:05:09", 0, "digits")));
+        assert_eq!(parse_hms("1:5:09"), Err(ParseError::invalid("1:5:09", 3, "two digits 00-59")));
+        assert_eq!(parse_hms("1:60:00"), Err(ParseError::invalid("1:60:00", 2, "two digits 00-59")));
+        assert_eq!(parse_hms("1:00:00:00"), Err(ParseError::invalid("1:00:00:00", 7, "end of input")));
+        assert_eq!(parse_hms("1:00."), Err(ParseError::invalid("1:00.", 5, "fractional digits")));
+        assert_eq!(parse_hms("1:00s"), Err(ParseError::invalid("1:00s", 4, "end of input")));
+    }
+
+    #[test]
+    fn monotonic_validation() {
+        use core::time::Duration;
+        let t = 1704465989u32.unix::<System>();
+        let ms = |n: i64| System::from_epoch((t.raw() as i64 + n) as u64);
+        let mut validator = MonotonicValidator::new().tolerance(Duration::from_millis(5));
+
+        assert_eq!(validator.check(&ms(0)), Ok(()));
+        assert_eq!(validator.check(&ms(10)), Ok(()));
+        // 2ms of jitter is within the tolerance, and doesn't lower the latest time
+        assert_eq!(validator.check(&ms(8)), Ok(()));
+        assert_eq!(validator.check(&ms(6)), Ok(()));
+        assert_eq!(validator.check(&ms(4)), Err(MonotonicViolation::Regression { prev: t.unix_ms() + 10, cur: t.unix_ms() + 4, delta: 6 }));
+        assert_eq!(validator.check(&ms(20)), Ok(()));
+        assert_eq!(validator.check(&ms(20)), Err(MonotonicViolation::Duplicate { at: t.unix_ms() + 20 }));
+        let regression = validator.check(&ms(20 - 10_000)).unwrap_err();
+        assert_eq!(regression, MonotonicViolation::Regression { prev: t.unix_ms() + 20, cur: t.unix_ms() - 9980, delta: 10_000 });
+        assert_eq!(regression.to_string(), "time went backwards by 10000ms (from 1704465989020 to 1704465979020 unix ms)");
+        assert_eq!(validator.check(&ms(21)), Ok(()));
+
+        assert_eq!((validator.checked(), validator.tolerated(), validator.regressions(), validator.duplicates()), (9, 2, 2, 1));
+        assert_eq!(validator.max_regression_ms(), 10_000);
+        assert_eq!(validator.summary(), "9 checked: 2 regressions, 1 duplicate, 2 within tolerance (worst 10000ms)");
+
+        // the batch form has no tolerance
+        let times = [ms(0), ms(10), ms(8), ms(10), ms(10), ms(11)];
+        assert_eq!(
+            validate_sorted(&times),
+            vec![
+                (2, MonotonicViolation::Regression { prev: t.unix_ms() + 10, cur: t.unix_ms() + 8, delta: 2 }),
+                (3, MonotonicViolation::Duplicate { at: t.unix_ms() + 10 }),
+                (4, MonotonicViolation::Duplicate { at: t.unix_ms() + 10 }),
+            ]
+        );
+        assert!(validate_sorted::<System>(&[]).is_empty());
+    }
+
+    #[test]
+    fn validity_windows() {
+        use core::time::Duration;
+        let nbf = 1704465989i64;
+        let exp = nbf + 3600;
+        let window = ValidityWindow::<System>::from_unix_seconds(nbf, exp).unwrap();
+        let at_ms = |ms: i64| System::from_epoch((ms + OFFSET_1601 as i64 * 1000) as u64);
+        let at = |secs: i64| at_ms(secs * 1000);
+
+        // zero leeway: nbf is the first valid instant, exp the first invalid one
+        assert_eq!(window.status(&at(nbf), Duration::ZERO), ValidityStatus::Valid { expires_in: Duration::from_secs(3600) });
+        assert_eq!(window.status(&at_ms(nbf * 1000 - 1), Duration::ZERO), ValidityStatus::NotYetValid { starts_in: Duration::from_millis(1) });
+        assert_eq!(window.status(&at_ms(exp * 1000 - 1), Duration::ZERO), ValidityStatus::Valid { expires_in: Duration::from_millis(1) });
+        assert_eq!(window.status(&at(exp), Duration::ZERO), ValidityStatus::Expired { expired_ago: Duration::ZERO });
+
+        // symmetric leeway widens both ends
+        let leeway = Duration::from_secs(30);
+        assert_eq!(window.status(&at(nbf), leeway), ValidityStatus::Valid { expires_in: Duration::from_secs(3630) });
+        assert_eq!(window.status(&at(exp), leeway), ValidityStatus::Valid { expires_in: Duration::from_secs(30) });
+        assert!(window.is_valid(&at(nbf - 30), leeway));
+        assert_eq!(window.status(&at(nbf - 31), leeway), ValidityStatus::NotYetValid { starts_in: Duration::from_secs(1) });
+        assert_eq!(window.status(&at(exp + 30), leeway), ValidityStatus::Expired { expired_ago: Duration::ZERO });
+
+        // per-end leeway
+        let leeway = Leeway::new(Duration::ZERO, Duration::from_secs(60));
+        assert!(!window.is_valid(&at(nbf - 1), leeway));
+        assert!(window.is_valid(&at(exp + 59), leeway));
+        assert_eq!(window.status(&at(exp + 90), leeway), ValidityStatus::Expired { expired_ago: Duration::from_secs(30) });
+
+        // an offset on `now` doesn't move the window
+        assert!(window.is_valid(&at(nbf).change_tz("-05:00"), Duration::ZERO));
+
+        assert!(ValidityWindow::<System>::from_unix_seconds(i64::MAX, 0).is_err());
+        assert!(ValidityWindow::<System>::from_unix_seconds(-(OFFSET_1601 as i64) - 1, 0).is_err());
+        assert!(ValidityWindow::<System>::from_unix_seconds(-(OFFSET_1601 as i64), 253402300799).is_ok());
+
+        let cert = ValidityWindow::<System>::from_asn1("240105144629Z", "20240205144629Z").unwrap();
+        assert_eq!(cert.not_before, at(nbf));
+        assert!(cert.is_valid(&at(nbf), Duration::ZERO));
+        assert!(ValidityWindow::<System>::from_asn1("240105144629", "20240205144629Z").is_err());
+    }
+
+    #[test]
+    fn streaming_parser_splits() {
+        let stream: &[u8] = b"2024-01-05 14:46:29\n\n2024-01-05 14:46:30\r\nnot a time\n2024-01-05 14:46:31 and then some\n2024-13-05 14:46:32\n2024-01-05 14:46:33";
+        let parser = || StreamingParser::new("%Y-%m-%d %H:%M:%S").max_len(24);
+        let mut whole = parser();
+        let expected = whole.push(stream);
+        let expected_tail = whole.finish();
+        assert_eq!(expected.len(), 5);
+        assert_eq!(expected[0].as_ref().unwrap().unix(), 1704465989);
+        assert_eq!(expected[1].as_ref().unwrap().unix(), 1704465990);
+        assert!(expected[2].is_err());
+        // too long: reported once, with only the bytes that fitted
+        assert_eq!(expected[3], Err(ParseError::invalid("2024-01-05 14:46:31 and ", 24, "a delimiter within 24 bytes")));
+        assert!(expected[4].is_err());
+        assert_eq!(expected_tail, Some(ParseError::invalid("2024-01-05 14:46:33", 19, "a delimiter")));
+
+        for i in 0..=stream.len() {
+            for j in i..=stream.len() {
+                let mut split = parser();
+                let mut got = split.push(&stream[..i]);
+                got.extend(split.push(&stream[i..j]));
+                got.extend(split.push(&stream[j..]));
+                assert!(split.buffered() <= 24);
+                assert_eq!(got, expected, "split at {} and {}", i, j);
+                assert_eq!(split.finish(), expected_tail);
+            }
+        }
+
+        let mut bytewise = parser();
+        let got: Vec<_> = stream.iter().flat_map(|b| bytewise.push(&[*b])).collect();
+        assert_eq!(got, expected);
+        assert_eq!(bytewise.finish(), expected_tail);
+        assert_eq!(bytewise.finish(), None);
+
+        let mut iso = StreamingParser::iso_lenient().delimiter(b';');
+        let got = iso.push(b"2024-01-05T14:46:29Z;2024-01-05 14:46:29.5+01:00;");
+        assert_eq!(got.iter().map(|x| x.as_ref().unwrap().unix_ms()).collect::<Vec<_>>(), [1704465989000, 1704462389500]);
     }
 
-    /// Convert an integer into a time struct of choice, from a SAS 4GL timestamp (seconds since 1960-01-01 00:00:00)
-    ///
-    /// # Examples
-    /// ```rust
-    /// use thetime::{System, Time, IntTime};
-    /// println!("2024 - {:#?}", 2020003754u64.sas_4gl::<System>());
-    /// assert_eq!(2020003754u64.sas_4gl::<System>().strftime("%Y-%m-%d %H:%M:%S"), "2024-01-04 16:09:14");
-    /// ```
-    fn sas_4gl<T: Time>(self) -> T {
-        let selfu64: u64 = self.into();
-        let selfi64: i64 = selfu64 as i64;
-        let unix: i64 = selfi64 - MAGIC_SAS_4GL;
-        T::from_epoch((unix + (OFFSET_1601 as i64)) as u64 * 1000)
+    #[test]
+    fn time_iter_adaptors() {
+        let base = 1704465989u32.unix::<System>();
+        let times: Vec<System> = [0, 3600, -7200, 86400, 1800].iter().map(|s| base.add_seconds(*s)).collect();
+        let span = TimeSpan::new(base.clone(), base.add_seconds(7200));
+
+        // by reference, then owned from the first adaptor on
+        let shifted: Vec<_> = times.iter().add_each(core::time::Duration::from_secs(60)).to_offset(-18000).filter_within(&span).collect();
+        assert_eq!(shifted.iter().map(|t| t.unix()).collect::<Vec<_>>(), [1704466049, 1704469649, 1704467849]);
+        assert!(shifted.iter().all(|t| t.utc_offset() == -18000));
+        assert_eq!(shifted[0].pretty(), "2024-01-05 09:47:29");
+
+        let clamped: Vec<_> = times.clone().into_iter().to_offset(3600).clamp_to(&span).add_each(chrono::Duration::seconds(-1)).collect();
+        assert_eq!(clamped.iter().map(|t| t.unix()).collect::<Vec<_>>(), [1704465988, 1704469588, 1704465988, 1704473188, 1704467788]);
+        assert!(clamped.iter().all(|t| t.utc_offset() == 3600));
+        // a span with its fields set back to front clamps and filters as the right way round does
+        let reversed = TimeSpan { start: span.end.clone(), end: span.start.clone() };
+        assert_eq!(times.iter().clamp_to(&reversed).collect::<Vec<_>>(), times.iter().clamp_to(&span).collect::<Vec<_>>());
+        assert_eq!(times.iter().filter_within(&reversed).count(), 3);
+
+        assert_eq!(times.iter().min_time().unwrap().unix(), 1704458789);
+        assert_eq!(times.iter().max_time().unwrap().unix(), 1704552389);
+        assert_eq!(times.iter().filter_within(&span).max_time().unwrap().unix(), 1704469589);
+        assert_eq!(Vec::<System>::new().into_iter().min_time(), None);
+        assert_eq!(times.iter().add_each(chrono::Duration::days(-1_000_000)).min_time().unwrap().raw(), 0);
     }
 
-    /// Prints the time duration in a formatted string. Note that this only goes up to weeks, as years are rather subjective
-    ///
-    /// # Examples
-    /// ```rust
-    /// use thetime::IntTime;
-    /// let duration = 3600u64;
-    /// let formatted = duration.ts_print();
-    /// assert_eq!(formatted, "0w 0d 1h 0m 0s");
-    /// ```
-    fn ts_print(self) -> String {
-        let duration = chrono::Duration::seconds(self.into() as i64);
-        format!(
-            "{}w {}d {}h {}m {}s",
-            duration.num_weeks(),
-            duration.num_days() % 7,
-            duration.num_hours() % 24,
-            duration.num_minutes() % 60,
-            duration.num_seconds() % 60
-        )
+    #[test]
+    fn calendar_durations() {
+        let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        let add = |t: &System, d: CalendarDuration, policy| t.add_calendar(&d, policy).map(|t| t.strftime("%Y-%m-%d %H:%M:%S %z"));
+        let jan30 = at("2024-01-30 09:00:00 +0100");
+
+        // months, then days, then the exact part - each other order gives a different answer here
+        let d = CalendarDuration::new(1, 1, 15 * 3_600_000);
+        assert_eq!(add(&jan30, d, MonthRollPolicy::Clamp).unwrap(), "2024-03-02 00:00:00 +0100");
+        let days_first = jan30.add_calendar(&CalendarDuration::days(1), MonthRollPolicy::Clamp).unwrap();
+        assert_eq!(add(&days_first, CalendarDuration::new(1, 0, 15 * 3_600_000), MonthRollPolicy::Clamp).unwrap(), "2024-03-01 00:00:00 +0100");
+        let exact_first = jan30.add_calendar(&CalendarDuration::exact(15 * 3600i64), MonthRollPolicy::Clamp).unwrap();
+        assert_eq!(add(&exact_first, CalendarDuration::new(1, 1, 0), MonthRollPolicy::Clamp).unwrap(), "2024-03-01 00:00:00 +0100");
+
+        // the policy only applies to the months
+        let jan31 = at("2024-01-31 09:00:00 +0100");
+        assert_eq!(add(&jan31, CalendarDuration::new(1, 1, 0), MonthRollPolicy::Clamp).unwrap(), "2024-03-01 09:00:00 +0100");
+        assert!(add(&jan31, CalendarDuration::new(1, 1, 0), MonthRollPolicy::Reject).is_err());
+        assert_eq!(add(&jan31, CalendarDuration::days(30), MonthRollPolicy::Reject).unwrap(), "2024-03-01 09:00:00 +0100");
+        let feb29 = at("2024-02-29 09:00:00 +0100");
+        assert_eq!(add(&feb29, CalendarDuration::months(1), MonthRollPolicy::EndOfMonth).unwrap(), "2024-03-31 09:00:00 +0100");
+
+        // negative parts, and going back by the same amount
+        let back = CalendarDuration::new(1, 3, 4 * 3_600_000).negated();
+        assert_eq!(back, CalendarDuration::new(-1, -3, -4 * 3_600_000));
+        assert_eq!(add(&at("2024-03-03 13:00:00 +0000"), back, MonthRollPolicy::Clamp).unwrap(), "2024-01-31 09:00:00 +0000");
+        assert_eq!(add(&jan30, CalendarDuration::default(), MonthRollPolicy::Reject).unwrap(), "2024-01-30 09:00:00 +0100");
+        assert_eq!(add(&jan30, CalendarDuration::days(i32::MAX), MonthRollPolicy::Clamp), Err(MonthRollError::OutOfRange));
+        assert_eq!(add(&jan30, CalendarDuration::exact(i64::MIN), MonthRollPolicy::Clamp), Err(MonthRollError::OutOfRange));
+
+        assert_eq!(CalendarDuration::new(1, 3, 4 * 3_600_000).to_string(), "1mo 3d 04:00:00");
+        assert_eq!(CalendarDuration::months(-2).to_string(), "-2mo");
+        assert_eq!(CalendarDuration::new(0, 1, -1500).to_string(), "1d -00:00:01.500");
+        assert_eq!(CalendarDuration::exact(core::time::Duration::from_secs(100 * 3600)).to_string(), "100:00:00");
+        assert_eq!(CalendarDuration::default().to_string(), "00:00:00");
+        assert!(CalendarDuration::default().is_zero());
+        // structural equality only: a month is not 30 days
+        assert_ne!(CalendarDuration::months(1), CalendarDuration::days(30));
     }
-}
 
-/// implement the StrTime trait for `String` types
-impl StrTime for str {}
+    #[test]
+    fn progress_helpers() {
+        let parse = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+        for offset in ["+0000", "+0530", "-0800", "+1345"] {
+            let noon = parse(&format!("2024-03-10 12:00:00 {}", offset));
+            assert_eq!(noon.progress_through_day(), 0.5);
+            assert_eq!(noon.at_midnight().progress_through_day(), 0.0);
+        }
 
-/// implement the StrTime trait for `&str` types
-impl StrTime for String {}
+        let leap_noon = parse("2024-02-29 12:00:00 +0100");
+        assert_eq!(leap_noon.progress_through_year(), 59.5 / 366.0);
+        assert_eq!(leap_noon.progress_through_month(), 28.5 / 29.0);
+        assert_eq!(parse("2023-12-31 12:00:00 +0000").progress_through_year(), 364.5 / 365.0);
 
-/// implement the IntTime trait for all integer types that implement conversion to u64
-impl<T: core::fmt::Display + Into<u64>> IntTime for T {}
+        let span = TimeSpan::new(parse("2024-01-01 00:00:00 +0200"), parse("2024-01-11 00:00:00 +0200"));
+        assert_eq!(parse("2023-12-25 00:00:00 +0200").progress_through(&span), 0.0);
+        assert_eq!(parse("2024-01-11 00:00:00 +0200").progress_through(&span), 1.0);
+        assert_eq!(parse("2024-02-01 00:00:00 +0200").progress_through(&span), 1.0);
+        assert_eq!(parse("2024-01-03 12:00:00 +0200").progress_through(&span), 0.25);
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        assert_eq!(span.at_fraction(0.25).pretty(), "2024-01-03 12:00:00");
+        assert_eq!(span.at_fraction(-1.0), span.start);
+        assert_eq!(span.at_fraction(7.0).unix(), span.end.unix());
+        for f in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            assert_eq!(span.at_fraction(f).progress_through(&span), f);
+        }
+
+        let empty = TimeSpan::new(span.start.clone(), span.start.clone());
+        assert_eq!(span.start.progress_through(&empty), 1.0);
+        assert_eq!(span.start.add_seconds(-1).progress_through(&empty), 0.0);
+        assert_eq!(span.start.add_seconds(1).progress_through(&empty), 1.0);
+
+        // a span built through its fields back to front reads the same as the swapped one, and deserializing swaps it
+        let reversed = TimeSpan { start: span.end.clone(), end: span.start.clone() };
+        assert_eq!(reversed.duration_ms(), span.duration_ms());
+        assert_eq!(reversed.at_fraction(0.25).pretty(), "2024-01-03 12:00:00");
+        assert!(reversed.contains(&parse("2024-01-03 12:00:00 +0200")));
+        assert_eq!(parse("2024-01-03 12:00:00 +0200").progress_through(&reversed), 0.25);
+        assert_eq!(reversed.duration_in_each(SplitUnit::Year), span.duration_in_each(SplitUnit::Year));
+        assert_eq!(reversed.split_by(SplitUnit::Day, 0).len(), span.split_by(SplitUnit::Day, 0).len());
+        let mut set = SpanSet::new();
+        set.insert(reversed.clone(), ());
+        assert_eq!(set.total_covered_duration(), core::time::Duration::from_millis(span.duration_ms()));
+        assert_eq!(set.query_point(&parse("2024-01-03 12:00:00 +0200")), vec![&()]);
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&reversed).unwrap();
+            assert_eq!(serde_json::from_str::<TimeSpan<System>>(&json).unwrap(), span);
+        }
+    }
 
     #[test]
-    fn test_system() {
-        let x = System::now();
-        println!("{}", x);
-        println!("{}", x.unix_ms());
-        println!("{}", x.strftime("%Y-%m-%d %H:%M:%S"));
+    fn replace_date_and_time() {
+        let a = "2024-01-05 14:30:15.250".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+        let b = "2023-07-20 08:05:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let ab = a.replace_date(&b);
+        assert_eq!(ab.pretty(), "2023-07-20 14:30:15");
+        assert_eq!(ab.raw() % 1000, 250);
+        assert_eq!(a.replace_time(&b).pretty(), "2024-01-05 08:05:00");
+        assert_eq!(b.replace_time(&a).pretty(), "2023-07-20 14:30:15");
+        assert_eq!(b.replace_date(&a).pretty(), "2024-01-05 08:05:00");
+        assert_eq!(a.at_midnight().pretty(), "2024-01-05 00:00:00");
+        assert_eq!(a.at_midnight().raw() % 1000, 0);
+
+        // a leap day source is copied whole, so it is never clamped
+        let leap = "2024-02-29 23:00:00".parse_time::<Ntp>("%Y-%m-%d %H:%M:%S");
+        assert_eq!(b.replace_date(&leap).pretty(), "2024-02-29 08:05:00");
     }
 
     #[test]
-    fn test_ntp() {
-        let x = Ntp::now();
-        println!("{:#?}", x);
-        println!("{}", x.unix_ms());
-        println!("{}", x);
+    fn replace_across_offsets() {
+        let utc = "2024-01-05 23:30:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let ist = utc.change_tz("+05:30");
+        assert_eq!(ist.pretty(), "2024-01-06 05:00:00");
+
+        // the wall clock of the source is kept, in the offset of self
+        let mixed = ist.replace_time(&utc);
+        assert_eq!(mixed.pretty(), "2024-01-06 23:30:00");
+        assert_eq!(mixed.utc_offset(), ist.utc_offset());
+
+        let mixed = utc.replace_date(&ist);
+        assert_eq!(mixed.pretty(), "2024-01-06 23:30:00");
+        assert_eq!(mixed.utc_offset(), utc.utc_offset());
     }
 
     #[test]
-    fn strptime() {
-        let x = System::strptime("2015-02-18 23:16:09.234", "%Y-%m-%d %H:%M:%S%.3f");
-        println!("2015 - {}", x);
-        let x = Ntp::strptime("2021-01-01 00:00:00 +0000", "%Y-%m-%d %H:%M:%S %z");
-        println!("2021 - {}", x);
-        assert_eq!(x.unix(), 1609459200);
-        println!("1950 - {}", Ntp::strptime("1950-01-01 00:00:00", "%Y-%m-%d %H:%M:%S"))
+    fn etime_shapes() {
+        assert_eq!(parse_etime("03:21").unwrap().as_secs(), 3 * 60 + 21);
+        assert_eq!(parse_etime("45:12").unwrap().as_secs(), 45 * 60 + 12);
+        assert_eq!(parse_etime("02:03:04").unwrap().as_secs(), 2 * 3600 + 3 * 60 + 4);
+        assert_eq!(parse_etime(" 1-02:03:04 ").unwrap().as_secs(), 86400 + 2 * 3600 + 3 * 60 + 4);
+        assert_eq!(parse_etime(""), Err(ParseError::Empty));
+        assert!(parse_etime("1-03:21").is_err());
+        assert!(parse_etime("3:x1").is_err());
+        assert_eq!(parse_etime("61:00"), Err(ParseError::OutOfRange { field: "minutes", value: 61 }));
+        // fields too large to multiply out are rejected rather than wrapping
+        assert_eq!(parse_etime("18446744073709551615:00:00"), Err(ParseError::OutOfRange { field: "hours", value: i64::MAX }));
+        assert_eq!(parse_etime("00:18446744073709551615"), Err(ParseError::OutOfRange { field: "seconds", value: i64::MAX }));
+        assert!(matches!(parse_etime("18446744073709551615-00:00:00"), Err(ParseError::OutOfRange { field: "days", .. })));
     }
 
     #[test]
-    fn str_time() {
-        let date2017 = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-        println!("2017 - {}", date2017);
-        assert_eq!(date2017.unix(), 1483228800);
+    fn proc_starttime_ticks() {
+        let boot = 1704465989u64.unix::<System>();
+        let started = System::from_proc_starttime(12345, &boot, 100);
+        assert_eq!(started.unix_ms() - boot.unix_ms(), 123450);
+        assert_eq!(started.pretty(), "2024-01-05 14:48:32");
     }
 
+    #[cfg(target_os = "linux")]
     #[test]
-    fn time_diff() {
-        let x = "2015-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-        let y = "2017-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
-        println!("{} difference", y.diff(&x).ts_print());
-        println!("{} milliseconds difference", x.diff_ms(&y));
-        assert_eq!(x.diff(&y), 63158400u64);
+    fn own_process_age() {
+        let age = process_age(std::process::id()).unwrap();
+        assert!(age.as_secs() < 24 * 3600);
     }
 
     #[test]
-    fn int_ntp_time() {
-        assert_eq!(1483228800u32.unix::<Ntp>().pretty(), "2017-01-01 00:00:00");
-        assert_eq!(
-            131277024000000000u64.windows_ns::<Ntp>().pretty(),
-            "2017-01-01 00:00:00"
-        );
-        assert_eq!(
-            3787310789u64.mac_os::<Ntp>().pretty(),
-            "2024-01-05 14:46:29"
-        );
-        assert_eq!(
-            726158877u32.mac_os_cfa::<Ntp>().pretty(),
-            "2024-01-05 14:47:57"
-        );
-        assert_eq!(0u32.sas_4gl::<Ntp>().pretty(), "1960-01-01 00:00:00");
+    fn week_boundaries() {
+        let sat = "2024-01-06 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let sun = "2024-01-07 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+        let fri = "2024-01-05 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+
+        // ISO weeks
+        assert_eq!(sat.week_days(Weekday::Mon)[0].pretty(), "2024-01-01 00:00:00");
+        assert_eq!(sun.week_days(Weekday::Mon)[6].pretty(), "2024-01-07 00:00:00");
+        assert!(sat.same_week_as(&sun, Weekday::Mon));
+        assert!(sat.same_week_as(&fri, Weekday::Mon));
+
+        // US weeks
+        assert_eq!(sat.week_days(Weekday::Sun)[0].pretty(), "2023-12-31 00:00:00");
+        assert_eq!(sun.week_days(Weekday::Sun)[0].pretty(), "2024-01-07 00:00:00");
+        assert!(!sat.same_week_as(&sun, Weekday::Sun));
+        assert!(sat.same_week_as(&fri, Weekday::Sun));
+
+        // Saturday weeks
+        assert_eq!(sat.week_days(Weekday::Sat)[0].pretty(), "2024-01-06 00:00:00");
+        assert_eq!(sun.week_days(Weekday::Sat)[0].pretty(), "2024-01-06 00:00:00");
+        assert!(sat.same_week_as(&sun, Weekday::Sat));
+        assert!(!sat.same_week_as(&fri, Weekday::Sat));
+
+        let span = sat.week_span(Weekday::Sat);
+        assert_eq!(span.duration_ms(), 7 * 86400 * 1000);
+        assert_eq!(span.end.pretty(), "2024-01-13 00:00:00");
     }
 
     #[test]
-    fn int_system_time() {
-        assert_eq!(1483228800u32.unix::<System>().pretty(), "2017-01-01 00:00:00");
-        assert_eq!(
-            131277024000000000u64.windows_ns::<System>().pretty(),
-            "2017-01-01 00:00:00"
-        );
+    fn week_respects_offset() {
+        // 22:00 UTC on a Sunday is already Monday in +05:30
+        let x = "2024-01-07 22:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S").change_tz("+05:30");
+        assert_eq!(x.week_days(Weekday::Mon)[0].pretty(), "2024-01-08 00:00:00");
+        assert_eq!(x.week_days(Weekday::Mon)[0].utc_offset(), x.utc_offset());
+        assert_eq!(x.week_span(Weekday::Mon).end.utc_offset(), x.utc_offset());
+    }
+
+    #[test]
+    fn world_view_golden() {
+        let x = 1704465989u32.unix::<System>();
+        let view = x
+            .world_view()
+            .into_iter()
+            .map(|(tz, time)| format!("{} {} {}", tz.offset_str(), tz, time))
+            .collect::<Vec<String>>()
+            .join("\n");
         assert_eq!(
-            3787310789u64.mac_os::<System>().pretty(),
-            "2024-01-05 14:46:29"
+            view,
+            "-11:00 SAST 2024-01-05 03:46:29
+-10:00 HAST 2024-01-05 04:46:29
+-09:30 MART 2024-01-05 05:16:29
+-09:00 ALST 2024-01-05 05:46:29
+-08:00 PST 2024-01-05 06:46:29
+-07:00 MST 2024-01-05 07:46:29
+-06:00 CENST 2024-01-05 08:46:29
+-05:00 EST 2024-01-05 09:46:29
+-04:00 ATST/CLT 2024-01-05 10:46:29
+-03:30 NST 2024-01-05 11:16:29
+-03:00 BT/AT 2024-01-05 11:46:29
++00:00 UTC/WET 2024-01-05 14:46:29
++01:00 BST/CET 2024-01-05 15:46:29
++02:00 CEST/EET 2024-01-05 16:46:29
++03:00 EEST/AST 2024-01-05 17:46:29
++03:30 IRST 2024-01-05 18:16:29
++05:30 IST 2024-01-05 20:16:29
++05:45 NPT 2024-01-05 20:31:29
++06:30 MMT 2024-01-05 21:16:29
++07:00 ICT/WIB 2024-01-05 21:46:29
++08:00 CST/AWST/SST/HKT 2024-01-05 22:46:29
++09:00 JST/KST 2024-01-05 23:46:29
++09:30 ACST 2024-01-06 00:16:29
++10:00 AEST/CHST 2024-01-06 00:46:29
++10:30 LWST 2024-01-06 01:16:29
++12:00 NZST/FJT 2024-01-06 02:46:29
++12:45 CHAST 2024-01-06 03:31:29
++14:00 LINT 2024-01-06 04:46:29"
         );
+
         assert_eq!(
-            726158877u32.mac_os_cfa::<System>().pretty(),
-            "2024-01-05 14:47:57"
+            x.in_zones_fmt(&[Tz::NzstFjt, Tz::Nst], "%H:%M"),
+            vec![(Tz::NzstFjt, "02:46".to_string()), (Tz::Nst, "11:16".to_string())]
         );
-        assert_eq!(0u32.sas_4gl::<System>().pretty(), "1960-01-01 00:00:00");
+        assert_eq!(x.change_tz_seconds(19800).pretty(), x.change_tz("+05:30").pretty());
     }
 
     #[test]
-    fn windows_tests() {
-        let x = System::now();
-        println!("{} lots of 100ns since Windows epoch", x.windows_ns());
+    fn tz_offset_str_parsing() {
+        assert_eq!(Tz::from_offset_str("-03:30"), Ok(Tz::Nst));
+        assert_eq!(Tz::from_offset_str("+05:30"), Ok(Tz::Ist));
+        assert_eq!(Tz::from_offset_str("+5"), Err(TzParseError::InvalidFormat("+5".to_string())));
+        assert_eq!(Tz::from_offset_str("05:30"), Err(TzParseError::MissingSign("05:30".to_string())));
+        assert_eq!(Tz::from_offset_str("banana"), Err(TzParseError::InvalidFormat("banana".to_string())));
+        assert_eq!(Tz::from_offset_str("+01:75"), Err(TzParseError::OutOfRange("+01:75".to_string())));
+        assert_eq!(Tz::from_offset_str(""), Err(TzParseError::InvalidFormat("".to_string())));
+        assert_eq!(Tz::from_offset_str_opt("banana"), None);
+
+        let x = 1704465989u32.unix::<System>();
+        assert_eq!(Tz::try_offset_struct("-03:30", x.clone()).unwrap().pretty(), "2024-01-05 11:16:29");
+        assert_eq!(Tz::Nst.offset_struct(x.clone()).pretty(), "2024-01-05 11:16:29");
+        assert!(Tz::try_offset_struct("+5", x).is_err());
     }
 
     #[test]
-    fn mac_os() {
-        let x = System::now();
-        println!("{} seconds since Mac OS epoch", x.mac_os());
-        println!("{} seconds since Mac OS Absolute epoch", x.mac_os_cfa());
+    fn downsample_sine() {
+        let origin = 1704465600u32.unix::<System>();
+        let mut ds = Downsampler::new(origin.clone(), core::time::Duration::from_secs(60), 100);
+
+        // one sample every second for ten minutes
+        for i in 0..600u64 {
+            let t = System::from_epoch(origin.raw() + i * 1000);
+            assert!(ds.push(&t, (i as f64 / 30.0).sin()));
+        }
+
+        let buckets = ds.buckets();
+        assert_eq!(buckets.len(), 10);
+        for (n, bucket) in buckets.iter().enumerate() {
+            let values = (n as u64 * 60..(n as u64 + 1) * 60).map(|i| (i as f64 / 30.0).sin()).collect::<Vec<f64>>();
+            assert_eq!(bucket.start.unix(), origin.unix() + n as i64 * 60);
+            assert_eq!(bucket.count, 60);
+            assert_eq!(bucket.min, values.iter().cloned().fold(f64::INFINITY, f64::min));
+            assert_eq!(bucket.max, values.iter().cloned().fold(f64::NEG_INFINITY, f64::max));
+            assert!((bucket.sum - values.iter().sum::<f64>()).abs() < 1e-9);
+            assert_eq!(bucket.last, values[59]);
+            assert!((bucket.avg() - bucket.sum / 60.0).abs() < 1e-12);
+        }
     }
 
     #[test]
-    fn sas_4gl() {
-        let x = System::now();
-        println!("{} seconds since SAS 4GL epoch", x.sas_4gl());
+    fn downsample_out_of_order_and_eviction() {
+        let origin = 1704465600u32.unix::<System>();
+        let mut ds = Downsampler::new(origin.clone(), core::time::Duration::from_secs(10), 3);
+
+        assert!(ds.push(&origin.add_seconds(25), 1.0));
+        assert!(ds.push(&origin.add_seconds(5), 2.0));
+        // earlier than the latest sample in its bucket, so `last` stays put
+        assert!(ds.push(&origin.add_seconds(21), 3.0));
+
+        let buckets = ds.buckets();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start.unix(), origin.unix());
+        assert_eq!(buckets[1].count, 2);
+        assert_eq!(buckets[1].last, 1.0);
+        assert_eq!(buckets[1].min, 1.0);
+        assert_eq!(buckets[1].max, 3.0);
+
+        // moving to the 5th bucket evicts the first two
+        assert!(ds.push(&origin.add_seconds(45), 4.0));
+        let buckets = ds.buckets();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].start.unix(), origin.unix() + 20);
+        assert!(!ds.push(&origin.add_seconds(1), 5.0));
+        assert_eq!(ds.buckets().len(), 2);
     }
 
+    #[cfg(feature = "time")]
     #[test]
-    fn rfc3339_iso8601() {
-        let x = System::now();
-        println!("{}", x.iso8601());
-        println!("{}", x.rfc3339());
+    fn time_rs_round_trip() {
+        use time::{macros::datetime, OffsetDateTime};
+
+        let nepal = datetime!(2024-01-05 20:31:29.123_456 +05:45);
+        let x: System = nepal.into();
+        assert_eq!(x.unix_ms(), 1704465989123);
+        assert_eq!(x.utc_offset(), 5 * 3600 + 45 * 60);
+        let back = OffsetDateTime::try_from(x).unwrap();
+        assert_eq!(back, datetime!(2024-01-05 20:31:29.123 +05:45));
+        assert_eq!(back.offset(), nepal.offset());
+
+        let old = datetime!(1950-06-01 12:00:00.5 UTC);
+        let x: Ntp = old.into();
+        assert_eq!(x.unix_ms(), -618062400000 + 500);
+        assert_eq!(OffsetDateTime::try_from(x).unwrap(), old);
+
+        let y = System::from_epoch(0).add_duration(time::Duration::days(1));
+        assert_eq!(y.pretty(), "1601-01-02 00:00:00");
     }
 
     #[test]
-    fn strptime_rfc_and_iso() {
-        let x = "2017-01-01T00:00:00.000".strp_iso8601::<System>();
-        let y = "2017-01-01T00:00:00.000Z".strp_rf3339::<System>();
-        assert_eq!(x.unix(), 1483228800);
-        assert_eq!(y.unix(), 1483228800);
+    fn strptime_offset_assumptions() {
+        let naive = "2024-01-05 14:46:29";
+        let fmt = "%Y-%m-%d %H:%M:%S";
+
+        let utc = System::strptime(naive, fmt);
+        assert_eq!(utc.unix(), 1704465989);
+        assert_eq!(utc.offset_source(), OffsetSource::AssumedUtc);
+
+        let ist = Ntp::strptime_with_default_offset(naive, fmt, 19800);
+        assert_eq!(ist.unix(), 1704465989 - 19800);
+        assert_eq!(ist.utc_offset(), 19800);
+        assert_eq!(ist.offset_source(), OffsetSource::AssumedDefault);
+
+        let local = System::strptime_local(naive, fmt);
+        let local_offset = chrono::Local
+            .from_local_datetime(&NaiveDateTime::parse_from_str(naive, fmt).unwrap())
+            .earliest()
+            .unwrap()
+            .offset()
+            .local_minus_utc();
+        assert_eq!(local.unix(), 1704465989 - local_offset as i64);
+        assert_eq!(local.offset_source(), OffsetSource::AssumedLocal);
+
+        // an explicit offset always wins
+        let explicit = System::strptime_local("2024-01-05 14:46:29 -0500", "%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(explicit.unix(), 1704465989 + 5 * 3600);
+        assert_eq!(explicit.offset_source(), OffsetSource::Explicit);
+        assert_eq!(System::now().offset_source(), OffsetSource::Explicit);
+
+        // the same instant and offset compare equal, however the offset was arrived at
+        let stated = System::strptime("2024-01-05 14:46:29 +0000", "%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(utc, stated);
+        assert_eq!(utc.cmp(&stated), core::cmp::Ordering::Equal);
+        let ist_stated = Ntp::strptime("2024-01-05 14:46:29 +0530", "%Y-%m-%d %H:%M:%S %z");
+        assert_eq!(ist, ist_stated);
+        assert_eq!(ist.cmp(&ist_stated), core::cmp::Ordering::Equal);
+        assert!(utc.change_tz_seconds(3600) != stated);
     }
 
     #[test]
-    fn tz_tests() {
-        let x = Ntp::now();
-        // println!("{}", x.tz_offset());
-        // println!("{}", x);
-        println!("{:#?}", x);
-        println!("{}", x.change_tz("+01:00"));
-        println!("{}", x.change_tz("-01:00"));
+    fn serde_wire_format() {
+        let x = Ntp::strptime_with_default_offset("2024-01-05 14:46:29.123", "%Y-%m-%d %H:%M:%S%.3f", 3600);
+        let json = serde_json::to_string(&x).unwrap();
+        assert_eq!(json, r#"{"v":1,"ms1601":13348935989123,"offset":3600,"server":"strptime"}"#);
+        let back: Ntp = serde_json::from_str(&json).unwrap();
+        assert_eq!((back.raw(), back.utc_offset(), back.server()), (x.raw(), x.utc_offset(), x.server()));
+
+        let y = System::from_epoch_offset(13346939189123, -19800);
+        let json = serde_json::to_string(&y).unwrap();
+        assert_eq!(json, r#"{"v":1,"ms1601":13346939189123,"offset":-19800}"#);
+        assert_eq!(serde_json::from_str::<System>(&json).unwrap(), y);
+
+        let bytes = bincode::serialize(&x).unwrap();
+        let back: Ntp = bincode::deserialize(&bytes).unwrap();
+        assert_eq!((back.raw(), back.utc_offset(), back.server()), (x.raw(), x.utc_offset(), x.server()));
+        assert_eq!(bincode::deserialize::<System>(&bincode::serialize(&y).unwrap()).unwrap(), y);
+
+        assert!(serde_json::from_str::<System>(r#"{"v":2,"ms1601":0,"offset":0}"#).is_err());
+        assert!(serde_json::from_str::<Ntp>(r#"{"v":1,"ms1601":0,"offset":0}"#).is_err());
     }
 
     #[test]
-    fn test_add_seconds() {
-        let x = System::now();
-        println!("{}", x.add_seconds(3600));
-    } 
+    fn serde_legacy_format() {
+        let legacy = r#"{"inner_secs":13348939589,"inner_milliseconds":123,"server":"pool.ntp.org","utc_offset":0}"#;
+        let x: Ntp = serde_json::from_str(legacy).unwrap();
+        assert_eq!(x.unix_ms(), 1704465989123);
+        assert_eq!(x.server(), "pool.ntp.org");
 
-    #[test]
-    fn test_add_minshoursdaysweeks() {
-        let x = System::now();
-        println!("{}", x.add_minutes(60));
-        println!("{}", x.add_hours(24));
-        println!("{}", x.add_days(7));
-        println!("{}", x.add_weeks(52));
+        let legacy = r#"{"inner_secs":13348939589,"inner_milliseconds":123,"utc_offset":3600}"#;
+        let x: System = serde_json::from_str(legacy).unwrap();
+        assert_eq!(x.unix_ms(), 1704465989123);
+        assert_eq!(x.utc_offset(), 3600);
+
+        // the structs as they were derived before the versioned format, for formats that write fields in order
+        #[derive(::serde::Serialize)]
+        struct LegacySystem {
+            inner_secs: u64,
+            inner_milliseconds: u64,
+            utc_offset: i32,
+        }
+        #[derive(::serde::Serialize)]
+        struct LegacyNtp {
+            inner_secs: u64,
+            inner_milliseconds: u64,
+            server: String,
+            utc_offset: i32,
+        }
+        let old = LegacySystem { inner_secs: 13348939589, inner_milliseconds: 123, utc_offset: 3600 };
+        let x: System = bincode::deserialize(&bincode::serialize(&old).unwrap()).unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704465989123, 3600));
+        let old = LegacyNtp { inner_secs: 13348939589, inner_milliseconds: 123, server: "pool.ntp.org".to_string(), utc_offset: -18000 };
+        let x: Ntp = bincode::deserialize(&bincode::serialize(&old).unwrap()).unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset(), x.server().as_str()), (1704465989123, -18000, "pool.ntp.org"));
+        // seconds ending in the byte 0x01 used to be mistaken for the version
+        let colliding = LegacySystem { inner_secs: 13348939777, inner_milliseconds: 123, utc_offset: 3600 };
+        assert_eq!(colliding.inner_secs & 0xff, 1);
+        let x: System = bincode::deserialize(&bincode::serialize(&colliding).unwrap()).unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704466177123, 3600));
+        // and a varint encoding reads both formats too
+        use bincode::Options;
+        let varint = bincode::DefaultOptions::new();
+        let x: System = varint.deserialize(&varint.serialize(&colliding).unwrap()).unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704466177123, 3600));
+        let y = System::from_epoch_offset(13348939777123, -19800);
+        assert_eq!(varint.deserialize::<System>(&varint.serialize(&y).unwrap()).unwrap(), y);
+        let n = Ntp::from_epoch_offset(13348939777123, 3600);
+        let back: Ntp = varint.deserialize(&varint.serialize(&n).unwrap()).unwrap();
+        assert_eq!((back.raw(), back.utc_offset(), back.server()), (n.raw(), n.utc_offset(), n.server()));
+
+        let x: System = serde_json::from_str("[13348939589,123,3600]").unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704465989123, 3600));
+        let x: Ntp = serde_json::from_str(r#"[13348939589,123,"pool.ntp.org",0]"#).unwrap();
+        assert_eq!((x.unix_ms(), x.server().as_str()), (1704465989123, "pool.ntp.org"));
+        // while a leading version reads as the current format
+        let x: System = serde_json::from_str("[1,13348939589123,3600]").unwrap();
+        assert_eq!((x.unix_ms(), x.utc_offset()), (1704465989123, 3600));
     }
 
+    #[cfg(feature = "sysclock")]
     #[test]
-    fn long_ago_dates() {
-        let x = System::from_epoch(0);
-        println!("{}", x);
-        println!("{}", x.unix());
-        println!("{}", x.strftime("%Y-%m-%d %H:%M:%S"));
+    fn sysclock_dry_run() {
+        use sysclock::*;
+        let now = 1704465989u32.unix::<System>();
+        let ntp = Ntp::from_epoch(now.raw() + 1500);
+
+        assert_eq!(clock_delta_ms(&ntp, &now), 1500);
+        assert_eq!(clock_delta_ms(&now, &ntp), -1500);
+        assert_eq!(plan_step(&ntp, &now, core::time::Duration::from_secs(2)), None);
+        assert_eq!(plan_step(&ntp, &now, core::time::Duration::from_millis(1500)), None);
+        assert_eq!(plan_step(&ntp, &now, core::time::Duration::from_millis(1499)), Some(1500));
+        assert_eq!(plan_step(&now, &ntp, chrono::Duration::seconds(1)), Some(-1500));
+        assert!(set_system_time_dry_run(&System::now()).abs() < 1000);
     }
 
+    #[cfg(feature = "sysclock")]
     #[test]
-    fn test_past_future() {
-        let x = "2029-01-01T00:00:00.000".strp_iso8601::<System>();
-        let y = "2017-01-01T00:00:00.000Z".strp_rf3339::<System>();
-        println!("{} is in the {}", x, x.past_future(&y));
-        println!("{} is in the {}", y, y.past_future(&x));
+    #[ignore = "actually sets the system clock"]
+    fn sysclock_set() {
+        match sysclock::set_system_time(&System::now()) {
+            Ok(()) | Err(sysclock::SysClockError::PermissionDenied) => {}
+            Err(e) => panic!("{}", e),
+        }
     }
 
+    #[cfg(feature = "solar")]
     #[test]
-    fn test_add_duration() {
-        let x = System::now();
-        
-        println!("{}", x.add_duration(std::time::Duration::from_secs(3600)));
+    fn solar_known_values() {
+        fn check(day: &str, expected: &str, actual: Option<System>) {
+            let expected = format!("{} {}", &day[..10], expected).parse_time::<System>("%Y-%m-%d %H:%M %z");
+            let actual = actual.unwrap();
+            assert!((actual.unix() - expected.unix()).abs() <= 180, "{} vs {}", actual, expected);
+            assert_eq!(actual.utc_offset(), expected.utc_offset());
+        }
+        let cases = [
+            // London, BST in summer
+            ("2024-06-21 12:00 +01:00", 51.5074, -0.1278, "04:43 +01:00", "21:21 +01:00"),
+            ("2024-12-21 12:00 +00:00", 51.5074, -0.1278, "08:04 +00:00", "15:53 +00:00"),
+            // Singapore, barely changes over the year
+            ("2024-06-21 12:00 +08:00", 1.3521, 103.8198, "07:00 +08:00", "19:13 +08:00"),
+            ("2024-12-21 12:00 +08:00", 1.3521, 103.8198, "07:01 +08:00", "19:04 +08:00"),
+        ];
+        for (day, lat, lon, rise, set) in cases {
+            let date = day.parse_time::<System>("%Y-%m-%d %H:%M %z");
+            check(day, rise, solar::sunrise(&date, lat, lon));
+            check(day, set, solar::sunset(&date, lat, lon));
+            let noon = solar::solar_noon(&date, lat, lon).unwrap();
+            assert!(noon > solar::sunrise(&date, lat, lon).unwrap() && noon < solar::sunset(&date, lat, lon).unwrap());
+        }
 
-        println!("{}", x.add_duration(chrono::Duration::seconds(3600)));
+        // Tromsø is in polar night in early January, and midnight sun in June
+        let jan = "2024-01-05 12:00 +01:00".parse_time::<System>("%Y-%m-%d %H:%M %z");
+        assert!(solar::sunrise(&jan, 69.6492, 18.9553).is_none());
+        assert!(solar::sunset(&jan, 69.6492, 18.9553).is_none());
+        assert!(solar::day_length(&jan, 69.6492, 18.9553).is_none());
+        let jun = "2024-06-21 12:00 +02:00".parse_time::<System>("%Y-%m-%d %H:%M %z");
+        assert!(solar::sunrise(&jun, 69.6492, 18.9553).is_none());
     }
+
     #[test]
-    fn test_local() {
-        let x = System::now();
-        println!("{}", x.local());
+    fn zoned_conversion() {
+        let x = 1704465989u32.unix::<System>();
+        let ist = x.in_zone(Tz::Ist);
+        assert_eq!(ist.to_string(), "2024-01-05 20:16:29 IST");
+        assert_eq!((ist.day(), ist.hour(), ist.minute()), (5, 20, 16));
+
+        let pst = ist.clone().to_zone(Tz::Pst);
+        assert_eq!(pst.to_string(), "2024-01-05 06:46:29 PST");
+        assert_eq!(pst.instant().unix_ms(), x.unix_ms());
+        assert_eq!(pst.clone().to_zone(Tz::Ist), ist);
+        assert_eq!(pst.weekday(), Weekday::Fri);
+
+        let nz = x.add_hours(-10).in_zone(Tz::NzstFjt);
+        assert!(nz < pst);
+        assert_eq!(nz.day(), 5);
+        assert_eq!(Zoned::from(x.clone()).zone(), Tz::UtcWet);
+
+        let json = serde_json::to_string(&ist).unwrap();
+        assert_eq!(json, r#"{"instant":{"v":1,"ms1601":13348939589000,"offset":0},"zone":"Ist"}"#);
+        let back: Zoned<System> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_string(), ist.to_string());
     }
 
     #[test]
-    fn test_tz_enum() {
-        let x = System::now().change_tz("+08:00");
-        println!("{}", x.tz_enum().unwrap_or_default());
-        println!("{}", Tz::from_offset(3600).unwrap_or_default());
-        println!("{}", Tz::from_offset(0).unwrap_or_default()); // Some(UtcWet)
-        println!("{}", Tz::from_offset(3600).unwrap_or_default()); // Some(BstCet)
-        println!("{}", Tz::from_offset(7200).unwrap_or_default()); // Some(CestEet)
-        println!("{}", Tz::from_offset(123456).unwrap_or_default()); // None
-        println!("{}", Tz::Acst.offset_struct(System::now()));
+    fn iso_lenient() {
+        for s in ["2024-01-05T14:46:29", "2024-01-05 14:46:29", "2024-01-05_14:46:29", "2024-01-05t14:46:29Z", " 2024-01-05  14:46:29 "] {
+            assert_eq!(s.strp_iso_lenient::<System>().unwrap().unix(), 1704465989, "{}", s);
+        }
+        let x = "2024-01-05T14:46:29,789".strp_iso_lenient::<Ntp>().unwrap();
+        assert_eq!(x.unix_ms(), 1704465989789);
+        assert_eq!("2024-01-05T14:46:29.5".strp_iso_lenient::<System>().unwrap().unix_ms(), 1704465989500);
+        assert_eq!("2024-01-05T14:46:29.123456".strp_iso_lenient::<System>().unwrap().unix_ms(), 1704465989123);
+
+        assert_eq!("2024-01-05T14".strp_iso_lenient::<System>().unwrap().pretty(), "2024-01-05 14:00:00");
+        assert_eq!("2024-01-05T14:46".strp_iso_lenient::<System>().unwrap().pretty(), "2024-01-05 14:46:00");
+        assert_eq!("2024-01-05".strp_iso_lenient::<System>().unwrap().pretty(), "2024-01-05 00:00:00");
+
+        let x = "2024-01-05T20:16:29+05:30".strp_iso_lenient::<System>().unwrap();
+        assert_eq!((x.unix(), x.utc_offset()), (1704465989, 19800));
+        assert_eq!("2024-01-05T09:46:29-0500".strp_iso_lenient::<System>().unwrap().unix(), 1704465989);
+
+        assert_eq!(
+            "2024-13-05T14".strp_iso_lenient::<System>(),
+            Err(ParseError::invalid("2024-13-05T14", 5, "month 01-12"))
+        );
+        assert_eq!(
+            "2024-01-05T25:00".strp_iso_lenient::<System>(),
+            Err(ParseError::invalid("2024-01-05T25:00", 11, "hour 00-23"))
+        );
+        assert_eq!(
+            "2023-02-29".strp_iso_lenient::<System>(),
+            Err(ParseError::invalid("2023-02-29", 8, "a day that exists in the month"))
+        );
+        assert!("2024-01-05T14:4".strp_iso_lenient::<System>().is_err());
+        assert!("2024-01-05T14:46:29.".strp_iso_lenient::<System>().is_err());
+        assert!("2024-01-05T14:46:29 junk".strp_iso_lenient::<System>().is_err());
+        assert_eq!("".strp_iso_lenient::<System>(), Err(ParseError::Empty));
     }
 
     #[test]
-    fn huge_number() {
-        let x = System::strptime("+262143-01-01 00:00:00", "%Y-%m-%d %H:%M:%S");
-        println!("{}", x);
+    fn ntp_packet_round_trip() {
+        let packet = NtpPacket {
+            li: 0,
+            vn: 4,
+            mode: 4,
+            stratum: 2,
+            poll: 3,
+            precision: -23,
+            root_delay: 0x0000_1a2b,
+            root_dispersion: 0x0000_0800,
+            ref_id: u32::from_be_bytes([192, 168, 1, 1]),
+            reference: NtpTimestamp::from_unix_ms(1704465900000),
+            origin: NtpTimestamp::from_unix_ms(1704465989000),
+            receive: NtpTimestamp::from_unix_ms(1704465989123),
+            transmit: NtpTimestamp::from_unix_ms(1704465989124),
+        };
+        let bytes = packet.to_bytes().unwrap();
+        assert_eq!(bytes[0], 0x24);
+        assert_eq!(NtpPacket::from_bytes(&bytes).unwrap(), packet);
+        assert_eq!(packet.transmit.to_unix_ms(), 1704465989124);
+
+        assert_eq!(
+            packet.hexdump(),
+            "00: 24                       li=0 vn=4 mode=4
+01: 02                       stratum=2
+02: 03                       poll=3
+03: e9                       precision=-23
+04: 00 00 1a 2b              root_delay=0.102219s
+08: 00 00 08 00              root_dispersion=0.031250s
+0c: c0 a8 01 01              ref_id=192.168.1.1
+10: e9 42 94 6c 00 00 00 00  reference=3913454700.000000000
+18: e9 42 94 c5 00 00 00 00  origin=3913454789.000000000
+20: e9 42 94 c5 1f 7c ed 92  receive=3913454789.123000000
+28: e9 42 94 c5 1f be 76 c9  transmit=3913454789.124000000
+"
+        );
+        // the fraction is binary: the top bit alone is half a second, and it never rounds up into the next second
+        let half = NtpPacket { transmit: NtpTimestamp { seconds: 1, fraction: 1 << 31 }, ..packet };
+        assert!(half.hexdump().ends_with("transmit=1.500000000\n"));
+        let almost = NtpPacket { transmit: NtpTimestamp { seconds: 1, fraction: u32::MAX }, ..packet };
+        assert!(almost.hexdump().ends_with("transmit=1.999999999\n"));
+
+        assert!(matches!(NtpPacket::from_bytes(&bytes[..47]), Err(NtpError::ShortPacket(47))));
+        let mut bad = bytes;
+        bad[0] = 0x3c; // version 7
+        assert!(matches!(NtpPacket::from_bytes(&bad), Err(NtpError::InvalidField { field: "vn", value: 7 })));
+        assert!(NtpPacket { mode: 8, ..packet }.to_bytes().is_err());
+        // both directions accept exactly versions 1-4
+        for vn in [1, 4] {
+            let at_limit = NtpPacket { vn, ..packet };
+            assert_eq!(NtpPacket::from_bytes(&at_limit.to_bytes().unwrap()).unwrap(), at_limit);
+        }
+        for vn in [0, 5, 7, 8] {
+            assert!(matches!(NtpPacket { vn, ..packet }.to_bytes(), Err(NtpError::InvalidField { field: "vn", .. })));
+        }
+        assert!(NtpPacket::default().hexdump().starts_with("invalid packet"));
+        assert_eq!(NtpPacket::client_request().to_bytes().unwrap()[0], 0x1b);
     }
 
     #[test]
@@ -855,3 +7176,53 @@ mod test {
         println!("{:#?}", x.cast::<Ntp>());
     }
 }
+
+#[cfg(test)]
+mod feature_matrix {
+    use super::*;
+
+    #[test]
+    fn core_without_features() {
+        let x = 1704465989u32.unix::<System>();
+        assert_eq!(x.pretty(), "2024-01-05 14:46:29");
+        assert_eq!(x.change_tz("+05:30").strftime("%H:%M %z"), "20:16 +0530");
+        assert_eq!("2024-01-05 14:46:29".parse_time::<System>("%Y-%m-%d %H:%M:%S").unix(), x.unix());
+        assert_eq!("2024-01-05T14:46:29Z".strp_iso_lenient::<Ntp>().unwrap().unix(), 1704465989);
+        assert_eq!(x.add_days(1).unix() - x.unix(), 86400);
+        assert_eq!(x.cast::<Ntp>().unix_ms(), x.unix_ms());
+        assert!(System::now() > x);
+    }
+
+    #[cfg(not(feature = "ntp-client"))]
+    #[test]
+    fn ntp_falls_back_to_the_system_clock() {
+        let before = System::now();
+        let now = Ntp::now();
+        assert_eq!(now.server(), "chrono::Utc");
+        assert!(now.last_packet().is_none() && now.peer_addr().is_none());
+        assert!((now.unix() - before.unix()).abs() <= 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_enabled() {
+        let x = 1704465989u32.unix::<System>();
+        let json = serde_json::to_string(&x).unwrap();
+        assert_eq!(serde_json::from_str::<System>(&json).unwrap(), x);
+    }
+
+    #[cfg(feature = "timezones")]
+    #[test]
+    fn timezones_enabled() {
+        let x = 1704465989u32.unix::<System>();
+        assert_eq!(x.in_zone(Tz::Ist).to_string(), format!("{} IST", x.change_tz_seconds(19800).pretty()));
+        assert_eq!(now_in(Tz::Ist).utc_offset(), 19800);
+    }
+
+    #[cfg(feature = "ntp-client")]
+    #[test]
+    fn ntp_client_enabled() {
+        let config = NtpConfig::new("127.0.0.1").port(9).timeout(core::time::Duration::from_millis(200)).srv(false);
+        assert!(Ntp::with_config(&config).is_err());
+    }
+}
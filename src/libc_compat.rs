@@ -0,0 +1,232 @@
+//! Free functions mirroring the C time API, for porting C code
+//!
+//! The names have a trailing underscore so they don't clash with the `libc` crate. Fields follow C's conventions exactly: `tm_year` counts
+//! from 1900, `tm_mon` and `tm_yday` count from 0, `tm_wday` counts from Sunday, and times are whole seconds since 1970 as an `i64`
+//!
+//! # Examples
+//! ```rust
+//! use thetime::libc_compat::{gmtime_, strftime_, timegm_};
+//! let tm = gmtime_(1704465989);
+//! assert_eq!((tm.tm_year, tm.tm_mon, tm.tm_mday), (124, 0, 5));
+//! assert_eq!(strftime_("%Y-%m-%d %H:%M:%S", &tm), "2024-01-05 14:46:29");
+//! assert_eq!(timegm_(&tm), 1704465989);
+//! ```
+
+use crate::{System, Time};
+use chrono::format::{Parsed, StrftimeItems};
+use chrono::{DateTime, Datelike, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+
+/// The fields of C's `struct tm`, with the same names and conventions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TmLike {
+    /// seconds after the minute, 0-60 (60 for a leap second)
+    pub tm_sec: i32,
+    /// minutes after the hour, 0-59
+    pub tm_min: i32,
+    /// hours since midnight, 0-23
+    pub tm_hour: i32,
+    /// day of the month, 1-31
+    pub tm_mday: i32,
+    /// months since January, 0-11
+    pub tm_mon: i32,
+    /// years since 1900
+    pub tm_year: i32,
+    /// days since Sunday, 0-6
+    pub tm_wday: i32,
+    /// days since January 1, 0-365
+    pub tm_yday: i32,
+    /// positive if daylight saving time is in effect, 0 if not, negative if unknown
+    pub tm_isdst: i32,
+    /// seconds east of UTC (the glibc and BSD extension)
+    pub tm_gmtoff: i64,
+}
+
+/// internal only - the fields of a wall clock
+fn tm_of(wall: NaiveDateTime, gmtoff: i64, isdst: i32) -> TmLike {
+    TmLike {
+        tm_sec: wall.second() as i32,
+        tm_min: wall.minute() as i32,
+        tm_hour: wall.hour() as i32,
+        tm_mday: wall.day() as i32,
+        tm_mon: wall.month0() as i32,
+        tm_year: wall.year() - 1900,
+        tm_wday: wall.weekday().num_days_from_sunday() as i32,
+        tm_yday: wall.ordinal0() as i32,
+        tm_isdst: isdst,
+        tm_gmtoff: gmtoff,
+    }
+}
+
+/// internal only - the UTC wall clock of a Unix time, clamped to what chrono can represent
+fn utc_wall(t: i64) -> NaiveDateTime {
+    let t = t.clamp(DateTime::<Utc>::MIN_UTC.timestamp(), DateTime::<Utc>::MAX_UTC.timestamp());
+    DateTime::from_timestamp(t, 0).unwrap().naive_utc()
+}
+
+/// internal only - days from 1970-01-01 to the first of a month, for any year (Howard Hinnant's `days_from_civil`)
+fn days_from_civil(year: i64, month: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// internal only - the seconds since 1970 of the wall clock in `tm` read as UTC, normalizing out of range fields
+fn wall_secs(tm: &TmLike) -> i64 {
+    let months = tm.tm_year as i64 * 12 + tm.tm_mon as i64;
+    let days = days_from_civil(1900 + months.div_euclid(12), months.rem_euclid(12) + 1) + tm.tm_mday as i64 - 1;
+    days * 86400 + tm.tm_hour as i64 * 3600 + tm.tm_min as i64 * 60 + tm.tm_sec as i64
+}
+
+/// internal only - the local offset in force at a UTC wall clock
+fn local_offset(utc: &NaiveDateTime) -> i32 {
+    Local.offset_from_utc_datetime(utc).local_minus_utc()
+}
+
+/// C's `time(NULL)`: the current time, in seconds since 1970 (respecting the mock clock)
+pub fn time_() -> i64 {
+    System::now().unix_ms().div_euclid(1000)
+}
+
+/// C's `gmtime`: the UTC fields of `t` (seconds since 1970). `tm_isdst` and `tm_gmtoff` are 0
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::gmtime_;
+/// let tm = gmtime_(-1);
+/// assert_eq!((tm.tm_year, tm.tm_mon, tm.tm_mday, tm.tm_hour, tm.tm_wday, tm.tm_yday), (69, 11, 31, 23, 3, 364));
+/// ```
+pub fn gmtime_(t: i64) -> TmLike {
+    tm_of(utc_wall(t), 0, 0)
+}
+
+/// C's `localtime`: the fields of `t` (seconds since 1970) in the system's local timezone. `tm_isdst` is 1 if the offset is ahead of
+/// the lowest offset the zone uses in January or July of that year, else 0
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::{localtime_, mktime_};
+/// let tm = localtime_(1704465989);
+/// assert_eq!(mktime_(&tm), 1704465989);
+/// ```
+pub fn localtime_(t: i64) -> TmLike {
+    let utc = utc_wall(t);
+    let offset = local_offset(&utc);
+    let standard = [1, 7]
+        .iter()
+        .filter_map(|month| NaiveDate::from_ymd_opt(utc.year(), *month, 1))
+        .map(|d| local_offset(&d.and_time(chrono::NaiveTime::MIN)))
+        .min()
+        .unwrap_or(offset);
+    let wall = utc.checked_add_signed(chrono::Duration::seconds(offset as i64)).unwrap_or(utc);
+    tm_of(wall, offset as i64, (offset > standard) as i32)
+}
+
+/// The inverse of `gmtime_` (the common `timegm` extension): the seconds since 1970 of the UTC wall clock in `tm`. Out of range fields
+/// are normalized as `mktime_` does, and `tm_wday`, `tm_yday`, `tm_isdst` and `tm_gmtoff` are ignored
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::{timegm_, TmLike};
+/// // February 0th 2024 is January 31st
+/// let tm = TmLike { tm_year: 124, tm_mon: 1, tm_mday: 0, ..Default::default() };
+/// assert_eq!(timegm_(&tm), 1706659200);
+/// ```
+pub fn timegm_(tm: &TmLike) -> i64 {
+    wall_secs(tm)
+}
+
+/// C's `mktime`: the seconds since 1970 of the local wall clock in `tm`
+///
+/// Out of range fields are normalized as C does (month 12 is January of the next year, day 0 is the last day of the previous month,
+/// 90 minutes is an hour and a half), and `tm_wday` and `tm_yday` are ignored. A wall clock repeated when the clocks go back is read as
+/// daylight saving time unless `tm_isdst` is 0, and one skipped when they go forward is read with the offset from before the change.
+/// Unlike C, `tm` is not updated - call `localtime_` on the result for the normalized fields
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::{localtime_, mktime_, TmLike};
+/// let tm = TmLike { tm_year: 123, tm_mon: 13, tm_mday: 0, tm_min: 90, ..Default::default() };
+/// let normalized = localtime_(mktime_(&tm));
+/// assert_eq!((normalized.tm_year, normalized.tm_mon, normalized.tm_mday, normalized.tm_hour, normalized.tm_min), (124, 0, 31, 1, 30));
+/// ```
+pub fn mktime_(tm: &TmLike) -> i64 {
+    let secs = wall_secs(tm);
+    let wall = utc_wall(secs);
+    match Local.from_local_datetime(&wall) {
+        LocalResult::Single(t) => t.timestamp(),
+        LocalResult::Ambiguous(dst, standard) => if tm.tm_isdst == 0 { standard } else { dst }.timestamp(),
+        LocalResult::None => secs - local_offset(&(wall - chrono::Duration::days(1))) as i64,
+    }
+}
+
+/// C's `strftime`: format the fields of `tm` (normalized as `timegm_` does) with chrono's strftime syntax, `%z` giving `tm_gmtoff`.
+/// Returns an empty string for a format that can't be used, as C returns 0
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::{strftime_, TmLike};
+/// let tm = TmLike { tm_year: 124, tm_mon: 0, tm_mday: 5, tm_hour: 14, tm_gmtoff: 3600, ..Default::default() };
+/// assert_eq!(strftime_("%a %d %b %Y %H:%M %z", &tm), "Fri 05 Jan 2024 14:00 +0100");
+/// assert_eq!(strftime_("%Y-%", &tm), "");
+/// ```
+pub fn strftime_(format: &str, tm: &TmLike) -> String {
+    use core::fmt::Write;
+    let wall = utc_wall(wall_secs(tm));
+    let items = StrftimeItems::new(format);
+    let mut out = String::new();
+    let written = match i32::try_from(tm.tm_gmtoff).ok().and_then(FixedOffset::east_opt).and_then(|o| o.from_local_datetime(&wall).single()) {
+        Some(local) => write!(out, "{}", local.format_with_items(items)),
+        None => write!(out, "{}", wall.format_with_items(items)),
+    };
+    if written.is_err() {
+        out.clear();
+    }
+    out
+}
+
+/// C's `strptime`: parse the start of `s` with chrono's strftime syntax, returning the fields found and how many bytes were used (C
+/// returns a pointer to the first unused character). Fields not in the format are 0, except that `tm_wday` and `tm_yday` are worked out
+/// from a full date, and `%z` sets `tm_gmtoff`. None if `s` doesn't match
+///
+/// # Examples
+/// ```rust
+/// use thetime::libc_compat::strptime_;
+/// let (tm, used) = strptime_("2024-02-29 12:00:00 and the rest", "%Y-%m-%d %H:%M:%S").unwrap();
+/// assert_eq!((tm.tm_year, tm.tm_mon, tm.tm_mday, tm.tm_hour, tm.tm_wday, tm.tm_yday), (124, 1, 29, 12, 4, 59));
+/// assert_eq!(used, 19);
+/// assert!(strptime_("2024-02-30", "%Y-%m-%d").is_none());
+/// ```
+pub fn strptime_(s: &str, format: &str) -> Option<(TmLike, usize)> {
+    let mut parsed = Parsed::new();
+    let rest = chrono::format::parse_and_remainder(&mut parsed, s, StrftimeItems::new(format)).ok()?;
+
+    let mut tm = TmLike::default();
+    if let Some(year) = parsed.year() {
+        tm.tm_year = year - 1900;
+    }
+    if let Some(month) = parsed.month() {
+        tm.tm_mon = month as i32 - 1;
+    }
+    if let Some(day) = parsed.day() {
+        tm.tm_mday = day as i32;
+    }
+    tm.tm_hour = parsed.hour_div_12().unwrap_or(0) as i32 * 12 + parsed.hour_mod_12().unwrap_or(0) as i32;
+    tm.tm_min = parsed.minute().unwrap_or(0) as i32;
+    tm.tm_sec = parsed.second().unwrap_or(0) as i32;
+    tm.tm_gmtoff = parsed.offset().unwrap_or(0) as i64;
+    if let Some(weekday) = parsed.weekday() {
+        tm.tm_wday = weekday.num_days_from_sunday() as i32;
+    }
+    if let Some(ordinal) = parsed.ordinal() {
+        tm.tm_yday = ordinal as i32 - 1;
+    }
+    if let (Some(year), Some(month), Some(day)) = (parsed.year(), parsed.month(), parsed.day()) {
+        let date = NaiveDate::from_ymd_opt(year, month, day)?;
+        tm.tm_wday = date.weekday().num_days_from_sunday() as i32;
+        tm.tm_yday = date.ordinal0() as i32;
+    }
+    Some((tm, s.len() - rest.len()))
+}
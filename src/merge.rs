@@ -0,0 +1,137 @@
+use crate::{MergeError, Time};
+use core::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// A k-way merge of sorted streams, from `merge_sorted` and friends
+///
+/// Only one item per stream is held at a time, so memory use does not grow with the length of the streams
+pub struct MergeSorted<I: Iterator> {
+    streams: Vec<I>,
+    /// the next item of each stream, waiting in the heap
+    pending: Vec<Option<I::Item>>,
+    /// (key, stream) for every pending item - smallest key first, then lowest stream index
+    heap: BinaryHeap<Reverse<(i64, usize)>>,
+    key: fn(&I::Item) -> i64,
+    /// in checked mode, the last key taken from each stream and how many items each has given
+    last: Option<Vec<(i64, usize)>>,
+    errors: VecDeque<MergeError>,
+}
+
+impl<I: Iterator> MergeSorted<I> {
+    fn new(streams: Vec<I>, key: fn(&I::Item) -> i64, checked: bool) -> Self {
+        let count = streams.len();
+        let mut merge = MergeSorted {
+            streams,
+            pending: (0..count).map(|_| None).collect(),
+            heap: BinaryHeap::with_capacity(count),
+            key,
+            last: checked.then(|| vec![(i64::MIN, 0); count]),
+            errors: VecDeque::new(),
+        };
+        for source in 0..count {
+            merge.refill(source);
+        }
+        merge
+    }
+
+    /// pull the next item from `source` into the heap, recording (and skipping) any that go backwards in checked mode
+    fn refill(&mut self, source: usize) {
+        for item in self.streams[source].by_ref() {
+            let key = (self.key)(&item);
+            if let Some(last) = self.last.as_mut() {
+                let (previous, position) = &mut last[source];
+                *position += 1;
+                if key < *previous {
+                    self.errors.push_back(MergeError::Unsorted {
+                        source,
+                        position: *position - 1,
+                    });
+                    continue;
+                }
+                *previous = key;
+            }
+            self.pending[source] = Some(item);
+            self.heap.push(Reverse((key, source)));
+            return;
+        }
+    }
+
+    /// the next item in global order, tagged with its stream
+    fn next_item(&mut self) -> Option<(usize, I::Item)> {
+        let Reverse((_, source)) = self.heap.pop()?;
+        let item = self.pending[source].take()?;
+        self.refill(source);
+        Some((source, item))
+    }
+}
+
+impl<I: Iterator> Iterator for MergeSorted<I> {
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_item()
+    }
+}
+
+/// A checked k-way merge, from `merge_sorted_checked` - yields an error in place of any item that is earlier than the one before it in its own stream
+pub struct MergeSortedChecked<I: Iterator>(MergeSorted<I>);
+
+impl<I: Iterator> Iterator for MergeSortedChecked<I> {
+    type Item = Result<(usize, I::Item), MergeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.0.errors.pop_front() {
+            return Some(Err(error));
+        }
+        // refilling after this item may find out of order items, which are reported on the following calls
+        self.0.next_item().map(Ok)
+    }
+}
+
+/// Merge streams that are each sorted by time into one stream in global order, tagging every item with the index of its stream
+///
+/// Items are ordered by instant (not wall clock); equal instants come out in stream order, and in their original order within a stream.
+/// If a stream is not actually sorted, every item is still yielded exactly once, but the output is not sorted either - use
+/// `merge_sorted_checked` to detect this
+///
+/// # Examples
+/// ```rust
+/// use thetime::{merge_sorted, System, Time, IntTime};
+/// let a = vec![1u32.unix::<System>(), 4u32.unix::<System>()];
+/// let b = vec![2u32.unix::<System>(), 3u32.unix::<System>()];
+/// let merged: Vec<(usize, i64)> = merge_sorted(vec![a.into_iter(), b.into_iter()]).map(|(i, t)| (i, t.unix())).collect();
+/// assert_eq!(merged, vec![(0, 1), (1, 2), (1, 3), (0, 4)]);
+/// ```
+pub fn merge_sorted<T: Time, I: Iterator<Item = T>>(streams: Vec<I>) -> MergeSorted<I> {
+    MergeSorted::new(streams, |t| t.unix_ms(), false)
+}
+
+/// Like `merge_sorted`, for streams of `(time, payload)` pairs sorted by their time
+///
+/// # Examples
+/// ```rust
+/// use thetime::{merge_sorted_by_key, System, Time, IntTime};
+/// let web = vec![(10u32.unix::<System>(), "GET /"), (30u32.unix::<System>(), "GET /about")];
+/// let db = vec![(20u32.unix::<System>(), "SELECT 1")];
+/// let merged: Vec<&str> = merge_sorted_by_key(vec![web.into_iter(), db.into_iter()]).map(|(_, (_, line))| line).collect();
+/// assert_eq!(merged, vec!["GET /", "SELECT 1", "GET /about"]);
+/// ```
+pub fn merge_sorted_by_key<T: Time, P, I: Iterator<Item = (T, P)>>(streams: Vec<I>) -> MergeSorted<I> {
+    MergeSorted::new(streams, |(t, _)| t.unix_ms(), false)
+}
+
+/// Like `merge_sorted`, but checking that each stream is sorted - an item earlier than the one before it in its stream is replaced by a
+/// `MergeError::Unsorted` error (the merge then carries on with the rest of that stream)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{merge_sorted_checked, MergeError, System, Time, IntTime};
+/// let sorted = vec![1u32.unix::<System>(), 2u32.unix::<System>()];
+/// let unsorted = vec![5u32.unix::<System>(), 3u32.unix::<System>()];
+/// let merged: Vec<_> = merge_sorted_checked(vec![sorted.into_iter(), unsorted.into_iter()]).collect();
+/// assert_eq!(merged.len(), 4);
+/// assert!(merged.contains(&Err(MergeError::Unsorted { source: 1, position: 1 })));
+/// ```
+pub fn merge_sorted_checked<T: Time, I: Iterator<Item = T>>(streams: Vec<I>) -> MergeSortedChecked<I> {
+    MergeSortedChecked(MergeSorted::new(streams, |t| t.unix_ms(), true))
+}
@@ -0,0 +1,124 @@
+use crate::Time;
+use core::cell::Cell;
+
+thread_local! {
+    /// the mocked time, as milliseconds since 1601 and an offset
+    static MOCK: Cell<Option<(u64, i32)>> = const { Cell::new(None) };
+    /// the overridden process start time
+    static APP_START: Cell<Option<&'static crate::System>> = const { Cell::new(None) };
+}
+
+/// Restores the previous mock time (or the real clock) when dropped - returned by `set_mock_time`
+#[derive(Debug)]
+#[must_use = "the mock time is cleared as soon as the guard is dropped"]
+pub struct MockGuard {
+    previous: Option<(u64, i32)>,
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        MOCK.with(|m| m.set(self.previous));
+    }
+}
+
+/// Freeze `System::now`, `Ntp::now` and the crate's `now_*` functions at `t` (including its offset), on the current thread, until the guard is dropped
+///
+/// The mock is per thread, so tests running in parallel do not see each other's clocks
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, mock};
+/// let frozen = 1704465989u32.unix::<System>();
+/// {
+///     let _guard = mock::set_mock_time(&frozen);
+///     assert_eq!(System::now(), frozen);
+///     assert_eq!(thetime::now(), 1704465989);
+/// }
+/// assert!(thetime::now() > 1704465989);
+/// ```
+pub fn set_mock_time<T: Time>(t: &T) -> MockGuard {
+    let previous = MOCK.with(|m| m.replace(Some((t.raw(), t.utc_offset()))));
+    MockGuard { previous }
+}
+
+/// Move the mocked time on by `ms` milliseconds (saturating at 1601) - does nothing if no mock time is set
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, mock};
+/// let _guard = mock::set_mock_time(&1704465989u32.unix::<System>());
+/// mock::advance_mock(1500);
+/// assert_eq!(thetime::now_ms(), 1704465990500);
+/// ```
+pub fn advance_mock(ms: i64) {
+    MOCK.with(|m| {
+        if let Some((raw, offset)) = m.get() {
+            m.set(Some(((raw as i64).saturating_add(ms).max(0) as u64, offset)));
+        }
+    });
+}
+
+/// Whether a mock time is set on the current thread
+pub fn is_mocked() -> bool {
+    MOCK.with(|m| m.get().is_some())
+}
+
+/// internal only - the mocked time, if any, as a time object
+pub(crate) fn mocked<T: Time>() -> Option<T> {
+    MOCK.with(|m| m.get()).map(|(raw, offset)| T::from_epoch_offset(raw, offset))
+}
+
+/// Restores the previous process start time when dropped - returned by `set_app_start`
+#[derive(Debug)]
+#[must_use = "the start time override is cleared as soon as the guard is dropped"]
+pub struct AppStartGuard {
+    previous: Option<&'static crate::System>,
+}
+
+impl Drop for AppStartGuard {
+    fn drop(&mut self) {
+        APP_START.with(|s| s.set(self.previous));
+    }
+}
+
+/// Make `app_start` return `t` (and `app_uptime` and `relative_to_start` count from it), on the current thread, until the guard is dropped
+///
+/// Each call leaks one `System`, as `app_start` hands out a `&'static` - fine in tests, but not something to call in a loop
+///
+/// # Examples
+/// ```rust
+/// use thetime::{app_start, app_uptime, mock, System, Time, IntTime};
+/// let _start = mock::set_app_start(&1704465989u32.unix::<System>());
+/// let _now = mock::set_mock_time(&1704466049u32.unix::<System>());
+/// assert_eq!(app_start().unix(), 1704465989);
+/// assert_eq!(app_uptime().as_secs(), 60);
+/// ```
+pub fn set_app_start<T: Time>(t: &T) -> AppStartGuard {
+    let start: &'static crate::System = Box::leak(Box::new(crate::System::from_epoch_offset(t.raw(), t.utc_offset())));
+    let previous = APP_START.with(|s| s.replace(Some(start)));
+    AppStartGuard { previous }
+}
+
+/// internal only - the overridden process start time, if any
+pub(crate) fn app_start_override() -> Option<&'static crate::System> {
+    APP_START.with(|s| s.get())
+}
+
+/// The per-thread mock time as a `Clock`, so code written against `Clock` can be driven by `set_mock_time` and `advance_mock` -
+/// reads the real clock if no mock time is set
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Clock, System, Time, IntTime, mock};
+/// let _guard = mock::set_mock_time(&1704465989u32.unix::<System>());
+/// mock::advance_mock(1000);
+/// assert_eq!(mock::MockClock.now_system().unix(), 1704465990);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct MockClock;
+
+impl crate::Clock for MockClock {
+    fn now_system(&self) -> crate::System {
+        mocked().unwrap_or_else(crate::System::now)
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{MonotonicViolation, Time};
+use core::marker::PhantomData;
+use core::time::Duration;
+
+/// Checks that a stream of times only ever increases, for spotting timestamp regressions and duplicates in audit logs
+///
+/// Each time is compared with the latest seen so far. Going backwards by up to the tolerance (zero by default) is accepted as clock
+/// jitter and counted, but doesn't lower the latest time, so many small steps back can't add up to a large one. Hitting the latest time
+/// exactly is a `Duplicate` whatever the tolerance
+///
+/// # Examples
+/// ```rust
+/// use thetime::{MonotonicValidator, MonotonicViolation, System, Time, IntTime};
+/// use core::time::Duration;
+/// let mut validator = MonotonicValidator::new().tolerance(Duration::from_millis(5));
+/// let t = 1704465989u32.unix::<System>();
+/// assert!(validator.check(&t).is_ok());
+/// assert!(validator.check(&t.add_seconds(1)).is_ok());
+/// assert!(matches!(validator.check(&t), Err(MonotonicViolation::Regression { delta: 1000, .. })));
+/// assert_eq!(validator.regressions(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonotonicValidator<T> {
+    tolerance_ms: u64,
+    latest: Option<i64>,
+    checked: u64,
+    tolerated: u64,
+    regressions: u64,
+    duplicates: u64,
+    max_regression_ms: u64,
+    // only the times' type - they are stored as Unix milliseconds
+    kind: PhantomData<fn(&T)>,
+}
+
+impl<T: Time> Default for MonotonicValidator<T> {
+    fn default() -> Self {
+        MonotonicValidator {
+            tolerance_ms: 0,
+            latest: None,
+            checked: 0,
+            tolerated: 0,
+            regressions: 0,
+            duplicates: 0,
+            max_regression_ms: 0,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T: Time> MonotonicValidator<T> {
+    /// A validator that has seen nothing yet, with no tolerance
+    pub fn new() -> Self {
+        MonotonicValidator::default()
+    }
+
+    /// Accept going backwards by up to `tolerance` (rounded down to a millisecond)
+    pub fn tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance_ms = u64::try_from(tolerance.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Check the next time in the stream
+    pub fn check(&mut self, t: &T) -> Result<(), MonotonicViolation> {
+        self.checked += 1;
+        let cur = t.unix_ms();
+        let Some(prev) = self.latest else {
+            self.latest = Some(cur);
+            return Ok(());
+        };
+        if cur > prev {
+            self.latest = Some(cur);
+            return Ok(());
+        }
+        if cur == prev {
+            self.duplicates += 1;
+            return Err(MonotonicViolation::Duplicate { at: cur });
+        }
+
+        let delta = prev.abs_diff(cur);
+        self.max_regression_ms = self.max_regression_ms.max(delta);
+        if delta <= self.tolerance_ms {
+            self.tolerated += 1;
+            Ok(())
+        } else {
+            self.regressions += 1;
+            Err(MonotonicViolation::Regression { prev, cur, delta })
+        }
+    }
+
+    /// How many times have been checked
+    pub fn checked(&self) -> u64 {
+        self.checked
+    }
+
+    /// How many times went backwards within the tolerance
+    pub fn tolerated(&self) -> u64 {
+        self.tolerated
+    }
+
+    /// How many times went backwards by more than the tolerance
+    pub fn regressions(&self) -> u64 {
+        self.regressions
+    }
+
+    /// How many times repeated the latest time
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+
+    /// The furthest any time went backwards, tolerated or not, in milliseconds
+    pub fn max_regression_ms(&self) -> u64 {
+        self.max_regression_ms
+    }
+
+    /// A one line summary of the counters
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{MonotonicValidator, System, Time, IntTime};
+    /// let mut validator = MonotonicValidator::new();
+    /// let t = 1704465989u32.unix::<System>();
+    /// for x in [t.clone(), t.clone(), t.add_seconds(-2)] {
+    ///     let _ = validator.check(&x);
+    /// }
+    /// assert_eq!(validator.summary(), "3 checked: 1 regression, 1 duplicate, 0 within tolerance (worst 2000ms)");
+    /// ```
+    pub fn summary(&self) -> String {
+        let plural = |n: u64| if n == 1 { "" } else { "s" };
+        format!(
+            "{} checked: {} regression{}, {} duplicate{}, {} within tolerance (worst {}ms)",
+            self.checked,
+            self.regressions,
+            plural(self.regressions),
+            self.duplicates,
+            plural(self.duplicates),
+            self.tolerated,
+            self.max_regression_ms
+        )
+    }
+}
+
+/// Check a whole slice with a zero tolerance `MonotonicValidator`, returning each violation with the index of the offending time
+///
+/// # Examples
+/// ```rust
+/// use thetime::{validate_sorted, MonotonicViolation, System, Time, IntTime};
+/// let t = 1704465989u32.unix::<System>();
+/// let times = [t.clone(), t.add_seconds(1), t.add_seconds(1), t.add_seconds(2)];
+/// assert_eq!(validate_sorted(&times), vec![(2, MonotonicViolation::Duplicate { at: 1704465990000 })]);
+/// ```
+pub fn validate_sorted<T: Time>(times: &[T]) -> Vec<(usize, MonotonicViolation)> {
+    let mut validator = MonotonicValidator::new();
+    times
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| validator.check(t).err().map(|v| (i, v)))
+        .collect()
+}
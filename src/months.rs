@@ -0,0 +1,138 @@
+use crate::{MonthRollError, Time, Weekday};
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeSet;
+
+/// What `Time::add_months_with` does when the day of the month doesn't exist in the target month, as with Jan 31 + 1 month
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, MonthRollPolicy};
+/// let x = "2024-01-31 09:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// assert_eq!(x.add_months_with(1, MonthRollPolicy::Clamp).unwrap().pretty(), "2024-02-29 09:00:00");
+/// assert!(x.add_months_with(1, MonthRollPolicy::Reject).is_err());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MonthRollPolicy {
+    /// keep the day of the month, clamped to the last day of the target month (Jan 31 + 1M = Feb 29, Feb 29 + 1M = Mar 29)
+    #[default]
+    Clamp,
+    /// as `Clamp`, except that the last day of a month stays the last day of a month (Feb 29 + 1M = Mar 31), the usual rule for rolling
+    /// financial dates
+    EndOfMonth,
+    /// error if the day of the month doesn't exist in the target month
+    Reject,
+}
+
+/// internal only - the number of days in a month
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(31, |d| d.day())
+}
+
+/// internal only - `Time::add_months_with`
+pub(crate) fn add_months<T: Time>(time: &T, months: i32, policy: MonthRollPolicy) -> Result<T, MonthRollError> {
+    let wall = time.wall_clock();
+    let date = wall.date();
+    let index = date.year() as i64 * 12 + date.month0() as i64 + months as i64;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    if !(1601..=9999).contains(&year) {
+        return Err(MonthRollError::OutOfRange);
+    }
+    let year = year as i32;
+
+    let last = days_in_month(year, month);
+    let day = match policy {
+        MonthRollPolicy::Clamp => date.day().min(last),
+        MonthRollPolicy::EndOfMonth if date.day() == days_in_month(date.year(), date.month()) => last,
+        MonthRollPolicy::EndOfMonth => date.day().min(last),
+        MonthRollPolicy::Reject if date.day() > last => {
+            return Err(MonthRollError::DayMissing { year, month: month as u8, day: date.day() as u8 });
+        }
+        MonthRollPolicy::Reject => date.day(),
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or(MonthRollError::OutOfRange)?;
+    Ok(T::from_wall_clock(date.and_time(wall.time()), time.utc_offset()).with_offset_source(time.offset_source()))
+}
+
+/// Which days are business days: every day except the weekend days (Saturday and Sunday by default) and any listed holidays
+///
+/// Days are compared by the date a time object displays, in its own offset
+///
+/// # Examples
+/// ```rust
+/// use thetime::{BusinessCalendar, System, Time, StrTime, Weekday};
+/// let cal = BusinessCalendar::new().holiday(2024, 12, 25);
+/// let xmas = "2024-12-25 12:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// assert!(!cal.is_business_day(&xmas));
+/// assert!(BusinessCalendar::new().is_business_day(&xmas));
+/// let gulf = BusinessCalendar::new().weekend(&[Weekday::Fri, Weekday::Sat]);
+/// assert!(!gulf.is_business_day(&"2024-01-05 12:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S")));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusinessCalendar {
+    weekend: Vec<Weekday>,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Default for BusinessCalendar {
+    fn default() -> Self {
+        BusinessCalendar {
+            weekend: vec![Weekday::Sat, Weekday::Sun],
+            holidays: BTreeSet::new(),
+        }
+    }
+}
+
+impl BusinessCalendar {
+    /// A calendar with a Saturday and Sunday weekend, and no holidays
+    pub fn new() -> Self {
+        BusinessCalendar::default()
+    }
+
+    /// Use different weekend days
+    pub fn weekend(mut self, days: &[Weekday]) -> Self {
+        self.weekend = days.to_vec();
+        self
+    }
+
+    /// Add a holiday - dates that don't exist are ignored
+    pub fn holiday(mut self, year: i32, month: u8, day: u8) -> Self {
+        if let Some(date) = NaiveDate::from_ymd_opt(year, month as u32, day as u32) {
+            self.holidays.insert(date);
+        }
+        self
+    }
+
+    /// internal only - whether a date is a business day
+    fn is_business_date(&self, date: NaiveDate) -> bool {
+        !self.weekend.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Whether the date `time` displays is a business day
+    pub fn is_business_day<T: Time>(&self, time: &T) -> bool {
+        self.is_business_date(time.wall_clock().date())
+    }
+
+    /// internal only - the nearest business day from `date` (inclusive), stepping `step` days at a time, None if there is none in range
+    fn nearest(&self, date: NaiveDate, step: i64) -> Option<NaiveDate> {
+        // with every weekday off there is nothing to find, otherwise a business day turns up within a week of the last holiday
+        if (0..7).all(|d| self.weekend.contains(&Weekday::try_from(d as u8).unwrap())) {
+            return None;
+        }
+        let mut date = date;
+        while !self.is_business_date(date) {
+            date = date.checked_add_signed(chrono::Duration::days(step))?;
+        }
+        Some(date)
+    }
+
+    /// internal only - the ISDA modified following adjustment of a date
+    pub(crate) fn modified_following_date(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self.nearest(date, 1) {
+            Some(next) if next.month() == date.month() => Some(next),
+            _ => self.nearest(date, -1),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/// Month and weekday names for one language, used by `Time::strftime_with_names` in place of chrono's English names
+///
+/// Months run from January, and weekdays from Monday (matching `Weekday::num_days_from_monday`). The built-in tables are `NameTable::EN`,
+/// `NameTable::DE`, `NameTable::FR` and `NameTable::ES`, and other languages can be added by filling in the fields
+///
+/// # Examples
+/// ```rust
+/// use thetime::NameTable;
+/// const MONTH: Option<&str> = NameTable::DE.month(3);
+/// assert_eq!(MONTH, Some("März"));
+/// assert_eq!(NameTable::FR.weekday_short(6), Some("dim."));
+/// assert_eq!(NameTable::EN.month(13), None);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct NameTable {
+    /// full month names, for `%B`
+    pub months: [&'static str; 12],
+    /// abbreviated month names, for `%b` and `%h`
+    pub months_short: [&'static str; 12],
+    /// full weekday names, Monday first, for `%A`
+    pub weekdays: [&'static str; 7],
+    /// abbreviated weekday names, Monday first, for `%a`
+    pub weekdays_short: [&'static str; 7],
+}
+
+impl NameTable {
+    /// English, the same names chrono uses
+    pub const EN: NameTable = NameTable {
+        months: ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"],
+        months_short: ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"],
+        weekdays: ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"],
+        weekdays_short: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    };
+
+    /// German
+    pub const DE: NameTable = NameTable {
+        months: ["Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember"],
+        months_short: ["Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez"],
+        weekdays: ["Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag", "Sonntag"],
+        weekdays_short: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    };
+
+    /// French, in lower case as French writes them
+    pub const FR: NameTable = NameTable {
+        months: ["janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre", "décembre"],
+        months_short: ["janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc."],
+        weekdays: ["lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche"],
+        weekdays_short: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    };
+
+    /// Spanish, in lower case as Spanish writes them
+    pub const ES: NameTable = NameTable {
+        months: ["enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre"],
+        months_short: ["ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic"],
+        weekdays: ["lunes", "martes", "miércoles", "jueves", "viernes", "sábado", "domingo"],
+        weekdays_short: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    };
+
+    /// The full name of a month, from 1 (January) to 12
+    pub const fn month(&self, month: u32) -> Option<&'static str> {
+        if month >= 1 && month <= 12 {
+            Some(self.months[month as usize - 1])
+        } else {
+            None
+        }
+    }
+
+    /// The abbreviated name of a month, from 1 (January) to 12
+    pub const fn month_short(&self, month: u32) -> Option<&'static str> {
+        if month >= 1 && month <= 12 {
+            Some(self.months_short[month as usize - 1])
+        } else {
+            None
+        }
+    }
+
+    /// The full name of a weekday, from 0 (Monday) to 6
+    pub const fn weekday(&self, days_from_monday: u32) -> Option<&'static str> {
+        if days_from_monday < 7 {
+            Some(self.weekdays[days_from_monday as usize])
+        } else {
+            None
+        }
+    }
+
+    /// The abbreviated name of a weekday, from 0 (Monday) to 6
+    pub const fn weekday_short(&self, days_from_monday: u32) -> Option<&'static str> {
+        if days_from_monday < 7 {
+            Some(self.weekdays_short[days_from_monday as usize])
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for NameTable {
+    fn default() -> Self {
+        NameTable::EN
+    }
+}
@@ -1,33 +1,212 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::Utc;
 use core::fmt::Display;
+#[cfg(feature = "ntp-client")]
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+#[cfg(feature = "ntp-client")]
+use std::collections::HashMap;
+#[cfg(feature = "ntp-client")]
 use std::net::UdpSocket;
+use std::sync::Arc;
+#[cfg(feature = "ntp-client")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "ntp-client")]
+use std::sync::Mutex;
+#[cfg(feature = "ntp-client")]
+use std::time::Instant;
+#[cfg(feature = "ntp-client")]
 use core::time::Duration;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{Time, TimeDiff, OFFSET_1601, REF_TIME_1970};
+#[cfg(feature = "serde")]
+use crate::wire::Wire;
+use crate::{NtpPacket, OffsetSource, ParseTimeError, Time, TimeDiff, OFFSET_1601};
+#[cfg(feature = "ntp-client")]
+use crate::{Resolver, ServerSelection};
 
 /// NTP time
 ///
 /// `inner_secs` is the time as seconds since `1601-01-01 00:00:00`, from `chrono::Utc`
 /// `inner_milliseconds` is the subsec milliseconds
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// 
+/// Serializes as `{ "v": 1, "ms1601": .., "offset": .., "server": .. }`, and also deserializes from the older `inner_secs`/`inner_milliseconds`/`server`/`utc_offset` form.
+/// The offset source is not serialized - deserialized values are always `OffsetSource::Explicit`
+///
+/// Debug output leads with the instant in UTC, the offset, the server and whether the value came from a server's reply (`valid`),
+/// followed by the raw fields. Comparisons look at everything but where the offset came from
+#[derive(Clone)]
 pub struct Ntp {
     inner_secs: u64,
     inner_milliseconds: u64,
-    server: String,
+    server: ServerName,
     utc_offset: i32,
+    offset_source: OffsetSource,
+    last_packet: Option<NtpPacket>,
+    peer: Option<SocketAddr>,
+}
+
+impl core::fmt::Debug for Ntp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Ntp")
+            .field("instant", &crate::debug_instant(self.unix_ms()))
+            .field("utc_offset", &format_args!("{}", self.tz_offset()))
+            .field("server", &self.server)
+            .field("valid", &self.last_packet.is_some())
+            .field("offset_source", &self.offset_source)
+            .field("raw_ms_1601", &self.raw())
+            .field("inner_secs", &self.inner_secs)
+            .field("inner_milliseconds", &self.inner_milliseconds)
+            .field("last_packet", &self.last_packet)
+            .field("peer", &self.peer)
+            .finish()
+    }
+}
+
+/// The server an `Ntp` value came from - common names are held as `&'static str`s, and everything else is shared, so cloning never allocates
+#[derive(Clone)]
+enum ServerName {
+    WellKnown(&'static str),
+    Custom(Arc<str>),
+}
+
+impl ServerName {
+    /// the sentinel names this crate uses, and public servers people commonly query
+    const WELL_KNOWN: [&'static str; 10] = [
+        "chrono::Utc",
+        "strptime",
+        "from_epoch",
+        "from_epoch_offset",
+        "",
+        "pool.ntp.org",
+        "time.google.com",
+        "time.cloudflare.com",
+        "time.windows.com",
+        "time.apple.com",
+    ];
+
+    fn as_str(&self) -> &str {
+        match self {
+            ServerName::WellKnown(name) => name,
+            ServerName::Custom(name) => name,
+        }
+    }
+}
+
+impl From<&str> for ServerName {
+    fn from(name: &str) -> Self {
+        match ServerName::WELL_KNOWN.iter().find(|known| **known == name) {
+            Some(known) => ServerName::WellKnown(known),
+            None => ServerName::Custom(Arc::from(name)),
+        }
+    }
+}
+
+impl core::fmt::Debug for ServerName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for ServerName {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ServerName {}
+
+impl PartialOrd for ServerName {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ServerName {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Ntp {
+    /// internal only - the fields compared by `Eq` and `Ord`, leaving out the offset source
+    fn key(&self) -> (u64, u64, &ServerName, i32, &Option<NtpPacket>, &Option<SocketAddr>) {
+        (self.inner_secs, self.inner_milliseconds, &self.server, self.utc_offset, &self.last_packet, &self.peer)
+    }
+}
+
+impl PartialEq for Ntp {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Ntp {}
+
+impl PartialOrd for Ntp {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ntp {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Hashes the instant only (the raw milliseconds), ignoring the offset and server - consistent with `Eq`, and with `Time::instant_eq`
+impl core::hash::Hash for Ntp {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Ntp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Wire {
+            ms1601: self.raw(),
+            offset: self.utc_offset,
+            server: Some(self.server.as_str().to_string()),
+        }
+        .serialize("Ntp", serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Ntp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = Wire::deserialize("Ntp", true, deserializer)?;
+        Ok(Ntp {
+            inner_secs: wire.ms1601 / 1000,
+            inner_milliseconds: wire.ms1601 % 1000,
+            server: ServerName::from(wire.server.as_deref().unwrap_or_default()),
+            utc_offset: crate::clamp_offset(wire.offset),
+            offset_source: OffsetSource::Explicit,
+            last_packet: None,
+            peer: None,
+        })
+    }
+}
+
+/// The Unix epoch, `1970-01-01 00:00:00` UTC (see `Time::unix_epoch`), rather than the 1601 internal zero
+impl Default for Ntp {
+    fn default() -> Self {
+        Ntp::unix_epoch()
+    }
 }
 
 impl Display for Ntp {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.pretty())
+        self.with_pretty(|s| f.write_str(s))
     }
 }
 
 impl Ntp {
     /// Returns the server address used to get the time
     pub fn server(&self) -> String {
-        self.server.to_string()
+        self.server.as_str().to_string()
     }
 
     /// returns whether the data was fetched from a valid server (ie not strptime or chrono::Utc)
@@ -39,65 +218,75 @@ impl Ntp {
 impl TimeDiff for Ntp {}
 
 impl Time for Ntp {
-    /// Note - there is a chance that this function fails, in which case we use the System time as a failsafe
+    /// Note - there is a chance that this function fails, in which case we use the System time as a failsafe (as it always does without the `ntp-client` feature)
     fn now() -> Self {
-        match Ntp::new("pool.ntp.org") {
-            Ok(x) => x,
-            Err(_) => {
-                let now = Utc::now();
-                Ntp {
-                    inner_secs: (now.timestamp() + OFFSET_1601 as i64) as u64,
-                    inner_milliseconds: now.timestamp_subsec_millis() as u64,
-                    server: "chrono::Utc".to_string(),
-                    utc_offset: 0,
-                }
-            },
+        #[cfg(feature = "testing")]
+        if let Some(mocked) = crate::mock::mocked() {
+            return mocked;
+        }
+        #[cfg(feature = "ntp-client")]
+        if let Ok(x) = Ntp::new("pool.ntp.org") {
+            return x;
+        }
+        let now = Utc::now();
+        Ntp {
+            inner_secs: (now.timestamp() + OFFSET_1601 as i64) as u64,
+            inner_milliseconds: now.timestamp_subsec_millis() as u64,
+            server: ServerName::WellKnown("chrono::Utc"),
+            utc_offset: 0,
+            offset_source: OffsetSource::Explicit,
+            last_packet: None,
+            peer: None,
         }
     }
     fn unix(&self) -> i64 {
+        crate::precision::strict(self.unix_checked().1, "unix");
         (self.inner_secs as i64) - (OFFSET_1601 as i64)
     }
     fn unix_ms(&self) -> i64 {
-        ((self.inner_secs as i64 * 1000i64) + self.inner_milliseconds as i64) - (OFFSET_1601 as i64 * 1000i64)
+        crate::unix_ms_of_raw(self.raw())
     }
     fn utc_offset(&self) -> i32 {
         self.utc_offset
     }
 
-    fn strptime<T: ToString, G: ToString>(s: T, format: G) -> Self {
-        let s = s.to_string();
-        let format = format.to_string();
-        let temp = DateTime::parse_from_str(&s, &format);
-        let x = match temp {
-            Err(_) => {
-                if !format.contains("%z") {
-                    return Self::strptime(s + " +0000", format + "%z");
-                }
-                panic!("Bad format string");
-            }
-            Ok(dt) => dt,
-        };
-        Ntp {
-            inner_secs: (x.timestamp() + (OFFSET_1601 as i64)) as u64,
-            inner_milliseconds: x.timestamp_subsec_millis() as u64,
-            server: "strptime".to_string(),
-            utc_offset: x.offset().local_minus_utc() as i32,
-        }
+    fn try_strptime_assuming<T: ToString, G: ToString>(s: T, format: G, default_offset: Option<i32>, assumed: OffsetSource) -> Result<Self, ParseTimeError> {
+        let (unix_ms, offset, source) = crate::try_parse_with_default_offset(&s.to_string(), &format.to_string(), default_offset, assumed)?;
+        // times before 1601 are clamped to it
+        let ms_1601 = (unix_ms + OFFSET_1601 as i64 * 1000).max(0) as u64;
+        Ok(Ntp {
+            inner_secs: ms_1601 / 1000,
+            inner_milliseconds: ms_1601 % 1000,
+            server: ServerName::WellKnown("strptime"),
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: source,
+            last_packet: None,
+            peer: None,
+        })
+    }
+
+    fn offset_source(&self) -> OffsetSource {
+        self.offset_source
+    }
+
+    fn with_offset_source(mut self, source: OffsetSource) -> Self {
+        self.offset_source = source;
+        self
     }
 
     fn strftime(&self, format: &str) -> String {
-        NaiveDateTime::from_timestamp_opt(self.inner_secs as i64 - OFFSET_1601 as i64, 0)
-            .unwrap()
-            .format(format)
-            .to_string()
+        self.format_items(chrono::format::StrftimeItems::new(format))
     }
 
     fn from_epoch(timestamp: u64) -> Self {
         Ntp {
             inner_secs: timestamp / 1000,
             inner_milliseconds: timestamp % 1000,
-            server: "from_epoch".to_string(),
+            server: ServerName::WellKnown("from_epoch"),
             utc_offset: 0,
+            offset_source: OffsetSource::Explicit,
+            last_packet: None,
+            peer: None,
         }
     }
 
@@ -109,52 +298,396 @@ impl Time for Ntp {
         Ntp {
             inner_secs: timestamp / 1000,
             inner_milliseconds: timestamp % 1000,
-            server: "from_epoch_offset".to_string(),
-            utc_offset: offset,
+            server: ServerName::WellKnown("from_epoch_offset"),
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: OffsetSource::Explicit,
+            last_packet: None,
+            peer: None,
+        }
+    }
+
+    fn canonicalize(&self) -> Self {
+        Ntp {
+            inner_secs: self.inner_secs.saturating_add(self.inner_milliseconds / 1000),
+            inner_milliseconds: self.inner_milliseconds % 1000,
+            utc_offset: crate::clamp_offset(self.utc_offset),
+            server: self.server.clone(),
+            offset_source: self.offset_source,
+            last_packet: self.last_packet,
+            peer: self.peer,
+        }
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.inner_milliseconds < 1000 && self.utc_offset.abs() <= crate::MAX_UTC_OFFSET
+    }
+}
+
+
+/// Settings for querying an NTP server (needs the `ntp-client` feature)
+///
+/// # Examples
+/// ```rust
+/// use thetime::NtpConfig;
+/// let config = NtpConfig::new("time.cloudflare.com").timeout(core::time::Duration::from_secs(2));
+/// assert_eq!(config.port, 123);
+/// ```
+#[cfg(feature = "ntp-client")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NtpConfig {
+    /// the server's host name or address
+    pub server: String,
+    /// the UDP port, 123 by default
+    pub port: u16,
+    /// the longest to wait for a reply in total, 5 seconds by default
+    pub timeout: Duration,
+    /// which of the server's addresses to query, `ServerSelection::Random` by default
+    pub selection: ServerSelection,
+    /// whether to look for `_ntp._udp` SRV records first, true by default (only the `dns` feature's resolver finds any)
+    pub srv: bool,
+}
+
+#[cfg(feature = "ntp-client")]
+impl Default for NtpConfig {
+    fn default() -> Self {
+        NtpConfig::new("pool.ntp.org")
+    }
+}
+
+#[cfg(feature = "ntp-client")]
+impl NtpConfig {
+    /// Query `server` on the standard port, with the default timeout
+    pub fn new<T: ToString>(server: T) -> Self {
+        NtpConfig {
+            server: server.to_string(),
+            port: 123,
+            timeout: Duration::from_secs(5),
+            selection: ServerSelection::default(),
+            srv: true,
+        }
+    }
+
+    /// Use a different port
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Use a different total timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Use a different strategy for picking between the server's addresses
+    pub fn selection(mut self, selection: ServerSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Turn the SRV lookup on or off
+    pub fn srv(mut self, srv: bool) -> Self {
+        self.srv = srv;
+        self
+    }
+}
+
+/// The shortest time the global rate guard allows between two queries to one server, unless changed with `set_min_query_interval`
+#[cfg(feature = "ntp-client")]
+pub const DEFAULT_MIN_QUERY_INTERVAL: Duration = Duration::from_secs(4);
+
+/// the guard every query goes through, created on first use
+#[cfg(feature = "ntp-client")]
+static RATE_GUARD: Mutex<Option<RateGuard>> = Mutex::new(None);
+
+/// false after `rate_guard_disable`
+#[cfg(feature = "ntp-client")]
+static RATE_GUARD_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// A registry of when each NTP server was last queried, and which have told us to go away, so that a loop can't hammer a server
+/// (needs the `ntp-client` feature)
+///
+/// A server is refused if it was queried less than the minimum interval ago, with `NtpError::RateLimited` saying how long to wait,
+/// and for good once it has sent a Kiss-o'-Death `DENY` or `RSTR`, with `NtpError::Denied`. Servers are keyed by name and port, so
+/// two ports on one host are two servers. If the clock goes backwards past a server's last query, that query no longer counts
+///
+/// Every query made by `Ntp::new`, `Ntp::with_config` and `Ntp::with_resolver` goes through one global guard - see
+/// `set_min_query_interval` and `rate_guard_disable`. Build a separate one to check against a clock of choice
+///
+/// # Examples
+/// ```rust
+/// use thetime::{NtpError, RateGuard, System, Time, IntTime};
+/// use core::time::Duration;
+/// let mut guard = RateGuard::new(Duration::from_secs(4));
+/// let t = 1704465989u32.unix::<System>();
+/// assert!(guard.check("pool.ntp.org:123", &t).is_ok());
+/// assert!(matches!(guard.check("pool.ntp.org:123", &t.add_seconds(1)), Err(NtpError::RateLimited { .. })));
+/// assert!(guard.check("time.google.com:123", &t.add_seconds(1)).is_ok());
+/// assert!(guard.check("pool.ntp.org:123", &t.add_seconds(4)).is_ok());
+/// ```
+#[cfg(feature = "ntp-client")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateGuard {
+    min_interval: Duration,
+    /// unix milliseconds of the last query allowed, by server
+    last_query: HashMap<String, i64>,
+    /// the Kiss-o'-Death code each denied server sent
+    denied: HashMap<String, String>,
+}
+
+#[cfg(feature = "ntp-client")]
+impl Default for RateGuard {
+    fn default() -> Self {
+        RateGuard::new(DEFAULT_MIN_QUERY_INTERVAL)
+    }
+}
+
+#[cfg(feature = "ntp-client")]
+impl RateGuard {
+    /// An empty registry, allowing one query per server every `min_interval`
+    pub fn new(min_interval: Duration) -> Self {
+        RateGuard {
+            min_interval,
+            last_query: HashMap::new(),
+            denied: HashMap::new(),
         }
     }
+
+    /// The shortest time allowed between two queries to one server
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// Change the shortest time allowed between two queries to one server - queries already made count against the new interval
+    pub fn set_min_interval(&mut self, min_interval: Duration) {
+        self.min_interval = min_interval;
+    }
+
+    /// Whether `server` may be queried at `now` - if so, the query is recorded, and the next one must wait for the interval
+    pub fn check<T: Time>(&mut self, server: &str, now: &T) -> Result<(), NtpError> {
+        if let Some(code) = self.denied.get(server) {
+            return Err(NtpError::Denied { server: server.to_string(), code: code.clone() });
+        }
+        let now = now.unix_ms();
+        if let Some(&last) = self.last_query.get(server) {
+            let elapsed = now.saturating_sub(last);
+            let min_interval = self.min_interval.as_millis().min(i64::MAX as u128) as i64;
+            if (0..min_interval).contains(&elapsed) {
+                return Err(NtpError::RateLimited { retry_after: Duration::from_millis((min_interval - elapsed) as u64) });
+            }
+        }
+        self.last_query.insert(server.to_string(), now);
+        Ok(())
+    }
+
+    /// Record that `server` answered with a Kiss-o'-Death - `DENY` and `RSTR` refuse it for good, and any other code is ignored. Returns
+    /// whether the server is now refused
+    pub fn record_kiss_of_death(&mut self, server: &str, code: &str) -> bool {
+        if matches!(code, "DENY" | "RSTR") {
+            self.denied.insert(server.to_string(), code.to_string());
+        }
+        self.is_denied(server)
+    }
+
+    /// Whether `server` has sent a `DENY` or `RSTR` Kiss-o'-Death
+    pub fn is_denied(&self, server: &str) -> bool {
+        self.denied.contains_key(server)
+    }
+}
+
+/// internal only - run `f` on the global rate guard, unless it has been disabled
+#[cfg(feature = "ntp-client")]
+fn with_rate_guard<R>(f: impl FnOnce(&mut RateGuard) -> R) -> Option<R> {
+    if !RATE_GUARD_ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let mut guard = RATE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    Some(f(guard.get_or_insert_with(RateGuard::default)))
+}
+
+/// Set the shortest time the global rate guard allows between two queries to one server - `DEFAULT_MIN_QUERY_INTERVAL` (4 seconds)
+/// unless changed. Needs the `ntp-client` feature
+#[cfg(feature = "ntp-client")]
+pub fn set_min_query_interval(min_interval: Duration) {
+    let mut guard = RATE_GUARD.lock().unwrap_or_else(|e| e.into_inner());
+    guard.get_or_insert_with(RateGuard::default).set_min_interval(min_interval);
 }
 
+/// Turn the global rate guard off for the rest of the process, for controlled environments such as a local test server - queries are
+/// neither limited nor refused after a Kiss-o'-Death (which is still returned as `NtpError::Denied`). Needs the `ntp-client` feature
+///
+/// Public servers require clients to back off, so leave the guard on when querying them
+#[cfg(feature = "ntp-client")]
+pub fn rate_guard_disable() {
+    RATE_GUARD_ENABLED.store(false, Ordering::Relaxed);
+}
 
+/// Turn the global rate guard back on after `rate_guard_disable`, with what it had recorded before. Needs the `ntp-client` feature
+#[cfg(feature = "ntp-client")]
+pub fn rate_guard_enable() {
+    RATE_GUARD_ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// The network client - without the `ntp-client` feature, `Ntp` values can still be parsed, converted and deserialized, but not fetched
+#[cfg(feature = "ntp-client")]
 impl Ntp {
     /// Fetches the time from an NTP server
     /// 
     /// # Example
-    /// ```
+    /// ```no_run
     /// use thetime::Ntp;
     /// let ntp = Ntp::new("pool.ntp.org").unwrap();
     /// println!("{}", ntp);
     /// ```
     pub fn new<T: ToString>(server_addr: T) -> Result<Ntp, Box<dyn std::error::Error>> {
-        let server = server_addr.to_string();
-        let client = UdpSocket::bind("0.0.0.0:0")?;
-        client.set_read_timeout(Some(Duration::from_secs(5)))?;
-    
-        let mut data = vec![0x1b];
-        data.extend(vec![0; 47]); // ping
-    
-        let start_time = Utc::now().timestamp_millis();
-        client.send_to(&data, format!("{}:123", server))?;
-    
+        Ok(Ntp::with_config(&NtpConfig::new(server_addr))?)
+    }
+
+    /// Fetches the time from an NTP server, with the given settings
+    /// 
+    /// Replies from any address other than the one queried are ignored, interrupted reads are retried, and the whole exchange gives up after `config.timeout`
+    ///
+    /// Queries go through the global `RateGuard`: querying a server again within `DEFAULT_MIN_QUERY_INTERVAL` fails with
+    /// `NtpError::RateLimited`, and a server that sent a Kiss-o'-Death `DENY` or `RSTR` is refused with `NtpError::Denied`
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use thetime::{Ntp, NtpConfig};
+    /// let ntp = Ntp::with_config(&NtpConfig::new("pool.ntp.org").timeout(core::time::Duration::from_secs(1))).unwrap();
+    /// println!("{}", ntp);
+    /// ```
+    pub fn with_config(config: &NtpConfig) -> Result<Ntp, NtpError> {
+        #[cfg(feature = "dns")]
+        if let Ok(resolver) = crate::DnsResolver::from_system_conf() {
+            return Ntp::with_resolver(config, &resolver);
+        }
+        Ntp::with_resolver(config, &crate::SystemResolver)
+    }
+
+    /// Fetches the time from an NTP server, with the given settings, looking the server up with `resolver`
+    /// 
+    /// Every address is resolved (after any SRV records), then one is picked with `config.selection` - `peer_addr` gives the one that answered
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use thetime::{Ntp, NtpConfig, ServerSelection, SystemResolver};
+    /// let config = NtpConfig::new("pool.ntp.org").selection(ServerSelection::RoundRobin);
+    /// let ntp = Ntp::with_resolver(&config, &SystemResolver).unwrap();
+    /// println!("{} answered", ntp.peer_addr().unwrap());
+    /// ```
+    pub fn with_resolver(config: &NtpConfig, resolver: &dyn Resolver) -> Result<Ntp, NtpError> {
+        let key = format!("{}:{}", config.server, config.port);
+        with_rate_guard(|guard| guard.check(&key, &crate::System::now())).transpose()?;
+        let addrs = crate::resolve_server(config, resolver)?;
+        let addr = config.selection.select(&addrs).ok_or_else(|| NtpError::Resolve(config.server.clone()))?;
+        let client = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" })?;
+        client.send_to(&NtpPacket::client_request().to_bytes()?, addr)?;
+
+        let deadline = Instant::now() + config.timeout;
+        // larger than any reply should be - anything past the first 48 bytes (extension fields, MACs) is ignored
         let mut buffer = [0; 1024];
-        let (size, _) = client.recv_from(&mut buffer)?;
-    
-        if size > 0 {
-            let t = u32::from_be_bytes([buffer[40], buffer[41], buffer[42], buffer[43]]) as u64;
-            let t = t - REF_TIME_1970;
-    
-            let elapsed_time = start_time - Utc::now().timestamp_millis();
-            let milliseconds = (elapsed_time % 1000).try_into().unwrap_or(0);
-    
-            return Ok(Ntp {
-                server: server.to_string(),
-                inner_secs: (t) + OFFSET_1601,
-                inner_milliseconds: milliseconds,
-                utc_offset: 0,
-            });
-        }
-    
-        Err("Failed to receive NTP response".into())
-    }
-    
+        let packet = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(NtpError::Timeout);
+            }
+            client.set_read_timeout(Some(remaining))?;
+            match client.recv_from(&mut buffer) {
+                Ok((_, from)) if from != addr => continue,
+                Ok((size, _)) => break NtpPacket::from_bytes(&buffer[..size])?,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => return Err(NtpError::Timeout),
+                Err(e) => return Err(e.into()),
+            }
+        };
+
+        if let Some(code) = packet.kiss_code().filter(|code| matches!(code.as_str(), "DENY" | "RSTR")) {
+            with_rate_guard(|guard| guard.record_kiss_of_death(&key, &code));
+            return Err(NtpError::Denied { server: key, code });
+        }
+
+        let ms_1601 = packet.transmit.to_unix_ms() + OFFSET_1601 as i64 * 1000;
+        Ok(Ntp {
+            server: ServerName::from(config.server.as_str()),
+            inner_secs: (ms_1601 / 1000) as u64,
+            inner_milliseconds: (ms_1601 % 1000) as u64,
+            utc_offset: 0,
+            offset_source: OffsetSource::Explicit,
+            last_packet: Some(packet),
+            peer: Some(addr),
+        })
+    }
+}
+
+impl Ntp {
+    /// internal only - a value from `server`, without the packet it came in (as when read back from a cache)
+    pub(crate) fn from_server(ms_1601: u64, offset: i32, server: &str) -> Ntp {
+        Ntp {
+            inner_secs: ms_1601 / 1000,
+            inner_milliseconds: ms_1601 % 1000,
+            server: ServerName::from(server),
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: OffsetSource::Explicit,
+            last_packet: None,
+            peer: None,
+        }
+    }
+
+    /// Returns the parsed response packet, if this value came from an NTP server
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use thetime::{Ntp, Time};
+    /// let ntp = Ntp::now();
+    /// println!("{}", ntp.last_packet().unwrap().hexdump());
+    /// ```
+    pub fn last_packet(&self) -> Option<&NtpPacket> {
+        self.last_packet.as_ref()
+    }
+
+    /// Returns the address of the server that answered, if this value came from an NTP server
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+}
+
+/// An error from the NTP client or packet parsing
+#[derive(Debug)]
+pub enum NtpError {
+    /// the packet was shorter than the 48 bytes an NTP packet needs
+    ShortPacket(usize),
+    /// a packet field held a value outside of its valid range
+    InvalidField { field: &'static str, value: u32 },
+    /// the server name did not resolve to any address
+    Resolve(String),
+    /// no reply arrived within the timeout
+    Timeout,
+    /// a socket error
+    Io(std::io::Error),
+    /// the server was queried too recently - try again after `retry_after` (see `RateGuard`)
+    RateLimited { retry_after: core::time::Duration },
+    /// the server sent a Kiss-o'-Death `DENY` or `RSTR`, and won't be queried again by this process (see `RateGuard`)
+    Denied { server: String, code: String },
+}
+
+impl Display for NtpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            NtpError::ShortPacket(len) => write!(f, "NTP packet too short: {} bytes", len),
+            NtpError::InvalidField { field, value } => write!(f, "invalid NTP packet field {}: {}", field, value),
+            NtpError::Resolve(server) => write!(f, "could not resolve NTP server {}", server),
+            NtpError::Timeout => write!(f, "timed out waiting for an NTP reply"),
+            NtpError::Io(e) => write!(f, "NTP socket error: {}", e),
+            NtpError::RateLimited { retry_after } => write!(f, "NTP server queried too recently, retry in {}ms", retry_after.as_millis()),
+            NtpError::Denied { server, code } => write!(f, "NTP server {} denied access ({})", server, code),
+        }
+    }
+}
+
+impl std::error::Error for NtpError {}
+
+impl From<std::io::Error> for NtpError {
+    fn from(e: std::io::Error) -> Self {
+        NtpError::Io(e)
+    }
 }
@@ -0,0 +1,130 @@
+use crate::{Ntp, System, Time};
+use std::io;
+use std::path::Path;
+
+/// the first bytes of a cache file
+const MAGIC: &[u8; 4] = b"TTNC";
+/// the current cache file version
+const VERSION: u8 = 1;
+/// the bytes before the server name: magic, version, NTP time, offset, local clock, server length
+const HEADER_LEN: usize = 4 + 1 + 8 + 4 + 8 + 2;
+
+/// The last good NTP time, saved to disk so that a device without a real time clock can start with a better estimate than 1970
+///
+/// The file holds the NTP time and what the local clock read when it was saved (the anchor). On the next start, if the local clock has
+/// moved on from the anchor, the estimate is the NTP time plus that difference - if it is behind (a clock reset to 1970 by a reboot),
+/// the estimate falls back to the stored time, and is flagged as stale. Either way it is never earlier than the stored time
+///
+/// The format is a small versioned binary record with a checksum - missing, truncated, corrupted or unknown version files load as None
+///
+/// # Examples
+/// ```rust
+/// use thetime::{NtpCache, Ntp, System, Time, IntTime};
+/// let path = std::env::temp_dir().join(format!("thetime-doc-{}.ntpcache", std::process::id()));
+/// let synced = 1704465989u32.unix::<Ntp>();
+/// NtpCache::save_with(&path, &synced, &1704465989u32.unix::<System>()).unwrap();
+///
+/// // the local clock was reset by a reboot
+/// let estimate = NtpCache::load_with(&path, &0u32.unix::<System>()).unwrap();
+/// assert!(estimate.stale);
+/// assert_eq!(estimate.time.unix(), 1704465989);
+///
+/// // the local clock has run on for a minute
+/// let estimate = NtpCache::load_with(&path, &1704466049u32.unix::<System>()).unwrap();
+/// assert!(!estimate.stale);
+/// assert_eq!(estimate.time.unix(), 1704466049);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NtpCache;
+
+/// An estimate of the current time from `NtpCache::load`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NtpEstimate {
+    /// the estimated time, in the synced time's offset
+    pub time: System,
+    /// the NTP time that was saved
+    pub last_sync: Ntp,
+    /// how long the local clock says has passed since the save, in milliseconds - None if it reads earlier than when the file was saved
+    pub elapsed_ms: Option<u64>,
+    /// whether the local clock could not be used, so `time` is just the saved time (and certainly behind the real time)
+    pub stale: bool,
+}
+
+impl NtpCache {
+    /// Save `ntp` to `path`, with the local clock (`System::now`) as the anchor - the file is written whole and then renamed into place,
+    /// so a crash part way through leaves the old file
+    pub fn save<P: AsRef<Path>>(path: P, ntp: &Ntp) -> io::Result<()> {
+        NtpCache::save_with(path, ntp, &System::now())
+    }
+
+    /// Like `save`, with the local clock reading supplied by the caller
+    pub fn save_with<P: AsRef<Path>>(path: P, ntp: &Ntp, local: &System) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, encode(ntp, local))?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// The estimated time from the file at `path` (see `NtpCache`), or None if it is missing or unreadable - use `load` to tell
+    /// whether the estimate is stale
+    pub fn load_estimate<P: AsRef<Path>>(path: P) -> Option<System> {
+        NtpCache::load(path).map(|estimate| estimate.time)
+    }
+
+    /// The estimate from the file at `path`, judged against the local clock (`System::now`), or None if it is missing or unreadable
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<NtpEstimate> {
+        NtpCache::load_with(path, &System::now())
+    }
+
+    /// Like `load`, with the local clock reading supplied by the caller
+    pub fn load_with<P: AsRef<Path>>(path: P, local_now: &System) -> Option<NtpEstimate> {
+        let (last_sync, anchor) = decode(&std::fs::read(path).ok()?)?;
+        let elapsed_ms = local_now.raw().checked_sub(anchor);
+        let raw = last_sync.raw().saturating_add(elapsed_ms.unwrap_or(0));
+        Some(NtpEstimate {
+            time: System::from_epoch_offset(raw, last_sync.utc_offset()),
+            last_sync,
+            elapsed_ms,
+            stale: elapsed_ms.is_none(),
+        })
+    }
+}
+
+/// internal only - FNV-1a, to catch corruption
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, b| (hash ^ *b as u32).wrapping_mul(0x01000193))
+}
+
+/// internal only - the cache file for `ntp`, saved when the local clock read `local`
+fn encode(ntp: &Ntp, local: &System) -> Vec<u8> {
+    let server = ntp.server();
+    let server = &server.as_bytes()[..server.len().min(u16::MAX as usize)];
+    let mut bytes = Vec::with_capacity(HEADER_LEN + server.len() + 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&ntp.raw().to_le_bytes());
+    bytes.extend_from_slice(&ntp.utc_offset().to_le_bytes());
+    bytes.extend_from_slice(&local.raw().to_le_bytes());
+    bytes.extend_from_slice(&(server.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(server);
+    bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+    bytes
+}
+
+/// internal only - the NTP time and local anchor in a cache file, or None if it is not a valid version 1 file
+fn decode(bytes: &[u8]) -> Option<(Ntp, u64)> {
+    let (body, sum) = bytes.split_at_checked(bytes.len().checked_sub(4)?)?;
+    if body.len() < HEADER_LEN || &body[..4] != MAGIC || body[4] != VERSION || checksum(body).to_le_bytes() != sum {
+        return None;
+    }
+    let u64_at = |at: usize| u64::from_le_bytes(body[at..at + 8].try_into().unwrap());
+    let ms_1601 = u64_at(5);
+    let offset = i32::from_le_bytes(body[13..17].try_into().unwrap());
+    let anchor = u64_at(17);
+    let server_len = u16::from_le_bytes(body[25..27].try_into().unwrap()) as usize;
+    let server = core::str::from_utf8(body.get(HEADER_LEN..).filter(|s| s.len() == server_len)?).ok()?;
+    // reject times chrono can't display, so the estimate is always usable
+    chrono::DateTime::from_timestamp_millis(crate::unix_ms_of_raw(ms_1601))?;
+    Some((Ntp::from_server(ms_1601, offset, server), anchor))
+}
@@ -0,0 +1,225 @@
+use crate::{NtpError, REF_TIME_1970};
+use core::fmt::Write;
+
+/// The length of an NTP packet without extension fields
+pub const NTP_PACKET_LEN: usize = 48;
+
+/// A 64-bit NTP timestamp - seconds since `1900-01-01 00:00:00` and a 32-bit binary fraction of a second
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NtpTimestamp {
+    pub seconds: u32,
+    pub fraction: u32,
+}
+
+impl NtpTimestamp {
+    /// Convert from milliseconds since the Unix epoch (within NTP era 0)
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::NtpTimestamp;
+    /// let ts = NtpTimestamp::from_unix_ms(1704465989500);
+    /// assert_eq!(ts.to_unix_ms(), 1704465989500);
+    /// ```
+    pub fn from_unix_ms(ms: i64) -> Self {
+        let seconds = ms.div_euclid(1000) + REF_TIME_1970 as i64;
+        // round up, so that converting back (which truncates) gives the same millisecond
+        let fraction = ((ms.rem_euclid(1000) as u64) << 32).div_ceil(1000);
+        NtpTimestamp {
+            seconds: seconds as u32,
+            fraction: fraction as u32,
+        }
+    }
+
+    /// Convert to milliseconds since the Unix epoch (within NTP era 0)
+    pub fn to_unix_ms(&self) -> i64 {
+        (self.seconds as i64 - REF_TIME_1970 as i64) * 1000 + ((self.fraction as u64 * 1000) >> 32) as i64
+    }
+
+    fn from_be_bytes(bytes: &[u8]) -> Self {
+        NtpTimestamp {
+            seconds: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            fraction: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        }
+    }
+
+    fn to_be_bytes(self) -> [u8; 8] {
+        let mut out = [0; 8];
+        out[..4].copy_from_slice(&self.seconds.to_be_bytes());
+        out[4..].copy_from_slice(&self.fraction.to_be_bytes());
+        out
+    }
+}
+
+/// An NTP (RFC 5905) packet header, with typed fields
+///
+/// # Examples
+/// ```rust
+/// use thetime::NtpPacket;
+/// let request = NtpPacket::client_request();
+/// let bytes = request.to_bytes().unwrap();
+/// assert_eq!(bytes[0], 0x1b);
+/// assert_eq!(NtpPacket::from_bytes(&bytes).unwrap(), request);
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NtpPacket {
+    /// leap indicator (0-3)
+    pub li: u8,
+    /// version number (1-4)
+    pub vn: u8,
+    /// mode (0-7) - 3 is client, 4 is server
+    pub mode: u8,
+    pub stratum: u8,
+    /// log2 of the maximum poll interval in seconds
+    pub poll: i8,
+    /// log2 of the clock precision in seconds
+    pub precision: i8,
+    /// the total round trip delay to the reference clock, as 16.16 fixed point seconds
+    pub root_delay: u32,
+    /// the total dispersion to the reference clock, as 16.16 fixed point seconds
+    pub root_dispersion: u32,
+    pub ref_id: u32,
+    pub reference: NtpTimestamp,
+    pub origin: NtpTimestamp,
+    pub receive: NtpTimestamp,
+    pub transmit: NtpTimestamp,
+}
+
+impl NtpPacket {
+    /// The request sent to a server - version 3, client mode, everything else zeroed
+    pub fn client_request() -> Self {
+        NtpPacket {
+            vn: 3,
+            mode: 3,
+            ..Default::default()
+        }
+    }
+
+    /// internal only - fails unless `vn` is a version this crate reads (1-4), so `to_bytes` never writes what `from_bytes` rejects
+    fn check_version(vn: u8) -> Result<(), NtpError> {
+        if (1..=4).contains(&vn) {
+            Ok(())
+        } else {
+            Err(NtpError::InvalidField { field: "vn", value: vn as u32 })
+        }
+    }
+
+    /// Encode the packet into its 48 byte wire form, failing if `li` or `mode` don't fit in their bits, or `vn` is outside 1-4
+    pub fn to_bytes(&self) -> Result<[u8; NTP_PACKET_LEN], NtpError> {
+        if self.li > 3 {
+            return Err(NtpError::InvalidField { field: "li", value: self.li as u32 });
+        }
+        NtpPacket::check_version(self.vn)?;
+        if self.mode > 7 {
+            return Err(NtpError::InvalidField { field: "mode", value: self.mode as u32 });
+        }
+
+        let mut out = [0; NTP_PACKET_LEN];
+        out[0] = (self.li << 6) | (self.vn << 3) | self.mode;
+        out[1] = self.stratum;
+        out[2] = self.poll as u8;
+        out[3] = self.precision as u8;
+        out[4..8].copy_from_slice(&self.root_delay.to_be_bytes());
+        out[8..12].copy_from_slice(&self.root_dispersion.to_be_bytes());
+        out[12..16].copy_from_slice(&self.ref_id.to_be_bytes());
+        out[16..24].copy_from_slice(&self.reference.to_be_bytes());
+        out[24..32].copy_from_slice(&self.origin.to_be_bytes());
+        out[32..40].copy_from_slice(&self.receive.to_be_bytes());
+        out[40..48].copy_from_slice(&self.transmit.to_be_bytes());
+        Ok(out)
+    }
+
+    /// Decode a packet, failing if it is shorter than 48 bytes or has a version outside 1-4
+    ///
+    /// Anything after the first 48 bytes (extension fields, MACs) is ignored
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NtpError> {
+        if bytes.len() < NTP_PACKET_LEN {
+            return Err(NtpError::ShortPacket(bytes.len()));
+        }
+        let vn = (bytes[0] >> 3) & 0b111;
+        NtpPacket::check_version(vn)?;
+        let word = |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        Ok(NtpPacket {
+            li: bytes[0] >> 6,
+            vn,
+            mode: bytes[0] & 0b111,
+            stratum: bytes[1],
+            poll: bytes[2] as i8,
+            precision: bytes[3] as i8,
+            root_delay: word(4),
+            root_dispersion: word(8),
+            ref_id: word(12),
+            reference: NtpTimestamp::from_be_bytes(&bytes[16..24]),
+            origin: NtpTimestamp::from_be_bytes(&bytes[24..32]),
+            receive: NtpTimestamp::from_be_bytes(&bytes[32..40]),
+            transmit: NtpTimestamp::from_be_bytes(&bytes[40..48]),
+        })
+    }
+
+    /// The Kiss-o'-Death code, if the packet is one - a stratum 0 reply carries an ASCII code such as `DENY`, `RSTR` or `RATE` in place
+    /// of the reference id
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::NtpPacket;
+    /// let kod = NtpPacket { mode: 4, stratum: 0, ref_id: u32::from_be_bytes(*b"DENY"), ..NtpPacket::client_request() };
+    /// assert_eq!(kod.kiss_code().as_deref(), Some("DENY"));
+    /// assert_eq!(NtpPacket { stratum: 2, ..kod }.kiss_code(), None);
+    /// ```
+    pub fn kiss_code(&self) -> Option<String> {
+        let bytes = self.ref_id.to_be_bytes();
+        // a zero reference id is just an unsynchronised server, not a kiss
+        let is_code = self.ref_id != 0 && bytes.iter().all(|b| *b == 0 || b.is_ascii_graphic());
+        (self.stratum == 0 && is_code).then(|| ref_id_str(self.ref_id, self.stratum))
+    }
+
+    /// Format the 48 bytes of the packet as hex, one field per line, annotated with the decoded values - timestamps as seconds since 1900,
+    /// to the nanosecond
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::NtpPacket;
+    /// println!("{}", NtpPacket::client_request().hexdump());
+    /// ```
+    pub fn hexdump(&self) -> String {
+        let bytes = match self.to_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => return format!("invalid packet: {}", e),
+        };
+        let fields: [(usize, usize, String); 11] = [
+            (0, 1, format!("li={} vn={} mode={}", self.li, self.vn, self.mode)),
+            (1, 2, format!("stratum={}", self.stratum)),
+            (2, 3, format!("poll={}", self.poll)),
+            (3, 4, format!("precision={}", self.precision)),
+            (4, 8, format!("root_delay={:.6}s", self.root_delay as f64 / 65536.0)),
+            (8, 12, format!("root_dispersion={:.6}s", self.root_dispersion as f64 / 65536.0)),
+            (12, 16, format!("ref_id={}", ref_id_str(self.ref_id, self.stratum))),
+            (16, 24, format!("reference={}", timestamp_str(&self.reference))),
+            (24, 32, format!("origin={}", timestamp_str(&self.origin))),
+            (32, 40, format!("receive={}", timestamp_str(&self.receive))),
+            (40, 48, format!("transmit={}", timestamp_str(&self.transmit))),
+        ];
+
+        let mut out = String::new();
+        for (start, end, annotation) in fields {
+            let hex = bytes[start..end].iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join(" ");
+            let _ = writeln!(out, "{:02x}: {:<23}  {}", start, hex, annotation);
+        }
+        out
+    }
+}
+
+/// internal only - an NTP timestamp as decimal seconds, the binary fraction rounded to the nearest nanosecond
+fn timestamp_str(timestamp: &NtpTimestamp) -> String {
+    let nanos = ((timestamp.fraction as u64 * 1_000_000_000 + (1 << 31)) >> 32).min(999_999_999);
+    format!("{}.{:09}", timestamp.seconds, nanos)
+}
+
+/// internal only - stratum 0/1 reference ids are ASCII codes (eg "GPS", "RATE"), others are addresses
+fn ref_id_str(ref_id: u32, stratum: u8) -> String {
+    let bytes = ref_id.to_be_bytes();
+    if stratum <= 1 && bytes.iter().all(|b| *b == 0 || b.is_ascii_graphic()) {
+        bytes.iter().filter(|b| **b != 0).map(|b| *b as char).collect()
+    } else {
+        format!("{}.{}.{}.{}", bytes[0], bytes[1], bytes[2], bytes[3])
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{NtpConfig, NtpError, ParseError};
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::net::{SocketAddr, ToSocketAddrs};
+
+/// How to choose between the addresses a server name resolves to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ServerSelection {
+    /// always the first address, as the resolver returned them
+    First,
+    /// a random address each query, so pools like pool.ntp.org spread the load
+    #[default]
+    Random,
+    /// cycle through the addresses across queries (the position is shared by the whole process)
+    RoundRobin,
+}
+
+static ROUND_ROBIN: AtomicUsize = AtomicUsize::new(0);
+static RANDOM_STATE: AtomicU64 = AtomicU64::new(0);
+
+/// internal only - a cheap, non-cryptographic random number (splitmix64 over the clock and a counter)
+pub(crate) fn random_u64() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let mut z = nanos ^ RANDOM_STATE.fetch_add(0x9e3779b97f4a7c15, Ordering::Relaxed);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl ServerSelection {
+    /// Pick one of `addrs`, or None if there are none
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::ServerSelection;
+    /// let addrs = ["192.0.2.1:123".parse().unwrap(), "192.0.2.2:123".parse().unwrap()];
+    /// assert_eq!(ServerSelection::First.select(&addrs), Some(addrs[0]));
+    /// assert!(addrs.contains(&ServerSelection::Random.select(&addrs).unwrap()));
+    /// ```
+    pub fn select(&self, addrs: &[SocketAddr]) -> Option<SocketAddr> {
+        if addrs.is_empty() {
+            return None;
+        }
+        let index = match self {
+            ServerSelection::First => 0,
+            ServerSelection::Random => (random_u64() % addrs.len() as u64) as usize,
+            ServerSelection::RoundRobin => ROUND_ROBIN.fetch_add(1, Ordering::Relaxed) % addrs.len(),
+        };
+        Some(addrs[index])
+    }
+}
+
+/// A DNS SRV record (RFC 2782)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    /// the host name, without a trailing dot
+    pub target: String,
+}
+
+impl SrvRecord {
+    /// Parse the presentation form of an SRV record's data, eg `10 5 123 ntp1.example.com.`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::SrvRecord;
+    /// let srv = SrvRecord::parse("10 5 123 ntp1.example.com.").unwrap();
+    /// assert_eq!((srv.priority, srv.weight, srv.port), (10, 5, 123));
+    /// assert_eq!(srv.target, "ntp1.example.com");
+    /// ```
+    pub fn parse(data: &str) -> Result<SrvRecord, ParseError> {
+        let trimmed = data.trim();
+        if trimmed.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let mut fields = trimmed.split_whitespace();
+        let mut number = |what: &str| -> Result<u16, ParseError> {
+            let field = fields.next().ok_or_else(|| ParseError::invalid(trimmed, trimmed.len(), what))?;
+            let position = field.as_ptr() as usize - trimmed.as_ptr() as usize;
+            field.parse().map_err(|_| ParseError::invalid(trimmed, position, what))
+        };
+        let priority = number("a priority 0-65535")?;
+        let weight = number("a weight 0-65535")?;
+        let port = number("a port 0-65535")?;
+        let target = fields.next().ok_or_else(|| ParseError::invalid(trimmed, trimmed.len(), "a target host"))?;
+        if let Some(extra) = fields.next() {
+            let position = extra.as_ptr() as usize - trimmed.as_ptr() as usize;
+            return Err(ParseError::invalid(trimmed, position, "end of input"));
+        }
+        Ok(SrvRecord {
+            priority,
+            weight,
+            port,
+            target: target.trim_end_matches('.').to_string(),
+        })
+    }
+}
+
+/// internal only - orders SRV records as RFC 2782 says to try them: by ascending priority, then a weighted random order within each priority
+pub(crate) fn order_srv(records: &[SrvRecord], mut random: impl FnMut() -> u64) -> Vec<SrvRecord> {
+    let mut remaining = records.to_vec();
+    remaining.sort_by_key(|r| r.priority);
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let priority = remaining[0].priority;
+        let group_len = remaining.iter().take_while(|r| r.priority == priority).count();
+        let total: u64 = remaining[..group_len].iter().map(|r| r.weight as u64).sum();
+        let index = if total == 0 {
+            0
+        } else {
+            let mut pick = random() % total;
+            remaining[..group_len]
+                .iter()
+                .position(|r| {
+                    if pick < r.weight as u64 {
+                        true
+                    } else {
+                        pick -= r.weight as u64;
+                        false
+                    }
+                })
+                .unwrap_or(0)
+        };
+        ordered.push(remaining.remove(index));
+    }
+    ordered
+}
+
+/// Looks up the addresses of an NTP server - implement this to plug in your own DNS, or to test without a network
+pub trait Resolver {
+    /// the addresses of `host`, with `port`
+    fn lookup_host(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>>;
+
+    /// the SRV records for `name` (eg `_ntp._udp.pool.ntp.org`) - the default has none, so plain resolution is used
+    fn lookup_srv(&self, _name: &str) -> std::io::Result<Vec<SrvRecord>> {
+        Ok(Vec::new())
+    }
+}
+
+/// The operating system's resolver - all A and AAAA records, but no SRV support
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn lookup_host(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+}
+
+/// A resolver with SRV support, using hickory-resolver and the system's DNS configuration (enabled with the `dns` feature)
+#[cfg(feature = "dns")]
+pub struct DnsResolver(hickory_resolver::Resolver);
+
+#[cfg(feature = "dns")]
+impl DnsResolver {
+    /// Create a resolver from the system's DNS configuration
+    pub fn from_system_conf() -> std::io::Result<Self> {
+        Ok(DnsResolver(hickory_resolver::Resolver::from_system_conf()?))
+    }
+}
+
+#[cfg(feature = "dns")]
+impl Resolver for DnsResolver {
+    fn lookup_host(&self, host: &str, port: u16) -> std::io::Result<Vec<SocketAddr>> {
+        let ips = self.0.lookup_ip(host).map_err(std::io::Error::other)?;
+        Ok(ips.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+    }
+
+    fn lookup_srv(&self, name: &str) -> std::io::Result<Vec<SrvRecord>> {
+        match self.0.srv_lookup(name) {
+            Ok(lookup) => Ok(lookup
+                .iter()
+                .map(|srv| SrvRecord {
+                    priority: srv.priority(),
+                    weight: srv.weight(),
+                    port: srv.port(),
+                    target: srv.target().to_utf8().trim_end_matches('.').to_string(),
+                })
+                .collect()),
+            // no SRV records is the common case, and means falling back to plain resolution
+            Err(e) if matches!(e.kind(), hickory_resolver::error::ResolveErrorKind::NoRecordsFound { .. }) => Ok(Vec::new()),
+            Err(e) => Err(std::io::Error::other(e)),
+        }
+    }
+}
+
+/// The candidate addresses for `config`, best first: from the first `_ntp._udp` SRV target that resolves (if `config.srv` is set and the server is a name), otherwise every address of the server name
+///
+/// # Examples
+/// ```rust
+/// use thetime::{resolve_server, NtpConfig, SystemResolver};
+/// let addrs = resolve_server(&NtpConfig::new("127.0.0.1"), &SystemResolver).unwrap();
+/// assert_eq!(addrs, vec!["127.0.0.1:123".parse().unwrap()]);
+/// ```
+pub fn resolve_server(config: &NtpConfig, resolver: &dyn Resolver) -> Result<Vec<SocketAddr>, NtpError> {
+    // addresses have no SRV records, and a failed SRV lookup falls back to plain resolution
+    if config.srv && config.server.parse::<std::net::IpAddr>().is_err() {
+        let records = resolver.lookup_srv(&format!("_ntp._udp.{}", config.server.trim_end_matches('.'))).unwrap_or_default();
+        for record in order_srv(&records, random_u64) {
+            match resolver.lookup_host(&record.target, record.port) {
+                Ok(addrs) if !addrs.is_empty() => return Ok(addrs),
+                _ => continue,
+            }
+        }
+    }
+    let addrs = resolver.lookup_host(&config.server, config.port)?;
+    if addrs.is_empty() {
+        return Err(NtpError::Resolve(config.server.clone()));
+    }
+    Ok(addrs)
+}
@@ -0,0 +1,164 @@
+use crate::ClockSnapshot;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One NTP measurement: how far the server's clock was ahead of ours, and the round trip delay, both in milliseconds
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NtpSample {
+    pub offset_ms: f64,
+    pub delay_ms: f64,
+}
+
+/// Statistics over repeated NTP samples - mean and median offset, jitter, and the delay range
+///
+/// Samples are kept in the order they were added, as jitter depends on it. Serializes as `{ "samples": [{ "offset_ms": .., "delay_ms": .. }, ..] }`;
+/// see `report` for the computed figures
+///
+/// # Examples
+/// ```rust
+/// use thetime::NtpStats;
+/// let mut stats = NtpStats::new();
+/// for (offset, delay) in [(1.0, 20.0), (3.0, 24.0), (2.0, 22.0)] {
+///     stats.add_sample(offset, delay);
+/// }
+/// assert_eq!(stats.median_offset_ms(), Some(2.0));
+/// assert_eq!(stats.jitter_ms(), Some(2.5f64.sqrt()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NtpStats {
+    samples: Vec<NtpSample>,
+}
+
+/// The figures computed by `NtpStats::report`, all in milliseconds, and None when there are too few samples
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NtpStatsReport {
+    pub count: usize,
+    pub mean_offset_ms: Option<f64>,
+    pub median_offset_ms: Option<f64>,
+    pub jitter_ms: Option<f64>,
+    pub min_delay_ms: Option<f64>,
+    pub max_delay_ms: Option<f64>,
+}
+
+impl NtpStats {
+    /// Statistics with no samples yet
+    pub fn new() -> NtpStats {
+        NtpStats::default()
+    }
+
+    /// Add a sample - non-finite values (NaN, infinities) are ignored, so one bad reading can't poison the figures
+    pub fn add_sample(&mut self, offset_ms: f64, delay_ms: f64) {
+        if offset_ms.is_finite() && delay_ms.is_finite() {
+            self.samples.push(NtpSample { offset_ms, delay_ms });
+        }
+    }
+
+    /// Add the skew and round trip of a `ClockSnapshot` as a sample
+    pub fn add_snapshot(&mut self, snapshot: &ClockSnapshot) {
+        self.add_sample(snapshot.skew_ms as f64, snapshot.rtt_ms as f64);
+    }
+
+    /// The samples, oldest first
+    pub fn samples(&self) -> &[NtpSample] {
+        &self.samples
+    }
+
+    /// How many samples have been added
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// The mean offset, or None without samples
+    pub fn mean_offset_ms(&self) -> Option<f64> {
+        (!self.samples.is_empty()).then(|| self.samples.iter().map(|s| s.offset_ms).sum::<f64>() / self.samples.len() as f64)
+    }
+
+    /// The median offset (the mean of the middle two, for an even count), or None without samples
+    pub fn median_offset_ms(&self) -> Option<f64> {
+        let mut offsets: Vec<f64> = self.samples.iter().map(|s| s.offset_ms).collect();
+        offsets.sort_by(f64::total_cmp);
+        let mid = offsets.len() / 2;
+        match offsets.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(offsets[mid]),
+            _ => Some((offsets[mid - 1] + offsets[mid]) / 2.0),
+        }
+    }
+
+    /// The jitter: the root mean square of the differences between successive offsets, as RFC 5905 defines it, or None with fewer than 2 samples
+    pub fn jitter_ms(&self) -> Option<f64> {
+        if self.samples.len() < 2 {
+            return None;
+        }
+        let squares: f64 = self.samples.windows(2).map(|w| (w[1].offset_ms - w[0].offset_ms).powi(2)).sum();
+        Some((squares / (self.samples.len() - 1) as f64).sqrt())
+    }
+
+    /// The smallest delay, or None without samples
+    pub fn min_delay_ms(&self) -> Option<f64> {
+        self.samples.iter().map(|s| s.delay_ms).min_by(f64::total_cmp)
+    }
+
+    /// The largest delay, or None without samples
+    pub fn max_delay_ms(&self) -> Option<f64> {
+        self.samples.iter().map(|s| s.delay_ms).max_by(f64::total_cmp)
+    }
+
+    /// Every figure at once
+    pub fn report(&self) -> NtpStatsReport {
+        NtpStatsReport {
+            count: self.count(),
+            mean_offset_ms: self.mean_offset_ms(),
+            median_offset_ms: self.median_offset_ms(),
+            jitter_ms: self.jitter_ms(),
+            min_delay_ms: self.min_delay_ms(),
+            max_delay_ms: self.max_delay_ms(),
+        }
+    }
+
+    /// A one line summary of the figures, to 3 decimal places
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::NtpStats;
+    /// let stats: NtpStats = [(1.0, 20.0), (3.0, 24.0), (2.0, 22.0)].into_iter().collect();
+    /// assert_eq!(stats.summary(), "3 samples: offset mean 2.000ms median 2.000ms, jitter 1.581ms, delay 20.000ms..24.000ms");
+    /// assert_eq!(NtpStats::new().summary(), "0 samples");
+    /// ```
+    pub fn summary(&self) -> String {
+        let r = self.report();
+        let (Some(mean), Some(median), Some(min), Some(max)) = (r.mean_offset_ms, r.median_offset_ms, r.min_delay_ms, r.max_delay_ms) else {
+            return format!("{} samples", r.count);
+        };
+        let jitter = r.jitter_ms.map_or("n/a".to_string(), |j| format!("{:.3}ms", j));
+        format!(
+            "{} sample{}: offset mean {:.3}ms median {:.3}ms, jitter {}, delay {:.3}ms..{:.3}ms",
+            r.count,
+            if r.count == 1 { "" } else { "s" },
+            mean,
+            median,
+            jitter,
+            min,
+            max
+        )
+    }
+}
+
+impl Extend<(f64, f64)> for NtpStats {
+    fn extend<I: IntoIterator<Item = (f64, f64)>>(&mut self, iter: I) {
+        for (offset_ms, delay_ms) in iter {
+            self.add_sample(offset_ms, delay_ms);
+        }
+    }
+}
+
+impl FromIterator<(f64, f64)> for NtpStats {
+    fn from_iter<I: IntoIterator<Item = (f64, f64)>>(iter: I) -> NtpStats {
+        let mut stats = NtpStats::new();
+        stats.extend(iter);
+        stats
+    }
+}
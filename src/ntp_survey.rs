@@ -0,0 +1,206 @@
+use crate::{ClockSnapshot, NtpError, NtpStats};
+use core::time::Duration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Settings for `survey`: how far from the median offset a server may be before it is flagged as a falseticker, and how to query each one
+///
+/// # Examples
+/// ```rust
+/// use thetime::SurveyConfig;
+/// let config = SurveyConfig::new().threshold_ms(50).timeout(core::time::Duration::from_secs(2));
+/// assert_eq!(config.threshold_ms, 50);
+/// assert_eq!(config.port, 123);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SurveyConfig {
+    /// the largest distance from the median offset, in milliseconds, a server can be and still agree with the others - 100 by default
+    pub threshold_ms: u64,
+    /// the longest to wait for each server, 5 seconds by default
+    pub timeout: Duration,
+    /// the UDP port, 123 by default
+    pub port: u16,
+}
+
+impl Default for SurveyConfig {
+    fn default() -> Self {
+        SurveyConfig {
+            threshold_ms: 100,
+            timeout: Duration::from_secs(5),
+            port: 123,
+        }
+    }
+}
+
+impl SurveyConfig {
+    /// The default settings
+    pub fn new() -> Self {
+        SurveyConfig::default()
+    }
+
+    /// Use a different falseticker threshold
+    pub fn threshold_ms(mut self, threshold_ms: u64) -> Self {
+        self.threshold_ms = threshold_ms;
+        self
+    }
+
+    /// Use a different timeout for each server
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Use a different port
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+}
+
+/// What one server's reply measured, as returned by the query function given to `survey_with`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SurveyReading {
+    /// how far the server's clock is ahead of ours, in milliseconds
+    pub offset_ms: i64,
+    /// the round trip time, in milliseconds
+    pub delay_ms: u64,
+    /// the server's stratum, if the reply packet is known
+    pub stratum: Option<u8>,
+}
+
+impl From<&ClockSnapshot> for SurveyReading {
+    fn from(snapshot: &ClockSnapshot) -> Self {
+        SurveyReading {
+            offset_ms: snapshot.skew_ms,
+            delay_ms: snapshot.rtt_ms,
+            stratum: snapshot.ntp.last_packet().map(|p| p.stratum),
+        }
+    }
+}
+
+/// One server's line in a `SurveyReport` - the measurements are None if the query failed, with `error` saying why
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SurveyEntry {
+    /// the server as given to `survey`
+    pub server: String,
+    /// how far the server's clock is ahead of ours, in milliseconds
+    pub offset_ms: Option<i64>,
+    /// the round trip time, in milliseconds
+    pub delay_ms: Option<u64>,
+    /// the server's stratum, if known
+    pub stratum: Option<u8>,
+    /// why the query failed
+    pub error: Option<String>,
+    /// whether the offset is more than the threshold from the median offset
+    pub falseticker: bool,
+}
+
+/// The result of `survey`: a line per server in the order given, the median offset of the servers that answered, and which of them
+/// disagree with it
+///
+/// Serializes as `{ "entries": [{ "server": .., "offset_ms": .., "delay_ms": .., "stratum": .., "error": .., "falseticker": .. }, ..],
+/// "median_offset_ms": .., "threshold_ms": .. }`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SurveyReport {
+    /// a line per server, in the order they were given
+    pub entries: Vec<SurveyEntry>,
+    /// the median offset of the servers that answered, None if none did
+    pub median_offset_ms: Option<f64>,
+    /// the falseticker threshold used
+    pub threshold_ms: u64,
+}
+
+impl SurveyReport {
+    /// The recommended server: the one with the shortest round trip out of those that answered and agree with the median, the lowest
+    /// stratum breaking ties (then the earliest listed). None if no server qualifies
+    pub fn best(&self) -> Option<&SurveyEntry> {
+        self.entries
+            .iter()
+            .filter(|e| !e.falseticker && e.error.is_none())
+            .min_by_key(|e| (e.delay_ms, e.stratum.unwrap_or(u8::MAX)))
+    }
+
+    /// The servers flagged as falsetickers
+    pub fn falsetickers(&self) -> impl Iterator<Item = &SurveyEntry> {
+        self.entries.iter().filter(|e| e.falseticker)
+    }
+
+    /// The servers that could not be queried
+    pub fn failures(&self) -> impl Iterator<Item = &SurveyEntry> {
+        self.entries.iter().filter(|e| e.error.is_some())
+    }
+}
+
+/// Query each server in turn and report how well they agree (needs the `ntp-client` feature)
+///
+/// # Examples
+/// ```rust,no_run
+/// use thetime::{survey, SurveyConfig};
+/// let report = survey(&["time.cloudflare.com", "time.google.com", "pool.ntp.org"], &SurveyConfig::new());
+/// println!("use {}", report.best().unwrap().server);
+/// ```
+#[cfg(feature = "ntp-client")]
+pub fn survey(servers: &[&str], config: &SurveyConfig) -> SurveyReport {
+    survey_with(servers, config, |server| {
+        let ntp_config = crate::NtpConfig::new(server).port(config.port).timeout(config.timeout);
+        ClockSnapshot::capture(&ntp_config).map(|snapshot| SurveyReading::from(&snapshot))
+    })
+}
+
+/// Like `survey`, but with each server queried by `query` - `config.timeout` and `config.port` are left to it
+///
+/// # Examples
+/// ```rust
+/// use thetime::{survey_with, NtpError, SurveyConfig, SurveyReading};
+/// let report = survey_with(&["a", "b", "c", "d"], &SurveyConfig::new(), |server| match server {
+///     "a" => Ok(SurveyReading { offset_ms: 12, delay_ms: 30, stratum: Some(2) }),
+///     "b" => Ok(SurveyReading { offset_ms: 8, delay_ms: 20, stratum: Some(1) }),
+///     "c" => Ok(SurveyReading { offset_ms: 5000, delay_ms: 10, stratum: Some(1) }),
+///     _ => Err(NtpError::Timeout),
+/// });
+/// assert_eq!(report.median_offset_ms, Some(12.0));
+/// assert_eq!(report.falsetickers().next().unwrap().server, "c");
+/// assert_eq!(report.best().unwrap().server, "b");
+/// ```
+pub fn survey_with(servers: &[&str], config: &SurveyConfig, mut query: impl FnMut(&str) -> Result<SurveyReading, NtpError>) -> SurveyReport {
+    let mut entries: Vec<SurveyEntry> = servers
+        .iter()
+        .map(|server| match query(server) {
+            Ok(reading) => SurveyEntry {
+                server: server.to_string(),
+                offset_ms: Some(reading.offset_ms),
+                delay_ms: Some(reading.delay_ms),
+                stratum: reading.stratum,
+                error: None,
+                falseticker: false,
+            },
+            Err(e) => SurveyEntry {
+                server: server.to_string(),
+                offset_ms: None,
+                delay_ms: None,
+                stratum: None,
+                error: Some(e.to_string()),
+                falseticker: false,
+            },
+        })
+        .collect();
+
+    let stats: NtpStats = entries
+        .iter()
+        .filter_map(|e| Some((e.offset_ms? as f64, e.delay_ms? as f64)))
+        .collect();
+    let median_offset_ms = stats.median_offset_ms();
+    if let Some(median) = median_offset_ms {
+        for entry in entries.iter_mut() {
+            entry.falseticker = entry.offset_ms.is_some_and(|offset| (offset as f64 - median).abs() > config.threshold_ms as f64);
+        }
+    }
+
+    SurveyReport {
+        entries,
+        median_offset_ms,
+        threshold_ms: config.threshold_ms,
+    }
+}
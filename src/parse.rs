@@ -0,0 +1,676 @@
+//! internal only - hand written parsers for formats chrono's strptime can't express
+
+use crate::{ParseError, ParseOptions, RangeCheck, Time};
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+/// A position-tracking cursor over ASCII input, producing positional `ParseError`s
+///
+/// Works on bytes, so input that is not valid UTF-8 can be parsed as long as the bytes that matter are ASCII
+pub(crate) struct Cursor<'a> {
+    bytes: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Cursor::from_bytes(input.as_bytes())
+    }
+
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Cursor { bytes, pos: 0 }
+    }
+
+    /// the byte `n` past the current position
+    pub fn peek_at(&self, n: usize) -> Option<u8> {
+        self.bytes.get(self.pos + n).copied()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    /// consume `b` if it is next
+    pub fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// consume `b`, or error expecting `what`
+    pub fn expect(&mut self, b: u8, what: &str) -> Result<(), ParseError> {
+        if self.eat(b) {
+            Ok(())
+        } else {
+            Err(self.error(what))
+        }
+    }
+
+    /// an error at the current position
+    pub fn error(&self, expected: &str) -> ParseError {
+        self.error_at(self.pos, expected)
+    }
+
+    /// an error at `pos`, showing the input lossily if it is not UTF-8
+    pub fn error_at(&self, pos: usize, expected: &str) -> ParseError {
+        ParseError::invalid(&String::from_utf8_lossy(self.bytes), pos, expected)
+    }
+
+    /// consume exactly `n` ASCII digits
+    pub fn digits(&mut self, n: usize, what: &str) -> Result<u32, ParseError> {
+        let mut value = 0u32;
+        for _ in 0..n {
+            match self.peek() {
+                Some(b) if b.is_ascii_digit() => {
+                    value = value * 10 + (b - b'0') as u32;
+                    self.pos += 1;
+                }
+                _ => return Err(self.error(what)),
+            }
+        }
+        Ok(value)
+    }
+
+    /// consume exactly `n` digits, checking they are within `min..=max` (reporting the error at the start of the field)
+    pub fn field(&mut self, n: usize, min: u32, max: u32, what: &str) -> Result<u32, ParseError> {
+        let start = self.pos;
+        let value = self.digits(n, what)?;
+        if value < min || value > max {
+            return Err(self.error_at(start, what));
+        }
+        Ok(value)
+    }
+
+    /// the input between two positions, as a string slice (empty if it is not valid UTF-8)
+    pub fn slice(&self, start: usize, end: usize) -> &'a str {
+        core::str::from_utf8(&self.bytes[start..end]).unwrap_or_default()
+    }
+
+    /// consume a run of ASCII digits, returning them as a string slice (possibly empty)
+    pub fn digit_run(&mut self) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b) if b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        // only ASCII digits were consumed, so this is always valid UTF-8
+        self.slice(start, self.pos)
+    }
+}
+
+/// internal only - milliseconds from up to the first three digits of a fraction ("5" is 500ms)
+pub(crate) fn fraction_ms(digits: &str) -> u32 {
+    digits
+        .bytes()
+        .chain(core::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |acc, b| acc * 10 + (b - b'0') as u32)
+}
+
+/// internal only - build a time object from a wall clock and its offset from UTC in seconds
+pub(crate) fn from_wall_and_offset<T: Time>(wall: NaiveDateTime, offset: i32) -> T {
+    T::from_wall_clock(wall, offset)
+}
+
+/// internal only - parse an optional "Z", "+HH", "+HHMM" or "+HH:MM" suffix, returning the offset in seconds (None if absent)
+pub(crate) fn offset_suffix(cur: &mut Cursor) -> Result<Option<i32>, ParseError> {
+    if cur.eat(b'Z') || cur.eat(b'z') {
+        return Ok(Some(0));
+    }
+    let sign = match cur.peek() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => return Ok(None),
+    };
+    cur.pos += 1;
+    let hours = cur.field(2, 0, 23, "offset hours 00-23")? as i32;
+    let had_colon = cur.eat(b':');
+    let minutes = if had_colon || matches!(cur.peek(), Some(b) if b.is_ascii_digit()) {
+        cur.field(2, 0, 59, "offset minutes 00-59")? as i32
+    } else {
+        0
+    };
+    Ok(Some(sign * (hours * 3600 + minutes * 60)))
+}
+
+/// Parse ISO 8601-ish input leniently - see `StrTime::strp_iso_lenient`
+pub(crate) fn iso_lenient<T: Time>(input: &str) -> Result<T, ParseError> {
+    iso_lenient_bytes(input.trim().as_bytes())
+}
+
+/// Parse ISO 8601-ish bytes leniently - see `BytesTime::strp_iso_lenient`
+pub(crate) fn iso_lenient_bytes<T: Time>(input: &[u8]) -> Result<T, ParseError> {
+    let trimmed = input.trim_ascii();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::from_bytes(trimmed);
+
+    let year = cur.digits(4, "a four digit year")? as i32;
+    cur.expect(b'-', "'-'")?;
+    let month = cur.field(2, 1, 12, "month 01-12")?;
+    cur.expect(b'-', "'-'")?;
+    let day_pos = cur.pos;
+    let day = cur.field(2, 1, 31, "day 01-31")?;
+    let date = NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| cur.error_at(day_pos, "a day that exists in the month"))?;
+
+    let (mut hour, mut minute, mut second, mut ms) = (0, 0, 0, 0);
+    if matches!(cur.peek(), Some(b'T' | b't' | b'_' | b' ')) {
+        while matches!(cur.peek(), Some(b'T' | b't' | b'_' | b' ')) {
+            cur.pos += 1;
+        }
+        hour = cur.field(2, 0, 23, "hour 00-23")?;
+        if cur.eat(b':') {
+            minute = cur.field(2, 0, 59, "minute 00-59")?;
+            if cur.eat(b':') {
+                second = cur.field(2, 0, 59, "second 00-59")?;
+                if cur.eat(b'.') || cur.eat(b',') {
+                    let fraction = cur.digit_run();
+                    if fraction.is_empty() {
+                        return Err(cur.error("fractional digits"));
+                    }
+                    ms = fraction_ms(fraction);
+                }
+            }
+        }
+    }
+
+    let offset = offset_suffix(&mut cur)?;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+
+    let time = NaiveTime::from_hms_milli_opt(hour, minute, second, ms).unwrap();
+    Ok(from_wall_and_offset(date.and_time(time), offset.unwrap_or(0)))
+}
+
+/// internal only - the month number (1-12) of a three letter English month abbreviation, ignoring case
+fn month_abbreviation(name: &[u8]) -> Option<u32> {
+    const MONTHS: [&[u8; 3]; 12] = [b"jan", b"feb", b"mar", b"apr", b"may", b"jun", b"jul", b"aug", b"sep", b"oct", b"nov", b"dec"];
+    MONTHS.iter().position(|m| name.eq_ignore_ascii_case(*m)).map(|i| i as u32 + 1)
+}
+
+/// internal only - parse an offset in the form "+|-HH:MM" (hours 00-23) into signed seconds - see `Time::try_change_tz`
+pub(crate) fn utc_offset(input: &str) -> Result<i32, ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(input);
+    let sign = match cur.peek() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => return Err(cur.error("'+' or '-'")),
+    };
+    cur.pos += 1;
+    let hours = cur.field(2, 0, 23, "hours 00-23")? as i32;
+    cur.expect(b':', "':'")?;
+    let minutes = cur.field(2, 0, 59, "minutes 00-59")? as i32;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(sign * (hours * 3600 + minutes * 60))
+}
+
+/// internal only - the weekday named by an English abbreviation ("Tue") or full name ("Tuesday"), ignoring case
+pub(crate) fn weekday_name(name: &[u8]) -> Option<Weekday> {
+    const DAYS: [&str; 7] = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+    let i = DAYS
+        .iter()
+        .position(|d| name.eq_ignore_ascii_case(d.as_bytes()) || name.eq_ignore_ascii_case(&d.as_bytes()[..3]))?;
+    Some(Weekday::try_from(i as u8).unwrap())
+}
+
+/// internal only - consume the letters of a weekday, returning where they start and the day they name (None if they name none - only
+/// an error when the weekday is checked)
+fn weekday(cur: &mut Cursor) -> Result<(usize, Option<Weekday>), ParseError> {
+    let start = cur.pos;
+    while matches!(cur.peek(), Some(b) if b.is_ascii_alphabetic()) {
+        cur.pos += 1;
+    }
+    if cur.pos == start {
+        return Err(cur.error("a weekday"));
+    }
+    Ok((start, weekday_name(cur.slice(start, cur.pos).as_bytes())))
+}
+
+/// internal only - the date from its fields, with the day checked against the month (or rolled over) and the weekday, if one was named,
+/// checked against the date, as `options` say
+fn checked_date(
+    cur: &Cursor,
+    (year, month, day): (i32, u32, u32),
+    day_pos: usize,
+    named: Option<(usize, Option<Weekday>)>,
+    options: &ParseOptions,
+) -> Result<NaiveDate, ParseError> {
+    let date = match (NaiveDate::from_ymd_opt(year, month, day), options.verify_ranges) {
+        (Some(date), _) => Some(date),
+        // the day is at most 31, so this only fails at the very end of chrono's range
+        (None, RangeCheck::Lenient) => NaiveDate::from_ymd_opt(year, month, 1).and_then(|first| first.checked_add_days(Days::new(day as u64 - 1))),
+        (None, RangeCheck::Strict) => None,
+    }
+    .ok_or_else(|| cur.error_at(day_pos, "a day that exists in the month"))?;
+
+    if let (true, Some((weekday_pos, named))) = (options.verify_weekday, named) {
+        let named = named.ok_or_else(|| cur.error_at(weekday_pos, "a weekday name"))?;
+        if named != date.weekday() {
+            return Err(ParseError::WeekdayMismatch { named, computed: date.weekday() });
+        }
+    }
+    Ok(date)
+}
+
+/// internal only - consume one or more spaces
+fn spaces(cur: &mut Cursor) -> Result<(), ParseError> {
+    cur.expect(b' ', "a space")?;
+    while cur.eat(b' ') {}
+    Ok(())
+}
+
+/// Parse JavaScript's `Date.toString()` or `Date.toUTCString()` output - see `StrTime::strp_js_date`
+pub(crate) fn js_date<T: Time>(input: &str, options: &ParseOptions) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+
+    // unless asked, the weekday is only checked for being letters - it is redundant with the date, and often mangled in copies
+    let named = weekday(&mut cur)?;
+    let utc_form = cur.eat(b',');
+    spaces(&mut cur)?;
+
+    let month_of = |cur: &mut Cursor| -> Result<u32, ParseError> {
+        let start = cur.pos;
+        let name = cur.slice(start, (start + 3).min(trimmed.len()));
+        cur.pos = start + name.len();
+        month_abbreviation(name.as_bytes()).ok_or_else(|| cur.error_at(start, "a month name (Jan-Dec)"))
+    };
+    // toUTCString is "Fri, 05 Jan 2024", toString is "Fri Jan 05 2024"
+    let (day_pos, day, month) = if utc_form {
+        let day_pos = cur.pos;
+        let day = cur.field(2, 1, 31, "day 01-31")?;
+        spaces(&mut cur)?;
+        (day_pos, day, month_of(&mut cur)?)
+    } else {
+        let month = month_of(&mut cur)?;
+        spaces(&mut cur)?;
+        let day_pos = cur.pos;
+        (day_pos, cur.field(2, 1, 31, "day 01-31")?, month)
+    };
+    spaces(&mut cur)?;
+    let year = cur.digits(4, "a four digit year")? as i32;
+    let date = checked_date(&cur, (year, month, day), day_pos, Some(named), options)?;
+
+    spaces(&mut cur)?;
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    cur.expect(b':', "':'")?;
+    let second = cur.field(2, 0, 59, "second 00-59")?;
+
+    spaces(&mut cur)?;
+    let zone_start = cur.pos;
+    let zone = cur.slice(zone_start, (zone_start + 3).min(trimmed.len()));
+    if !zone.eq_ignore_ascii_case("GMT") && !zone.eq_ignore_ascii_case("UTC") {
+        return Err(cur.error("\"GMT\""));
+    }
+    cur.pos += 3;
+    let offset = if utc_form {
+        0
+    } else {
+        match cur.peek() {
+            Some(b'+' | b'-') => offset_suffix(&mut cur)?.unwrap_or(0),
+            _ => 0,
+        }
+    };
+
+    // the zone name JS appends, such as "(India Standard Time)", is ignored
+    while cur.eat(b' ') {}
+    if cur.eat(b'(') {
+        while !matches!(cur.peek(), Some(b')') | None) {
+            cur.pos += 1;
+        }
+        cur.expect(b')', "')'")?;
+    }
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+
+    let time = NaiveTime::from_hms_opt(hour, minute, second).unwrap();
+    Ok(from_wall_and_offset(date.and_time(time), offset))
+}
+
+/// internal only - the "YYMMDDHHMMSS" or "YYYYMMDDHHMMSS" of an ASN.1 time, with `year` already read
+fn asn1_wall(cur: &mut Cursor, year: i32) -> Result<NaiveDateTime, ParseError> {
+    let month = cur.field(2, 1, 12, "month 01-12")?;
+    let day_pos = cur.pos;
+    let day = cur.field(2, 1, 31, "day 01-31")?;
+    let date = NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| cur.error_at(day_pos, "a day that exists in the month"))?;
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    let second = cur.field(2, 0, 59, "second 00-59")?;
+    Ok(date.and_time(NaiveTime::from_hms_opt(hour, minute, second).unwrap()))
+}
+
+/// Parse a DER ASN.1 UTCTime - see `StrTime::strp_asn1_utctime`
+pub(crate) fn asn1_utctime<T: Time>(input: &str) -> Result<T, ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(input);
+    let yy = cur.digits(2, "a two digit year")? as i32;
+    let wall = asn1_wall(&mut cur, if yy < 50 { 2000 + yy } else { 1900 + yy })?;
+    cur.expect(b'Z', "'Z'")?;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(from_wall_and_offset(wall, 0))
+}
+
+/// Parse a DER ASN.1 GeneralizedTime - see `StrTime::strp_asn1_generalizedtime`
+pub(crate) fn asn1_generalizedtime<T: Time>(input: &str) -> Result<T, ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(input);
+    let year = cur.digits(4, "a four digit year")? as i32;
+    let mut wall = asn1_wall(&mut cur, year)?;
+    if cur.eat(b'.') {
+        let start = cur.pos;
+        let fraction = cur.digit_run();
+        if fraction.is_empty() {
+            return Err(cur.error("fractional digits"));
+        }
+        if fraction.ends_with('0') {
+            return Err(cur.error_at(start, "a fraction without trailing zeros"));
+        }
+        wall += chrono::Duration::milliseconds(fraction_ms(fraction) as i64);
+    }
+    cur.expect(b'Z', "'Z'")?;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(from_wall_and_offset(wall, 0))
+}
+
+/// Parse an Apache Common Log Format timestamp - see `StrTime::strp_clf`
+pub(crate) fn clf<T: Time>(input: &str) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+    // brackets are optional, and either one may be missing, as when a log line was cut
+    cur.eat(b'[');
+
+    let day_pos = cur.pos;
+    let day = cur.field(2, 1, 31, "day 01-31")?;
+    cur.expect(b'/', "'/'")?;
+    let month_pos = cur.pos;
+    let month = month_abbreviation(cur.slice(month_pos, (month_pos + 3).min(trimmed.len())).as_bytes())
+        .ok_or_else(|| cur.error_at(month_pos, "a month name (Jan-Dec)"))?;
+    cur.pos += 3;
+    cur.expect(b'/', "'/'")?;
+    let year = cur.digits(4, "a four digit year")? as i32;
+    let date = NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| cur.error_at(day_pos, "a day that exists in the month"))?;
+    cur.expect(b':', "':'")?;
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    cur.expect(b':', "':'")?;
+    let second = cur.field(2, 0, 59, "second 00-59")?;
+    cur.expect(b' ', "a space")?;
+    if !matches!(cur.peek(), Some(b'+' | b'-')) {
+        return Err(cur.error("an offset (+HHMM or -HHMM)"));
+    }
+    let offset = offset_suffix(&mut cur)?.unwrap_or(0);
+    cur.eat(b']');
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    let time = NaiveTime::from_hms_opt(hour, minute, second).unwrap();
+    Ok(from_wall_and_offset(date.and_time(time), offset))
+}
+
+/// Parse an nginx error log timestamp - see `StrTime::strp_nginx_error`
+pub(crate) fn nginx_error<T: Time>(input: &str) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+    let year = cur.digits(4, "a four digit year")? as i32;
+    cur.expect(b'/', "'/'")?;
+    let month = cur.field(2, 1, 12, "month 01-12")?;
+    cur.expect(b'/', "'/'")?;
+    let day_pos = cur.pos;
+    let day = cur.field(2, 1, 31, "day 01-31")?;
+    let date = NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| cur.error_at(day_pos, "a day that exists in the month"))?;
+    cur.expect(b' ', "a space")?;
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    cur.expect(b':', "':'")?;
+    let second = cur.field(2, 0, 59, "second 00-59")?;
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    let time = NaiveTime::from_hms_opt(hour, minute, second).unwrap();
+    Ok(from_wall_and_offset::<T>(date.and_time(time), 0).with_offset_source(crate::OffsetSource::AssumedUtc))
+}
+
+/// Parse a two-line element set epoch - see `StrTime::strp_tle_epoch`
+pub(crate) fn tle_epoch<T: Time>(input: &str) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+    let yy = cur.digits(2, "a two digit year")? as i32;
+    let year = if yy < 57 { 2000 + yy } else { 1900 + yy };
+    let day_pos = cur.pos;
+    let ordinal = cur.field(3, 1, 366, "day of year 001-366")?;
+    let date = NaiveDate::from_yo_opt(year, ordinal).ok_or_else(|| cur.error_at(day_pos, "a day that exists in the year"))?;
+
+    let mut ms_of_day = 0;
+    if cur.eat(b'.') {
+        let fraction = cur.digit_run();
+        if fraction.is_empty() {
+            return Err(cur.error("fractional digits"));
+        }
+        // digits past the 18th are far below a millisecond
+        let fraction = &fraction[..fraction.len().min(18)];
+        let scale = 10u128.pow(fraction.len() as u32);
+        let numerator = fraction.parse::<u128>().unwrap_or(0) * 86_400_000;
+        ms_of_day = ((numerator + scale / 2) / scale) as i64;
+    }
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    Ok(from_wall_and_offset(date.and_time(NaiveTime::MIN) + chrono::Duration::milliseconds(ms_of_day), 0))
+}
+
+/// internal only - a three letter month name at the cursor
+fn month_name(cur: &mut Cursor) -> Result<u32, ParseError> {
+    let start = cur.pos;
+    let name = (0..3).map_while(|i| cur.peek_at(i)).collect::<Vec<u8>>();
+    cur.pos += name.len();
+    month_abbreviation(&name).ok_or_else(|| cur.error_at(start, "a month name (Jan-Dec)"))
+}
+
+/// internal only - "HH:MM:SS"
+fn hms(cur: &mut Cursor) -> Result<NaiveTime, ParseError> {
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    cur.expect(b':', "':'")?;
+    let second = cur.field(2, 0, 59, "second 00-59")?;
+    Ok(NaiveTime::from_hms_opt(hour, minute, second).unwrap())
+}
+
+/// Parse an HTTP date in any of the three forms RFC 9110 requires recipients to accept - see `http::parse_http_date`
+pub(crate) fn http_date<T: Time>(input: &str, options: &ParseOptions) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+
+    // as with JS dates, the weekday is only checked for being letters unless asked
+    let named = weekday(&mut cur)?;
+
+    let (day_pos, day, month, year, time) = if cur.eat(b',') {
+        spaces(&mut cur)?;
+        let day_pos = cur.pos;
+        let day = cur.field(2, 1, 31, "day 01-31")?;
+        let (month, year) = if cur.eat(b'-') {
+            // RFC 850: "Sunday, 06-Nov-94 08:49:37 GMT", two digit years below 70 being 20YY
+            let month = month_name(&mut cur)?;
+            cur.expect(b'-', "'-'")?;
+            let yy = cur.digits(2, "a two digit year")? as i32;
+            (month, if yy < 70 { 2000 + yy } else { 1900 + yy })
+        } else {
+            // IMF-fixdate: "Sun, 06 Nov 1994 08:49:37 GMT"
+            spaces(&mut cur)?;
+            let month = month_name(&mut cur)?;
+            spaces(&mut cur)?;
+            (month, cur.digits(4, "a four digit year")? as i32)
+        };
+        spaces(&mut cur)?;
+        let time = hms(&mut cur)?;
+        spaces(&mut cur)?;
+        let zone_start = cur.pos;
+        if cur.slice(zone_start, (zone_start + 3).min(trimmed.len())) != "GMT" {
+            return Err(cur.error("\"GMT\""));
+        }
+        cur.pos += 3;
+        (day_pos, day, month, year, time)
+    } else {
+        // asctime: "Sun Nov  6 08:49:37 1994"
+        spaces(&mut cur)?;
+        let month = month_name(&mut cur)?;
+        spaces(&mut cur)?;
+        let day_pos = cur.pos;
+        let day = cur.digit_run();
+        let day = match day.parse::<u32>() {
+            Ok(d) if day.len() <= 2 && (1..=31).contains(&d) => d,
+            _ => return Err(cur.error_at(day_pos, "day 1-31")),
+        };
+        spaces(&mut cur)?;
+        let time = hms(&mut cur)?;
+        spaces(&mut cur)?;
+        (day_pos, day, month, cur.digits(4, "a four digit year")? as i32, time)
+    };
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+    let date = checked_date(&cur, (year, month, day), day_pos, Some(named), options)?;
+    Ok(from_wall_and_offset(date.and_time(time), 0))
+}
+
+/// internal only - the offset of an RFC 2822 zone: "+HHMM", or one of the obsolete names ("GMT", "EST"...)
+fn rfc2822_zone(cur: &mut Cursor) -> Result<i32, ParseError> {
+    const NAMED: [(&str, i32); 11] = [
+        ("UT", 0),
+        ("GMT", 0),
+        ("Z", 0),
+        ("EST", -5),
+        ("EDT", -4),
+        ("CST", -6),
+        ("CDT", -5),
+        ("MST", -7),
+        ("MDT", -6),
+        ("PST", -8),
+        ("PDT", -7),
+    ];
+    let start = cur.pos;
+    if let Some(sign) = match cur.peek() {
+        Some(b'+') => Some(1),
+        Some(b'-') => Some(-1),
+        _ => None,
+    } {
+        cur.pos += 1;
+        let hours = cur.field(2, 0, 99, "offset hours")? as i32;
+        let minutes = cur.field(2, 0, 59, "offset minutes 00-59")? as i32;
+        return Ok(sign * (hours * 3600 + minutes * 60));
+    }
+    while matches!(cur.peek(), Some(b) if b.is_ascii_alphabetic()) {
+        cur.pos += 1;
+    }
+    let name = cur.slice(start, cur.pos);
+    match NAMED.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        Some((_, hours)) => Ok(hours * 3600),
+        None => Err(cur.error_at(start, "an offset (+HHMM) or zone name")),
+    }
+}
+
+/// Parse an RFC 2822 (email) date - see `StrTime::strp_rfc2822`
+pub(crate) fn rfc2822<T: Time>(input: &str, options: &ParseOptions) -> Result<T, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let mut cur = Cursor::new(trimmed);
+
+    // the weekday is optional
+    let named = if matches!(cur.peek(), Some(b) if b.is_ascii_alphabetic()) {
+        let named = weekday(&mut cur)?;
+        cur.expect(b',', "','")?;
+        while cur.eat(b' ') {}
+        Some(named)
+    } else {
+        None
+    };
+
+    let day_pos = cur.pos;
+    let day = cur.digit_run();
+    let day = match day.parse::<u32>() {
+        Ok(d) if day.len() <= 2 && (1..=31).contains(&d) => d,
+        _ => return Err(cur.error_at(day_pos, "day 1-31")),
+    };
+    spaces(&mut cur)?;
+    let month = month_name(&mut cur)?;
+    spaces(&mut cur)?;
+    // two and three digit years are obsolete, but still turn up: below 50 is 20YY, and otherwise 1900 is added
+    let year_pos = cur.pos;
+    let year = cur.digit_run();
+    let year = match (year.len(), year.parse::<i32>()) {
+        (4, Ok(y)) => y,
+        (2, Ok(y)) if y < 50 => 2000 + y,
+        (2 | 3, Ok(y)) => 1900 + y,
+        _ => return Err(cur.error_at(year_pos, "a four digit year")),
+    };
+    spaces(&mut cur)?;
+
+    let hour = cur.field(2, 0, 23, "hour 00-23")?;
+    cur.expect(b':', "':'")?;
+    let minute = cur.field(2, 0, 59, "minute 00-59")?;
+    let second = if cur.eat(b':') { cur.field(2, 0, 59, "second 00-59")? } else { 0 };
+    spaces(&mut cur)?;
+    let offset = rfc2822_zone(&mut cur)?;
+
+    // a trailing comment, such as "(PDT)", is ignored
+    while cur.eat(b' ') {}
+    if cur.eat(b'(') {
+        while !matches!(cur.peek(), Some(b')') | None) {
+            cur.pos += 1;
+        }
+        cur.expect(b')', "')'")?;
+    }
+    if !cur.is_done() {
+        return Err(cur.error("end of input"));
+    }
+
+    let date = checked_date(&cur, (year, month, day), day_pos, named, options)?;
+    let time = NaiveTime::from_hms_opt(hour, minute, second).unwrap();
+    Ok(from_wall_and_offset(date.and_time(time), offset))
+}
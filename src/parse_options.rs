@@ -0,0 +1,56 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How strictly a date's day is checked against its month, in `ParseOptions`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RangeCheck {
+    /// a day past the end of its month (as in "2024-02-30") is an error
+    #[default]
+    Strict,
+    /// a day past the end of its month rolls over into the next, as C's `mktime` does - "2024-02-30" is March 1st
+    Lenient,
+}
+
+/// Extra checks for the parsers that take them - `StrTime::strp_rfc2822_with`, `StrTime::strp_js_date_with` and
+/// `http::parse_http_date_with`
+///
+/// The default is what the plain parsers do: the weekday is read but not checked, and impossible days are rejected. `strict` turns every
+/// check on, for catching corrupted input
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, ParseOptions, ParseError, Weekday};
+/// // 2003-07-01 was a Tuesday
+/// assert!("Wed, 01 Jul 2003 10:52:37 +0200".strp_rfc2822::<System>().is_ok());
+/// assert_eq!(
+///     "Wed, 01 Jul 2003 10:52:37 +0200".strp_rfc2822_with::<System>(&ParseOptions::strict()),
+///     Err(ParseError::WeekdayMismatch { named: Weekday::Wed, computed: Weekday::Tue })
+/// );
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParseOptions {
+    /// whether a named weekday must be the one the date falls on - errors with `ParseError::WeekdayMismatch` if not
+    pub verify_weekday: bool,
+    /// what to do with a day past the end of its month
+    pub verify_ranges: RangeCheck,
+}
+
+impl ParseOptions {
+    /// Every check on: the weekday must match, and impossible days are rejected
+    pub fn strict() -> Self {
+        ParseOptions {
+            verify_weekday: true,
+            verify_ranges: RangeCheck::Strict,
+        }
+    }
+
+    /// No checks: the weekday is ignored, and days past the end of the month roll over into the next
+    pub fn lenient() -> Self {
+        ParseOptions {
+            verify_weekday: false,
+            verify_ranges: RangeCheck::Lenient,
+        }
+    }
+}
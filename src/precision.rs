@@ -0,0 +1,52 @@
+use core::fmt::{self, Display};
+
+/// How much a conversion to a coarser representation dropped - the milliseconds below its resolution, such as the 999 in
+/// 12:00:00.999 when converting to whole Unix seconds. Returned by `Time::unix_checked` and the other `_checked` conversions
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, PrecisionLoss};
+/// let x = "2024-01-05 14:46:29.999".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f");
+/// assert_eq!(x.unix_checked(), (1704465989, PrecisionLoss { discarded_ms: 999 }));
+/// assert!(!x.unix_checked().1.is_lossless());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PrecisionLoss {
+    /// the milliseconds discarded - less than the resolution of the target representation
+    pub discarded_ms: u32,
+}
+
+impl PrecisionLoss {
+    /// Nothing was dropped
+    pub const NONE: PrecisionLoss = PrecisionLoss { discarded_ms: 0 };
+
+    /// Whether nothing was dropped
+    pub fn is_lossless(&self) -> bool {
+        self.discarded_ms == 0
+    }
+
+    /// internal only - what is lost rounding `ms` down to a multiple of `resolution_ms`
+    pub(crate) fn rounding(ms: i64, resolution_ms: i64) -> Self {
+        PrecisionLoss { discarded_ms: ms.rem_euclid(resolution_ms) as u32 }
+    }
+}
+
+/// "lossless", or "999ms discarded"
+impl Display for PrecisionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.discarded_ms {
+            0 => f.write_str("lossless"),
+            ms => write!(f, "{}ms discarded", ms),
+        }
+    }
+}
+
+/// internal only - with the `strict-conversions` feature, `debug_assert` that `conversion` dropped nothing. The crate's own unit tests
+/// exercise lossy conversions on purpose, so they are exempt
+#[inline]
+pub(crate) fn strict(loss: PrecisionLoss, conversion: &str) {
+    #[cfg(all(feature = "strict-conversions", not(test)))]
+    debug_assert!(loss.is_lossless(), "{} dropped precision: {}", conversion, loss);
+    #[cfg(not(all(feature = "strict-conversions", not(test))))]
+    let _ = (loss, conversion);
+}
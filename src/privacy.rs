@@ -0,0 +1,134 @@
+use crate::{ImplsDuration, Time, TruncateUnit};
+use core::hash::{Hash, Hasher};
+
+/// internal only - 64-bit FNV-1a, used instead of `DefaultHasher` because its output must not change between Rust releases
+struct Fnv1a(u64);
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    // the defaults write native-endian bytes (and `usize` at the pointer width, which includes slice and str length prefixes),
+    // which would make the same id hash differently across targets
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+}
+
+/// internal only - moves `t` by `ms` milliseconds, keeping its offset, clamped at 1601
+fn shift_ms<T: Time>(t: &T, ms: i64) -> T {
+    let raw = (t.raw() as i64).saturating_add(ms).max(0);
+    T::from_epoch_offset(raw as u64, t.utc_offset())
+}
+
+/// Coarsen a time object to the start of `unit`, dropping everything finer (an alias of `Time::truncate_to`)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, TruncateUnit, privacy};
+/// let x = "2024-01-05 14:30:15".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// assert_eq!(privacy::truncate_precision(&x, TruncateUnit::Day).pretty(), "2024-01-05 00:00:00");
+/// ```
+pub fn truncate_precision<T: Time>(t: &T, unit: TruncateUnit) -> T {
+    t.truncate_to(unit)
+}
+
+/// Move a time object by a pseudo-random amount within `±max`, keyed on `id` and `seed`
+///
+/// The same `(id, seed)` pair always gets the same jitter, so all events for one id move together and keep their spacing,
+/// while someone without the seed cannot recover the original times. The jitter has millisecond granularity
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, TimeDiff, privacy};
+/// let x = System::now();
+/// let a = privacy::jitter(&x, core::time::Duration::from_secs(300), "user-42", 0xC0FFEE);
+/// let b = privacy::jitter(&x, core::time::Duration::from_secs(300), "user-42", 0xC0FFEE);
+/// assert_eq!(a, b);
+/// assert!(a.diff(&x) <= 300);
+/// ```
+pub fn jitter<T: Time, D: ImplsDuration, H: Hash + ?Sized>(t: &T, max: D, id: &H, seed: u64) -> T {
+    let max = max.num_milliseconds().abs();
+    if max == 0 {
+        return shift_ms(t, 0);
+    }
+    let mut hasher = Fnv1a(0xcbf29ce484222325);
+    seed.hash(&mut hasher);
+    id.hash(&mut hasher);
+    let span = (2 * max as u64) + 1;
+    shift_ms(t, (hasher.finish() % span) as i64 - max)
+}
+
+/// Shift every time in `times` by the same hidden amount - ordering and the gaps between times are preserved
+///
+/// Use `unshift_dataset` with the same secret to get the original times back. A shift that would take a time before 1601 stops at
+/// 1601, and that time can't be recovered
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, IntTime, privacy};
+/// let mut times = vec![1704465989u32.unix::<System>(), 1704466000u32.unix::<System>()];
+/// privacy::shift_dataset(&mut times, chrono::Duration::days(-1234));
+/// assert_eq!(times[1].unix() - times[0].unix(), 11);
+/// privacy::unshift_dataset(&mut times, chrono::Duration::days(-1234));
+/// assert_eq!(times[0].unix(), 1704465989);
+/// ```
+pub fn shift_dataset<T: Time, D: ImplsDuration>(times: &mut [T], secret_offset: D) {
+    let ms = secret_offset.num_milliseconds();
+    for t in times.iter_mut() {
+        *t = shift_ms(t, ms);
+    }
+}
+
+/// Undo `shift_dataset`, given the same secret offset - except for any times the shift clamped at 1601, which come back later than they
+/// were
+pub fn unshift_dataset<T: Time, D: ImplsDuration>(times: &mut [T], secret_offset: D) {
+    let ms = secret_offset.num_milliseconds();
+    for t in times.iter_mut() {
+        *t = shift_ms(t, -ms);
+    }
+}
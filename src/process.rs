@@ -0,0 +1,152 @@
+use crate::{ParseError, System, Time};
+use core::time::Duration;
+use std::sync::OnceLock;
+
+/// the wall clock when `app_start` was first called
+static APP_START: OnceLock<System> = OnceLock::new();
+
+/// The wall clock time this process started, for log correlation - captured on the first call, so call it early in `main`
+///
+/// Every later call returns the same value. The real clock is read even if a mock time is set; with the `testing` feature,
+/// `mock::set_app_start` overrides it on the current thread
+///
+/// # Examples
+/// ```rust
+/// use thetime::app_start;
+/// let start = app_start();
+/// assert!(std::ptr::eq(start, app_start()));
+/// println!("started at {}", start);
+/// ```
+pub fn app_start() -> &'static System {
+    #[cfg(feature = "testing")]
+    if let Some(start) = crate::mock::app_start_override() {
+        return start;
+    }
+    APP_START.get_or_init(System::unmocked_now)
+}
+
+/// How long this process has been running by the wall clock (`System::now` less `app_start`), zero if the clock has been set back
+/// past the start
+///
+/// # Examples
+/// ```rust
+/// use thetime::app_uptime;
+/// let before = app_uptime();
+/// assert!(app_uptime() >= before);
+/// ```
+pub fn app_uptime() -> Duration {
+    Duration::from_millis(relative_to_start(&System::now()).max(0) as u64)
+}
+
+/// Milliseconds from `app_start` to `t` - negative if `t` is before the process started
+///
+/// # Examples
+/// ```rust
+/// use thetime::{app_start, relative_to_start, Time};
+/// assert_eq!(relative_to_start(&app_start().add_seconds(-2)), -2000);
+/// ```
+pub fn relative_to_start<T: Time>(t: &T) -> i64 {
+    t.unix_ms().saturating_sub(app_start().unix_ms())
+}
+
+/// Parse the `ELAPSED`/`etime` column printed by `ps`, in the form `[[dd-]hh:]mm:ss`
+///
+/// # Examples
+/// ```rust
+/// use thetime::parse_etime;
+/// assert_eq!(parse_etime("03:21").unwrap().as_secs(), 201);
+/// assert_eq!(parse_etime("1-02:03:04").unwrap().as_secs(), 93784);
+/// ```
+pub fn parse_etime(etime: &str) -> Result<Duration, ParseError> {
+    let trimmed = etime.trim();
+    if trimmed.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let (days, clock, clock_start) = match trimmed.split_once('-') {
+        Some((days, clock)) => (Some(days), clock, days.len() + 1),
+        None => (None, trimmed, 0),
+    };
+
+    let mut fields = Vec::new();
+    let mut position = clock_start;
+    for field in clock.split(':') {
+        if field.is_empty() || !field.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseError::invalid(trimmed, position, "digits"));
+        }
+        fields.push(field.parse::<u64>().map_err(|_| ParseError::invalid(trimmed, position, "digits"))?);
+        position += field.len() + 1;
+    }
+
+    let (hours, minutes, seconds) = match (days, fields.as_slice()) {
+        (None, [m, s]) => (0, *m, *s),
+        (_, [h, m, s]) => (*h, *m, *s),
+        _ => return Err(ParseError::invalid(trimmed, clock_start, "[[dd-]hh:]mm:ss")),
+    };
+
+    let days = match days {
+        Some(d) if !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit()) => {
+            d.parse::<u64>().map_err(|_| ParseError::invalid(trimmed, 0, "digits"))?
+        }
+        Some(_) => return Err(ParseError::invalid(trimmed, 0, "digits")),
+        None => 0,
+    };
+
+    if hours >= 24 {
+        return Err(ParseError::OutOfRange { field: "hours", value: i64::try_from(hours).unwrap_or(i64::MAX) });
+    }
+    if minutes >= 60 {
+        return Err(ParseError::OutOfRange { field: "minutes", value: i64::try_from(minutes).unwrap_or(i64::MAX) });
+    }
+    if seconds >= 60 {
+        return Err(ParseError::OutOfRange { field: "seconds", value: i64::try_from(seconds).unwrap_or(i64::MAX) });
+    }
+
+    // hours, minutes and seconds are in range, so only the days can overflow - but every step is checked regardless
+    let secs = days
+        .checked_mul(24)
+        .and_then(|h| h.checked_add(hours))
+        .and_then(|h| h.checked_mul(60))
+        .and_then(|m| m.checked_add(minutes))
+        .and_then(|m| m.checked_mul(60))
+        .and_then(|s| s.checked_add(seconds));
+    secs.map(Duration::from_secs).ok_or(ParseError::OutOfRange {
+        field: "days",
+        value: i64::try_from(days).unwrap_or(i64::MAX),
+    })
+}
+
+/// The tick rate the Linux kernel reports `/proc` times in (`USER_HZ`, which is 100 on every mainstream architecture)
+#[cfg(target_os = "linux")]
+pub const USER_HZ: u64 = 100;
+
+/// Get the age of a running process, from its start time in `/proc/[pid]/stat` and the boot time in `/proc/stat`
+///
+/// # Examples
+/// ```rust
+/// use thetime::process_age;
+/// println!("running for {:?}", process_age(std::process::id()).unwrap());
+/// ```
+#[cfg(target_os = "linux")]
+pub fn process_age(pid: u32) -> Result<Duration, Box<dyn std::error::Error>> {
+    use crate::{IntTime, TimeDiff};
+
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    // the command name (field 2) is in parentheses and may contain spaces, so start counting after it
+    let after_comm = stat.rsplit_once(')').ok_or("malformed /proc/[pid]/stat")?.1;
+    let start_ticks: u64 = after_comm
+        .split_whitespace()
+        .nth(22 - 3)
+        .ok_or("missing starttime in /proc/[pid]/stat")?
+        .parse()?;
+
+    let btime: u64 = std::fs::read_to_string("/proc/stat")?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .ok_or("missing btime in /proc/stat")?
+        .trim()
+        .parse()?;
+
+    let started = System::from_proc_starttime(start_ticks, &btime.unix::<System>(), USER_HZ);
+    Ok(Duration::from_millis(System::now().diff_ms(&started)))
+}
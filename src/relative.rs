@@ -0,0 +1,107 @@
+use crate::NameTable;
+use chrono::{Datelike, NaiveDateTime};
+
+/// The phrases `Time::describe_relative_with` builds its descriptions from, with the month and weekday names to fill them in
+///
+/// Each phrase is a template: `{time}` becomes the time as `HH:MM`, `{weekday}` and `{month}` the names from `names`, and `{year}` the
+/// year. `RelativeWords::EN` is the built-in English table - other languages can be added by filling in the fields
+///
+/// # Examples
+/// ```rust
+/// use thetime::{NameTable, RelativeWords, System, Time, IntTime};
+/// let de = RelativeWords {
+///     names: NameTable::DE,
+///     today: "heute um {time}",
+///     yesterday: "gestern",
+///     ..RelativeWords::EN
+/// };
+/// let now = 1704465989u32.unix::<System>();
+/// assert_eq!(now.add_hours(-3).describe_relative_with(&now, &de), "heute um 11:46");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RelativeWords {
+    /// month and weekday names
+    pub names: NameTable,
+    /// the same day
+    pub today: &'static str,
+    /// the day before
+    pub yesterday: &'static str,
+    /// the day after
+    pub tomorrow: &'static str,
+    /// 2 to 6 days before
+    pub last_weekday: &'static str,
+    /// 2 to 6 days after
+    pub next_weekday: &'static str,
+    /// 7 to 13 days before
+    pub last_week: &'static str,
+    /// 7 to 13 days after
+    pub next_week: &'static str,
+    /// further back, in the same month
+    pub earlier_this_month: &'static str,
+    /// further ahead, in the same month
+    pub later_this_month: &'static str,
+    /// the month before
+    pub last_month: &'static str,
+    /// the month after
+    pub next_month: &'static str,
+    /// 2 to 11 months before
+    pub past_month: &'static str,
+    /// 2 to 11 months after
+    pub future_month: &'static str,
+    /// a year or more away, either way
+    pub other_year: &'static str,
+}
+
+impl RelativeWords {
+    /// English
+    pub const EN: RelativeWords = RelativeWords {
+        names: NameTable::EN,
+        today: "today at {time}",
+        yesterday: "yesterday",
+        tomorrow: "tomorrow at {time}",
+        last_weekday: "last {weekday}",
+        next_weekday: "on {weekday}",
+        last_week: "last week",
+        next_week: "next week",
+        earlier_this_month: "earlier this month",
+        later_this_month: "later this month",
+        last_month: "last month",
+        next_month: "next month",
+        past_month: "last {month}",
+        future_month: "in {month}",
+        other_year: "in {month} {year}",
+    };
+
+    /// internal only - describe the wall clock `when` as seen from the wall clock `now`
+    pub(crate) fn describe(&self, when: NaiveDateTime, now: NaiveDateTime) -> String {
+        let days = (when.date() - now.date()).num_days();
+        let months = (when.year() as i64 * 12 + when.month0() as i64) - (now.year() as i64 * 12 + now.month0() as i64);
+        let template = match (days, months) {
+            (0, _) => self.today,
+            (-1, _) => self.yesterday,
+            (1, _) => self.tomorrow,
+            (-6..=-2, _) => self.last_weekday,
+            (2..=6, _) => self.next_weekday,
+            (-13..=-7, _) => self.last_week,
+            (7..=13, _) => self.next_week,
+            (..=-14, 0) => self.earlier_this_month,
+            (_, 0) => self.later_this_month,
+            (_, -1) => self.last_month,
+            (_, 1) => self.next_month,
+            (_, -11..=-2) => self.past_month,
+            (_, 2..=11) => self.future_month,
+            _ => self.other_year,
+        };
+        template
+            .replace("{time}", &when.format("%H:%M").to_string())
+            .replace("{weekday}", self.names.weekdays[when.weekday().num_days_from_monday() as usize])
+            .replace("{month}", self.names.months[when.month0() as usize])
+            .replace("{year}", &when.year().to_string())
+    }
+}
+
+impl Default for RelativeWords {
+    fn default() -> Self {
+        RelativeWords::EN
+    }
+}
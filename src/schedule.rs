@@ -0,0 +1,169 @@
+use crate::Time;
+use core::time::Duration;
+use std::collections::VecDeque;
+
+/// A rule for when something recurs, as used by `next_fire_in` and `CatchUp`
+pub trait Recurrence {
+    /// The first occurrence strictly after `after`, in `after`'s offset - None if there are no more
+    fn next_after<T: Time>(&self, after: &T) -> Option<T>;
+
+    /// The `n`th latest occurrence at or before `at` (`n = 1` being the latest), in `at`'s offset - None if there are fewer than `n`.
+    /// Lets `CatchUp` skip over a long gap rather than step through it; the default is always None, which means stepping from the start
+    fn nth_before<T: Time>(&self, at: &T, n: usize) -> Option<T> {
+        let _ = (at, n);
+        None
+    }
+}
+
+impl<R: Recurrence + ?Sized> Recurrence for &R {
+    fn next_after<T: Time>(&self, after: &T) -> Option<T> {
+        (**self).next_after(after)
+    }
+
+    fn nth_before<T: Time>(&self, at: &T, n: usize) -> Option<T> {
+        (**self).nth_before(at, n)
+    }
+}
+
+/// A recurrence at a fixed interval from an anchor time: the anchor, the anchor plus one step, plus two steps, and so on
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Every, Recurrence, System, Time, StrTime};
+/// use core::time::Duration;
+/// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let daily = Every::new(&at("2024-01-01 03:00:00"), Duration::from_secs(86400));
+/// assert_eq!(daily.next_after(&at("2024-01-05 14:46:29")).unwrap().pretty(), "2024-01-06 03:00:00");
+/// assert_eq!(daily.next_after(&at("2023-06-01 00:00:00")).unwrap().pretty(), "2024-01-01 03:00:00");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Every {
+    anchor_raw: u64,
+    step_ms: u64,
+}
+
+impl Every {
+    /// Recur every `step` from `anchor` - a step under a millisecond is taken as one millisecond
+    pub fn new<T: Time>(anchor: &T, step: Duration) -> Every {
+        Every {
+            anchor_raw: anchor.raw(),
+            step_ms: u64::try_from(step.as_millis()).unwrap_or(u64::MAX).max(1),
+        }
+    }
+}
+
+impl Recurrence for Every {
+    fn next_after<T: Time>(&self, after: &T) -> Option<T> {
+        let raw = match after.raw().checked_sub(self.anchor_raw) {
+            None => self.anchor_raw,
+            Some(since) => (since / self.step_ms + 1).checked_mul(self.step_ms)?.checked_add(self.anchor_raw)?,
+        };
+        (raw <= crate::FAR_FUTURE_UNIX_MS as u64 + crate::OFFSET_1601 * 1000).then(|| T::from_epoch_offset(raw, after.utc_offset()))
+    }
+
+    fn nth_before<T: Time>(&self, at: &T, n: usize) -> Option<T> {
+        let latest = at.raw().checked_sub(self.anchor_raw)? / self.step_ms;
+        let back = u64::try_from(n).ok()?.checked_sub(1)?;
+        let raw = self.anchor_raw + latest.checked_sub(back)? * self.step_ms;
+        Some(T::from_epoch_offset(raw, at.utc_offset()))
+    }
+}
+
+/// How long to sleep until the next occurrence of `rule` at or after `now` - zero if one is due right now, None if there are no more
+///
+/// # Examples
+/// ```rust
+/// use thetime::{next_fire_in, Every, System, Time, StrTime};
+/// use core::time::Duration;
+/// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let hourly = Every::new(&at("2024-01-01 00:00:00"), Duration::from_secs(3600));
+/// assert_eq!(next_fire_in(&hourly, &at("2024-01-05 14:46:29")), Some(Duration::from_secs(13 * 60 + 31)));
+/// assert_eq!(next_fire_in(&hourly, &at("2024-01-05 15:00:00")), Some(Duration::ZERO));
+/// ```
+pub fn next_fire_in<R: Recurrence, T: Time>(rule: &R, now: &T) -> Option<Duration> {
+    let just_before = T::from_epoch_offset(now.raw().checked_sub(1)?, now.utc_offset());
+    let next = rule.next_after(&just_before)?;
+    Some(Duration::from_millis(next.raw() - now.raw()))
+}
+
+/// What to run for occurrences that were due while a process was not running, for `CatchUp`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MissedPolicy {
+    /// run nothing if more than one occurrence was due - a single due occurrence is just on time, and still runs
+    #[default]
+    Skip,
+    /// run every occurrence that was due, oldest first (up to the `CatchUp` maximum, keeping the most recent)
+    CatchUpAll,
+    /// run once, for the most recent occurrence that was due
+    CatchUpOne,
+}
+
+/// Decides which occurrences of a rule to run after a gap, such as a daemon waking from sleep
+///
+/// The catch-up list holds at most `max` occurrences (100 by default), however long the gap was
+///
+/// # Examples
+/// ```rust
+/// use thetime::{CatchUp, Every, MissedPolicy, System, Time, StrTime};
+/// use core::time::Duration;
+/// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let daily = Every::new(&at("2024-01-01 03:00:00"), Duration::from_secs(86400));
+/// let runs = CatchUp::new(MissedPolicy::CatchUpOne).fires_between(&daily, &at("2024-01-01 03:00:00"), &at("2024-01-04 12:00:00"));
+/// assert_eq!(runs.len(), 1);
+/// assert_eq!(runs[0].pretty(), "2024-01-04 03:00:00");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CatchUp {
+    /// what to run for missed occurrences
+    pub policy: MissedPolicy,
+    /// the most occurrences `fires_between` returns
+    pub max: usize,
+}
+
+impl CatchUp {
+    /// A catch-up with the given policy, returning at most 100 occurrences
+    pub fn new(policy: MissedPolicy) -> CatchUp {
+        CatchUp { policy, max: 100 }
+    }
+
+    /// Return at most `max` occurrences
+    pub fn max(mut self, max: usize) -> CatchUp {
+        self.max = max;
+        self
+    }
+
+    /// The occurrences of `rule` after `last_run` and up to and including `now` that should run, oldest first, per the policy - record
+    /// `now` as the new last run afterwards, so that skipped occurrences are not offered again
+    ///
+    /// Rules that implement `Recurrence::nth_before` (such as `Every`) take time in `max`, not in the length of the gap
+    pub fn fires_between<R: Recurrence, T: Time>(&self, rule: &R, last_run: &T, now: &T) -> Vec<T> {
+        let keep = match self.policy {
+            MissedPolicy::CatchUpAll => self.max,
+            // the last two are enough to tell one due occurrence from several
+            MissedPolicy::Skip | MissedPolicy::CatchUpOne => 2,
+        };
+        let mut due = VecDeque::with_capacity(keep.min(16));
+        // skip straight to the last `keep` occurrences when the rule can count back, otherwise step through from the last run
+        let cursor = rule.nth_before(&T::from_epoch_offset(now.raw(), last_run.utc_offset()), keep);
+        let mut cursor = match cursor {
+            Some(t) if t.raw() > last_run.raw() => Some(t),
+            _ => rule.next_after(last_run),
+        };
+        while let Some(t) = cursor {
+            if t.raw() > now.raw() || keep == 0 {
+                break;
+            }
+            cursor = rule.next_after(&t);
+            if due.len() == keep {
+                due.pop_front();
+            }
+            due.push_back(t);
+        }
+
+        match self.policy {
+            MissedPolicy::CatchUpAll => due.into(),
+            MissedPolicy::Skip if due.len() > 1 => Vec::new(),
+            MissedPolicy::Skip | MissedPolicy::CatchUpOne => due.pop_back().filter(|_| self.max > 0).into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Serde helpers for timestamps that don't survive a trip through a JSON number
+//!
+//! JavaScript (and any JSON parser that reads numbers as `f64`) only holds integers exactly up to 2^53 (9007199254740992). Windows
+//! timestamps (~1.3e17 today), WebKit timestamps and nanosecond counts are well past that, so as JSON numbers they are silently rounded.
+//! The helpers here write them as decimal strings instead, and accept either a string or a number when reading
+//!
+//! # Examples
+//! ```rust
+//! use thetime::{System, Time, serde::PrecisionSafe};
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Event {
+//!     #[serde(with = "thetime::serde::string_windows_ns")]
+//!     at: System,
+//!     #[serde(with = "thetime::serde::string_ms")]
+//!     seen: System,
+//!     id: PrecisionSafe<u64>,
+//! }
+//! let json = r#"{"at":"133491133891230000","seen":1704639789123,"id":"18014398509481985"}"#;
+//! let event: Event = serde_json::from_str(json).unwrap();
+//! assert_eq!(event.at.unix_ms(), 1704639789123);
+//! assert_eq!(event.seen, event.at);
+//! assert_eq!(event.id.0, (1 << 54) + 1);
+//! assert_eq!(serde_json::to_string(&event).unwrap(), r#"{"at":"133491133891230000","seen":"1704639789123","id":"18014398509481985"}"#);
+//! ```
+
+use crate::epoch::{Epoch, Unit};
+use crate::Time;
+use ::serde::de::{self, Deserializer, Visitor};
+use ::serde::{Deserialize, Serialize, Serializer};
+use core::fmt::Display;
+
+/// The largest integer an `f64` (and so a JavaScript number) holds exactly, 2^53
+pub const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// internal only - reads an integer from a decimal string or any JSON number (a float only if it is a whole number)
+struct IntVisitor;
+
+impl Visitor<'_> for IntVisitor {
+    type Value = i128;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("an integer, or a string holding one")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<i128, E> {
+        Ok(v as i128)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<i128, E> {
+        Ok(v as i128)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<i128, E> {
+        if v.fract() == 0.0 && v.abs() < 1e38 {
+            Ok(v as i128)
+        } else {
+            Err(E::invalid_value(de::Unexpected::Float(v), &self))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<i128, E> {
+        v.trim().parse().map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+/// internal only - an integer from `IntVisitor`, narrowed to `T`
+fn deserialize_int<'de, T: TryFrom<i128>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    let value = deserializer.deserialize_any(IntVisitor)?;
+    T::try_from(value).map_err(|_| de::Error::custom(format!("{} is out of range", value)))
+}
+
+/// An integer that serializes as a decimal string, so it survives JSON parsers that read numbers as `f64` - see the module docs for the
+/// 2^53 hazard. Deserializes from either a string or a number
+///
+/// # Examples
+/// ```rust
+/// use thetime::serde::PrecisionSafe;
+/// let big = PrecisionSafe(133491133891230001i64);
+/// assert_eq!(serde_json::to_string(&big).unwrap(), r#""133491133891230001""#);
+/// assert_eq!(serde_json::from_str::<PrecisionSafe<i64>>("42").unwrap(), PrecisionSafe(42));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PrecisionSafe<T>(pub T);
+
+impl<T: Display> Serialize for PrecisionSafe<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de, T: TryFrom<i128>> Deserialize<'de> for PrecisionSafe<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_int(deserializer).map(PrecisionSafe)
+    }
+}
+
+impl<T> From<T> for PrecisionSafe<T> {
+    fn from(value: T) -> Self {
+        PrecisionSafe(value)
+    }
+}
+
+/// `#[serde(with = "thetime::serde::precision_safe")]` for a plain integer field - the same format as `PrecisionSafe`, without the wrapper
+pub mod precision_safe {
+    use super::*;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T: TryFrom<i128>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        deserialize_int(deserializer)
+    }
+}
+
+/// `#[serde(with = "thetime::serde::string_ms")]` for a time object field, written as Unix milliseconds in a string (and read from a string or a number)
+pub mod string_ms {
+    use super::*;
+
+    pub fn serialize<T: Time, S: Serializer>(time: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&time.unix_ms())
+    }
+
+    pub fn deserialize<'de, T: Time, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let ms: i64 = deserialize_int(deserializer)?;
+        Epoch::Unix.decode(ms, Unit::Milliseconds).map_err(de::Error::custom)
+    }
+}
+
+/// `#[serde(with = "thetime::serde::string_windows_ns")]` for a time object field, written as a Windows timestamp (100ns ticks since 1601) in a
+/// string (and read from a string or a number). Times too far in the future for an `i64` of ticks fail to serialize
+pub mod string_windows_ns {
+    use super::*;
+
+    pub fn serialize<T: Time, S: Serializer>(time: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let ticks = time.try_windows_ns().map_err(::serde::ser::Error::custom)?;
+        serializer.collect_str(&ticks)
+    }
+
+    pub fn deserialize<'de, T: Time, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        let ticks: i64 = deserialize_int(deserializer)?;
+        Epoch::Windows.decode(ticks, Unit::Ticks100ns).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{System, Time};
+use core::time::Duration;
+
+/// How long until `t`, measured against `System::now()`, or None if `t` is now or in the past
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sleep::duration_until};
+/// assert_eq!(duration_until(&System::now().add_seconds(-5)), None);
+/// assert!(duration_until(&System::now().add_seconds(60)).unwrap().as_secs() <= 60);
+/// ```
+pub fn duration_until<T: Time>(t: &T) -> Option<Duration> {
+    let ms = t.unix_ms() - System::now().unix_ms();
+    if ms > 0 {
+        Some(Duration::from_millis(ms as u64))
+    } else {
+        None
+    }
+}
+
+/// Block the current thread until `t` - returns immediately if `t` is not in the future
+///
+/// The wait is computed once, so later changes to the system clock do not affect it
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sleep::sleep_until};
+/// sleep_until(&System::now().add_seconds(-1)); // already passed, returns straight away
+/// ```
+pub fn sleep_until<T: Time>(t: &T) {
+    if let Some(remaining) = duration_until(t) {
+        std::thread::sleep(remaining);
+    }
+}
+
+/// Convert a wall-clock time into a `tokio::time::Instant` (enabled with the `tokio` feature)
+///
+/// `Instant`s come from a monotonic clock unrelated to the wall clock, so the current time is read from both clocks together and the gap is applied.
+/// Times in the past map to instants in the past (or `Instant::now()` if the monotonic clock does not reach back that far)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sleep::to_tokio_instant};
+/// let deadline = to_tokio_instant(&System::now().add_seconds(2));
+/// assert!(deadline > tokio::time::Instant::now());
+/// ```
+#[cfg(feature = "tokio")]
+pub fn to_tokio_instant<T: Time>(t: &T) -> tokio::time::Instant {
+    // read the monotonic clock either side of the wall clock, and use the midpoint as its pair
+    let before = tokio::time::Instant::now();
+    let wall_ms = System::now().unix_ms();
+    let after = tokio::time::Instant::now();
+    let now = before + (after - before) / 2;
+
+    let delta = t.unix_ms() - wall_ms;
+    if delta >= 0 {
+        now + Duration::from_millis(delta as u64)
+    } else {
+        now.checked_sub(Duration::from_millis(delta.unsigned_abs())).unwrap_or(now)
+    }
+}
+
+/// A tokio sleep future that completes at `t` (enabled with the `tokio` feature) - completes immediately for past times
+///
+/// # Examples
+/// ```rust,no_run
+/// use thetime::{System, Time, sleep::tokio_sleep_until};
+/// # async fn run() {
+/// tokio_sleep_until(&System::now().add_seconds(5)).await;
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub fn tokio_sleep_until<T: Time>(t: &T) -> tokio::time::Sleep {
+    tokio::time::sleep_until(to_tokio_instant(t))
+}
@@ -0,0 +1,82 @@
+use crate::{Ntp, NtpError, System, Time};
+#[cfg(feature = "ntp-client")]
+use crate::NtpConfig;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The local clock and an NTP clock, read at (approximately) the same moment, with how far apart they are
+///
+/// The system time is the midpoint of two readings taken either side of the NTP request, so the skew is not thrown off by the round trip.
+/// Serializes as `{ "system": .., "ntp": .., "skew_ms": .., "rtt_ms": .. }`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{ClockSnapshot, Ntp, System, Time, IntTime};
+/// let mut readings = vec![1000u32.unix::<System>(), 1002u32.unix::<System>()].into_iter();
+/// let snapshot = ClockSnapshot::capture_with(|| readings.next().unwrap(), || Ok(1004u32.unix::<Ntp>())).unwrap();
+/// assert_eq!(snapshot.system.unix(), 1001);
+/// assert_eq!(snapshot.skew_ms, 3000);
+/// assert_eq!(snapshot.rtt_ms, 2000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockSnapshot {
+    /// the local clock, at the midpoint of the NTP request
+    pub system: System,
+    /// the NTP clock
+    pub ntp: Ntp,
+    /// how far the NTP clock is ahead of the local clock, in milliseconds (negative if the local clock is ahead)
+    pub skew_ms: i64,
+    /// the time the NTP request took, by the local clock, in milliseconds
+    pub rtt_ms: u64,
+}
+
+impl ClockSnapshot {
+    /// Read the local clock and query the NTP server in `config` together (needs the `ntp-client` feature)
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use thetime::{ClockSnapshot, NtpConfig};
+    /// let snapshot = ClockSnapshot::capture(&NtpConfig::default()).unwrap();
+    /// println!("{}", snapshot.pretty());
+    /// ```
+    #[cfg(feature = "ntp-client")]
+    pub fn capture(config: &NtpConfig) -> Result<ClockSnapshot, NtpError> {
+        ClockSnapshot::capture_with(System::now, || Ntp::with_config(config))
+    }
+
+    /// Like `capture`, but with the clocks supplied by the caller - `system_src` is called once before and once after `ntp_src`
+    pub fn capture_with(mut system_src: impl FnMut() -> System, ntp_src: impl FnOnce() -> Result<Ntp, NtpError>) -> Result<ClockSnapshot, NtpError> {
+        let before = system_src();
+        let ntp = ntp_src()?;
+        let after = system_src();
+
+        // a local clock stepping backwards mid request counts as an instant round trip
+        let rtt_ms = after.unix_ms().saturating_sub(before.unix_ms()).max(0) as u64;
+        let system = System::from_epoch_offset(before.raw() + rtt_ms / 2, before.utc_offset()).with_offset_source(before.offset_source());
+        Ok(ClockSnapshot {
+            skew_ms: ntp.unix_ms().saturating_sub(system.unix_ms()),
+            system,
+            ntp,
+            rtt_ms,
+        })
+    }
+
+    /// A one line report of the snapshot
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{ClockSnapshot, Ntp, System, Time, IntTime};
+    /// let snapshot = ClockSnapshot::capture_with(|| 1704465989u32.unix::<System>(), || Ok(1704465988u32.unix::<Ntp>())).unwrap();
+    /// assert_eq!(snapshot.pretty(), "system 2024-01-05 14:46:29.000 ntp 2024-01-05 14:46:28.000 (skew -1000ms, rtt 0ms)");
+    /// ```
+    pub fn pretty(&self) -> String {
+        format!(
+            "system {} ntp {} (skew {}ms, rtt {}ms)",
+            self.system.strftime("%Y-%m-%d %H:%M:%S%.3f"),
+            self.ntp.strftime("%Y-%m-%d %H:%M:%S%.3f"),
+            self.skew_ms,
+            self.rtt_ms
+        )
+    }
+}
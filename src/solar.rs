@@ -0,0 +1,142 @@
+use crate::{Time, OFFSET_1601};
+use chrono::NaiveDate;
+use core::time::Duration;
+
+/// zenith of the sun at sunrise/sunset, allowing for refraction and the solar disc
+const ZENITH: f64 = 90.833;
+
+/// Julian centuries since J2000.0 for a Julian day
+fn julian_century(jd: f64) -> f64 {
+    (jd - 2451545.0) / 36525.0
+}
+
+/// the Julian day at 00:00 UTC on `date`
+fn julian_day(date: NaiveDate) -> f64 {
+    let days = date.signed_duration_since(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()).num_days();
+    days as f64 + 2440587.5
+}
+
+/// the equation of time (in minutes) and the solar declination (in radians) at Julian century `t`
+fn sun_position(t: f64) -> (f64, f64) {
+    let mean_long = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0).to_radians();
+    let mean_anom = (357.52911 + t * (35999.05029 - 0.0001537 * t)).to_radians();
+    let ecc = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+
+    let center = mean_anom.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2.0 * mean_anom).sin() * (0.019993 - 0.000101 * t)
+        + (3.0 * mean_anom).sin() * 0.000289;
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let apparent_long = (mean_long.to_degrees() + center - 0.00569 - 0.00478 * omega.sin()).to_radians();
+
+    let mean_obliq = 23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliq = (mean_obliq + 0.00256 * omega.cos()).to_radians();
+
+    let declination = (obliq.sin() * apparent_long.sin()).asin();
+
+    let y = (obliq / 2.0).tan().powi(2);
+    let eq_time = y * (2.0 * mean_long).sin() - 2.0 * ecc * mean_anom.sin()
+        + 4.0 * ecc * y * mean_anom.sin() * (2.0 * mean_long).cos()
+        - 0.5 * y * y * (4.0 * mean_long).sin()
+        - 1.25 * ecc * ecc * (2.0 * mean_anom).sin();
+
+    (4.0 * eq_time.to_degrees(), declination)
+}
+
+/// minutes after 00:00 UTC on `date` of solar noon at longitude `lon`
+fn noon_minutes(date: NaiveDate, lon: f64) -> f64 {
+    let jd = julian_day(date);
+    let (eq_time, _) = sun_position(julian_century(jd + (720.0 - 4.0 * lon) / 1440.0));
+    let (eq_time, _) = sun_position(julian_century(jd + (720.0 - 4.0 * lon - eq_time) / 1440.0));
+    720.0 - 4.0 * lon - eq_time
+}
+
+/// minutes after 00:00 UTC on `date` of sunrise (`rising`) or sunset, or None if the sun does not rise or set
+fn event_minutes(date: NaiveDate, lat: f64, lon: f64, rising: bool) -> Option<f64> {
+    let jd = julian_day(date);
+    let sign = if rising { 1.0 } else { -1.0 };
+
+    // evaluate once at solar noon, then again at the estimate to refine it
+    let mut minutes = noon_minutes(date, lon);
+    for _ in 0..2 {
+        let (eq_time, declination) = sun_position(julian_century(jd + minutes / 1440.0));
+        let lat = lat.to_radians();
+        let cos_ha = ZENITH.to_radians().cos() / (lat.cos() * declination.cos()) - lat.tan() * declination.tan();
+        if !(-1.0..=1.0).contains(&cos_ha) {
+            return None;
+        }
+        let hour_angle = cos_ha.acos().to_degrees();
+        minutes = 720.0 - 4.0 * (lon + sign * hour_angle) - eq_time;
+    }
+    Some(minutes)
+}
+
+/// builds a time object `minutes` after 00:00 UTC on `date`, in `offset`
+fn at_minutes<T: Time>(date: NaiveDate, minutes: f64, offset: i32) -> T {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_millis();
+    let ms = midnight + (minutes * 60_000.0).round() as i64 + OFFSET_1601 as i64 * 1000;
+    T::from_epoch_offset(ms as u64, offset)
+}
+
+/// The time of sunrise on the (local) date of `date`, at latitude `lat` and longitude `lon` (degrees, north and east positive)
+///
+/// Uses the NOAA solar calculation, which is accurate to within a minute or two. Returns None if the sun does not rise that day (polar day or night).
+/// The result is in the same UTC offset as `date`.
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, solar};
+/// let day = "2024-06-21 12:00:00 +01:00".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// let rise = solar::sunrise(&day, 51.5074, -0.1278).unwrap();
+/// println!("London sunrise: {}", rise);
+/// ```
+pub fn sunrise<T: Time>(date: &T, lat: f64, lon: f64) -> Option<T> {
+    let day = date.wall_clock().date();
+    event_minutes(day, lat, lon, true).map(|m| at_minutes(day, m, date.utc_offset()))
+}
+
+/// The time of sunset on the (local) date of `date`, at latitude `lat` and longitude `lon` (degrees, north and east positive)
+///
+/// Returns None if the sun does not set that day (polar day or night). The result is in the same UTC offset as `date`.
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, solar};
+/// let day = "2024-06-21 12:00:00 +01:00".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// let set = solar::sunset(&day, 51.5074, -0.1278).unwrap();
+/// assert!(set > solar::sunrise(&day, 51.5074, -0.1278).unwrap());
+/// ```
+pub fn sunset<T: Time>(date: &T, lat: f64, lon: f64) -> Option<T> {
+    let day = date.wall_clock().date();
+    event_minutes(day, lat, lon, false).map(|m| at_minutes(day, m, date.utc_offset()))
+}
+
+/// The time of solar noon (when the sun is highest) on the (local) date of `date`, at longitude `lon`
+///
+/// Returns None for polar day and night, like `sunrise` and `sunset`. The result is in the same UTC offset as `date`.
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, solar};
+/// let day = "2024-06-21 12:00:00 +01:00".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// println!("{}", solar::solar_noon(&day, 51.5074, -0.1278).unwrap());
+/// ```
+pub fn solar_noon<T: Time>(date: &T, lat: f64, lon: f64) -> Option<T> {
+    let day = date.wall_clock().date();
+    event_minutes(day, lat, lon, true)?;
+    Some(at_minutes(day, noon_minutes(day, lon), date.utc_offset()))
+}
+
+/// The time between sunrise and sunset on the (local) date of `date`, or None for polar day and night
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, StrTime, solar};
+/// let day = "2024-12-21 12:00:00 +00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// let length = solar::day_length(&day, 51.5074, -0.1278).unwrap();
+/// assert!(length.as_secs() < 8 * 3600);
+/// ```
+pub fn day_length<T: Time>(date: &T, lat: f64, lon: f64) -> Option<Duration> {
+    let rise = sunrise(date, lat, lon)?;
+    let set = sunset(date, lat, lon)?;
+    Some(Duration::from_millis(set.unix_ms().saturating_sub(rise.unix_ms()) as u64))
+}
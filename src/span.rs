@@ -0,0 +1,355 @@
+use crate::{Time, TruncateUnit, YearWeek};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A span of time between two time objects, from `start` (inclusive) to `end` (exclusive)
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, TimeSpan, StrTime};
+/// let start = "2024-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let span = TimeSpan::new(start.clone(), start.add_days(7));
+/// assert!(span.contains(&start.add_days(3)));
+/// assert!(!span.contains(&start.add_days(7)));
+/// ```
+///
+/// The fields are public, so a span built directly may have `end` before `start` - the methods read such a span as running from the
+/// earlier of the two to the later, and deserializing swaps them like `new`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TimeSpan<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Time> TimeSpan<T> {
+    /// Create a new span - if `end` is before `start`, they are swapped
+    pub fn new(start: T, end: T) -> Self {
+        if end.raw() < start.raw() {
+            TimeSpan { start: end, end: start }
+        } else {
+            TimeSpan { start, end }
+        }
+    }
+
+    /// internal only - the raw start and end, earlier first
+    pub(crate) fn bounds(&self) -> (u64, u64) {
+        let (start, end) = (self.start.raw(), self.end.raw());
+        (start.min(end), start.max(end))
+    }
+
+    /// whether the given time falls within the span (`start` inclusive, `end` exclusive)
+    pub fn contains<U: Time>(&self, time: &U) -> bool {
+        let (start, end) = self.bounds();
+        start <= time.raw() && time.raw() < end
+    }
+
+    /// the length of the span, in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.end.raw().abs_diff(self.start.raw())
+    }
+
+    /// The time `fraction` of the way through the span (clamped to `0.0..=1.0`), in the offset of `start` - the inverse of `Time::progress_through`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeSpan, StrTime};
+    /// let start = "2024-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let span = TimeSpan::new(start.clone(), start.add_days(10));
+    /// assert_eq!(span.at_fraction(0.25).pretty(), "2024-01-03 12:00:00");
+    /// ```
+    pub fn at_fraction(&self, fraction: f64) -> T {
+        let fraction = if fraction.is_nan() { 0.0 } else { fraction.clamp(0.0, 1.0) };
+        let offset = (self.duration_ms() as f64 * fraction).round() as u64;
+        T::from_epoch_offset(self.bounds().0 + offset, self.start.utc_offset())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Time + Deserialize<'de>> Deserialize<'de> for TimeSpan<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// internal only - the span as written, before `new` puts it in order
+        #[derive(Deserialize)]
+        #[serde(rename = "TimeSpan")]
+        struct Raw<T> {
+            start: T,
+            end: T,
+        }
+
+        let Raw { start, end } = Raw::deserialize(deserializer)?;
+        Ok(TimeSpan::new(start, end))
+    }
+}
+
+/// The calendar units `TimeSpan::split_by` can split at
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SplitUnit {
+    Day,
+    /// ISO weeks, starting on Monday
+    Week,
+    Month,
+    Year,
+}
+
+impl SplitUnit {
+    /// internal only - the matching truncation unit
+    fn truncate_unit(self) -> TruncateUnit {
+        match self {
+            SplitUnit::Day => TruncateUnit::Day,
+            SplitUnit::Week => TruncateUnit::Week,
+            SplitUnit::Month => TruncateUnit::Month,
+            SplitUnit::Year => TruncateUnit::Year,
+        }
+    }
+
+    /// internal only - the label of the unit starting at `start`: "2024-01-28", "2024-W05", "2024-01" or "2024"
+    fn label<T: Time>(self, start: &T) -> String {
+        match self {
+            SplitUnit::Day => start.strftime("%Y-%m-%d"),
+            SplitUnit::Week => YearWeek::of(start).to_string(),
+            SplitUnit::Month => start.strftime("%Y-%m"),
+            SplitUnit::Year => start.strftime("%Y"),
+        }
+    }
+}
+
+impl<T: Time> TimeSpan<T> {
+    /// Split the span at each boundary of `unit` (local midnights, Mondays, firsts of the month or New Years), read `offset` seconds
+    /// from UTC. The first and last pieces may be partial, and the pieces are in `offset`. A span within a single unit (or an empty
+    /// span) comes back as itself
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeSpan, SplitUnit, StrTime};
+    /// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let stay = TimeSpan::new(at("2024-01-28 14:00:00"), at("2024-02-03 09:00:00"));
+    /// let months = stay.split_by(SplitUnit::Month, 0);
+    /// assert_eq!(months.len(), 2);
+    /// assert_eq!(months[0].end.pretty(), "2024-02-01 00:00:00");
+    /// assert_eq!(stay.split_by(SplitUnit::Day, 0).len(), 7);
+    /// ```
+    pub fn split_by(&self, unit: SplitUnit, offset: i32) -> Vec<TimeSpan<T>> {
+        let (start, end) = self.bounds();
+        let mut pieces = Vec::new();
+        let mut start = T::from_epoch_offset(start, offset);
+        loop {
+            let boundary = period_of(&start, unit.truncate_unit()).end;
+            // past the end of chrono's range a period ends where it starts, so stop there too
+            if boundary.raw() >= end || boundary.raw() <= start.raw() {
+                pieces.push(TimeSpan { start, end: T::from_epoch_offset(end, offset) });
+                break;
+            }
+            let next = T::from_epoch_offset(boundary.raw(), offset);
+            pieces.push(TimeSpan { start, end: boundary });
+            start = next;
+        }
+
+        if pieces.len() == 1 {
+            let copy = |t: &T| T::from_epoch_offset(t.raw(), t.utc_offset()).with_offset_source(t.offset_source());
+            return vec![TimeSpan { start: copy(&self.start), end: copy(&self.end) }];
+        }
+        pieces
+    }
+
+    /// How much of the span falls in each `unit` it touches, read in the offset of `start`, labelled "2024-01-28" (days), "2024-W05"
+    /// (ISO weeks), "2024-01" (months) or "2024" (years) - see `split_by`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeSpan, SplitUnit, StrTime};
+    /// use core::time::Duration;
+    /// let at = |s: &str| s.parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// let stay = TimeSpan::new(at("2024-01-28 14:00:00"), at("2024-02-03 09:00:00"));
+    /// assert_eq!(stay.duration_in_each(SplitUnit::Month), vec![
+    ///     ("2024-01".to_string(), Duration::from_secs((3 * 24 + 10) * 3600)),
+    ///     ("2024-02".to_string(), Duration::from_secs((2 * 24 + 9) * 3600)),
+    /// ]);
+    /// ```
+    pub fn duration_in_each(&self, unit: SplitUnit) -> Vec<(String, core::time::Duration)> {
+        self.split_by(unit, self.start.utc_offset())
+            .iter()
+            .map(|piece| (unit.label(&piece.start), core::time::Duration::from_millis(piece.duration_ms())))
+            .collect()
+    }
+}
+
+/// internal only - the span of the calendar unit containing `time`, read in its own offset
+pub(crate) fn period_of<T: Time>(time: &T, unit: TruncateUnit) -> TimeSpan<T> {
+    let start = time.truncate_to(unit);
+    let wall = start.wall_clock();
+    let next = match unit {
+        TruncateUnit::Second => Some(wall + chrono::Duration::seconds(1)),
+        TruncateUnit::Minute => Some(wall + chrono::Duration::minutes(1)),
+        TruncateUnit::Hour => Some(wall + chrono::Duration::hours(1)),
+        TruncateUnit::Day => Some(wall + chrono::Duration::days(1)),
+        TruncateUnit::Week => Some(wall + chrono::Duration::days(7)),
+        TruncateUnit::Month => wall.checked_add_months(chrono::Months::new(1)),
+        TruncateUnit::Year => wall.checked_add_months(chrono::Months::new(12)),
+    };
+    // past the end of chrono's range there is no next period, so the span ends where it starts
+    let end = next.map(|n| T::from_wall_clock(n, start.utc_offset()));
+    let end = end.unwrap_or_else(|| T::from_epoch_offset(start.raw(), start.utc_offset()));
+    TimeSpan::new(start, end)
+}
+
+/// A set of spans, each carrying a value, answering "which spans contain this instant" without checking every span
+///
+/// Spans are half-open like `TimeSpan::contains`, so spans that only touch (one's `end` is the other's `start`) do not overlap,
+/// and empty spans contain and overlap nothing. Spans may be nested or overlap freely.
+///
+/// Internally the spans are kept sorted by start, alongside a running maximum of their ends, so a query only looks at spans that could reach it
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, TimeSpan, SpanSet, StrTime};
+/// let day = "2024-01-01 00:00:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let mut windows = SpanSet::new();
+/// windows.insert(TimeSpan::new(day.clone(), day.add_days(2)), "db upgrade");
+/// windows.insert(TimeSpan::new(day.add_days(1), day.add_days(3)), "network work");
+/// assert_eq!(windows.query_point(&day.add_days(1)), vec![&"db upgrade", &"network work"]);
+/// assert_eq!(windows.query_point(&day.add_days(2)), vec![&"network work"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpanSet<T, V> {
+    /// sorted by start
+    entries: Vec<(TimeSpan<T>, V)>,
+    /// `max_end[i]` is the latest end among `entries[..=i]`
+    max_end: Vec<u64>,
+}
+
+impl<T: Time, V> Default for SpanSet<T, V> {
+    fn default() -> Self {
+        SpanSet::new()
+    }
+}
+
+impl<T: Time, V> SpanSet<T, V> {
+    /// Create an empty set
+    pub fn new() -> Self {
+        SpanSet {
+            entries: Vec::new(),
+            max_end: Vec::new(),
+        }
+    }
+
+    /// the number of spans in the set
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// whether the set has no spans
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add a span with its value
+    pub fn insert(&mut self, span: TimeSpan<T>, value: V) {
+        // a reversed span would break the ordering by start and the running maximum of ends
+        let span = TimeSpan::new(span.start, span.end);
+        let at = self.entries.partition_point(|(s, _)| s.start.raw() <= span.start.raw());
+        self.entries.insert(at, (span, value));
+        self.max_end.insert(at, 0);
+        self.rebuild_max_end(at);
+    }
+
+    /// The values of every span containing `time`, ordered by span start
+    pub fn query_point<U: Time>(&self, time: &U) -> Vec<&V> {
+        let t = time.raw();
+        self.collect(self.entries.partition_point(|(s, _)| s.start.raw() <= t), t, |s| s.contains(time))
+    }
+
+    /// The values of every span overlapping `span`, ordered by span start - spans that only touch it are not included
+    pub fn query_overlapping<U: Time>(&self, span: &TimeSpan<U>) -> Vec<&V> {
+        let (start, end) = span.bounds();
+        if start == end {
+            return Vec::new();
+        }
+        self.collect(self.entries.partition_point(|(s, _)| s.start.raw() < end), start, |s| {
+            s.start.raw() < end && start < s.end.raw()
+        })
+    }
+
+    /// The total time covered by at least one span, counting overlapping time once
+    pub fn total_covered_duration(&self) -> core::time::Duration {
+        let mut total = 0;
+        let mut current: Option<(u64, u64)> = None;
+        for (span, _) in &self.entries {
+            let (start, end) = span.bounds();
+            current = match current {
+                Some((s, e)) if start <= e => Some((s, e.max(end))),
+                Some((s, e)) => {
+                    total += e - s;
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((s, e)) = current {
+            total += e - s;
+        }
+        core::time::Duration::from_millis(total)
+    }
+
+    /// iterate over the spans and their values, ordered by span start
+    pub fn iter(&self) -> impl Iterator<Item = (&TimeSpan<T>, &V)> {
+        self.entries.iter().map(|(s, v)| (s, v))
+    }
+
+    /// internal only - walks back from `upto` while a span could still reach `after`, keeping those matching `keep`
+    fn collect<F: Fn(&TimeSpan<T>) -> bool>(&self, upto: usize, after: u64, keep: F) -> Vec<&V> {
+        let mut found = Vec::new();
+        for i in (0..upto).rev() {
+            if self.max_end[i] <= after {
+                break;
+            }
+            if keep(&self.entries[i].0) {
+                found.push(&self.entries[i].1);
+            }
+        }
+        found.reverse();
+        found
+    }
+
+    /// internal only - recomputes the running maximum of ends from `from` onwards
+    fn rebuild_max_end(&mut self, from: usize) {
+        let mut max = if from == 0 { 0 } else { self.max_end[from - 1] };
+        for i in from..self.entries.len() {
+            max = max.max(self.entries[i].0.end.raw());
+            self.max_end[i] = max;
+        }
+    }
+}
+
+impl<T: Time, V: PartialEq> SpanSet<T, V> {
+    /// Merge spans with equal values that overlap or touch into single spans
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, TimeSpan, SpanSet, IntTime};
+    /// let t = |s: u32| s.unix::<System>();
+    /// let mut set = SpanSet::new();
+    /// set.insert(TimeSpan::new(t(0), t(10)), 'a');
+    /// set.insert(TimeSpan::new(t(10), t(20)), 'a');
+    /// set.insert(TimeSpan::new(t(5), t(15)), 'b');
+    /// set.merge_adjacent();
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn merge_adjacent(&mut self) {
+        let mut merged: Vec<(TimeSpan<T>, V)> = Vec::with_capacity(self.entries.len());
+        for (span, value) in self.entries.drain(..) {
+            // equal-valued spans already merged never touch each other, so only the latest one can touch this span
+            match merged.iter_mut().rev().find(|(_, v)| *v == value) {
+                Some((existing, _)) if span.start.raw() <= existing.end.raw() => {
+                    if span.end.raw() > existing.end.raw() {
+                        existing.end = span.end;
+                    }
+                }
+                _ => merged.push((span, value)),
+            }
+        }
+        self.entries = merged;
+        self.max_end = vec![0; self.entries.len()];
+        self.rebuild_max_end(0);
+    }
+}
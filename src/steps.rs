@@ -0,0 +1,54 @@
+use crate::Time;
+
+/// An iterator of evenly spaced times, from `Time::iter_from`, `Time::iter_until` and `Time::countdown`
+///
+/// Each item is computed from the start as `start ± n * step` in whole milliseconds, so no error builds up however many steps are taken.
+/// Items keep the start's offset
+#[derive(Debug, Clone)]
+pub struct TimeSteps<T: Time> {
+    start: T,
+    step_ms: u64,
+    n: u64,
+    backwards: bool,
+    /// the last raw value that may be yielded (inclusive), if bounded
+    end: Option<u64>,
+    done: bool,
+}
+
+impl<T: Time> TimeSteps<T> {
+    pub(crate) fn new(start: T, step: core::time::Duration, backwards: bool, end: Option<u64>) -> Self {
+        TimeSteps {
+            start,
+            step_ms: u64::try_from(step.as_millis()).unwrap_or(u64::MAX),
+            n: 0,
+            backwards,
+            end,
+            done: false,
+        }
+    }
+}
+
+impl<T: Time> Iterator for TimeSteps<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.done {
+            return None;
+        }
+        let raw = self.step_ms.checked_mul(self.n).and_then(|delta| {
+            if self.backwards {
+                self.start.raw().checked_sub(delta)
+            } else {
+                self.start.raw().checked_add(delta)
+            }
+        });
+        let Some(raw) = raw.filter(|raw| self.end.is_none_or(|end| *raw <= end)) else {
+            self.done = true;
+            return None;
+        };
+        // a step under a millisecond would never move, so only the start is yielded
+        self.n += 1;
+        self.done = self.step_ms == 0;
+        Some(T::from_epoch_offset(raw, self.start.utc_offset()).with_offset_source(self.start.offset_source()))
+    }
+}
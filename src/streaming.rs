@@ -0,0 +1,161 @@
+use crate::parse::iso_lenient_bytes;
+use crate::{parse_time_bytes, ParseError, System};
+
+/// The format a `StreamingParser` reads each record with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// a strftime format, as for `parse_time_bytes`
+    Strftime(String),
+    /// the hand-written ISO 8601 parser behind `BytesTime::strp_iso_lenient`, which is faster than any strftime format
+    IsoLenient,
+}
+
+/// Parses timestamps out of a byte stream that arrives in arbitrary chunks, one timestamp per delimited record ('\n' by default, with
+/// an optional '\r' before it)
+///
+/// `push` buffers any record split across chunks and returns the timestamps completed so far, so that the results are the same however
+/// the stream is split. Empty records are skipped. A record longer than the maximum length (256 bytes by default) is reported as an
+/// error as soon as it overflows and the rest of it discarded, so the buffer never grows past the maximum
+///
+/// # Examples
+/// ```rust
+/// use thetime::{StreamingParser, Time};
+/// let mut parser = StreamingParser::new("%Y-%m-%d %H:%M:%S");
+/// assert!(parser.push(b"2024-01-05 14:4").is_empty());
+/// let parsed = parser.push(b"6:29\n2024-01-05 14:46:30\n2024");
+/// assert_eq!(parsed.iter().map(|x| x.as_ref().unwrap().unix()).collect::<Vec<_>>(), [1704465989, 1704465990]);
+/// assert!(parser.finish().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamingParser {
+    format: StreamFormat,
+    delimiter: u8,
+    max_len: usize,
+    buf: Vec<u8>,
+    // set after an over-long record has been reported, until its delimiter turns up
+    discarding: bool,
+}
+
+impl StreamingParser {
+    /// A parser reading each record with a strftime format
+    pub fn new(format: &str) -> Self {
+        StreamingParser::with_format(StreamFormat::Strftime(format.to_string()))
+    }
+
+    /// A parser reading each record with `StrTime::strp_iso_lenient`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{StreamingParser, Time};
+    /// let mut parser = StreamingParser::iso_lenient();
+    /// let parsed = parser.push(b"2024-01-05T14:46:29Z\r\n\r\n2024-01-05 14:46:30+01:00\n");
+    /// assert_eq!(parsed.len(), 2);
+    /// assert_eq!(parsed[1].as_ref().unwrap().unix(), 1704462390);
+    /// ```
+    pub fn iso_lenient() -> Self {
+        StreamingParser::with_format(StreamFormat::IsoLenient)
+    }
+
+    /// A parser reading each record with `format`
+    pub fn with_format(format: StreamFormat) -> Self {
+        StreamingParser {
+            format,
+            delimiter: b'\n',
+            max_len: 256,
+            buf: Vec::new(),
+            discarding: false,
+        }
+    }
+
+    /// Split records on `delimiter` instead of '\n' - a '\r' before it is still dropped
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The longest record to buffer, in bytes - counting any '\r', but not the delimiter
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// How many bytes of an incomplete record are buffered
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Feed the next chunk of the stream, returning a result for each record it completes, in order
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Result<System, ParseError>> {
+        let mut out = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (piece, complete) = match rest.iter().position(|&b| b == self.delimiter) {
+                Some(i) => (&rest[..i], true),
+                None => (rest, false),
+            };
+            rest = if complete { &rest[piece.len() + 1..] } else { &[] };
+
+            if !self.discarding {
+                let room = self.max_len - self.buf.len();
+                if piece.len() > room {
+                    self.buf.extend_from_slice(&piece[..room]);
+                    out.push(Err(self.overflow()));
+                    self.buf.clear();
+                    self.discarding = true;
+                } else if complete && self.buf.is_empty() {
+                    // the whole record is in this chunk, so parse it without copying
+                    if let Some(result) = self.parse(piece) {
+                        out.push(result);
+                    }
+                } else {
+                    self.buf.extend_from_slice(piece);
+                    if complete {
+                        let record = core::mem::take(&mut self.buf);
+                        if let Some(result) = self.parse(&record) {
+                            out.push(result);
+                        }
+                        // hand the allocation back for the next record
+                        self.buf = record;
+                        self.buf.clear();
+                    }
+                }
+            }
+            if complete {
+                self.discarding = false;
+            }
+        }
+        out
+    }
+
+    /// End the stream - a record left without its delimiter is returned as an error, and the parser is ready for a new stream
+    pub fn finish(&mut self) -> Option<ParseError> {
+        self.discarding = false;
+        if self.buf.is_empty() {
+            return None;
+        }
+        let error = ParseError::invalid(&String::from_utf8_lossy(&self.buf), self.buf.len(), "a delimiter");
+        self.buf.clear();
+        Some(error)
+    }
+
+    /// internal only - parse a complete record, or `None` if it is empty
+    fn parse(&self, record: &[u8]) -> Option<Result<System, ParseError>> {
+        let record = record.strip_suffix(b"\r").unwrap_or(record);
+        if record.is_empty() {
+            return None;
+        }
+        Some(match &self.format {
+            StreamFormat::Strftime(format) => parse_time_bytes(record, format),
+            StreamFormat::IsoLenient => iso_lenient_bytes(record),
+        })
+    }
+
+    /// internal only - the error for a record longer than `max_len`, reporting the bytes that fitted
+    fn overflow(&self) -> ParseError {
+        ParseError::invalid(
+            &String::from_utf8_lossy(&self.buf),
+            self.max_len,
+            format!("a delimiter within {} bytes", self.max_len),
+        )
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{ImplsDuration, System, Time};
+use core::fmt::Display;
+
+/// An error returned when setting the system clock fails
+#[derive(Debug)]
+pub enum SysClockError {
+    /// the process is not allowed to set the clock (needs root / CAP_SYS_TIME on Unix, SeSystemtimePrivilege on Windows)
+    PermissionDenied,
+    /// the time cannot be represented by the OS clock
+    OutOfRange,
+    /// setting the clock is not supported on this platform
+    Unsupported,
+    /// any other error reported by the OS
+    Os(std::io::Error),
+}
+
+impl Display for SysClockError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SysClockError::PermissionDenied => write!(f, "permission denied setting the system clock"),
+            SysClockError::OutOfRange => write!(f, "time out of range for the system clock"),
+            SysClockError::Unsupported => write!(f, "setting the system clock is not supported on this platform"),
+            SysClockError::Os(e) => write!(f, "failed to set the system clock: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SysClockError {}
+
+/// The step (in milliseconds) that would move the clock reading `now` to `target` - positive means the clock is behind
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sysclock::clock_delta_ms};
+/// let now = System::now();
+/// assert_eq!(clock_delta_ms(&now.add_seconds(2), &now), 2000);
+/// ```
+pub fn clock_delta_ms<T: Time, N: Time>(target: &T, now: &N) -> i64 {
+    target.unix_ms() - now.unix_ms()
+}
+
+/// The step (in milliseconds) that `step_if_beyond` would apply, or None if the clock is within `tolerance` of `target`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sysclock::plan_step};
+/// let now = System::now();
+/// assert_eq!(plan_step(&now.add_seconds(2), &now, core::time::Duration::from_secs(5)), None);
+/// assert_eq!(plan_step(&now.add_seconds(-10), &now, core::time::Duration::from_secs(5)), Some(-10000));
+/// ```
+pub fn plan_step<T: Time, N: Time, D: ImplsDuration>(target: &T, now: &N, tolerance: D) -> Option<i64> {
+    let delta = clock_delta_ms(target, now);
+    if delta.abs() > tolerance.num_milliseconds().abs() {
+        Some(delta)
+    } else {
+        None
+    }
+}
+
+/// Returns the step (in milliseconds) that `set_system_time` would apply right now, without changing anything
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, sysclock::set_system_time_dry_run};
+/// println!("would step by {}ms", set_system_time_dry_run(&System::now().add_seconds(60)));
+/// ```
+pub fn set_system_time_dry_run<T: Time>(time: &T) -> i64 {
+    clock_delta_ms(time, &System::now())
+}
+
+/// Step the system clock to the given time, only if it is more than `tolerance` away from `System::now()`
+///
+/// Returns the step applied in milliseconds, or None if the clock was left alone
+///
+/// # Examples
+/// ```rust,no_run
+/// use thetime::{Ntp, Time, sysclock::step_if_beyond};
+/// let ntp = Ntp::now();
+/// match step_if_beyond(&ntp, core::time::Duration::from_millis(500)) {
+///     Ok(Some(step)) => println!("stepped the clock by {}ms", step),
+///     Ok(None) => println!("clock already in sync"),
+///     Err(e) => println!("{}", e),
+/// }
+/// ```
+pub fn step_if_beyond<T: Time, D: ImplsDuration>(time: &T, tolerance: D) -> Result<Option<i64>, SysClockError> {
+    match plan_step(time, &System::now(), tolerance) {
+        Some(step) => set_system_time(time).map(|_| Some(step)),
+        None => Ok(None),
+    }
+}
+
+/// Set the system clock (`CLOCK_REALTIME` on Unix, `SetSystemTime` on Windows) to the given time
+///
+/// # Examples
+/// ```rust,no_run
+/// use thetime::{Ntp, Time, sysclock::set_system_time};
+/// set_system_time(&Ntp::now()).unwrap();
+/// ```
+#[cfg(unix)]
+pub fn set_system_time<T: Time>(time: &T) -> Result<(), SysClockError> {
+    let ms = time.unix_ms();
+    let spec = libc::timespec {
+        tv_sec: libc::time_t::try_from(ms.div_euclid(1000)).map_err(|_| SysClockError::OutOfRange)?,
+        tv_nsec: (ms.rem_euclid(1000) * 1_000_000) as _,
+    };
+    // SAFETY: `spec` is a valid, initialised timespec that outlives the call
+    if unsafe { libc::clock_settime(libc::CLOCK_REALTIME, &spec) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => Err(SysClockError::PermissionDenied),
+        Some(libc::EINVAL) => Err(SysClockError::OutOfRange),
+        _ => Err(SysClockError::Os(err)),
+    }
+}
+
+/// Set the system clock (`CLOCK_REALTIME` on Unix, `SetSystemTime` on Windows) to the given time
+#[cfg(windows)]
+pub fn set_system_time<T: Time>(time: &T) -> Result<(), SysClockError> {
+    use chrono::{Datelike, Timelike};
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_PRIVILEGE_NOT_HELD, SYSTEMTIME};
+    use windows_sys::Win32::System::SystemInformation::SetSystemTime;
+
+    let utc = chrono::DateTime::from_timestamp_millis(time.unix_ms()).ok_or(SysClockError::OutOfRange)?;
+    let year = u16::try_from(utc.year()).map_err(|_| SysClockError::OutOfRange)?;
+    let st = SYSTEMTIME {
+        wYear: year,
+        wMonth: utc.month() as u16,
+        wDayOfWeek: utc.weekday().num_days_from_sunday() as u16,
+        wDay: utc.day() as u16,
+        wHour: utc.hour() as u16,
+        wMinute: utc.minute() as u16,
+        wSecond: utc.second() as u16,
+        wMilliseconds: (utc.timestamp_subsec_millis() % 1000) as u16,
+    };
+    // SAFETY: `st` is a valid, initialised SYSTEMTIME that outlives the call
+    if unsafe { SetSystemTime(&st) } != 0 {
+        return Ok(());
+    }
+    // SAFETY: no preconditions
+    match unsafe { GetLastError() } {
+        ERROR_PRIVILEGE_NOT_HELD => Err(SysClockError::PermissionDenied),
+        _ => Err(SysClockError::Os(std::io::Error::last_os_error())),
+    }
+}
+
+/// Set the system clock (`CLOCK_REALTIME` on Unix, `SetSystemTime` on Windows) to the given time
+#[cfg(not(any(unix, windows)))]
+pub fn set_system_time<T: Time>(_time: &T) -> Result<(), SysClockError> {
+    Err(SysClockError::Unsupported)
+}
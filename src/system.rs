@@ -1,78 +1,168 @@
-use crate::{Time, TimeDiff, OFFSET_1601};
-use chrono::{DateTime, NaiveDateTime, Local};
+use crate::{OffsetSource, ParseTimeError, Time, TimeDiff, OFFSET_1601};
+use chrono::{DateTime, Local};
 use core::fmt::Display;
-use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use crate::wire::Wire;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// System time, as grabbed from the system (obviously). Its timezone is dependent on the system's timezone as configured in the BIOS
 ///
 /// `inner_secs` is the time as seconds since `1601-01-01 00:00:00`, from `chrono::Local`
 /// `inner_milliseconds` is the subsec milliseconds
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// 
+/// Serializes as `{ "v": 1, "ms1601": .., "offset": .. }`, and also deserializes from the older `inner_secs`/`inner_milliseconds`/`utc_offset` form.
+/// The offset source is not serialized - deserialized values are always `OffsetSource::Explicit`
+///
+/// Debug output leads with the instant in UTC and the offset, followed by the raw fields. Comparisons look at the instant and the offset,
+/// not at where the offset came from
+#[derive(Clone)]
 pub struct System {
     inner_secs: u64,
     inner_milliseconds: u64,
     pub utc_offset: i32,
+    offset_source: OffsetSource,
+}
+
+impl core::fmt::Debug for System {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("System")
+            .field("instant", &crate::debug_instant(self.unix_ms()))
+            .field("utc_offset", &format_args!("{}", self.tz_offset()))
+            .field("offset_source", &self.offset_source)
+            .field("raw_ms_1601", &self.raw())
+            .field("inner_secs", &self.inner_secs)
+            .field("inner_milliseconds", &self.inner_milliseconds)
+            .finish()
+    }
+}
+
+impl System {
+    /// internal only - the fields compared by `Eq` and `Ord`, leaving out the offset source
+    fn key(&self) -> (u64, u64, i32) {
+        (self.inner_secs, self.inner_milliseconds, self.utc_offset)
+    }
+}
+
+impl PartialEq for System {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for System {}
+
+impl PartialOrd for System {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for System {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Hashes the instant only (the raw milliseconds), so values that differ only in offset hash equally - consistent with `Eq`, and with `Time::instant_eq`
+impl core::hash::Hash for System {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.raw().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for System {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Wire {
+            ms1601: self.raw(),
+            offset: self.utc_offset,
+            server: None,
+        }
+        .serialize("System", serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for System {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = Wire::deserialize("System", false, deserializer)?;
+        Ok(System::from_epoch_offset(wire.ms1601, wire.offset))
+    }
+}
+
+/// The Unix epoch, `1970-01-01 00:00:00` UTC (see `Time::unix_epoch`), rather than the 1601 internal zero
+impl Default for System {
+    fn default() -> Self {
+        System::unix_epoch()
+    }
 }
 
 impl Display for System {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", self.strftime("%Y-%m-%d %H:%M:%S"))
+        self.with_pretty(|s| f.write_str(s))
     }
 }
 
 impl TimeDiff for System {}
 
-impl Time for System {
-    fn now() -> Self {
+impl System {
+    /// internal only - the real local clock, whether or not a mock time is set
+    pub(crate) fn unmocked_now() -> System {
         let now: DateTime<Local> = Local::now();
         System {
             inner_secs: (now.timestamp() + OFFSET_1601 as i64) as u64,
             inner_milliseconds: (now.timestamp_subsec_millis()) as u64,
             utc_offset: now.offset().local_minus_utc(),
+            offset_source: OffsetSource::Explicit,
         }
     }
+}
+
+impl Time for System {
+    fn now() -> Self {
+        #[cfg(feature = "testing")]
+        if let Some(mocked) = crate::mock::mocked() {
+            return mocked;
+        }
+        System::unmocked_now()
+    }
 
     fn utc_offset(&self) -> i32 {
         self.utc_offset
     }
 
-    fn strptime<T: ToString, G: ToString>(s: T, format: G) -> Self {
-        let s = s.to_string();
-        let format = format.to_string();
-        let temp = DateTime::parse_from_str(&s, &format);
-        let x = match temp {
-            Err(_) => {
-                if !format.contains("%z") {
-                    return Self::strptime(s + " +0000", format + "%z");
-                }
-                panic!("Bad format string");
-            }
-            Ok(dt) => dt,
-        };
-        System {
-            inner_secs: (x.timestamp() + (OFFSET_1601 as i64)) as u64,
-            inner_milliseconds: x.timestamp_subsec_millis() as u64,
-            utc_offset: x.offset().local_minus_utc() as i32,
-        }
+    fn try_strptime_assuming<T: ToString, G: ToString>(s: T, format: G, default_offset: Option<i32>, assumed: OffsetSource) -> Result<Self, ParseTimeError> {
+        let (unix_ms, offset, source) = crate::try_parse_with_default_offset(&s.to_string(), &format.to_string(), default_offset, assumed)?;
+        // times before 1601 are clamped to it
+        let ms_1601 = (unix_ms + OFFSET_1601 as i64 * 1000).max(0) as u64;
+        Ok(System {
+            inner_secs: ms_1601 / 1000,
+            inner_milliseconds: ms_1601 % 1000,
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: source,
+        })
+    }
+
+    fn offset_source(&self) -> OffsetSource {
+        self.offset_source
+    }
+
+    fn with_offset_source(mut self, source: OffsetSource) -> Self {
+        self.offset_source = source;
+        self
     }
 
     fn unix(&self) -> i64 {
+        crate::precision::strict(self.unix_checked().1, "unix");
         (self.inner_secs as i64) - (OFFSET_1601 as i64)
     }
     fn unix_ms(&self) -> i64 {
-        ((self.inner_secs as i64 * 1000i64) + self.inner_milliseconds as i64) - (OFFSET_1601 as i64 * 1000i64)
+        crate::unix_ms_of_raw(self.raw())
     }
 
     fn strftime(&self, format: &str) -> String {
-        let timestamp = if self.inner_secs >= OFFSET_1601 {
-            (self.inner_secs - OFFSET_1601) as i64
-        } else {
-            -((OFFSET_1601 as i64) - (self.inner_secs as i64))
-        };
-        NaiveDateTime::from_timestamp_opt(timestamp, 0)
-            .unwrap()
-            .format(format)
-            .to_string()
+        self.format_items(chrono::format::StrftimeItems::new(format))
     }
 
     fn from_epoch(timestamp: u64) -> Self {
@@ -80,6 +170,7 @@ impl Time for System {
             inner_secs: (timestamp / 1000),
             inner_milliseconds: timestamp % 1000,
             utc_offset: 0,
+            offset_source: OffsetSource::Explicit,
         }
     }
 
@@ -91,7 +182,56 @@ impl Time for System {
         System {
             inner_secs: (timestamp / 1000),
             inner_milliseconds: timestamp % 1000,
-            utc_offset: offset,
+            utc_offset: crate::clamp_offset(offset),
+            offset_source: OffsetSource::Explicit,
+        }
+    }
+
+    fn canonicalize(&self) -> Self {
+        System {
+            inner_secs: self.inner_secs.saturating_add(self.inner_milliseconds / 1000),
+            inner_milliseconds: self.inner_milliseconds % 1000,
+            utc_offset: crate::clamp_offset(self.utc_offset),
+            offset_source: self.offset_source,
+        }
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.inner_milliseconds < 1000 && self.utc_offset.abs() <= crate::MAX_UTC_OFFSET
+    }
+}
+
+impl System {
+    /// Convert a process start time from `/proc/[pid]/stat` (field 22, clock ticks since boot) into a `System`, given the boot time and the tick rate
+    ///
+    /// The result keeps the offset of `boot_time`
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, IntTime};
+    /// let boot = 1704465989u64.unix::<System>();
+    /// assert_eq!(System::from_proc_starttime(12345, &boot, 100).pretty(), "2024-01-05 14:48:32");
+    /// ```
+    pub fn from_proc_starttime(clock_ticks: u64, boot_time: &System, hz: u64) -> System {
+        let since_boot_ms = (clock_ticks as u128 * 1000 / hz.max(1) as u128) as u64;
+        System::from_epoch_offset(boot_time.raw() + since_boot_ms, boot_time.utc_offset)
+    }
+
+    /// Build a UTC `System` from whole seconds since the Unix epoch, in a const context (clamped at 1601)
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time};
+    /// const RELEASED: System = System::from_unix_const(1704465989);
+    /// assert_eq!(RELEASED.pretty(), "2024-01-05 14:46:29");
+    /// ```
+    pub const fn from_unix_const(secs: i64) -> System {
+        let secs_1601 = secs.saturating_add(OFFSET_1601 as i64);
+        System {
+            inner_secs: if secs_1601 < 0 { 0 } else { secs_1601 as u64 },
+            inner_milliseconds: 0,
+            utc_offset: 0,
+            offset_source: OffsetSource::Explicit,
         }
     }
 }
@@ -0,0 +1,94 @@
+//! Assertions for tests that compare time objects, exported for downstream crates with the `test-util` feature
+//!
+//! On failure, both times are printed in RFC 3339 (in UTC, to the millisecond) with their raw values, and the signed difference
+//! between them in human units
+
+use crate::{IsoProfile, Time};
+use core::time::Duration;
+
+/// A signed number of milliseconds in human units, such as "+250ms", "-1.500s" or "+1d 2h 3m 4.005s"
+///
+/// # Examples
+/// ```rust
+/// use thetime::test_util::human_diff;
+/// assert_eq!(human_diff(250), "+250ms");
+/// assert_eq!(human_diff(-61_500), "-1m 1.500s");
+/// assert_eq!(human_diff(0), "0ms");
+/// ```
+pub fn human_diff(ms: i64) -> String {
+    if ms == 0 {
+        return "0ms".to_string();
+    }
+    let sign = if ms < 0 { '-' } else { '+' };
+    let abs = ms.unsigned_abs();
+    if abs < 1000 {
+        return format!("{}{}ms", sign, abs);
+    }
+    let (days, hours, minutes, ms) = (abs / 86_400_000, abs / 3_600_000 % 24, abs / 60_000 % 60, abs % 60_000);
+    let mut parts = Vec::new();
+    for (value, unit) in [(days, "d"), (hours, "h"), (minutes, "m")] {
+        if value != 0 {
+            parts.push(format!("{}{}", value, unit));
+        }
+    }
+    if ms != 0 {
+        parts.push(format!("{}.{:03}s", ms / 1000, ms % 1000));
+    }
+    format!("{}{}", sign, parts.join(" "))
+}
+
+/// internal only - a time as RFC 3339 in UTC, with its raw value
+fn describe<T: Time>(time: &T) -> String {
+    format!("{} (raw {}ms)", time.to_utc().iso8601_strict(IsoProfile::Calendar), time.raw())
+}
+
+/// The failure message for `assert_time_eq!` (with no tolerance) or `assert_time_within!`, or None if `left` and `right` are within
+/// `tolerance` of each other (inclusive). Offsets are ignored, only the instants are compared
+#[doc(hidden)]
+pub fn time_mismatch<A: Time, B: Time>(left: &A, right: &B, tolerance: Option<Duration>) -> Option<String> {
+    let diff = right.unix_ms().saturating_sub(left.unix_ms());
+    let allowed = tolerance.map_or(0, |t| u64::try_from(t.as_millis()).unwrap_or(u64::MAX));
+    if diff.unsigned_abs() <= allowed {
+        return None;
+    }
+    let headline = match tolerance {
+        None => "assertion `left == right` failed for times".to_string(),
+        Some(t) => format!("assertion `left ≈ right` failed for times (tolerance {})", human_diff(t.as_millis().min(i64::MAX as u128) as i64).trim_start_matches('+')),
+    };
+    Some(format!("{}\n  left: {}\n right: {}\n  diff: {} (right - left)", headline, describe(left), describe(right), human_diff(diff)))
+}
+
+/// Assert that two time objects are the same instant, to the millisecond - offsets are ignored
+///
+/// # Examples
+/// ```rust
+/// use thetime::{assert_time_eq, System, Time, StrTime};
+/// let a = "2024-01-05 14:46:29 +0000".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// let b = "2024-01-05 15:46:29 +0100".parse_time::<System>("%Y-%m-%d %H:%M:%S %z");
+/// assert_time_eq!(a, b);
+/// ```
+#[macro_export]
+macro_rules! assert_time_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(message) = $crate::test_util::time_mismatch(&$left, &$right, None) {
+            panic!("{}", message);
+        }
+    };
+}
+
+/// Assert that two time objects are at most `tolerance` (a `core::time::Duration`) apart, either way - offsets are ignored
+///
+/// # Examples
+/// ```rust
+/// use thetime::{assert_time_within, System, Ntp, Time};
+/// use core::time::Duration;
+/// assert_time_within!(System::now(), Ntp::now(), Duration::from_secs(5));
+/// ```
+#[macro_export]
+macro_rules! assert_time_within {
+    ($left:expr, $right:expr, $tolerance:expr $(,)?) => {
+        if let Some(message) = $crate::test_util::time_mismatch(&$left, &$right, Some($tolerance)) {
+            panic!("{}", message);
+        }
+    };
+}
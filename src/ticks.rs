@@ -0,0 +1,103 @@
+use crate::{Time, TimeSpan};
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike};
+use core::time::Duration;
+
+/// The "nice" steps `Time::nice_floor`, `Time::nice_ceil` and `tick_marks` choose between, shortest first
+pub const NICE_STEPS: [Duration; 19] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(15),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(2 * 60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(3600),
+    Duration::from_secs(2 * 3600),
+    Duration::from_secs(3 * 3600),
+    Duration::from_secs(6 * 3600),
+    Duration::from_secs(12 * 3600),
+    Duration::from_secs(86_400),
+    Duration::from_secs(7 * 86_400),
+];
+
+/// internal only - milliseconds in a day
+const DAY_MS: u64 = 86_400_000;
+
+/// The shortest nice step at or above `target` - 7 days for anything longer
+///
+/// # Examples
+/// ```rust
+/// use thetime::nice_step;
+/// use core::time::Duration;
+/// assert_eq!(nice_step(Duration::from_secs(7 * 60)), Duration::from_secs(10 * 60));
+/// assert_eq!(nice_step(Duration::from_secs(4 * 3600)), Duration::from_secs(6 * 3600));
+/// ```
+pub fn nice_step(target: Duration) -> Duration {
+    NICE_STEPS.iter().copied().find(|step| *step >= target).unwrap_or(NICE_STEPS[NICE_STEPS.len() - 1])
+}
+
+/// internal only - `t` rounded down to a multiple of `step_ms` on its wall clock: since midnight for steps of a day or less, and
+/// in whole days since a Monday otherwise (so weekly steps start on Mondays)
+pub(crate) fn snap_floor<T: Time>(t: &T, step_ms: u64) -> T {
+    let wall = t.wall_clock();
+    let snapped = if step_ms <= DAY_MS {
+        let ms_of_day = wall.num_seconds_from_midnight() as u64 * 1000 + wall.and_utc().timestamp_subsec_millis() as u64;
+        let floored = ms_of_day - ms_of_day % step_ms;
+        wall.date().and_time(NaiveTime::MIN) + chrono::Duration::milliseconds(floored as i64)
+    } else {
+        // 0001-01-01 is a Monday, and day 1 counting from the common era
+        let step_days = (step_ms / DAY_MS) as i64;
+        let day = wall.date().num_days_from_ce() as i64 - 1;
+        let floored = day - day.rem_euclid(step_days);
+        NaiveDate::from_num_days_from_ce_opt(floored as i32 + 1).unwrap_or(wall.date()).and_time(NaiveTime::MIN)
+    };
+    T::from_wall_clock(snapped, t.utc_offset()).with_offset_source(t.offset_source())
+}
+
+/// internal only - `t` rounded up to a multiple of `step_ms`, as `snap_floor`
+pub(crate) fn snap_ceil<T: Time>(t: &T, step_ms: u64) -> T {
+    let floor = snap_floor(t, step_ms);
+    if floor.raw() == t.raw() {
+        return floor;
+    }
+    T::from_epoch_offset(floor.raw().saturating_add(step_ms), t.utc_offset()).with_offset_source(t.offset_source())
+}
+
+/// Evenly spaced ticks at nice boundaries across `span` (including its ends), for a chart axis with about `approx_count` ticks
+///
+/// The step is the shortest nice step (see `NICE_STEPS`) at or above the span's length divided by `approx_count`, or a whole number of
+/// weeks for spans longer than `approx_count` weeks. Ticks are aligned in the start's offset - to midnight for steps of up to a day,
+/// and to Mondays for weekly steps - so there may be a tick or two more or fewer than asked for
+///
+/// # Examples
+/// ```rust
+/// use thetime::{tick_marks, System, Time, StrTime, TimeSpan};
+/// let start = "2024-01-05 14:03:00".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let ticks = tick_marks(&TimeSpan::new(start.clone(), start.add_minutes(47)), 6);
+/// let labels: Vec<String> = ticks.iter().map(|t| t.strftime("%H:%M")).collect();
+/// assert_eq!(labels, ["14:10", "14:20", "14:30", "14:40", "14:50"]);
+/// ```
+pub fn tick_marks<T: Time>(span: &TimeSpan<T>, approx_count: usize) -> Vec<T> {
+    if approx_count == 0 {
+        return Vec::new();
+    }
+    let target_ms = span.duration_ms() / approx_count as u64;
+    let week_ms = 7 * DAY_MS;
+    let step_ms = if target_ms > week_ms {
+        target_ms.div_ceil(week_ms) * week_ms
+    } else {
+        nice_step(Duration::from_millis(target_ms)).as_millis() as u64
+    };
+
+    let first = snap_ceil(&span.start, step_ms);
+    (0..)
+        .map_while(|n: u64| first.raw().checked_add(n.checked_mul(step_ms)?))
+        .take_while(|raw| *raw <= span.end.raw())
+        .map(|raw| T::from_epoch_offset(raw, span.start.utc_offset()).with_offset_source(span.start.offset_source()))
+        .collect()
+}
@@ -0,0 +1,52 @@
+use crate::Time;
+use core::cmp::Ordering;
+
+/// Wraps a time object so that it orders by its wall clock time of day (see `Time::time_of_day_key`), whatever the date
+///
+/// Times with the same time of day are ordered by instant, earliest first, so the order is total and only values at the same instant
+/// are equal
+///
+/// # Examples
+/// ```rust
+/// use thetime::{ByTimeOfDay, System, Time, StrTime};
+/// let mut events: Vec<ByTimeOfDay<System>> = ["2024-01-05 18:00:00", "2024-01-07 07:30:00", "2024-01-06 12:00:00"]
+///     .iter()
+///     .map(|s| ByTimeOfDay(s.parse_time::<System>("%Y-%m-%d %H:%M:%S")))
+///     .collect();
+/// events.sort();
+/// assert_eq!(events[0].0.pretty(), "2024-01-07 07:30:00");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByTimeOfDay<T>(pub T);
+
+impl<T: Time> ByTimeOfDay<T> {
+    /// internal only - the sort key, time of day then instant
+    fn key(&self) -> (u32, i64) {
+        (self.0.time_of_day_key(), self.0.unix_ms())
+    }
+}
+
+impl<T: Time> PartialEq for ByTimeOfDay<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T: Time> Eq for ByTimeOfDay<T> {}
+
+impl<T: Time> PartialOrd for ByTimeOfDay<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Time> Ord for ByTimeOfDay<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// internal only - milliseconds since midnight of an (hour, minute, second)
+pub(crate) fn hms_ms((hour, minute, second): (u8, u8, u8)) -> u32 {
+    (hour as u32 * 3600 + minute as u32 * 60 + second as u32) * 1000
+}
@@ -1,4 +1,9 @@
+use crate::ParseError;
+use chrono::format::{Fixed, Item, StrftimeItems};
+use std::sync::RwLock;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Tz {
     /// Universal Standard Time (+00:00) and Western European Time (+00:00)
     #[default]
@@ -66,6 +71,24 @@ pub enum Tz {
 
     /// Indochina Time (+07:00) and Western Indonesian Time (+07:00)
     IctWib = 25200,
+
+    /// Marquesas Time (-09:30)
+    Mart = -34200,
+
+    /// Iran Standard Time (+03:30)
+    Irst = 12600,
+
+    /// Nepal Time (+05:45)
+    Npt = 20700,
+
+    /// Myanmar Time (+06:30)
+    Mmt = 23400,
+
+    /// Chatham Standard Time (+12:45)
+    Chast = 45900,
+
+    /// Line Islands Time (+14:00)
+    Lint = 50400,
 }
 
 impl core::fmt::Display for Tz {
@@ -75,6 +98,44 @@ impl core::fmt::Display for Tz {
 }
 
 impl Tz {
+    /// Every timezone variant, in declaration order
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::Tz;
+    /// assert_eq!(Tz::ALL.len(), 28);
+    /// ```
+    pub const ALL: [Tz; 28] = [
+        Tz::UtcWet,
+        Tz::BstCet,
+        Tz::CestEet,
+        Tz::EestAst,
+        Tz::Ist,
+        Tz::JstKst,
+        Tz::CstAwstSstHkt,
+        Tz::Acst,
+        Tz::AestChst,
+        Tz::Lwst,
+        Tz::NzstFjt,
+        Tz::Sast,
+        Tz::Hast,
+        Tz::Alst,
+        Tz::Pst,
+        Tz::Mst,
+        Tz::Censt,
+        Tz::Est,
+        Tz::AtstClt,
+        Tz::Nst,
+        Tz::BtAtArtUyt,
+        Tz::IctWib,
+        Tz::Mart,
+        Tz::Irst,
+        Tz::Npt,
+        Tz::Mmt,
+        Tz::Chast,
+        Tz::Lint,
+    ];
+
     /// Returns the offset in seconds from UTC.
     /// 
     /// # Examples
@@ -139,6 +200,12 @@ impl Tz {
             Tz::Nst => "NST".to_string(),
             Tz::BtAtArtUyt => "BT/AT".to_string(),
             Tz::IctWib => "ICT/WIB".to_string(),
+            Tz::Mart => "MART".to_string(),
+            Tz::Irst => "IRST".to_string(),
+            Tz::Npt => "NPT".to_string(),
+            Tz::Mmt => "MMT".to_string(),
+            Tz::Chast => "CHAST".to_string(),
+            Tz::Lint => "LINT".to_string(),
         }
     }
 
@@ -176,10 +243,35 @@ impl Tz {
             "NST" => Some(Tz::Nst),
             "BT/AT" => Some(Tz::BtAtArtUyt),
             "ICT/WIB" => Some(Tz::IctWib),
+            "MART" => Some(Tz::Mart),
+            "IRST" => Some(Tz::Irst),
+            "NPT" => Some(Tz::Npt),
+            "MMT" => Some(Tz::Mmt),
+            "CHAST" => Some(Tz::Chast),
+            "LINT" => Some(Tz::Lint),
             _ => None,
         }
     }
 
+    /// The timezones an abbreviation such as "EST" or "CET" can mean, most common first - empty if it is unknown
+    ///
+    /// Matching ignores case. Some abbreviations mean more than one zone ("IST" is India, Ireland or Israel, "CST" is US Central or China),
+    /// and daylight saving abbreviations ("EDT", "PDT") map to the zone with the same offset
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::Tz;
+    /// assert_eq!(Tz::from_abbreviation("est"), &[Tz::Est]);
+    /// assert_eq!(Tz::from_abbreviation("IST"), &[Tz::Ist, Tz::BstCet, Tz::CestEet]);
+    /// assert!(Tz::from_abbreviation("XYZ").is_empty());
+    /// ```
+    pub fn from_abbreviation(abbreviation: &str) -> &'static [Tz] {
+        ABBREVIATIONS
+            .iter()
+            .find(|(abbr, _)| abbr.eq_ignore_ascii_case(abbreviation))
+            .map_or(&[], |(_, zones)| *zones)
+    }
+
     /// Returns the timezone from the offset.
     /// 
     /// # Examples
@@ -214,13 +306,42 @@ impl Tz {
             -12600 => Some(Tz::Nst),
             -10800 => Some(Tz::BtAtArtUyt),
             25200 => Some(Tz::IctWib),
+            -34200 => Some(Tz::Mart),
+            12600 => Some(Tz::Irst),
+            20700 => Some(Tz::Npt),
+            23400 => Some(Tz::Mmt),
+            45900 => Some(Tz::Chast),
+            50400 => Some(Tz::Lint),
             _ => None,
         }
     }
 
-    pub fn from_offset_str(offset: &str) -> Option<Self> {
-        let offset = offset.split(':').collect::<Vec<&str>>().into_iter().map(|x| x.parse::<i32>().unwrap_or(0)).collect::<Vec<i32>>();
-        Self::from_offset((offset[0] * 3600) + (offset[1] * 60))
+    /// Returns the timezone from an offset string in the form "+|-HH:MM".
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Tz, TzParseError};
+    /// assert_eq!(Tz::from_offset_str("+05:30"), Ok(Tz::Ist));
+    /// assert_eq!(Tz::from_offset_str("-03:30"), Ok(Tz::Nst));
+    /// assert_eq!(Tz::from_offset_str("+05:45"), Ok(Tz::Npt));
+    /// assert_eq!(Tz::from_offset_str("+05:15"), Err(TzParseError::UnknownOffset(18900)));
+    /// assert!(Tz::from_offset_str("banana").is_err());
+    /// ```
+    pub fn from_offset_str(offset: &str) -> Result<Self, TzParseError> {
+        let seconds = parse_offset(offset)?;
+        Self::from_offset(seconds).ok_or(TzParseError::UnknownOffset(seconds))
+    }
+
+    /// Returns the timezone from an offset string in the form "+|-HH:MM", or None if it is invalid or unknown.
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::Tz;
+    /// println!("{:?}", Tz::from_offset_str_opt("+01:00")); // Some(BstCet)
+    /// println!("{:?}", Tz::from_offset_str_opt("banana")); // None
+    /// ```
+    pub fn from_offset_str_opt(offset: &str) -> Option<Self> {
+        Self::from_offset_str(offset).ok()
     }
 
     /// Offsets the provided struct by the timezone.
@@ -231,6 +352,372 @@ impl Tz {
     /// println!("{:?}", Tz::Acst.offset_struct(System::now()));
     /// ```
     pub fn offset_struct<T: crate::Time>(&self, time: T) -> T {
-        time.change_tz(self.offset_str())
+        time.change_tz_seconds(self.offset())
+    }
+
+    /// Offsets the provided struct by the timezone with the given offset string (in the form "+|-HH:MM"), validating it first.
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Time, System, Tz};
+    /// println!("{:?}", Tz::try_offset_struct("-03:30", System::now()));
+    /// assert!(Tz::try_offset_struct("banana", System::now()).is_err());
+    /// ```
+    pub fn try_offset_struct<T: crate::Time>(offset: &str, time: T) -> Result<T, TzParseError> {
+        Ok(Self::from_offset_str(offset)?.offset_struct(time))
+    }
+
+    /// The inverse of `offset_struct`: reads the wall clock of `local_wall` as being in this timezone, and returns that instant in UTC
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Time, System, StrTime, Tz};
+    /// let wall = "2024-01-05 08:16:29".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    /// assert_eq!(Tz::Nst.to_utc_struct(wall).pretty(), "2024-01-05 11:46:29");
+    /// ```
+    pub fn to_utc_struct<T: crate::Time>(&self, local_wall: T) -> T {
+        T::from_wall_clock(local_wall.wall_clock(), self.offset()).to_utc()
+    }
+
+    /// The hour, minute and second of `utc` on a clock in this timezone, without building a new time object
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Time, System, IntTime, Tz};
+    /// let x = 1704465989u32.unix::<System>(); // 2024-01-05 14:46:29 UTC
+    /// assert_eq!(Tz::Npt.wall_clock_of(&x), (20, 31, 29));
+    /// assert_eq!(Tz::Mart.wall_clock_of(&x), (5, 16, 29));
+    /// ```
+    pub fn wall_clock_of<T: crate::Time>(&self, utc: &T) -> (u8, u8, u8) {
+        let secs = (utc.unix_ms().div_euclid(1000) + self.offset() as i64).rem_euclid(86400);
+        ((secs / 3600) as u8, (secs % 3600 / 60) as u8, (secs % 60) as u8)
+    }
+
+    /// The name to display for the timezone under the process-wide `TzNamePreference` - the compound `name()` if none is set or it
+    /// does not cover this zone
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{set_tz_name_preference, reset_tz_name_preference, Tz, TzNamePreference};
+    /// set_tz_name_preference(TzNamePreference::new(&["CET"]));
+    /// assert_eq!(Tz::BstCet.preferred_name(), "CET");
+    /// assert_eq!(Tz::CestEet.preferred_name(), "CEST/EET");
+    /// reset_tz_name_preference();
+    /// assert_eq!(Tz::BstCet.preferred_name(), "BST/CET");
+    /// ```
+    pub fn preferred_name(&self) -> String {
+        TZ_NAME_PREFERENCE.read().unwrap_or_else(|e| e.into_inner()).name_for(*self)
+    }
+
+    /// The name to display for the timezone under the given preference, rather than the process-wide one
+    /// 
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Tz, TzNamePreference};
+    /// let london = TzNamePreference::new(&["GMT", "BST"]);
+    /// assert_eq!(Tz::BstCet.name_with(&london), "BST");
+    /// assert_eq!(Tz::UtcWet.name_with(&london), "GMT");
+    /// assert_eq!(Tz::Pst.name_with(&london), "PST");
+    /// ```
+    pub fn name_with(&self, preference: &TzNamePreference) -> String {
+        preference.name_for(*self)
+    }
+}
+
+/// the preference set with `set_tz_name_preference`
+static TZ_NAME_PREFERENCE: RwLock<TzNamePreference> = RwLock::new(TzNamePreference { names: Vec::new() });
+
+/// An ordered list of preferred abbreviations, used to pick one name for a zone whose offset is shared by several ("CET" rather than
+/// "BST/CET" for a Paris deployment, "BST" for London)
+///
+/// For each zone, the first abbreviation that can mean it (see `Tz::from_abbreviation`) wins; zones the list does not cover keep their
+/// compound `name()`. Abbreviations are matched ignoring case but displayed as given. Set it for the whole process with
+/// `set_tz_name_preference`, which `Zoned`, `world_view_fmt` and `%Z` in `Zoned::strftime` honour, or pass one per call to
+/// `Tz::name_with` and `Zoned::strftime_with_tz_names`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Tz, TzNamePreference};
+/// let paris = TzNamePreference::new(&["CET", "CEST"]);
+/// assert_eq!(paris.name_for(Tz::BstCet), "CET");
+/// assert_eq!(paris.name_for(Tz::CestEet), "CEST");
+/// assert_eq!(paris.name_for(Tz::Ist), "IST");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TzNamePreference {
+    names: Vec<String>,
+}
+
+impl TzNamePreference {
+    /// A preference for the given abbreviations, most preferred first - ones that mean no known zone are kept but never match
+    pub fn new<S: AsRef<str>>(names: &[S]) -> Self {
+        TzNamePreference { names: names.iter().map(|n| n.as_ref().to_string()).collect() }
+    }
+
+    /// the abbreviations, most preferred first
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The name to display for `tz`: the first preferred abbreviation that can mean it, or its compound `name()`
+    pub fn name_for(&self, tz: Tz) -> String {
+        self.names
+            .iter()
+            .find(|name| Tz::from_abbreviation(name).contains(&tz))
+            .cloned()
+            .unwrap_or_else(|| tz.name())
+    }
+}
+
+/// Set the `TzNamePreference` used for the whole process - by `Tz::preferred_name`, `Zoned`'s `Display` and `strftime`, and
+/// `Time::world_view_fmt`/`in_zones_fmt`. Undo with `reset_tz_name_preference`
+pub fn set_tz_name_preference(preference: TzNamePreference) {
+    *TZ_NAME_PREFERENCE.write().unwrap_or_else(|e| e.into_inner()) = preference;
+}
+
+/// Undo `set_tz_name_preference`, going back to the compound names such as "BST/CET"
+pub fn reset_tz_name_preference() {
+    *TZ_NAME_PREFERENCE.write().unwrap_or_else(|e| e.into_inner()) = TzNamePreference::default();
+}
+
+/// The `TzNamePreference` currently used for the whole process
+pub fn tz_name_preference() -> TzNamePreference {
+    TZ_NAME_PREFERENCE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// internal only - format `time` with `%Z` rendered as `zone_name`, rather than the numeric offset chrono gives a fixed offset
+pub(crate) fn strftime_zone_name<T: crate::Time>(time: &T, format: &str, zone_name: &str) -> String {
+    let items: Vec<Item> = StrftimeItems::new(format)
+        .map(|item| match item {
+            Item::Fixed(Fixed::TimezoneName) => Item::Literal(zone_name),
+            other => other,
+        })
+        .collect();
+    time.format_items(items.iter())
+}
+
+/// internal only - abbreviations and the zones they can mean, most common first
+const ABBREVIATIONS: [(&str, &[Tz]); 66] = [
+    ("UTC", &[Tz::UtcWet]),
+    ("GMT", &[Tz::UtcWet]),
+    ("UT", &[Tz::UtcWet]),
+    ("Z", &[Tz::UtcWet]),
+    ("WET", &[Tz::UtcWet]),
+    ("BST", &[Tz::BstCet]),
+    ("CET", &[Tz::BstCet]),
+    ("WEST", &[Tz::BstCet]),
+    ("WAT", &[Tz::BstCet]),
+    ("CEST", &[Tz::CestEet]),
+    ("EET", &[Tz::CestEet]),
+    ("CAT", &[Tz::CestEet]),
+    ("EEST", &[Tz::EestAst]),
+    ("MSK", &[Tz::EestAst]),
+    ("EAT", &[Tz::EestAst]),
+    ("AST", &[Tz::EestAst, Tz::AtstClt]),
+    ("IST", &[Tz::Ist, Tz::BstCet, Tz::CestEet]),
+    ("JST", &[Tz::JstKst]),
+    ("KST", &[Tz::JstKst]),
+    ("CST", &[Tz::Censt, Tz::CstAwstSstHkt]),
+    ("AWST", &[Tz::CstAwstSstHkt]),
+    ("SST", &[Tz::CstAwstSstHkt, Tz::Sast]),
+    ("SGT", &[Tz::CstAwstSstHkt]),
+    ("HKT", &[Tz::CstAwstSstHkt]),
+    ("PHT", &[Tz::CstAwstSstHkt]),
+    ("ACST", &[Tz::Acst]),
+    ("AEST", &[Tz::AestChst]),
+    ("CHST", &[Tz::AestChst]),
+    ("ACDT", &[Tz::Lwst]),
+    ("LHST", &[Tz::Lwst]),
+    ("LWST", &[Tz::Lwst]),
+    ("AEDT", &[Tz::Lint]),
+    ("NZST", &[Tz::NzstFjt]),
+    ("FJT", &[Tz::NzstFjt]),
+    ("NZDT", &[Tz::Lint]),
+    ("SAST", &[Tz::Sast]),
+    ("HST", &[Tz::Hast]),
+    ("HAST", &[Tz::Hast]),
+    ("AKST", &[Tz::Alst]),
+    ("ALST", &[Tz::Alst]),
+    ("AKDT", &[Tz::Pst]),
+    ("PST", &[Tz::Pst]),
+    ("PDT", &[Tz::Mst]),
+    ("MST", &[Tz::Mst]),
+    ("MDT", &[Tz::Censt]),
+    ("CENST", &[Tz::Censt]),
+    ("CDT", &[Tz::Est]),
+    ("EST", &[Tz::Est]),
+    ("EDT", &[Tz::AtstClt]),
+    ("ATST", &[Tz::AtstClt]),
+    ("CLT", &[Tz::AtstClt]),
+    ("ADT", &[Tz::BtAtArtUyt]),
+    ("NST", &[Tz::Nst]),
+    ("BRT", &[Tz::BtAtArtUyt]),
+    ("ART", &[Tz::BtAtArtUyt]),
+    ("UYT", &[Tz::BtAtArtUyt]),
+    ("BT", &[Tz::BtAtArtUyt]),
+    ("AT", &[Tz::BtAtArtUyt]),
+    ("ICT", &[Tz::IctWib]),
+    ("WIB", &[Tz::IctWib]),
+    ("MART", &[Tz::Mart]),
+    ("IRST", &[Tz::Irst]),
+    ("NPT", &[Tz::Npt]),
+    ("MMT", &[Tz::Mmt]),
+    ("CHAST", &[Tz::Chast]),
+    ("LINT", &[Tz::Lint]),
+];
+
+/// internal only - the number of single character edits between two ASCII strings, ignoring case
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.to_ascii_uppercase().into_bytes(), b.to_ascii_uppercase().into_bytes());
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// internal only - the timezone a parsed name means, choosing between an ambiguous abbreviation's zones by `preferences`
+fn resolve_name(name: &str, position: usize, preferences: &[Tz]) -> Result<Tz, TzNameError> {
+    // abbreviations come first, as some ("IST") are also the name of one of the zones they could mean
+    let zones = Tz::from_abbreviation(name);
+    if let (true, Some(tz)) = (zones.is_empty(), Tz::from_name(name)) {
+        return Ok(tz);
+    }
+    match zones {
+        [] => {
+            let names = Tz::ALL.iter().map(|tz| tz.name()).collect::<Vec<_>>();
+            let mut near: Vec<(usize, String)> = ABBREVIATIONS
+                .iter()
+                .map(|(abbr, _)| abbr.to_string())
+                .chain(names)
+                .map(|known| (edit_distance(name, &known), known))
+                .filter(|(distance, _)| *distance <= 1 + name.len() / 4)
+                .collect();
+            near.sort();
+            near.dedup();
+            Err(TzNameError::Unknown {
+                name: name.to_string(),
+                position,
+                near: near.into_iter().take(5).map(|(_, known)| known).collect(),
+            })
+        }
+        [tz] => Ok(*tz),
+        candidates => preferences.iter().find(|tz| candidates.contains(tz)).copied().ok_or_else(|| TzNameError::Ambiguous {
+            name: name.to_string(),
+            position,
+            candidates: candidates.to_vec(),
+        }),
+    }
+}
+
+/// internal only - `StrTime::parse_with_tz_names`
+pub(crate) fn parse_with_tz_names<T: crate::Time>(input: &str, format: &str, preferences: &[Tz]) -> Result<T, TzNameError> {
+    if input.trim().is_empty() {
+        return Err(ParseError::Empty.into());
+    }
+    let (parsed, name) = crate::bytes::parse_with_zone_name(input, format)?;
+    let invalid = |e: chrono::ParseError| TzNameError::Parse(ParseError::invalid(input, input.len(), e.to_string()));
+    match name {
+        Some((name, position)) => {
+            let tz = resolve_name(&name, position, preferences)?;
+            let naive = parsed.to_naive_datetime_with_offset(0).map_err(invalid)?;
+            Ok(T::from_wall_clock(naive, tz.offset()))
+        }
+        None => {
+            let (unix_ms, offset, source) = crate::resolve_parsed(&parsed, format, Some(0), crate::OffsetSource::AssumedUtc).map_err(invalid)?;
+            let wall = chrono::DateTime::from_timestamp_millis(unix_ms + offset as i64 * 1000)
+                .ok_or_else(|| TzNameError::Parse(ParseError::invalid(input, input.len(), "a representable time")))?;
+            Ok(T::from_wall_clock(wall.naive_utc(), offset).with_offset_source(source))
+        }
+    }
+}
+
+/// An error from `StrTime::parse_with_tz_names`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TzNameError {
+    /// the input did not match the format
+    Parse(ParseError),
+    /// the timezone name at `position` is not known - `near` holds up to 5 similar names that are
+    Unknown { name: String, position: usize, near: Vec<String> },
+    /// the timezone name at `position` can mean several zones, and none of them were in the preferences
+    Ambiguous { name: String, position: usize, candidates: Vec<Tz> },
+}
+
+impl core::fmt::Display for TzNameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TzNameError::Parse(e) => write!(f, "{}", e),
+            TzNameError::Unknown { name, position, near } if near.is_empty() => write!(f, "unknown timezone {:?} at position {}", name, position),
+            TzNameError::Unknown { name, position, near } => {
+                write!(f, "unknown timezone {:?} at position {}, did you mean {}?", name, position, near.join(", "))
+            }
+            TzNameError::Ambiguous { name, position, candidates } => {
+                let candidates: Vec<String> = candidates.iter().map(|tz| format!("{} ({})", tz.name(), tz.offset_str())).collect();
+                write!(f, "ambiguous timezone {:?} at position {}, could be {}", name, position, candidates.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for TzNameError {}
+
+impl From<ParseError> for TzNameError {
+    fn from(e: ParseError) -> Self {
+        TzNameError::Parse(e)
+    }
+}
+
+/// An error returned when parsing a timezone offset string fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TzParseError {
+    /// the string was not in the form "+|-HH:MM"
+    InvalidFormat(String),
+    /// the string had no leading '+' or '-'
+    MissingSign(String),
+    /// the hours or minutes were out of range
+    OutOfRange(String),
+    /// the offset (in seconds) was valid, but no timezone has it
+    UnknownOffset(i32),
+}
+
+impl core::fmt::Display for TzParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TzParseError::InvalidFormat(s) => write!(f, "invalid offset {:?}, expected +|-HH:MM", s),
+            TzParseError::MissingSign(s) => write!(f, "offset {:?} is missing a leading '+' or '-'", s),
+            TzParseError::OutOfRange(s) => write!(f, "offset {:?} is out of range", s),
+            TzParseError::UnknownOffset(o) => write!(f, "no timezone has an offset of {} seconds", o),
+        }
+    }
+}
+
+impl std::error::Error for TzParseError {}
+
+/// internal only - parses "+|-HH:MM" into signed seconds, applying the sign to the minutes as well as the hours
+pub(crate) fn parse_offset(offset: &str) -> Result<i32, TzParseError> {
+    let bytes = offset.as_bytes();
+    let sign = match bytes.first() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        Some(b) if b.is_ascii_digit() => return Err(TzParseError::MissingSign(offset.to_string())),
+        _ => return Err(TzParseError::InvalidFormat(offset.to_string())),
+    };
+    if bytes.len() != 6
+        || bytes[3] != b':'
+        || ![1, 2, 4, 5].iter().all(|i| bytes[*i].is_ascii_digit())
+    {
+        return Err(TzParseError::InvalidFormat(offset.to_string()));
+    }
+    let hours = ((bytes[1] - b'0') * 10 + (bytes[2] - b'0')) as i32;
+    let minutes = ((bytes[4] - b'0') * 10 + (bytes[5] - b'0')) as i32;
+    if hours > 23 || minutes > 59 {
+        return Err(TzParseError::OutOfRange(offset.to_string()));
     }
+    Ok(sign * (hours * 3600 + minutes * 60))
 }
\ No newline at end of file
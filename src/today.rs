@@ -0,0 +1,100 @@
+use crate::{System, Time, TimeSpan};
+use chrono::{NaiveDate, NaiveTime};
+
+/// A calendar day taken from a single reading of the clock, so that its start, end and date always agree - reading `System::now()`
+/// once for the start of the day and again for the end can straddle midnight
+///
+/// The day is read on the wall clock `offset` seconds from UTC. `yesterday` and `tomorrow` give the days either side, from the same
+/// reading. With the `testing` feature, `capture` respects the mock clock
+///
+/// # Examples
+/// ```rust
+/// use thetime::{Today, System, Time, StrTime};
+/// let now = "2024-01-05 23:59:59".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+/// let today = Today::at(&now, 0);
+/// assert_eq!(today.start().pretty(), "2024-01-05 00:00:00");
+/// assert_eq!(today.end().pretty(), "2024-01-06 00:00:00");
+/// assert_eq!(today.tomorrow().date().to_string(), "2024-01-06");
+/// assert!(today.is_today(&now) && !today.is_today(&today.end()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Today {
+    now: System,
+    date: NaiveDate,
+    offset: i32,
+}
+
+impl Today {
+    /// Read the clock once, and take the day it falls in on the wall clock `offset` seconds from UTC
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{Today, System, Time};
+    /// let today = Today::capture(System::now().utc_offset());
+    /// println!("{} runs from {} to {}", today.date(), today.start(), today.end());
+    /// ```
+    pub fn capture(offset: i32) -> Today {
+        Today::at(&System::now(), offset)
+    }
+
+    /// The day `now` falls in on the wall clock `offset` seconds from UTC, as if the clock had read `now`
+    pub fn at<T: Time>(now: &T, offset: i32) -> Today {
+        let now = System::from_epoch_offset(now.raw(), crate::clamp_offset(offset));
+        Today {
+            date: now.wall_clock().date(),
+            offset: now.utc_offset(),
+            now,
+        }
+    }
+
+    /// The day before, from the same reading
+    pub fn yesterday(&self) -> Today {
+        Today {
+            date: self.date.pred_opt().unwrap_or(self.date),
+            ..self.clone()
+        }
+    }
+
+    /// The day after, from the same reading
+    pub fn tomorrow(&self) -> Today {
+        Today {
+            date: self.date.succ_opt().unwrap_or(self.date),
+            ..self.clone()
+        }
+    }
+
+    /// The reading the day was taken from
+    pub fn now(&self) -> &System {
+        &self.now
+    }
+
+    /// The offset from UTC the day is read in, in seconds
+    pub fn utc_offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// The calendar date
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    /// Midnight at the start of the day
+    pub fn start(&self) -> System {
+        System::from_wall_clock(self.date.and_time(NaiveTime::MIN), self.offset)
+    }
+
+    /// Midnight at the end of the day (the start of the next), which is not part of it
+    pub fn end(&self) -> System {
+        self.tomorrow().start()
+    }
+
+    /// The day as a span, from `start` to `end`
+    pub fn span(&self) -> TimeSpan<System> {
+        TimeSpan::new(self.start(), self.end())
+    }
+
+    /// Whether `time` falls in the day - at or after `start`, and before `end`
+    pub fn is_today<T: Time>(&self, time: &T) -> bool {
+        self.span().contains(time)
+    }
+}
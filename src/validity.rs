@@ -0,0 +1,139 @@
+use crate::{ParseError, StrTime, Time, TimeError, OFFSET_1601};
+use core::time::Duration;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How much clock skew `ValidityWindow::status` allows at each end of the window
+///
+/// A `Duration` converts to the same leeway at both ends
+///
+/// # Examples
+/// ```rust
+/// use thetime::Leeway;
+/// use core::time::Duration;
+/// let leeway = Leeway::new(Duration::from_secs(60), Duration::ZERO);
+/// assert_eq!(Leeway::from(Duration::from_secs(5)), Leeway::symmetric(Duration::from_secs(5)));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Leeway {
+    /// how long before `not_before` to start accepting
+    pub not_before: Duration,
+    /// how long after `not_after` to keep accepting
+    pub not_after: Duration,
+}
+
+impl Leeway {
+    /// Different leeways at each end
+    pub fn new(not_before: Duration, not_after: Duration) -> Self {
+        Leeway { not_before, not_after }
+    }
+
+    /// The same leeway at both ends
+    pub fn symmetric(leeway: Duration) -> Self {
+        Leeway::new(leeway, leeway)
+    }
+}
+
+impl From<Duration> for Leeway {
+    fn from(leeway: Duration) -> Self {
+        Leeway::symmetric(leeway)
+    }
+}
+
+/// Where a time falls relative to a `ValidityWindow`, with the distance to the nearest edge of the window as widened by the leeway
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ValidityStatus {
+    /// before the window opens
+    NotYetValid { starts_in: Duration },
+    /// inside the window
+    Valid { expires_in: Duration },
+    /// at or after the window closes
+    Expired { expired_ago: Duration },
+}
+
+impl ValidityStatus {
+    /// Whether this is `Valid`
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ValidityStatus::Valid { .. })
+    }
+}
+
+/// The window a certificate or token is valid in, from `not_before` (inclusive) to `not_after` (exclusive) - the way JWTs read `nbf` and
+/// `exp`
+///
+/// # Examples
+/// ```rust
+/// use thetime::{ValidityWindow, ValidityStatus, System, Time, IntTime};
+/// use core::time::Duration;
+/// let token = ValidityWindow::<System>::from_unix_seconds(1704465989, 1704469589).unwrap();
+/// let now = 1704465989u32.unix::<System>();
+/// assert_eq!(token.status(&now, Duration::ZERO), ValidityStatus::Valid { expires_in: Duration::from_secs(3600) });
+/// assert!(!token.status(&now.add_seconds(-1), Duration::ZERO).is_valid());
+/// assert!(token.status(&now.add_seconds(-1), Duration::from_secs(30)).is_valid());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidityWindow<T> {
+    pub not_before: T,
+    pub not_after: T,
+}
+
+impl<T: Time> ValidityWindow<T> {
+    /// A window from `not_before` to `not_after` - a window that closes before it opens is never valid
+    pub fn new(not_before: T, not_after: T) -> Self {
+        ValidityWindow { not_before, not_after }
+    }
+
+    /// A window from a JWT's `nbf` and `exp` claims, in seconds since 1970 - errors if either is before 1601 or after 9999
+    pub fn from_unix_seconds(nbf: i64, exp: i64) -> Result<Self, TimeError> {
+        let time = |secs: i64| {
+            let raw = secs.checked_add(OFFSET_1601 as i64).and_then(|s| s.checked_mul(1000)).ok_or(TimeError::Overflow)?;
+            if raw < 0 || raw - OFFSET_1601 as i64 * 1000 > crate::FAR_FUTURE_UNIX_MS {
+                return Err(TimeError::Overflow);
+            }
+            Ok(T::from_epoch_offset(raw as u64, 0))
+        };
+        Ok(ValidityWindow::new(time(nbf)?, time(exp)?))
+    }
+
+    /// A window from an X.509 certificate's `notBefore` and `notAfter`, each a DER UTCTime ("240105144629Z") or GeneralizedTime
+    /// ("20240105144629Z"), told apart by length
+    ///
+    /// Note that RFC 5280 counts the `notAfter` second itself as valid, where this window closes at it - give a leeway of at least a
+    /// second at that end to match
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{ValidityWindow, System, Time};
+    /// let cert = ValidityWindow::<System>::from_asn1("240105144629Z", "20500105144629Z").unwrap();
+    /// assert_eq!(cert.not_after.pretty(), "2050-01-05 14:46:29");
+    /// ```
+    pub fn from_asn1(not_before: &str, not_after: &str) -> Result<Self, ParseError> {
+        let time = |s: &str| if s.len() == 13 { s.strp_asn1_utctime::<T>() } else { s.strp_asn1_generalizedtime::<T>() };
+        Ok(ValidityWindow::new(time(not_before)?, time(not_after)?))
+    }
+
+    /// Where `now` falls relative to the window, widened by `leeway` at each end - a `Duration` for the same leeway at both ends, or a
+    /// `Leeway` for different ones. Open from `not_before - leeway` (inclusive) to `not_after + leeway` (exclusive)
+    pub fn status<U: Time, L: Into<Leeway>>(&self, now: &U, leeway: L) -> ValidityStatus {
+        let leeway = leeway.into();
+        let as_ms = |d: Duration| i64::try_from(d.as_millis()).unwrap_or(i64::MAX);
+        let now = now.unix_ms();
+        let opens = self.not_before.unix_ms().saturating_sub(as_ms(leeway.not_before));
+        let closes = self.not_after.unix_ms().saturating_add(as_ms(leeway.not_after));
+        let ms = |a: i64, b: i64| Duration::from_millis(a.abs_diff(b));
+        if now < opens {
+            ValidityStatus::NotYetValid { starts_in: ms(opens, now) }
+        } else if now < closes {
+            ValidityStatus::Valid { expires_in: ms(closes, now) }
+        } else {
+            ValidityStatus::Expired { expired_ago: ms(now, closes) }
+        }
+    }
+
+    /// Whether `now` is inside the window, widened by `leeway` - see `status`
+    pub fn is_valid<U: Time, L: Into<Leeway>>(&self, now: &U, leeway: L) -> bool {
+        self.status(now, leeway).is_valid()
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{ParseError, Time};
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use core::fmt::Display;
+use core::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An ISO 8601 week, such as `2024-W05` - weeks start on Monday, and week 1 is the week containing the year's first Thursday
+///
+/// Serializes as its string form
+///
+/// # Examples
+/// ```rust
+/// use thetime::{YearWeek, System, Time, IntTime};
+/// let week: YearWeek = "2024-W05".parse().unwrap();
+/// assert_eq!(week.start::<System>().pretty(), "2024-01-29 00:00:00");
+/// assert_eq!(week.succ().to_string(), "2024-W06");
+/// assert_eq!(YearWeek::of(&1704465989u32.unix::<System>()).to_string(), "2024-W01");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct YearWeek {
+    year: i32,
+    week: u8,
+}
+
+impl YearWeek {
+    /// A week of an ISO week-numbering year, or None if the year has no such week (or is outside chrono's range)
+    pub fn new(year: i32, week: u8) -> Option<YearWeek> {
+        (week >= 1 && week <= YearWeek::weeks_in_year(year)?).then_some(YearWeek { year, week })
+    }
+
+    /// The week containing a time object, by its wall clock
+    pub fn of<T: Time>(time: &T) -> YearWeek {
+        YearWeek {
+            year: time.iso_week_year(),
+            week: time.iso_week() as u8,
+        }
+    }
+
+    /// The number of weeks (52 or 53) in an ISO week-numbering year, or None outside chrono's range
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::YearWeek;
+    /// assert_eq!(YearWeek::weeks_in_year(2020), Some(53));
+    /// assert_eq!(YearWeek::weeks_in_year(2024), Some(52));
+    /// ```
+    pub fn weeks_in_year(year: i32) -> Option<u8> {
+        // 28 December is always in the last week of its year
+        NaiveDate::from_ymd_opt(year, 12, 28).map(|d| d.iso_week().week() as u8)
+    }
+
+    /// The ISO week-numbering year, which can differ from the calendar year for days around new year
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    /// The week number, from 1 to 52 or 53
+    pub fn week(&self) -> u8 {
+        self.week
+    }
+
+    /// internal only - the Monday starting the week
+    fn monday(&self) -> NaiveDate {
+        NaiveDate::from_isoywd_opt(self.year, self.week as u32, Weekday::Mon).unwrap()
+    }
+
+    /// Midnight UTC on the Monday starting the week
+    pub fn start<T: Time>(&self) -> T {
+        T::from_wall_clock(self.monday().and_time(NaiveTime::MIN), 0)
+    }
+
+    /// Midnight UTC on the Monday after the week - the end is exclusive, as with `Time::week_span`
+    pub fn end<T: Time>(&self) -> T {
+        T::from_wall_clock((self.monday() + chrono::Duration::days(7)).and_time(NaiveTime::MIN), 0)
+    }
+
+    /// Whether a time object falls in this week, by its wall clock
+    pub fn contains<T: Time>(&self, time: &T) -> bool {
+        YearWeek::of(time) == *self
+    }
+
+    /// The next week, moving into the next year after week 52 or 53
+    pub fn succ(&self) -> YearWeek {
+        match YearWeek::new(self.year, self.week + 1) {
+            Some(next) => next,
+            None => YearWeek { year: self.year + 1, week: 1 },
+        }
+    }
+
+    /// The previous week, moving into the last week of the previous year before week 1
+    pub fn pred(&self) -> YearWeek {
+        if self.week > 1 {
+            return YearWeek { year: self.year, week: self.week - 1 };
+        }
+        let year = self.year - 1;
+        YearWeek {
+            year,
+            week: YearWeek::weeks_in_year(year).unwrap_or(52),
+        }
+    }
+}
+
+impl<T: Time> From<&T> for YearWeek {
+    fn from(time: &T) -> Self {
+        YearWeek::of(time)
+    }
+}
+
+impl Display for YearWeek {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:04}-W{:02}", self.year, self.week)
+    }
+}
+
+impl FromStr for YearWeek {
+    type Err = ParseError;
+
+    /// Parses `YYYY-Www` (the week always two digits)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let (year, week) = s.split_once("-W").ok_or_else(|| ParseError::invalid(s, s.find('-').unwrap_or(s.len()), "\"-W\""))?;
+        let year_ok = year.len() >= 4 && year.trim_start_matches('-').bytes().all(|b| b.is_ascii_digit());
+        let year: i32 = year
+            .parse()
+            .ok()
+            .filter(|_| year_ok)
+            .ok_or_else(|| ParseError::invalid(s, 0, "a four digit year"))?;
+        let week_ok = week.len() == 2 && week.bytes().all(|b| b.is_ascii_digit());
+        let week: u8 = week
+            .parse()
+            .ok()
+            .filter(|_| week_ok)
+            .ok_or_else(|| ParseError::invalid(s, s.len() - week.len(), "a two digit week"))?;
+        YearWeek::new(year, week).ok_or(ParseError::OutOfRange {
+            field: "week",
+            value: week as i64,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for YearWeek {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for YearWeek {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,164 @@
+//! internal only - the stable serde wire format shared by `System` and `Ntp`
+//!
+//! Version 1 is `{ "v": 1, "ms1601": <milliseconds since 1601-01-01 00:00:00>, "offset": <seconds>, "server": <string, Ntp only> }`.
+//! The legacy derived form (`inner_secs`, `inner_milliseconds`, `utc_offset`, `server`) is still accepted when deserializing.
+//!
+//! In formats that aren't human readable (such as bincode), the version is written as the `u64` tag `u64::MAX - version` rather than
+//! as the bare number. Legacy seconds can never reach it (they were at most `u64::MAX / 1000`), so formats that write structs as
+//! sequences can tell a version 1 value from a legacy one by its first element, whatever their integer encoding. Human readable
+//! formats write structs as maps; a hand-written sequence leading with 1 (or the tag) is read as version 1, and anything else as the
+//! legacy fields in their declared order.
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserializer, Serializer};
+
+/// the current wire format version
+pub(crate) const WIRE_VERSION: u8 = 1;
+
+/// internal only - the version as written by formats that aren't human readable, above any legacy seconds value
+const BINARY_TAG: u64 = u64::MAX - WIRE_VERSION as u64;
+
+// every field either format may have
+const FIELDS: &[&str] = &["v", "ms1601", "offset", "inner_secs", "inner_milliseconds", "utc_offset"];
+const FIELDS_WITH_SERVER: &[&str] = &["v", "ms1601", "offset", "server", "inner_secs", "inner_milliseconds", "utc_offset"];
+
+/// the decoded contents of either wire format
+pub(crate) struct Wire {
+    pub ms1601: u64,
+    pub offset: i32,
+    pub server: Option<String>,
+}
+
+impl Wire {
+    pub(crate) fn serialize<S: Serializer>(&self, name: &'static str, serializer: S) -> Result<S::Ok, S::Error> {
+        let human_readable = serializer.is_human_readable();
+        let mut state = serializer.serialize_struct(name, if self.server.is_some() { 4 } else { 3 })?;
+        if human_readable {
+            state.serialize_field("v", &WIRE_VERSION)?;
+        } else {
+            state.serialize_field("v", &BINARY_TAG)?;
+        }
+        state.serialize_field("ms1601", &self.ms1601)?;
+        state.serialize_field("offset", &self.offset)?;
+        if let Some(server) = &self.server {
+            state.serialize_field("server", server)?;
+        }
+        state.end()
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(name: &'static str, with_server: bool, deserializer: D) -> Result<Wire, D::Error> {
+        let fields = if with_server { FIELDS_WITH_SERVER } else { FIELDS };
+        deserializer.deserialize_struct(name, fields, WireVisitor { with_server })
+    }
+}
+
+struct WireVisitor {
+    with_server: bool,
+}
+
+/// internal only - reads the first element of a sequence, which is the version tag or the legacy whole seconds
+struct Leading;
+
+impl<'de> DeserializeSeed<'de> for Leading {
+    /// None for version 1, or the legacy seconds
+    type Value = Option<u64>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<u64>, D::Error> {
+        use serde::Deserialize;
+        let human_readable = deserializer.is_human_readable();
+        let n = u64::deserialize(deserializer)?;
+        let version = n == BINARY_TAG || (human_readable && n == WIRE_VERSION as u64);
+        Ok(if version { None } else { Some(n) })
+    }
+}
+
+impl WireVisitor {
+    /// internal only - the rest of a version 1 sequence, after the version
+    fn visit_seq_v1<'de, A: SeqAccess<'de>>(self, mut seq: A) -> Result<Wire, A::Error> {
+        let ms1601 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let offset = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        let server = if self.with_server {
+            Some(seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?)
+        } else {
+            None
+        };
+        Ok(Wire { ms1601, offset, server })
+    }
+}
+
+impl<'de> Visitor<'de> for WireVisitor {
+    type Value = Wire;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "a time struct (wire format version {} or the legacy field names)", WIRE_VERSION)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Wire, A::Error> {
+        let inner_secs = match seq.next_element_seed(Leading)?.ok_or_else(|| de::Error::invalid_length(0, &self))? {
+            None => return self.visit_seq_v1(seq),
+            Some(secs) => secs,
+        };
+        // the legacy derived format: inner_secs, inner_milliseconds, then server (Ntp only) before utc_offset
+        let ms: u64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let server = if self.with_server {
+            Some(seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?)
+        } else {
+            None
+        };
+        let offset = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(if self.with_server { 3 } else { 2 }, &self))?;
+        Ok(Wire {
+            ms1601: inner_secs.saturating_mul(1000).saturating_add(ms),
+            offset,
+            server,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Wire, A::Error> {
+        let mut v: Option<u64> = None;
+        let mut ms1601: Option<u64> = None;
+        let mut offset: Option<i32> = None;
+        let mut server: Option<String> = None;
+        let mut inner_secs: Option<u64> = None;
+        let mut inner_milliseconds: Option<u64> = None;
+        let mut utc_offset: Option<i32> = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "v" => v = Some(map.next_value()?),
+                "ms1601" => ms1601 = Some(map.next_value()?),
+                "offset" => offset = Some(map.next_value()?),
+                "server" => server = Some(map.next_value()?),
+                "inner_secs" => inner_secs = Some(map.next_value()?),
+                "inner_milliseconds" => inner_milliseconds = Some(map.next_value()?),
+                "utc_offset" => utc_offset = Some(map.next_value()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let (ms1601, offset) = match v {
+            Some(v) if v == WIRE_VERSION as u64 || v == BINARY_TAG => (
+                ms1601.ok_or_else(|| de::Error::missing_field("ms1601"))?,
+                offset.unwrap_or(0),
+            ),
+            Some(v) => return Err(de::Error::custom(format!("unsupported wire format version {}", v))),
+            None => {
+                // the legacy derived format
+                let secs = inner_secs.ok_or_else(|| de::Error::missing_field("inner_secs"))?;
+                let ms = inner_milliseconds.unwrap_or(0);
+                (secs.saturating_mul(1000).saturating_add(ms), utc_offset.unwrap_or(0))
+            }
+        };
+
+        if self.with_server && server.is_none() {
+            return Err(de::Error::missing_field("server"));
+        }
+        Ok(Wire {
+            ms1601,
+            offset,
+            server: if self.with_server { server } else { None },
+        })
+    }
+}
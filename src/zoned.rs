@@ -0,0 +1,139 @@
+use crate::timezones::strftime_zone_name;
+use crate::{Time, Tz, TzNamePreference};
+use chrono::{Datelike, Timelike, Weekday};
+use core::cmp::Ordering;
+use core::fmt::Display;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A time object paired with the timezone it should be viewed in
+///
+/// The instant itself never changes when switching zones - only how it is displayed, and what the component accessors return.
+/// Ordering and equality only consider the instant, not the zone
+///
+/// # Examples
+/// ```rust
+/// use thetime::{System, Time, Tz, IntTime};
+/// let x = 1704465989u32.unix::<System>().in_zone(Tz::Ist);
+/// assert_eq!(x.to_string(), "2024-01-05 20:16:29 IST");
+/// assert_eq!(x.to_zone(Tz::Pst).hour(), 6);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Zoned<T> {
+    instant: T,
+    zone: Tz,
+}
+
+impl<T: Time> Zoned<T> {
+    /// Pair a time object with a timezone
+    pub fn new(instant: T, zone: Tz) -> Self {
+        Zoned { instant, zone }
+    }
+
+    /// the underlying time object, as it was given
+    pub fn instant(&self) -> &T {
+        &self.instant
+    }
+
+    /// the timezone this is viewed in
+    pub fn zone(&self) -> Tz {
+        self.zone
+    }
+
+    /// the same instant, viewed in another timezone
+    pub fn to_zone(self, zone: Tz) -> Self {
+        Zoned { zone, ..self }
+    }
+
+    /// the underlying time object shifted into the zone, so that it displays the wall clock of the zone
+    pub fn local(&self) -> T {
+        self.instant.change_tz_seconds(self.zone.offset())
+    }
+
+    /// Format the time as seen in the zone, with `%Z` giving the zone's name under the process-wide `TzNamePreference`
+    pub fn strftime(&self, format: &str) -> String {
+        strftime_zone_name(&self.local(), format, &self.zone.preferred_name())
+    }
+
+    /// Like `strftime`, but naming the zone with the given preference rather than the process-wide one
+    ///
+    /// # Examples
+    /// ```rust
+    /// use thetime::{System, Time, Tz, TzNamePreference, IntTime};
+    /// let x = 1704465989u32.unix::<System>().in_zone(Tz::BstCet);
+    /// assert_eq!(x.strftime_with_tz_names("%H:%M %Z", &TzNamePreference::new(&["CET"])), "15:46 CET");
+    /// assert_eq!(x.strftime_with_tz_names("%H:%M %Z", &TzNamePreference::default()), "15:46 BST/CET");
+    /// ```
+    pub fn strftime_with_tz_names(&self, format: &str, preference: &TzNamePreference) -> String {
+        strftime_zone_name(&self.local(), format, &self.zone.name_with(preference))
+    }
+
+    /// the year in the zone
+    pub fn year(&self) -> i32 {
+        self.local().wall_clock().year()
+    }
+
+    /// the month (1-12) in the zone
+    pub fn month(&self) -> u32 {
+        self.local().wall_clock().month()
+    }
+
+    /// the day of the month (1-31) in the zone
+    pub fn day(&self) -> u32 {
+        self.local().wall_clock().day()
+    }
+
+    /// the day of the week in the zone
+    pub fn weekday(&self) -> Weekday {
+        self.local().wall_clock().weekday()
+    }
+
+    /// the hour (0-23) in the zone
+    pub fn hour(&self) -> u32 {
+        self.local().wall_clock().hour()
+    }
+
+    /// the minute (0-59) in the zone
+    pub fn minute(&self) -> u32 {
+        self.local().wall_clock().minute()
+    }
+
+    /// the second (0-59) in the zone
+    pub fn second(&self) -> u32 {
+        self.local().wall_clock().second()
+    }
+}
+
+impl<T: Time> Display for Zoned<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} {}", self.local().pretty(), self.zone.preferred_name())
+    }
+}
+
+/// wraps a time object in UTC/WET
+impl<T: Time> From<T> for Zoned<T> {
+    fn from(instant: T) -> Self {
+        Zoned::new(instant, Tz::UtcWet)
+    }
+}
+
+impl<T: Time> PartialEq for Zoned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.instant.unix_ms() == other.instant.unix_ms()
+    }
+}
+
+impl<T: Time> Eq for Zoned<T> {}
+
+impl<T: Time> PartialOrd for Zoned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Time> Ord for Zoned<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.instant.unix_ms().cmp(&other.instant.unix_ms())
+    }
+}
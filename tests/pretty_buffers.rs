@@ -0,0 +1,97 @@
+//! Counts allocations with a global allocator, so these live in their own test binary - counts are per thread, so the tests can
+//! still run in parallel
+
+use std::alloc::{GlobalAlloc, Layout, System as SystemAlloc};
+use std::cell::Cell;
+use std::fmt::Write;
+use thetime::{IntTime, Ntp, System, Time};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        SystemAlloc.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        SystemAlloc.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        SystemAlloc.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// how many allocations `f` makes on this thread
+fn allocations(f: impl FnOnce()) -> u64 {
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+fn times() -> Vec<System> {
+    (0..100u32).map(|i| (1_704_465_989 + i * 86_461).unix::<System>().change_tz(if i % 2 == 0 { "+05:30" } else { "-08:00" })).collect()
+}
+
+#[test]
+fn reused_buffers_do_not_allocate() {
+    let times = times();
+    // warm up the per thread buffer, the environment lookup and the output string
+    let mut out = String::with_capacity(256);
+    times[0].with_pretty(|_| ());
+    times[0].pretty_into_string(&mut out);
+
+    assert_eq!(allocations(|| for t in &times { t.with_pretty(|s| assert_eq!(s.len(), 19)) }), 0);
+    assert_eq!(allocations(|| for t in &times { t.pretty_into_string(&mut out) }), 0);
+    assert_eq!(allocations(|| for t in &times { out.clear(); write!(out, "{}", t).unwrap() }), 0);
+    // the allocating form, for comparison
+    assert_eq!(allocations(|| for t in &times { assert_eq!(t.pretty().len(), 19) }), times.len() as u64);
+}
+
+#[test]
+fn buffers_do_not_leak_between_values() {
+    let mut out = String::new();
+    let short = 0u32.unix::<Ntp>();
+    let times = times();
+    for t in &times {
+        t.pretty_into_string(&mut out);
+        assert_eq!(out, t.pretty());
+        assert_eq!(t.to_string(), t.pretty());
+        t.with_pretty(|s| assert_eq!(s, t.pretty()));
+        short.with_pretty(|s| assert_eq!(s, short.pretty()));
+        assert_eq!(format!("{} / {}", t, short), format!("{} / {}", t.pretty(), short.pretty()));
+    }
+
+    // a call from inside another gets its own string
+    let (a, b) = (&times[0], &times[1]);
+    a.with_pretty(|outer| {
+        b.with_pretty(|inner| assert_eq!(inner, b.pretty()));
+        assert_eq!(outer, a.pretty());
+    });
+
+    // and every thread has its own buffer
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let times = times.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    for t in &times {
+                        t.with_pretty(|s| assert_eq!(s, t.pretty()));
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
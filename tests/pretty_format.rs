@@ -0,0 +1,49 @@
+//! The default pretty format is process wide, so these live in their own test binary, serialized with a mutex
+
+use std::sync::{Mutex, Once};
+use thetime::{default_pretty_format, reset_default_pretty_format, set_default_pretty_format, IntTime, Ntp, System, Time};
+
+static LOCK: Mutex<()> = Mutex::new(());
+static ENV: Once = Once::new();
+
+/// set `THETIME_FORMAT` before anything can read it
+fn setup() -> std::sync::MutexGuard<'static, ()> {
+    ENV.call_once(|| std::env::set_var("THETIME_FORMAT", "%Y-%m-%dT%H:%M"));
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    reset_default_pretty_format();
+    guard
+}
+
+#[test]
+fn env_format_is_the_default() {
+    let _guard = setup();
+    let x = 1704465989u32.unix::<System>();
+    assert_eq!(default_pretty_format(), "%Y-%m-%dT%H:%M");
+    assert_eq!(x.pretty(), "2024-01-05T14:46");
+    assert_eq!(x.to_string(), "2024-01-05T14:46");
+}
+
+#[test]
+fn custom_format_changes_display() {
+    let _guard = setup();
+    let x = 1704465989u32.unix::<System>().change_tz("+01:00");
+    let n = 1704465989u32.unix::<Ntp>();
+    let (iso, rfc) = (x.iso8601(), x.rfc3339());
+
+    set_default_pretty_format("%d/%m/%Y %H:%M:%S %z").unwrap();
+    assert_eq!(x.to_string(), "05/01/2024 15:46:29 +0100");
+    assert_eq!(n.to_string(), "05/01/2024 14:46:29 +0000");
+    assert_eq!(format!("{}", n.pretty()), "05/01/2024 14:46:29 +0000");
+    assert_eq!((x.iso8601(), x.rfc3339()), (iso.clone(), rfc.clone()));
+
+    // an invalid format is rejected and leaves the current one in place
+    assert!(set_default_pretty_format("%Y-%").is_err());
+    assert_eq!(default_pretty_format(), "%d/%m/%Y %H:%M:%S %z");
+
+    // visible from other threads too
+    assert_eq!(std::thread::spawn(move || n.to_string()).join().unwrap(), "05/01/2024 14:46:29 +0000");
+
+    reset_default_pretty_format();
+    assert_eq!(x.to_string(), "2024-01-05T15:46");
+    assert_eq!((x.iso8601(), x.rfc3339()), (iso, rfc));
+}
@@ -0,0 +1,57 @@
+//! The library's own unit tests are exempt from `strict-conversions`, so its assertions are checked from outside, in their own test binary
+#![cfg(all(feature = "strict-conversions", debug_assertions))]
+
+use thetime::{Ntp, StrTime, System, Time};
+
+fn with_ms() -> System {
+    "2024-01-05 14:46:29.999".parse_time::<System>("%Y-%m-%d %H:%M:%S%.3f")
+}
+
+#[test]
+fn lossless_conversions_pass() {
+    let x = "2024-01-05 14:46:28".parse_time::<System>("%Y-%m-%d %H:%M:%S");
+    assert_eq!(x.unix(), 1704465988);
+    assert_eq!(x.cast::<Ntp>().unix(), 1704465988);
+    assert_eq!(x.mac_os(), 3787310788);
+    assert_eq!(x.sas_4gl(), 2020085188);
+    assert_eq!(x.asn1_utctime().unwrap(), "240105144628Z");
+    assert_eq!(x.dos(), Some(0x5825_75ce));
+    // the checked forms never assert
+    assert_eq!(with_ms().unix_checked().1.discarded_ms, 999);
+}
+
+#[test]
+#[should_panic(expected = "unix dropped precision: 999ms discarded")]
+fn unix_asserts() {
+    with_ms().unix();
+}
+
+#[test]
+#[should_panic(expected = "unix dropped precision")]
+fn ntp_unix_asserts() {
+    with_ms().cast::<Ntp>().unix();
+}
+
+#[test]
+#[should_panic(expected = "dos dropped precision: 1000ms discarded")]
+fn dos_asserts_on_an_odd_second() {
+    "2024-01-05 14:46:29".parse_time::<System>("%Y-%m-%d %H:%M:%S").dos();
+}
+
+#[test]
+#[should_panic(expected = "mac_os dropped precision")]
+fn mac_os_asserts() {
+    with_ms().mac_os();
+}
+
+#[test]
+#[should_panic(expected = "sas_4gl dropped precision")]
+fn sas_4gl_asserts() {
+    with_ms().sas_4gl();
+}
+
+#[test]
+#[should_panic(expected = "asn1_utctime dropped precision")]
+fn asn1_utctime_asserts() {
+    with_ms().asn1_utctime();
+}
@@ -0,0 +1,54 @@
+//! The timezone name preference is process wide, so these live in their own test binary, serialized with a mutex
+#![cfg(feature = "timezones")]
+
+use std::sync::Mutex;
+use thetime::{reset_tz_name_preference, set_tz_name_preference, tz_name_preference, IntTime, System, Time, Tz, TzNamePreference};
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// take the lock, starting from no preference
+fn setup() -> std::sync::MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    reset_tz_name_preference();
+    guard
+}
+
+#[test]
+fn preference_names_zoned_times() {
+    let _guard = setup();
+    let x = 1704465989u32.unix::<System>();
+    set_tz_name_preference(TzNamePreference::new(&["CET"]));
+    assert_eq!(tz_name_preference().names(), ["CET"]);
+    assert_eq!(x.in_zone(Tz::BstCet).to_string(), "2024-01-05 15:46:29 CET");
+    assert_eq!(x.in_zone(Tz::BstCet).strftime("%H:%M %Z"), "15:46 CET");
+
+    set_tz_name_preference(TzNamePreference::new(&["BST"]));
+    assert_eq!(x.in_zone(Tz::BstCet).to_string(), "2024-01-05 15:46:29 BST");
+
+    reset_tz_name_preference();
+    assert_eq!(x.in_zone(Tz::BstCet).to_string(), "2024-01-05 15:46:29 BST/CET");
+}
+
+#[test]
+fn preference_names_world_view() {
+    let _guard = setup();
+    let x = 1704465989u32.unix::<System>();
+    set_tz_name_preference(TzNamePreference::new(&["GMT", "CET", "EET", "HKT"]));
+    let view = x.in_zones_fmt(&[Tz::UtcWet, Tz::BstCet, Tz::CestEet, Tz::CstAwstSstHkt, Tz::JstKst], "%H:%M %Z");
+    let names: Vec<&str> = view.iter().map(|(_, s)| s.as_str()).collect();
+    assert_eq!(names, ["14:46 GMT", "15:46 CET", "16:46 EET", "22:46 HKT", "23:46 JST/KST"]);
+
+    let world = x.world_view_fmt("%Z");
+    assert!(world.contains(&(Tz::BstCet, "CET".to_string())));
+    assert!(world.contains(&(Tz::Pst, "PST".to_string())));
+    reset_tz_name_preference();
+}
+
+#[test]
+fn preference_leaves_plain_strftime_alone() {
+    let _guard = setup();
+    set_tz_name_preference(TzNamePreference::new(&["CET"]));
+    let x = 1704465989u32.unix::<System>().change_tz("+01:00");
+    assert_eq!(x.strftime("%H:%M %Z"), "15:46 +01:00");
+    reset_tz_name_preference();
+}